@@ -10,6 +10,8 @@ use apollo_batcher_types::batcher_types::{
     GetProposalContent,
     GetProposalContentInput,
     GetProposalContentResponse,
+    PipelineNextHeightInput,
+    PipelineNextHeightResponse,
     ProposalCommitment,
     ProposalId,
     ProposalStatus,
@@ -42,6 +44,7 @@ use mockall::automock;
 use starknet_api::block::{BlockHeaderWithoutHash, BlockNumber};
 use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::execution_resources::GasAmount;
 use starknet_api::state::ThinStateDiff;
 use starknet_api::transaction::TransactionHash;
 use tokio::sync::Mutex;
@@ -88,6 +91,19 @@ use crate::utils::{
 type OutputStreamReceiver = tokio::sync::mpsc::UnboundedReceiver<InternalConsensusTransaction>;
 type InputStreamSender = tokio::sync::mpsc::Sender<InternalConsensusTransaction>;
 
+// The height after the active one, whose proposals are built before the active height is decided.
+// See `Batcher::pipeline_next_height`.
+struct PipelinedHeight {
+    height: BlockNumber,
+    // The executed proposal of the active height which the pipelined height is built on.
+    parent_proposal_id: ProposalId,
+    parent_state_diff: ThinStateDiff,
+    parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+    parent_l2_gas_used: GasAmount,
+    // The proposals built for the pipelined height so far.
+    proposal_ids: Vec<ProposalId>,
+}
+
 pub struct Batcher {
     pub config: BatcherConfig,
     pub storage_reader: Arc<dyn BatcherStorageReaderTrait>,
@@ -104,9 +120,13 @@ pub struct Batcher {
     pre_confirmed_block_writer_factory: Box<dyn PreconfirmedBlockWriterFactoryTrait>,
 
     /// The height that the batcher is currently working on.
-    /// All proposals are considered to be at this height.
+    /// All proposals are considered to be at this height, except for those of the pipelined
+    /// height.
     active_height: Option<BlockNumber>,
 
+    /// The next height, if it is being built on top of a proposal of the active height.
+    pipelined_height: Option<PipelinedHeight>,
+
     /// The block proposal that is currently being built, if any.
     /// At any given time, there can be only one proposal being actively executed (either proposed
     /// or validated).
@@ -149,6 +169,7 @@ impl Batcher {
             block_builder_factory,
             pre_confirmed_block_writer_factory,
             active_height: None,
+            pipelined_height: None,
             active_proposal: Arc::new(Mutex::new(None)),
             active_proposal_task: None,
             executed_proposals: Arc::new(Mutex::new(HashMap::new())),
@@ -171,7 +192,10 @@ impl Batcher {
             });
         }
 
-        self.abort_active_height().await;
+        match self.pipelined_height.take().filter(|pipelined| pipelined.height == input.height) {
+            Some(pipelined_height) => self.promote_pipelined_height(pipelined_height).await,
+            None => self.abort_active_height().await,
+        }
 
         info!("Starting to work on height {}.", input.height);
         self.active_height = Some(input.height);
@@ -179,6 +203,51 @@ impl Batcher {
         Ok(())
     }
 
+    #[instrument(skip(self), err)]
+    pub async fn pipeline_next_height(
+        &mut self,
+        input: PipelineNextHeightInput,
+    ) -> BatcherResult<PipelineNextHeightResponse> {
+        let active_height = self.active_height.ok_or(BatcherError::NoActiveHeight)?;
+        let parent_proposal_id = input.parent_proposal_id;
+        if let Some(pipelined_height) = &self.pipelined_height {
+            if pipelined_height.parent_proposal_id == parent_proposal_id {
+                return Ok(PipelineNextHeightResponse {
+                    parent_l2_gas_used: pipelined_height.parent_l2_gas_used,
+                });
+            }
+            // Another proposal is now more likely to be decided.
+            self.discard_pipelined_height().await;
+        }
+
+        let (parent_state_diff, parent_l1_handler_tx_hashes, parent_l2_gas_used) =
+            match self.executed_proposals.lock().await.get(&parent_proposal_id) {
+                Some(Ok(artifacts)) => (
+                    artifacts.thin_state_diff(),
+                    artifacts.execution_data.consumed_l1_handler_tx_hashes.clone(),
+                    artifacts.l2_gas_used,
+                ),
+                Some(Err(_)) => return Err(BatcherError::ProposalFailed),
+                None => {
+                    return Err(BatcherError::ExecutedProposalNotFound {
+                        proposal_id: parent_proposal_id,
+                    });
+                }
+            };
+        let height = active_height.unchecked_next();
+        info!("Pipelining height {} on top of proposal {}.", height, parent_proposal_id);
+        self.pipelined_height = Some(PipelinedHeight {
+            height,
+            parent_proposal_id,
+            parent_state_diff,
+            parent_l1_handler_tx_hashes,
+            parent_l2_gas_used,
+            proposal_ids: Vec::new(),
+        });
+
+        Ok(PipelineNextHeightResponse { parent_l2_gas_used })
+    }
+
     #[instrument(skip(self), err)]
     pub async fn propose_block(
         &mut self,
@@ -187,20 +256,37 @@ impl Batcher {
         let block_number = propose_block_input.block_info.block_number;
         let proposal_metrics_handle = ProposalMetricsHandle::new();
         let active_height = self.active_height.ok_or(BatcherError::NoActiveHeight)?;
+        // Proposals of the pipelined height are built on top of its parent, which isn't committed
+        // yet.
+        let pipelined_parent = self
+            .pipelined_height
+            .as_ref()
+            .filter(|pipelined_height| pipelined_height.height == block_number)
+            .map(|pipelined_height| {
+                (
+                    pipelined_height.parent_state_diff.clone(),
+                    pipelined_height.parent_l1_handler_tx_hashes.clone(),
+                )
+            });
+        let is_pipelined = pipelined_parent.is_some();
         verify_block_input(
-            active_height,
+            if is_pipelined { block_number } else { active_height },
             block_number,
             propose_block_input.retrospective_block_hash,
         )?;
 
         // TODO(yair): extract function for the following calls, use join_all.
-        self.mempool_client.commit_block(CommitBlockArgs::default()).await.map_err(|err| {
-            error!(
-                "Mempool is not ready to start proposal {}: {}.",
-                propose_block_input.proposal_id, err
-            );
-            BatcherError::NotReady
-        })?;
+        // Rewinds the transactions taken by previous proposals. A pipelined proposal must not
+        // rewind the transactions of its parent, which is probably about to be committed.
+        if !is_pipelined {
+            self.mempool_client.commit_block(CommitBlockArgs::default()).await.map_err(|err| {
+                error!(
+                    "Mempool is not ready to start proposal {}: {}.",
+                    propose_block_input.proposal_id, err
+                );
+                BatcherError::NotReady
+            })?;
+        }
         self.mempool_client
             .update_gas_price(
                 propose_block_input.block_info.gas_prices.strk_gas_prices.l2_gas_price.get(),
@@ -210,34 +296,57 @@ impl Batcher {
                 error!("Failed to update gas price in mempool: {}", err);
                 BatcherError::InternalError
             })?;
-        self.l1_provider_client
-            .start_block(SessionState::Propose, propose_block_input.block_info.block_number)
-            .await
-            .map_err(|err| {
-                error!(
-                    "L1 provider is not ready to start proposing block {}: {}. ",
-                    propose_block_input.block_info.block_number, err
-                );
-                BatcherError::NotReady
-            })?;
+        // The parent of a pipelined proposal isn't committed to the L1 provider yet, so its L1
+        // handler transactions are excluded explicitly.
+        let (parent_state_diff, start_block_result) = match pipelined_parent {
+            Some((parent_state_diff, parent_l1_handler_tx_hashes)) => (
+                Some(parent_state_diff),
+                self.l1_provider_client
+                    .start_pipelined_block(parent_l1_handler_tx_hashes, block_number)
+                    .await,
+            ),
+            None => (
+                None,
+                self.l1_provider_client.start_block(SessionState::Propose, block_number).await,
+            ),
+        };
+        start_block_result.map_err(|err| {
+            error!("L1 provider is not ready to start proposing block {}: {}. ", block_number, err);
+            BatcherError::NotReady
+        })?;
 
-        let tx_provider = ProposeTransactionProvider::new(
+        let mut tx_provider = ProposeTransactionProvider::new(
             self.mempool_client.clone(),
             self.l1_provider_client.clone(),
             self.config.max_l1_handler_txs_per_block_proposal,
             propose_block_input.block_info.block_number,
         );
+        if is_pipelined {
+            tx_provider = tx_provider.skip_repeated_txs();
+        }
 
         // A channel to receive the transactions included in the proposed block.
         let (output_tx_sender, output_tx_receiver) = tokio::sync::mpsc::unbounded_channel();
 
-        let cende_block_metadata = CendeBlockMetadata::new(propose_block_input.block_info.clone());
+        // A pipelined block isn't pre-confirmed, since it is discarded if its parent isn't decided.
         let (pre_confirmed_block_writer, candidate_tx_sender, pre_confirmed_tx_sender) =
-            self.pre_confirmed_block_writer_factory.create(
-                propose_block_input.block_info.block_number,
-                propose_block_input.proposal_round,
-                cende_block_metadata,
-            );
+            if is_pipelined {
+                (None, None, None)
+            } else {
+                let cende_block_metadata =
+                    CendeBlockMetadata::new(propose_block_input.block_info.clone());
+                let (pre_confirmed_block_writer, candidate_tx_sender, pre_confirmed_tx_sender) =
+                    self.pre_confirmed_block_writer_factory.create(
+                        propose_block_input.block_info.block_number,
+                        propose_block_input.proposal_round,
+                        cende_block_metadata,
+                    );
+                (
+                    Some(pre_confirmed_block_writer),
+                    Some(candidate_tx_sender),
+                    Some(pre_confirmed_tx_sender),
+                )
+            };
 
         let (block_builder, abort_signal_sender) = self
             .block_builder_factory
@@ -245,6 +354,7 @@ impl Batcher {
                 BlockMetadata {
                     block_info: propose_block_input.block_info,
                     retrospective_block_hash: propose_block_input.retrospective_block_hash,
+                    parent_state_diff,
                 },
                 BlockBuilderExecutionParams {
                     deadline: deadline_as_instant(propose_block_input.deadline)?,
@@ -252,8 +362,8 @@ impl Batcher {
                 },
                 Box::new(tx_provider),
                 Some(output_tx_sender),
-                Some(candidate_tx_sender),
-                Some(pre_confirmed_tx_sender),
+                candidate_tx_sender,
+                pre_confirmed_tx_sender,
                 tokio::runtime::Handle::current(),
             )
             .map_err(|err| {
@@ -266,10 +376,13 @@ impl Batcher {
             block_builder,
             abort_signal_sender,
             None,
-            Some(pre_confirmed_block_writer),
+            pre_confirmed_block_writer,
             proposal_metrics_handle,
         )
         .await?;
+        if let Some(pipelined_height) = self.pipelined_height.as_mut().filter(|_| is_pipelined) {
+            pipelined_height.proposal_ids.push(propose_block_input.proposal_id);
+        }
 
         let proposal_already_exists =
             self.propose_tx_streams.insert(propose_block_input.proposal_id, output_tx_receiver);
@@ -325,6 +438,7 @@ impl Batcher {
                 BlockMetadata {
                     block_info: validate_block_input.block_info,
                     retrospective_block_hash: validate_block_input.retrospective_block_hash,
+                    parent_state_diff: None,
                 },
                 BlockBuilderExecutionParams {
                     deadline: deadline_as_instant(validate_block_input.deadline)?,
@@ -390,6 +504,43 @@ impl Batcher {
         self.propose_tx_streams.clear();
         self.validate_tx_streams.clear();
         self.active_height = None;
+        self.pipelined_height = None;
+    }
+
+    // Moves on to the pipelined height, keeping the proposals built for it and dropping the rest.
+    async fn promote_pipelined_height(&mut self, pipelined_height: PipelinedHeight) {
+        let is_pipelined =
+            |proposal_id: &ProposalId| pipelined_height.proposal_ids.contains(proposal_id);
+        let active_proposal = *self.active_proposal.lock().await;
+        if active_proposal.is_some_and(|proposal_id| !is_pipelined(&proposal_id)) {
+            self.abort_active_proposal().await;
+        }
+        self.executed_proposals.lock().await.retain(|proposal_id, _| is_pipelined(proposal_id));
+        self.propose_tx_streams.retain(|proposal_id, _| is_pipelined(proposal_id));
+        self.validate_tx_streams.clear();
+    }
+
+    // Drops the proposals of the pipelined height, once another proposal than its parent is
+    // decided or likely to be decided.
+    async fn discard_pipelined_height(&mut self) {
+        let Some(pipelined_height) = self.pipelined_height.take() else {
+            return;
+        };
+        info!(
+            "Discarding the proposals of height {} pipelined on top of proposal {}.",
+            pipelined_height.height, pipelined_height.parent_proposal_id
+        );
+        let active_proposal = *self.active_proposal.lock().await;
+        if active_proposal
+            .is_some_and(|proposal_id| pipelined_height.proposal_ids.contains(&proposal_id))
+        {
+            self.abort_active_proposal().await;
+        }
+        let mut executed_proposals = self.executed_proposals.lock().await;
+        for proposal_id in &pipelined_height.proposal_ids {
+            executed_proposals.remove(proposal_id);
+            self.propose_tx_streams.remove(proposal_id);
+        }
     }
 
     async fn handle_send_txs_request(
@@ -520,6 +671,7 @@ impl Batcher {
             account_transaction_hashes,
             l1_transaction_hashes,
             block_header_without_hash: BlockHeaderWithoutHash { block_number, .. },
+            commit_certificate: _,
         } = sync_block;
 
         let height = self.get_height_from_storage()?;
@@ -559,6 +711,13 @@ impl Batcher {
         let height = self.active_height.ok_or(BatcherError::NoActiveHeight)?;
 
         let proposal_id = input.proposal_id;
+        if self
+            .pipelined_height
+            .as_ref()
+            .is_some_and(|pipelined_height| pipelined_height.parent_proposal_id != proposal_id)
+        {
+            self.discard_pipelined_height().await;
+        }
         let proposal_result = self.executed_proposals.lock().await.remove(&proposal_id);
         let block_execution_artifacts = proposal_result
             .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })?
@@ -8,6 +8,7 @@ use apollo_batcher_types::batcher_types::{
     GetProposalContent,
     GetProposalContentInput,
     GetProposalContentResponse,
+    PipelineNextHeightInput,
     ProposalCommitment,
     ProposalId,
     ProposalStatus,
@@ -84,6 +85,7 @@ const LATEST_BLOCK_IN_STORAGE: BlockNumber = BlockNumber(INITIAL_HEIGHT.0 - 1);
 const STREAMING_CHUNK_SIZE: usize = 3;
 const BLOCK_GENERATION_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(1);
 const PROPOSAL_ID: ProposalId = ProposalId(0);
+const PIPELINED_PROPOSAL_ID: ProposalId = ProposalId(1);
 const BUILD_BLOCK_FAIL_ON_ERROR: BlockBuilderError =
     BlockBuilderError::FailOnError(FailOnErrorCause::BlockFull);
 
@@ -101,6 +103,12 @@ fn propose_block_input(proposal_id: ProposalId) -> ProposeBlockInput {
     }
 }
 
+fn pipelined_propose_block_input(proposal_id: ProposalId) -> ProposeBlockInput {
+    let mut input = propose_block_input(proposal_id);
+    input.block_info.block_number = INITIAL_HEIGHT.unchecked_next();
+    input
+}
+
 fn validate_block_input(proposal_id: ProposalId) -> ValidateBlockInput {
     ValidateBlockInput {
         proposal_id,
@@ -224,6 +232,61 @@ fn mock_create_builder_for_propose_block(
     );
 }
 
+// A pipelined block is built on top of the state diff of its parent, and isn't pre-confirmed.
+fn mock_create_builder_for_pipelined_block(
+    block_builder_factory: &mut MockBlockBuilderFactoryTrait,
+    output_txs: Vec<InternalConsensusTransaction>,
+) {
+    let parent_state_diff = BlockExecutionArtifacts::create_for_testing().thin_state_diff();
+    block_builder_factory
+        .expect_create_block_builder()
+        .times(1)
+        .withf(move |block_metadata, _, _, _, candidate_tx_sender, pre_confirmed_tx_sender, _| {
+            block_metadata.parent_state_diff.as_ref() == Some(&parent_state_diff)
+                && candidate_tx_sender.is_none()
+                && pre_confirmed_tx_sender.is_none()
+        })
+        .return_once(move |_, _, _, output_content_sender, _, _, _| {
+            let block_builder = FakeProposeBlockBuilder {
+                output_content_sender: output_content_sender.unwrap(),
+                output_txs,
+                build_block_result: Some(Ok(BlockExecutionArtifacts::create_for_testing())),
+            };
+            Ok((Box::new(block_builder), abort_signal_sender()))
+        });
+}
+
+// Mocks committing the block of the initial height, after which the storage is at the next height.
+fn mock_commit_initial_height(mock_dependencies: &mut MockDependencies) {
+    let artifacts = BlockExecutionArtifacts::create_for_testing();
+    let mut storage_reader = MockBatcherStorageReaderTrait::new();
+    // Metrics registration and the first start_height.
+    storage_reader.expect_height().times(2).returning(|| Ok(INITIAL_HEIGHT));
+    storage_reader.expect_height().times(1).returning(|| Ok(INITIAL_HEIGHT.unchecked_next()));
+    mock_dependencies.storage_reader = storage_reader;
+    mock_dependencies
+        .storage_writer
+        .expect_commit_proposal()
+        .times(1)
+        .with(eq(INITIAL_HEIGHT), eq(artifacts.thin_state_diff()))
+        .returning(|_, _| Ok(()));
+    mock_dependencies
+        .mempool_client
+        .expect_commit_block()
+        .times(1)
+        .with(eq(CommitBlockArgs {
+            address_to_nonce: artifacts.address_to_nonce(),
+            rejected_tx_hashes: artifacts.execution_data.rejected_tx_hashes,
+        }))
+        .returning(|_| Ok(()));
+    mock_dependencies
+        .l1_provider_client
+        .expect_commit_block()
+        .times(1)
+        .with(eq(IndexSet::new()), eq(IndexSet::new()), eq(INITIAL_HEIGHT))
+        .returning(|_, _, _| Ok(()));
+}
+
 async fn create_batcher_with_active_validate_block(
     build_block_result: BlockBuilderResult<BlockExecutionArtifacts>,
 ) -> Batcher {
@@ -1061,6 +1124,137 @@ async fn decision_reached_no_executed_proposal() {
     assert_eq!(decision_reached_result, Err(expected_error));
 }
 
+#[tokio::test]
+async fn pipelined_proposal_kept_when_parent_is_decided() {
+    let mut mock_dependencies = MockDependencies::default();
+    mock_commit_initial_height(&mut mock_dependencies);
+    mock_dependencies
+        .l1_provider_client
+        .expect_start_block()
+        .times(1)
+        .with(eq(SessionState::Propose), eq(INITIAL_HEIGHT))
+        .returning(|_, _| Ok(()));
+    // The L1 handler transactions of the parent aren't proposed again.
+    let parent_l1_handler_tx_hashes =
+        BlockExecutionArtifacts::create_for_testing().execution_data.consumed_l1_handler_tx_hashes;
+    mock_dependencies
+        .l1_provider_client
+        .expect_start_pipelined_block()
+        .times(1)
+        .with(eq(parent_l1_handler_tx_hashes), eq(INITIAL_HEIGHT.unchecked_next()))
+        .returning(|_, _| Ok(()));
+    mock_create_builder_for_propose_block(
+        &mut mock_dependencies.block_builder_factory,
+        vec![],
+        Ok(BlockExecutionArtifacts::create_for_testing()),
+    );
+    let pipelined_txs = test_txs(0..2);
+    mock_create_builder_for_pipelined_block(
+        &mut mock_dependencies.block_builder_factory,
+        pipelined_txs.clone(),
+    );
+    let mut batcher = create_batcher(mock_dependencies).await;
+
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.propose_block(propose_block_input(PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal(DUMMY_FINAL_N_EXECUTED_TXS).await.unwrap();
+
+    let response = batcher
+        .pipeline_next_height(PipelineNextHeightInput { parent_proposal_id: PROPOSAL_ID })
+        .await
+        .unwrap();
+    assert_eq!(
+        response.parent_l2_gas_used,
+        BlockExecutionArtifacts::create_for_testing().l2_gas_used
+    );
+    batcher.propose_block(pipelined_propose_block_input(PIPELINED_PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal(DUMMY_FINAL_N_EXECUTED_TXS).await.unwrap();
+
+    batcher.decision_reached(DecisionReachedInput { proposal_id: PROPOSAL_ID }).await.unwrap();
+    batcher
+        .start_height(StartHeightInput { height: INITIAL_HEIGHT.unchecked_next() })
+        .await
+        .unwrap();
+
+    // The pipelined proposal is now a proposal of the active height.
+    let content = batcher
+        .get_proposal_content(GetProposalContentInput { proposal_id: PIPELINED_PROPOSAL_ID })
+        .await
+        .unwrap()
+        .content;
+    assert_eq!(content, GetProposalContent::Txs(pipelined_txs));
+}
+
+#[tokio::test]
+async fn pipelined_proposal_discarded_when_another_proposal_is_decided() {
+    const DECIDED_PROPOSAL_ID: ProposalId = ProposalId(2);
+
+    let mut mock_dependencies = MockDependencies::default();
+    mock_commit_initial_height(&mut mock_dependencies);
+    mock_dependencies.l1_provider_client.expect_start_block().times(2).returning(|_, _| Ok(()));
+    mock_dependencies
+        .l1_provider_client
+        .expect_start_pipelined_block()
+        .times(1)
+        .returning(|_, _| Ok(()));
+    mock_create_builder_for_propose_block(
+        &mut mock_dependencies.block_builder_factory,
+        vec![],
+        Ok(BlockExecutionArtifacts::create_for_testing()),
+    );
+    mock_create_builder_for_pipelined_block(
+        &mut mock_dependencies.block_builder_factory,
+        test_txs(0..2),
+    );
+    mock_create_builder_for_propose_block(
+        &mut mock_dependencies.block_builder_factory,
+        vec![],
+        Ok(BlockExecutionArtifacts::create_for_testing()),
+    );
+    let mut batcher = create_batcher(mock_dependencies).await;
+
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.propose_block(propose_block_input(PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal(DUMMY_FINAL_N_EXECUTED_TXS).await.unwrap();
+    batcher
+        .pipeline_next_height(PipelineNextHeightInput { parent_proposal_id: PROPOSAL_ID })
+        .await
+        .unwrap();
+    batcher.propose_block(pipelined_propose_block_input(PIPELINED_PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal(DUMMY_FINAL_N_EXECUTED_TXS).await.unwrap();
+    // A proposal of a later round is decided instead of the parent.
+    batcher.propose_block(propose_block_input(DECIDED_PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal(DUMMY_FINAL_N_EXECUTED_TXS).await.unwrap();
+
+    batcher
+        .decision_reached(DecisionReachedInput { proposal_id: DECIDED_PROPOSAL_ID })
+        .await
+        .unwrap();
+    batcher
+        .start_height(StartHeightInput { height: INITIAL_HEIGHT.unchecked_next() })
+        .await
+        .unwrap();
+
+    let content = batcher
+        .get_proposal_content(GetProposalContentInput { proposal_id: PIPELINED_PROPOSAL_ID })
+        .await;
+    assert_eq!(content, Err(BatcherError::ProposalNotFound { proposal_id: PIPELINED_PROPOSAL_ID }));
+}
+
+#[tokio::test]
+async fn pipeline_next_height_without_executed_parent() {
+    let mut batcher = create_batcher(MockDependencies::default()).await;
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+
+    let result = batcher
+        .pipeline_next_height(PipelineNextHeightInput { parent_proposal_id: PROPOSAL_ID })
+        .await;
+    assert_eq!(result, Err(BatcherError::ExecutedProposalNotFound { proposal_id: PROPOSAL_ID }));
+    // Without a pipelined height, only proposals of the active height can be built.
+    let result = batcher.propose_block(pipelined_propose_block_input(PIPELINED_PROPOSAL_ID)).await;
+    assert_matches!(result, Err(BatcherError::InvalidBlockNumber { .. }));
+}
+
 // Test that the batcher returns the execution_infos in the same order as returned from the
 // block_builder. It is crucial that the execution_infos will be ordered in the same order as
 // the transactions in the block for the correct execution of starknet.
@@ -559,6 +559,8 @@ async fn collect_execution_results_and_stream_txs(
 pub struct BlockMetadata {
     pub block_info: BlockInfo,
     pub retrospective_block_hash: Option<BlockHashAndNumber>,
+    /// The state diff of the parent block, if it isn't committed to the storage yet.
+    pub parent_state_diff: Option<ThinStateDiff>,
 }
 
 // Type definitions for the abort channel required to abort the block builder.
@@ -665,8 +667,20 @@ impl BlockBuilderFactory {
         );
 
         let class_reader = Some(ClassReader { reader: self.class_manager_client.clone(), runtime });
-        let papyrus_reader =
-            PapyrusReader::new_with_class_reader(self.storage_reader.clone(), height, class_reader);
+        let papyrus_reader = match block_metadata.parent_state_diff {
+            // Read the state before the parent block, with the parent's state diff on top of it.
+            Some(parent_state_diff) => PapyrusReader::new_with_class_reader(
+                self.storage_reader.clone(),
+                height.prev().expect("A block with an uncommitted parent can't be the first."),
+                class_reader,
+            )
+            .with_pending_state_diff(parent_state_diff),
+            None => PapyrusReader::new_with_class_reader(
+                self.storage_reader.clone(),
+                height,
+                class_reader,
+            ),
+        };
         let state_reader = StateReaderAndContractManager {
             state_reader: papyrus_reader,
             contract_class_manager: self.contract_class_manager.clone(),
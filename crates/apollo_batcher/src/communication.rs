@@ -24,6 +24,9 @@ impl ComponentRequestHandler<BatcherRequest, BatcherResponse> for Batcher {
             BatcherRequest::StartHeight(input) => {
                 BatcherResponse::StartHeight(self.start_height(input).await)
             }
+            BatcherRequest::PipelineNextHeight(input) => {
+                BatcherResponse::PipelineNextHeight(self.pipeline_next_height(input).await)
+            }
             BatcherRequest::DecisionReached(input) => {
                 BatcherResponse::DecisionReached(self.decision_reached(input).await.map(Box::new))
             }
@@ -1,4 +1,5 @@
 use std::cmp::min;
+use std::collections::HashSet;
 use std::vec;
 
 use apollo_l1_provider_types::errors::L1ProviderClientError;
@@ -56,6 +57,9 @@ pub struct ProposeTransactionProvider {
     pub height: BlockNumber,
     phase: TxProviderPhase,
     n_l1handler_txs_so_far: usize,
+    // The hashes of the mempool transactions provided so far, if repeated transactions are
+    // skipped.
+    provided_tx_hashes: Option<HashSet<TransactionHash>>,
 }
 
 // Keeps track of whether we need to fetch L1 handler transactions or mempool transactions.
@@ -77,11 +81,24 @@ impl ProposeTransactionProvider {
             l1_provider_client,
             max_l1_handler_txs_per_block,
             height,
-            phase: TxProviderPhase::L1,
+            phase: if max_l1_handler_txs_per_block == 0 {
+                TxProviderPhase::Mempool
+            } else {
+                TxProviderPhase::L1
+            },
             n_l1handler_txs_so_far: 0,
+            provided_tx_hashes: None,
         }
     }
 
+    /// Skips mempool transactions which were already provided. The mempool returns transactions
+    /// again if they are rewound while the block is built, which happens when the previous height
+    /// is committed during the building of a pipelined block.
+    pub fn skip_repeated_txs(mut self) -> Self {
+        self.provided_tx_hashes = Some(HashSet::new());
+        self
+    }
+
     async fn get_l1_handler_txs(
         &mut self,
         n_txs: usize,
@@ -99,13 +116,11 @@ impl ProposeTransactionProvider {
         &mut self,
         n_txs: usize,
     ) -> TransactionProviderResult<Vec<InternalConsensusTransaction>> {
-        Ok(self
-            .mempool_client
-            .get_txs(n_txs)
-            .await?
-            .into_iter()
-            .map(InternalConsensusTransaction::RpcTransaction)
-            .collect())
+        let mut txs = self.mempool_client.get_txs(n_txs).await?;
+        if let Some(provided_tx_hashes) = &mut self.provided_tx_hashes {
+            txs.retain(|tx| provided_tx_hashes.insert(tx.tx_hash()));
+        }
+        Ok(txs.into_iter().map(InternalConsensusTransaction::RpcTransaction).collect())
     }
 }
 
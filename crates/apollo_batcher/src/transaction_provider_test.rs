@@ -203,6 +203,26 @@ async fn no_more_l1_handler(mut mock_dependencies: MockDependencies) {
     assert!(data.iter().all(|tx| matches!(tx, InternalConsensusTransaction::RpcTransaction(_))));
 }
 
+#[rstest]
+#[tokio::test]
+async fn skip_repeated_txs(mut mock_dependencies: MockDependencies) {
+    // Without L1 handler transactions, the L1 provider isn't queried.
+    mock_dependencies.expect_get_mempool_txs(MAX_TXS_PER_FETCH);
+    let mut tx_provider = ProposeTransactionProvider::new(
+        Arc::new(mock_dependencies.mempool_client),
+        Arc::new(mock_dependencies.l1_provider_client),
+        0,
+        HEIGHT,
+    )
+    .skip_repeated_txs();
+
+    // The mempool returns copies of the same transaction.
+    let txs = tx_provider.get_txs(MAX_TXS_PER_FETCH).await.unwrap();
+    assert_eq!(txs.len(), 1);
+    let txs = tx_provider.get_txs(MAX_TXS_PER_FETCH).await.unwrap();
+    assert!(txs.is_empty());
+}
+
 #[rstest]
 #[tokio::test]
 async fn validate_flow(mut mock_dependencies: MockDependencies) {
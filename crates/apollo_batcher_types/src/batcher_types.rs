@@ -141,6 +141,19 @@ pub struct DecisionReachedInput {
     pub proposal_id: ProposalId,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PipelineNextHeightInput {
+    /// An executed proposal of the active height, which the next height is built on.
+    pub parent_proposal_id: ProposalId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PipelineNextHeightResponse {
+    /// The L2 gas used by the parent proposal, which determines the L2 gas price of the next
+    /// height.
+    pub parent_l2_gas_used: GasAmount,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct RevertBlockInput {
     pub height: BlockNumber,
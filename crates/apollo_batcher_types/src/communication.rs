@@ -19,6 +19,8 @@ use crate::batcher_types::{
     GetHeightResponse,
     GetProposalContentInput,
     GetProposalContentResponse,
+    PipelineNextHeightInput,
+    PipelineNextHeightResponse,
     ProposeBlockInput,
     RevertBlockInput,
     SendProposalContentInput,
@@ -66,6 +68,15 @@ pub trait BatcherClient: Send + Sync {
     /// From this point onwards, the batcher will accept requests only for proposals associated
     /// with this height.
     async fn start_height(&self, input: StartHeightInput) -> BatcherClientResult<()>;
+    /// Starts working on the height after the active one, on top of an executed proposal of the
+    /// active height, before the active height is decided. Proposals for the next height can then
+    /// be built (but not validated) in parallel to voting on the active height.
+    /// If another proposal is decided, the work on the next height is discarded. Otherwise, it is
+    /// kept when the next height is started.
+    async fn pipeline_next_height(
+        &self,
+        input: PipelineNextHeightInput,
+    ) -> BatcherClientResult<PipelineNextHeightResponse>;
     /// Adds a block from the state sync. Updates the batcher's state and commits the
     /// transactions to the mempool.
     async fn add_sync_block(&self, sync_block: SyncBlock) -> BatcherClientResult<()>;
@@ -86,6 +97,7 @@ pub enum BatcherRequest {
     ValidateBlock(ValidateBlockInput),
     SendProposalContent(SendProposalContentInput),
     StartHeight(StartHeightInput),
+    PipelineNextHeight(PipelineNextHeightInput),
     GetCurrentHeight,
     DecisionReached(DecisionReachedInput),
     AddSyncBlock(SyncBlock),
@@ -101,6 +113,7 @@ pub enum BatcherResponse {
     ValidateBlock(BatcherResult<()>),
     SendProposalContent(BatcherResult<SendProposalContentResponse>),
     StartHeight(BatcherResult<()>),
+    PipelineNextHeight(BatcherResult<PipelineNextHeightResponse>),
     DecisionReached(BatcherResult<Box<DecisionReachedResponse>>),
     AddSyncBlock(BatcherResult<()>),
     RevertBlock(BatcherResult<()>),
@@ -181,6 +194,20 @@ where
         )
     }
 
+    async fn pipeline_next_height(
+        &self,
+        input: PipelineNextHeightInput,
+    ) -> BatcherClientResult<PipelineNextHeightResponse> {
+        let request = BatcherRequest::PipelineNextHeight(input);
+        handle_all_response_variants!(
+            BatcherResponse,
+            PipelineNextHeight,
+            BatcherClientError,
+            BatcherError,
+            Direct
+        )
+    }
+
     async fn get_height(&self) -> BatcherClientResult<GetHeightResponse> {
         let request = BatcherRequest::GetCurrentHeight;
         handle_all_response_variants!(
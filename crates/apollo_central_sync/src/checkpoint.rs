@@ -0,0 +1,317 @@
+//! Starting the sync from a trusted checkpoint instead of from the genesis block.
+//!
+//! A node with an empty storage is seeded from a state snapshot of the checkpoint block (see
+//! [`apollo_storage::checkpoint`]), and syncs the blocks after it as usual. The headers of the
+//! blocks below the checkpoint are downloaded lazily, from the checkpoint backwards, and each one
+//! is verified against the parent hash of the header after it. The blocks up to the checkpoint are
+//! never reverted.
+
+#[cfg(test)]
+#[path = "checkpoint_test.rs"]
+mod checkpoint_test;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_state_sync_metrics::metrics::{
+    STATE_SYNC_BODY_MARKER,
+    STATE_SYNC_COMPILED_CLASS_MARKER,
+    STATE_SYNC_HEADER_MARKER,
+    STATE_SYNC_STATE_MARKER,
+};
+use apollo_storage::checkpoint::{
+    read_state_snapshot,
+    CheckpointStorageReader,
+    CheckpointStorageWriter,
+};
+use apollo_storage::class_manager::ClassManagerStorageWriter;
+use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
+use apollo_storage::{StorageError, StorageReader};
+use async_stream::try_stream;
+use futures::future::pending;
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{
+    BlockHash,
+    BlockHashAndNumber,
+    BlockHeader,
+    BlockNumber,
+    BlockSignature,
+};
+use starknet_api::block_hash::block_hash_calculator::{
+    calculate_block_hash,
+    BlockHashVersion,
+    BlockHeaderCommitments,
+};
+use starknet_api::contract_class::{ContractClass, SierraVersion};
+use starknet_api::core::CompiledClassHash;
+use tokio::task::spawn_blocking;
+use tracing::{debug, info};
+use validator::Validate;
+
+use crate::sources::base_layer::BaseLayerSourceTrait;
+use crate::sources::central::CentralSourceTrait;
+use crate::sources::pending::PendingSourceTrait;
+use crate::state_root_verifier::StateRootVerifier;
+use crate::{
+    GenericStateSync,
+    StateSyncError,
+    StateSyncResult,
+    SyncEvent,
+    STARKNET_VERSION_TO_COMPILE_FROM,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Validate)]
+pub struct CheckpointConfig {
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+    pub snapshot_path: PathBuf,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            block_number: BlockNumber(0),
+            block_hash: BlockHash::default(),
+            snapshot_path: PathBuf::from("./state_snapshot.zst"),
+        }
+    }
+}
+
+impl SerializeConfig for CheckpointConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "block_number",
+                &self.block_number,
+                "The number of the trusted checkpoint block.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "block_hash",
+                &self.block_hash,
+                "The hash of the trusted checkpoint block.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "snapshot_path",
+                &self.snapshot_path,
+                "The state snapshot of the checkpoint block to seed an empty storage with.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl<
+        TCentralSource: CentralSourceTrait + Sync + Send + 'static,
+        TPendingSource: PendingSourceTrait + Sync + Send + 'static,
+        TBaseLayerSource: BaseLayerSourceTrait + Sync + Send,
+    > GenericStateSync<TCentralSource, TPendingSource, TBaseLayerSource>
+{
+    // Seeds the storage from the state snapshot of the checkpoint, unless the storage isn't empty.
+    // The snapshot must be of the checkpoint block, its header must match the block's hash, and its
+    // state must match the state root in the header.
+    pub(crate) async fn seed_checkpoint(&mut self, config: &CheckpointConfig) -> StateSyncResult {
+        let header_marker = self.reader.begin_ro_txn()?.get_header_marker()?;
+        if header_marker != BlockNumber(0) {
+            debug!(
+                "The storage already has blocks up to {header_marker}, not seeding it from the \
+                 checkpoint."
+            );
+            return Ok(());
+        }
+
+        info!("Seeding the storage from the state snapshot {}.", config.snapshot_path.display());
+        let snapshot_path = config.snapshot_path.clone();
+        let snapshot = spawn_blocking(move || read_state_snapshot(&snapshot_path)).await??;
+        let block_number = snapshot.header.block_header_without_hash.block_number;
+        let snapshot_block =
+            BlockHashAndNumber { hash: snapshot.header.block_hash, number: block_number };
+        let checkpoint =
+            BlockHashAndNumber { hash: config.block_hash, number: config.block_number };
+        if snapshot_block != checkpoint {
+            return Err(StateSyncError::StateSnapshotNotOfCheckpoint {
+                checkpoint,
+                snapshot_block,
+            });
+        }
+        // The header is trusted only through the checkpoint hash, so the hash must be verifiable.
+        let starknet_version = snapshot.header.block_header_without_hash.starknet_version;
+        if BlockHashVersion::try_from(starknet_version).is_err() {
+            return Err(StateSyncError::StateSnapshotHeaderUnverifiable { starknet_version });
+        }
+        verify_block_hash_of_header(&snapshot.header)?;
+
+        let state_tries_update = StateRootVerifier::verify_first_block(
+            &self.reader,
+            block_number,
+            snapshot.state_diff.clone(),
+            snapshot.header.block_header_without_hash.state_root,
+        )
+        .await?;
+
+        if let Some(class_manager_client) = &self.class_manager_client {
+            for (class_hash, class) in &snapshot.classes {
+                let casm = snapshot.casms.get(class_hash).ok_or(StorageError::DBInconsistency {
+                    msg: format!("Missing the compiled class of {class_hash} in the snapshot."),
+                })?;
+                let sierra_version = SierraVersion::extract_from_program(&class.sierra_program)
+                    .expect("Failed reading sierra version from program.");
+                // TODO(Aviv): Compute the actual compiled class hash v2.
+                class_manager_client
+                    .add_class_and_executable_unsafe(
+                        *class_hash,
+                        class.clone(),
+                        CompiledClassHash::default(),
+                        ContractClass::V1((casm.clone(), sierra_version)),
+                    )
+                    .await?;
+            }
+            for (class_hash, deprecated_class) in &snapshot.deprecated_classes {
+                class_manager_client
+                    .add_deprecated_class(*class_hash, deprecated_class.clone())
+                    .await?;
+            }
+        }
+
+        let has_class_manager = self.class_manager_client.is_some();
+        let state_root_verifier = self.state_root_verifier;
+        let is_compiler_backward_compatible =
+            snapshot.header.block_header_without_hash.starknet_version
+                >= STARKNET_VERSION_TO_COMPILE_FROM;
+        self.perform_storage_writes(move |writer| {
+            let mut txn = writer.begin_rw_txn()?.seed_checkpoint(&snapshot)?;
+            // The tries of the snapshot are stored for verifying the blocks after it.
+            if let Some(state_root_verifier) = state_root_verifier {
+                txn = state_root_verifier.append_state_tries(txn, state_tries_update)?;
+            }
+            if !is_compiler_backward_compatible {
+                txn = txn.update_compiler_backward_compatibility_marker(
+                    &block_number.unchecked_next(),
+                )?;
+            }
+            if has_class_manager {
+                txn = txn.update_class_manager_block_marker(&block_number.unchecked_next())?;
+            }
+            txn.commit()?;
+            Ok(())
+        })
+        .await?;
+        STATE_SYNC_HEADER_MARKER.set_lossy(block_number.unchecked_next().0);
+        STATE_SYNC_BODY_MARKER.set_lossy(block_number.unchecked_next().0);
+        STATE_SYNC_STATE_MARKER.set_lossy(block_number.unchecked_next().0);
+        STATE_SYNC_COMPILED_CLASS_MARKER.set_lossy(block_number.unchecked_next().0);
+        info!("Seeded the storage with the state of block {block_number}.");
+        Ok(())
+    }
+
+    // Stores the header of the block below the first stored header, after verifying it against the
+    // parent hash of the first stored header.
+    pub(crate) async fn store_backfilled_header(
+        &mut self,
+        block_number: BlockNumber,
+        header: BlockHeader,
+        signature: BlockSignature,
+    ) -> StateSyncResult {
+        let child_block_number = block_number.unchecked_next();
+        let expected_block_hash = self
+            .reader
+            .begin_ro_txn()?
+            .get_block_header(child_block_number)?
+            .ok_or(StorageError::DBInconsistency {
+                msg: format!(
+                    "Missing block {child_block_number} in the storage (for verifying block \
+                     {block_number})."
+                ),
+            })?
+            .block_header_without_hash
+            .parent_hash;
+        if header.block_hash != expected_block_hash {
+            return Err(StateSyncError::BackfilledBlockHashMismatch {
+                block_number,
+                expected_block_hash,
+                block_hash: header.block_hash,
+            });
+        }
+        verify_block_hash_of_header(&header)?;
+
+        self.perform_storage_writes(move |writer| {
+            writer
+                .begin_rw_txn()?
+                .prepend_header(block_number, &header)?
+                .append_block_signature(block_number, &signature)?
+                .commit()?;
+            Ok(())
+        })
+        .await?;
+        debug!("Backfilled the header of block {block_number}.");
+        Ok(())
+    }
+}
+
+// Verifies that the hash of the block is the hash of its header's fields. The hash of blocks before
+// Starknet v0.13.2 depends on their body as well, so it isn't verified.
+fn verify_block_hash_of_header(header: &BlockHeader) -> StateSyncResult {
+    let header_without_hash = &header.block_header_without_hash;
+    if BlockHashVersion::try_from(header_without_hash.starknet_version).is_err() {
+        return Ok(());
+    }
+    let block_number = header_without_hash.block_number;
+    let Some(block_commitments) = BlockHeaderCommitments::from_header(header) else {
+        return Err(StateSyncError::HeaderWithoutCommitments { block_number });
+    };
+    let computed_block_hash = calculate_block_hash(header_without_hash.clone(), block_commitments)
+        .expect("The block hash version was checked.");
+    if computed_block_hash != header.block_hash {
+        return Err(StateSyncError::BlockHashNotOfHeader {
+            block_number,
+            block_hash: header.block_hash,
+            computed_block_hash,
+        });
+    }
+    Ok(())
+}
+
+// Streams the headers of the blocks below the first stored header, from the highest to the lowest,
+// until reaching the genesis block. Nothing is streamed if the storage has no checkpoint.
+pub(crate) fn stream_backfilled_headers<TCentralSource: CentralSourceTrait + Sync + Send>(
+    reader: StorageReader,
+    central_source: Arc<TCentralSource>,
+    max_stream_size: u32,
+) -> impl Stream<Item = Result<SyncEvent, StateSyncError>> {
+    try_stream! {
+        loop {
+            let txn = reader.begin_ro_txn()?;
+            let first_header_block_number = match txn.get_checkpoint()? {
+                Some(_) => txn.get_first_header_block_number()?,
+                None => None,
+            };
+            drop(txn);
+            let Some(up_to) =
+                first_header_block_number.filter(|block_number| *block_number > BlockNumber(0))
+            else {
+                debug!("No headers to backfill.");
+                pending::<()>().await;
+                continue;
+            };
+            let from = BlockNumber(up_to.0.saturating_sub(u64::from(max_stream_size)));
+            debug!("Downloading the headers of blocks [{from}, {up_to}) for backfilling.");
+            let mut blocks = vec![];
+            let mut block_stream = central_source.stream_new_blocks(from, up_to);
+            while let Some(block) = block_stream.next().await {
+                blocks.push(block?);
+            }
+            for (block_number, block, signature) in blocks.into_iter().rev() {
+                yield SyncEvent::BackfilledHeaderAvailable {
+                    block_number,
+                    header: block.header,
+                    signature,
+                };
+            }
+        }
+    }
+}
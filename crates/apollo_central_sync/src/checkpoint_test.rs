@@ -0,0 +1,281 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use apollo_starknet_client::reader::PendingData;
+use apollo_storage::archive::ArchivedBlock;
+use apollo_storage::checkpoint::{
+    write_state_snapshot,
+    CheckpointStorageReader,
+    CheckpointStorageWriter,
+};
+use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
+use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::{StorageReader, StorageWriter};
+use assert_matches::assert_matches;
+use papyrus_common::pending_classes::PendingClasses;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    BlockSignature,
+    BlockTimestamp,
+    StarknetVersion,
+};
+use starknet_api::block_hash::block_hash_calculator::{
+    calculate_block_hash,
+    BlockHashVersion,
+    BlockHeaderCommitments,
+};
+use starknet_api::core::GlobalRoot;
+use starknet_api::felt;
+use tokio::sync::{watch, Mutex, RwLock};
+
+use crate::checkpoint::CheckpointConfig;
+use crate::sources::base_layer::MockBaseLayerSourceTrait;
+use crate::sources::central::MockCentralSourceTrait;
+use crate::sources::pending::MockPendingSourceTrait;
+use crate::{GenericStateSync, StateSyncError, SyncConfig};
+
+type TestStateSync =
+    GenericStateSync<MockCentralSourceTrait, MockPendingSourceTrait, MockBaseLayerSourceTrait>;
+
+fn state_sync(reader: StorageReader, writer: StorageWriter) -> TestStateSync {
+    GenericStateSync {
+        config: SyncConfig::default(),
+        shared_highest_block: Arc::new(RwLock::new(None)),
+        pending_data: Arc::new(RwLock::new(PendingData::default())),
+        pending_data_updates: watch::channel(()).0,
+        central_source: Arc::new(MockCentralSourceTrait::new()),
+        pending_source: Arc::new(MockPendingSourceTrait::new()),
+        pending_classes: Arc::new(RwLock::new(PendingClasses::default())),
+        base_layer_source: None,
+        reader,
+        writer: Arc::new(Mutex::new(writer)),
+        sequencer_pub_key: None,
+        class_manager_client: None,
+        state_root_verifier: None,
+        checkpoint: None,
+        decided_commit_certificates: None,
+    }
+}
+
+fn header(block_number: u64) -> BlockHeader {
+    BlockHeader {
+        block_hash: BlockHash(felt!(block_number + 100)),
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(block_number),
+            parent_hash: BlockHash(felt!(block_number + 99)),
+            // The hash of blocks of this version isn't verified against their header.
+            starknet_version: StarknetVersion::V0_13_1,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn store_backfilled_header() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let snapshot = ArchivedBlock { header: header(5), ..Default::default() };
+    writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot).unwrap().commit().unwrap();
+    let mut state_sync = state_sync(reader.clone(), writer);
+
+    state_sync
+        .store_backfilled_header(BlockNumber(4), header(4), BlockSignature::default())
+        .await
+        .unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_first_header_block_number().unwrap(), Some(BlockNumber(4)));
+    assert_eq!(txn.get_block_header(BlockNumber(4)).unwrap(), Some(header(4)));
+    assert_eq!(txn.get_checkpoint().unwrap(), Some(BlockNumber(5)));
+    drop(txn);
+
+    // The hash of the block doesn't match the parent hash of the block after it.
+    let mut wrong_header = header(3);
+    wrong_header.block_hash = BlockHash(felt!("0x666"));
+    let res = state_sync
+        .store_backfilled_header(BlockNumber(3), wrong_header, BlockSignature::default())
+        .await;
+    assert_matches!(
+        res,
+        Err(StateSyncError::BackfilledBlockHashMismatch { block_number: BlockNumber(3), .. })
+    );
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_first_header_block_number().unwrap(),
+        Some(BlockNumber(4))
+    );
+}
+
+#[tokio::test]
+async fn store_backfilled_header_not_of_its_hash() {
+    let mut backfilled_header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(4),
+            starknet_version: BlockHashVersion::V0_13_4.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let block_commitments =
+        BlockHeaderCommitments { concatenated_counts: felt!("0x1"), ..Default::default() };
+    // The header can't be verified without its commitments.
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let mut snapshot = ArchivedBlock { header: header(5), ..Default::default() };
+    writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot).unwrap().commit().unwrap();
+    let mut state_sync = state_sync(reader, writer);
+    backfilled_header.block_hash = header(4).block_hash;
+    let res = state_sync
+        .store_backfilled_header(
+            BlockNumber(4),
+            backfilled_header.clone(),
+            BlockSignature::default(),
+        )
+        .await;
+    assert_matches!(
+        res,
+        Err(StateSyncError::HeaderWithoutCommitments { block_number: BlockNumber(4) })
+    );
+
+    backfilled_header.transaction_commitment = Some(block_commitments.transaction_commitment);
+    backfilled_header.event_commitment = Some(block_commitments.event_commitment);
+    backfilled_header.receipt_commitment = Some(block_commitments.receipt_commitment);
+    backfilled_header.state_diff_commitment = Some(block_commitments.state_diff_commitment);
+    backfilled_header.state_diff_length = Some(0);
+    backfilled_header.block_hash = calculate_block_hash(
+        backfilled_header.block_header_without_hash.clone(),
+        BlockHeaderCommitments::from_header(&backfilled_header).unwrap(),
+    )
+    .unwrap();
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    snapshot.header.block_header_without_hash.parent_hash = backfilled_header.block_hash;
+    writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot).unwrap().commit().unwrap();
+    let mut state_sync = state_sync(reader.clone(), writer);
+
+    // A header whose fields were changed after hashing.
+    let mut wrong_header = backfilled_header.clone();
+    wrong_header.block_header_without_hash.timestamp = BlockTimestamp(1);
+    let res = state_sync
+        .store_backfilled_header(BlockNumber(4), wrong_header, BlockSignature::default())
+        .await;
+    assert_matches!(
+        res,
+        Err(StateSyncError::BlockHashNotOfHeader { block_number: BlockNumber(4), .. })
+    );
+
+    state_sync
+        .store_backfilled_header(
+            BlockNumber(4),
+            backfilled_header.clone(),
+            BlockSignature::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_block_header(BlockNumber(4)).unwrap(),
+        Some(backfilled_header)
+    );
+}
+
+#[tokio::test]
+async fn seed_checkpoint_skipped_in_non_empty_storage() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header(0))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let mut state_sync = state_sync(reader.clone(), writer);
+
+    // The snapshot isn't read, so it doesn't have to exist.
+    let config = CheckpointConfig {
+        snapshot_path: PathBuf::from("./non_existing_snapshot.zst"),
+        ..Default::default()
+    };
+    state_sync.seed_checkpoint(&config).await.unwrap();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_checkpoint().unwrap(), None);
+}
+
+#[tokio::test]
+async fn seed_checkpoint_with_snapshot_of_another_block() {
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let snapshot_path = snapshot_dir.path().join("state_snapshot.zst");
+    let snapshot = ArchivedBlock { header: header(5), ..Default::default() };
+    write_state_snapshot(&snapshot, &snapshot_path).unwrap();
+    let mut state_sync = state_sync(reader.clone(), writer);
+
+    let config = CheckpointConfig {
+        block_number: BlockNumber(6),
+        block_hash: header(6).block_hash,
+        snapshot_path,
+    };
+    let res = state_sync.seed_checkpoint(&config).await;
+    assert_matches!(res, Err(StateSyncError::StateSnapshotNotOfCheckpoint { .. }));
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_checkpoint().unwrap(), None);
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(0));
+}
+
+#[tokio::test]
+async fn seed_checkpoint_with_tampered_snapshot_header() {
+    let mut snapshot_header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(5),
+            starknet_version: BlockHashVersion::V0_13_4.into(),
+            ..Default::default()
+        },
+        state_diff_length: Some(0),
+        ..Default::default()
+    };
+    let block_commitments = BlockHeaderCommitments::default();
+    snapshot_header.transaction_commitment = Some(block_commitments.transaction_commitment);
+    snapshot_header.event_commitment = Some(block_commitments.event_commitment);
+    snapshot_header.receipt_commitment = Some(block_commitments.receipt_commitment);
+    snapshot_header.state_diff_commitment = Some(block_commitments.state_diff_commitment);
+    snapshot_header.block_hash = calculate_block_hash(
+        snapshot_header.block_header_without_hash.clone(),
+        BlockHeaderCommitments::from_header(&snapshot_header).unwrap(),
+    )
+    .unwrap();
+    let config = |snapshot_path| CheckpointConfig {
+        block_number: BlockNumber(5),
+        block_hash: snapshot_header.block_hash,
+        snapshot_path,
+    };
+    let snapshot_dir = tempfile::tempdir().unwrap();
+
+    // A state root which isn't the one the checkpoint hash commits to.
+    let mut tampered_header = snapshot_header.clone();
+    tampered_header.block_header_without_hash.state_root = GlobalRoot(felt!("0x666"));
+    let snapshot_path = snapshot_dir.path().join("tampered_snapshot.zst");
+    write_state_snapshot(
+        &ArchivedBlock { header: tampered_header, ..Default::default() },
+        &snapshot_path,
+    )
+    .unwrap();
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    let mut state_sync = state_sync(reader.clone(), writer);
+    let res = state_sync.seed_checkpoint(&config(snapshot_path)).await;
+    assert_matches!(
+        res,
+        Err(StateSyncError::BlockHashNotOfHeader { block_number: BlockNumber(5), .. })
+    );
+    assert_eq!(reader.begin_ro_txn().unwrap().get_checkpoint().unwrap(), None);
+
+    // A version whose hash can't be verified against the header.
+    let mut old_header = snapshot_header.clone();
+    old_header.block_header_without_hash.starknet_version = StarknetVersion::V0_13_1;
+    let snapshot_path = snapshot_dir.path().join("old_snapshot.zst");
+    write_state_snapshot(
+        &ArchivedBlock { header: old_header, ..Default::default() },
+        &snapshot_path,
+    )
+    .unwrap();
+    let res = state_sync.seed_checkpoint(&config(snapshot_path)).await;
+    assert_matches!(res, Err(StateSyncError::StateSnapshotHeaderUnverifiable { .. }));
+    assert_eq!(reader.begin_ro_txn().unwrap().get_checkpoint().unwrap(), None);
+}
@@ -2,8 +2,10 @@
 // within this crate
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 
+pub mod checkpoint;
 mod pending_sync;
 pub mod sources;
+mod state_root_verifier;
 #[cfg(test)]
 mod sync_test;
 
@@ -16,7 +18,10 @@ use apollo_class_manager_types::{ClassManagerClientError, SharedClassManagerClie
 use apollo_config::converters::deserialize_seconds_to_duration;
 use apollo_config::dumping::{ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_consensus::commit_certificate::verify_commit_certificate;
+use apollo_consensus::types::vote_signing_payload;
 use apollo_proc_macros::latency_histogram;
+use apollo_signature_manager::signature_manager::verify_precommit_vote_signature;
 use apollo_starknet_client::reader::PendingData;
 use apollo_state_sync_metrics::metrics::{
     CENTRAL_SYNC_BASE_LAYER_MARKER,
@@ -29,14 +34,17 @@ use apollo_state_sync_metrics::metrics::{
     STATE_SYNC_PROCESSED_TRANSACTIONS,
     STATE_SYNC_STATE_MARKER,
 };
+use apollo_storage::archive::ArchiveError;
 use apollo_storage::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter};
 use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::checkpoint::CheckpointStorageReader;
 use apollo_storage::class::{ClassStorageReader, ClassStorageWriter};
 use apollo_storage::class_manager::{ClassManagerStorageReader, ClassManagerStorageWriter};
 use apollo_storage::compiled_class::{CasmStorageReader, CasmStorageWriter};
 use apollo_storage::db::DbError;
 use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
 use apollo_storage::state::{StateStorageReader, StateStorageWriter};
+use apollo_storage::state_tries::StateTriesStorageReader;
 use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use async_stream::try_stream;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
@@ -52,22 +60,30 @@ use starknet_api::block::{
     Block,
     BlockHash,
     BlockHashAndNumber,
+    BlockHeader,
     BlockNumber,
     BlockSignature,
+    CommitCertificate,
+    CommitteeMember,
     StarknetVersion,
 };
 use starknet_api::contract_class::{ContractClass, SierraVersion};
-use starknet_api::core::{ClassHash, CompiledClassHash, SequencerPublicKey};
+use starknet_api::core::{ClassHash, CompiledClassHash, GlobalRoot, SequencerPublicKey};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::{StateDiff, ThinStateDiff};
-use tokio::sync::{Mutex, RwLock};
+use starknet_committer::block_committer::errors::BlockCommitmentError;
+use starknet_patricia::patricia_merkle_tree::traversal::TraversalError;
+use tokio::sync::{watch, Mutex, RwLock};
 use tokio::task::{spawn_blocking, JoinError};
 use tracing::{debug, error, info, instrument, trace, warn};
 
+use crate::checkpoint::{stream_backfilled_headers, CheckpointConfig};
 use crate::pending_sync::sync_pending_data;
-use crate::sources::base_layer::{BaseLayerSourceTrait, EthereumBaseLayerSource};
-use crate::sources::central::{CentralError, CentralSource, CentralSourceTrait};
+use crate::sources::base_layer::{BaseLayerSourceTrait, ConfiguredBaseLayerSource};
+use crate::sources::central::{CentralError, CentralSourceTrait};
+use crate::sources::configured_central_source::ConfiguredCentralSource;
 use crate::sources::pending::{PendingError, PendingSource, PendingSourceTrait};
+use crate::state_root_verifier::StateRootVerifier;
 
 // TODO(shahak): Consider adding genesis hash to the config to support chains that have
 // different genesis hash.
@@ -98,6 +114,8 @@ pub struct SyncConfig {
     pub verify_blocks: bool,
     pub collect_pending_data: bool,
     pub store_sierras_and_casms: bool,
+    pub verify_state_root: bool,
+    pub n_retained_state_tries: u64,
 }
 
 impl SerializeConfig for SyncConfig {
@@ -153,6 +171,21 @@ impl SerializeConfig for SyncConfig {
                  backward-compatibility with native-blockifier",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "verify_state_root",
+                &self.verify_state_root,
+                "Whether to verify the state root of each block by committing its state diff into \
+                 the state tries in the storage. The sync stops if the state root doesn't match \
+                 the block header.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "n_retained_state_tries",
+                &self.n_retained_state_tries,
+                "The number of recent blocks whose state tries are kept in the storage, if the \
+                 state root is verified. Storage proofs are served for these blocks.",
+                ParamPrivacyInput::Public,
+            ),
         ])
     }
 }
@@ -168,6 +201,8 @@ impl Default for SyncConfig {
             verify_blocks: true,
             collect_pending_data: false,
             store_sierras_and_casms: false,
+            verify_state_root: false,
+            n_retained_state_tries: 128,
         }
     }
 }
@@ -182,6 +217,8 @@ pub struct GenericStateSync<
     config: SyncConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    // Notified whenever the pending data is updated.
+    pending_data_updates: watch::Sender<()>,
     central_source: Arc<TCentralSource>,
     pending_source: Arc<TPendingSource>,
     pending_classes: Arc<RwLock<PendingClasses>>,
@@ -190,6 +227,23 @@ pub struct GenericStateSync<
     writer: Arc<Mutex<StorageWriter>>,
     sequencer_pub_key: Option<SequencerPublicKey>,
     class_manager_client: Option<SharedClassManagerClient>,
+    // Set iff the state roots should be verified.
+    state_root_verifier: Option<StateRootVerifier>,
+    // If set, an empty storage is seeded from this checkpoint before syncing.
+    checkpoint: Option<CheckpointConfig>,
+    // Set iff consensus is enabled.
+    decided_commit_certificates: Option<DecidedCommitCertificates>,
+}
+
+/// The commit certificates of the blocks decided by consensus, which central sync verifies against
+/// the committee and stores along with the blocks. The central source doesn't serve certificates,
+/// so a block that wasn't decided by this node's consensus is stored without one.
+#[derive(Clone)]
+pub struct DecidedCommitCertificates {
+    /// The committee which decides the blocks.
+    pub committee: Vec<CommitteeMember>,
+    /// The certificates of the decided blocks which weren't stored yet, by block number.
+    pub certificates: Arc<std::sync::Mutex<BTreeMap<BlockNumber, CommitCertificate>>>,
 }
 
 pub type StateSyncResult = Result<(), StateSyncError>;
@@ -234,6 +288,59 @@ pub enum StateSyncError {
     ClassManagerClientError(#[from] ClassManagerClientError),
     #[error(transparent)]
     JoinError(#[from] JoinError),
+    #[error(
+        "The state root of block {block_number} is {header_state_root} according to its header, \
+         but committing its state diff resulted in {computed_state_root}."
+    )]
+    StateRootMismatch {
+        block_number: BlockNumber,
+        header_state_root: GlobalRoot,
+        computed_state_root: GlobalRoot,
+    },
+    #[error(transparent)]
+    BlockCommitmentError(#[from] BlockCommitmentError),
+    #[error(transparent)]
+    TraversalError(#[from] TraversalError),
+    #[error(transparent)]
+    StateSnapshotError(#[from] ArchiveError),
+    #[error(
+        "The state snapshot is of block {snapshot_block:?} instead of the checkpoint \
+         {checkpoint:?}."
+    )]
+    StateSnapshotNotOfCheckpoint {
+        checkpoint: BlockHashAndNumber,
+        snapshot_block: BlockHashAndNumber,
+    },
+    #[error(
+        "Block {block_number} was reverted in the source, but the blocks up to the checkpoint \
+         {checkpoint} are trusted."
+    )]
+    CheckpointReverted { block_number: BlockNumber, checkpoint: BlockNumber },
+    #[error(
+        "The hash of block {block_number} is {block_hash}, but the block after it in the storage \
+         has the parent hash {expected_block_hash}."
+    )]
+    BackfilledBlockHashMismatch {
+        block_number: BlockNumber,
+        expected_block_hash: BlockHash,
+        block_hash: BlockHash,
+    },
+    #[error(
+        "The hash of block {block_number} is {block_hash}, but the hash of its header is \
+         {computed_block_hash}."
+    )]
+    BlockHashNotOfHeader {
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        computed_block_hash: BlockHash,
+    },
+    #[error("The header of block {block_number} is missing the commitments of the block.")]
+    HeaderWithoutCommitments { block_number: BlockNumber },
+    #[error(
+        "The state snapshot is of a block of Starknet {starknet_version}, whose hash can't be \
+         verified against its header."
+    )]
+    StateSnapshotHeaderUnverifiable { starknet_version: StarknetVersion },
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -265,6 +372,12 @@ pub enum SyncEvent {
         block_number: BlockNumber,
         block_hash: BlockHash,
     },
+    // The header of a block below the checkpoint.
+    BackfilledHeaderAvailable {
+        block_number: BlockNumber,
+        header: BlockHeader,
+        signature: BlockSignature,
+    },
 }
 
 impl<
@@ -275,6 +388,12 @@ impl<
 {
     pub async fn run(mut self) -> StateSyncResult {
         info!("State sync started.");
+        if let Some(checkpoint) = self.checkpoint.clone() {
+            if let Err(err) = self.seed_checkpoint(&checkpoint).await {
+                error!("Failed to seed the storage from the checkpoint: {}", err);
+                return Err(err);
+            }
+        }
         loop {
             match self.sync_while_ok().await {
                 // A recoverable error occurred. Sleep and try syncing again.
@@ -311,7 +430,17 @@ impl<
                 | StateSyncError::ClassManagerClientError(_)
                 | StateSyncError::BaseLayerBlockWithoutMatchingHeader { .. }
                 | StateSyncError::JoinError(_) => true,
-                StateSyncError::SequencerPubKeyChanged { .. } => false,
+                StateSyncError::SequencerPubKeyChanged { .. }
+                | StateSyncError::StateRootMismatch { .. }
+                | StateSyncError::BlockCommitmentError(_)
+                | StateSyncError::TraversalError(_)
+                | StateSyncError::StateSnapshotError(_)
+                | StateSyncError::StateSnapshotNotOfCheckpoint { .. }
+                | StateSyncError::CheckpointReverted { .. }
+                | StateSyncError::BackfilledBlockHashMismatch { .. }
+                | StateSyncError::BlockHashNotOfHeader { .. }
+                | StateSyncError::HeaderWithoutCommitments { .. }
+                | StateSyncError::StateSnapshotHeaderUnverifiable { .. } => false,
             }
         }
     }
@@ -356,6 +485,7 @@ impl<
             self.pending_source.clone(),
             self.shared_highest_block.clone(),
             self.pending_data.clone(),
+            self.pending_data_updates.clone(),
             self.pending_classes.clone(),
             self.config.block_propagation_sleep_duration,
             self.config.collect_pending_data,
@@ -389,6 +519,12 @@ impl<
             .fuse(),
             None => stream::pending().boxed().fuse(),
         };
+        let backfilled_header_stream = stream_backfilled_headers(
+            self.reader.clone(),
+            self.central_source.clone(),
+            self.config.blocks_max_stream_size,
+        )
+        .fuse();
         // TODO(dvir): try use interval instead of stream.
         // TODO(DvirYo): fix the bug and remove this check.
         let check_sync_progress =
@@ -398,6 +534,7 @@ impl<
             state_diff_stream,
             compiled_class_stream,
             base_layer_block_stream,
+            backfilled_header_stream,
             check_sync_progress
         );
 
@@ -408,6 +545,7 @@ impl<
               res = state_diff_stream.next() => res,
               res = compiled_class_stream.next() => res,
               res = base_layer_block_stream.next() => res,
+              res = backfilled_header_stream.next() => res,
               res = check_sync_progress.next() => res,
               complete => break,
             }
@@ -455,6 +593,9 @@ impl<
             SyncEvent::NewBaseLayerBlock { block_number, block_hash } => {
                 self.store_base_layer_block(block_number, block_hash).await
             }
+            SyncEvent::BackfilledHeaderAvailable { block_number, header, signature } => {
+                self.store_backfilled_header(block_number, header, signature).await
+            }
             SyncEvent::NoProgress => Err(StateSyncError::NoProgress),
         }
     }
@@ -482,12 +623,16 @@ impl<
         let num_txs =
             block.body.transactions.len().try_into().expect("Failed to convert usize to u64");
         let timestamp = block.header.block_header_without_hash.timestamp;
+        let commit_certificate = self.take_commit_certificate(&block.header);
         self.perform_storage_writes(move |writer| {
             let mut txn = writer
                 .begin_rw_txn()?
                 .append_header(block_number, &block.header)?
                 .append_block_signature(block_number, &signature)?
                 .append_body(block_number, block.body)?;
+            if let Some(commit_certificate) = &commit_certificate {
+                txn = txn.append_commit_certificate(block_number, commit_certificate)?;
+            }
             if block.header.block_header_without_hash.starknet_version
                 < STARKNET_VERSION_TO_COMPILE_FROM
             {
@@ -516,6 +661,41 @@ impl<
         Ok(())
     }
 
+    // Returns the certificate of the given block if consensus decided it and the certificate is
+    // valid, and discards the certificates of the blocks up to it.
+    fn take_commit_certificate(&self, header: &BlockHeader) -> Option<CommitCertificate> {
+        let decided_commit_certificates = self.decided_commit_certificates.as_ref()?;
+        let block_number = header.block_header_without_hash.block_number;
+        let commit_certificate = {
+            let mut certificates = decided_commit_certificates
+                .certificates
+                .lock()
+                .expect("Lock on the commit certificates was poisoned due to a previous panic");
+            *certificates = certificates.split_off(&block_number);
+            certificates.remove(&block_number)
+        }?;
+        let verification_result = verify_commit_certificate(
+            &commit_certificate,
+            header,
+            &decided_commit_certificates.committee,
+            |vote, public_key| {
+                verify_precommit_vote_signature(
+                    &vote_signing_payload(self.reader.chain_id(), vote),
+                    vote.signature.clone(),
+                    *public_key,
+                )
+                .unwrap_or(false)
+            },
+        );
+        match verification_result {
+            Ok(()) => Some(commit_certificate),
+            Err(error) => {
+                warn!("Discarding the commit certificate of block {block_number}: {error}");
+                None
+            }
+        }
+    }
+
     #[latency_histogram("sync_store_state_diff_latency_seconds", false)]
     #[instrument(skip(self, state_diff, deployed_contract_class_definitions), level = "debug", err)]
     async fn store_state_diff(
@@ -525,7 +705,6 @@ impl<
         state_diff: StateDiff,
         deployed_contract_class_definitions: IndexMap<ClassHash, DeprecatedContractClass>,
     ) -> StateSyncResult {
-        // TODO(dan): verifications - verify state diff against stored header.
         debug!("Storing state diff.");
         trace!("StateDiff data: {state_diff:#?}");
 
@@ -534,6 +713,19 @@ impl<
         let (thin_state_diff, classes, deprecated_classes) =
             ThinStateDiff::from_state_diff(state_diff);
 
+        // Verifying before updating the storage so that a state diff that doesn't match the stored
+        // header is never stored.
+        let state_tries_update = match self.state_root_verifier {
+            Some(state_root_verifier) => {
+                self.store_missing_state_tries(state_root_verifier, block_number).await?;
+                Some(
+                    StateRootVerifier::verify(&self.reader, block_number, thin_state_diff.clone())
+                        .await?,
+                )
+            }
+            None => None,
+        };
+
         // Sending to class manager before updating the storage so that if the class manager send
         // fails we retry the same block.
         if let Some(class_manager_client) = &self.class_manager_client {
@@ -572,6 +764,7 @@ impl<
         }
         let has_class_manager = self.class_manager_client.is_some();
         let store_sierras_and_casms = self.config.store_sierras_and_casms;
+        let state_root_verifier = self.state_root_verifier;
         self.perform_storage_writes(move |writer| {
             if has_class_manager {
                 writer
@@ -582,6 +775,11 @@ impl<
             }
             let mut txn = writer.begin_rw_txn()?;
             txn = txn.append_state_diff(block_number, thin_state_diff)?;
+            if let (Some(state_root_verifier), Some(state_tries_update)) =
+                (state_root_verifier, state_tries_update)
+            {
+                txn = state_root_verifier.append_state_tries(txn, state_tries_update)?;
+            }
             if store_sierras_and_casms {
                 txn = txn.append_classes(
                     block_number,
@@ -685,12 +883,19 @@ impl<
     ) -> StateSyncResult {
         self.perform_storage_writes(move |writer| {
             let txn = writer.begin_rw_txn()?;
-            // Missing header can be because of a base layer reorg, the matching header may be
-            // reverted.
-            let expected_hash = txn
-                .get_block_header(block_number)?
-                .ok_or(StateSyncError::BaseLayerBlockWithoutMatchingHeader { block_number })?
-                .block_hash;
+            // The header of a block below the checkpoint may not be backfilled yet.
+            let Some(header) = txn.get_block_header(block_number)? else {
+                if txn.get_checkpoint()?.is_some_and(|checkpoint| block_number < checkpoint) {
+                    debug!(
+                        "Block {block_number} wasn't backfilled yet, skipping base layer block."
+                    );
+                    return Ok(());
+                }
+                // Missing header can be because of a base layer reorg, the matching header may be
+                // reverted.
+                return Err(StateSyncError::BaseLayerBlockWithoutMatchingHeader { block_number });
+            };
+            let expected_hash = header.block_hash;
             // Can be caused because base layer reorg or l2 reverts.
             if expected_hash != block_hash {
                 return Err(StateSyncError::BaseLayerHashMismatch {
@@ -752,11 +957,17 @@ impl<
     async fn handle_block_reverts(&mut self) -> Result<(), StateSyncError> {
         debug!("Handling block reverts.");
         let header_marker = self.reader.begin_ro_txn()?.get_header_marker()?;
+        let checkpoint = self.reader.begin_ro_txn()?.get_checkpoint()?;
 
         // Revert last blocks if needed.
         let mut last_block_in_storage = header_marker.prev();
         while let Some(block_number) = last_block_in_storage {
             if self.should_revert_block(block_number).await? {
+                if let Some(checkpoint) =
+                    checkpoint.filter(|checkpoint| block_number <= *checkpoint)
+                {
+                    return Err(StateSyncError::CheckpointReverted { block_number, checkpoint });
+                }
                 self.revert_block(block_number).await?;
                 last_block_in_storage = block_number.prev();
             } else {
@@ -815,6 +1026,30 @@ impl<
         }
     }
 
+    // Stores the state tries of the blocks before the given block whose state diff is stored and
+    // whose tries aren't, e.g. since verifying the state root was enabled after they were synced.
+    async fn store_missing_state_tries(
+        &mut self,
+        state_root_verifier: StateRootVerifier,
+        block_number: BlockNumber,
+    ) -> StateSyncResult {
+        loop {
+            let state_tries_marker = self.reader.begin_ro_txn()?.get_state_tries_marker()?;
+            if state_tries_marker >= block_number {
+                return Ok(());
+            }
+            let state_tries_update =
+                StateRootVerifier::verify_stored_block(&self.reader, state_tries_marker).await?;
+            self.perform_storage_writes(move |writer| {
+                state_root_verifier
+                    .append_state_tries(writer.begin_rw_txn()?, state_tries_update)?
+                    .commit()?;
+                Ok(())
+            })
+            .await?;
+        }
+    }
+
     async fn perform_storage_writes<
         F: FnOnce(&mut StorageWriter) -> Result<(), StateSyncError> + Send + 'static,
     >(
@@ -836,6 +1071,7 @@ fn stream_new_blocks<
     pending_source: Arc<TPendingSource>,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Sender<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     block_propagation_sleep_duration: Duration,
     collect_pending_data: bool,
@@ -861,6 +1097,7 @@ fn stream_new_blocks<
                         central_source.clone(),
                         pending_source.clone(),
                         pending_data.clone(),
+                        pending_data_updates.clone(),
                         pending_classes.clone(),
                         pending_sleep_duration,
                     ).await?;
@@ -937,7 +1174,8 @@ pub fn sort_state_diff(diff: &mut StateDiff) {
     }
 }
 
-pub type StateSync = GenericStateSync<CentralSource, PendingSource, EthereumBaseLayerSource>;
+pub type StateSync =
+    GenericStateSync<ConfiguredCentralSource, PendingSource, ConfiguredBaseLayerSource>;
 
 impl StateSync {
     #[allow(clippy::too_many_arguments)]
@@ -945,19 +1183,25 @@ impl StateSync {
         config: SyncConfig,
         shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
         pending_data: Arc<RwLock<PendingData>>,
+        pending_data_updates: watch::Sender<()>,
         pending_classes: Arc<RwLock<PendingClasses>>,
-        central_source: CentralSource,
+        central_source: ConfiguredCentralSource,
         pending_source: PendingSource,
-        base_layer_source: Option<EthereumBaseLayerSource>,
+        base_layer_source: Option<ConfiguredBaseLayerSource>,
         reader: StorageReader,
         writer: StorageWriter,
         class_manager_client: Option<SharedClassManagerClient>,
+        checkpoint: Option<CheckpointConfig>,
+        decided_commit_certificates: Option<DecidedCommitCertificates>,
     ) -> Self {
         let base_layer_source = base_layer_source.map(Arc::new);
+        let state_root_verifier =
+            config.verify_state_root.then(|| StateRootVerifier::new(config.n_retained_state_tries));
         Self {
             config,
             shared_highest_block,
             pending_data,
+            pending_data_updates,
             pending_classes,
             central_source: Arc::new(central_source),
             pending_source: Arc::new(pending_source),
@@ -966,6 +1210,9 @@ impl StateSync {
             writer: Arc::new(Mutex::new(writer)),
             sequencer_pub_key: None,
             class_manager_client,
+            state_root_verifier,
+            checkpoint,
+            decided_commit_certificates,
         }
     }
 }
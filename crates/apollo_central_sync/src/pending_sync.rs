@@ -11,7 +11,7 @@ use papyrus_common::pending_classes::{PendingClasses, PendingClassesTrait};
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::ClassHash;
 use starknet_types_core::felt::Felt;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tracing::{debug, trace};
 
 use crate::sources::central::CentralSourceTrait;
@@ -27,6 +27,7 @@ pub(crate) async fn sync_pending_data<
     central_source: Arc<TCentralSource>,
     pending_source: Arc<TPendingSource>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Sender<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     sleep_duration: Duration,
 ) -> Result<(), StateSyncError> {
@@ -51,6 +52,7 @@ pub(crate) async fn sync_pending_data<
             latest_block_hash,
             pending_source.clone(),
             pending_data.clone(),
+            pending_data_updates.clone(),
             pending_classes.clone(),
             Duration::ZERO,
         )
@@ -110,6 +112,7 @@ pub(crate) async fn sync_pending_data<
                         latest_block_hash,
                         pending_source.clone(),
                         pending_data.clone(),
+                        pending_data_updates.clone(),
                         pending_classes.clone(),
                         sleep_duration,
                     )
@@ -121,6 +124,7 @@ pub(crate) async fn sync_pending_data<
                     latest_block_hash,
                     pending_source.clone(),
                     pending_data.clone(),
+                    pending_data_updates.clone(),
                     pending_classes.clone(),
                     sleep_duration,
                 )
@@ -142,6 +146,7 @@ async fn get_pending_data<TPendingSource: PendingSourceTrait + Sync + Send + 'st
     latest_block_hash: BlockHash,
     pending_source: Arc<TPendingSource>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Sender<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     sleep_duration: Duration,
 ) -> Result<PendingSyncTaskResult, StateSyncError> {
@@ -177,6 +182,7 @@ async fn get_pending_data<TPendingSource: PendingSourceTrait + Sync + Send + 'st
             pending_classes.write().await.clear();
         }
         *pending_data.write().await = new_pending_data;
+        pending_data_updates.send_replace(());
         Ok(PendingSyncTaskResult::DownloadedNewPendingData)
     } else {
         debug!("Pending block wasn't updated. Waiting for pending block to be updated.");
@@ -0,0 +1,307 @@
+#[cfg(test)]
+#[path = "archive_test.rs"]
+mod archive_test;
+
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_storage::archive::{ArchiveError, ArchiveFile, ArchiveReader, ArchivedBlock};
+use async_stream::stream;
+use async_trait::async_trait;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+use lru::LruCache;
+use papyrus_common::pending_classes::ApiContractClass;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{Block, BlockHash, BlockHashAndNumber, BlockNumber};
+use starknet_api::core::{ClassHash, SequencerPublicKey};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::StateDiff;
+use tracing::{debug, info};
+use validator::Validate;
+
+use crate::sources::central::{
+    BlocksStream,
+    CentralError,
+    CentralSourceTrait,
+    CompiledClassesStream,
+    StateUpdatesStream,
+};
+
+// The number of decompressed archive files to keep in memory. The blocks, state updates and
+// compiled classes streams read the same files, each at its own pace.
+const ARCHIVE_FILE_CACHE_SIZE: usize = 4;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Validate)]
+pub struct ArchiveSourceConfig {
+    pub path: PathBuf,
+}
+
+impl Default for ArchiveSourceConfig {
+    fn default() -> Self {
+        Self { path: PathBuf::from("./archive") }
+    }
+}
+
+impl SerializeConfig for ArchiveSourceConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([ser_param(
+            "path",
+            &self.path,
+            "The directory of the archive files to sync from.",
+            ParamPrivacyInput::Public,
+        )])
+    }
+}
+
+/// A source that reads the blocks from an archive exported from the storage of another node (see
+/// [`apollo_storage::archive`]), for syncing without network access. The archive isn't trusted:
+/// the blocks go through the same validations as blocks from central.
+///
+/// The archive doesn't hold pending data, so the sync shouldn't collect pending data from it.
+pub struct ArchiveSource {
+    archive: ArchiveReader,
+    latest_block: Option<BlockHashAndNumber>,
+    file_cache: Mutex<LruCache<BlockNumber, Arc<Vec<ArchivedBlock>>>>,
+}
+
+impl ArchiveSource {
+    /// Opens the archive at the configured path. The archive must hold `next_block`, the first
+    /// block the sync will ask for.
+    pub fn new(config: ArchiveSourceConfig, next_block: BlockNumber) -> Result<Self, ArchiveError> {
+        let archive = ArchiveReader::open(&config.path, next_block)?;
+        let latest_block = match archive.files().last() {
+            None => None,
+            Some(file) => file.read_blocks()?.last().map(|block| BlockHashAndNumber {
+                hash: block.header.block_hash,
+                number: block.header.block_header_without_hash.block_number,
+            }),
+        };
+        info!(
+            "Syncing from the archive at {}, up to block {:?}.",
+            config.path.display(),
+            latest_block.map(|block| block.number)
+        );
+        Ok(Self {
+            archive,
+            latest_block,
+            file_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(ARCHIVE_FILE_CACHE_SIZE).expect("Invalid cache size."),
+            )),
+        })
+    }
+
+    // Applies the given function to the archived block with the given number.
+    async fn with_block<T>(
+        &self,
+        block_number: BlockNumber,
+        f: impl FnOnce(&ArchivedBlock) -> T,
+    ) -> Result<T, CentralError> {
+        let file = self
+            .archive
+            .file_of(block_number)
+            .ok_or(CentralError::BlockNotFound { block_number })?;
+        let blocks = self.read_file(file).await?;
+        let index = usize::try_from(block_number.0 - file.first_block.0)
+            .expect("Failed to convert u64 to usize.");
+        Ok(f(&blocks[index]))
+    }
+
+    async fn read_file(&self, file: &ArchiveFile) -> Result<Arc<Vec<ArchivedBlock>>, CentralError> {
+        {
+            let mut file_cache = self.file_cache.lock().expect("Failed to lock file cache.");
+            if let Some(blocks) = file_cache.get(&file.first_block) {
+                return Ok(blocks.clone());
+            }
+        }
+        debug!("Reading the archive file {}.", file.path.display());
+        let file_to_read = file.clone();
+        let blocks = Arc::new(
+            tokio::task::spawn_blocking(move || file_to_read.read_blocks())
+                .await
+                .expect("Failed to join the archive file reading task.")?,
+        );
+        self.file_cache
+            .lock()
+            .expect("Failed to lock file cache.")
+            .put(file.first_block, blocks.clone());
+        Ok(blocks)
+    }
+}
+
+#[async_trait]
+impl CentralSourceTrait for ArchiveSource {
+    async fn get_latest_block(&self) -> Result<Option<BlockHashAndNumber>, CentralError> {
+        Ok(self.latest_block)
+    }
+
+    fn stream_new_blocks(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> BlocksStream<'_> {
+        stream! {
+            for block_number in initial_block_number.iter_up_to(up_to_block_number) {
+                let block_and_signature = self
+                    .with_block(block_number, |block| {
+                        let signature = block.signature.unwrap_or_default();
+                        (Block { header: block.header.clone(), body: block.body.clone() }, signature)
+                    })
+                    .await;
+                match block_and_signature {
+                    Ok((block, signature)) => {
+                        yield Ok((block_number, block, signature));
+                    }
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn stream_state_updates(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> StateUpdatesStream<'_> {
+        stream! {
+            for block_number in initial_block_number.iter_up_to(up_to_block_number) {
+                let state_update = self
+                    .with_block(block_number, |block| {
+                        archived_block_state_diff(block).map(
+                            |(state_diff, deployed_contract_class_definitions)| {
+                                (block.header.block_hash, state_diff, deployed_contract_class_definitions)
+                            },
+                        )
+                    })
+                    .await
+                    .and_then(|state_update| state_update);
+                match state_update {
+                    Ok((block_hash, state_diff, deployed_contract_class_definitions)) => {
+                        yield Ok((
+                            block_number,
+                            block_hash,
+                            state_diff,
+                            deployed_contract_class_definitions,
+                        ));
+                    }
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    async fn get_block_hash(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<Option<BlockHash>, CentralError> {
+        if self.archive.file_of(block_number).is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.with_block(block_number, |block| block.header.block_hash).await?))
+    }
+
+    fn stream_compiled_classes(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> CompiledClassesStream<'_> {
+        stream! {
+            for block_number in initial_block_number.iter_up_to(up_to_block_number) {
+                let compiled_classes = self
+                    .with_block(block_number, |block| {
+                        block
+                            .state_diff
+                            .declared_classes
+                            .iter()
+                            .map(|(class_hash, compiled_class_hash)| {
+                                let casm = block.casms.get(class_hash).ok_or(
+                                    CentralError::CompiledClassNotFound { class_hash: *class_hash },
+                                )?;
+                                Ok((*class_hash, *compiled_class_hash, casm.clone()))
+                            })
+                            .collect::<Result<Vec<_>, CentralError>>()
+                    })
+                    .await
+                    .and_then(|compiled_classes| compiled_classes);
+                match compiled_classes {
+                    Ok(compiled_classes) => {
+                        for compiled_class in compiled_classes {
+                            yield Ok(compiled_class);
+                        }
+                    }
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    // The archive doesn't hold pending classes.
+    async fn get_class(&self, _class_hash: ClassHash) -> Result<ApiContractClass, CentralError> {
+        Err(CentralError::ClassNotFound)
+    }
+
+    async fn get_compiled_class(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<CasmContractClass, CentralError> {
+        Err(CentralError::CompiledClassNotFound { class_hash })
+    }
+
+    // The archive doesn't hold the sequencer public key, which is only tracked for changes.
+    async fn get_sequencer_pub_key(&self) -> Result<SequencerPublicKey, CentralError> {
+        Ok(SequencerPublicKey::default())
+    }
+}
+
+// Returns the state diff of the block, and the Cairo 0 classes that are defined by deployments in
+// it.
+fn archived_block_state_diff(
+    block: &ArchivedBlock,
+) -> Result<(StateDiff, IndexMap<ClassHash, DeprecatedContractClass>), CentralError> {
+    let declared_classes = block
+        .state_diff
+        .declared_classes
+        .iter()
+        .map(|(class_hash, compiled_class_hash)| {
+            let class = block.classes.get(class_hash).ok_or(CentralError::ClassNotFound)?;
+            Ok((*class_hash, (*compiled_class_hash, class.clone())))
+        })
+        .collect::<Result<_, CentralError>>()?;
+    let mut deployed_contract_class_definitions = block.deprecated_classes.clone();
+    let deprecated_declared_classes = block
+        .state_diff
+        .deprecated_declared_classes
+        .iter()
+        .map(|class_hash| {
+            let deprecated_class = deployed_contract_class_definitions
+                .shift_remove(class_hash)
+                .ok_or(CentralError::ClassNotFound)?;
+            Ok((*class_hash, deprecated_class))
+        })
+        .collect::<Result<_, CentralError>>()?;
+    let state_diff = StateDiff {
+        deployed_contracts: block.state_diff.deployed_contracts.clone(),
+        storage_diffs: block.state_diff.storage_diffs.clone(),
+        declared_classes,
+        deprecated_declared_classes,
+        nonces: block.state_diff.nonces.clone(),
+    };
+    Ok((state_diff, deployed_contract_class_definitions))
+}
@@ -0,0 +1,119 @@
+use apollo_storage::archive::export_archive;
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::class::ClassStorageWriter;
+use apollo_storage::compiled_class::CasmStorageWriter;
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::test_utils::get_test_storage;
+use apollo_test_utils::{get_rng, GetTestInstance};
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use futures_util::StreamExt;
+use indexmap::indexmap;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockBody, BlockHash, BlockHashAndNumber, BlockHeader, BlockNumber};
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::{SierraContractClass, StateDiff, ThinStateDiff};
+use starknet_api::{contract_address, felt};
+
+use crate::sources::archive::{ArchiveSource, ArchiveSourceConfig};
+use crate::sources::central::{CentralError, CentralSourceTrait};
+
+#[tokio::test]
+async fn stream_from_archive() {
+    let mut rng = get_rng();
+    let class = SierraContractClass::get_test_instance(&mut rng);
+    let casm = CasmContractClass::get_test_instance(&mut rng);
+    let deprecated_class = DeprecatedContractClass::get_test_instance(&mut rng);
+    let class_hash = ClassHash(felt!("0x1"));
+    let compiled_class_hash = CompiledClassHash(felt!("0x2"));
+    let declared_deprecated_class_hash = ClassHash(felt!("0x3"));
+    let deployed_deprecated_class_hash = ClassHash(felt!("0x4"));
+    let header = BlockHeader { block_hash: BlockHash(felt!("0x10")), ..Default::default() };
+    let state_diff = ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!("0x100") => deployed_deprecated_class_hash },
+        declared_classes: indexmap! { class_hash => compiled_class_hash },
+        deprecated_declared_classes: vec![declared_deprecated_class_hash],
+        ..Default::default()
+    };
+
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header)
+        .unwrap()
+        .append_body(BlockNumber(0), BlockBody::default())
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff.clone())
+        .unwrap()
+        .append_classes(
+            BlockNumber(0),
+            &[(class_hash, &class)],
+            &[
+                (declared_deprecated_class_hash, &deprecated_class),
+                (deployed_deprecated_class_hash, &deprecated_class),
+            ],
+        )
+        .unwrap()
+        .append_casm(&class_hash, &casm)
+        .unwrap()
+        .commit()
+        .unwrap();
+    let archive_dir = tempfile::tempdir().unwrap();
+    export_archive(&reader, archive_dir.path(), BlockNumber(0), BlockNumber(1), 1).unwrap();
+
+    let source = ArchiveSource::new(
+        ArchiveSourceConfig { path: archive_dir.path().to_path_buf() },
+        BlockNumber(0),
+    )
+    .unwrap();
+    assert_eq!(
+        source.get_latest_block().await.unwrap(),
+        Some(BlockHashAndNumber { hash: header.block_hash, number: BlockNumber(0) })
+    );
+    assert_eq!(source.get_block_hash(BlockNumber(0)).await.unwrap(), Some(header.block_hash));
+    assert_eq!(source.get_block_hash(BlockNumber(1)).await.unwrap(), None);
+
+    let blocks = source.stream_new_blocks(BlockNumber(0), BlockNumber(1)).collect::<Vec<_>>().await;
+    assert_matches!(&blocks[..], [Ok((BlockNumber(0), block, _))] if block.header == header);
+
+    let state_updates = source
+        .stream_state_updates(BlockNumber(0), BlockNumber(1))
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .await;
+    let expected_state_diff = StateDiff {
+        deployed_contracts: state_diff.deployed_contracts,
+        declared_classes: indexmap! { class_hash => (compiled_class_hash, class) },
+        deprecated_declared_classes: indexmap! {
+            declared_deprecated_class_hash => deprecated_class.clone(),
+        },
+        ..Default::default()
+    };
+    // The class of the deployed contract is given as a class definition of a deployment.
+    assert_eq!(
+        state_updates,
+        vec![(
+            BlockNumber(0),
+            header.block_hash,
+            expected_state_diff,
+            indexmap! { deployed_deprecated_class_hash => deprecated_class },
+        )]
+    );
+
+    let compiled_classes = source
+        .stream_compiled_classes(BlockNumber(0), BlockNumber(1))
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(compiled_classes, vec![(class_hash, compiled_class_hash, casm)]);
+
+    // Blocks after the archive aren't found.
+    let mut blocks = source.stream_new_blocks(BlockNumber(1), BlockNumber(2));
+    assert_matches!(
+        blocks.next().await,
+        Some(Err(CentralError::BlockNotFound { block_number: BlockNumber(1) }))
+    );
+}
@@ -1,11 +1,12 @@
 use async_trait::async_trait;
 #[cfg(test)]
 use mockall::automock;
+use papyrus_base_layer::configured_base_layer::ConfiguredBaseLayer;
 use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
 use papyrus_base_layer::BaseLayerContract;
 use starknet_api::block::{BlockHash, BlockNumber};
 
-pub type EthereumBaseLayerSource = EthereumBaseLayerContract;
+pub type ConfiguredBaseLayerSource = ConfiguredBaseLayer<EthereumBaseLayerContract>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum BaseLayerSourceError {
@@ -17,6 +17,7 @@ use apollo_starknet_client::reader::{
     StarknetReader,
 };
 use apollo_starknet_client::{ClientCreationError, RetryConfig};
+use apollo_storage::archive::ArchiveError;
 use apollo_storage::state::StateStorageReader;
 use apollo_storage::{StorageError, StorageReader};
 use async_stream::stream;
@@ -163,6 +164,8 @@ pub enum CentralError {
         "Block downloaded from central is in 0.13.1 format, while signature is in 0.13.2 format."
     )]
     BlockAndSignatureVersionMismatch,
+    #[error(transparent)]
+    ArchiveError(#[from] ArchiveError),
 }
 
 #[cfg_attr(test, automock)]
@@ -30,7 +30,7 @@ use starknet_api::core::{ClassHash, SequencerPublicKey};
 use starknet_api::crypto::utils::PublicKey;
 use starknet_api::felt;
 use starknet_api::state::StateDiff;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{watch, Mutex, RwLock};
 use tokio::time::sleep;
 use tracing::{debug, error};
 
@@ -42,6 +42,7 @@ use crate::sources::central::{
     MockCentralSourceTrait,
     StateUpdatesStream,
 };
+use crate::state_root_verifier::StateRootVerifier;
 use crate::{
     CentralError,
     CentralSourceTrait,
@@ -108,6 +109,8 @@ fn get_test_sync_config(verify_blocks: bool) -> SyncConfig {
         collect_pending_data: false,
         // TODO(Shahak): Add test where store_sierras_and_casms is set to false.
         store_sierras_and_casms: true,
+        verify_state_root: false,
+        n_retained_state_tries: 128,
     }
 }
 
@@ -128,6 +131,7 @@ async fn run_sync(
         config,
         shared_highest_block: Arc::new(RwLock::new(None)),
         pending_data: Arc::new(RwLock::new(PendingData::default())),
+        pending_data_updates: watch::channel(()).0,
         central_source: Arc::new(central),
         pending_source: Arc::new(pending_source),
         pending_classes: Arc::new(RwLock::new(PendingClasses::default())),
@@ -140,6 +144,11 @@ async fn run_sync(
         // up until that block we call add_class_and_executable_unsafe and from that block we call
         // add_class.
         class_manager_client,
+        state_root_verifier: config
+            .verify_state_root
+            .then(|| StateRootVerifier::new(config.n_retained_state_tries)),
+        checkpoint: None,
+        decided_commit_certificates: None,
     };
 
     state_sync.run().await?;
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use papyrus_common::pending_classes::ApiContractClass;
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
+use starknet_api::core::{ClassHash, SequencerPublicKey};
+
+use crate::sources::archive::ArchiveSource;
+use crate::sources::central::{
+    BlocksStream,
+    CentralError,
+    CentralSource,
+    CentralSourceTrait,
+    CompiledClassesStream,
+    StateUpdatesStream,
+};
+
+/// The source a node is configured to sync from: the Starknet feeder gateway, or a local archive.
+pub enum ConfiguredCentralSource {
+    Central(CentralSource),
+    Archive(ArchiveSource),
+}
+
+// Calls the method on the configured source.
+macro_rules! delegate {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            ConfiguredCentralSource::Central(source) => source.$method($($arg),*),
+            ConfiguredCentralSource::Archive(source) => source.$method($($arg),*),
+        }
+    };
+}
+
+#[async_trait]
+impl CentralSourceTrait for ConfiguredCentralSource {
+    async fn get_latest_block(&self) -> Result<Option<BlockHashAndNumber>, CentralError> {
+        delegate!(self.get_latest_block()).await
+    }
+
+    fn stream_new_blocks(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> BlocksStream<'_> {
+        delegate!(self.stream_new_blocks(initial_block_number, up_to_block_number))
+    }
+
+    fn stream_state_updates(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> StateUpdatesStream<'_> {
+        delegate!(self.stream_state_updates(initial_block_number, up_to_block_number))
+    }
+
+    async fn get_block_hash(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<Option<BlockHash>, CentralError> {
+        delegate!(self.get_block_hash(block_number)).await
+    }
+
+    fn stream_compiled_classes(
+        &self,
+        initial_block_number: BlockNumber,
+        up_to_block_number: BlockNumber,
+    ) -> CompiledClassesStream<'_> {
+        delegate!(self.stream_compiled_classes(initial_block_number, up_to_block_number))
+    }
+
+    async fn get_class(&self, class_hash: ClassHash) -> Result<ApiContractClass, CentralError> {
+        delegate!(self.get_class(class_hash)).await
+    }
+
+    async fn get_compiled_class(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<CasmContractClass, CentralError> {
+        delegate!(self.get_compiled_class(class_hash)).await
+    }
+
+    async fn get_sequencer_pub_key(&self) -> Result<SequencerPublicKey, CentralError> {
+        delegate!(self.get_sequencer_pub_key()).await
+    }
+}
@@ -1,5 +1,7 @@
+pub mod archive;
 pub mod base_layer;
 pub mod central;
 #[cfg(test)]
 mod central_sync_test;
+pub mod configured_central_source;
 pub mod pending;
@@ -0,0 +1,209 @@
+#[cfg(test)]
+#[path = "state_root_verifier_test.rs"]
+mod state_root_verifier_test;
+
+use apollo_storage::checkpoint::CheckpointStorageReader;
+use apollo_storage::db::{TransactionKind, RW};
+use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::state::StateStorageReader;
+use apollo_storage::state_tries::{
+    StateTriesRoots,
+    StateTriesStorageReader,
+    StateTriesStorageWriter,
+};
+use apollo_storage::{StorageError, StorageReader, StorageResult, StorageTxn};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::GlobalRoot;
+use starknet_api::state::ThinStateDiff;
+use starknet_committer::block_committer::commit::{
+    calculate_global_root,
+    commit_block,
+    fetch_commitment_witnesses,
+    fetch_replaced_nodes,
+};
+use starknet_committer::block_committer::input::{ConfigImpl, Input, StateDiff};
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia_storage::storage_trait::{DbKey, DbValue};
+use tracing::debug;
+use tracing::level_filters::LevelFilter;
+
+use crate::StateSyncError;
+
+/// Commits the synced state diffs into the state tries in the storage, and verifies that the
+/// resulting global root of each block matches the state root in its header.
+///
+/// The tries are built from the first block whose state is stored: the checkpoint if the storage
+/// was seeded from one, or the genesis block. Only the tries of the last `n_retained_blocks` blocks
+/// are kept, and they're shared with the RPC for serving storage proofs.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct StateRootVerifier {
+    n_retained_blocks: u64,
+}
+
+/// The state tries after a block whose state root was verified, to be stored along with its state
+/// diff.
+pub(crate) struct StateTriesUpdate {
+    block_number: BlockNumber,
+    roots: StateTriesRoots,
+    new_nodes: Vec<(DbKey, DbValue)>,
+    replaced_nodes: Vec<DbKey>,
+}
+
+impl StateRootVerifier {
+    pub(crate) fn new(n_retained_blocks: u64) -> Self {
+        Self { n_retained_blocks }
+    }
+
+    /// Verifies the state root of the given block, whose state diff is about to be stored, by
+    /// committing the state diff on top of the stored tries of the previous block.
+    pub(crate) async fn verify(
+        reader: &StorageReader,
+        block_number: BlockNumber,
+        state_diff: ThinStateDiff,
+    ) -> Result<StateTriesUpdate, StateSyncError> {
+        let (previous_roots, header_state_root) = {
+            let txn = reader.begin_ro_txn()?;
+            let header_state_root = txn
+                .get_block_header(block_number)?
+                .ok_or(StorageError::DBInconsistency {
+                    msg: format!(
+                        "Missing block {block_number} in the storage (for verifying its state \
+                         root)."
+                    ),
+                })?
+                .block_header_without_hash
+                .state_root;
+            (previous_roots(&txn, block_number)?, header_state_root)
+        };
+        Self::verify_state_diff(reader, block_number, previous_roots, state_diff, header_state_root)
+            .await
+    }
+
+    /// Verifies the state root of a block whose state diff is stored and whose tries aren't, e.g.
+    /// since verifying the state root was enabled after the block was synced.
+    pub(crate) async fn verify_stored_block(
+        reader: &StorageReader,
+        block_number: BlockNumber,
+    ) -> Result<StateTriesUpdate, StateSyncError> {
+        let state_diff = {
+            let txn = reader.begin_ro_txn()?;
+            // The whole state is committed for the checkpoint block.
+            if txn.get_checkpoint()? == Some(block_number) {
+                txn.get_checkpoint_state()?
+            } else {
+                txn.get_state_diff(block_number)?
+            }
+        }
+        .ok_or(StorageError::DBInconsistency {
+            msg: format!(
+                "Missing the state diff of block {block_number} in the storage (for verifying its \
+                 state root)."
+            ),
+        })?;
+        Self::verify(reader, block_number, state_diff).await
+    }
+
+    /// Verifies the state root of the first block of the tries, whose state diff is the whole state
+    /// after it, against the given state root.
+    pub(crate) async fn verify_first_block(
+        reader: &StorageReader,
+        block_number: BlockNumber,
+        state: ThinStateDiff,
+        state_root: GlobalRoot,
+    ) -> Result<StateTriesUpdate, StateSyncError> {
+        Self::verify_state_diff(reader, block_number, StateTriesRoots::default(), state, state_root)
+            .await
+    }
+
+    /// Stores the tries after the verified block and prunes the tries that left the window.
+    pub(crate) fn append_state_tries<'env>(
+        &self,
+        txn: StorageTxn<'env, RW>,
+        update: StateTriesUpdate,
+    ) -> StorageResult<StorageTxn<'env, RW>> {
+        txn.append_state_tries(
+            update.block_number,
+            &update.roots,
+            update.new_nodes,
+            update.replaced_nodes,
+            self.n_retained_blocks,
+        )
+    }
+
+    async fn verify_state_diff(
+        reader: &StorageReader,
+        block_number: BlockNumber,
+        previous_roots: StateTriesRoots,
+        state_diff: ThinStateDiff,
+        header_state_root: GlobalRoot,
+    ) -> Result<StateTriesUpdate, StateSyncError> {
+        let state_diff = StateDiff::from(state_diff);
+        let contracts_trie_root_hash = HashOutput(previous_roots.contracts_trie_root_hash);
+        let classes_trie_root_hash = HashOutput(previous_roots.classes_trie_root_hash);
+        let (witnesses, replaced_nodes) = {
+            let txn = reader.begin_ro_txn()?;
+            (
+                fetch_commitment_witnesses(
+                    &txn,
+                    contracts_trie_root_hash,
+                    classes_trie_root_hash,
+                    &state_diff,
+                )?,
+                fetch_replaced_nodes(
+                    &txn,
+                    contracts_trie_root_hash,
+                    classes_trie_root_hash,
+                    &state_diff,
+                )?,
+            )
+        };
+        let filled_forest = commit_block(Input {
+            storage: witnesses,
+            state_diff,
+            contracts_trie_root_hash,
+            classes_trie_root_hash,
+            config: ConfigImpl::new(false, LevelFilter::INFO),
+        })
+        .await?;
+
+        let contracts_trie_root_hash = filled_forest.get_contract_root_hash();
+        let classes_trie_root_hash = filled_forest.get_compiled_class_root_hash();
+        let computed_state_root =
+            calculate_global_root(contracts_trie_root_hash, classes_trie_root_hash);
+        if computed_state_root != header_state_root {
+            return Err(StateSyncError::StateRootMismatch {
+                block_number,
+                header_state_root,
+                computed_state_root,
+            });
+        }
+        debug!("Verified the state root of block {block_number}.");
+        Ok(StateTriesUpdate {
+            block_number,
+            roots: StateTriesRoots {
+                contracts_trie_root_hash: contracts_trie_root_hash.0,
+                classes_trie_root_hash: classes_trie_root_hash.0,
+            },
+            new_nodes: filled_forest.new_nodes(),
+            replaced_nodes,
+        })
+    }
+}
+
+// Returns the roots of the tries before the given block, which are empty before the first block of
+// the tries.
+fn previous_roots<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    block_number: BlockNumber,
+) -> StorageResult<StateTriesRoots> {
+    let first_block = txn.get_checkpoint()?.unwrap_or_default();
+    let Some(previous_block) = block_number.prev().filter(|_| block_number != first_block) else {
+        return Ok(StateTriesRoots::default());
+    };
+    txn.get_state_tries_roots(previous_block)?.ok_or(StorageError::DBInconsistency {
+        msg: format!(
+            "Missing the state tries of block {previous_block} in the storage (for verifying the \
+             state root of block {block_number})."
+        ),
+    })
+}
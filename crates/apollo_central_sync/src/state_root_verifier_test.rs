@@ -0,0 +1,152 @@
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::state_tries::StateTriesStorageReader;
+use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::{StorageReader, StorageWriter};
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+use starknet_api::block::{BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::core::{GlobalRoot, Nonce};
+use starknet_api::state::ThinStateDiff;
+use starknet_api::{contract_address, felt, storage_key};
+use starknet_committer::block_committer::commit::{
+    calculate_global_root,
+    commit_block,
+    fetch_commitment_witnesses,
+};
+use starknet_committer::block_committer::input::{ConfigImpl, Input, StateDiff};
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia_storage::map_storage::MapStorage;
+use tracing::level_filters::LevelFilter;
+
+use crate::state_root_verifier::StateRootVerifier;
+use crate::StateSyncError;
+
+const N_RETAINED_BLOCKS: u64 = 2;
+
+fn state_diff(value: u64) -> ThinStateDiff {
+    ThinStateDiff {
+        storage_diffs: indexmap! {
+            contract_address!("0x100") => indexmap! { storage_key!("0x1") => felt!(value) },
+        },
+        nonces: indexmap! { contract_address!("0x100") => Nonce(felt!(value)) },
+        ..Default::default()
+    }
+}
+
+fn append_header(writer: &mut StorageWriter, block_number: BlockNumber, state_root: GlobalRoot) {
+    let header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number,
+            state_root,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    writer.begin_rw_txn().unwrap().append_header(block_number, &header).unwrap().commit().unwrap();
+}
+
+/// Returns the global roots after committing the given state diffs one after the other into tries
+/// in memory.
+async fn expected_roots(state_diffs: &[ThinStateDiff]) -> Vec<GlobalRoot> {
+    let mut storage = MapStorage::default();
+    let mut contracts_trie_root_hash = HashOutput::ROOT_OF_EMPTY_TREE;
+    let mut classes_trie_root_hash = HashOutput::ROOT_OF_EMPTY_TREE;
+    let mut roots = vec![];
+    for state_diff in state_diffs {
+        let state_diff = StateDiff::from(state_diff.clone());
+        let witnesses = fetch_commitment_witnesses(
+            &storage,
+            contracts_trie_root_hash,
+            classes_trie_root_hash,
+            &state_diff,
+        )
+        .unwrap();
+        let filled_forest = commit_block(Input {
+            storage: witnesses,
+            state_diff,
+            contracts_trie_root_hash,
+            classes_trie_root_hash,
+            config: ConfigImpl::new(false, LevelFilter::INFO),
+        })
+        .await
+        .unwrap();
+        filled_forest.write_to_storage(&mut storage);
+        contracts_trie_root_hash = filled_forest.get_contract_root_hash();
+        classes_trie_root_hash = filled_forest.get_compiled_class_root_hash();
+        roots.push(calculate_global_root(contracts_trie_root_hash, classes_trie_root_hash));
+    }
+    roots
+}
+
+/// Verifies the given block and stores its state diff along with its tries.
+async fn verify_and_store(
+    reader: &StorageReader,
+    writer: &mut StorageWriter,
+    block_number: BlockNumber,
+    state_diff: ThinStateDiff,
+) -> Result<(), StateSyncError> {
+    let state_tries_update =
+        StateRootVerifier::verify(reader, block_number, state_diff.clone()).await?;
+    let txn = writer.begin_rw_txn()?.append_state_diff(block_number, state_diff)?;
+    StateRootVerifier::new(N_RETAINED_BLOCKS)
+        .append_state_tries(txn, state_tries_update)?
+        .commit()?;
+    Ok(())
+}
+
+fn state_tries_marker(reader: &StorageReader) -> BlockNumber {
+    reader.begin_ro_txn().unwrap().get_state_tries_marker().unwrap()
+}
+
+#[tokio::test]
+async fn mismatching_state_root() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    append_header(&mut writer, BlockNumber(0), GlobalRoot::default());
+
+    let result = verify_and_store(&reader, &mut writer, BlockNumber(0), state_diff(1)).await;
+    assert_matches!(
+        result,
+        Err(StateSyncError::StateRootMismatch { block_number: BlockNumber(0), .. })
+    );
+    // The tries of the block aren't stored.
+    assert_eq!(state_tries_marker(&reader), BlockNumber(0));
+}
+
+#[tokio::test]
+async fn verify_on_top_of_the_stored_tries() {
+    let state_diffs = [state_diff(1), state_diff(2)];
+    let roots = expected_roots(&state_diffs).await;
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    append_header(&mut writer, BlockNumber(0), roots[0]);
+    append_header(&mut writer, BlockNumber(1), roots[1]);
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diffs[0].clone())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // The tries of block 0 are built from its stored state diff.
+    let state_tries_update =
+        StateRootVerifier::verify_stored_block(&reader, BlockNumber(0)).await.unwrap();
+    StateRootVerifier::new(N_RETAINED_BLOCKS)
+        .append_state_tries(writer.begin_rw_txn().unwrap(), state_tries_update)
+        .unwrap()
+        .commit()
+        .unwrap();
+    verify_and_store(&reader, &mut writer, BlockNumber(1), state_diffs[1].clone()).await.unwrap();
+    assert_eq!(state_tries_marker(&reader), BlockNumber(2));
+
+    // After a revert, the block is verified again on top of the tries of the block before it.
+    let (txn, _reverted_state_diff) =
+        writer.begin_rw_txn().unwrap().revert_state_diff(BlockNumber(1)).unwrap();
+    txn.commit().unwrap();
+    assert_matches!(
+        verify_and_store(&reader, &mut writer, BlockNumber(1), state_diff(3)).await,
+        Err(StateSyncError::StateRootMismatch { block_number: BlockNumber(1), .. })
+    );
+    verify_and_store(&reader, &mut writer, BlockNumber(1), state_diffs[1].clone()).await.unwrap();
+    assert_eq!(state_tries_marker(&reader), BlockNumber(2));
+}
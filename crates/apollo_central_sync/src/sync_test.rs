@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -21,13 +22,19 @@ use futures_util::StreamExt;
 use indexmap::IndexMap;
 use papyrus_common::pending_classes::{ApiContractClass, PendingClasses, PendingClassesTrait};
 use pretty_assertions::assert_eq;
-use starknet_api::block::{BlockHash, BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    CommitCertificate,
+};
 use starknet_api::core::{ClassHash, CompiledClassHash, Nonce};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::hash::StarkHash;
 use starknet_api::state::{SierraContractClass, StateDiff};
 use starknet_api::{contract_address, felt, storage_key};
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{watch, Mutex, RwLock};
 
 use crate::sources::base_layer::MockBaseLayerSourceTrait;
 use crate::sources::central::MockCentralSourceTrait;
@@ -36,6 +43,7 @@ use crate::{
     sort_state_diff,
     stream_new_base_layer_block,
     sync_pending_data,
+    DecidedCommitCertificates,
     GenericStateSync,
     StateSyncError,
     SyncConfig,
@@ -187,6 +195,7 @@ async fn store_base_layer_block_test() {
         config: SyncConfig::default(),
         shared_highest_block: Arc::new(RwLock::new(None)),
         pending_data: Arc::new(RwLock::new(PendingData::default())),
+        pending_data_updates: watch::channel(()).0,
         central_source: Arc::new(MockCentralSourceTrait::new()),
         pending_source: Arc::new(MockPendingSourceTrait::new()),
         pending_classes: Arc::new(RwLock::new(PendingClasses::default())),
@@ -195,6 +204,9 @@ async fn store_base_layer_block_test() {
         writer: Arc::new(Mutex::new(writer)),
         sequencer_pub_key: None,
         class_manager_client: None,
+        state_root_verifier: None,
+        checkpoint: None,
+        decided_commit_certificates: None,
     };
 
     // Trying to store a block without a header in the storage.
@@ -215,6 +227,55 @@ async fn store_base_layer_block_test() {
 }
 
 // Adds to the storage 'headers_num' headers.
+#[tokio::test]
+async fn invalid_commit_certificate_is_discarded() {
+    let ((reader, writer), _temp_dir) = get_test_storage();
+    // A certificate of another block under block 1, and leftover certificates around it.
+    let certificate = |block_number| CommitCertificate {
+        block_number: BlockNumber(block_number),
+        ..Default::default()
+    };
+    let decided_commit_certificates = DecidedCommitCertificates {
+        committee: vec![],
+        certificates: Arc::new(std::sync::Mutex::new(BTreeMap::from([
+            (BlockNumber(0), certificate(0)),
+            (BlockNumber(1), certificate(2)),
+            (BlockNumber(2), certificate(2)),
+        ]))),
+    };
+    let gen_state_sync = GenericStateSync {
+        config: SyncConfig::default(),
+        shared_highest_block: Arc::new(RwLock::new(None)),
+        pending_data: Arc::new(RwLock::new(PendingData::default())),
+        pending_data_updates: watch::channel(()).0,
+        central_source: Arc::new(MockCentralSourceTrait::new()),
+        pending_source: Arc::new(MockPendingSourceTrait::new()),
+        pending_classes: Arc::new(RwLock::new(PendingClasses::default())),
+        base_layer_source: None::<Arc<MockBaseLayerSourceTrait>>,
+        reader,
+        writer: Arc::new(Mutex::new(writer)),
+        sequencer_pub_key: None,
+        class_manager_client: None,
+        state_root_verifier: None,
+        checkpoint: None,
+        decided_commit_certificates: Some(decided_commit_certificates.clone()),
+    };
+
+    let header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(gen_state_sync.take_commit_certificate(&header), None);
+    // Only the certificates of the next blocks are kept.
+    assert_eq!(
+        *decided_commit_certificates.certificates.lock().unwrap(),
+        BTreeMap::from([(BlockNumber(2), certificate(2))])
+    );
+}
+
 fn add_headers(headers_num: u64, writer: &mut StorageWriter) {
     for i in 0..headers_num {
         let header = BlockHeader {
@@ -277,6 +338,7 @@ async fn test_pending_sync(
         Arc::new(mock_central_source),
         Arc::new(mock_pending_source),
         pending_data_lock.clone(),
+        watch::channel(()).0,
         pending_classes_lock.clone(),
         Duration::ZERO,
     )
@@ -103,6 +103,7 @@ impl From<ClassHashStorageConfig> for StorageConfig {
             },
             scope: value.scope,
             mmap_file_config: value.mmap_file_config,
+            index_transactions_by_sender: false,
         }
     }
 }
@@ -0,0 +1,166 @@
+//! Commit certificates: the proof that a block was decided by the consensus committee.
+//!
+//! When consensus decides, the quorum of precommits for the decided block is packed, together with
+//! the committee of the height, into a [`CommitCertificate`]. Nodes which learn of the block
+//! through sync can then check that it was actually finalized by the committee, without having
+//! taken part in consensus. Since the certificate embeds its committee, it's only trusted if that
+//! committee is the one the node knows for the height.
+
+use std::collections::{HashMap, HashSet};
+
+use apollo_protobuf::consensus::{Vote, VoteType, DEFAULT_VALIDATOR_ID};
+use starknet_api::block::{
+    BlockHeader,
+    BlockNumber,
+    CommitCertificate,
+    CommitteeMember,
+    PrecommitSignature,
+};
+use starknet_api::core::StateDiffCommitment;
+use starknet_api::crypto::utils::PublicKey;
+
+use crate::types::{ProposalCommitment, ValidatorId, VotingWeight};
+use crate::votes_threshold::BYZANTINE_QUORUM;
+
+#[cfg(test)]
+#[path = "commit_certificate_test.rs"]
+mod commit_certificate_test;
+
+/// The reasons a commit certificate can fail verification.
+#[derive(thiserror::Error, Clone, Debug, PartialEq)]
+pub enum CommitCertificateError {
+    #[error("The certificate is of block {certificate_block_number} instead of {block_number}.")]
+    WrongBlockNumber { certificate_block_number: BlockNumber, block_number: BlockNumber },
+    #[error(
+        "The certificate is of the proposal {certificate_commitment}, but the state diff \
+         commitment of the block is {block_commitment:?}."
+    )]
+    WrongProposalCommitment {
+        certificate_commitment: ProposalCommitment,
+        block_commitment: Option<StateDiffCommitment>,
+    },
+    #[error("The committee of the certificate isn't the committee of the height.")]
+    WrongCommittee,
+    #[error("Precommit from {voter}, which is not a member of the committee.")]
+    UnknownVoter { voter: ValidatorId },
+    #[error("More than one precommit from {voter}.")]
+    DuplicateVoter { voter: ValidatorId },
+    #[error("Invalid signature on the precommit of {voter}.")]
+    InvalidSignature { voter: ValidatorId },
+    #[error(
+        "The precommits have a voting weight of {weight} out of {total_weight}, which is not a \
+         quorum."
+    )]
+    NoQuorum { weight: VotingWeight, total_weight: VotingWeight },
+}
+
+/// Builds the certificate of a decision from its precommits, which must all be for `block` in the
+/// same height and round (as guaranteed by [`Decision`](crate::types::Decision)).
+///
+/// `committee` is the committee of the height, with the public key of each member.
+pub fn build_commit_certificate(
+    block: ProposalCommitment,
+    precommits: &[Vote],
+    committee: Vec<CommitteeMember>,
+) -> CommitCertificate {
+    let first = precommits.first().expect("A decision must have at least one precommit.");
+    CommitCertificate {
+        block_number: BlockNumber(first.height),
+        round: first.round,
+        block_hash: block,
+        committee,
+        precommits: precommits
+            .iter()
+            .map(|vote| PrecommitSignature { voter: vote.voter, signature: vote.signature.clone() })
+            .collect(),
+    }
+}
+
+/// Reconstructs the precommit votes which the certificate's signatures were made on.
+pub fn certificate_precommits(certificate: &CommitCertificate) -> Vec<Vote> {
+    certificate
+        .precommits
+        .iter()
+        .map(|precommit| Vote {
+            vote_type: VoteType::Precommit,
+            height: certificate.block_number.0,
+            round: certificate.round,
+            block_hash: Some(certificate.block_hash),
+            voter: precommit.voter,
+            signature: precommit.signature.clone(),
+        })
+        .collect()
+}
+
+/// The committee of the configured validators, which consensus uses when the committee isn't read
+/// from the staking contract: their IDs are consecutive from [`DEFAULT_VALIDATOR_ID`], in the order
+/// of their public keys, and they all have the same weight.
+pub fn configured_committee(public_keys: &[PublicKey]) -> Vec<CommitteeMember> {
+    (DEFAULT_VALIDATOR_ID..)
+        .zip(public_keys)
+        .map(|(id, public_key)| CommitteeMember {
+            address: ValidatorId::from(id),
+            weight: 1,
+            public_key: *public_key,
+        })
+        .collect()
+}
+
+/// Verifies that the certificate proves that the given block was decided: it must be of the block's
+/// number and of its state diff commitment (the proposal commitment consensus votes on), embed
+/// `committee`, and hold a quorum of validly signed precommits from it.
+///
+/// `committee` is the committee of the block's height as known locally, rather than taken from the
+/// certificate, so that a peer can't make up a committee of its own.
+///
+/// `verify_signature` checks the signature of a precommit against the voter's public key. The
+/// signed message depends on the chain (see [`vote_signing_payload`]), so the caller provides it.
+///
+/// [`vote_signing_payload`]: crate::types::vote_signing_payload
+pub fn verify_commit_certificate(
+    certificate: &CommitCertificate,
+    header: &BlockHeader,
+    committee: &[CommitteeMember],
+    verify_signature: impl Fn(&Vote, &PublicKey) -> bool,
+) -> Result<(), CommitCertificateError> {
+    let block_number = header.block_header_without_hash.block_number;
+    if certificate.block_number != block_number {
+        return Err(CommitCertificateError::WrongBlockNumber {
+            certificate_block_number: certificate.block_number,
+            block_number,
+        });
+    }
+    let block_commitment = header.state_diff_commitment;
+    if block_commitment.map(|commitment| commitment.0 .0) != Some(certificate.block_hash.0) {
+        return Err(CommitCertificateError::WrongProposalCommitment {
+            certificate_commitment: certificate.block_hash,
+            block_commitment,
+        });
+    }
+    if certificate.committee != committee {
+        return Err(CommitCertificateError::WrongCommittee);
+    }
+
+    let total_weight = committee.iter().map(|member| member.weight).sum();
+    let committee: HashMap<ValidatorId, &CommitteeMember> =
+        committee.iter().map(|member| (member.address, member)).collect();
+
+    let mut voters = HashSet::new();
+    let mut weight: VotingWeight = 0;
+    for vote in certificate_precommits(certificate) {
+        let voter = vote.voter;
+        let member = committee.get(&voter).ok_or(CommitCertificateError::UnknownVoter { voter })?;
+        if !voters.insert(voter) {
+            return Err(CommitCertificateError::DuplicateVoter { voter });
+        }
+        if !verify_signature(&vote, &member.public_key) {
+            return Err(CommitCertificateError::InvalidSignature { voter });
+        }
+        weight += member.weight;
+    }
+
+    if !BYZANTINE_QUORUM.is_met(weight, total_weight) {
+        return Err(CommitCertificateError::NoQuorum { weight, total_weight });
+    }
+    Ok(())
+}
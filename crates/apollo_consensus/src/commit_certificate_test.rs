@@ -0,0 +1,175 @@
+use apollo_protobuf::consensus::Vote;
+use starknet_api::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    CommitCertificate,
+    CommitteeMember,
+};
+use starknet_api::core::StateDiffCommitment;
+use starknet_api::crypto::utils::PublicKey;
+use starknet_api::hash::PoseidonHash;
+use starknet_types_core::felt::Felt;
+
+use crate::commit_certificate::{
+    build_commit_certificate,
+    certificate_precommits,
+    configured_committee,
+    verify_commit_certificate,
+    CommitCertificateError,
+};
+use crate::test_utils::{precommit, sign_vote};
+use crate::types::ValidatorId;
+
+const HEIGHT: u64 = 1;
+const ROUND: u32 = 2;
+const BLOCK: Felt = Felt::ONE;
+
+fn voter(id: u8) -> ValidatorId {
+    ValidatorId::from(100_u64 + u64::from(id))
+}
+
+// The test signature scheme signs with the voter's address as its key.
+fn committee(weights: &[u128]) -> Vec<CommitteeMember> {
+    (0..)
+        .zip(weights)
+        .map(|(id, weight)| CommitteeMember {
+            address: voter(id),
+            weight: *weight,
+            public_key: PublicKey(*voter(id).0.key()),
+        })
+        .collect()
+}
+
+fn verify_signature(vote: &Vote, public_key: &PublicKey) -> bool {
+    let signature = sign_vote(vote.clone()).signature;
+    signature.0[0] == public_key.0 && vote.signature == signature
+}
+
+fn precommits(voters: &[u8]) -> Vec<Vote> {
+    voters.iter().map(|id| precommit(Some(BLOCK), HEIGHT, ROUND, voter(*id))).collect()
+}
+
+fn certificate(voters: &[u8], weights: &[u128]) -> CommitCertificate {
+    build_commit_certificate(BlockHash(BLOCK), &precommits(voters), committee(weights))
+}
+
+// The header of the block the precommits are for.
+fn header() -> BlockHeader {
+    BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(HEIGHT),
+            ..Default::default()
+        },
+        state_diff_commitment: Some(StateDiffCommitment(PoseidonHash(BLOCK))),
+        ..Default::default()
+    }
+}
+
+fn verify(certificate: &CommitCertificate, weights: &[u128]) -> Result<(), CommitCertificateError> {
+    verify_commit_certificate(certificate, &header(), &committee(weights), verify_signature)
+}
+
+#[test]
+fn certificate_holds_the_decision_precommits() {
+    let certificate = certificate(&[0, 1, 2], &[1, 1, 1, 1]);
+    assert_eq!(certificate_precommits(&certificate), precommits(&[0, 1, 2]));
+}
+
+#[test]
+fn verify_quorum() {
+    assert_eq!(verify(&certificate(&[0, 1, 2], &[1, 1, 1, 1]), &[1, 1, 1, 1]), Ok(()));
+    // A single heavy validator is a quorum on its own.
+    assert_eq!(verify(&certificate(&[3], &[1, 1, 1, 7]), &[1, 1, 1, 7]), Ok(()));
+}
+
+#[test]
+fn verify_rejects_insufficient_weight() {
+    assert_eq!(
+        verify(&certificate(&[0, 1], &[1, 1, 1, 1]), &[1, 1, 1, 1]),
+        Err(CommitCertificateError::NoQuorum { weight: 2, total_weight: 4 })
+    );
+}
+
+#[test]
+fn verify_rejects_duplicate_voter() {
+    assert_eq!(
+        verify(&certificate(&[0, 1, 1], &[1, 1, 1]), &[1, 1, 1]),
+        Err(CommitCertificateError::DuplicateVoter { voter: voter(1) })
+    );
+}
+
+#[test]
+fn verify_rejects_unknown_voter() {
+    assert_eq!(
+        verify(&certificate(&[0, 1, 5], &[1, 1, 1]), &[1, 1, 1]),
+        Err(CommitCertificateError::UnknownVoter { voter: voter(5) })
+    );
+}
+
+#[test]
+fn verify_rejects_invalid_signature() {
+    let mut certificate = certificate(&[0, 1, 2], &[1, 1, 1]);
+    // A signature on a different round.
+    certificate.round += 1;
+    assert_eq!(
+        verify(&certificate, &[1, 1, 1]),
+        Err(CommitCertificateError::InvalidSignature { voter: voter(0) })
+    );
+}
+
+#[test]
+fn verify_rejects_certificate_of_another_block() {
+    let certificate = certificate(&[0, 1, 2], &[1, 1, 1]);
+
+    let mut next_block = header();
+    next_block.block_header_without_hash.block_number = BlockNumber(HEIGHT + 1);
+    assert_eq!(
+        verify_commit_certificate(
+            &certificate,
+            &next_block,
+            &committee(&[1, 1, 1]),
+            verify_signature
+        ),
+        Err(CommitCertificateError::WrongBlockNumber {
+            certificate_block_number: BlockNumber(HEIGHT),
+            block_number: BlockNumber(HEIGHT + 1),
+        })
+    );
+
+    let mut other_proposal = header();
+    let block_commitment = Some(StateDiffCommitment(PoseidonHash(Felt::TWO)));
+    other_proposal.state_diff_commitment = block_commitment;
+    assert_eq!(
+        verify_commit_certificate(
+            &certificate,
+            &other_proposal,
+            &committee(&[1, 1, 1]),
+            verify_signature
+        ),
+        Err(CommitCertificateError::WrongProposalCommitment {
+            certificate_commitment: BlockHash(BLOCK),
+            block_commitment,
+        })
+    );
+}
+
+#[test]
+fn verify_rejects_unknown_committee() {
+    // A committee made up by the certificate, in which its precommits are a quorum.
+    let certificate = certificate(&[0], &[7, 1, 1]);
+    assert_eq!(verify(&certificate, &[1, 1, 1]), Err(CommitCertificateError::WrongCommittee));
+}
+
+#[test]
+fn configured_committee_matches_the_validator_ids() {
+    let public_keys = [PublicKey(Felt::ONE), PublicKey(Felt::TWO)];
+    assert_eq!(
+        configured_committee(&public_keys),
+        vec![
+            CommitteeMember { address: voter(0), weight: 1, public_key: public_keys[0] },
+            CommitteeMember { address: voter(1), weight: 1, public_key: public_keys[1] },
+        ]
+    );
+}
@@ -3,16 +3,25 @@
 //! such as the validator ID, the network topic of the consensus, and the starting block height.
 
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use apollo_config::converters::{
     deserialize_float_seconds_to_duration,
     deserialize_seconds_to_duration,
 };
-use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
+use apollo_config::dumping::{
+    prepend_sub_config_name,
+    ser_optional_param,
+    ser_param,
+    SerializeConfig,
+};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use apollo_protobuf::consensus::DEFAULT_VALIDATOR_ID;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use starknet_api::crypto::utils::PublicKey;
+use starknet_types_core::felt::Felt;
 use validator::Validate;
 
 use crate::types::ValidatorId;
@@ -36,6 +45,9 @@ pub struct ConsensusConfig {
     pub future_round_limit: u32,
     /// How many rounds should we cache for future heights.
     pub future_height_round_limit: u32,
+    /// The directory of the write-ahead log of the votes the node sends. If None, the node may
+    /// vote differently in a round it already voted in if it restarts in the middle of a height.
+    pub write_ahead_log_dir: Option<PathBuf>,
 }
 
 impl SerializeConfig for ConsensusConfig {
@@ -78,6 +90,14 @@ impl SerializeConfig for ConsensusConfig {
                 ParamPrivacyInput::Public,
             ),
         ]);
+        config.extend(ser_optional_param(
+            &self.write_ahead_log_dir,
+            PathBuf::from("./data/consensus_wal"),
+            "write_ahead_log_dir",
+            "The directory of the write-ahead log of the votes the node sends, which is replayed \
+             if the node restarts in the middle of a height so it won't vote differently.",
+            ParamPrivacyInput::Public,
+        ));
         config.extend(prepend_sub_config_name(self.timeouts.dump(), "timeouts"));
         config
     }
@@ -93,6 +113,7 @@ impl Default for ConsensusConfig {
             future_height_limit: 10,
             future_round_limit: 10,
             future_height_round_limit: 1,
+            write_ahead_log_dir: None,
         }
     }
 }
@@ -179,3 +200,29 @@ impl SerializeConfig for StreamHandlerConfig {
         ])
     }
 }
+
+/// Serializes the public keys of the validators into a config value: their hex representations,
+/// separated by spaces.
+pub fn serialize_public_keys(public_keys: &[PublicKey]) -> String {
+    public_keys
+        .iter()
+        .map(|public_key| format!("{:#x}", public_key.0))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Deserializes the public keys of the validators from a config value of the form written by
+/// [`serialize_public_keys`].
+pub fn deserialize_public_keys<'de, D>(de: D) -> Result<Vec<PublicKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: String = Deserialize::deserialize(de)?;
+    raw.split_whitespace()
+        .map(|public_key| {
+            Felt::from_hex(public_key)
+                .map(PublicKey)
+                .map_err(|e| D::Error::custom(format!("Invalid public key '{public_key}': {e}")))
+        })
+        .collect()
+}
@@ -24,12 +24,15 @@
 //! 1. The outbound messages are not sent as responses to the inbound messages.
 //! 2. It generates and runs its own events (e.g. timeouts).
 
+#[allow(missing_docs)]
+pub mod commit_certificate;
 pub mod config;
 #[allow(missing_docs)]
 pub mod types;
 pub use manager::{run_consensus, RunConsensusArguments};
 #[allow(missing_docs)]
 pub mod metrics;
+pub mod proposer_rotation;
 #[allow(missing_docs)]
 pub mod simulation_network_receiver;
 pub mod stream_handler;
@@ -41,6 +44,9 @@ mod single_height_consensus;
 mod state_machine;
 #[allow(missing_docs)]
 pub mod votes_threshold;
+mod write_ahead_log;
 
+#[cfg(test)]
+pub(crate) mod simulation;
 #[cfg(test)]
 pub(crate) mod test_utils;
@@ -10,6 +10,7 @@
 mod manager_test;
 
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use apollo_network::network_manager::BroadcastTopicClientTrait;
@@ -23,7 +24,7 @@ use futures::{FutureExt, StreamExt};
 use metrics::counter;
 use papyrus_common::metrics::{PAPYRUS_CONSENSUS_HEIGHT, PAPYRUS_CONSENSUS_SYNC_COUNT};
 use starknet_api::block::BlockNumber;
-use tracing::{debug, error, info, instrument, trace};
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::config::TimeoutsConfig;
 use crate::metrics::{
@@ -32,12 +33,14 @@ use crate::metrics::{
     CONSENSUS_CACHED_VOTES,
     CONSENSUS_DECISIONS_REACHED_BY_CONSENSUS,
     CONSENSUS_DECISIONS_REACHED_BY_SYNC,
+    CONSENSUS_INVALID_VOTE_SIGNATURES,
     CONSENSUS_MAX_CACHED_BLOCK_NUMBER,
     CONSENSUS_PROPOSALS_RECEIVED,
 };
 use crate::single_height_consensus::{ShcReturn, SingleHeightConsensus};
 use crate::types::{BroadcastVoteChannel, ConsensusContext, ConsensusError, Decision, ValidatorId};
 use crate::votes_threshold::QuorumType;
+use crate::write_ahead_log::{WalEntry, WriteAheadLog};
 
 /// Arguments for running consensus.
 #[derive(Clone, Debug)]
@@ -56,6 +59,9 @@ pub struct RunConsensusArguments {
     pub sync_retry_interval: Duration,
     /// Set to Byzantine by default. Using Honest means we trust all validators. Use with caution!
     pub quorum_type: QuorumType,
+    /// The directory of the write-ahead log of the votes this node sends. If None, the node may
+    /// vote differently in a round it already voted in if it restarts in the middle of a height.
+    pub write_ahead_log_dir: Option<PathBuf>,
 }
 
 /// Run consensus indefinitely.
@@ -93,6 +99,7 @@ where
         run_consensus_args.sync_retry_interval,
         run_consensus_args.quorum_type,
         run_consensus_args.timeouts,
+        run_consensus_args.write_ahead_log_dir,
     );
     #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
     loop {
@@ -153,6 +160,7 @@ struct MultiHeightManager<ContextT: ConsensusContext> {
     // Mapping: { Height : { Round : (Init, Receiver)}}
     cached_proposals: BTreeMap<u64, BTreeMap<u32, ProposalReceiverTuple<ContextT::ProposalPart>>>,
     timeouts: TimeoutsConfig,
+    write_ahead_log_dir: Option<PathBuf>,
 }
 
 impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
@@ -162,6 +170,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         sync_retry_interval: Duration,
         quorum_type: QuorumType,
         timeouts: TimeoutsConfig,
+        write_ahead_log_dir: Option<PathBuf>,
     ) -> Self {
         Self {
             validator_id,
@@ -170,6 +179,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
             future_votes: BTreeMap::new(),
             cached_proposals: BTreeMap::new(),
             timeouts,
+            write_ahead_log_dir,
         }
     }
 
@@ -247,13 +257,23 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         }
 
         let validators = context.validators(height).await;
-        let is_observer = must_observer || !validators.contains(&self.validator_id);
+        let is_observer =
+            must_observer || !validators.iter().any(|validator| validator.id == self.validator_id);
         info!(
             "START_HEIGHT: running consensus for height {:?}. is_observer: {}, validators: {:?}",
             height, is_observer, validators,
         );
         CONSENSUS_BLOCK_NUMBER.set_lossy(height.0);
 
+        // Observers don't vote, so they have nothing to log.
+        let (write_ahead_log, wal_entries) = match &self.write_ahead_log_dir {
+            Some(write_ahead_log_dir) if !is_observer => {
+                let (write_ahead_log, wal_entries) =
+                    WriteAheadLog::open(write_ahead_log_dir, height)?;
+                (Some(write_ahead_log), wal_entries)
+            }
+            _ => (None, Vec::new()),
+        };
         let mut shc = SingleHeightConsensus::new(
             height,
             is_observer,
@@ -261,10 +281,11 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
             validators,
             self.quorum_type,
             self.timeouts.clone(),
+            write_ahead_log,
         );
         let mut shc_events = FuturesUnordered::new();
 
-        match self.start_height(context, height, &mut shc).await? {
+        match self.start_height(context, height, &mut shc, wal_entries).await? {
             ShcReturn::Decision(decision) => {
                 return Ok(RunHeightRes::Decision(decision));
             }
@@ -318,9 +339,16 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         context: &mut ContextT,
         height: BlockNumber,
         shc: &mut SingleHeightConsensus,
+        wal_entries: Vec<WalEntry>,
     ) -> Result<ShcReturn, ConsensusError> {
         CONSENSUS_CACHED_VOTES.set_lossy(self.future_votes.entry(height.0).or_default().len());
-        let mut tasks = match shc.start(context).await? {
+        let start_return = if wal_entries.is_empty() {
+            shc.start(context).await?
+        } else {
+            // The node restarted in the middle of this height.
+            shc.resume(context, wal_entries).await?
+        };
+        let mut tasks = match start_return {
             decision @ ShcReturn::Decision(_) => {
                 // Start should generate either TimeoutProposal (validator) or GetProposal
                 // (proposer). We do not enforce this since the Manager is
@@ -343,6 +371,12 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         let cached_votes = self.get_current_height_votes(height);
         trace!("Cached votes for height {}: {:?}", height, cached_votes);
         for msg in cached_votes {
+            // The sender of a cached vote is no longer known, so it can't be reported.
+            if !context.verify_vote_signature(&msg) {
+                warn!("Dropping cached vote with an invalid signature: {:?}", msg);
+                CONSENSUS_INVALID_VOTE_SIGNATURES.increment(1);
+                continue;
+            }
             match shc.handle_vote(context, msg).await? {
                 decision @ ShcReturn::Decision(_) => return Ok(decision),
                 ShcReturn::Tasks(new_tasks) => tasks.extend(new_tasks),
@@ -421,7 +455,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         vote: Option<(Result<Vote, ProtobufConversionError>, BroadcastedMessageMetadata)>,
         broadcast_channels: &mut BroadcastVoteChannel,
     ) -> Result<ShcReturn, ConsensusError> {
-        let message = match vote {
+        let (message, metadata) = match vote {
             None => Err(ConsensusError::InternalNetworkError(
                 "NetworkReceiver should never be closed".to_string(),
             )),
@@ -435,7 +469,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
                 {
                     error!("Unable to send continue_propagation. {:?}", metadata);
                 }
-                Ok(msg)
+                Ok((msg, metadata))
             }
             Some((Err(e), metadata)) => {
                 // Failed to parse consensus message
@@ -465,7 +499,24 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
                 Ok(ShcReturn::Tasks(Vec::new()))
             }
             std::cmp::Ordering::Equal => match shc {
-                Some(shc) => shc.handle_vote(context, message).await,
+                Some(shc) => {
+                    if !context.verify_vote_signature(&message) {
+                        warn!("Dropping vote with an invalid signature: {:?}", message);
+                        CONSENSUS_INVALID_VOTE_SIGNATURES.increment(1);
+                        if broadcast_channels
+                            .broadcast_topic_client
+                            .report_peer(metadata.clone())
+                            .now_or_never()
+                            .is_none()
+                        {
+                            error!("Unable to send report_peer. {:?}", metadata)
+                        }
+                        return Ok(ShcReturn::Tasks(Vec::new()));
+                    }
+                    // The peer which propagated a conflicting vote isn't necessarily its voter, so
+                    // it's not reported. The context reports the voter.
+                    shc.handle_vote(context, message).await
+                }
                 None => {
                     trace!("Drop message from just completed height. {:?}", message);
                     Ok(ShcReturn::Tasks(Vec::new()))
@@ -10,15 +10,24 @@ use apollo_network_types::network_types::BroadcastedMessageMetadata;
 use apollo_protobuf::consensus::{Vote, DEFAULT_VALIDATOR_ID};
 use apollo_test_utils::{get_rng, GetTestInstance};
 use futures::channel::{mpsc, oneshot};
-use futures::{FutureExt, SinkExt};
+use futures::{FutureExt, SinkExt, StreamExt};
 use lazy_static::lazy_static;
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::crypto::utils::RawSignature;
 use starknet_types_core::felt::Felt;
 
 use super::{run_consensus, MultiHeightManager, RunHeightRes};
 use crate::config::TimeoutsConfig;
-use crate::test_utils::{precommit, prevote, proposal_init, MockTestContext, TestProposalPart};
-use crate::types::ValidatorId;
+use crate::test_utils::{
+    expect_vote_signatures,
+    precommit,
+    prevote,
+    proposal_init,
+    validators,
+    MockTestContext,
+    TestProposalPart,
+};
+use crate::types::{EquivocationEvidence, ValidatorId};
 use crate::votes_threshold::QuorumType;
 use crate::RunConsensusArguments;
 
@@ -99,10 +108,11 @@ async fn manager_multiple_heights_unordered() {
     send(&mut sender, precommit(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
 
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
     // Run the manager for height 1.
     context.expect_try_sync().returning(|_| false);
     expect_validate_proposal(&mut context, Felt::ONE, 1);
-    context.expect_validators().returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID]);
+    context.expect_validators().returning(move |_| validators(&[*PROPOSER_ID, *VALIDATOR_ID]));
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_set_height_and_round().returning(move |_, _| ());
     context.expect_broadcast().returning(move |_| Ok(()));
@@ -112,6 +122,7 @@ async fn manager_multiple_heights_unordered() {
         SYNC_RETRY_INTERVAL,
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
     let mut subscriber_channels = subscriber_channels.into();
     let decision = manager
@@ -145,12 +156,13 @@ async fn manager_multiple_heights_unordered() {
 async fn run_consensus_sync() {
     // Set expectations.
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
     let (decision_tx, decision_rx) = oneshot::channel();
 
     let (mut proposal_receiver_sender, proposal_receiver_receiver) = mpsc::channel(CHANNEL_SIZE);
 
     expect_validate_proposal(&mut context, Felt::TWO, 1);
-    context.expect_validators().returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID]);
+    context.expect_validators().returning(move |_| validators(&[*PROPOSER_ID, *VALIDATOR_ID]));
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_set_height_and_round().returning(move |_, _| ());
     context.expect_broadcast().returning(move |_| Ok(()));
@@ -187,6 +199,7 @@ async fn run_consensus_sync() {
         timeouts: TIMEOUTS.clone(),
         sync_retry_interval: SYNC_RETRY_INTERVAL,
         quorum_type: QuorumType::Byzantine,
+        write_ahead_log_dir: None,
     };
     // Start at height 1.
     tokio::spawn(async move {
@@ -223,11 +236,12 @@ async fn test_timeouts() {
     send(&mut sender, precommit(None, 1, 0, *VALIDATOR_ID_3)).await;
 
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
     context.expect_set_height_and_round().returning(move |_, _| ());
     expect_validate_proposal(&mut context, Felt::ONE, 2);
-    context
-        .expect_validators()
-        .returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2, *VALIDATOR_ID_3]);
+    context.expect_validators().returning(move |_| {
+        validators(&[*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2, *VALIDATOR_ID_3])
+    });
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_try_sync().returning(|_| false);
 
@@ -248,6 +262,7 @@ async fn test_timeouts() {
         SYNC_RETRY_INTERVAL,
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
     let manager_handle = tokio::spawn(async move {
         let decision = manager
@@ -286,6 +301,7 @@ async fn timely_message_handling() {
     // TODO(matan): Make run_height more generic so don't need mock network?
     // Check that, even when sync is immediately ready, consensus still handles queued messages.
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
     context.expect_try_sync().returning(|_| true);
 
     // Send messages
@@ -309,6 +325,7 @@ async fn timely_message_handling() {
         SYNC_RETRY_INTERVAL,
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
     let res = manager
         .run_height(
@@ -326,3 +343,228 @@ async fn timely_message_handling() {
     proposal_receiver_sender.try_send(mpsc::channel(1).1).unwrap();
     assert!(vote_sender.send((vote.clone(), metadata.clone())).now_or_never().is_some());
 }
+
+#[tokio::test]
+async fn restart_in_the_middle_of_a_height() {
+    let write_ahead_log_dir = tempfile::tempdir().unwrap();
+    // A long proposal timeout, so that the node won't prevote nil due to the timeout.
+    let timeouts = TimeoutsConfig { proposal_timeout: Duration::from_secs(10), ..TIMEOUTS.clone() };
+
+    let run_height = |block_hash: Felt| {
+        let TestSubscriberChannels { mock_network, subscriber_channels } =
+            mock_register_broadcast_topic().unwrap();
+        let (proposal_receiver_sender, mut proposal_receiver_receiver) =
+            mpsc::channel(CHANNEL_SIZE);
+        let (broadcast_sender, broadcast_receiver) = mpsc::unbounded();
+
+        let mut context = MockTestContext::new();
+        expect_vote_signatures(&mut context);
+        context.expect_try_sync().returning(|_| false);
+        expect_validate_proposal(&mut context, block_hash, 1);
+        context.expect_validators().returning(move |_| {
+            validators(&[*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2, *VALIDATOR_ID_3])
+        });
+        context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+        context.expect_set_height_and_round().returning(move |_, _| ());
+        context.expect_broadcast().returning(move |vote| {
+            broadcast_sender.unbounded_send(vote).unwrap();
+            Ok(())
+        });
+
+        let mut manager = MultiHeightManager::new(
+            *VALIDATOR_ID,
+            SYNC_RETRY_INTERVAL,
+            QuorumType::Byzantine,
+            timeouts.clone(),
+            Some(write_ahead_log_dir.path().to_path_buf()),
+        );
+        let manager_handle = tokio::spawn(async move {
+            manager
+                .run_height(
+                    &mut context,
+                    BlockNumber(1),
+                    false,
+                    &mut subscriber_channels.into(),
+                    &mut proposal_receiver_receiver,
+                )
+                .await
+        });
+        (
+            manager_handle,
+            mock_network.broadcasted_messages_sender,
+            proposal_receiver_sender,
+            broadcast_receiver,
+        )
+    };
+
+    // The node prevotes and precommits in favor of the block, locking on it.
+    let (manager_handle, mut sender, mut proposal_receiver_sender, mut broadcast_receiver) =
+        run_height(Felt::ONE);
+    send_proposal(
+        &mut proposal_receiver_sender,
+        vec![TestProposalPart::Init(proposal_init(1, 0, *PROPOSER_ID))],
+    )
+    .await;
+    send(&mut sender, prevote(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
+    send(&mut sender, prevote(Some(Felt::ONE), 1, 0, *VALIDATOR_ID_2)).await;
+    while broadcast_receiver.next().await.unwrap()
+        != precommit(Some(Felt::ONE), 1, 0, *VALIDATOR_ID)
+    {}
+    // Simulate a crash.
+    manager_handle.abort();
+    assert!(manager_handle.await.unwrap_err().is_cancelled());
+
+    // After the restart the node rebroadcasts its votes instead of voting again.
+    let (manager_handle, mut sender, mut proposal_receiver_sender, mut broadcast_receiver) =
+        run_height(Felt::TWO);
+    assert_eq!(
+        broadcast_receiver.next().await.unwrap(),
+        prevote(Some(Felt::ONE), 1, 0, *VALIDATOR_ID)
+    );
+    assert_eq!(
+        broadcast_receiver.next().await.unwrap(),
+        precommit(Some(Felt::ONE), 1, 0, *VALIDATOR_ID)
+    );
+
+    // Move to the next round, in which a different block is proposed.
+    send(&mut sender, precommit(None, 1, 0, *PROPOSER_ID)).await;
+    send(&mut sender, precommit(None, 1, 0, *VALIDATOR_ID_2)).await;
+    send_proposal(
+        &mut proposal_receiver_sender,
+        vec![TestProposalPart::Init(proposal_init(1, 1, *PROPOSER_ID))],
+    )
+    .await;
+    // The node is still locked on the block it precommitted to before the restart.
+    let vote = loop {
+        let vote = broadcast_receiver.next().await.unwrap();
+        if vote.round == 1 {
+            break vote;
+        }
+    };
+    assert_eq!(vote, prevote(None, 1, 1, *VALIDATOR_ID));
+    manager_handle.abort();
+}
+
+#[tokio::test]
+async fn report_equivocation_without_reporting_the_relaying_peer() {
+    let TestSubscriberChannels { mut mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().unwrap();
+    let mut sender = mock_network.broadcasted_messages_sender;
+
+    let (mut proposal_receiver_sender, mut proposal_receiver_receiver) =
+        mpsc::channel(CHANNEL_SIZE);
+
+    send_proposal(
+        &mut proposal_receiver_sender,
+        vec![TestProposalPart::Init(proposal_init(1, 0, *PROPOSER_ID))],
+    )
+    .await;
+    send(&mut sender, prevote(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
+    // The proposer equivocates.
+    send(&mut sender, prevote(Some(Felt::TWO), 1, 0, *PROPOSER_ID)).await;
+    send(&mut sender, precommit(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
+
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+    context.expect_try_sync().returning(|_| false);
+    expect_validate_proposal(&mut context, Felt::ONE, 1);
+    context.expect_validators().returning(move |_| validators(&[*PROPOSER_ID, *VALIDATOR_ID]));
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().returning(move |_| Ok(()));
+    context
+        .expect_report_equivocation()
+        .times(1)
+        .withf(move |evidence| {
+            evidence
+                == &EquivocationEvidence::Vote(
+                    prevote(Some(Felt::ONE), 1, 0, *PROPOSER_ID),
+                    prevote(Some(Felt::TWO), 1, 0, *PROPOSER_ID),
+                )
+        })
+        .returning(move |_| ());
+
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        SYNC_RETRY_INTERVAL,
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        None,
+    );
+    let decision = manager
+        .run_height(
+            &mut context,
+            BlockNumber(1),
+            false,
+            &mut subscriber_channels.into(),
+            &mut proposal_receiver_receiver,
+        )
+        .await
+        .unwrap();
+    assert_decision(decision, Felt::ONE);
+
+    // The peer that propagated the conflicting vote isn't necessarily its voter, so it's not
+    // reported.
+    assert_eq!(mock_network.reported_messages_receiver.next().now_or_never().flatten(), None);
+}
+
+#[tokio::test]
+async fn drop_and_report_votes_with_invalid_signature() {
+    let TestSubscriberChannels { mut mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().unwrap();
+    let mut sender = mock_network.broadcasted_messages_sender;
+
+    let (mut proposal_receiver_sender, mut proposal_receiver_receiver) =
+        mpsc::channel(CHANNEL_SIZE);
+
+    send_proposal(
+        &mut proposal_receiver_sender,
+        vec![TestProposalPart::Init(proposal_init(1, 0, *PROPOSER_ID))],
+    )
+    .await;
+    // A forged vote, which would conflict with the proposer's actual vote if it was accepted.
+    let forged_vote = Vote {
+        signature: RawSignature(vec![Felt::ONE]),
+        ..prevote(Some(Felt::TWO), 1, 0, *PROPOSER_ID)
+    };
+    let forged_vote_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
+    sender.send((forged_vote, forged_vote_metadata.clone())).await.unwrap();
+    send(&mut sender, prevote(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
+    send(&mut sender, precommit(Some(Felt::ONE), 1, 0, *PROPOSER_ID)).await;
+
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+    context.expect_try_sync().returning(|_| false);
+    expect_validate_proposal(&mut context, Felt::ONE, 1);
+    context.expect_validators().returning(move |_| validators(&[*PROPOSER_ID, *VALIDATOR_ID]));
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().returning(move |_| Ok(()));
+    context.expect_report_equivocation().never();
+
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        SYNC_RETRY_INTERVAL,
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        None,
+    );
+    let decision = manager
+        .run_height(
+            &mut context,
+            BlockNumber(1),
+            false,
+            &mut subscriber_channels.into(),
+            &mut proposal_receiver_receiver,
+        )
+        .await
+        .unwrap();
+    assert_decision(decision, Felt::ONE);
+
+    // Only the peer that sent the forged vote is reported.
+    assert_eq!(
+        mock_network.reported_messages_receiver.next().await.unwrap(),
+        forged_vote_metadata.originator_id.private_get_peer_id()
+    );
+    assert_eq!(mock_network.reported_messages_receiver.next().now_or_never().flatten(), None);
+}
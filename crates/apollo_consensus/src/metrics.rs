@@ -27,6 +27,8 @@ define_metrics!(
         // TODO(Matan): remove this metric.
         MetricCounter { CONSENSUS_ROUND_ABOVE_ZERO, "consensus_round_above_zero", "The number of times the consensus round has increased above zero", init=0 },
         MetricCounter { CONSENSUS_CONFLICTING_VOTES, "consensus_conflicting_votes", "The number of times consensus has received conflicting votes", init=0 },
+        MetricCounter { CONSENSUS_CONFLICTING_PROPOSALS, "consensus_conflicting_proposals", "The number of times consensus has received conflicting proposals", init=0 },
+        MetricCounter { CONSENSUS_INVALID_VOTE_SIGNATURES, "consensus_invalid_vote_signatures", "The number of votes dropped since their signature failed verification", init=0 },
         LabeledMetricCounter { CONSENSUS_TIMEOUTS, "consensus_timeouts", "The number of times consensus has timed out", init=0, labels = CONSENSUS_TIMEOUT_LABELS },
     },
 );
@@ -69,5 +71,7 @@ pub(crate) fn register_metrics() {
     CONSENSUS_OUTBOUND_STREAM_FINISHED.register();
     CONSENSUS_ROUND_ABOVE_ZERO.register();
     CONSENSUS_CONFLICTING_VOTES.register();
+    CONSENSUS_CONFLICTING_PROPOSALS.register();
+    CONSENSUS_INVALID_VOTE_SIGNATURES.register();
     CONSENSUS_TIMEOUTS.register();
 }
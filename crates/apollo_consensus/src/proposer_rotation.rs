@@ -0,0 +1,70 @@
+//! Stake-weighted selection of the proposer of each round.
+//!
+//! The proposers are chosen by a weighted round robin with priority accumulation, as in Tendermint:
+//! in each slot the priority of every validator grows by its voting weight, the validator with the
+//! highest priority proposes, and its priority is then reduced by the total weight. This way each
+//! validator proposes in proportion to its weight, and its turns are spread evenly over the slots
+//! rather than grouped together.
+//!
+//! Since all nodes must agree on the proposer of a round without sharing any state, the rotation is
+//! a fixed schedule computed from the validators, which is indexed by `height + round`. When all
+//! the validators have the same weight, this is a plain round robin in the order of the validators.
+
+#[cfg(test)]
+#[path = "proposer_rotation_test.rs"]
+mod proposer_rotation_test;
+
+use starknet_api::block::BlockNumber;
+
+use crate::types::{Round, Validator, ValidatorId, VotingWeight};
+
+// The length of the schedule, per validator. A validator whose share of the total weight is below
+// `1 / (SLOTS_PER_VALIDATOR * n_validators)` may not get a slot.
+const SLOTS_PER_VALIDATOR: usize = 32;
+
+/// A deterministic, stake-weighted rotation of the proposers of a committee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposerRotation {
+    schedule: Vec<ValidatorId>,
+}
+
+impl ProposerRotation {
+    /// Computes the rotation of the given validators, whose total weight must be positive.
+    pub fn new(validators: &[Validator]) -> Self {
+        let total_weight: VotingWeight = validators.iter().map(|validator| validator.weight).sum();
+        assert!(total_weight > 0, "The total weight of the validators must be positive.");
+        let total_weight = to_priority(total_weight);
+
+        let mut priorities = vec![0; validators.len()];
+        let schedule = (0..validators.len() * SLOTS_PER_VALIDATOR)
+            .map(|_| {
+                for (priority, validator) in priorities.iter_mut().zip(validators) {
+                    *priority += to_priority(validator.weight);
+                }
+                // Ties are broken in favor of the validator which comes first.
+                let (index, _) = priorities
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .max_by_key(|(_, priority)| **priority)
+                    .expect("There should be at least one validator");
+                priorities[index] -= total_weight;
+                validators[index].id
+            })
+            .collect();
+        Self { schedule }
+    }
+
+    /// Returns the proposer of the given height and round.
+    pub fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId {
+        let slot = (u128::from(height.0) + u128::from(round))
+            % u128::try_from(self.schedule.len()).expect("Schedule length should fit in u128");
+        self.schedule[usize::try_from(slot).expect("Slot should fit in usize")]
+    }
+}
+
+// Priorities stay between minus and plus the total weight, so they fit in an i128 along with the
+// weights.
+fn to_priority(weight: VotingWeight) -> i128 {
+    i128::try_from(weight).expect("Voting weight should fit in i128")
+}
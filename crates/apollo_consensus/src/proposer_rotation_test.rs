@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use apollo_protobuf::consensus::DEFAULT_VALIDATOR_ID;
+use starknet_api::block::BlockNumber;
+
+use crate::proposer_rotation::{ProposerRotation, SLOTS_PER_VALIDATOR};
+use crate::types::{Validator, ValidatorId, VotingWeight};
+
+fn validators(weights: &[VotingWeight]) -> Vec<Validator> {
+    weights
+        .iter()
+        .zip(DEFAULT_VALIDATOR_ID..)
+        .map(|(weight, id)| Validator { id: id.into(), weight: *weight })
+        .collect()
+}
+
+// Counts how many times each validator proposes in a full cycle of the rotation.
+fn count_proposals(rotation: &ProposerRotation, n_validators: usize) -> HashMap<ValidatorId, u64> {
+    let n_slots = u64::try_from(n_validators * SLOTS_PER_VALIDATOR).unwrap();
+    let mut counts = HashMap::new();
+    for height in 0..n_slots {
+        *counts.entry(rotation.proposer(BlockNumber(height), 0)).or_default() += 1;
+    }
+    counts
+}
+
+#[test]
+fn equal_weights_round_robin() {
+    let validators = validators(&[7, 7, 7, 7]);
+    let rotation = ProposerRotation::new(&validators);
+
+    for height in 0..100 {
+        for round in 0..3 {
+            let expected = validators[usize::try_from(height + u64::from(round)).unwrap() % 4].id;
+            assert_eq!(rotation.proposer(BlockNumber(height), round), expected);
+        }
+    }
+}
+
+#[test]
+fn proposals_follow_weights() {
+    let validators = validators(&[1, 2, 5]);
+    let rotation = ProposerRotation::new(&validators);
+
+    // The cycle has 3 * 32 = 96 slots, which are split by the 1:2:5 ratio of the weights.
+    let counts = count_proposals(&rotation, validators.len());
+    assert_eq!(counts[&validators[0].id], 12);
+    assert_eq!(counts[&validators[1].id], 24);
+    assert_eq!(counts[&validators[2].id], 60);
+
+    // The turns of the heaviest validator are spread out, so the others still get to propose.
+    let first_proposers: Vec<_> =
+        (0..8).map(|height| rotation.proposer(BlockNumber(height), 0)).collect();
+    assert!(first_proposers.contains(&validators[0].id));
+    assert!(first_proposers.contains(&validators[1].id));
+}
+
+#[test]
+fn zero_weight_never_proposes() {
+    let validators = validators(&[3, 0, 3]);
+    let rotation = ProposerRotation::new(&validators);
+
+    let counts = count_proposals(&rotation, validators.len());
+    assert!(!counts.contains_key(&validators[1].id));
+    assert_eq!(counts[&validators[0].id], counts[&validators[2].id]);
+}
+
+#[test]
+#[should_panic]
+fn zero_total_weight() {
+    ProposerRotation::new(&validators(&[0, 0]));
+}
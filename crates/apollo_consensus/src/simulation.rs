@@ -0,0 +1,582 @@
+//! An in-process simulation of a committee running consensus, for testing consensus under faults.
+//!
+//! Each validator runs [`run_consensus`] as a task of the test's runtime, with a context which
+//! sends its proposals and votes to a virtual network instead of the real one. The network delivers
+//! every message after a random latency, and applies a script of [`Fault`]s: partitions, delays,
+//! reordering, crashes and slow proposal streams. Some of the validators can also be byzantine,
+//! sending conflicting votes and proposals to different parts of the committee.
+//!
+//! The simulation must run on a runtime whose clock is paused (`#[tokio::test(start_paused =
+//! true)]`), so that time only advances when all the nodes are idle, and minutes of consensus run
+//! in milliseconds. All the randomness of the network comes from a seeded RNG, so a failing seed
+//! replays the same latencies and fault schedule. Note that tokio may still order events which fall
+//! on the same millisecond differently between runs.
+
+#[cfg(test)]
+#[path = "simulation_test.rs"]
+mod simulation_test;
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use apollo_network::network_manager::test_utils::{
+    mock_register_broadcast_topic,
+    TestSubscriberChannels,
+};
+use apollo_network_types::network_types::BroadcastedMessageMetadata;
+use apollo_protobuf::consensus::{
+    ProposalFin,
+    ProposalInit,
+    ProposalPart,
+    Vote,
+    DEFAULT_VALIDATOR_ID,
+};
+use apollo_test_utils::GetTestInstance;
+use async_trait::async_trait;
+use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_types_core::felt::Felt;
+use tempfile::TempDir;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::config::TimeoutsConfig;
+use crate::proposer_rotation::ProposerRotation;
+use crate::test_utils::sign_vote;
+use crate::types::{
+    BroadcastVoteChannel,
+    ConsensusContext,
+    ConsensusError,
+    EquivocationEvidence,
+    ProposalCommitment,
+    Round,
+    Validator,
+    ValidatorId,
+    VotingWeight,
+};
+use crate::votes_threshold::QuorumType;
+use crate::{run_consensus, RunConsensusArguments};
+
+/// The index of a validator in the committee.
+pub type NodeIndex = usize;
+
+const CHANNEL_SIZE: usize = 100;
+const SYNC_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A fault which the virtual network injects. The times are measured from the start of the
+/// simulation.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// Only nodes in the same group can communicate; other messages sent `during` are dropped.
+    Partition { groups: Vec<Vec<NodeIndex>>, during: Range<Duration> },
+    /// The messages that `nodes` send `during` are delayed by an extra `delay`.
+    Delay { nodes: Vec<NodeIndex>, delay: Duration, during: Range<Duration> },
+    /// The messages sent `during` are delayed by a random extra of up to `jitter`, which must be
+    /// positive, so they overtake each other.
+    Reorder { jitter: Duration, during: Range<Duration> },
+    /// The node stops at `at`. If `restart_at` is set, it then restarts from the height it was at.
+    Crash { node: NodeIndex, at: Duration, restart_at: Option<Duration> },
+    /// The proposals of the node finish streaming `delay` after they start.
+    SlowProposals { node: NodeIndex, delay: Duration },
+}
+
+/// The setup of a simulation.
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    /// The voting weight of each validator.
+    pub weights: Vec<VotingWeight>,
+    /// Validators which send conflicting votes and proposals to the odd and even nodes.
+    pub byzantine: Vec<NodeIndex>,
+    /// The range of the latency of every message.
+    pub latency: Range<Duration>,
+    pub timeouts: TimeoutsConfig,
+    pub faults: Vec<Fault>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            weights: vec![1; 4],
+            byzantine: Vec::new(),
+            latency: Duration::from_millis(1)..Duration::from_millis(20),
+            timeouts: TimeoutsConfig {
+                proposal_timeout: Duration::from_millis(500),
+                prevote_timeout: Duration::from_millis(200),
+                precommit_timeout: Duration::from_millis(200),
+            },
+            faults: Vec::new(),
+        }
+    }
+}
+
+/// The outcome of a simulation, which the nodes record as they run.
+#[derive(Debug, Default)]
+pub struct SimulationResult {
+    /// The blocks which the nodes decided by consensus in each height.
+    pub decisions: BTreeMap<BlockNumber, BTreeMap<NodeIndex, ProposalCommitment>>,
+    /// The first height each node hasn't completed, either by consensus or by sync.
+    pub heights: Vec<BlockNumber>,
+    /// The equivocations which each node reported.
+    pub equivocations: Vec<(NodeIndex, EquivocationEvidence)>,
+    /// The prevote quorums which each node reported, by height and round.
+    pub prevote_quorums: Vec<(NodeIndex, BlockNumber, Round, ProposalCommitment)>,
+    /// Errors which stopped the consensus of a node.
+    pub errors: Vec<(NodeIndex, ConsensusError)>,
+    /// The nodes which crash and don't restart.
+    pub stopped: BTreeSet<NodeIndex>,
+    pub byzantine: Vec<NodeIndex>,
+    /// The virtual time the simulation ran for.
+    pub elapsed: Duration,
+}
+
+impl SimulationResult {
+    /// Panics if two honest nodes decided on different blocks in the same height.
+    pub fn assert_safety(&self) {
+        for (height, decisions) in &self.decisions {
+            let honest_blocks: HashSet<_> = decisions
+                .iter()
+                .filter(|(node, _)| !self.byzantine.contains(node))
+                .map(|(_, block)| block)
+                .collect();
+            assert!(
+                honest_blocks.len() <= 1,
+                "Conflicting decisions in height {height}: {decisions:?}"
+            );
+        }
+    }
+
+    /// The first height which some honest node that doesn't stop hasn't completed.
+    pub fn min_honest_height(&self) -> BlockNumber {
+        self.heights
+            .iter()
+            .enumerate()
+            .filter(|(node, _)| !self.byzantine.contains(node) && !self.stopped.contains(node))
+            .map(|(_, height)| *height)
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// Runs a committee of validators over a virtual network. See the [module docs](self).
+pub struct Simulation {
+    config: SimulationConfig,
+    rng: ChaCha8Rng,
+    validators: Vec<Validator>,
+    result: Arc<Mutex<SimulationResult>>,
+    // None for the nodes which are down.
+    nodes: Vec<Option<Node>>,
+    outgoing_sender: mpsc::UnboundedSender<(NodeIndex, Message)>,
+    outgoing_receiver: mpsc::UnboundedReceiver<(NodeIndex, Message)>,
+    // Ordered by time, and then by the order of scheduling.
+    events: BTreeMap<(Instant, u64), Event>,
+    n_scheduled_events: u64,
+    start: Instant,
+    write_ahead_log_dir: TempDir,
+}
+
+struct Node {
+    votes_sender: mpsc::UnboundedSender<Vote>,
+    proposals_sender: mpsc::Sender<mpsc::Receiver<ProposalPart>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Clone, Debug)]
+enum Message {
+    Vote(Vote),
+    Proposal(ProposalInit, ProposalCommitment),
+}
+
+#[derive(Debug)]
+enum Event {
+    DeliverVote { to: NodeIndex, vote: Vote },
+    DeliverProposal { to: NodeIndex, init: ProposalInit, block: ProposalCommitment },
+    FinishProposal { content_sender: mpsc::Sender<ProposalPart>, block: ProposalCommitment },
+    Crash(NodeIndex),
+    Restart(NodeIndex),
+}
+
+impl Simulation {
+    pub fn new(config: SimulationConfig, rng: ChaCha8Rng) -> Self {
+        let validators = (0..)
+            .zip(&config.weights)
+            .map(|(index, weight)| Validator { id: validator_id(index), weight: *weight })
+            .collect();
+        let result = SimulationResult {
+            heights: vec![BlockNumber(0); config.weights.len()],
+            byzantine: config.byzantine.clone(),
+            ..Default::default()
+        };
+        let (outgoing_sender, outgoing_receiver) = mpsc::unbounded();
+        Self {
+            nodes: config.weights.iter().map(|_| None).collect(),
+            config,
+            rng,
+            validators,
+            result: Arc::new(Mutex::new(result)),
+            outgoing_sender,
+            outgoing_receiver,
+            events: BTreeMap::new(),
+            n_scheduled_events: 0,
+            start: Instant::now(),
+            write_ahead_log_dir: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    /// Runs the committee until all the honest nodes, other than those which crash for good,
+    /// complete `target_height`, or until `max_duration` passes.
+    pub async fn run(
+        mut self,
+        target_height: BlockNumber,
+        max_duration: Duration,
+    ) -> SimulationResult {
+        self.start = Instant::now();
+        for node in 0..self.nodes.len() {
+            self.start_node(node);
+        }
+        for fault in self.config.faults.clone() {
+            if let Fault::Crash { node, at, restart_at } = fault {
+                self.schedule(self.start + at, Event::Crash(node));
+                match restart_at {
+                    Some(restart_at) => {
+                        self.schedule(self.start + restart_at, Event::Restart(node))
+                    }
+                    None => {
+                        self.result.lock().unwrap().stopped.insert(node);
+                    }
+                }
+            }
+        }
+
+        let deadline = self.start + max_duration;
+        while self.result.lock().unwrap().min_honest_height() <= target_height {
+            let next_event =
+                self.events.first_key_value().map(|((at, _), _)| *at).filter(|at| *at <= deadline);
+            tokio::select! {
+                biased;
+                Some((from, message)) = self.outgoing_receiver.next() => self.send(from, message),
+                _ = tokio::time::sleep_until(next_event.unwrap_or(deadline)) => {
+                    if next_event.is_none() {
+                        break;
+                    }
+                    let (_, event) = self.events.pop_first().expect("An event is due.");
+                    self.handle_event(event);
+                }
+            }
+        }
+
+        for node in self.nodes.iter().flatten() {
+            node.task.abort();
+        }
+        let mut result = std::mem::take(&mut *self.result.lock().unwrap());
+        result.elapsed = self.start.elapsed();
+        result
+    }
+
+    fn start_node(&mut self, node: NodeIndex) {
+        let (votes_sender, votes_receiver) = mpsc::unbounded();
+        let metadata = BroadcastedMessageMetadata::get_test_instance(&mut self.rng);
+        // Consensus only reports peers and continues propagation through the client, which the
+        // virtual network has no use for.
+        let TestSubscriberChannels { subscriber_channels, .. } =
+            mock_register_broadcast_topic().unwrap();
+        let vote_channel = BroadcastVoteChannel {
+            broadcasted_messages_receiver: Box::new(
+                votes_receiver.map(move |vote| (Ok(vote), metadata.clone())),
+            ),
+            broadcast_topic_client: subscriber_channels.broadcast_topic_client,
+        };
+        let (proposals_sender, proposals_receiver) = mpsc::channel(CHANNEL_SIZE);
+
+        let context = SimulationContext {
+            node,
+            validators: self.validators.clone(),
+            proposer_rotation: ProposerRotation::new(&self.validators),
+            outgoing_sender: self.outgoing_sender.clone(),
+            result: self.result.clone(),
+        };
+        let height = self.result.lock().unwrap().heights[node];
+        let run_consensus_args = RunConsensusArguments {
+            start_active_height: height,
+            start_observe_height: height,
+            validator_id: validator_id(node),
+            consensus_delay: Duration::ZERO,
+            timeouts: self.config.timeouts.clone(),
+            sync_retry_interval: SYNC_RETRY_INTERVAL,
+            quorum_type: QuorumType::Byzantine,
+            // Keeps a restarted node from voting twice in the same round.
+            write_ahead_log_dir: Some(self.write_ahead_log_dir.path().join(node.to_string())),
+        };
+        let result = self.result.clone();
+        let task = tokio::spawn(async move {
+            if let Err(error) =
+                run_consensus(run_consensus_args, context, vote_channel, proposals_receiver).await
+            {
+                result.lock().unwrap().errors.push((node, error));
+            }
+        });
+        self.nodes[node] = Some(Node { votes_sender, proposals_sender, task });
+    }
+
+    fn schedule(&mut self, at: Instant, event: Event) {
+        self.events.insert((at, self.n_scheduled_events), event);
+        self.n_scheduled_events += 1;
+    }
+
+    fn send(&mut self, from: NodeIndex, message: Message) {
+        let now = Instant::now();
+        let elapsed = now - self.start;
+        for to in 0..self.nodes.len() {
+            if to == from || self.is_cut(from, to, elapsed) {
+                continue;
+            }
+            let message = if self.config.byzantine.contains(&from) && to % 2 == 1 {
+                conflicting_message(&message)
+            } else {
+                message.clone()
+            };
+            let at = now + self.latency(from, elapsed);
+            let event = match message {
+                Message::Vote(vote) => Event::DeliverVote { to, vote },
+                Message::Proposal(init, block) => Event::DeliverProposal { to, init, block },
+            };
+            self.schedule(at, event);
+        }
+    }
+
+    fn is_cut(&self, from: NodeIndex, to: NodeIndex, elapsed: Duration) -> bool {
+        self.config.faults.iter().any(|fault| match fault {
+            Fault::Partition { groups, during } => {
+                during.contains(&elapsed)
+                    && !groups.iter().any(|group| group.contains(&from) && group.contains(&to))
+            }
+            _ => false,
+        })
+    }
+
+    fn latency(&mut self, from: NodeIndex, elapsed: Duration) -> Duration {
+        let mut latency = self.rng.gen_range(self.config.latency.clone());
+        for fault in &self.config.faults {
+            match fault {
+                Fault::Delay { nodes, delay, during }
+                    if nodes.contains(&from) && during.contains(&elapsed) =>
+                {
+                    latency += *delay;
+                }
+                Fault::Reorder { jitter, during } if during.contains(&elapsed) => {
+                    latency += self.rng.gen_range(Duration::ZERO..*jitter);
+                }
+                _ => {}
+            }
+        }
+        latency
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::DeliverVote { to, vote } => {
+                if let Some(node) = &self.nodes[to] {
+                    // The node's consensus may have stopped on an error, which it records.
+                    let _ = node.votes_sender.unbounded_send(vote);
+                }
+            }
+            Event::DeliverProposal { to, init, block } => {
+                let Some(node) = &mut self.nodes[to] else {
+                    return;
+                };
+                // Consensus expects the init to be available as soon as it gets the stream.
+                let (mut content_sender, content_receiver) = mpsc::channel(CHANNEL_SIZE);
+                content_sender.try_send(ProposalPart::Init(init)).expect("The stream is empty.");
+                if node.proposals_sender.try_send(content_receiver).is_err() {
+                    return;
+                }
+                let stream_delay = self.stream_delay(init.proposer);
+                self.schedule(
+                    Instant::now() + stream_delay,
+                    Event::FinishProposal { content_sender, block },
+                );
+            }
+            Event::FinishProposal { mut content_sender, block } => {
+                // The receiver is gone if consensus moved on from the proposal.
+                let _ = content_sender
+                    .try_send(ProposalPart::Fin(ProposalFin { proposal_commitment: block }));
+            }
+            Event::Crash(node) => {
+                if let Some(node) = self.nodes[node].take() {
+                    node.task.abort();
+                }
+            }
+            Event::Restart(node) => self.start_node(node),
+        }
+    }
+
+    fn stream_delay(&self, proposer: ValidatorId) -> Duration {
+        self.config
+            .faults
+            .iter()
+            .find_map(|fault| match fault {
+                Fault::SlowProposals { node, delay } if validator_id(*node) == proposer => {
+                    Some(*delay)
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn validator_id(node: NodeIndex) -> ValidatorId {
+    ValidatorId::from(DEFAULT_VALIDATOR_ID + u64::try_from(node).unwrap())
+}
+
+// The block proposed in a round. Byzantine validators also propose and vote for a conflicting
+// block, which no honest proposer proposes.
+fn block(height: BlockNumber, round: Round, conflicting: bool) -> ProposalCommitment {
+    BlockHash(Felt::from(
+        u128::from(conflicting) << 96 | u128::from(height.0) << 32 | u128::from(round),
+    ))
+}
+
+fn conflicting_message(message: &Message) -> Message {
+    match message {
+        Message::Vote(vote) => Message::Vote(sign_vote(Vote {
+            block_hash: Some(block(BlockNumber(vote.height), vote.round, true)),
+            ..vote.clone()
+        })),
+        Message::Proposal(init, _) => {
+            Message::Proposal(*init, block(init.height, init.round, true))
+        }
+    }
+}
+
+// The context of a simulated node, which sends its messages to the virtual network and records
+// the progress of the node in the result of the simulation.
+struct SimulationContext {
+    node: NodeIndex,
+    validators: Vec<Validator>,
+    proposer_rotation: ProposerRotation,
+    outgoing_sender: mpsc::UnboundedSender<(NodeIndex, Message)>,
+    result: Arc<Mutex<SimulationResult>>,
+}
+
+impl SimulationContext {
+    fn send(&self, message: Message) {
+        self.outgoing_sender
+            .unbounded_send((self.node, message))
+            .expect("The network outlives the nodes.");
+    }
+}
+
+#[async_trait]
+impl ConsensusContext for SimulationContext {
+    type ProposalPart = ProposalPart;
+
+    async fn build_proposal(
+        &mut self,
+        init: ProposalInit,
+        _timeout: Duration,
+    ) -> oneshot::Receiver<ProposalCommitment> {
+        let block = block(init.height, init.round, false);
+        self.send(Message::Proposal(init, block));
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(block).expect("The receiver is alive.");
+        block_receiver
+    }
+
+    async fn validate_proposal(
+        &mut self,
+        _init: ProposalInit,
+        timeout: Duration,
+        mut content: mpsc::Receiver<ProposalPart>,
+    ) -> oneshot::Receiver<ProposalCommitment> {
+        let (block_sender, block_receiver) = oneshot::channel();
+        tokio::spawn(async move {
+            let fin = tokio::time::timeout(timeout, async {
+                while let Some(part) = content.next().await {
+                    if let ProposalPart::Fin(fin) = part {
+                        return Some(fin.proposal_commitment);
+                    }
+                }
+                None
+            })
+            .await;
+            // A proposal which doesn't finish streaming in time is invalid.
+            if let Ok(Some(block)) = fin {
+                let _ = block_sender.send(block);
+            }
+        });
+        block_receiver
+    }
+
+    async fn repropose(&mut self, id: ProposalCommitment, init: ProposalInit) {
+        self.send(Message::Proposal(init, id));
+    }
+
+    async fn validators(&self, _height: BlockNumber) -> Vec<Validator> {
+        self.validators.clone()
+    }
+
+    fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId {
+        self.proposer_rotation.proposer(height, round)
+    }
+
+    async fn sign_vote(&mut self, vote: Vote) -> Result<Vote, ConsensusError> {
+        Ok(sign_vote(vote))
+    }
+
+    fn verify_vote_signature(&self, vote: &Vote) -> bool {
+        *vote == sign_vote(vote.clone())
+    }
+
+    async fn broadcast(&mut self, message: Vote) -> Result<(), ConsensusError> {
+        self.send(Message::Vote(message));
+        Ok(())
+    }
+
+    async fn decision_reached(
+        &mut self,
+        block: ProposalCommitment,
+        precommits: Vec<Vote>,
+    ) -> Result<(), ConsensusError> {
+        let height = BlockNumber(precommits[0].height);
+        let mut result = self.result.lock().unwrap();
+        result.decisions.entry(height).or_default().insert(self.node, block);
+        result.heights[self.node] = height.unchecked_next();
+        Ok(())
+    }
+
+    // Sync serves the blocks which honest nodes decided.
+    async fn try_sync(&mut self, height: BlockNumber) -> bool {
+        let mut result = self.result.lock().unwrap();
+        let synced = result
+            .decisions
+            .get(&height)
+            .is_some_and(|decisions| decisions.keys().any(|node| !result.byzantine.contains(node)));
+        if synced {
+            result.heights[self.node] = height.unchecked_next();
+        }
+        synced
+    }
+
+    async fn set_height_and_round(&mut self, _height: BlockNumber, _round: Round) {}
+
+    async fn report_equivocation(&mut self, evidence: EquivocationEvidence) {
+        self.result.lock().unwrap().equivocations.push((self.node, evidence));
+    }
+
+    async fn prevote_quorum_reached(
+        &mut self,
+        height: BlockNumber,
+        round: Round,
+        block: ProposalCommitment,
+    ) {
+        self.result.lock().unwrap().prevote_quorums.push((self.node, height, round, block));
+    }
+
+    fn pipelines_next_height(&self) -> bool {
+        true
+    }
+}
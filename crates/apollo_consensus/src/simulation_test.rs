@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::time::Duration;
+
+use apollo_test_utils::get_rng;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use starknet_api::block::BlockNumber;
+
+use crate::simulation::{Fault, Simulation, SimulationConfig, SimulationResult};
+
+const TARGET_HEIGHT: BlockNumber = BlockNumber(10);
+
+async fn simulate(
+    config: SimulationConfig,
+    rng: ChaCha8Rng,
+    max_duration: Duration,
+) -> SimulationResult {
+    let result = Simulation::new(config, rng).run(TARGET_HEIGHT, max_duration).await;
+    result.assert_safety();
+    assert!(result.errors.is_empty(), "Consensus failed: {:?}", result.errors);
+    result
+}
+
+fn assert_liveness(result: &SimulationResult) {
+    assert!(
+        result.min_honest_height() > TARGET_HEIGHT,
+        "Stuck at height {} after {:?}: {:?}",
+        result.min_honest_height(),
+        result.elapsed,
+        result.heights
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn honest_committee() {
+    let result = simulate(SimulationConfig::default(), get_rng(), Duration::from_secs(60)).await;
+    assert_liveness(&result);
+    // Without faults, every height is decided in its first round.
+    assert!(result.elapsed < Duration::from_secs(2), "Took {:?}", result.elapsed);
+    assert!(result.equivocations.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn prevote_quorums_precede_decisions() {
+    let result = simulate(SimulationConfig::default(), get_rng(), Duration::from_secs(60)).await;
+    assert!(!result.prevote_quorums.is_empty());
+    let mut reported = HashSet::new();
+    for (node, height, round, block) in &result.prevote_quorums {
+        assert!(reported.insert((node, height, round)), "Reported twice: {node} {height} {round}");
+        // Without faults, the block with a prevote quorum is the one which is decided.
+        if let Some(decisions) = result.decisions.get(height) {
+            assert!(decisions.values().all(|decided| decided == block));
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn partition_without_quorum_heals() {
+    let config = SimulationConfig {
+        faults: vec![Fault::Partition {
+            groups: vec![vec![0, 1], vec![2, 3]],
+            during: Duration::ZERO..Duration::from_secs(10),
+        }],
+        ..Default::default()
+    };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+    // Nothing can be decided before the partition heals.
+    assert!(result.elapsed > Duration::from_secs(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn isolated_validator_catches_up() {
+    let config = SimulationConfig {
+        faults: vec![Fault::Partition {
+            groups: vec![vec![0], vec![1, 2, 3]],
+            during: Duration::ZERO..Duration::from_secs(10),
+        }],
+        ..Default::default()
+    };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+}
+
+#[tokio::test(start_paused = true)]
+async fn byzantine_validator() {
+    let config = SimulationConfig { byzantine: vec![1], ..Default::default() };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+}
+
+#[tokio::test(start_paused = true)]
+async fn crashed_validator_restarts() {
+    let config = SimulationConfig {
+        faults: vec![Fault::Crash {
+            node: 2,
+            at: Duration::from_millis(100),
+            restart_at: Some(Duration::from_secs(5)),
+        }],
+        ..Default::default()
+    };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+    // The other validators wait for the restarted one to catch up.
+    assert!(result.elapsed >= Duration::from_secs(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_progress_without_quorum() {
+    let crash = |node| Fault::Crash { node, at: Duration::from_millis(100), restart_at: None };
+    let config = SimulationConfig { faults: vec![crash(0), crash(1)], ..Default::default() };
+    let result = simulate(config, get_rng(), Duration::from_secs(60)).await;
+    assert!(result.min_honest_height() <= TARGET_HEIGHT);
+    assert!(result.elapsed >= Duration::from_secs(60));
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_proposals_skip_rounds() {
+    let config = SimulationConfig {
+        faults: vec![Fault::SlowProposals { node: 0, delay: Duration::from_secs(1) }],
+        ..Default::default()
+    };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+}
+
+#[tokio::test(start_paused = true)]
+async fn delays_and_reordering() {
+    let config = SimulationConfig {
+        faults: vec![
+            Fault::Delay {
+                nodes: vec![0],
+                delay: Duration::from_millis(300),
+                during: Duration::ZERO..Duration::from_secs(20),
+            },
+            Fault::Reorder {
+                jitter: Duration::from_millis(250),
+                during: Duration::ZERO..Duration::from_secs(20),
+            },
+        ],
+        ..Default::default()
+    };
+    let result = simulate(config, get_rng(), Duration::from_secs(120)).await;
+    assert_liveness(&result);
+}
+
+fn random_window(rng: &mut impl Rng) -> Range<Duration> {
+    let start = Duration::from_millis(rng.gen_range(0..10_000));
+    start..start + Duration::from_millis(rng.gen_range(0..10_000))
+}
+
+// A random schedule of faults, all of which end within the first 20 seconds.
+#[tokio::test(start_paused = true)]
+async fn random_faults() {
+    let mut rng = get_rng();
+    let faults = vec![
+        Fault::Partition { groups: vec![vec![0, 1], vec![2, 3]], during: random_window(&mut rng) },
+        Fault::Reorder { jitter: Duration::from_millis(200), during: random_window(&mut rng) },
+        Fault::Delay {
+            nodes: vec![rng.gen_range(0..4)],
+            delay: Duration::from_millis(rng.gen_range(0..500)),
+            during: random_window(&mut rng),
+        },
+        Fault::Crash {
+            node: rng.gen_range(0..4),
+            at: Duration::from_millis(rng.gen_range(0..10_000)),
+            restart_at: Some(Duration::from_secs(15)),
+        },
+        Fault::SlowProposals {
+            node: rng.gen_range(0..4),
+            delay: Duration::from_millis(rng.gen_range(0..1000)),
+        },
+    ];
+    // At most one validator is byzantine, so consensus is safe throughout.
+    let byzantine = if rng.gen_bool(0.5) { vec![rng.gen_range(0..4)] } else { Vec::new() };
+    let config = SimulationConfig { byzantine: byzantine.clone(), faults, ..Default::default() };
+    let result = simulate(config, rng, Duration::from_secs(300)).await;
+    // A validator which locked on a block in a round whose prevotes the others lost can't unlock
+    // without them: the others can't form a quorum for another block without the byzantine
+    // validator. So consensus is only live with a byzantine validator if no messages are lost.
+    if byzantine.is_empty() {
+        assert_liveness(&result);
+    }
+}
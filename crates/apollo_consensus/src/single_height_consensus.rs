@@ -14,10 +14,11 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
 use std::time::Duration;
 
-use apollo_protobuf::consensus::{ProposalInit, Vote, VoteType};
+use apollo_protobuf::consensus::{ProposalFin, ProposalInit, Vote, VoteType};
 #[cfg(test)]
 use enum_as_inner::EnumAsInner;
 use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::BlockNumber;
 use tracing::{debug, info, instrument, trace, warn};
@@ -26,6 +27,7 @@ use crate::config::TimeoutsConfig;
 use crate::metrics::{
     CONSENSUS_BUILD_PROPOSAL_FAILED,
     CONSENSUS_BUILD_PROPOSAL_TOTAL,
+    CONSENSUS_CONFLICTING_PROPOSALS,
     CONSENSUS_CONFLICTING_VOTES,
     CONSENSUS_PROPOSALS_INVALID,
     CONSENSUS_PROPOSALS_VALIDATED,
@@ -37,11 +39,16 @@ use crate::types::{
     ConsensusContext,
     ConsensusError,
     Decision,
+    EquivocationEvidence,
     ProposalCommitment,
+    ProposalEvidence,
     Round,
+    Validator,
     ValidatorId,
+    VotingWeight,
 };
 use crate::votes_threshold::QuorumType;
+use crate::write_ahead_log::{WalEntry, WriteAheadLog};
 
 /// The SHC can either update the manager of a decision or return tasks that should be run without
 /// blocking further calls to itself.
@@ -63,6 +70,7 @@ pub enum ShcEvent {
     BuildProposal(StateMachineEvent),
     // TODO(Matan): Replace ProposalCommitment with the unvalidated signature from the proposer.
     ValidateProposal(StateMachineEvent),
+    DuplicateProposal(ProposalInit, Option<ProposalCommitment>),
 }
 
 /// A task which should be run without blocking calls to SHC.
@@ -90,6 +98,9 @@ pub enum ShcTask {
     /// 3. Once validation is complete, the manager returns the built proposal to the SHC as an
     ///    event, which can be sent to the SM.
     ValidateProposal(ProposalInit, oneshot::Receiver<ProposalCommitment>),
+    /// Another proposal for a round which already has one is only read up to its `ProposalFin`, to
+    /// check whether the proposer equivocated. It's neither validated nor accepted.
+    ReadDuplicateProposal(ProposalInit, oneshot::Receiver<ProposalCommitment>),
 }
 
 impl PartialEq for ShcTask {
@@ -101,7 +112,10 @@ impl PartialEq for ShcTask {
             | (ShcTask::Prevote(d1, e1), ShcTask::Prevote(d2, e2))
             | (ShcTask::Precommit(d1, e1), ShcTask::Precommit(d2, e2)) => d1 == d2 && e1 == e2,
             (ShcTask::BuildProposal(r1, _), ShcTask::BuildProposal(r2, _)) => r1 == r2,
-            (ShcTask::ValidateProposal(pi1, _), ShcTask::ValidateProposal(pi2, _)) => pi1 == pi2,
+            (ShcTask::ValidateProposal(pi1, _), ShcTask::ValidateProposal(pi2, _))
+            | (ShcTask::ReadDuplicateProposal(pi1, _), ShcTask::ReadDuplicateProposal(pi2, _)) => {
+                pi1 == pi2
+            }
             _ => false,
         }
     }
@@ -145,10 +159,40 @@ impl ShcTask {
                     init.valid_round,
                 ))
             }
+            ShcTask::ReadDuplicateProposal(init, commitment_receiver) => {
+                ShcEvent::DuplicateProposal(init, commitment_receiver.await.ok())
+            }
         }
     }
 }
 
+// Reads the content of a proposal up to its `ProposalFin` in a separate task. The sender is dropped
+// if the fin isn't received within `timeout`.
+fn read_proposal_commitment<ProposalPartT>(
+    mut content_receiver: mpsc::Receiver<ProposalPartT>,
+    timeout: Duration,
+) -> oneshot::Receiver<ProposalCommitment>
+where
+    ProposalPartT: TryInto<ProposalFin> + Send + 'static,
+{
+    let (commitment_sender, commitment_receiver) = oneshot::channel();
+    tokio::spawn(async move {
+        let read_fin = async {
+            while let Some(part) = content_receiver.next().await {
+                if let Ok(ProposalFin { proposal_commitment }) = part.try_into() {
+                    return Some(proposal_commitment);
+                }
+            }
+            None
+        };
+        if let Ok(Some(proposal_commitment)) = tokio::time::timeout(timeout, read_fin).await {
+            // The receiver is dropped if the height ended meanwhile.
+            let _ = commitment_sender.send(proposal_commitment);
+        }
+    });
+    commitment_receiver
+}
+
 /// Represents a single height of consensus. It is responsible for mapping between the idealized
 /// view of consensus represented in the StateMachine and the real world implementation.
 ///
@@ -157,22 +201,35 @@ impl ShcTask {
 ///   by the Manager. The manager though unaware of the specific task as it has minimal consensus
 ///   logic.
 ///
-/// Each height is begun with a call to `start`, with no further calls to it.
+/// Each height is begun with a call to `start`, with no further calls to it. If the node restarted
+/// in the middle of the height, it's begun with a call to `resume` instead.
 ///
 /// SHC is not a top level task, it is called directly and returns values (doesn't directly run sub
 /// tasks). SHC does have side effects, such as sending messages to the network via the context.
 #[derive(Serialize, Deserialize)]
 pub(crate) struct SingleHeightConsensus {
     height: BlockNumber,
-    validators: Vec<ValidatorId>,
+    validators: Vec<Validator>,
     id: ValidatorId,
     timeouts: TimeoutsConfig,
     state_machine: StateMachine,
     proposals: HashMap<Round, Option<ProposalCommitment>>,
+    // The inits of the proposals received from peers, used to detect conflicting proposals.
+    proposal_inits: HashMap<Round, ProposalInit>,
+    // The other proposals received for rounds which already had one.
+    duplicate_proposals: HashMap<Round, Vec<ProposalEvidence>>,
     prevotes: HashMap<(Round, ValidatorId), Vote>,
     precommits: HashMap<(Round, ValidatorId), Vote>,
     last_prevote: Option<Vote>,
     last_precommit: Option<Vote>,
+    #[serde(skip)]
+    write_ahead_log: Option<WriteAheadLog>,
+    // The locked and valid values that were last written to the write-ahead log.
+    logged_locked_value_round: Option<(ProposalCommitment, Round)>,
+    logged_valid_value_round: Option<(ProposalCommitment, Round)>,
+    // The last prevote quorum the context was notified of.
+    reported_prevote_quorum: Option<(ProposalCommitment, Round)>,
+    equivocations: Vec<EquivocationEvidence>,
 }
 
 impl SingleHeightConsensus {
@@ -180,14 +237,21 @@ impl SingleHeightConsensus {
         height: BlockNumber,
         is_observer: bool,
         id: ValidatorId,
-        validators: Vec<ValidatorId>,
+        validators: Vec<Validator>,
         quroum_type: QuorumType,
         timeouts: TimeoutsConfig,
+        write_ahead_log: Option<WriteAheadLog>,
     ) -> Self {
-        // TODO(matan): Use actual weights, not just `len`.
-        let n_validators =
-            u64::try_from(validators.len()).expect("Should have way less than u64::MAX validators");
-        let state_machine = StateMachine::new(id, n_validators, is_observer, quroum_type);
+        let total_weight = validators.iter().map(|validator| validator.weight).sum();
+        let weight = if is_observer {
+            0
+        } else {
+            validators
+                .iter()
+                .find(|validator| validator.id == id)
+                .map_or(0, |validator| validator.weight)
+        };
+        let state_machine = StateMachine::new(id, weight, total_weight, is_observer, quroum_type);
         Self {
             height,
             validators,
@@ -195,10 +259,17 @@ impl SingleHeightConsensus {
             timeouts,
             state_machine,
             proposals: HashMap::new(),
+            proposal_inits: HashMap::new(),
+            duplicate_proposals: HashMap::new(),
             prevotes: HashMap::new(),
             precommits: HashMap::new(),
             last_prevote: None,
             last_precommit: None,
+            write_ahead_log,
+            logged_locked_value_round: None,
+            logged_valid_value_round: None,
+            reported_prevote_quorum: None,
+            equivocations: Vec::new(),
         }
     }
 
@@ -217,6 +288,85 @@ impl SingleHeightConsensus {
         ret
     }
 
+    /// Resumes the height after the node restarted in the middle of it, using the entries of the
+    /// height's write-ahead log. Called instead of `start`.
+    ///
+    /// Restores the votes the node sent and the values it locked on and found valid before
+    /// restarting, so it won't vote differently in the rounds it already voted in, and rebroadcasts
+    /// its latest votes.
+    #[instrument(skip_all)]
+    pub(crate) async fn resume<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        wal_entries: Vec<WalEntry>,
+    ) -> Result<ShcReturn, ConsensusError> {
+        let mut sm_votes = Vec::new();
+        for entry in wal_entries {
+            match entry {
+                WalEntry::Vote(vote) => {
+                    if vote.height != self.height.0 || vote.voter != self.id {
+                        return Err(ConsensusError::InternalInconsistency(format!(
+                            "Write-ahead log of height {} contains a foreign vote: {vote:?}",
+                            self.height
+                        )));
+                    }
+                    let (votes, last_vote, sm_vote) = match vote.vote_type {
+                        VoteType::Prevote => (
+                            &mut self.prevotes,
+                            &mut self.last_prevote,
+                            StateMachineEvent::Prevote(vote.block_hash, vote.round),
+                        ),
+                        VoteType::Precommit => (
+                            &mut self.precommits,
+                            &mut self.last_precommit,
+                            StateMachineEvent::Precommit(vote.block_hash, vote.round),
+                        ),
+                    };
+                    if last_vote.as_ref().is_none_or(|last_vote| vote.round > last_vote.round) {
+                        *last_vote = Some(vote.clone());
+                    }
+                    votes.insert((vote.round, self.id), vote);
+                    sm_votes.push(sm_vote);
+                }
+                WalEntry::LockedValue(proposal_id, round) => {
+                    self.logged_locked_value_round = Some((proposal_id, round));
+                }
+                WalEntry::ValidValue(proposal_id, round) => {
+                    self.logged_valid_value_round = Some((proposal_id, round));
+                }
+            }
+        }
+        if sm_votes.is_empty() {
+            return self.start(context).await;
+        }
+        info!("Resuming height {} from the write-ahead log.", self.height);
+        self.state_machine.resume(
+            sm_votes,
+            self.logged_locked_value_round,
+            self.logged_valid_value_round,
+        )?;
+        context.set_height_and_round(self.height, self.state_machine.round()).await;
+
+        let mut tasks = Vec::new();
+        if let Some(last_prevote) = &self.last_prevote {
+            debug!("Rebroadcasting {last_prevote:?}");
+            context.broadcast(last_prevote.clone()).await?;
+            tasks.push(ShcTask::Prevote(
+                self.timeouts.prevote_timeout,
+                StateMachineEvent::Prevote(last_prevote.block_hash, last_prevote.round),
+            ));
+        }
+        if let Some(last_precommit) = &self.last_precommit {
+            debug!("Rebroadcasting {last_precommit:?}");
+            context.broadcast(last_precommit.clone()).await?;
+            tasks.push(ShcTask::Precommit(
+                self.timeouts.precommit_timeout,
+                StateMachineEvent::Precommit(last_precommit.block_hash, last_precommit.round),
+            ));
+        }
+        Ok(ShcReturn::Tasks(tasks))
+    }
+
     /// Process the proposal init and initiate block validation. See [`ShcTask::ValidateProposal`]
     /// for more details on the full proposal flow.
     #[instrument(skip_all)]
@@ -237,8 +387,19 @@ impl SingleHeightConsensus {
             return Ok(ShcReturn::Tasks(Vec::new()));
         }
         let Entry::Vacant(proposal_entry) = self.proposals.entry(init.round) else {
-            warn!("Round {} already has a proposal, ignoring", init.round);
-            return Ok(ShcReturn::Tasks(Vec::new()));
+            if !self.proposal_inits.contains_key(&init.round) {
+                warn!("Round {} already has a proposal, ignoring", init.round);
+                return Ok(ShcReturn::Tasks(Vec::new()));
+            }
+            // The proposer may have sent different content under the same init, so the commitment
+            // to the content is read to compare the proposals.
+            debug!("Round {} already has a proposal, checking {init:?} for a conflict", init.round);
+            let commitment_receiver =
+                read_proposal_commitment(p2p_messages_receiver, self.timeouts.proposal_timeout);
+            return Ok(ShcReturn::Tasks(vec![ShcTask::ReadDuplicateProposal(
+                init,
+                commitment_receiver,
+            )]));
         };
         let timeout = self.timeouts.proposal_timeout;
         info!(
@@ -250,6 +411,7 @@ impl SingleHeightConsensus {
         // Since validating the proposal is non-blocking, we want to avoid validating the same round
         // twice in parallel. This could be caused by a network repeat or a malicious spam attack.
         proposal_entry.insert(None);
+        self.proposal_inits.insert(init.round, init);
         let block_receiver = context.validate_proposal(init, timeout, p2p_messages_receiver).await;
         context.set_height_and_round(self.height, self.state_machine.round()).await;
         Ok(ShcReturn::Tasks(vec![ShcTask::ValidateProposal(init, block_receiver)]))
@@ -329,12 +491,28 @@ impl SingleHeightConsensus {
                     panic!("Proposal entry should exist from init. round: {round}")
                 });
                 assert!(old.is_none(), "Proposal already exists for this round: {round}. {old:?}");
+                // The content of duplicates with the same init can only be compared once the
+                // proposal's commitment is known.
+                let duplicates = self.duplicate_proposals.get(&round).cloned().unwrap_or_default();
+                for duplicate in duplicates {
+                    if duplicate.init == self.proposal_inits[&round] {
+                        self.check_conflicting_proposal(context, duplicate).await;
+                    }
+                }
+                let leader_fn =
+                    |round: Round| -> ValidatorId { context.proposer(self.height, round) };
                 let sm_events = self.state_machine.handle_event(
                     StateMachineEvent::Proposal(proposal_id, round, valid_round),
                     &leader_fn,
                 );
                 self.handle_state_machine_events(context, sm_events).await
             }
+            ShcEvent::DuplicateProposal(init, commitment) => {
+                let duplicate = ProposalEvidence { init, commitment };
+                self.duplicate_proposals.entry(init.round).or_default().push(duplicate);
+                self.check_conflicting_proposal(context, duplicate).await;
+                Ok(ShcReturn::Tasks(Vec::new()))
+            }
             ShcEvent::BuildProposal(StateMachineEvent::GetProposal(proposal_id, round)) => {
                 if proposal_id.is_none() {
                     CONSENSUS_BUILD_PROPOSAL_FAILED.increment(1);
@@ -378,10 +556,10 @@ impl SingleHeightConsensus {
         vote: Vote,
     ) -> Result<ShcReturn, ConsensusError> {
         trace!("Received {:?}", vote);
-        if !self.validators.contains(&vote.voter) {
+        let Some(weight) = self.voting_weight(vote.voter) else {
             debug!("Ignoring vote from non validator: vote={:?}", vote);
             return Ok(ShcReturn::Tasks(Vec::new()));
-        }
+        };
 
         let (votes, sm_vote) = match vote.vote_type {
             VoteType::Prevote => {
@@ -401,27 +579,84 @@ impl SingleHeightConsensus {
                 if old.block_hash != vote.block_hash {
                     warn!("Conflicting votes: old={:?}, new={:?}", old, vote);
                     CONSENSUS_CONFLICTING_VOTES.increment(1);
-                    return Ok(ShcReturn::Tasks(Vec::new()));
-                } else {
-                    // Replay, ignore.
-                    return Ok(ShcReturn::Tasks(Vec::new()));
+                    let evidence = EquivocationEvidence::Vote(old.clone(), vote);
+                    self.record_equivocation(context, evidence).await;
                 }
+                // Otherwise it's a replay, ignore.
+                return Ok(ShcReturn::Tasks(Vec::new()));
             }
         }
         info!("Accepting {:?}", vote);
         let leader_fn = |round: Round| -> ValidatorId { context.proposer(self.height, round) };
-        let sm_events = self.state_machine.handle_event(sm_vote, &leader_fn);
+        let sm_events = self.state_machine.handle_vote(sm_vote, weight, &leader_fn);
         let ret = self.handle_state_machine_events(context, sm_events).await;
         context.set_height_and_round(self.height, self.state_machine.round()).await;
         ret
     }
 
+    fn voting_weight(&self, validator_id: ValidatorId) -> Option<VotingWeight> {
+        self.validators
+            .iter()
+            .find(|validator| validator.id == validator_id)
+            .map(|validator| validator.weight)
+    }
+
+    /// The evidence of the equivocations detected in this height, one per offender, in the order
+    /// they were detected.
+    #[cfg(test)]
+    pub(crate) fn equivocations(&self) -> &[EquivocationEvidence] {
+        &self.equivocations
+    }
+
+    // Compares a duplicate proposal with the proposal accepted for its round, and records an
+    // equivocation if they differ in their init or in their (known) commitments.
+    async fn check_conflicting_proposal<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        duplicate: ProposalEvidence,
+    ) {
+        let round = duplicate.init.round;
+        let Some(init) = self.proposal_inits.get(&round).copied() else {
+            return;
+        };
+        let accepted =
+            ProposalEvidence { init, commitment: self.proposals.get(&round).copied().flatten() };
+        let conflicting_content = matches!(
+            (accepted.commitment, duplicate.commitment),
+            (Some(accepted_commitment), Some(duplicate_commitment))
+                if accepted_commitment != duplicate_commitment
+        );
+        if accepted.init == duplicate.init && !conflicting_content {
+            // A replay of the same proposal, or one whose content isn't known yet.
+            return;
+        }
+        warn!("Conflicting proposals: old={:?}, new={:?}", accepted, duplicate);
+        CONSENSUS_CONFLICTING_PROPOSALS.increment(1);
+        self.record_equivocation(context, EquivocationEvidence::Proposal(accepted, duplicate))
+            .await;
+    }
+
+    async fn record_equivocation<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        evidence: EquivocationEvidence,
+    ) {
+        // A single piece of evidence is enough to prove the offender equivocated.
+        if self.equivocations.iter().any(|recorded| recorded.offender() == evidence.offender()) {
+            return;
+        }
+        self.equivocations.push(evidence.clone());
+        context.report_equivocation(evidence).await;
+    }
+
     // Handle events output by the state machine.
     async fn handle_state_machine_events<ContextT: ConsensusContext>(
         &mut self,
         context: &mut ContextT,
         mut events: VecDeque<StateMachineEvent>,
     ) -> Result<ShcReturn, ConsensusError> {
+        self.log_locked_and_valid_values()?;
+        self.report_prevote_quorum(context).await;
         let mut ret_val = Vec::new();
         while let Some(event) = events.pop_front() {
             trace!("Handling sm event: {:?}", event);
@@ -560,7 +795,9 @@ impl SingleHeightConsensus {
             round,
             block_hash: proposal_id,
             voter: self.id,
+            ..Default::default()
         };
+        let vote = context.sign_vote(vote).await?;
         if let Some(old) = votes.insert((round, self.id), vote.clone()) {
             return Err(ConsensusError::InternalInconsistency(format!(
                 "State machine should not send repeat votes: old={old:?}, new={vote:?}"
@@ -580,11 +817,60 @@ impl SingleHeightConsensus {
             }
         };
 
+        if let Some(write_ahead_log) = &mut self.write_ahead_log {
+            write_ahead_log.append(&WalEntry::Vote(vote.clone()))?;
+        }
         info!("Broadcasting {vote:?}");
         context.broadcast(vote).await?;
         Ok(vec![task])
     }
 
+    // Writes the values the state machine locked on and found valid to the write-ahead log, if
+    // they changed. Called before handling the events of the state machine, so they are durable
+    // before any vote or proposal which follows them is broadcast.
+    fn log_locked_and_valid_values(&mut self) -> Result<(), ConsensusError> {
+        let Some(write_ahead_log) = &mut self.write_ahead_log else {
+            return Ok(());
+        };
+        let locked_value_round = self.state_machine.locked_value_round();
+        if locked_value_round != self.logged_locked_value_round {
+            if let Some((proposal_id, round)) = locked_value_round {
+                write_ahead_log.append(&WalEntry::LockedValue(proposal_id, round))?;
+            }
+            self.logged_locked_value_round = locked_value_round;
+        }
+        let valid_value_round = self.state_machine.valid_value_round();
+        if valid_value_round != self.logged_valid_value_round {
+            if let Some((proposal_id, round)) = valid_value_round {
+                write_ahead_log.append(&WalEntry::ValidValue(proposal_id, round))?;
+            }
+            self.logged_valid_value_round = valid_value_round;
+        }
+        Ok(())
+    }
+
+    // Notifies the context of a new prevote quorum for a block, which the state machine records as
+    // its valid value.
+    async fn report_prevote_quorum<ContextT: ConsensusContext>(&mut self, context: &mut ContextT) {
+        if !context.pipelines_next_height() {
+            return;
+        }
+        let valid_value_round = self.state_machine.valid_value_round();
+        if valid_value_round == self.reported_prevote_quorum {
+            return;
+        }
+        self.reported_prevote_quorum = valid_value_round;
+        let Some((block, round)) = valid_value_round else {
+            return;
+        };
+        // Only the next height's first proposer can build on top of the block.
+        if context.proposer(self.height.unchecked_next(), 0) != self.id {
+            return;
+        }
+        debug!("Prevote quorum for {block} in round {round}.");
+        context.prevote_quorum_reached(self.height, round, block).await;
+    }
+
     async fn handle_state_machine_decision(
         &mut self,
         proposal_id: ProposalCommitment,
@@ -609,23 +895,25 @@ impl SingleHeightConsensus {
                 "StateMachine block hash should match the stored block. Shc.block_id: {block}"
             )));
         }
-        let supporting_precommits: Vec<Vote> = self
+        let (supporting_precommits, vote_weights): (Vec<Vote>, Vec<VotingWeight>) = self
             .validators
             .iter()
-            .filter_map(|v| {
-                let vote = self.precommits.get(&(round, *v))?;
-                if vote.block_hash == Some(proposal_id) { Some(vote.clone()) } else { None }
+            .filter_map(|validator| {
+                let vote = self.precommits.get(&(round, validator.id))?;
+                if vote.block_hash == Some(proposal_id) {
+                    Some((vote.clone(), validator.weight))
+                } else {
+                    None
+                }
             })
-            .collect();
+            .unzip();
 
-        // TODO(matan): Check actual weights.
-        let vote_weight = u64::try_from(supporting_precommits.len())
-            .expect("Should have way less than u64::MAX supporting votes");
+        let vote_weight = vote_weights.into_iter().sum();
         let total_weight = self.state_machine.total_weight();
 
         if !self.state_machine.quorum().is_met(vote_weight, total_weight) {
             let msg = format!(
-                "Not enough supporting votes. num_supporting_votes: {vote_weight} out of \
+                "Not enough supporting votes. supporting_weight: {vote_weight} out of \
                  {total_weight}. supporting_votes: {supporting_precommits:?}",
             );
             return Err(invalid_decision(msg));
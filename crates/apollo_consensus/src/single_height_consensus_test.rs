@@ -10,17 +10,32 @@ use super::SingleHeightConsensus;
 use crate::config::TimeoutsConfig;
 use crate::single_height_consensus::{ShcEvent, ShcReturn, ShcTask};
 use crate::state_machine::StateMachineEvent;
-use crate::test_utils::{precommit, prevote, MockTestContext, TestBlock, TestProposalPart};
-use crate::types::ValidatorId;
+use crate::test_utils::{
+    expect_vote_signatures,
+    precommit,
+    prevote,
+    validators,
+    MockTestContext,
+    TestBlock,
+    TestProposalPart,
+};
+use crate::types::{
+    EquivocationEvidence,
+    ProposalCommitment,
+    ProposalEvidence,
+    Validator,
+    ValidatorId,
+};
 use crate::votes_threshold::QuorumType;
+use crate::write_ahead_log::{WalEntry, WriteAheadLog};
 
 lazy_static! {
     static ref PROPOSER_ID: ValidatorId = DEFAULT_VALIDATOR_ID.into();
     static ref VALIDATOR_ID_1: ValidatorId = (DEFAULT_VALIDATOR_ID + 1).into();
     static ref VALIDATOR_ID_2: ValidatorId = (DEFAULT_VALIDATOR_ID + 2).into();
     static ref VALIDATOR_ID_3: ValidatorId = (DEFAULT_VALIDATOR_ID + 3).into();
-    static ref VALIDATORS: Vec<ValidatorId> =
-        vec![*PROPOSER_ID, *VALIDATOR_ID_1, *VALIDATOR_ID_2, *VALIDATOR_ID_3];
+    static ref VALIDATORS: Vec<Validator> =
+        validators(&[*PROPOSER_ID, *VALIDATOR_ID_1, *VALIDATOR_ID_2, *VALIDATOR_ID_3]);
     static ref BLOCK: TestBlock = TestBlock { content: vec![1, 2, 3], id: BlockHash(Felt::ONE) };
     static ref PROPOSAL_INIT: ProposalInit =
         ProposalInit { proposer: *PROPOSER_ID, ..Default::default() };
@@ -69,9 +84,28 @@ async fn handle_proposal(
     shc.handle_proposal(context, *PROPOSAL_INIT, content_receiver).await.unwrap()
 }
 
+// Sends another proposal for a round which already has one, and handles the commitment read from
+// its content.
+async fn handle_duplicate_proposal(
+    shc: &mut SingleHeightConsensus,
+    context: &mut MockTestContext,
+    init: ProposalInit,
+    proposal_commitment: ProposalCommitment,
+) -> ShcReturn {
+    let (mut content_sender, content_receiver) = mpsc::channel(CHANNEL_SIZE);
+    content_sender.try_send(TestProposalPart::Init(init)).unwrap();
+    content_sender.try_send(TestProposalPart::Fin(ProposalFin { proposal_commitment })).unwrap();
+
+    let mut tasks = shc.handle_proposal(context, init, content_receiver).await.unwrap();
+    let task = tasks.as_tasks_mut().unwrap().pop().unwrap();
+    assert_eq!(task.as_read_duplicate_proposal().unwrap().0, &init);
+    shc.handle_event(context, task.run().await).await.unwrap()
+}
+
 #[tokio::test]
 async fn proposer() {
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
 
     let mut shc = SingleHeightConsensus::new(
         BlockNumber(0),
@@ -80,6 +114,7 @@ async fn proposer() {
         VALIDATORS.to_vec(),
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
 
     context.expect_proposer().times(1).returning(move |_, _| *PROPOSER_ID);
@@ -151,6 +186,7 @@ async fn proposer() {
 #[tokio::test]
 async fn validator(repeat_proposal: bool) {
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
 
     // Creation calls to `context.validators`.
     let mut shc = SingleHeightConsensus::new(
@@ -160,6 +196,7 @@ async fn validator(repeat_proposal: bool) {
         VALIDATORS.to_vec(),
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
 
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
@@ -181,8 +218,10 @@ async fn validator(repeat_proposal: bool) {
         Ok(ShcReturn::Tasks(vec![prevote_task(Some(BLOCK.id.0), 0)]))
     );
     if repeat_proposal {
-        // Send the same proposal again, which should be ignored (no expectations).
-        let shc_ret = handle_proposal(&mut shc, &mut context).await;
+        // Send the same proposal again, which is only read to check for a conflict (no
+        // expectations).
+        let shc_ret =
+            handle_duplicate_proposal(&mut shc, &mut context, *PROPOSAL_INIT, BLOCK.id).await;
         assert_eq!(shc_ret, ShcReturn::Tasks(Vec::new()));
     }
     assert_eq!(
@@ -224,6 +263,7 @@ async fn validator(repeat_proposal: bool) {
 #[tokio::test]
 async fn vote_twice(same_vote: bool) {
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
 
     let mut shc = SingleHeightConsensus::new(
         BlockNumber(0),
@@ -232,6 +272,7 @@ async fn vote_twice(same_vote: bool) {
         VALIDATORS.to_vec(),
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
 
     context.expect_proposer().times(1).returning(move |_, _| *PROPOSER_ID);
@@ -274,8 +315,23 @@ async fn vote_twice(same_vote: bool) {
 
     let second_vote =
         if same_vote { first_vote.clone() } else { precommit(Some(Felt::TWO), 0, 0, *PROPOSER_ID) };
+    if !same_vote {
+        let expected_evidence = EquivocationEvidence::Vote(first_vote.clone(), second_vote.clone());
+        context
+            .expect_report_equivocation()
+            .times(1)
+            .withf(move |evidence| evidence == &expected_evidence)
+            .returning(move |_| ());
+    }
     let res = shc.handle_vote(&mut context, second_vote.clone()).await;
     assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
+    assert_eq!(shc.equivocations().is_empty(), same_vote);
+    if !same_vote {
+        // Further conflicting votes of the same voter aren't recorded or reported again.
+        let res = shc.handle_vote(&mut context, precommit(None, 0, 0, *PROPOSER_ID)).await;
+        assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
+        assert_eq!(shc.equivocations().len(), 1);
+    }
 
     let ShcReturn::Decision(decision) = shc
         .handle_vote(&mut context, precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2))
@@ -290,6 +346,7 @@ async fn vote_twice(same_vote: bool) {
 #[tokio::test]
 async fn rebroadcast_votes() {
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
 
     let mut shc = SingleHeightConsensus::new(
         BlockNumber(0),
@@ -298,6 +355,7 @@ async fn rebroadcast_votes() {
         VALIDATORS.to_vec(),
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
 
     context.expect_proposer().times(1).returning(move |_, _| *PROPOSER_ID);
@@ -354,6 +412,7 @@ async fn rebroadcast_votes() {
 #[tokio::test]
 async fn repropose() {
     let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
 
     let mut shc = SingleHeightConsensus::new(
         BlockNumber(0),
@@ -362,6 +421,7 @@ async fn repropose() {
         VALIDATORS.to_vec(),
         QuorumType::Byzantine,
         TIMEOUTS.clone(),
+        None,
     );
 
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
@@ -436,3 +496,252 @@ async fn repropose() {
     assert_eq!(decision.block, BLOCK.id);
     assert!(decision.precommits.into_iter().all(|item| precommits.contains(&item)));
 }
+
+#[tokio::test]
+async fn resume_from_write_ahead_log() {
+    let write_ahead_log_dir = tempfile::tempdir().unwrap();
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+
+    let (write_ahead_log, wal_entries) =
+        WriteAheadLog::open(write_ahead_log_dir.path(), BlockNumber(0)).unwrap();
+    assert!(wal_entries.is_empty());
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        VALIDATORS.to_vec(),
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        Some(write_ahead_log),
+    );
+
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(BLOCK.id).unwrap();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().times(2).returning(move |_| Ok(()));
+    // Prevote and precommit in favor of the block, locking on it.
+    handle_proposal(&mut shc, &mut context).await;
+    shc.handle_event(&mut context, VALIDATE_PROPOSAL_EVENT.clone()).await.unwrap();
+    shc.handle_vote(&mut context, prevote(Some(BLOCK.id.0), 0, 0, *PROPOSER_ID)).await.unwrap();
+    shc.handle_vote(&mut context, prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2)).await.unwrap();
+    // Simulate a restart.
+    drop(shc);
+
+    let (write_ahead_log, wal_entries) =
+        WriteAheadLog::open(write_ahead_log_dir.path(), BlockNumber(0)).unwrap();
+    assert_eq!(
+        wal_entries,
+        vec![
+            WalEntry::Vote(prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1)),
+            WalEntry::LockedValue(BLOCK.id, 0),
+            WalEntry::ValidValue(BLOCK.id, 0),
+            WalEntry::Vote(precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1)),
+        ]
+    );
+
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        VALIDATORS.to_vec(),
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        Some(write_ahead_log),
+    );
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &Vote| msg == &prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1))
+        .returning(move |_| Ok(()));
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &Vote| msg == &precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1))
+        .returning(move |_| Ok(()));
+    // The node rebroadcasts its votes instead of starting the height from scratch.
+    assert_eq!(
+        shc.resume(&mut context, wal_entries).await,
+        Ok(ShcReturn::Tasks(vec![
+            prevote_task(Some(BLOCK.id.0), 0),
+            precommit_task(Some(BLOCK.id.0), 0)
+        ]))
+    );
+
+    // The restored precommit counts towards the quorum.
+    assert_eq!(
+        shc.handle_vote(&mut context, precommit(Some(BLOCK.id.0), 0, 0, *PROPOSER_ID)).await,
+        Ok(ShcReturn::Tasks(Vec::new()))
+    );
+    assert_eq!(
+        shc.handle_vote(&mut context, precommit(None, 0, 0, *VALIDATOR_ID_2)).await,
+        Ok(ShcReturn::Tasks(vec![timeout_precommit_task(0)]))
+    );
+}
+
+#[tokio::test]
+async fn conflicting_proposals() {
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        VALIDATORS.to_vec(),
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        None,
+    );
+
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(BLOCK.id).unwrap();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    let shc_ret = handle_proposal(&mut shc, &mut context).await;
+    assert_eq!(shc_ret.as_tasks().unwrap()[0].as_validate_proposal().unwrap().0, &*PROPOSAL_INIT);
+
+    // A repeat of the same proposal isn't an equivocation.
+    let shc_ret = handle_duplicate_proposal(&mut shc, &mut context, *PROPOSAL_INIT, BLOCK.id).await;
+    assert_eq!(shc_ret, ShcReturn::Tasks(Vec::new()));
+    assert!(shc.equivocations().is_empty());
+
+    // A proposal with a different init for the same round is neither validated nor accepted, but
+    // reported as an equivocation.
+    let conflicting_init = ProposalInit { valid_round: Some(0), ..*PROPOSAL_INIT };
+    let expected_evidence = EquivocationEvidence::Proposal(
+        ProposalEvidence { init: *PROPOSAL_INIT, commitment: None },
+        ProposalEvidence { init: conflicting_init, commitment: Some(BLOCK.id) },
+    );
+    let reported_evidence = expected_evidence.clone();
+    context
+        .expect_report_equivocation()
+        .times(1)
+        .withf(move |evidence| evidence == &reported_evidence)
+        .returning(move |_| ());
+    let shc_ret =
+        handle_duplicate_proposal(&mut shc, &mut context, conflicting_init, BLOCK.id).await;
+    assert_eq!(shc_ret, ShcReturn::Tasks(Vec::new()));
+    assert_eq!(shc.equivocations(), &[expected_evidence]);
+}
+
+#[test_case(true; "validated_before_duplicate")]
+#[test_case(false; "duplicate_before_validated")]
+#[tokio::test]
+async fn conflicting_proposal_content(validated_first: bool) {
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        VALIDATORS.to_vec(),
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        None,
+    );
+
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(BLOCK.id).unwrap();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().returning(move |_| Ok(()));
+    handle_proposal(&mut shc, &mut context).await;
+
+    // The proposer sends different content under the same init.
+    let conflicting_commitment = BlockHash(Felt::TWO);
+    let expected_evidence = EquivocationEvidence::Proposal(
+        ProposalEvidence { init: *PROPOSAL_INIT, commitment: Some(BLOCK.id) },
+        ProposalEvidence { init: *PROPOSAL_INIT, commitment: Some(conflicting_commitment) },
+    );
+    let reported_evidence = expected_evidence.clone();
+    context
+        .expect_report_equivocation()
+        .times(1)
+        .withf(move |evidence| evidence == &reported_evidence)
+        .returning(move |_| ());
+    if validated_first {
+        shc.handle_event(&mut context, VALIDATE_PROPOSAL_EVENT.clone()).await.unwrap();
+        handle_duplicate_proposal(&mut shc, &mut context, *PROPOSAL_INIT, conflicting_commitment)
+            .await;
+    } else {
+        handle_duplicate_proposal(&mut shc, &mut context, *PROPOSAL_INIT, conflicting_commitment)
+            .await;
+        // The content can only be compared once the proposal is validated.
+        assert!(shc.equivocations().is_empty());
+        shc.handle_event(&mut context, VALIDATE_PROPOSAL_EVENT.clone()).await.unwrap();
+    }
+    assert_eq!(shc.equivocations(), &[expected_evidence]);
+}
+
+#[tokio::test]
+async fn stake_weighted_decision() {
+    let mut context = MockTestContext::new();
+    expect_vote_signatures(&mut context);
+
+    // A quorum requires a weight of more than 16/3, so the heavy validator along with the node are
+    // enough.
+    let validators = vec![
+        Validator { id: *PROPOSER_ID, weight: 1 },
+        Validator { id: *VALIDATOR_ID_1, weight: 1 },
+        Validator { id: *VALIDATOR_ID_2, weight: 5 },
+        Validator { id: *VALIDATOR_ID_3, weight: 1 },
+    ];
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        validators,
+        QuorumType::Byzantine,
+        TIMEOUTS.clone(),
+        None,
+    );
+
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(BLOCK.id).unwrap();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().times(2).returning(move |_| Ok(()));
+    handle_proposal(&mut shc, &mut context).await;
+    assert_eq!(
+        shc.handle_event(&mut context, VALIDATE_PROPOSAL_EVENT.clone()).await,
+        Ok(ShcReturn::Tasks(vec![prevote_task(Some(BLOCK.id.0), 0)]))
+    );
+
+    assert_eq!(
+        shc.handle_vote(&mut context, prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2)).await,
+        Ok(ShcReturn::Tasks(vec![timeout_prevote_task(0), precommit_task(Some(BLOCK.id.0), 0)]))
+    );
+    let ShcReturn::Decision(decision) = shc
+        .handle_vote(&mut context, precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2))
+        .await
+        .unwrap()
+    else {
+        panic!("Expected decision");
+    };
+    assert_eq!(decision.block, BLOCK.id);
+    assert_eq!(
+        decision.precommits,
+        vec![
+            precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1),
+            precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2)
+        ]
+    );
+}
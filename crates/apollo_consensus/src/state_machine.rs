@@ -21,7 +21,7 @@ use crate::metrics::{
     CONSENSUS_TIMEOUTS,
     LABEL_NAME_TIMEOUT_REASON,
 };
-use crate::types::{ProposalCommitment, Round, ValidatorId};
+use crate::types::{ConsensusError, ProposalCommitment, Round, ValidatorId, VotingWeight};
 use crate::votes_threshold::{QuorumType, VotesThreshold, ROUND_SKIP_THRESHOLD};
 
 /// Events which the state machine sends/receives.
@@ -51,7 +51,7 @@ pub enum StateMachineEvent {
     TimeoutPrecommit(Round),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Step {
     Propose,
     Prevote,
@@ -70,17 +70,20 @@ pub struct StateMachine {
     step: Step,
     quorum: VotesThreshold,
     round_skip_threshold: VotesThreshold,
-    total_weight: u64,
+    weight: VotingWeight,
+    total_weight: VotingWeight,
     is_observer: bool,
     // {round: (proposal_id, valid_round)}
     proposals: HashMap<Round, (Option<ProposalCommitment>, Option<Round>)>,
-    // {round: {proposal_id: vote_count}
-    prevotes: HashMap<Round, HashMap<Option<ProposalCommitment>, u32>>,
-    precommits: HashMap<Round, HashMap<Option<ProposalCommitment>, u32>>,
+    // {round: {proposal_id: vote_weight}
+    prevotes: HashMap<Round, HashMap<Option<ProposalCommitment>, VotingWeight>>,
+    precommits: HashMap<Round, HashMap<Option<ProposalCommitment>, VotingWeight>>,
     // When true, the state machine will wait for a GetProposal event, buffering all other input
     // events in `events_queue`.
     awaiting_get_proposal: bool,
-    events_queue: VecDeque<StateMachineEvent>,
+    // The events to handle, each with the voting weight of its sender. The weight is only used for
+    // votes.
+    events_queue: VecDeque<(StateMachineEvent, VotingWeight)>,
     locked_value_round: Option<(ProposalCommitment, Round)>,
     valid_value_round: Option<(ProposalCommitment, Round)>,
     prevote_quorum: HashSet<Round>,
@@ -89,10 +92,12 @@ pub struct StateMachine {
 }
 
 impl StateMachine {
+    /// weight - the voting weight of this node, which is zero for observers.
     /// total_weight - the total voting weight of all validators for this height.
     pub fn new(
         id: ValidatorId,
-        total_weight: u64,
+        weight: VotingWeight,
+        total_weight: VotingWeight,
         is_observer: bool,
         quorum_type: QuorumType,
     ) -> Self {
@@ -104,6 +109,7 @@ impl StateMachine {
             quorum: VotesThreshold::from_quorum_type(quorum_type),
             // Skip round threshold is 1/3 of the total weight.
             round_skip_threshold: ROUND_SKIP_THRESHOLD,
+            weight,
             total_weight,
             is_observer,
             proposals: HashMap::new(),
@@ -123,7 +129,7 @@ impl StateMachine {
         self.round
     }
 
-    pub fn total_weight(&self) -> u64 {
+    pub fn total_weight(&self) -> VotingWeight {
         self.total_weight
     }
 
@@ -131,6 +137,14 @@ impl StateMachine {
         &self.quorum
     }
 
+    pub fn locked_value_round(&self) -> Option<(ProposalCommitment, Round)> {
+        self.locked_value_round
+    }
+
+    pub fn valid_value_round(&self) -> Option<(ProposalCommitment, Round)> {
+        self.valid_value_round
+    }
+
     /// Starts the state machine, effectively calling `StartRound(0)` from the paper. This is
     /// needed to trigger the first leader to propose.
     /// See [`GetProposal`](StateMachineEvent::GetProposal)
@@ -141,7 +155,49 @@ impl StateMachine {
         self.advance_to_round(0, leader_fn)
     }
 
-    /// Process the incoming event.
+    /// Resumes the height after the node restarted in the middle of it, instead of calling
+    /// `start`. Restores the votes the node sent before restarting and the values it locked on and
+    /// found valid, and moves to the latest round and step in which it voted so it won't vote in
+    /// them again.
+    ///
+    /// Returns no events; the caller is responsible for rebroadcasting the restored votes.
+    pub fn resume(
+        &mut self,
+        own_votes: Vec<StateMachineEvent>,
+        locked_value_round: Option<(ProposalCommitment, Round)>,
+        valid_value_round: Option<(ProposalCommitment, Round)>,
+    ) -> Result<(), ConsensusError> {
+        for vote in own_votes {
+            let (votes, proposal_id, round, step) = match vote {
+                StateMachineEvent::Prevote(proposal_id, round) => {
+                    (&mut self.prevotes, proposal_id, round, Step::Prevote)
+                }
+                StateMachineEvent::Precommit(proposal_id, round) => {
+                    (&mut self.precommits, proposal_id, round, Step::Precommit)
+                }
+                _ => {
+                    return Err(ConsensusError::InternalInconsistency(format!(
+                        "Only votes can be restored. Got: {vote:?}"
+                    )));
+                }
+            };
+            *votes.entry(round).or_default().entry(proposal_id).or_insert(0) += self.weight;
+            if (round, &step) > (self.round, &self.step) {
+                self.round = round;
+                self.step = step;
+            }
+        }
+        self.locked_value_round = locked_value_round;
+        self.valid_value_round = valid_value_round;
+        CONSENSUS_ROUND.set(self.round);
+        info!(
+            "Resuming at round {} and step {:?}. locked_value_round: {:?}, valid_value_round: {:?}",
+            self.round, self.step, self.locked_value_round, self.valid_value_round
+        );
+        Ok(())
+    }
+
+    /// Process the incoming event, which must not be a vote (see `handle_vote`).
     ///
     /// If we are waiting for a response to [`GetProposal`](`StateMachineEvent::GetProposal`) all
     /// other incoming events are buffered until that response arrives.
@@ -155,6 +211,40 @@ impl StateMachine {
         event: StateMachineEvent,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
+    where
+        LeaderFn: Fn(Round) -> ValidatorId,
+    {
+        assert!(
+            !matches!(event, StateMachineEvent::Prevote(_, _) | StateMachineEvent::Precommit(_, _)),
+            "Votes must be handled by `handle_vote`. Got: {event:?}"
+        );
+        self.enqueue_and_handle(event, 0, leader_fn)
+    }
+
+    /// Process a vote from a peer, which is counted by the voting weight of the voter. Otherwise
+    /// the same as `handle_event`.
+    pub fn handle_vote<LeaderFn>(
+        &mut self,
+        vote: StateMachineEvent,
+        weight: VotingWeight,
+        leader_fn: &LeaderFn,
+    ) -> VecDeque<StateMachineEvent>
+    where
+        LeaderFn: Fn(Round) -> ValidatorId,
+    {
+        assert!(
+            matches!(vote, StateMachineEvent::Prevote(_, _) | StateMachineEvent::Precommit(_, _)),
+            "Expected a vote. Got: {vote:?}"
+        );
+        self.enqueue_and_handle(vote, weight, leader_fn)
+    }
+
+    fn enqueue_and_handle<LeaderFn>(
+        &mut self,
+        event: StateMachineEvent,
+        weight: VotingWeight,
+        leader_fn: &LeaderFn,
+    ) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
@@ -163,15 +253,15 @@ impl StateMachine {
         if self.awaiting_get_proposal {
             match event {
                 StateMachineEvent::GetProposal(_, round) if round == self.round => {
-                    self.events_queue.push_front(event);
+                    self.events_queue.push_front((event, weight));
                 }
                 _ => {
-                    self.events_queue.push_back(event);
+                    self.events_queue.push_back((event, weight));
                     return VecDeque::new();
                 }
             }
         } else {
-            self.events_queue.push_back(event);
+            self.events_queue.push_back((event, weight));
         }
 
         self.handle_enqueued_events(leader_fn)
@@ -185,10 +275,10 @@ impl StateMachine {
         LeaderFn: Fn(Round) -> ValidatorId,
     {
         let mut output_events = VecDeque::new();
-        while let Some(event) = self.events_queue.pop_front() {
+        while let Some((event, weight)) = self.events_queue.pop_front() {
             // Handle a specific event and then decide which of the output events should also be
             // sent to self.
-            let mut resultant_events = self.handle_event_internal(event, leader_fn);
+            let mut resultant_events = self.handle_event_internal(event, weight, leader_fn);
             while let Some(e) = resultant_events.pop_front() {
                 match e {
                     StateMachineEvent::Proposal(_, _, _)
@@ -197,7 +287,7 @@ impl StateMachine {
                         if self.is_observer {
                             continue;
                         }
-                        self.events_queue.push_back(e.clone());
+                        self.events_queue.push_back((e.clone(), self.weight));
                     }
                     StateMachineEvent::Decision(_, _) => {
                         output_events.push_back(e);
@@ -221,6 +311,7 @@ impl StateMachine {
     fn handle_event_internal<LeaderFn>(
         &mut self,
         event: StateMachineEvent,
+        weight: VotingWeight,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
     where
@@ -239,10 +330,10 @@ impl StateMachine {
                 self.handle_proposal(proposal_id, round, valid_round, leader_fn)
             }
             StateMachineEvent::Prevote(proposal_id, round) => {
-                self.handle_prevote(proposal_id, round, leader_fn)
+                self.handle_prevote(proposal_id, round, weight, leader_fn)
             }
             StateMachineEvent::Precommit(proposal_id, round) => {
-                self.handle_precommit(proposal_id, round, leader_fn)
+                self.handle_precommit(proposal_id, round, weight, leader_fn)
             }
             StateMachineEvent::Decision(_, _) => {
                 unimplemented!(
@@ -302,14 +393,13 @@ impl StateMachine {
         &mut self,
         proposal_id: Option<ProposalCommitment>,
         round: u32,
+        weight: VotingWeight,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
-        let prevote_count = self.prevotes.entry(round).or_default().entry(proposal_id).or_insert(0);
-        // TODO(matan): Use variable weight.
-        *prevote_count += 1;
+        *self.prevotes.entry(round).or_default().entry(proposal_id).or_insert(0) += weight;
         self.map_round_to_upons(round, leader_fn)
     }
 
@@ -330,15 +420,13 @@ impl StateMachine {
         &mut self,
         proposal_id: Option<ProposalCommitment>,
         round: u32,
+        weight: VotingWeight,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
-        let precommit_count =
-            self.precommits.entry(round).or_default().entry(proposal_id).or_insert(0);
-        // TODO(matan): Use variable weight.
-        *precommit_count += 1;
+        *self.precommits.entry(round).or_default().entry(proposal_id).or_insert(0) += weight;
         self.map_round_to_upons(round, leader_fn)
     }
 
@@ -603,24 +691,21 @@ impl StateMachine {
 
     fn round_has_enough_votes(
         &self,
-        votes: &HashMap<u32, HashMap<Option<ProposalCommitment>, u32>>,
+        votes: &HashMap<u32, HashMap<Option<ProposalCommitment>, VotingWeight>>,
         round: u32,
         threshold: &VotesThreshold,
     ) -> bool {
-        threshold
-            .is_met(votes.get(&round).map_or(0, |v| v.values().sum()).into(), self.total_weight)
+        threshold.is_met(votes.get(&round).map_or(0, |v| v.values().sum()), self.total_weight)
     }
 
     fn value_has_enough_votes(
         &self,
-        votes: &HashMap<u32, HashMap<Option<ProposalCommitment>, u32>>,
+        votes: &HashMap<u32, HashMap<Option<ProposalCommitment>, VotingWeight>>,
         round: u32,
         value: &Option<ProposalCommitment>,
         threshold: &VotesThreshold,
     ) -> bool {
-        threshold.is_met(
-            votes.get(&round).map_or(0, |v| *v.get(value).unwrap_or(&0)).into(),
-            self.total_weight,
-        )
+        threshold
+            .is_met(votes.get(&round).map_or(0, |v| *v.get(value).unwrap_or(&0)), self.total_weight)
     }
 }
@@ -8,7 +8,7 @@ use test_case::test_case;
 
 use super::Round;
 use crate::state_machine::{StateMachine, StateMachineEvent};
-use crate::types::{ProposalCommitment, ValidatorId};
+use crate::types::{ConsensusError, ProposalCommitment, ValidatorId, VotingWeight};
 use crate::votes_threshold::QuorumType;
 
 lazy_static! {
@@ -28,13 +28,19 @@ struct TestWrapper<LeaderFn: Fn(Round) -> ValidatorId> {
 impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
     pub fn new(
         id: ValidatorId,
-        total_weight: u64,
+        total_weight: VotingWeight,
         leader_fn: LeaderFn,
         is_observer: bool,
         quorum_type: QuorumType,
     ) -> Self {
         Self {
-            state_machine: StateMachine::new(id, total_weight, is_observer, quorum_type),
+            state_machine: StateMachine::new(
+                id,
+                if is_observer { 0 } else { 1 },
+                total_weight,
+                is_observer,
+                quorum_type,
+            ),
             leader_fn,
             events: VecDeque::new(),
         }
@@ -57,11 +63,11 @@ impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
     }
 
     pub fn send_prevote(&mut self, proposal_id: Option<ProposalCommitment>, round: Round) {
-        self.send_event(StateMachineEvent::Prevote(proposal_id, round))
+        self.send_vote(StateMachineEvent::Prevote(proposal_id, round), 1)
     }
 
     pub fn send_precommit(&mut self, proposal_id: Option<ProposalCommitment>, round: Round) {
-        self.send_event(StateMachineEvent::Precommit(proposal_id, round))
+        self.send_vote(StateMachineEvent::Precommit(proposal_id, round), 1)
     }
 
     pub fn send_timeout_propose(&mut self, round: Round) {
@@ -76,6 +82,10 @@ impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
         self.send_event(StateMachineEvent::TimeoutPrecommit(round))
     }
 
+    fn send_vote(&mut self, vote: StateMachineEvent, weight: VotingWeight) {
+        self.events.append(&mut self.state_machine.handle_vote(vote, weight, &self.leader_fn));
+    }
+
     fn send_event(&mut self, event: StateMachineEvent) {
         self.events.append(&mut self.state_machine.handle_event(event, &self.leader_fn));
     }
@@ -491,3 +501,82 @@ fn number_of_required_votes(quorum_type: QuorumType) {
     );
     assert!(wrapper.next_event().is_none());
 }
+
+#[test]
+fn resume_keeps_votes_and_locked_and_valid_values() {
+    let mut wrapper =
+        TestWrapper::new(*VALIDATOR_ID, 4, |_: Round| *PROPOSER_ID, false, QuorumType::Byzantine);
+
+    // The node prevoted and precommitted in favor of the proposal before restarting.
+    wrapper
+        .state_machine
+        .resume(
+            vec![
+                StateMachineEvent::Prevote(PROPOSAL_ID, ROUND),
+                StateMachineEvent::Precommit(PROPOSAL_ID, ROUND),
+            ],
+            Some((PROPOSAL_ID.unwrap(), ROUND)),
+            Some((PROPOSAL_ID.unwrap(), ROUND)),
+        )
+        .unwrap();
+    assert_eq!(wrapper.state_machine.round(), ROUND);
+    assert_eq!(wrapper.state_machine.locked_value_round(), Some((PROPOSAL_ID.unwrap(), ROUND)));
+    assert_eq!(wrapper.state_machine.valid_value_round(), Some((PROPOSAL_ID.unwrap(), ROUND)));
+
+    // The node's own precommit counts towards the quorum.
+    wrapper.send_precommit(None, ROUND);
+    assert!(wrapper.next_event().is_none());
+    wrapper.send_precommit(None, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrecommit(ROUND));
+    assert!(wrapper.next_event().is_none());
+
+    // The node is still locked, so it doesn't prevote for a different proposal in the next round.
+    wrapper.send_timeout_precommit(ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND + 1));
+    assert!(wrapper.next_event().is_none());
+    wrapper.send_proposal(Some(BlockHash(Felt::TWO)), ROUND + 1);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(None, ROUND + 1));
+    assert!(wrapper.next_event().is_none());
+}
+
+#[test]
+fn resume_rejects_non_votes() {
+    let mut wrapper =
+        TestWrapper::new(*VALIDATOR_ID, 4, |_: Round| *PROPOSER_ID, false, QuorumType::Byzantine);
+
+    assert!(matches!(
+        wrapper.state_machine.resume(vec![StateMachineEvent::TimeoutPropose(ROUND)], None, None),
+        Err(ConsensusError::InternalInconsistency(_))
+    ));
+}
+
+#[test]
+fn votes_are_counted_by_weight() {
+    // The node has a weight of 1 out of 10, so a quorum requires a weight of more than 20/3.
+    let mut wrapper =
+        TestWrapper::new(*VALIDATOR_ID, 10, |_: Round| *PROPOSER_ID, false, QuorumType::Byzantine);
+
+    wrapper.start();
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND));
+    wrapper.send_proposal(PROPOSAL_ID, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert!(wrapper.next_event().is_none());
+
+    // A single heavy validator isn't enough for a quorum along with the node: 1 + 5 < 20/3.
+    wrapper.send_vote(StateMachineEvent::Prevote(PROPOSAL_ID, ROUND), 5);
+    assert!(wrapper.next_event().is_none());
+
+    // Another light validator is: 1 + 5 + 1 > 20/3.
+    wrapper.send_prevote(PROPOSAL_ID, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert!(wrapper.next_event().is_none());
+
+    // The weight of the precommits is counted the same way.
+    wrapper.send_vote(StateMachineEvent::Precommit(PROPOSAL_ID, ROUND), 6);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrecommit(ROUND));
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Decision(PROPOSAL_ID.unwrap(), ROUND)
+    );
+}
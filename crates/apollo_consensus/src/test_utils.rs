@@ -1,14 +1,27 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::time::Duration;
 
-use apollo_protobuf::consensus::{ProposalInit, Vote, VoteType};
+use apollo_protobuf::consensus::{ProposalFin, ProposalInit, Vote, VoteType};
 use apollo_protobuf::converters::ProtobufConversionError;
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use mockall::mock;
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::crypto::utils::RawSignature;
 use starknet_types_core::felt::Felt;
 
-use crate::types::{ConsensusContext, ConsensusError, ProposalCommitment, Round, ValidatorId};
+use crate::types::{
+    vote_signing_payload,
+    ConsensusContext,
+    ConsensusError,
+    EquivocationEvidence,
+    ProposalCommitment,
+    Round,
+    Validator,
+    ValidatorId,
+};
 
 /// Define a consensus block which can be used to enable auto mocking Context.
 #[derive(Debug, PartialEq, Clone)]
@@ -20,6 +33,7 @@ pub struct TestBlock {
 #[derive(Debug, PartialEq, Clone)]
 pub enum TestProposalPart {
     Init(ProposalInit),
+    Fin(ProposalFin),
 }
 
 impl From<ProposalInit> for TestProposalPart {
@@ -31,15 +45,39 @@ impl From<ProposalInit> for TestProposalPart {
 impl TryFrom<TestProposalPart> for ProposalInit {
     type Error = ProtobufConversionError;
     fn try_from(part: TestProposalPart) -> Result<Self, Self::Error> {
-        let TestProposalPart::Init(init) = part;
-        Ok(init)
+        match part {
+            TestProposalPart::Init(init) => Ok(init),
+            _ => Err(ProtobufConversionError::WrongEnumVariant {
+                type_description: "TestProposalPart",
+                expected: "Init",
+                value_as_str: format!("{part:?}"),
+            }),
+        }
     }
 }
 
+impl TryFrom<TestProposalPart> for ProposalFin {
+    type Error = ProtobufConversionError;
+    fn try_from(part: TestProposalPart) -> Result<Self, Self::Error> {
+        match part {
+            TestProposalPart::Fin(fin) => Ok(fin),
+            _ => Err(ProtobufConversionError::WrongEnumVariant {
+                type_description: "TestProposalPart",
+                expected: "Fin",
+                value_as_str: format!("{part:?}"),
+            }),
+        }
+    }
+}
+
+// The first byte tells the variant apart.
 impl From<TestProposalPart> for Vec<u8> {
     fn from(part: TestProposalPart) -> Vec<u8> {
-        let TestProposalPart::Init(init) = part;
-        init.into()
+        let (variant, bytes): (u8, Vec<u8>) = match part {
+            TestProposalPart::Init(init) => (0, init.into()),
+            TestProposalPart::Fin(fin) => (1, fin.into()),
+        };
+        [vec![variant], bytes].concat()
     }
 }
 
@@ -47,7 +85,15 @@ impl TryFrom<Vec<u8>> for TestProposalPart {
     type Error = ProtobufConversionError;
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        Ok(TestProposalPart::Init(value.try_into()?))
+        match value.split_first() {
+            Some((0, bytes)) => Ok(TestProposalPart::Init(bytes.to_vec().try_into()?)),
+            Some((1, bytes)) => Ok(TestProposalPart::Fin(bytes.to_vec().try_into()?)),
+            _ => Err(ProtobufConversionError::WrongEnumVariant {
+                type_description: "TestProposalPart",
+                expected: "Init or Fin",
+                value_as_str: format!("{value:?}"),
+            }),
+        }
     }
 }
 
@@ -78,10 +124,14 @@ mock! {
             init: ProposalInit,
         );
 
-        async fn validators(&self, height: BlockNumber) -> Vec<ValidatorId>;
+        async fn validators(&self, height: BlockNumber) -> Vec<Validator>;
 
         fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId;
 
+        async fn sign_vote(&mut self, vote: Vote) -> Result<Vote, ConsensusError>;
+
+        fn verify_vote_signature(&self, vote: &Vote) -> bool;
+
         async fn broadcast(&mut self, message: Vote) -> Result<(), ConsensusError>;
 
         async fn decision_reached(
@@ -93,18 +143,58 @@ mock! {
         async fn try_sync(&mut self, height: BlockNumber) -> bool;
 
         async fn set_height_and_round(&mut self, height: BlockNumber, round: Round);
+
+        async fn report_equivocation(&mut self, evidence: EquivocationEvidence);
     }
 }
 
 pub fn prevote(block_felt: Option<Felt>, height: u64, round: u32, voter: ValidatorId) -> Vote {
     let block_hash = block_felt.map(BlockHash);
-    Vote { vote_type: VoteType::Prevote, height, round, block_hash, voter }
+    sign_vote(Vote {
+        vote_type: VoteType::Prevote,
+        height,
+        round,
+        block_hash,
+        voter,
+        ..Default::default()
+    })
 }
 
 pub fn precommit(block_felt: Option<Felt>, height: u64, round: u32, voter: ValidatorId) -> Vote {
     let block_hash = block_felt.map(BlockHash);
-    Vote { vote_type: VoteType::Precommit, height, round, block_hash, voter }
+    sign_vote(Vote {
+        vote_type: VoteType::Precommit,
+        height,
+        round,
+        block_hash,
+        voter,
+        ..Default::default()
+    })
 }
+
+/// A stand-in for a real signature, which binds the vote's content to its voter.
+fn test_signature(vote: &Vote) -> RawSignature {
+    let mut hasher = DefaultHasher::new();
+    vote_signing_payload(&ChainId::Mainnet, vote).hash(&mut hasher);
+    RawSignature(vec![*vote.voter.0.key(), Felt::from(hasher.finish())])
+}
+
+/// Signs the vote with the test signature scheme used by [`expect_vote_signatures`].
+pub fn sign_vote(vote: Vote) -> Vote {
+    let signature = test_signature(&vote);
+    Vote { signature, ..vote }
+}
+
+/// Sets the context to sign votes with, and verify them against, the test signature scheme.
+pub fn expect_vote_signatures(context: &mut MockTestContext) {
+    context.expect_sign_vote().returning(|vote| Ok(sign_vote(vote)));
+    context.expect_verify_vote_signature().returning(|vote| vote.signature == test_signature(vote));
+}
+/// Validators with the given IDs, each with a voting weight of 1.
+pub fn validators(ids: &[ValidatorId]) -> Vec<Validator> {
+    ids.iter().map(|id| Validator { id: *id, weight: 1 }).collect()
+}
+
 pub fn proposal_init(height: u64, round: u32, proposer: ValidatorId) -> ProposalInit {
     ProposalInit { height: BlockNumber(height), round, proposer, ..Default::default() }
 }
@@ -8,12 +8,14 @@ use apollo_network::network_manager::{
     GenericReceiver,
 };
 use apollo_network_types::network_types::BroadcastedMessageMetadata;
-use apollo_protobuf::consensus::{ProposalInit, Vote};
+use apollo_protobuf::consensus::{ProposalFin, ProposalInit, Vote};
 use apollo_protobuf::converters::ProtobufConversionError;
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber};
-use starknet_api::core::ContractAddress;
+use starknet_api::core::{ChainId, ContractAddress};
+use starknet_api::crypto::utils::RawSignature;
 
 /// Used to identify the node by consensus.
 /// 1. This ID is derived from the id registered with Starknet's L2 staking contract.
@@ -23,21 +25,32 @@ use starknet_api::core::ContractAddress;
 pub type ValidatorId = ContractAddress;
 pub type Round = u32;
 pub type ProposalCommitment = BlockHash;
+/// The voting power of a validator, which is proportional to its stake.
+pub type VotingWeight = u128;
+
+/// A member of the committee which proposes and votes on the blocks of a height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    pub id: ValidatorId,
+    pub weight: VotingWeight,
+}
 
 /// Interface for consensus to call out to the node.
 ///
 /// Function calls should be assumed to not be cancel safe.
 #[async_trait]
-pub trait ConsensusContext {
+pub trait ConsensusContext: Send {
     /// The parts of the proposal that are streamed in.
     /// Must contain at least the ProposalInit and ProposalFin.
     type ProposalPart: TryFrom<Vec<u8>, Error = ProtobufConversionError>
         + Into<Vec<u8>>
         + TryInto<ProposalInit, Error = ProtobufConversionError>
+        + TryInto<ProposalFin, Error = ProtobufConversionError>
         + From<ProposalInit>
         + Clone
         + Send
-        + Debug;
+        + Debug
+        + 'static;
 
     // TODO(matan): The oneshot for receiving the build block could be generalized to just be some
     // future which returns a block.
@@ -89,17 +102,25 @@ pub trait ConsensusContext {
     /// - `init`: The `ProposalInit` that is broadcast to the network.
     async fn repropose(&mut self, id: ProposalCommitment, init: ProposalInit);
 
-    /// Get the set of validators for a given height. These are the nodes that can propose and vote
-    /// on blocks.
-    // TODO(matan): We expect this to change in the future to BTreeMap. Why?
-    // 1. Map - The nodes will have associated information (e.g. voting weight).
-    // 2. BTreeMap - We want a stable ordering of the nodes for deterministic leader selection.
-    async fn validators(&self, height: BlockNumber) -> Vec<ValidatorId>;
+    /// Get the set of validators for a given height, along with their voting weights. These are
+    /// the nodes that can propose and vote on blocks. Votes are counted by the weights of the
+    /// voters. The order of the validators must be the same in all nodes, since it's used for
+    /// deterministic leader selection (see
+    /// [`ProposerRotation`](crate::proposer_rotation::ProposerRotation)).
+    async fn validators(&self, height: BlockNumber) -> Vec<Validator>;
 
     /// Calculates the ID of the Proposer based on the inputs.
     // TODO(matan): Consider passing the validator set in order to keep this sync.
     fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId;
 
+    /// Sign a vote with the node's validator key. Returns the vote with its signature set. The
+    /// signed message is [`vote_signing_payload`].
+    async fn sign_vote(&mut self, vote: Vote) -> Result<Vote, ConsensusError>;
+
+    /// Check that a vote received from the network was signed by its voter. Votes which fail this
+    /// check are dropped before reaching consensus, and the peer which sent them is reported.
+    fn verify_vote_signature(&self, vote: &Vote) -> bool;
+
     async fn broadcast(&mut self, message: Vote) -> Result<(), ConsensusError>;
 
     /// Update the context that a decision has been reached for a given height.
@@ -119,6 +140,29 @@ pub trait ConsensusContext {
     /// Update the context with the current height and round.
     /// Must be called at the beginning of each height.
     async fn set_height_and_round(&mut self, height: BlockNumber, round: Round);
+
+    /// Report that a validator equivocated in the current height. Called once per validator, with
+    /// the first conflicting message it sent along with the message it conflicts with.
+    async fn report_equivocation(&mut self, evidence: EquivocationEvidence);
+
+    /// Notifies the context that `block` received a quorum of prevotes (a polka) in `round` of
+    /// `height`. A polka makes `block` the likely decision of the height, so the context may start
+    /// preparing the next height on top of it. This is only a hint: a different block may still be
+    /// decided, in which case that work must be discarded. Called at most once per round, and only
+    /// on the proposer of the first round of the next height, if `pipelines_next_height` is true.
+    async fn prevote_quorum_reached(
+        &mut self,
+        _height: BlockNumber,
+        _round: Round,
+        _block: ProposalCommitment,
+    ) {
+    }
+
+    /// Whether the context prepares the next height on top of a prevote quorum. Otherwise, it isn't
+    /// notified of prevote quorums.
+    fn pipelines_next_height(&self) -> bool {
+        false
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -127,6 +171,49 @@ pub struct Decision {
     pub block: ProposalCommitment,
 }
 
+/// Evidence that a validator equivocated, i.e. sent two conflicting messages for the same height
+/// and round. The first message in each pair is the one that was received first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EquivocationEvidence {
+    /// Two votes of the same type for different values. Both votes are signed by the voter, so
+    /// anyone who knows its public key can verify the evidence.
+    Vote(Vote, Vote),
+    /// Two different proposals from the proposer of the round, either with different inits or with
+    /// the same init and different content. Proposals aren't signed, so unlike votes, this
+    /// evidence can't be verified by others.
+    Proposal(ProposalEvidence, ProposalEvidence),
+}
+
+impl EquivocationEvidence {
+    /// The validator that equivocated.
+    pub fn offender(&self) -> ValidatorId {
+        match self {
+            EquivocationEvidence::Vote(vote, _) => vote.voter,
+            EquivocationEvidence::Proposal(proposal, _) => proposal.init.proposer,
+        }
+    }
+}
+
+/// A proposal received from the proposer of a round.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProposalEvidence {
+    pub init: ProposalInit,
+    /// The commitment to the proposal's content, sent in its `ProposalFin`. `None` if it's
+    /// unknown, e.g. if the content wasn't fully received or failed validation.
+    pub commitment: Option<ProposalCommitment>,
+}
+
+/// The message a validator signs when voting: the chain ID, followed by the serialized vote without
+/// its signature. The chain ID keeps votes from being replayed on other chains, and is prefixed by
+/// its length so that the two parts can't be confused.
+pub fn vote_signing_payload(chain_id: &ChainId, vote: &Vote) -> Vec<u8> {
+    let chain_id = chain_id.to_string().into_bytes();
+    let chain_id_length =
+        u64::try_from(chain_id.len()).expect("Chain ID length should fit in u64.").to_be_bytes();
+    let vote: Vec<u8> = Vote { signature: RawSignature::default(), ..vote.clone() }.into();
+    [chain_id_length.as_slice(), &chain_id, &vote].concat()
+}
+
 pub struct BroadcastVoteChannel {
     pub broadcasted_messages_receiver:
         GenericReceiver<(Result<Vote, ProtobufConversionError>, BroadcastedMessageMetadata)>,
@@ -163,6 +250,10 @@ pub enum ConsensusError {
     InternalInconsistency(String),
     #[error("Block info conversion error: {0}")]
     BlockInfoConversion(#[from] starknet_api::StarknetApiError),
+    #[error("Write-ahead log error: {0}")]
+    WriteAheadLog(String),
+    #[error("Failed to sign vote: {0}")]
+    VoteSigning(String),
     #[error("{0}")]
     Other(String),
 }
@@ -1,13 +1,15 @@
 use serde::{Deserialize, Serialize};
 
+use crate::types::VotingWeight;
+
 #[cfg(test)]
 #[path = "votes_threshold_test.rs"]
 mod votes_threshold_test;
 
-/// Represents a threshold for the number of votes (out of total votes) required to meet a quorum.
-/// For example, a threshold of 2/3 means that more than 2/3 of the total votes must be in favor.
-/// Note that if the number of votes is exactly equal to the denominator, the threshold is not met.
-/// If the total number of votes is zero, the threshold is not met.
+/// Represents a threshold for the voting weight (out of the total voting weight) required to meet a
+/// quorum. For example, a threshold of 2/3 means that more than 2/3 of the total weight must be in
+/// favor. Note that if the weight is exactly equal to the threshold, the threshold is not met.
+/// If the total weight is zero, the threshold is not met.
 #[derive(Serialize, Deserialize)]
 pub struct VotesThreshold {
     numerator: u64,
@@ -42,8 +44,8 @@ impl VotesThreshold {
         }
     }
 
-    pub fn is_met(&self, amount: u64, total: u64) -> bool {
-        amount.checked_mul(self.denominator).expect("Numeric overflow")
-            > total.checked_mul(self.numerator).expect("Numeric overflow")
+    pub fn is_met(&self, amount: VotingWeight, total: VotingWeight) -> bool {
+        amount.checked_mul(self.denominator.into()).expect("Numeric overflow")
+            > total.checked_mul(self.numerator.into()).expect("Numeric overflow")
     }
 }
@@ -0,0 +1,146 @@
+//! A write-ahead log (WAL) of the commitments a node makes while running consensus.
+//!
+//! Every vote the node sends, and every update to the values it locked on and found valid, is
+//! appended to the log and synced to disk before the vote is broadcast. If the node crashes in the
+//! middle of a height, the log is replayed when it restarts so that it resumes the height with its
+//! previous commitments instead of voting differently in a round it already voted in.
+//!
+//! The log of each height is kept in a separate file, named after the height, in the log's
+//! directory. Logs of heights which are lower than the height being run are deleted. Each entry is
+//! tagged with the format version it was written in, and entries of a newer format than the one
+//! this node supports fail the replay instead of being misread.
+
+#[cfg(test)]
+#[path = "write_ahead_log_test.rs"]
+mod write_ahead_log_test;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use apollo_protobuf::consensus::Vote;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use tracing::{debug, warn};
+
+use crate::types::{ConsensusError, ProposalCommitment, Round};
+
+const FILE_EXTENSION: &str = "wal";
+const FORMAT_VERSION: u32 = 1;
+
+/// A commitment of the node, recorded in the write-ahead log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum WalEntry {
+    /// A vote that the node is about to broadcast.
+    Vote(Vote),
+    /// The value that the node locked on, and the round in which it locked on it.
+    LockedValue(ProposalCommitment, Round),
+    /// The value that the node found valid, and the round in which it found it valid.
+    ValidValue(ProposalCommitment, Round),
+}
+
+/// An entry as it's written in the log, along with the format version it was written in.
+#[derive(Serialize, Deserialize)]
+struct VersionedWalEntry<Entry> {
+    version: u32,
+    entry: Entry,
+}
+
+/// The write-ahead log of a single height.
+#[derive(Debug)]
+pub(crate) struct WriteAheadLog {
+    file: File,
+}
+
+impl WriteAheadLog {
+    /// Opens the log of the given height in `dir`, creating it if needed, and deletes the logs of
+    /// lower heights. Returns the log along with the entries that were already written to it.
+    pub(crate) fn open(
+        dir: &Path,
+        height: BlockNumber,
+    ) -> Result<(Self, Vec<WalEntry>), ConsensusError> {
+        fs::create_dir_all(dir).map_err(wal_error)?;
+        delete_lower_heights(dir, height)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(height_path(dir, height))
+            .map_err(wal_error)?;
+        // Make sure the creation of the file is durable.
+        File::open(dir).and_then(|dir| dir.sync_all()).map_err(wal_error)?;
+        let entries = read_entries(&mut file)?;
+        debug!("Opened the write-ahead log of height {height} with {} entries.", entries.len());
+        Ok((Self { file }, entries))
+    }
+
+    /// Appends the entry to the log. Returns only once the entry is synced to disk.
+    pub(crate) fn append(&mut self, entry: &WalEntry) -> Result<(), ConsensusError> {
+        let mut line = serde_json::to_vec(&VersionedWalEntry { version: FORMAT_VERSION, entry })
+            .map_err(wal_error)?;
+        line.push(b'\n');
+        self.file.write_all(&line).map_err(wal_error)?;
+        self.file.sync_data().map_err(wal_error)
+    }
+}
+
+fn height_path(dir: &Path, height: BlockNumber) -> PathBuf {
+    dir.join(format!("{}.{FILE_EXTENSION}", height.0))
+}
+
+fn delete_lower_heights(dir: &Path, height: BlockNumber) -> Result<(), ConsensusError> {
+    for dir_entry in fs::read_dir(dir).map_err(wal_error)? {
+        let path = dir_entry.map_err(wal_error)?.path();
+        if path.extension().is_none_or(|extension| extension != FILE_EXTENSION) {
+            continue;
+        }
+        let Some(log_height) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        if log_height < height.0 {
+            fs::remove_file(&path).map_err(wal_error)?;
+        }
+    }
+    Ok(())
+}
+
+// Each entry is written in a single line. Since the node may have crashed in the middle of writing
+// an entry, a trailing line without a newline is a partially written entry. Such an entry was never
+// acted upon, so it's removed in order to append the next entries after the complete ones.
+fn read_entries(file: &mut File) -> Result<Vec<WalEntry>, ConsensusError> {
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).map_err(wal_error)?;
+    let complete_len = content.iter().rposition(|byte| *byte == b'\n').map_or(0, |i| i + 1);
+    if complete_len < content.len() {
+        warn!("Removing a partially written entry from the write-ahead log.");
+        file.set_len(u64::try_from(complete_len).expect("File length should fit in u64"))
+            .map_err(wal_error)?;
+        file.sync_data().map_err(wal_error)?;
+    }
+    content[..complete_len]
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(parse_entry)
+        .collect()
+}
+
+fn parse_entry(line: &[u8]) -> Result<WalEntry, ConsensusError> {
+    let VersionedWalEntry { version, entry } =
+        serde_json::from_slice::<VersionedWalEntry<serde_json::Value>>(line).map_err(wal_error)?;
+    if version > FORMAT_VERSION {
+        return Err(ConsensusError::WriteAheadLog(format!(
+            "Entry of format version {version} is newer than the supported format version \
+             {FORMAT_VERSION}: {entry}"
+        )));
+    }
+    serde_json::from_value(entry).map_err(wal_error)
+}
+
+fn wal_error(err: impl ToString) -> ConsensusError {
+    ConsensusError::WriteAheadLog(err.to_string())
+}
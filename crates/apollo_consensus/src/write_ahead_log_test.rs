@@ -0,0 +1,87 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use apollo_protobuf::consensus::DEFAULT_VALIDATOR_ID;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_types_core::felt::Felt;
+
+use crate::test_utils::{precommit, prevote};
+use crate::types::ConsensusError;
+use crate::write_ahead_log::{height_path, WalEntry, WriteAheadLog};
+
+const HEIGHT: BlockNumber = BlockNumber(1);
+
+fn entries() -> Vec<WalEntry> {
+    vec![
+        WalEntry::Vote(prevote(Some(Felt::ONE), HEIGHT.0, 0, DEFAULT_VALIDATOR_ID.into())),
+        WalEntry::LockedValue(BlockHash(Felt::ONE), 0),
+        WalEntry::ValidValue(BlockHash(Felt::ONE), 0),
+        WalEntry::Vote(precommit(Some(Felt::ONE), HEIGHT.0, 0, DEFAULT_VALIDATOR_ID.into())),
+    ]
+}
+
+#[test]
+fn reopen_returns_appended_entries() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let (mut write_ahead_log, read_entries) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    assert!(read_entries.is_empty());
+    for entry in entries() {
+        write_ahead_log.append(&entry).unwrap();
+    }
+    drop(write_ahead_log);
+
+    let (_, read_entries) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    assert_eq!(read_entries, entries());
+}
+
+#[test]
+fn open_deletes_lower_heights() {
+    let dir = tempfile::tempdir().unwrap();
+    for height in [HEIGHT.prev().unwrap(), HEIGHT.unchecked_next()] {
+        let (mut write_ahead_log, _) = WriteAheadLog::open(dir.path(), height).unwrap();
+        write_ahead_log.append(&entries()[1]).unwrap();
+    }
+
+    WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    assert!(!height_path(dir.path(), HEIGHT.prev().unwrap()).exists());
+    // The log of a higher height may still be needed.
+    assert!(height_path(dir.path(), HEIGHT.unchecked_next()).exists());
+}
+
+#[test]
+fn open_removes_partially_written_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut write_ahead_log, _) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    write_ahead_log.append(&entries()[0]).unwrap();
+    drop(write_ahead_log);
+    // Simulate a crash in the middle of writing an entry.
+    let mut file = OpenOptions::new().append(true).open(height_path(dir.path(), HEIGHT)).unwrap();
+    file.write_all(br#"{"version":1,"entry":{"LockedValue":["#).unwrap();
+    drop(file);
+
+    let (mut write_ahead_log, read_entries) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    assert_eq!(read_entries, entries()[..1]);
+    write_ahead_log.append(&entries()[1]).unwrap();
+    drop(write_ahead_log);
+
+    let (_, read_entries) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    assert_eq!(read_entries, entries()[..2]);
+}
+
+#[test]
+fn open_rejects_newer_format_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut write_ahead_log, _) = WriteAheadLog::open(dir.path(), HEIGHT).unwrap();
+    write_ahead_log.append(&entries()[0]).unwrap();
+    drop(write_ahead_log);
+    let mut file = OpenOptions::new().append(true).open(height_path(dir.path(), HEIGHT)).unwrap();
+    file.write_all(b"{\"version\":2,\"entry\":{\"NewEntry\":[]}}\n").unwrap();
+    drop(file);
+
+    let error = WriteAheadLog::open(dir.path(), HEIGHT).unwrap_err();
+    assert!(
+        matches!(&error, ConsensusError::WriteAheadLog(message) if message.contains("version 2")),
+        "{error:?}"
+    );
+}
@@ -13,6 +13,10 @@ use apollo_consensus::stream_handler::StreamHandler;
 use apollo_consensus::types::ConsensusError;
 use apollo_consensus::votes_threshold::QuorumType;
 use apollo_consensus_orchestrator::cende::CendeAmbassador;
+use apollo_consensus_orchestrator::committee_provider::{
+    CommitteeProvider,
+    StakingCommitteeProvider,
+};
 use apollo_consensus_orchestrator::sequencer_consensus_context::{
     SequencerConsensusContext,
     SequencerConsensusContextDeps,
@@ -21,23 +25,29 @@ use apollo_infra::component_definitions::ComponentStarter;
 use apollo_infra_utils::type_name::short_type_name;
 use apollo_l1_gas_price::eth_to_strk_oracle::EthToStrkOracleClient;
 use apollo_l1_gas_price_types::L1GasPriceProviderClient;
+use apollo_network::allowlist::StakerAuthentication;
 use apollo_network::gossipsub_impl::Topic;
+use apollo_network::network_manager::admin::NetworkAdminRequests;
 use apollo_network::network_manager::metrics::{BroadcastNetworkMetrics, NetworkMetrics};
 use apollo_network::network_manager::{BroadcastTopicChannels, NetworkManager};
+use apollo_network::utils::peer_id_from_secret_key;
 use apollo_protobuf::consensus::{HeightAndRound, ProposalPart, StreamMessage, Vote};
 use apollo_reverts::revert_blocks_and_eternal_pending;
-use apollo_signature_manager_types::SharedSignatureManagerClient;
+use apollo_signature_manager::signature_manager::verify_identity;
+use apollo_signature_manager_types::{PeerId, SharedSignatureManagerClient};
 use apollo_state_sync_types::communication::SharedStateSyncClient;
 use apollo_time::time::DefaultClock;
 use async_trait::async_trait;
 use futures::channel::mpsc;
+use futures::FutureExt;
 use starknet_api::block::BlockNumber;
-use tracing::{info, info_span, Instrument};
+use tracing::{info, info_span, warn, Instrument};
 
 use crate::config::ConsensusManagerConfig;
 use crate::metrics::{
     CONSENSUS_NUM_BLACKLISTED_PEERS,
     CONSENSUS_NUM_CONNECTED_PEERS,
+    CONSENSUS_NUM_REJECTED_CONNECTIONS,
     CONSENSUS_PROPOSALS_NUM_RECEIVED_MESSAGES,
     CONSENSUS_PROPOSALS_NUM_SENT_MESSAGES,
     CONSENSUS_VOTES_NUM_RECEIVED_MESSAGES,
@@ -52,6 +62,8 @@ pub struct ConsensusManager {
     pub class_manager_client: SharedClassManagerClient,
     pub signature_manager_client: SharedSignatureManagerClient,
     l1_gas_price_provider: Arc<dyn L1GasPriceProviderClient>,
+    // Taken by the network manager once it's created.
+    network_admin_requests: Option<NetworkAdminRequests>,
 }
 
 impl ConsensusManager {
@@ -62,6 +74,7 @@ impl ConsensusManager {
         class_manager_client: SharedClassManagerClient,
         signature_manager_client: SharedSignatureManagerClient,
         l1_gas_price_provider: Arc<dyn L1GasPriceProviderClient>,
+        network_admin_requests: Option<NetworkAdminRequests>,
     ) -> Self {
         Self {
             config,
@@ -70,10 +83,11 @@ impl ConsensusManager {
             class_manager_client,
             signature_manager_client,
             l1_gas_price_provider,
+            network_admin_requests,
         }
     }
 
-    pub async fn run(&self) -> Result<(), ConsensusError> {
+    pub async fn run(&mut self) -> Result<(), ConsensusError> {
         if self.config.revert_config.should_revert {
             self.revert_batcher_blocks(self.config.revert_config.revert_up_to_and_including).await;
         }
@@ -96,11 +110,20 @@ impl ConsensusManager {
         let network_manager_metrics = Some(NetworkMetrics {
             num_connected_peers: CONSENSUS_NUM_CONNECTED_PEERS,
             num_blacklisted_peers: CONSENSUS_NUM_BLACKLISTED_PEERS,
+            num_rejected_connections: CONSENSUS_NUM_REJECTED_CONNECTIONS,
             broadcast_metrics_by_topic: Some(broadcast_metrics_by_topic),
             sqmr_metrics: None,
         });
-        let mut network_manager =
-            NetworkManager::new(self.config.network_config.clone(), None, network_manager_metrics);
+        let staker_authentication = self.staker_authentication();
+        let mut network_manager = NetworkManager::new(
+            self.config.network_config.clone(),
+            None,
+            network_manager_metrics,
+            staker_authentication,
+        );
+        if let Some(network_admin_requests) = self.network_admin_requests.take() {
+            network_manager.serve_admin_requests(network_admin_requests);
+        }
 
         let proposals_broadcast_channels = network_manager
             .register_broadcast_topic::<StreamMessage<ProposalPart, HeightAndRound>>(
@@ -169,6 +192,16 @@ impl ConsensusManager {
                 clock: Arc::new(DefaultClock),
                 outbound_proposal_sender: outbound_internal_sender,
                 vote_broadcast_client: votes_broadcast_channels.broadcast_topic_client.clone(),
+                signature_manager_client: Arc::clone(&self.signature_manager_client),
+                allowed_stakers_sender: network_manager.get_allowed_stakers_sender(),
+                reported_stakers_sender: network_manager.get_reported_stakers_sender(),
+                committee_provider: self.config.context_config.use_staking_committee.then(|| {
+                    Arc::new(StakingCommitteeProvider::new(
+                        &self.config.context_config,
+                        Arc::clone(&self.state_sync_client),
+                        Arc::clone(&self.class_manager_client),
+                    )) as Arc<dyn CommitteeProvider>
+                }),
             },
         );
 
@@ -188,6 +221,7 @@ impl ConsensusManager {
             timeouts: self.config.consensus_config.timeouts.clone(),
             sync_retry_interval: self.config.consensus_config.sync_retry_interval,
             quorum_type,
+            write_ahead_log_dir: self.config.consensus_config.write_ahead_log_dir.clone(),
         };
         let consensus_fut = apollo_consensus::run_consensus(
             run_consensus_args,
@@ -212,6 +246,46 @@ impl ConsensusManager {
         }
     }
 
+    // Lets the nodes of the committee's stakers connect when the network allowlist is enabled, and
+    // proves to them that this node is run by a staker.
+    fn staker_authentication(&self) -> Option<StakerAuthentication> {
+        let network_config = &self.config.network_config;
+        if !network_config.allowlist_config.enabled {
+            return None;
+        }
+        let secret_key = network_config
+            .secret_key
+            .clone()
+            .expect("The network allowlist requires a secret key, so that the peer id is known.");
+        let peer_id = PeerId(peer_id_from_secret_key(secret_key).to_bytes());
+        let signature_manager_client = self.signature_manager_client.clone();
+        Some(StakerAuthentication {
+            sign_identity: Arc::new(move |nonce| {
+                let signature_manager_client = signature_manager_client.clone();
+                let peer_id = peer_id.clone();
+                async move {
+                    signature_manager_client
+                        .identify(peer_id, nonce)
+                        .await
+                        .inspect_err(|e| {
+                            warn!("Failed to sign the identity proof of this node: {e}")
+                        })
+                        .ok()
+                }
+                .boxed()
+            }),
+            verify_identity: Arc::new(|peer_id, identity_proof, staker| {
+                verify_identity(
+                    PeerId(peer_id.to_bytes()),
+                    identity_proof.nonce,
+                    identity_proof.signature.clone(),
+                    *staker,
+                )
+                .unwrap_or(false)
+            }),
+        })
+    }
+
     // Performs reverts to the batcher.
     async fn revert_batcher_blocks(&self, revert_up_to_and_including: BlockNumber) {
         // If we revert all blocks up to height X (including), the new height marker will be X.
@@ -247,6 +321,7 @@ pub fn create_consensus_manager(
     class_manager_client: SharedClassManagerClient,
     signature_manager_client: SharedSignatureManagerClient,
     l1_gas_price_provider: Arc<dyn L1GasPriceProviderClient>,
+    network_admin_requests: Option<NetworkAdminRequests>,
 ) -> ConsensusManager {
     ConsensusManager::new(
         config,
@@ -255,6 +330,7 @@ pub fn create_consensus_manager(
         class_manager_client,
         signature_manager_client,
         l1_gas_price_provider,
+        network_admin_requests,
     )
 }
 
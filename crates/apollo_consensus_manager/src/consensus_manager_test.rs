@@ -43,13 +43,14 @@ async fn revert_batcher_blocks() {
         ..Default::default()
     };
 
-    let consensus_manager = ConsensusManager::new(
+    let mut consensus_manager = ConsensusManager::new(
         manager_config,
         Arc::new(mock_batcher_client),
         Arc::new(MockStateSyncClient::new()),
         Arc::new(EmptyClassManagerClient),
         Arc::new(MockSignatureManagerClient::new()),
         Arc::new(MockL1GasPriceProviderClient::new()),
+        None,
     );
 
     // TODO(Shahak, dvir): try to solve this better (the test will take 100 milliseconds to run).
@@ -62,13 +63,14 @@ async fn no_reverts_without_config() {
     mock_batcher.expect_revert_block().times(0).returning(|_| Ok(()));
     mock_batcher.expect_get_height().returning(|| Ok(GetHeightResponse { height: BlockNumber(0) }));
 
-    let consensus_manager = ConsensusManager::new(
+    let mut consensus_manager = ConsensusManager::new(
         ConsensusManagerConfig::default(),
         Arc::new(mock_batcher),
         Arc::new(MockStateSyncClient::new()),
         Arc::new(EmptyClassManagerClient),
         Arc::new(MockSignatureManagerClient::new()),
         Arc::new(MockL1GasPriceProviderClient::new()),
+        None,
     );
 
     // TODO(Shahak, dvir): try to solve this better (the test will take 100 milliseconds to run).
@@ -5,6 +5,7 @@ define_metrics!(
         // topic agnostic metrics
         MetricGauge { CONSENSUS_NUM_CONNECTED_PEERS, "apollo_consensus_num_connected_peers", "The number of connected peers to the consensus p2p component" },
         MetricGauge { CONSENSUS_NUM_BLACKLISTED_PEERS, "apollo_consensus_num_blacklisted_peers", "The number of currently blacklisted peers by the consensus component" },
+        MetricCounter { CONSENSUS_NUM_REJECTED_CONNECTIONS, "apollo_consensus_num_rejected_connections", "The number of connections rejected by the consensus p2p component since the peer is not in the allowlist", init = 0 },
 
         // Votes topic metrics
         MetricCounter { CONSENSUS_VOTES_NUM_SENT_MESSAGES, "apollo_consensus_votes_num_sent_messages", "The number of messages sent by the consensus p2p component over the Votes topic", init = 0 },
@@ -27,7 +27,7 @@ use apollo_protobuf::consensus::{
 };
 use apollo_state_sync_types::communication::StateSyncClientError;
 use apollo_time::time::{Clock, DateTime};
-use starknet_api::block::{BlockHash, GasPrice};
+use starknet_api::block::{BlockHash, BlockNumber, GasPrice};
 use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::core::ContractAddress;
 use starknet_api::data_availability::L1DataAvailabilityMode;
@@ -62,6 +62,8 @@ pub(crate) struct ProposalBuildArguments {
     pub cancel_token: CancellationToken,
     pub previous_block_info: Option<ConsensusBlockInfo>,
     pub proposal_round: Round,
+    // Set if the batcher already started building the proposal, ahead of the height.
+    pub pipelined_block_info: Option<ConsensusBlockInfo>,
 }
 
 type BuildProposalResult<T> = Result<T, BuildProposalError>;
@@ -96,7 +98,10 @@ pub(crate) async fn build_proposal(
     mut args: ProposalBuildArguments,
 ) -> BuildProposalResult<ProposalCommitment> {
     let batcher_deadline = args.deps.clock.now() + args.batcher_timeout;
-    let block_info = initiate_build(&args).await?;
+    let block_info = match args.pipelined_block_info.take() {
+        Some(block_info) => block_info,
+        None => initiate_build(&args).await?,
+    };
     args.stream_sender
         .send(ProposalPart::Init(args.proposal_init))
         .await
@@ -132,40 +137,70 @@ pub(crate) async fn build_proposal(
 }
 
 async fn initiate_build(args: &ProposalBuildArguments) -> BuildProposalResult<ConsensusBlockInfo> {
-    let batcher_timeout = chrono::Duration::from_std(args.batcher_timeout)
+    start_building(
+        &args.deps,
+        args.proposal_init.height,
+        args.proposal_round,
+        args.proposal_id,
+        args.batcher_timeout,
+        args.l1_da_mode,
+        &args.gas_price_params,
+        args.l2_gas_price,
+        args.builder_address,
+        args.previous_block_info.as_ref(),
+    )
+    .await
+}
+
+/// Asks the batcher to start building a proposal, and returns the block info it is built with.
+/// Used directly to build a proposal for the next height before the current one is decided.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn start_building(
+    deps: &SequencerConsensusContextDeps,
+    height: BlockNumber,
+    proposal_round: Round,
+    proposal_id: ProposalId,
+    batcher_timeout: Duration,
+    l1_da_mode: L1DataAvailabilityMode,
+    gas_price_params: &GasPriceParams,
+    l2_gas_price: GasPrice,
+    builder_address: ContractAddress,
+    previous_block_info: Option<&ConsensusBlockInfo>,
+) -> BuildProposalResult<ConsensusBlockInfo> {
+    let batcher_timeout = chrono::Duration::from_std(batcher_timeout)
         .expect("Can't convert timeout to chrono::Duration");
-    let timestamp = args.deps.clock.unix_now();
+    let timestamp = deps.clock.unix_now();
     let (eth_to_fri_rate, l1_prices) = get_oracle_rate_and_prices(
-        args.deps.eth_to_strk_oracle_client.clone(),
-        args.deps.l1_gas_price_provider.clone(),
+        deps.eth_to_strk_oracle_client.clone(),
+        deps.l1_gas_price_provider.clone(),
         timestamp,
-        args.previous_block_info.as_ref(),
-        &args.gas_price_params,
+        previous_block_info,
+        gas_price_params,
     )
     .await;
 
     let block_info = ConsensusBlockInfo {
-        height: args.proposal_init.height,
+        height,
         timestamp,
-        builder: args.builder_address,
-        l1_da_mode: args.l1_da_mode,
-        l2_gas_price_fri: args.l2_gas_price,
+        builder: builder_address,
+        l1_da_mode,
+        l2_gas_price_fri: l2_gas_price,
         l1_gas_price_wei: l1_prices.base_fee_per_gas,
         l1_data_gas_price_wei: l1_prices.blob_fee,
         eth_to_fri_rate,
     };
 
     let retrospective_block_hash =
-        retrospective_block_hash(args.deps.state_sync_client.clone(), &block_info).await?;
+        retrospective_block_hash(deps.state_sync_client.clone(), &block_info).await?;
     let build_proposal_input = ProposeBlockInput {
-        proposal_id: args.proposal_id,
-        deadline: args.deps.clock.now() + batcher_timeout,
+        proposal_id,
+        deadline: deps.clock.now() + batcher_timeout,
         retrospective_block_hash,
         block_info: convert_to_sn_api_block_info(&block_info)?,
-        proposal_round: args.proposal_round,
+        proposal_round,
     };
     debug!("Initiating build proposal: {build_proposal_input:?}");
-    args.deps.batcher.propose_block(build_proposal_input.clone()).await.map_err(|err| {
+    deps.batcher.propose_block(build_proposal_input.clone()).await.map_err(|err| {
         BuildProposalError::Batcher(
             format!("Failed to initiate build proposal {build_proposal_input:?}."),
             err,
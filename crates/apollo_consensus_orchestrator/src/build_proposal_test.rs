@@ -74,6 +74,7 @@ impl From<TestProposalBuildArguments> for ProposalBuildArguments {
             cancel_token: args.cancel_token,
             previous_block_info: args.previous_block_info,
             proposal_round: args.proposal_round,
+            pipelined_block_info: None,
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use apollo_consensus::types::Validator;
 use blockifier::context::BlockContext;
 use blockifier::execution::call_info::Retdata;
 use blockifier::execution::entry_point::call_view_entry_point;
@@ -161,6 +162,13 @@ impl TryFrom<[Felt; Self::CAIRO_OBJECT_NUM_FELTS]> for Staker {
     }
 }
 
+// The voting power of a committee member in consensus is its staking weight.
+impl From<&Staker> for Validator {
+    fn from(staker: &Staker) -> Self {
+        Self { id: staker.address, weight: staker.weight.0 }
+    }
+}
+
 #[cfg(test)]
 impl From<&Staker> for Vec<Felt> {
     fn from(staker: &Staker) -> Self {
@@ -0,0 +1,239 @@
+use std::sync::{Arc, Mutex};
+
+use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_state_sync_types::communication::{SharedStateSyncClient, StateSyncClientError};
+use apollo_state_sync_types::errors::StateSyncError;
+use async_trait::async_trait;
+use blockifier::blockifier_versioned_constants::VersionedConstants;
+use blockifier::bouncer::BouncerConfig;
+use blockifier::context::{BlockContext, ChainInfo};
+use blockifier::execution::contract_class::RunnableCompiledClass;
+use blockifier::state::errors::StateError;
+use blockifier::state::state_api::{StateReader, StateResult};
+#[cfg(test)]
+use mockall::automock;
+use starknet_api::block::{BlockInfo, BlockNumber};
+use starknet_api::contract_class::ContractClass;
+use starknet_api::core::{ChainId, ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::state::StorageKey;
+use starknet_types_core::felt::Felt;
+use thiserror::Error;
+use tokio::runtime::Handle;
+
+use crate::committee_manager::{
+    Committee,
+    CommitteeManager,
+    CommitteeManagerConfig,
+    CommitteeManagerError,
+};
+use crate::config::ContextConfig;
+
+// The context keeps the committees it uses, so the manager only needs the committees of the current
+// and the next epochs.
+const MAX_CACHED_EPOCHS: usize = 2;
+
+#[derive(Debug, Error)]
+pub enum CommitteeProviderError {
+    #[error(transparent)]
+    CommitteeManagerError(#[from] CommitteeManagerError),
+    #[error(transparent)]
+    StateSyncClientError(#[from] StateSyncClientError),
+    #[error("There's no synced block to read the committee from.")]
+    EmptyState,
+    #[error("The committee of epoch {epoch} is empty.")]
+    EmptyCommittee { epoch: u64 },
+}
+
+pub type CommitteeProviderResult<T> = Result<T, CommitteeProviderError>;
+
+/// Provides the consensus context with the committee of each epoch.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait CommitteeProvider: Send + Sync {
+    /// Returns the committee of the given epoch, which must not be empty.
+    async fn get_committee(&self, epoch: u64) -> CommitteeProviderResult<Arc<Committee>>;
+}
+
+/// Reads the committee of each epoch from the staking contract, in the state of the latest synced
+/// block.
+pub struct StakingCommitteeProvider {
+    // Calling the staking contract blocks on reading the state, so the manager is used from a
+    // blocking task.
+    committee_manager: Arc<Mutex<CommitteeManager>>,
+    chain_id: ChainId,
+    state_sync_client: SharedStateSyncClient,
+    class_manager_client: SharedClassManagerClient,
+}
+
+impl StakingCommitteeProvider {
+    pub fn new(
+        config: &ContextConfig,
+        state_sync_client: SharedStateSyncClient,
+        class_manager_client: SharedClassManagerClient,
+    ) -> Self {
+        let committee_manager_config = CommitteeManagerConfig {
+            staking_contract_address: config.staking_contract_address,
+            max_cached_epochs: MAX_CACHED_EPOCHS,
+            committee_size: config.committee_size,
+        };
+        Self {
+            committee_manager: Arc::new(Mutex::new(CommitteeManager::new(
+                committee_manager_config,
+            ))),
+            chain_id: config.chain_id.clone(),
+            state_sync_client,
+            class_manager_client,
+        }
+    }
+
+    // The context of a call to the staking contract on top of the given block.
+    async fn block_context(
+        &self,
+        block_number: BlockNumber,
+    ) -> CommitteeProviderResult<Arc<BlockContext>> {
+        let header = self
+            .state_sync_client
+            .get_block(block_number)
+            .await?
+            .ok_or(StateSyncClientError::StateSyncError(StateSyncError::BlockNotFound(
+                block_number,
+            )))?
+            .block_header_without_hash;
+        // Reading the committee doesn't charge any fee, so the gas prices are irrelevant.
+        let block_info = BlockInfo {
+            block_number: header.block_number,
+            block_timestamp: header.timestamp,
+            sequencer_address: header.sequencer.0,
+            ..Default::default()
+        };
+        let chain_info = ChainInfo { chain_id: self.chain_id.clone(), ..Default::default() };
+        Ok(Arc::new(BlockContext::new(
+            block_info,
+            chain_info,
+            VersionedConstants::latest_constants().clone(),
+            BouncerConfig::default(),
+        )))
+    }
+}
+
+#[async_trait]
+impl CommitteeProvider for StakingCommitteeProvider {
+    async fn get_committee(&self, epoch: u64) -> CommitteeProviderResult<Arc<Committee>> {
+        let block_number = self
+            .state_sync_client
+            .get_latest_block_number()
+            .await?
+            .ok_or(CommitteeProviderError::EmptyState)?;
+        let block_context = self.block_context(block_number).await?;
+        let state_reader = SyncStateReader {
+            block_number,
+            state_sync_client: self.state_sync_client.clone(),
+            class_manager_client: self.class_manager_client.clone(),
+            runtime: Handle::current(),
+        };
+        let committee_manager = self.committee_manager.clone();
+        let committee = tokio::task::spawn_blocking(move || {
+            committee_manager
+                .lock()
+                .expect("Committee manager should not be poisoned.")
+                .get_committee(epoch, state_reader, block_context)
+        })
+        .await
+        .expect("Reading the committee should not panic.")?;
+        if committee.is_empty() {
+            return Err(CommitteeProviderError::EmptyCommittee { epoch });
+        }
+        Ok(committee)
+    }
+}
+
+// Reads the state at the given block from the state sync, for calling the staking contract.
+struct SyncStateReader {
+    block_number: BlockNumber,
+    state_sync_client: SharedStateSyncClient,
+    class_manager_client: SharedClassManagerClient,
+    runtime: Handle,
+}
+
+impl StateReader for SyncStateReader {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<Felt> {
+        let res = self.runtime.block_on(self.state_sync_client.get_storage_at(
+            self.block_number,
+            contract_address,
+            key,
+        ));
+
+        match res {
+            Ok(value) => Ok(value),
+            Err(StateSyncClientError::StateSyncError(StateSyncError::ContractNotFound(_))) => {
+                Ok(Felt::default())
+            }
+            Err(e) => Err(StateError::StateReadError(e.to_string())),
+        }
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let res = self
+            .runtime
+            .block_on(self.state_sync_client.get_nonce_at(self.block_number, contract_address));
+
+        match res {
+            Ok(value) => Ok(value),
+            Err(StateSyncClientError::StateSyncError(StateSyncError::ContractNotFound(_))) => {
+                Ok(Nonce::default())
+            }
+            Err(e) => Err(StateError::StateReadError(e.to_string())),
+        }
+    }
+
+    fn get_compiled_class(&self, class_hash: ClassHash) -> StateResult<RunnableCompiledClass> {
+        let is_class_declared = self
+            .runtime
+            .block_on(self.state_sync_client.is_class_declared_at(self.block_number, class_hash))
+            .map_err(|e| StateError::StateReadError(e.to_string()))?;
+
+        if !is_class_declared {
+            return Err(StateError::UndeclaredClassHash(class_hash));
+        }
+
+        let contract_class = self
+            .runtime
+            .block_on(self.class_manager_client.get_executable(class_hash))
+            .map_err(|e| StateError::StateReadError(e.to_string()))?
+            .ok_or(StateError::UndeclaredClassHash(class_hash))?;
+
+        match contract_class {
+            ContractClass::V1(casm_contract_class) => {
+                Ok(RunnableCompiledClass::V1(casm_contract_class.try_into()?))
+            }
+            ContractClass::V0(deprecated_contract_class) => {
+                Ok(RunnableCompiledClass::V0(deprecated_contract_class.try_into()?))
+            }
+        }
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let res = self.runtime.block_on(
+            self.state_sync_client.get_class_hash_at(self.block_number, contract_address),
+        );
+
+        match res {
+            Ok(value) => Ok(value),
+            Err(StateSyncClientError::StateSyncError(StateSyncError::ContractNotFound(_))) => {
+                Ok(ClassHash::default())
+            }
+            Err(e) => Err(StateError::StateReadError(e.to_string())),
+        }
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        // Only declaring a class reads its compiled class hash, which a view call doesn't do.
+        Err(StateError::StateReadError(format!(
+            "The compiled class hash of {class_hash} isn't read when calling the staking contract."
+        )))
+    }
+}
@@ -5,20 +5,37 @@ use std::time::Duration;
 use apollo_config::converters::deserialize_milliseconds_to_duration;
 use apollo_config::dumping::{ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_consensus::config::{deserialize_public_keys, serialize_public_keys};
 use serde::{Deserialize, Serialize};
 use starknet_api::core::{ChainId, ContractAddress};
-use validator::Validate;
+use starknet_api::crypto::utils::PublicKey;
+use validator::{Validate, ValidationError};
 
 const GWEI_FACTOR: u128 = u128::pow(10, 9);
 const ETH_FACTOR: u128 = u128::pow(10, 18);
 
 /// Configuration for the Context struct.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Validate)]
+#[validate(schema(function = "validate_context_config"))]
 pub struct ContextConfig {
     /// Buffer size for streaming outbound proposals.
     pub proposal_buffer_size: usize,
-    /// The number of validators.
+    /// The number of validators, if the committee isn't read from the staking contract.
     pub num_validators: u64,
+    /// The public keys the validators sign their votes with, in the order of their IDs, if the
+    /// committee isn't read from the staking contract.
+    #[serde(deserialize_with = "deserialize_public_keys")]
+    pub validator_public_keys: Vec<PublicKey>,
+    /// If true, the committee of each epoch is read from the staking contract. Otherwise, the
+    /// configured validators are the committee of every epoch, all with the same weight.
+    pub use_staking_committee: bool,
+    /// The address of the staking contract, which the committee is read from.
+    pub staking_contract_address: ContractAddress,
+    /// The maximal number of stakers in the committee, which are the stakers with the highest
+    /// weights.
+    pub committee_size: usize,
+    /// The number of blocks in an epoch, during which the committee doesn't change.
+    pub epoch_length: u64,
     /// The chain id of the Starknet chain.
     pub chain_id: ChainId,
     /// Maximum allowed deviation (seconds) of a proposed block's timestamp from the current time.
@@ -52,6 +69,13 @@ pub struct ContextConfig {
     pub l1_data_gas_price_multiplier_ppt: u128,
     /// This additional gas is added to the L1 gas price.
     pub l1_gas_tip_wei: u128,
+    /// If true, the proposer of the next height starts building its proposal once a block of the
+    /// current height receives a quorum of prevotes, instead of waiting for the decision.
+    pub pipeline_next_height: bool,
+    /// The time in milliseconds the batcher has to build a proposal for the next height, which is
+    /// built before the current height is decided.
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub pipelined_build_timeout_millis: Duration,
 }
 
 impl SerializeConfig for ContextConfig {
@@ -69,6 +93,38 @@ impl SerializeConfig for ContextConfig {
                 "The number of validators.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "validator_public_keys",
+                &serialize_public_keys(&self.validator_public_keys),
+                "The space-separated public keys the validators sign their votes with, in the \
+                 order of their IDs.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "use_staking_committee",
+                &self.use_staking_committee,
+                "If true, the committee of each epoch is read from the staking contract. \
+                 Otherwise, the configured validators are the committee of every epoch.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "staking_contract_address",
+                &self.staking_contract_address,
+                "The address of the staking contract, which the committee is read from.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "committee_size",
+                &self.committee_size,
+                "The maximal number of stakers in the committee.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "epoch_length",
+                &self.epoch_length,
+                "The number of blocks in an epoch, during which the committee doesn't change.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "chain_id",
                 &self.chain_id,
@@ -145,6 +201,20 @@ impl SerializeConfig for ContextConfig {
                 "This additional gas is added to the L1 gas price.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "pipeline_next_height",
+                &self.pipeline_next_height,
+                "If true, the proposer of the next height starts building its proposal once a \
+                 block of the current height receives a quorum of prevotes.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "pipelined_build_timeout_millis",
+                &self.pipelined_build_timeout_millis.as_millis(),
+                "The time (in ms) the batcher has to build a proposal for the next height, which \
+                 is built before the current height is decided.",
+                ParamPrivacyInput::Public,
+            ),
         ])
     }
 }
@@ -154,6 +224,11 @@ impl Default for ContextConfig {
         Self {
             proposal_buffer_size: 100,
             num_validators: 1,
+            validator_public_keys: vec![PublicKey::default()],
+            use_staking_committee: false,
+            staking_contract_address: ContractAddress::default(),
+            committee_size: 100,
+            epoch_length: 100,
             chain_id: ChainId::Mainnet,
             block_timestamp_window_seconds: 1,
             l1_da_mode: true,
@@ -166,6 +241,20 @@ impl Default for ContextConfig {
             max_l1_data_gas_price_wei: ETH_FACTOR,
             l1_data_gas_price_multiplier_ppt: 135,
             l1_gas_tip_wei: GWEI_FACTOR,
+            pipeline_next_height: false,
+            pipelined_build_timeout_millis: Duration::from_millis(2000),
         }
     }
 }
+
+fn validate_context_config(config: &ContextConfig) -> Result<(), ValidationError> {
+    if u64::try_from(config.validator_public_keys.len()) != Ok(config.num_validators) {
+        return Err(ValidationError::new(
+            "validator_public_keys must hold a public key for each of the validators",
+        ));
+    }
+    if config.epoch_length == 0 {
+        return Err(ValidationError::new("epoch_length must be positive"));
+    }
+    Ok(())
+}
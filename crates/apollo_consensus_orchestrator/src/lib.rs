@@ -18,6 +18,9 @@ pub mod cende;
 #[allow(missing_docs)]
 pub mod committee_manager;
 
+#[allow(missing_docs)]
+pub mod committee_provider;
+
 /// Fee market logic.
 pub mod fee_market;
 
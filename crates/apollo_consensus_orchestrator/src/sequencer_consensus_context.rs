@@ -6,26 +6,34 @@
 mod sequencer_consensus_context_test;
 
 use std::cmp::max;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use apollo_batcher_types::batcher_types::{
     DecisionReachedInput,
     DecisionReachedResponse,
+    PipelineNextHeightInput,
+    PipelineNextHeightResponse,
     ProposalId,
     StartHeightInput,
 };
 use apollo_batcher_types::communication::BatcherClient;
 use apollo_class_manager_types::transaction_converter::TransactionConverterTrait;
+use apollo_consensus::commit_certificate::{build_commit_certificate, configured_committee};
+use apollo_consensus::proposer_rotation::ProposerRotation;
 use apollo_consensus::types::{
+    vote_signing_payload,
     ConsensusContext,
     ConsensusError,
+    EquivocationEvidence,
     ProposalCommitment,
     Round,
+    Validator,
     ValidatorId,
 };
 use apollo_l1_gas_price_types::{EthToStrkOracleClientTrait, L1GasPriceProviderClient};
+use apollo_network::allowlist::{AllowedStakersSender, ReportedStakersSender};
 use apollo_network::network_manager::{BroadcastTopicClient, BroadcastTopicClientTrait};
 use apollo_protobuf::consensus::{
     ConsensusBlockInfo,
@@ -35,8 +43,9 @@ use apollo_protobuf::consensus::{
     ProposalPart,
     TransactionBatch,
     Vote,
-    DEFAULT_VALIDATOR_ID,
 };
+use apollo_signature_manager::signature_manager::verify_precommit_vote_signature;
+use apollo_signature_manager_types::SharedSignatureManagerClient;
 use apollo_state_sync_types::communication::StateSyncClient;
 use apollo_state_sync_types::state_sync_types::SyncBlock;
 use apollo_time::time::Clock;
@@ -48,22 +57,31 @@ use starknet_api::block::{
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockTimestamp,
+    CommitteeMember,
     GasPrice,
     GasPricePerToken,
     WEI_PER_ETH,
 };
 use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::core::SequencerContractAddress;
+use starknet_api::crypto::utils::PublicKey;
 use starknet_api::data_availability::L1DataAvailabilityMode;
 use starknet_api::execution_resources::GasAmount;
 use starknet_api::transaction::TransactionHash;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
-use tracing::{error, error_span, info, instrument, trace, warn, Instrument};
+use tracing::{debug, error, error_span, info, instrument, trace, warn, Instrument};
 
-use crate::build_proposal::{build_proposal, BuildProposalError, ProposalBuildArguments};
+use crate::build_proposal::{
+    build_proposal,
+    start_building,
+    BuildProposalError,
+    ProposalBuildArguments,
+};
 use crate::cende::{BlobParameters, CendeContext};
+use crate::committee_manager::Committee;
+use crate::committee_provider::CommitteeProvider;
 use crate::config::ContextConfig;
 use crate::fee_market::{calculate_next_base_gas_price, FeeMarketInfo};
 use crate::metrics::{register_metrics, CONSENSUS_L2_GAS_PRICE};
@@ -76,6 +94,11 @@ use crate::validate_proposal::{
     ValidateProposalError,
 };
 
+// The committees of the current and the next epochs, since the proposer of the next height is
+// needed before it starts.
+const MAX_KNOWN_EPOCHS: usize = 2;
+const READ_COMMITTEE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
 type ValidationParams = (BlockNumber, ValidatorId, Duration, mpsc::Receiver<ProposalPart>);
 
 type HeightToIdToContent = BTreeMap<
@@ -114,6 +137,10 @@ impl BuiltProposals {
             .unwrap_or_else(|| panic!("No proposal found for height {height} and id {commitment}"))
     }
 
+    fn contains_proposal(&self, height: &BlockNumber, commitment: &ProposalCommitment) -> bool {
+        self.data.get(height).is_some_and(|proposals| proposals.contains_key(commitment))
+    }
+
     fn remove_proposals_below_or_at_height(&mut self, height: &BlockNumber) {
         self.data.retain(|&h, _| h > *height);
     }
@@ -133,10 +160,78 @@ impl BuiltProposals {
     }
 }
 
+// A proposal for the next height, which the batcher started building on top of a block of the
+// current height which received a quorum of prevotes.
+struct PipelinedProposal {
+    parent: ProposalCommitment,
+    block_info: ConsensusBlockInfo,
+    proposal_id: ProposalId,
+}
+
+// The validators of an epoch, along with the keys they sign their votes with.
+struct EpochCommittee {
+    validators: Vec<Validator>,
+    public_keys: HashMap<ValidatorId, PublicKey>,
+    proposer_rotation: ProposerRotation,
+}
+
+impl EpochCommittee {
+    fn new(validators: Vec<Validator>, public_keys: HashMap<ValidatorId, PublicKey>) -> Self {
+        let proposer_rotation = ProposerRotation::new(&validators);
+        Self { validators, public_keys, proposer_rotation }
+    }
+
+    // The sync verifies the commit certificates against the same committee.
+    fn from_config(config: &ContextConfig) -> Self {
+        // TODO(Matan): Set the actual validator IDs (contract addresses).
+        let members = configured_committee(&config.validator_public_keys);
+        let validators = members
+            .iter()
+            .map(|member| Validator { id: member.address, weight: member.weight })
+            .collect();
+        let public_keys =
+            members.iter().map(|member| (member.address, member.public_key)).collect();
+        Self::new(validators, public_keys)
+    }
+
+    fn from_stakers(stakers: &Committee) -> Self {
+        let validators = stakers.iter().map(Validator::from).collect();
+        let public_keys =
+            stakers.iter().map(|staker| (staker.address, PublicKey(staker.public_key))).collect();
+        Self::new(validators, public_keys)
+    }
+
+    // The keys of the validators, in their order.
+    fn stakers(&self) -> impl Iterator<Item = PublicKey> + '_ {
+        self.validators.iter().map(|validator| self.public_keys[&validator.id])
+    }
+
+    fn members(&self) -> Vec<CommitteeMember> {
+        self.validators
+            .iter()
+            .map(|validator| CommitteeMember {
+                address: validator.id,
+                weight: validator.weight,
+                public_key: self.public_keys[&validator.id],
+            })
+            .collect()
+    }
+}
+
+enum Committees {
+    // The configured validators are the committee of every epoch.
+    Configured(Arc<EpochCommittee>),
+    // The committees of the last epochs which were read from the provider, by epoch.
+    Provided {
+        provider: Arc<dyn CommitteeProvider>,
+        epochs: Mutex<BTreeMap<u64, Arc<EpochCommittee>>>,
+    },
+}
+
 pub struct SequencerConsensusContext {
     config: ContextConfig,
     deps: SequencerConsensusContextDeps,
-    validators: Vec<ValidatorId>,
+    committees: Committees,
     // Proposal building/validating returns immediately, leaving the actual processing to a spawned
     // task. The spawned task processes the proposal asynchronously and updates the
     // valid_proposals map upon completion, ensuring consistency across tasks.
@@ -157,6 +252,8 @@ pub struct SequencerConsensusContext {
     l2_gas_price: GasPrice,
     l1_da_mode: L1DataAvailabilityMode,
     previous_block_info: Option<ConsensusBlockInfo>,
+    // Only kept while its parent may still be decided.
+    pipelined_proposal: Option<PipelinedProposal>,
 }
 
 #[derive(Clone)]
@@ -173,24 +270,36 @@ pub struct SequencerConsensusContextDeps {
     pub outbound_proposal_sender: mpsc::Sender<(HeightAndRound, mpsc::Receiver<ProposalPart>)>,
     // Used to broadcast votes to other consensus nodes.
     pub vote_broadcast_client: BroadcastTopicClient<Vote>,
+    // Used to sign the node's votes with its validator key.
+    pub signature_manager_client: SharedSignatureManagerClient,
+    // Used to let only the nodes of the committee's stakers connect, if the consensus network is
+    // permissioned.
+    pub allowed_stakers_sender: AllowedStakersSender,
+    // Used to report the nodes of stakers which equivocated to the consensus network.
+    pub reported_stakers_sender: ReportedStakersSender,
+    // Used to read the committee of each epoch from the staking contract. If unset, the configured
+    // validators are the committee of every epoch.
+    pub committee_provider: Option<Arc<dyn CommitteeProvider>>,
 }
 
 impl SequencerConsensusContext {
     pub fn new(config: ContextConfig, deps: SequencerConsensusContextDeps) -> Self {
         register_metrics();
-        let num_validators = config.num_validators;
+        let committees = match deps.committee_provider.clone() {
+            Some(provider) => {
+                Committees::Provided { provider, epochs: Mutex::new(BTreeMap::new()) }
+            }
+            None => Committees::Configured(Arc::new(EpochCommittee::from_config(&config))),
+        };
         let l1_da_mode = if config.l1_da_mode {
             L1DataAvailabilityMode::Blob
         } else {
             L1DataAvailabilityMode::Calldata
         };
-        Self {
+        let context = Self {
             config,
             deps,
-            // TODO(Matan): Set the actual validator IDs (contract addresses).
-            validators: (0..num_validators)
-                .map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i))
-                .collect(),
+            committees,
             valid_proposals: Arc::new(Mutex::new(BuiltProposals::new())),
             proposal_id: 0,
             current_height: None,
@@ -200,9 +309,101 @@ impl SequencerConsensusContext {
             l2_gas_price: VersionedConstants::latest_constants().min_gas_price,
             l1_da_mode,
             previous_block_info: None,
+            pipelined_proposal: None,
+        };
+        // The configured committee doesn't change, so its stakers are only allowed once.
+        if let Committees::Configured(committee) = &context.committees {
+            context.update_allowed_stakers(committee.stakers().collect());
+        }
+        context
+    }
+
+    fn update_allowed_stakers(&self, stakers: Vec<PublicKey>) {
+        if let Err(e) = self.deps.allowed_stakers_sender.clone().try_send(stakers) {
+            warn!("Failed to update the stakers allowed to connect to the consensus network: {e}");
+        }
+    }
+
+    fn epoch(&self, height: BlockNumber) -> u64 {
+        height.0 / self.config.epoch_length
+    }
+
+    // The committee of the given height, if it was already read.
+    fn known_committee(&self, height: BlockNumber) -> Option<Arc<EpochCommittee>> {
+        match &self.committees {
+            Committees::Configured(committee) => Some(committee.clone()),
+            Committees::Provided { epochs, .. } => epochs
+                .lock()
+                .expect("Lock on the committees was poisoned due to a previous panic")
+                .get(&self.epoch(height))
+                .cloned(),
         }
     }
 
+    // The committee of the given height, which is read until it succeeds if it isn't known yet.
+    async fn committee(&self, height: BlockNumber) -> Arc<EpochCommittee> {
+        if let Some(committee) = self.known_committee(height) {
+            return committee;
+        }
+        let Committees::Provided { provider, epochs } = &self.committees else {
+            unreachable!("The configured committee is always known.");
+        };
+        let epoch = self.epoch(height);
+        let stakers = loop {
+            match provider.get_committee(epoch).await {
+                Ok(stakers) => break stakers,
+                Err(e) => {
+                    warn!("Failed to read the committee of epoch {epoch}: {e}");
+                    tokio::time::sleep(READ_COMMITTEE_RETRY_INTERVAL).await;
+                }
+            }
+        };
+        let committee = Arc::new(EpochCommittee::from_stakers(&stakers));
+        let allowed_stakers = {
+            let mut epochs =
+                epochs.lock().expect("Lock on the committees was poisoned due to a previous panic");
+            epochs.insert(epoch, committee.clone());
+            while epochs.len() > MAX_KNOWN_EPOCHS {
+                epochs.pop_first();
+            }
+            epochs
+                .values()
+                .flat_map(|committee| committee.stakers())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect()
+        };
+        // The stakers of the next epoch are allowed along with those of the current one, so they
+        // can connect before the committee changes.
+        self.update_allowed_stakers(allowed_stakers);
+        committee
+    }
+
+    fn gas_price_params(&self) -> GasPriceParams {
+        GasPriceParams {
+            min_l1_gas_price_wei: GasPrice(self.config.min_l1_gas_price_wei),
+            max_l1_gas_price_wei: GasPrice(self.config.max_l1_gas_price_wei),
+            min_l1_data_gas_price_wei: GasPrice(self.config.min_l1_data_gas_price_wei),
+            max_l1_data_gas_price_wei: GasPrice(self.config.max_l1_data_gas_price_wei),
+            l1_data_gas_price_multiplier: Ratio::new(
+                self.config.l1_data_gas_price_multiplier_ppt,
+                1000,
+            ),
+            l1_gas_tip_wei: GasPrice(self.config.l1_gas_tip_wei),
+        }
+    }
+
+    // The proposal the batcher already started building for the given height and round, if any.
+    // Once we get here its parent was decided, since otherwise it would have been dropped.
+    fn take_pipelined_proposal(
+        &mut self,
+        proposal_init: &ProposalInit,
+    ) -> Option<PipelinedProposal> {
+        self.pipelined_proposal.take().filter(|pipelined| {
+            pipelined.block_info.height == proposal_init.height && proposal_init.round == 0
+        })
+    }
+
     async fn start_stream(&mut self, stream_id: HeightAndRound) -> StreamSender {
         let (proposal_sender, proposal_receiver) = mpsc::channel(self.config.proposal_buffer_size);
         self.deps
@@ -239,33 +440,30 @@ impl ConsensusContext for SequencerConsensusContext {
         );
 
         let (fin_sender, fin_receiver) = oneshot::channel();
-        let proposal_id = ProposalId(self.proposal_id);
-        self.proposal_id += 1;
+        let pipelined_proposal = self.take_pipelined_proposal(&proposal_init);
+        let proposal_id = match &pipelined_proposal {
+            Some(pipelined) => pipelined.proposal_id,
+            None => {
+                let proposal_id = ProposalId(self.proposal_id);
+                self.proposal_id += 1;
+                proposal_id
+            }
+        };
         assert!(timeout > self.config.build_proposal_margin_millis);
         let stream_id = HeightAndRound(proposal_init.height.0, proposal_init.round);
         let stream_sender = self.start_stream(stream_id).await;
 
-        info!(?proposal_init, ?timeout, %proposal_id, "Building proposal");
+        let pipelined = pipelined_proposal.is_some();
+        info!(?proposal_init, ?timeout, %proposal_id, pipelined, "Building proposal");
         let cancel_token = CancellationToken::new();
         let cancel_token_clone = cancel_token.clone();
-        let gas_price_params = GasPriceParams {
-            min_l1_gas_price_wei: GasPrice(self.config.min_l1_gas_price_wei),
-            max_l1_gas_price_wei: GasPrice(self.config.max_l1_gas_price_wei),
-            min_l1_data_gas_price_wei: GasPrice(self.config.min_l1_data_gas_price_wei),
-            max_l1_data_gas_price_wei: GasPrice(self.config.max_l1_data_gas_price_wei),
-            l1_data_gas_price_multiplier: Ratio::new(
-                self.config.l1_data_gas_price_multiplier_ppt,
-                1000,
-            ),
-            l1_gas_tip_wei: GasPrice(self.config.l1_gas_tip_wei),
-        };
         let args = ProposalBuildArguments {
             deps: self.deps.clone(),
             batcher_timeout: timeout - self.config.build_proposal_margin_millis,
             proposal_init,
             l1_da_mode: self.l1_da_mode,
             stream_sender,
-            gas_price_params,
+            gas_price_params: self.gas_price_params(),
             valid_proposals: Arc::clone(&self.valid_proposals),
             proposal_id,
             cende_write_success,
@@ -274,6 +472,7 @@ impl ConsensusContext for SequencerConsensusContext {
             cancel_token,
             previous_block_info: self.previous_block_info.clone(),
             proposal_round: self.current_round,
+            pipelined_block_info: pipelined_proposal.map(|pipelined| pipelined.block_info),
         };
         let handle = tokio::spawn(
             async move {
@@ -352,12 +551,19 @@ impl ConsensusContext for SequencerConsensusContext {
     async fn repropose(&mut self, id: ProposalCommitment, init: ProposalInit) {
         info!(?id, ?init, "Reproposing.");
         let height = init.height;
-        let (block_info, txs, _) = self
-            .valid_proposals
-            .lock()
-            .expect("Lock on active proposals was poisoned due to a previous panic")
-            .get_proposal(&height, &id)
-            .clone();
+        let (block_info, txs, _) = {
+            let valid_proposals = self
+                .valid_proposals
+                .lock()
+                .expect("Lock on active proposals was poisoned due to a previous panic");
+            // A valid value restored from the write-ahead log after a restart has no content, so
+            // the round is left without a proposal.
+            if !valid_proposals.contains_proposal(&height, &id) {
+                warn!(?id, ?init, "Cannot repropose: the proposal's content is unknown.");
+                return;
+            }
+            valid_proposals.get_proposal(&height, &id).clone()
+        };
 
         let transaction_converter = self.deps.transaction_converter.clone();
         let mut stream_sender = self.start_stream(HeightAndRound(height.0, init.round)).await;
@@ -405,17 +611,50 @@ impl ConsensusContext for SequencerConsensusContext {
         );
     }
 
-    async fn validators(&self, _height: BlockNumber) -> Vec<ValidatorId> {
-        self.validators.clone()
+    async fn validators(&self, height: BlockNumber) -> Vec<Validator> {
+        // The proposer of the next height is needed before it starts, so its committee is read
+        // along with the current one.
+        self.committee(height.unchecked_next()).await;
+        self.committee(height).await.validators.clone()
     }
 
     fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId {
-        let height: usize = height.0.try_into().expect("Cannot convert to usize");
-        let round: usize = round.try_into().expect("Cannot convert to usize");
-        *self
-            .validators
-            .get((height + round) % self.validators.len())
-            .expect("There should be at least one validator")
+        self.known_committee(height)
+            .unwrap_or_else(|| {
+                panic!("The committee of height {height} should be read before its proposers.")
+            })
+            .proposer_rotation
+            .proposer(height, round)
+    }
+
+    async fn sign_vote(&mut self, vote: Vote) -> Result<Vote, ConsensusError> {
+        let signature = self
+            .deps
+            .signature_manager_client
+            .sign_precommit_vote(vote_signing_payload(&self.config.chain_id, &vote))
+            .await
+            .map_err(|e| ConsensusError::VoteSigning(e.to_string()))?;
+        Ok(Vote { signature, ..vote })
+    }
+
+    fn verify_vote_signature(&self, vote: &Vote) -> bool {
+        let Some(committee) = self.known_committee(BlockNumber(vote.height)) else {
+            debug!("Vote from a height whose committee is unknown: {vote:?}");
+            return false;
+        };
+        let Some(public_key) = committee.public_keys.get(&vote.voter) else {
+            debug!("Vote from a non validator: {vote:?}");
+            return false;
+        };
+        verify_precommit_vote_signature(
+            &vote_signing_payload(&self.config.chain_id, vote),
+            vote.signature.clone(),
+            *public_key,
+        )
+        .unwrap_or_else(|e| {
+            debug!("Failed to verify the signature of {vote:?}: {e}");
+            false
+        })
     }
 
     async fn broadcast(&mut self, message: Vote) -> Result<(), ConsensusError> {
@@ -433,6 +672,10 @@ impl ConsensusContext for SequencerConsensusContext {
         info!("Finished consensus for height: {height}. Agreed on block: {:#064x}", block.0);
 
         self.interrupt_active_proposal().await;
+        if self.pipelined_proposal.as_ref().is_some_and(|pipelined| pipelined.parent != block) {
+            info!("Discarding the proposal pipelined on top of another block.");
+            self.pipelined_proposal = None;
+        }
         let proposal_id;
         let transactions;
         let block_info;
@@ -519,11 +762,14 @@ impl ConsensusContext for SequencerConsensusContext {
             })
             .collect::<Vec<TransactionHash>>();
 
+        let committee = self.committee(BlockNumber(height)).await.members();
+        let commit_certificate = build_commit_certificate(block, &precommits, committee);
         let sync_block = SyncBlock {
             state_diff: state_diff.clone(),
             account_transaction_hashes,
             l1_transaction_hashes,
             block_header_without_hash,
+            commit_certificate: Some(commit_certificate),
         };
         let state_sync_client = self.deps.state_sync_client.clone();
         // `add_new_block` returns immediately, it doesn't wait for sync to fully process the block.
@@ -622,6 +868,8 @@ impl ConsensusContext for SequencerConsensusContext {
             eth_to_fri_rate,
         });
         self.interrupt_active_proposal().await;
+        // The batcher discards the pipelined proposal when it learns of a synced block.
+        self.pipelined_proposal = None;
         self.deps.batcher.add_sync_block(sync_block).await.unwrap();
         true
     }
@@ -684,6 +932,104 @@ impl ConsensusContext for SequencerConsensusContext {
         )
         .await;
     }
+
+    async fn report_equivocation(&mut self, evidence: EquivocationEvidence) {
+        let offender = evidence.offender();
+        let (first_vote, second_vote) = match &evidence {
+            EquivocationEvidence::Vote(first_vote, second_vote) => (first_vote, second_vote),
+            EquivocationEvidence::Proposal(..) => {
+                // Proposals aren't signed, so the peer which sent them may not be the proposer.
+                // The evidence is only logged, without reporting the proposer to the network.
+                warn!("Validator {offender} sent conflicting proposals: {evidence:?}");
+                return;
+            }
+        };
+        let committee = self.committee(BlockNumber(first_vote.height)).await;
+        if !self.verify_vote_signature(first_vote) || !self.verify_vote_signature(second_vote) {
+            error!("Ignoring equivocation evidence which isn't signed by {offender}: {evidence:?}");
+            return;
+        }
+        warn!("Validator {offender} equivocated: {evidence:?}");
+        let public_key = committee.public_keys[&offender];
+        if let Err(e) = self.deps.reported_stakers_sender.try_send(public_key) {
+            warn!("Failed to report the equivocating validator {offender} to the network: {e}");
+        }
+    }
+
+    fn pipelines_next_height(&self) -> bool {
+        self.config.pipeline_next_height
+    }
+
+    async fn prevote_quorum_reached(
+        &mut self,
+        height: BlockNumber,
+        round: Round,
+        block: ProposalCommitment,
+    ) {
+        if !self.config.pipeline_next_height || self.current_height != Some(height) {
+            return;
+        }
+        if self.pipelined_proposal.as_ref().is_some_and(|pipelined| pipelined.parent == block) {
+            return;
+        }
+        // A proposal pipelined on top of another block is now unlikely to be used.
+        self.pipelined_proposal = None;
+        let parent = {
+            let proposals = self
+                .valid_proposals
+                .lock()
+                .expect("Lock on active proposals was poisoned due to a previous panic");
+            proposals.contains_proposal(&height, &block).then(|| {
+                let (block_info, _, proposal_id) = proposals.get_proposal(&height, &block);
+                (block_info.clone(), *proposal_id)
+            })
+        };
+        let Some((parent_block_info, parent_proposal_id)) = parent else {
+            debug!("Not pipelining the next height on top of {block}, which we didn't validate.");
+            return;
+        };
+        let parent_l2_gas_used = match self
+            .deps
+            .batcher
+            .pipeline_next_height(PipelineNextHeightInput { parent_proposal_id })
+            .await
+        {
+            Ok(PipelineNextHeightResponse { parent_l2_gas_used }) => parent_l2_gas_used,
+            Err(e) => {
+                warn!("Failed to pipeline the next height on top of {block}: {e:?}");
+                return;
+            }
+        };
+        // The same price validators expect once the parent is decided (see `decision_reached`).
+        let gas_target = GasAmount(VersionedConstants::latest_constants().max_block_size.0 / 2);
+        let l2_gas_price =
+            calculate_next_base_gas_price(self.l2_gas_price, parent_l2_gas_used, gas_target);
+
+        let proposal_id = ProposalId(self.proposal_id);
+        self.proposal_id += 1;
+        let next_height = height.unchecked_next();
+        info!(%proposal_id, round, "Pipelining height {next_height} on top of {block}.");
+        match start_building(
+            &self.deps,
+            next_height,
+            0,
+            proposal_id,
+            self.config.pipelined_build_timeout_millis,
+            self.l1_da_mode,
+            &self.gas_price_params(),
+            l2_gas_price,
+            self.config.builder_address,
+            Some(&parent_block_info),
+        )
+        .await
+        {
+            Ok(block_info) => {
+                self.pipelined_proposal =
+                    Some(PipelinedProposal { parent: block, block_info, proposal_id });
+            }
+            Err(e) => warn!("Failed to pipeline a proposal for height {next_height}: {e:?}"),
+        }
+    }
 }
 
 impl SequencerConsensusContext {
@@ -702,17 +1048,7 @@ impl SequencerConsensusContext {
 
         let cancel_token = CancellationToken::new();
         let cancel_token_clone = cancel_token.clone();
-        let gas_price_params = GasPriceParams {
-            min_l1_gas_price_wei: GasPrice(self.config.min_l1_gas_price_wei),
-            max_l1_gas_price_wei: GasPrice(self.config.max_l1_gas_price_wei),
-            min_l1_data_gas_price_wei: GasPrice(self.config.min_l1_data_gas_price_wei),
-            max_l1_data_gas_price_wei: GasPrice(self.config.max_l1_data_gas_price_wei),
-            l1_data_gas_price_multiplier: Ratio::new(
-                self.config.l1_data_gas_price_multiplier_ppt,
-                1000,
-            ),
-            l1_gas_tip_wei: GasPrice(self.config.l1_gas_tip_wei),
-        };
+        let gas_price_params = self.gas_price_params();
         let args = ProposalValidateArguments {
             deps: self.deps.clone(),
             block_info_validation,
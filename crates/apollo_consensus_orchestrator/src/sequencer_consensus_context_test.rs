@@ -1,11 +1,27 @@
+use std::collections::HashSet;
 use std::future::ready;
 use std::sync::Arc;
 use std::vec;
 
-use apollo_batcher_types::batcher_types::{CentralObjects, DecisionReachedResponse};
+use apollo_batcher_types::batcher_types::{
+    CentralObjects,
+    DecisionReachedResponse,
+    GetProposalContent,
+    GetProposalContentResponse,
+    PipelineNextHeightResponse,
+    ProposalCommitment,
+    ProposalId,
+};
 use apollo_batcher_types::communication::BatcherClientError;
 use apollo_batcher_types::errors::BatcherError;
-use apollo_consensus::types::{ConsensusContext, Round};
+use apollo_consensus::types::{
+    ConsensusContext,
+    EquivocationEvidence,
+    ProposalEvidence,
+    Round,
+    Validator,
+    ValidatorId,
+};
 use apollo_l1_gas_price_types::errors::{
     EthToStrkOracleClientError,
     L1GasPriceClientError,
@@ -17,11 +33,22 @@ use apollo_l1_gas_price_types::{
     PriceInfo,
     DEFAULT_ETH_TO_FRI_RATE,
 };
-use apollo_protobuf::consensus::{ProposalFin, ProposalInit, ProposalPart, TransactionBatch, Vote};
+use apollo_protobuf::consensus::{
+    ProposalFin,
+    ProposalInit,
+    ProposalPart,
+    TransactionBatch,
+    Vote,
+    DEFAULT_VALIDATOR_ID,
+};
+use apollo_signature_manager::signature_manager::LocalKeyStore;
+use apollo_signature_manager::SignatureManager;
+use apollo_signature_manager_types::SignatureManagerClientError;
 use apollo_time::time::MockClock;
 use chrono::{TimeZone, Utc};
 use futures::channel::mpsc;
 use futures::channel::oneshot::Canceled;
+use futures::executor::block_on;
 use futures::future::pending;
 use futures::{FutureExt, SinkExt, StreamExt};
 use metrics_exporter_prometheus::PrometheusBuilder;
@@ -33,10 +60,16 @@ use starknet_api::block::{
     TEMP_ETH_BLOB_GAS_FEE_IN_WEI,
     TEMP_ETH_GAS_FEE_IN_WEI,
 };
+use starknet_api::core::{ChainId, ContractAddress};
+use starknet_api::crypto::utils::PublicKey;
 use starknet_api::execution_resources::GasAmount;
+use starknet_api::staking::StakingWeight;
 use starknet_api::state::ThinStateDiff;
+use starknet_types_core::felt::Felt;
 
 use crate::cende::MockCendeContext;
+use crate::committee_manager::Staker;
+use crate::committee_provider::MockCommitteeProvider;
 use crate::config::ContextConfig;
 use crate::metrics::CONSENSUS_L2_GAS_PRICE;
 use crate::orchestrator_versioned_constants::VersionedConstants;
@@ -45,6 +78,7 @@ use crate::test_utils::{
     create_test_and_network_deps,
     ETH_TO_FRI_RATE,
     INTERNAL_TX_BATCH,
+    NUM_VALIDATORS,
     STATE_DIFF_COMMITMENT,
     TIMEOUT,
     TX_BATCH,
@@ -549,6 +583,9 @@ async fn decision_reached_sends_correct_values() {
     // TODO(guy.f): Add expectations and validations for all the other values being written.
     deps.state_sync_client.expect_add_new_block().times(1).return_once(|block_info| {
         assert_eq!(block_info.block_header_without_hash.timestamp.0, BLOCK_TIME_STAMP_SECONDS);
+        let commit_certificate = block_info.commit_certificate.unwrap();
+        assert_eq!(commit_certificate.block_hash, BlockHash(STATE_DIFF_COMMITMENT.0 .0));
+        assert_eq!(commit_certificate.precommits.len(), 1);
         Ok(())
     });
 
@@ -564,7 +601,7 @@ async fn decision_reached_sends_correct_values() {
     // At this point we should have a valid proposal in the context which contains the timestamp.
 
     let vote = Vote {
-        // Currently this is the only field used by decision_reached.
+        // Other than the height, the vote is only copied into the commit certificate.
         height: 0,
         ..Default::default()
     };
@@ -770,3 +807,199 @@ async fn oracle_fails_on_second_block(#[case] l1_oracle_failure: bool) {
     assert!(receiver.next().await.is_none());
     assert_eq!(fin_receiver.await.unwrap().0, STATE_DIFF_COMMITMENT.0.0);
 }
+
+#[tokio::test]
+async fn sign_and_verify_votes() {
+    let (mut deps, _network) = create_test_and_network_deps();
+    deps.signature_manager_client.expect_sign_precommit_vote().returning(|vote| {
+        block_on(SignatureManager::new_for_testing().sign_precommit_vote(vote))
+            .map_err(SignatureManagerClientError::from)
+    });
+    let mut context = deps.build_context();
+
+    let vote = Vote {
+        block_hash: Some(BlockHash(STATE_DIFF_COMMITMENT.0 .0)),
+        voter: ValidatorId::from(DEFAULT_VALIDATOR_ID),
+        ..Default::default()
+    };
+    let signed_vote = context.sign_vote(vote.clone()).await.unwrap();
+    assert!(context.verify_vote_signature(&signed_vote));
+
+    // The signature doesn't hold for a different vote.
+    assert!(!context.verify_vote_signature(&Vote { round: 1, ..signed_vote.clone() }));
+    // Votes from non validators are rejected.
+    assert!(!context.verify_vote_signature(&Vote {
+        voter: ValidatorId::from(DEFAULT_VALIDATOR_ID + 1),
+        ..signed_vote.clone()
+    }));
+    // Unsigned votes are rejected.
+    assert!(!context.verify_vote_signature(&vote));
+    // Votes signed on another chain are rejected.
+    context.config.chain_id = ChainId::Sepolia;
+    assert!(!context.verify_vote_signature(&signed_vote));
+}
+
+#[tokio::test]
+async fn equivocating_validator_is_reported() {
+    let (mut deps, mut network) = create_test_and_network_deps();
+    deps.signature_manager_client.expect_sign_precommit_vote().returning(|vote| {
+        block_on(SignatureManager::new_for_testing().sign_precommit_vote(vote))
+            .map_err(SignatureManagerClientError::from)
+    });
+    let mut context = deps.build_context();
+    let vote = Vote { voter: ValidatorId::from(DEFAULT_VALIDATOR_ID), ..Default::default() };
+    let conflicting_vote =
+        Vote { block_hash: Some(BlockHash(STATE_DIFF_COMMITMENT.0 .0)), ..vote.clone() };
+
+    // Evidence which isn't signed by the offender isn't reported.
+    context
+        .report_equivocation(EquivocationEvidence::Vote(vote.clone(), conflicting_vote.clone()))
+        .await;
+    assert!(network.reported_stakers_receiver.next().now_or_never().is_none());
+
+    // Neither are conflicting proposals, since proposals aren't signed.
+    let proposal = ProposalEvidence { init: ProposalInit::default(), commitment: None };
+    let conflicting_proposal =
+        ProposalEvidence { commitment: Some(BlockHash(STATE_DIFF_COMMITMENT.0 .0)), ..proposal };
+    context
+        .report_equivocation(EquivocationEvidence::Proposal(proposal, conflicting_proposal))
+        .await;
+    assert!(network.reported_stakers_receiver.next().now_or_never().is_none());
+
+    let evidence = EquivocationEvidence::Vote(
+        context.sign_vote(vote).await.unwrap(),
+        context.sign_vote(conflicting_vote).await.unwrap(),
+    );
+    context.report_equivocation(evidence).await;
+    assert_eq!(
+        network.reported_stakers_receiver.next().now_or_never().unwrap().unwrap(),
+        LocalKeyStore::new_for_testing().public_key
+    );
+}
+
+#[tokio::test]
+async fn committee_stakers_are_allowed_to_connect() {
+    let (deps, mut network) = create_test_and_network_deps();
+    let _context = deps.build_context();
+
+    let stakers = network.allowed_stakers_receiver.next().now_or_never().unwrap().unwrap();
+    let num_validators = usize::try_from(NUM_VALIDATORS).unwrap();
+    assert_eq!(stakers, vec![LocalKeyStore::new_for_testing().public_key; num_validators]);
+}
+
+#[tokio::test]
+async fn committee_is_read_once_per_epoch() {
+    let (mut deps, mut network) = create_test_and_network_deps();
+    // The committee of each epoch has a single staker, whose address and key are `epoch + 1`.
+    let mut committee_provider = MockCommitteeProvider::new();
+    committee_provider.expect_get_committee().times(2).returning(|epoch| {
+        Ok(Arc::new(vec![Staker {
+            address: ContractAddress::from(epoch + 1),
+            weight: StakingWeight(1),
+            public_key: Felt::from(epoch + 1),
+        }]))
+    });
+    deps.committee_provider = Some(committee_provider);
+    let context = deps.build_context();
+    let epoch_length = ContextConfig::default().epoch_length;
+    let last_height_of_epoch = BlockNumber(epoch_length - 1);
+
+    // The committee of the epoch is read along with that of the next height.
+    let validators = context.validators(BlockNumber(epoch_length - 2)).await;
+    assert_eq!(validators, vec![Validator { id: ContractAddress::from(1_u64), weight: 1 }]);
+    assert_eq!(context.proposer(last_height_of_epoch, 0), ContractAddress::from(1_u64));
+    let stakers = network.allowed_stakers_receiver.next().now_or_never().unwrap().unwrap();
+    assert_eq!(stakers, vec![PublicKey(Felt::ONE)]);
+
+    // The next epoch is read before it starts, and its stakers are allowed to connect along with
+    // those of the current epoch.
+    context.validators(last_height_of_epoch).await;
+    assert_eq!(context.proposer(BlockNumber(epoch_length), 0), ContractAddress::from(2_u64));
+    let stakers = network.allowed_stakers_receiver.next().now_or_never().unwrap().unwrap();
+    assert_eq!(
+        stakers.into_iter().collect::<HashSet<_>>(),
+        HashSet::from([PublicKey(Felt::ONE), PublicKey(Felt::TWO)])
+    );
+}
+
+#[tokio::test]
+async fn pipelined_proposal_is_used_in_the_next_height() {
+    let (mut deps, mut network) = create_test_and_network_deps();
+    // Height 1 is pipelined on top of the proposal validated in height 0.
+    deps.setup_deps_for_validate(BlockNumber(0), INTERNAL_TX_BATCH.len());
+    deps.batcher
+        .expect_pipeline_next_height()
+        .times(1)
+        .withf(|input| input.parent_proposal_id == ProposalId(0))
+        .return_const(Ok(PipelineNextHeightResponse { parent_l2_gas_used: GasAmount(0) }));
+    // The batcher starts building the proposal of height 1 only once, before height 0 is decided.
+    deps.batcher
+        .expect_propose_block()
+        .times(1)
+        .withf(|input| {
+            input.proposal_id == ProposalId(1) && input.block_info.block_number == BlockNumber(1)
+        })
+        .return_const(Ok(()));
+    deps.batcher
+        .expect_decision_reached()
+        .times(1)
+        .withf(|input| input.proposal_id == ProposalId(0))
+        .return_once(|_| Ok(DecisionReachedResponse::default()));
+    deps.state_sync_client.expect_add_new_block().times(1).return_once(|_| Ok(()));
+    deps.cende_ambassador.expect_prepare_blob_for_next_height().return_once(|_| Ok(()));
+    deps.batcher
+        .expect_start_height()
+        .times(1)
+        .withf(|input| input.height == BlockNumber(1))
+        .return_const(Ok(()));
+    deps.batcher
+        .expect_get_proposal_content()
+        .times(1)
+        .withf(|input| input.proposal_id == ProposalId(1))
+        .return_const(Ok(GetProposalContentResponse {
+            content: GetProposalContent::Finished {
+                id: ProposalCommitment { state_diff_commitment: STATE_DIFF_COMMITMENT },
+                final_n_executed_txs: 0,
+            },
+        }));
+    let mut context = deps.build_context();
+    context.config.pipeline_next_height = true;
+
+    context.set_height_and_round(BlockNumber(0), 0).await;
+    let (mut content_sender, content_receiver) = mpsc::channel(context.config.proposal_buffer_size);
+    content_sender.send(ProposalPart::BlockInfo(block_info(BlockNumber(0)))).await.unwrap();
+    content_sender
+        .send(ProposalPart::Transactions(TransactionBatch { transactions: TX_BATCH.to_vec() }))
+        .await
+        .unwrap();
+    content_sender
+        .send(ProposalPart::ExecutedTransactionCount(INTERNAL_TX_BATCH.len().try_into().unwrap()))
+        .await
+        .unwrap();
+    content_sender
+        .send(ProposalPart::Fin(ProposalFin {
+            proposal_commitment: BlockHash(STATE_DIFF_COMMITMENT.0 .0),
+        }))
+        .await
+        .unwrap();
+    let fin_receiver =
+        context.validate_proposal(ProposalInit::default(), TIMEOUT, content_receiver).await;
+    let block = fin_receiver.await.unwrap();
+
+    context.prevote_quorum_reached(BlockNumber(0), 0, block).await;
+    // A quorum for the same block in a later round keeps the pipelined proposal.
+    context.prevote_quorum_reached(BlockNumber(0), 1, block).await;
+    context.decision_reached(block, vec![Vote::default()]).await.unwrap();
+
+    let init = ProposalInit { height: BlockNumber(1), ..Default::default() };
+    let fin_receiver = context.build_proposal(init, TIMEOUT).await;
+    let (_, mut receiver) = network.outbound_proposal_receiver.next().await.unwrap();
+    assert_eq!(receiver.next().await.unwrap(), ProposalPart::Init(init));
+    let ProposalPart::BlockInfo(info) = receiver.next().await.unwrap() else {
+        panic!("Expected ProposalPart::BlockInfo");
+    };
+    assert_eq!(info.height, BlockNumber(1));
+    // The price validators expect after the decision.
+    assert_eq!(info.l2_gas_price_fri, context.l2_gas_price);
+    assert_eq!(fin_receiver.await.unwrap(), block);
+}
@@ -32,6 +32,8 @@ use apollo_network::network_manager::test_utils::{
 };
 use apollo_network::network_manager::{BroadcastTopicChannels, BroadcastTopicClient};
 use apollo_protobuf::consensus::{ConsensusBlockInfo, HeightAndRound, ProposalPart, Vote};
+use apollo_signature_manager::signature_manager::LocalKeyStore;
+use apollo_signature_manager_types::MockSignatureManagerClient;
 use apollo_state_sync_types::communication::MockStateSyncClient;
 use apollo_time::time::{Clock, DefaultClock};
 use futures::channel::mpsc;
@@ -44,6 +46,7 @@ use starknet_api::block::{
 };
 use starknet_api::consensus_transaction::{ConsensusTransaction, InternalConsensusTransaction};
 use starknet_api::core::{ChainId, Nonce, StateDiffCommitment};
+use starknet_api::crypto::utils::PublicKey;
 use starknet_api::data_availability::L1DataAvailabilityMode;
 use starknet_api::felt;
 use starknet_api::hash::PoseidonHash;
@@ -51,6 +54,7 @@ use starknet_api::test_utils::invoke::{rpc_invoke_tx, InvokeTxArgs};
 use starknet_types_core::felt::Felt;
 
 use crate::cende::MockCendeContext;
+use crate::committee_provider::{CommitteeProvider, MockCommitteeProvider};
 use crate::config::ContextConfig;
 use crate::orchestrator_versioned_constants::VersionedConstants;
 use crate::sequencer_consensus_context::{
@@ -98,6 +102,10 @@ pub(crate) struct TestDeps {
     pub clock: Arc<dyn Clock>,
     pub outbound_proposal_sender: mpsc::Sender<(HeightAndRound, mpsc::Receiver<ProposalPart>)>,
     pub vote_broadcast_client: BroadcastTopicClient<Vote>,
+    pub signature_manager_client: MockSignatureManagerClient,
+    pub allowed_stakers_sender: mpsc::Sender<Vec<PublicKey>>,
+    pub reported_stakers_sender: mpsc::Sender<PublicKey>,
+    pub committee_provider: Option<MockCommitteeProvider>,
 }
 
 impl From<TestDeps> for SequencerConsensusContextDeps {
@@ -112,6 +120,12 @@ impl From<TestDeps> for SequencerConsensusContextDeps {
             clock: deps.clock,
             outbound_proposal_sender: deps.outbound_proposal_sender,
             vote_broadcast_client: deps.vote_broadcast_client,
+            signature_manager_client: Arc::new(deps.signature_manager_client),
+            allowed_stakers_sender: deps.allowed_stakers_sender,
+            reported_stakers_sender: deps.reported_stakers_sender,
+            committee_provider: deps.committee_provider.map(|committee_provider| {
+                Arc::new(committee_provider) as Arc<dyn CommitteeProvider>
+            }),
         }
     }
 }
@@ -240,6 +254,10 @@ impl TestDeps {
             ContextConfig {
                 proposal_buffer_size: CHANNEL_SIZE,
                 num_validators: NUM_VALIDATORS,
+                validator_public_keys: vec![
+                    LocalKeyStore::new_for_testing().public_key;
+                    usize::try_from(NUM_VALIDATORS).unwrap()
+                ],
                 chain_id: CHAIN_ID,
                 ..Default::default()
             },
@@ -264,6 +282,9 @@ pub(crate) fn create_test_and_network_deps() -> (TestDeps, NetworkDependencies)
     let eth_to_strk_oracle_client = MockEthToStrkOracleClientTrait::new();
     let l1_gas_price_provider = MockL1GasPriceProviderClient::new();
     let clock = Arc::new(DefaultClock);
+    let signature_manager_client = MockSignatureManagerClient::new();
+    let (allowed_stakers_sender, allowed_stakers_receiver) = mpsc::channel(CHANNEL_SIZE);
+    let (reported_stakers_sender, reported_stakers_receiver) = mpsc::channel(CHANNEL_SIZE);
 
     let test_deps = TestDeps {
         transaction_converter,
@@ -275,10 +296,18 @@ pub(crate) fn create_test_and_network_deps() -> (TestDeps, NetworkDependencies)
         clock,
         outbound_proposal_sender,
         vote_broadcast_client: votes_topic_client,
+        signature_manager_client,
+        allowed_stakers_sender,
+        reported_stakers_sender,
+        committee_provider: None,
     };
 
-    let network_deps =
-        NetworkDependencies { _vote_network: mock_vote_network, outbound_proposal_receiver };
+    let network_deps = NetworkDependencies {
+        _vote_network: mock_vote_network,
+        outbound_proposal_receiver,
+        allowed_stakers_receiver,
+        reported_stakers_receiver,
+    };
 
     (test_deps, network_deps)
 }
@@ -309,4 +338,6 @@ pub(crate) fn block_info(height: BlockNumber) -> ConsensusBlockInfo {
 pub(crate) struct NetworkDependencies {
     _vote_network: BroadcastNetworkMock<Vote>,
     pub outbound_proposal_receiver: mpsc::Receiver<(HeightAndRound, mpsc::Receiver<ProposalPart>)>,
+    pub allowed_stakers_receiver: mpsc::Receiver<Vec<PublicKey>>,
+    pub reported_stakers_receiver: mpsc::Receiver<PublicKey>,
 }
@@ -15,6 +15,7 @@ use apollo_mempool::metrics::MEMPOOL_ALL_METRICS;
 use apollo_mempool_p2p::metrics::MEMPOOL_P2P_ALL_METRICS;
 use apollo_state_sync_metrics::metrics::STATE_SYNC_ALL_METRICS;
 use blockifier::metrics::BLOCKIFIER_ALL_METRICS;
+use papyrus_base_layer::metrics::BASE_LAYER_ALL_METRICS;
 
 #[test]
 fn metric_names_no_duplications() {
@@ -34,6 +35,7 @@ fn metric_names_no_duplications() {
         .chain(MEMPOOL_P2P_ALL_METRICS.iter())
         .chain(STATE_SYNC_ALL_METRICS.iter())
         .chain(BLOCKIFIER_ALL_METRICS.iter())
+        .chain(BASE_LAYER_ALL_METRICS.iter())
         .collect::<Vec<&&'static str>>();
 
     let mut unique_metric_names: HashSet<&&'static str> = HashSet::new();
@@ -132,6 +132,8 @@ pub struct DeploymentConfigOverride {
     l1_provider_config_provider_startup_height_override_is_none: bool,
     #[serde(rename = "consensus_manager_config.context_config.num_validators")]
     consensus_manager_config_context_config_num_validators: usize,
+    #[serde(rename = "consensus_manager_config.context_config.validator_public_keys")]
+    consensus_manager_config_context_config_validator_public_keys: String,
     #[serde(flatten)]
     state_sync_config: StateSyncConfig,
 }
@@ -146,7 +148,7 @@ impl DeploymentConfigOverride {
         strk_fee_token_address: impl ToString,
         pragma_domain: PragmaDomain,
         l1_startup_height_override: Option<BlockNumber>,
-        consensus_manager_config_context_config_num_validators: usize,
+        validator_public_keys: &[&str],
         state_sync_type: StateSyncType,
     ) -> Self {
         let (
@@ -167,7 +169,9 @@ impl DeploymentConfigOverride {
                 .format(&[&pragma_domain]),
             l1_provider_config_provider_startup_height_override,
             l1_provider_config_provider_startup_height_override_is_none,
-            consensus_manager_config_context_config_num_validators,
+            consensus_manager_config_context_config_num_validators: validator_public_keys.len(),
+            consensus_manager_config_context_config_validator_public_keys: validator_public_keys
+                .join(" "),
             state_sync_config: state_sync_type.get_state_sync_config(),
         }
     }
@@ -26,6 +26,10 @@ pub(crate) const CONFIG_BASE_DIR: &str = "crates/apollo_deployments/resources/";
 pub(crate) const DEPLOYMENT_CONFIG_DIR_NAME: &str = "deployments/";
 pub(crate) const BASE_APP_CONFIG_PATH: &str =
     "crates/apollo_deployments/resources/base_app_config.json";
+// The public key of `signature_manager_config.private_key` in the secrets of the deployments'
+// nodes, which they all sign their votes with.
+pub(crate) const VALIDATOR_PUBLIC_KEY: &str =
+    "0x125d56b1fbba593f1dd215b7c55e384acd838cad549c4a2b9c6d32d264f4e2a";
 
 type DeploymentFn = fn() -> Vec<Deployment>;
 
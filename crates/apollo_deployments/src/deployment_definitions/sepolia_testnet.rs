@@ -2,7 +2,7 @@ use apollo_infra_utils::template::Template;
 
 use crate::config_override::{ConfigOverride, DeploymentConfigOverride};
 use crate::deployment::{Deployment, P2PCommunicationType, PragmaDomain};
-use crate::deployment_definitions::{Environment, StateSyncType};
+use crate::deployment_definitions::{Environment, StateSyncType, VALIDATOR_PUBLIC_KEY};
 use crate::deployments::hybrid::{create_hybrid_instance_config_override, INSTANCE_NAME_FORMAT};
 use crate::k8s::{ExternalSecret, IngressParams, K8sServiceConfigParams};
 use crate::service::NodeType;
@@ -28,7 +28,7 @@ fn deployment_config_override() -> DeploymentConfigOverride {
         "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d",
         PragmaDomain::Dev,
         None,
-        NODE_IDS.len(),
+        &[VALIDATOR_PUBLIC_KEY; NODE_IDS.len()],
         StateSyncType::Central,
     )
 }
@@ -7,7 +7,7 @@ use crate::config_override::{
     NetworkConfigOverride,
 };
 use crate::deployment::{Deployment, PragmaDomain};
-use crate::deployment_definitions::{Environment, StateSyncType};
+use crate::deployment_definitions::{Environment, StateSyncType, VALIDATOR_PUBLIC_KEY};
 use crate::k8s::IngressParams;
 use crate::service::NodeType;
 
@@ -31,7 +31,7 @@ fn testing_deployment_config_override() -> DeploymentConfigOverride {
         "0x1002",
         PragmaDomain::Dev,
         Some(BlockNumber(1)),
-        TESTING_NODE_IDS.len(),
+        &[VALIDATOR_PUBLIC_KEY; TESTING_NODE_IDS.len()],
         StateSyncType::P2P,
     )
 }
@@ -2,7 +2,7 @@ use apollo_infra_utils::template::Template;
 
 use crate::config_override::{ConfigOverride, DeploymentConfigOverride};
 use crate::deployment::{Deployment, P2PCommunicationType, PragmaDomain};
-use crate::deployment_definitions::{Environment, StateSyncType};
+use crate::deployment_definitions::{Environment, StateSyncType, VALIDATOR_PUBLIC_KEY};
 use crate::deployments::hybrid::{create_hybrid_instance_config_override, INSTANCE_NAME_FORMAT};
 use crate::k8s::{ExternalSecret, IngressParams, K8sServiceConfigParams};
 use crate::service::NodeType;
@@ -31,7 +31,7 @@ fn upgrade_test_deployment_config_override() -> DeploymentConfigOverride {
         "0x54a93d918d62b2fb62b25e77d9cb693bd277ab7e6fa236e53af263f1adb40e4",
         PragmaDomain::Dev,
         None,
-        UPGRADE_TEST_NODE_IDS.len(),
+        &[VALIDATOR_PUBLIC_KEY; UPGRADE_TEST_NODE_IDS.len()],
         StateSyncType::Central,
     )
 }
@@ -8,6 +8,8 @@ pub(crate) const FIX_BINARY_NAME: &str = "deployment_generator";
 pub struct SecretsConfigOverride {
     #[serde(rename = "base_layer_config.node_url")]
     base_layer_config_node_url: Url,
+    #[serde(rename = "consensus_manager_config.eth_to_strk_oracle_config.additional_sources")]
+    consensus_manager_config_eth_to_strk_oracle_config_additional_sources: String,
     #[serde(rename = "consensus_manager_config.eth_to_strk_oracle_config.base_url")]
     consensus_manager_config_eth_to_strk_oracle_config_base_url: Url,
     #[serde(rename = "consensus_manager_config.eth_to_strk_oracle_config.headers")]
@@ -28,6 +30,8 @@ pub struct SecretsConfigOverride {
     )]
     mempool_p2p_config_network_config_secret_key: Option<Vec<u8>>,
     recorder_url: Url,
+    #[serde(rename = "signature_manager_config.private_key")]
+    signature_manager_config_private_key: String,
     #[serde(
         rename = "state_sync_config.central_sync_client_config.central_source_config.http_headers"
     )]
@@ -43,6 +47,7 @@ impl Default for SecretsConfigOverride {
     fn default() -> Self {
         Self {
             base_layer_config_node_url: Url::parse("https://arbitrary.url.com").unwrap(),
+            consensus_manager_config_eth_to_strk_oracle_config_additional_sources: "".to_string(),
             consensus_manager_config_eth_to_strk_oracle_config_base_url: Url::parse(
                 "https://arbitrary.eth_to_strk_oracle.url",
             )
@@ -55,6 +60,7 @@ impl Default for SecretsConfigOverride {
             ],
             mempool_p2p_config_network_config_secret_key: None,
             recorder_url: Url::parse("https://arbitrary.recorder.url").unwrap(),
+            signature_manager_config_private_key: "".to_string(),
             state_sync_config_central_sync_client_config_central_source_config_http_headers: ""
                 .to_string(),
             state_sync_config_network_config_secret_key: None,
@@ -38,6 +38,8 @@ use apollo_node::config::component_config::ComponentConfig;
 use apollo_node::config::definitions::ConfigPointersMap;
 use apollo_node::config::node_config::{SequencerNodeConfig, CONFIG_POINTERS};
 use apollo_rpc::RpcConfig;
+use apollo_signature_manager::config::SignatureManagerConfig;
+use apollo_signature_manager::signature_manager::LocalKeyStore;
 use apollo_state_sync::config::StateSyncConfig;
 use apollo_storage::StorageConfig;
 use axum::extract::Query;
@@ -250,6 +252,7 @@ pub fn create_node_config(
             mempool_config,
             mempool_p2p_config,
             monitoring_endpoint_config,
+            signature_manager_config: SignatureManagerConfig::new_for_testing(),
             state_sync_config,
             components: component_config,
             l1_scraper_config,
@@ -289,6 +292,11 @@ pub(crate) fn create_consensus_manager_configs_from_network_configs(
             },
             context_config: ContextConfig {
                 num_validators,
+                // All the nodes sign with the same testing key.
+                validator_public_keys: vec![
+                    LocalKeyStore::new_for_testing().public_key;
+                    n_composed_nodes
+                ],
                 chain_id: chain_id.clone(),
                 builder_address: ContractAddress::from(4_u128),
                 ..Default::default()
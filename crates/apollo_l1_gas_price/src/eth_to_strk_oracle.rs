@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashMap};
 use std::num::NonZeroUsize;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use apollo_config::converters::{deserialize_optional_map, serialize_optional_map};
@@ -9,10 +9,12 @@ use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use apollo_l1_gas_price_types::errors::EthToStrkOracleClientError;
 use apollo_l1_gas_price_types::EthToStrkOracleClientTrait;
 use async_trait::async_trait;
+use futures::future::join_all;
 use futures::FutureExt;
 use lru::LruCache;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use serde::{Deserialize, Serialize};
+use serde::de::Error as DeserializationError;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json;
 use tokio_util::task::AbortOnDropHandle;
 use tracing::{debug, info, instrument, warn};
@@ -21,7 +23,14 @@ use url::Url;
 use crate::metrics::{
     register_eth_to_strk_metrics,
     ETH_TO_STRK_ERROR_COUNT,
+    ETH_TO_STRK_FALLBACK_COUNT,
+    ETH_TO_STRK_RATE_DEVIATION_COUNT,
+    ETH_TO_STRK_SOURCE_DEVIATION_COUNT,
+    ETH_TO_STRK_SOURCE_ERROR_COUNT,
+    ETH_TO_STRK_SOURCE_LABELS,
+    ETH_TO_STRK_SOURCE_SUCCESS_COUNT,
     ETH_TO_STRK_SUCCESS_COUNT,
+    MAX_ETH_TO_STRK_SOURCES,
 };
 
 #[cfg(test)]
@@ -43,13 +52,75 @@ fn hashmap_to_headermap(hash_map: Option<HashMap<String, String>>) -> HeaderMap
     header_map
 }
 
+/// The format of the response of an eth to strk oracle source. The response may also include a
+/// `timestamp` field, with the time (seconds) the rate was taken at.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EthToStrkResponseParser {
+    /// `{"price": "0x...", "decimals": 18}`: the rate as a hexadecimal string, with
+    /// `ETH_TO_STRK_QUANTIZATION` decimals.
+    #[default]
+    HexPriceWithDecimals,
+    /// `{"price": "3456.78"}` or `{"price": 3456.78}`: the number of STRK per ETH.
+    DecimalPrice,
+}
+
+/// An eth to strk oracle source, queried in addition to `base_url`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EthToStrkOracleSource {
+    /// URL to query, with the `timestamp` parameter appended like for `base_url`.
+    pub url: Url,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub parser: EthToStrkResponseParser,
+}
+
+/// Serializes the sources into a space-separated string of JSON objects.
+fn serialize_optional_sources(sources: &Option<Vec<EthToStrkOracleSource>>) -> String {
+    match sources {
+        None => "".to_owned(),
+        Some(sources) => sources
+            .iter()
+            .map(|source| {
+                serde_json::to_string(source).expect("Failed to serialize EthToStrkOracleSource")
+            })
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
+
+/// Deserializes a space-separated string of JSON objects into sources.
+fn deserialize_optional_sources<'de, D>(
+    de: D,
+) -> Result<Option<Vec<EthToStrkOracleSource>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: String = Deserialize::deserialize(de)?;
+    if raw.trim().is_empty() {
+        return Ok(None);
+    }
+    raw.split_whitespace()
+        .map(|item| {
+            serde_json::from_str(item)
+                .map_err(|e| D::Error::custom(format!("Invalid JSON '{item}': {e}")))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct EthToStrkOracleConfig {
     pub base_url: Url,
     #[serde(deserialize_with = "deserialize_optional_map")]
     pub headers: Option<HashMap<String, String>>,
+    #[serde(deserialize_with = "deserialize_optional_sources")]
+    pub additional_sources: Option<Vec<EthToStrkOracleSource>>,
     pub lag_interval_seconds: u64,
     pub max_cache_size: usize,
+    pub max_rate_deviation_percent: u64,
+    pub max_staleness_seconds: u64,
     pub query_timeout_sec: u64,
 }
 
@@ -64,6 +135,14 @@ impl SerializeConfig for EthToStrkOracleConfig {
                  rate.",
                 ParamPrivacyInput::Private,
             ),
+            ser_param(
+                "additional_sources",
+                &serialize_optional_sources(&self.additional_sources),
+                "Eth to strk oracle sources to query in addition to `base_url`, formatted as \
+                 space-separated JSON objects with `url`, `headers` and `parser` fields. The \
+                 median of the rates of all the sources is used.",
+                ParamPrivacyInput::Private,
+            ),
             ser_param(
                 "headers",
                 &serialize_optional_map(&self.headers),
@@ -85,6 +164,24 @@ impl SerializeConfig for EthToStrkOracleConfig {
                 "The maximum number of cached conversion rates.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_rate_deviation_percent",
+                &self.max_rate_deviation_percent,
+                "The maximal deviation (percent) of a rate from the last good rate, and of the \
+                 rate of a source from the median of the rates of all the sources. A rate which \
+                 deviates from the last good rate is replaced by the latter, unless it's stale. \
+                 Sources with deviating rates are discarded, and if they are the majority, the \
+                 query fails.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_staleness_seconds",
+                &self.max_staleness_seconds,
+                "The maximal age (seconds) of a rate, relative to the queried timestamp. Older \
+                 rates returned by a source are discarded, and an older last good rate isn't used \
+                 as a fallback, nor to check the deviation of new rates.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "query_timeout_sec",
                 &self.query_timeout_sec,
@@ -100,8 +197,11 @@ impl Default for EthToStrkOracleConfig {
         Self {
             base_url: Url::parse("https://example.com/api").unwrap(),
             headers: None,
+            additional_sources: None,
             lag_interval_seconds: 1,
             max_cache_size: 100,
+            max_rate_deviation_percent: 20,
+            max_staleness_seconds: 3600,
             query_timeout_sec: 3,
         }
     }
@@ -110,29 +210,60 @@ impl Default for EthToStrkOracleConfig {
 /// Client for interacting with the eth to strk Oracle API.
 pub struct EthToStrkOracleClient {
     config: EthToStrkOracleConfig,
-    /// The base URL of the eth to strk Oracle API.
+    /// The queried sources: `base_url` followed by the additional sources.
+    sources: Arc<Vec<OracleSource>>,
+    client: reqwest::Client,
+    cached_prices: Mutex<LruCache<u64, u128>>,
+    queries: Mutex<LruCache<u64, AbortOnDropHandle<Result<u128, EthToStrkOracleClientError>>>>,
+    last_good_rate: Mutex<Option<TimestampedRate>>,
+}
+
+struct OracleSource {
     /// The `timestamp` parameter is appended dynamically when making requests,
     /// in order to have a stable mapping from block timestamp to conversion rate.
-    base_url: Url,
+    url: Url,
     /// HTTP headers required for requests.
     headers: HeaderMap,
-    client: reqwest::Client,
-    cached_prices: Mutex<LruCache<u64, u128>>,
-    queries: Mutex<LruCache<u64, AbortOnDropHandle<Result<u128, EthToStrkOracleClientError>>>>,
+    parser: EthToStrkResponseParser,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TimestampedRate {
+    timestamp: u64,
+    rate: u128,
 }
 
 impl EthToStrkOracleClient {
     pub fn new(config: EthToStrkOracleConfig) -> Self {
         info!(
             "Creating EthToStrkOracleClient with: base_url={:} headers={:?} \
-             lag_interval_seconds={}",
-            config.base_url, config.headers, config.lag_interval_seconds
+             lag_interval_seconds={} additional_sources={}",
+            config.base_url,
+            config.headers,
+            config.lag_interval_seconds,
+            config.additional_sources.as_ref().map_or(0, Vec::len)
         );
         register_eth_to_strk_metrics();
+        let base_source = OracleSource {
+            url: config.base_url.clone(),
+            headers: hashmap_to_headermap(config.headers.clone()),
+            parser: EthToStrkResponseParser::default(),
+        };
+        let additional_sources =
+            config.additional_sources.iter().flatten().map(|source| OracleSource {
+                url: source.url.clone(),
+                headers: hashmap_to_headermap(Some(source.headers.clone().into_iter().collect())),
+                parser: source.parser,
+            });
+        let sources: Vec<_> = std::iter::once(base_source).chain(additional_sources).collect();
+        assert!(
+            sources.len() <= MAX_ETH_TO_STRK_SOURCES,
+            "At most {MAX_ETH_TO_STRK_SOURCES} eth to strk oracle sources are supported, got {}.",
+            sources.len()
+        );
+
         Self {
-            config: config.clone(),
-            base_url: config.base_url,
-            headers: hashmap_to_headermap(config.headers),
+            sources: Arc::new(sources),
             client: reqwest::Client::new(),
             cached_prices: Mutex::new(LruCache::new(
                 NonZeroUsize::new(config.max_cache_size).expect("Invalid cache size"),
@@ -140,51 +271,214 @@ impl EthToStrkOracleClient {
             queries: Mutex::new(LruCache::new(
                 NonZeroUsize::new(config.max_cache_size).expect("Invalid cache size"),
             )),
+            last_good_rate: Mutex::new(None),
+            config,
         }
     }
 
+    /// Queries all the sources, and resolves to the median of the rates that agree with each other.
     fn spawn_query(
         &self,
         quantized_timestamp: u64,
     ) -> AbortOnDropHandle<Result<u128, EthToStrkOracleClientError>> {
         let adjusted_timestamp = quantized_timestamp * self.config.lag_interval_seconds;
         let client = self.client.clone();
-        let base_url = self.base_url.clone();
-        let headers = self.headers.clone();
-        let query_timeout_sec = self.config.query_timeout_sec;
+        let sources = self.sources.clone();
+        let query_timeout = Duration::from_secs(self.config.query_timeout_sec);
+        let max_staleness_seconds = self.config.max_staleness_seconds;
+        let max_rate_deviation_percent = self.config.max_rate_deviation_percent;
 
         let future = async move {
-            let response_body = loop {
-                let mut url = base_url.clone();
-                url.query_pairs_mut().append_pair("timestamp", &adjusted_timestamp.to_string());
-
-                let result = tokio::time::timeout(Duration::from_secs(query_timeout_sec), async {
-                    let response = client.get(url).headers(headers.clone()).send().await?;
-                    let body = response.text().await?;
-                    Ok::<_, EthToStrkOracleClientError>(body)
-                })
-                .await;
-
-                match result {
-                    Ok(inner_result) => {
-                        break inner_result?;
+            let queries = sources.iter().enumerate().map(|(index, source)| {
+                let client = &client;
+                async move {
+                    let result = query_source(client, source, adjusted_timestamp, query_timeout)
+                        .await
+                        .and_then(|(rate, rate_timestamp)| {
+                            check_staleness(
+                                adjusted_timestamp,
+                                rate_timestamp,
+                                max_staleness_seconds,
+                            )
+                            .map(|()| rate)
+                        });
+                    let labels = ETH_TO_STRK_SOURCE_LABELS[index];
+                    match &result {
+                        Ok(_) => ETH_TO_STRK_SOURCE_SUCCESS_COUNT.increment(1, labels),
+                        Err(e) => {
+                            warn!(
+                                "Eth to strk oracle source {index} failed for timestamp \
+                                 {adjusted_timestamp}: {e:?}"
+                            );
+                            ETH_TO_STRK_SOURCE_ERROR_COUNT.increment(1, labels);
+                        }
                     }
-                    Err(_) => {
-                        ETH_TO_STRK_ERROR_COUNT.increment(1);
-                        warn!("Timeout when resolving query for timestamp {adjusted_timestamp}");
-                        continue;
+                    result
+                }
+            });
+            let mut rates = Vec::new();
+            let mut first_error = None;
+            for (index, result) in join_all(queries).await.into_iter().enumerate() {
+                match result {
+                    Ok(rate) => rates.push((index, rate)),
+                    Err(e) => {
+                        first_error.get_or_insert(e);
                     }
                 }
-            };
-            resolve_query(response_body)
+            }
+            if rates.is_empty() {
+                return Err(first_error.expect("There is at least one oracle source."));
+            }
+            aggregate_rates(adjusted_timestamp, rates, max_rate_deviation_percent)
         };
 
         AbortOnDropHandle::new(tokio::spawn(future))
     }
+
+    /// Returns the last good rate, unless it's stale relative to `timestamp`.
+    fn fallback_rate(&self, timestamp: u64) -> Option<TimestampedRate> {
+        self.last_good_rate.lock().unwrap().filter(|last_good_rate| {
+            last_good_rate.timestamp.abs_diff(timestamp) <= self.config.max_staleness_seconds
+        })
+    }
+
+    /// Fails if `rate` deviates too much from the last good rate, unless the latter is stale.
+    fn check_rate_deviation(
+        &self,
+        timestamp: u64,
+        rate: u128,
+    ) -> Result<u128, EthToStrkOracleClientError> {
+        match self.fallback_rate(timestamp) {
+            Some(last_good_rate)
+                if deviates(rate, last_good_rate.rate, self.config.max_rate_deviation_percent) =>
+            {
+                ETH_TO_STRK_RATE_DEVIATION_COUNT.increment(1);
+                Err(EthToStrkOracleClientError::RateDeviationError {
+                    timestamp,
+                    rate,
+                    last_good_rate: last_good_rate.rate,
+                })
+            }
+            _ => Ok(rate),
+        }
+    }
+
+    fn update_last_good_rate(&self, timestamp: u64, rate: u128) {
+        let mut last_good_rate = self.last_good_rate.lock().unwrap();
+        if last_good_rate.is_none_or(|last_good_rate| last_good_rate.timestamp <= timestamp) {
+            *last_good_rate = Some(TimestampedRate { timestamp, rate });
+        }
+    }
+}
+
+/// Returns the rate of the source, and the time it was taken at, if the source reports it.
+async fn query_source(
+    client: &reqwest::Client,
+    source: &OracleSource,
+    adjusted_timestamp: u64,
+    query_timeout: Duration,
+) -> Result<(u128, Option<u64>), EthToStrkOracleClientError> {
+    let response_body = loop {
+        let mut url = source.url.clone();
+        url.query_pairs_mut().append_pair("timestamp", &adjusted_timestamp.to_string());
+
+        let result = tokio::time::timeout(query_timeout, async {
+            let response = client.get(url).headers(source.headers.clone()).send().await?;
+            let body = response.text().await?;
+            Ok::<_, EthToStrkOracleClientError>(body)
+        })
+        .await;
+
+        match result {
+            Ok(inner_result) => {
+                break inner_result?;
+            }
+            Err(_) => {
+                ETH_TO_STRK_ERROR_COUNT.increment(1);
+                warn!("Timeout when resolving query for timestamp {adjusted_timestamp}");
+                continue;
+            }
+        }
+    };
+    resolve_query(response_body, source.parser)
 }
 
-fn resolve_query(body: String) -> Result<u128, EthToStrkOracleClientError> {
+fn check_staleness(
+    timestamp: u64,
+    rate_timestamp: Option<u64>,
+    max_staleness_seconds: u64,
+) -> Result<(), EthToStrkOracleClientError> {
+    match rate_timestamp {
+        Some(rate_timestamp)
+            if timestamp.saturating_sub(rate_timestamp) > max_staleness_seconds =>
+        {
+            Err(EthToStrkOracleClientError::StaleRateError { timestamp, rate_timestamp })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn median(mut rates: Vec<u128>) -> Option<u128> {
+    rates.sort_unstable();
+    let middle = rates.len() / 2;
+    match rates.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(rates[middle]),
+        _ => Some(rates[middle - 1] + (rates[middle] - rates[middle - 1]) / 2),
+    }
+}
+
+/// Returns the median of the rates that deviate from the median of all the `(source index, rate)`
+/// pairs by at most `max_rate_deviation_percent`, as long as they are the majority.
+fn aggregate_rates(
+    timestamp: u64,
+    rates: Vec<(usize, u128)>,
+    max_rate_deviation_percent: u64,
+) -> Result<u128, EthToStrkOracleClientError> {
+    let n_rates = rates.len();
+    let median_rate =
+        median(rates.iter().map(|(_, rate)| *rate).collect()).expect("There is at least one rate.");
+    let (agreeing_rates, deviating_rates): (Vec<_>, Vec<_>) = rates
+        .into_iter()
+        .partition(|(_, rate)| !deviates(*rate, median_rate, max_rate_deviation_percent));
+    for (index, rate) in &deviating_rates {
+        warn!(
+            "Eth to strk oracle source {index} rate {rate} for timestamp {timestamp} deviates \
+             from the median rate {median_rate}."
+        );
+        ETH_TO_STRK_SOURCE_DEVIATION_COUNT.increment(1, ETH_TO_STRK_SOURCE_LABELS[*index]);
+    }
+    if agreeing_rates.len() * 2 <= n_rates {
+        return Err(EthToStrkOracleClientError::RatesDisagreeError {
+            timestamp,
+            n_agreeing: agreeing_rates.len(),
+            n_rates,
+        });
+    }
+    Ok(median(agreeing_rates.into_iter().map(|(_, rate)| rate).collect())
+        .expect("There is at least one agreeing rate."))
+}
+
+fn deviates(rate: u128, reference_rate: u128, max_rate_deviation_percent: u64) -> bool {
+    rate.abs_diff(reference_rate) * 100 > reference_rate * u128::from(max_rate_deviation_percent)
+}
+
+fn resolve_query(
+    body: String,
+    parser: EthToStrkResponseParser,
+) -> Result<(u128, Option<u64>), EthToStrkOracleClientError> {
     let json: serde_json::Value = serde_json::from_str(&body)?;
+    let rate = match parser {
+        EthToStrkResponseParser::HexPriceWithDecimals => parse_hex_price_with_decimals(&json)?,
+        EthToStrkResponseParser::DecimalPrice => parse_decimal_price(&json)?,
+    };
+    let rate_timestamp = json.get("timestamp").and_then(|v| v.as_u64());
+    Ok((rate, rate_timestamp))
+}
+
+fn parse_hex_price_with_decimals(
+    json: &serde_json::Value,
+) -> Result<u128, EthToStrkOracleClientError> {
     let price = json
         .get("price")
         .and_then(|v| v.as_str())
@@ -203,15 +497,37 @@ fn resolve_query(body: String) -> Result<u128, EthToStrkOracleClientError> {
             decimals,
         ));
     }
-    ETH_TO_STRK_SUCCESS_COUNT.increment(1);
     Ok(rate)
 }
 
+/// Converts a decimal number of STRK per ETH to a rate with `ETH_TO_STRK_QUANTIZATION` decimals.
+fn parse_decimal_price(json: &serde_json::Value) -> Result<u128, EthToStrkOracleClientError> {
+    let price = match json.get("price") {
+        Some(serde_json::Value::String(price)) => price.clone(),
+        Some(serde_json::Value::Number(price)) => price.to_string(),
+        _ => return Err(EthToStrkOracleClientError::MissingFieldError("price")),
+    };
+    let invalid_rate = || EthToStrkOracleClientError::InvalidRateError(price.clone());
+
+    let (integer, fraction) = price.split_once('.').unwrap_or((&price, ""));
+    let decimals = usize::try_from(ETH_TO_STRK_QUANTIZATION).expect("Decimals fit in usize.");
+    if integer.is_empty()
+        || fraction.len() > decimals
+        || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid_rate());
+    }
+    let digits = format!("{integer}{fraction:0<decimals$}");
+    digits.parse().map_err(|_| invalid_rate())
+}
+
 #[async_trait]
 impl EthToStrkOracleClientTrait for EthToStrkOracleClient {
-    /// The HTTP response must include the following fields:
+    /// The HTTP response of `base_url` must include the following fields:
     /// - `price`: a hexadecimal string representing the price.
     /// - `decimals`: a `u64` value, must be equal to `ETH_TO_STRK_QUANTIZATION`.
+    ///
+    /// The responses of the additional sources are parsed according to their configured parser.
     #[instrument(skip(self))]
     async fn eth_to_fri_rate(&self, timestamp: u64) -> Result<u128, EthToStrkOracleClientError> {
         let quantized_timestamp = (timestamp - self.config.lag_interval_seconds)
@@ -246,15 +562,21 @@ impl EthToStrkOracleClientTrait for EthToStrkOracleClient {
                 return Err(EthToStrkOracleClientError::JoinError(e));
             }
         };
-        let rate = match query_result {
+        let rate = match query_result.and_then(|rate| self.check_rate_deviation(timestamp, rate)) {
             Ok(rate) => rate,
             Err(e) => {
                 queries.pop(&quantized_timestamp);
                 warn!("Query failed to reach oracle for timestamp {timestamp}: {e:?}");
                 ETH_TO_STRK_ERROR_COUNT.increment(1);
-                return Err(e);
+                // The fallback isn't cached, so the sources are queried again on the next call.
+                let last_good_rate = self.fallback_rate(timestamp).ok_or(e)?;
+                warn!("Using the last good rate {last_good_rate:?} for timestamp {timestamp}.");
+                ETH_TO_STRK_FALLBACK_COUNT.increment(1);
+                return Ok(last_good_rate.rate);
             }
         };
+        ETH_TO_STRK_SUCCESS_COUNT.increment(1);
+        self.update_last_good_rate(timestamp, rate);
 
         // Make sure to cache the result.
         cache.put(quantized_timestamp, rate);
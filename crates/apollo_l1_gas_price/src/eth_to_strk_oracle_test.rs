@@ -1,9 +1,63 @@
+use std::collections::BTreeMap;
+
+use apollo_l1_gas_price_types::errors::EthToStrkOracleClientError;
 use apollo_l1_gas_price_types::EthToStrkOracleClientTrait;
+use assert_matches::assert_matches;
+use rstest::rstest;
 use serde_json::json;
 use tokio::{self};
 use url::Url;
 
-use crate::eth_to_strk_oracle::{EthToStrkOracleClient, EthToStrkOracleConfig};
+use crate::eth_to_strk_oracle::{
+    resolve_query,
+    EthToStrkOracleClient,
+    EthToStrkOracleConfig,
+    EthToStrkOracleSource,
+    EthToStrkResponseParser,
+};
+
+const STRK_PER_ETH: u128 = 10_u128.pow(18);
+
+async fn mock_source(
+    server: &mut mockito::ServerGuard,
+    timestamp: u64,
+    body: serde_json::Value,
+) -> mockito::Mock {
+    server
+        .mock("GET", "/")
+        .match_query(mockito::Matcher::UrlEncoded("timestamp".into(), timestamp.to_string()))
+        .with_header("Content-Type", "application/json")
+        .with_body(body.to_string())
+        .create_async()
+        .await
+}
+
+fn hex_price(rate: u128) -> serde_json::Value {
+    json!({ "price": format!("0x{rate:x}"), "decimals": 18 })
+}
+
+fn additional_source(server: &mockito::ServerGuard) -> EthToStrkOracleSource {
+    EthToStrkOracleSource {
+        url: Url::parse(&server.url()).unwrap(),
+        headers: BTreeMap::new(),
+        parser: EthToStrkResponseParser::DecimalPrice,
+    }
+}
+
+/// Queries the rate until the query resolves.
+async fn resolved_rate(
+    client: &EthToStrkOracleClient,
+    timestamp: u64,
+) -> Result<u128, EthToStrkOracleClientError> {
+    loop {
+        match client.eth_to_fri_rate(timestamp).await {
+            Err(EthToStrkOracleClientError::QueryNotReadyError(_)) => {
+                tokio::task::yield_now().await;
+            }
+            result => return result,
+        }
+    }
+}
 
 #[tokio::test]
 async fn eth_to_fri_rate_uses_cache_on_quantized_hit() {
@@ -48,3 +102,154 @@ async fn eth_to_fri_rate_uses_cache_on_quantized_hit() {
         .expect("Should resolve immediately due to the cache");
     assert_eq!(rate1, rate2);
 }
+
+#[rstest]
+#[case::hex(EthToStrkResponseParser::HexPriceWithDecimals, hex_price(3 * STRK_PER_ETH), 3 * STRK_PER_ETH)]
+#[case::decimal_string(
+    EthToStrkResponseParser::DecimalPrice,
+    json!({ "price": "3456.5" }),
+    3456 * STRK_PER_ETH + STRK_PER_ETH / 2
+)]
+#[case::decimal_number(EthToStrkResponseParser::DecimalPrice, json!({ "price": 3456 }), 3456 * STRK_PER_ETH)]
+fn parse_response(
+    #[case] parser: EthToStrkResponseParser,
+    #[case] body: serde_json::Value,
+    #[case] expected_rate: u128,
+) {
+    assert_eq!(resolve_query(body.to_string(), parser).unwrap(), (expected_rate, None));
+}
+
+#[rstest]
+#[case::exponent(json!({ "price": "3.4e3" }))]
+#[case::negative(json!({ "price": "-3456" }))]
+#[case::too_many_decimals(json!({ "price": "0.0000000000000000001" }))]
+fn parse_invalid_decimal_price(#[case] body: serde_json::Value) {
+    assert_matches!(
+        resolve_query(body.to_string(), EthToStrkResponseParser::DecimalPrice),
+        Err(EthToStrkOracleClientError::InvalidRateError(_))
+    );
+}
+
+#[tokio::test]
+async fn median_of_agreeing_sources_ignoring_failed_sources() {
+    let timestamp = 1000;
+    let mut servers = Vec::new();
+    for _ in 0..4 {
+        servers.push(mockito::Server::new_async().await);
+    }
+    let _mocks = [
+        mock_source(&mut servers[0], timestamp, hex_price(3000 * STRK_PER_ETH)).await,
+        mock_source(&mut servers[1], timestamp, json!({ "price": "3100" })).await,
+        mock_source(&mut servers[2], timestamp, json!({ "price": 5000 })).await,
+        mock_source(&mut servers[3], timestamp, json!({ "unexpected": "field" })).await,
+    ];
+    let config = EthToStrkOracleConfig {
+        base_url: Url::parse(&servers[0].url()).unwrap(),
+        additional_sources: Some(servers[1..].iter().map(additional_source).collect()),
+        ..Default::default()
+    };
+    let client = EthToStrkOracleClient::new(config);
+
+    // The timestamp is lagged by one interval. The rate of the third source deviates from the
+    // median, so it's discarded.
+    assert_eq!(resolved_rate(&client, timestamp + 1).await.unwrap(), 3050 * STRK_PER_ETH);
+}
+
+#[tokio::test]
+async fn stale_rate_is_discarded() {
+    let timestamp = 10_000;
+    let max_staleness_seconds = 60;
+    let mut server = mockito::Server::new_async().await;
+    let mut body = hex_price(3000 * STRK_PER_ETH);
+    body["timestamp"] = json!(timestamp - max_staleness_seconds - 1);
+    let _mock = mock_source(&mut server, timestamp, body).await;
+    let config = EthToStrkOracleConfig {
+        base_url: Url::parse(&server.url()).unwrap(),
+        max_staleness_seconds,
+        ..Default::default()
+    };
+    let client = EthToStrkOracleClient::new(config);
+
+    assert_matches!(
+        resolved_rate(&client, timestamp + 1).await,
+        Err(EthToStrkOracleClientError::StaleRateError { .. })
+    );
+}
+
+#[tokio::test]
+async fn fallback_to_last_good_rate() {
+    let good_rate = 3000 * STRK_PER_ETH;
+    let next_good_rate = 3300 * STRK_PER_ETH;
+    let max_staleness_seconds = 100;
+    let mut base_server = mockito::Server::new_async().await;
+    let mut additional_server = mockito::Server::new_async().await;
+    let _mocks = [
+        mock_source(&mut base_server, 1000, hex_price(good_rate)).await,
+        mock_source(&mut additional_server, 1000, json!({ "price": 3000 })).await,
+        mock_source(&mut base_server, 1010, hex_price(good_rate)).await,
+        mock_source(&mut additional_server, 1010, json!({ "price": 5000 })).await,
+        mock_source(&mut base_server, 2000, hex_price(good_rate)).await,
+        mock_source(&mut additional_server, 2000, json!({ "price": 5000 })).await,
+    ];
+    let failing_mocks = [
+        mock_source(&mut base_server, 1020, json!({ "unexpected": "field" })).await,
+        mock_source(&mut additional_server, 1020, json!({ "unexpected": "field" })).await,
+    ];
+    let config = EthToStrkOracleConfig {
+        base_url: Url::parse(&base_server.url()).unwrap(),
+        additional_sources: Some(vec![additional_source(&additional_server)]),
+        max_rate_deviation_percent: 20,
+        max_staleness_seconds,
+        ..Default::default()
+    };
+    let client = EthToStrkOracleClient::new(config);
+
+    assert_eq!(resolved_rate(&client, 1001).await.unwrap(), good_rate);
+    // Sources that disagree with each other fail the query, so the last good rate is used.
+    assert_eq!(resolved_rate(&client, 1011).await.unwrap(), good_rate);
+    // So is a failed query.
+    assert_eq!(resolved_rate(&client, 1021).await.unwrap(), good_rate);
+
+    // The fallback isn't cached, so once the sources recover, their rate is used.
+    for mock in failing_mocks {
+        mock.remove_async().await;
+    }
+    let _mocks = [
+        mock_source(&mut base_server, 1020, hex_price(next_good_rate)).await,
+        mock_source(&mut additional_server, 1020, json!({ "price": 3300 })).await,
+    ];
+    assert_eq!(resolved_rate(&client, 1021).await.unwrap(), next_good_rate);
+
+    // A stale last good rate isn't used.
+    assert_matches!(
+        resolved_rate(&client, 2001).await,
+        Err(EthToStrkOracleClientError::RatesDisagreeError { n_agreeing: 0, n_rates: 2, .. })
+    );
+}
+
+#[tokio::test]
+async fn single_source_rate_deviating_from_last_good_rate() {
+    let good_rate = 3000 * STRK_PER_ETH;
+    let deviating_rate = 5000 * STRK_PER_ETH;
+    let max_staleness_seconds = 100;
+    let mut server = mockito::Server::new_async().await;
+    let _mocks = [
+        mock_source(&mut server, 1000, hex_price(good_rate)).await,
+        mock_source(&mut server, 1010, hex_price(deviating_rate)).await,
+        mock_source(&mut server, 2000, hex_price(deviating_rate)).await,
+    ];
+    let config = EthToStrkOracleConfig {
+        base_url: Url::parse(&server.url()).unwrap(),
+        max_rate_deviation_percent: 20,
+        max_staleness_seconds,
+        ..Default::default()
+    };
+    let client = EthToStrkOracleClient::new(config);
+
+    assert_eq!(resolved_rate(&client, 1001).await.unwrap(), good_rate);
+    // A single source can't be checked against other sources, but its rate is still checked
+    // against the last good rate.
+    assert_eq!(resolved_rate(&client, 1011).await.unwrap(), good_rate);
+    // Once the last good rate is stale, the new rate is used.
+    assert_eq!(resolved_rate(&client, 2001).await.unwrap(), deviating_rate);
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[cfg(test)]
+#[path = "gas_price_aggregation_test.rs"]
+pub mod gas_price_aggregation_test;
+
+/// How the gas price samples in the provider's window are reduced to a single price.
+///
+/// All strategies are pure functions of the window, so every node computes the same price for the
+/// same timestamp.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GasPriceAggregationStrategy {
+    /// The arithmetic mean of the samples.
+    #[default]
+    Mean,
+    /// The median of the samples. For an even number of samples, the mean of the middle two.
+    Median,
+    /// The nearest-rank percentile of the samples.
+    Percentile,
+    /// An exponential moving average over the samples, from the oldest to the newest.
+    ExponentialMovingAverage,
+    /// Follows the samples from the oldest to the newest, limiting the change of the price in each
+    /// block to a maximal percentage.
+    CappedChangeRate,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct GasPriceAggregationConfig {
+    pub strategy: GasPriceAggregationStrategy,
+    // Only used by the `Percentile` strategy.
+    #[validate(range(max = 100))]
+    pub percentile: u64,
+    // Only used by the `ExponentialMovingAverage` strategy. The weight of each new sample.
+    #[validate(range(min = 1, max = 100))]
+    pub ema_alpha_percent: u64,
+    // Only used by the `CappedChangeRate` strategy.
+    pub max_change_per_block_percent: u64,
+}
+
+impl Default for GasPriceAggregationConfig {
+    fn default() -> Self {
+        Self {
+            strategy: GasPriceAggregationStrategy::default(),
+            percentile: 50,
+            ema_alpha_percent: 10,
+            // Matches the maximal base fee change per block in EIP-1559.
+            max_change_per_block_percent: 12,
+        }
+    }
+}
+
+impl SerializeConfig for GasPriceAggregationConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param(
+                "strategy",
+                &self.strategy,
+                "The strategy for aggregating the gas prices of the blocks in the window. One of \
+                 Mean, Median, Percentile, ExponentialMovingAverage or CappedChangeRate",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "percentile",
+                &self.percentile,
+                "The percentile (0-100) to use in the Percentile strategy",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "ema_alpha_percent",
+                &self.ema_alpha_percent,
+                "The weight (1-100 percent) of each new block in the ExponentialMovingAverage \
+                 strategy",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_change_per_block_percent",
+                &self.max_change_per_block_percent,
+                "The maximal change (percent) of the price between consecutive blocks in the \
+                 CappedChangeRate strategy",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl GasPriceAggregationConfig {
+    /// Aggregates the samples, ordered from the oldest to the newest, into a single price.
+    ///
+    /// Panics if `samples` is empty.
+    pub fn aggregate(&self, samples: &[u128]) -> u128 {
+        assert!(!samples.is_empty(), "Cannot aggregate an empty window of gas prices.");
+        match self.strategy {
+            GasPriceAggregationStrategy::Mean => mean(samples),
+            GasPriceAggregationStrategy::Median => median(samples),
+            GasPriceAggregationStrategy::Percentile => percentile(samples, self.percentile),
+            GasPriceAggregationStrategy::ExponentialMovingAverage => {
+                exponential_moving_average(samples, self.ema_alpha_percent)
+            }
+            GasPriceAggregationStrategy::CappedChangeRate => {
+                capped_change_rate(samples, self.max_change_per_block_percent)
+            }
+        }
+    }
+}
+
+fn len_as_u128(samples: &[u128]) -> u128 {
+    u128::try_from(samples.len()).expect("Cannot convert to u128")
+}
+
+fn mean(samples: &[u128]) -> u128 {
+    let sum = samples.iter().fold(0_u128, |sum, sample| sum.saturating_add(*sample));
+    sum / len_as_u128(samples)
+}
+
+fn sorted(samples: &[u128]) -> Vec<u128> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    sorted
+}
+
+fn median(samples: &[u128]) -> u128 {
+    let sorted = sorted(samples);
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        mean(&sorted[middle - 1..=middle])
+    } else {
+        sorted[middle]
+    }
+}
+
+fn percentile(samples: &[u128], percentile: u64) -> u128 {
+    let sorted = sorted(samples);
+    // Nearest-rank: the smallest sample such that at least `percentile` percent of the samples are
+    // less than or equal to it.
+    let rank = (len_as_u128(&sorted) * u128::from(percentile)).div_ceil(100);
+    let index = usize::try_from(rank.saturating_sub(1)).expect("Rank is bounded by the length.");
+    sorted[index]
+}
+
+/// Moves `from` towards `to` by `percent` percent of the distance between them.
+fn step_towards(from: u128, to: u128, percent: u64) -> u128 {
+    let percent = u128::from(percent);
+    if to >= from {
+        from.saturating_add((to - from).saturating_mul(percent) / 100)
+    } else {
+        from - (from - to).saturating_mul(percent) / 100
+    }
+}
+
+fn exponential_moving_average(samples: &[u128], alpha_percent: u64) -> u128 {
+    samples[1..]
+        .iter()
+        .fold(samples[0], |average, sample| step_towards(average, *sample, alpha_percent))
+}
+
+fn capped_change_rate(samples: &[u128], max_change_percent: u64) -> u128 {
+    samples[1..].iter().fold(samples[0], |price, sample| {
+        let max_change = price.saturating_mul(u128::from(max_change_percent)) / 100;
+        (*sample).clamp(price.saturating_sub(max_change), price.saturating_add(max_change))
+    })
+}
@@ -0,0 +1,70 @@
+use rstest::rstest;
+use validator::Validate;
+
+use crate::gas_price_aggregation::{GasPriceAggregationConfig, GasPriceAggregationStrategy};
+
+fn config(strategy: GasPriceAggregationStrategy) -> GasPriceAggregationConfig {
+    GasPriceAggregationConfig { strategy, ..Default::default() }
+}
+
+#[rstest]
+#[case::mean(GasPriceAggregationStrategy::Mean, &[10, 20, 60], 30)]
+#[case::mean_rounds_down(GasPriceAggregationStrategy::Mean, &[1, 2], 1)]
+#[case::mean_saturates(GasPriceAggregationStrategy::Mean, &[u128::MAX, u128::MAX], u128::MAX / 2)]
+#[case::median_odd(GasPriceAggregationStrategy::Median, &[60, 10, 20], 20)]
+#[case::median_even(GasPriceAggregationStrategy::Median, &[1000, 10, 20, 30], 25)]
+#[case::percentile(GasPriceAggregationStrategy::Percentile, &[40, 10, 30, 20], 20)]
+#[case::ema(GasPriceAggregationStrategy::ExponentialMovingAverage, &[100, 200, 0], 99)]
+#[case::capped_increase(GasPriceAggregationStrategy::CappedChangeRate, &[100, 1000, 1000], 125)]
+#[case::capped_decrease(GasPriceAggregationStrategy::CappedChangeRate, &[100, 0], 88)]
+#[case::capped_within_limit(GasPriceAggregationStrategy::CappedChangeRate, &[100, 110, 105], 105)]
+fn aggregate(
+    #[case] strategy: GasPriceAggregationStrategy,
+    #[case] samples: &[u128],
+    #[case] expected: u128,
+) {
+    // The default config uses a weight of 10% for the moving average and a 12% cap per block.
+    assert_eq!(config(strategy).aggregate(samples), expected);
+}
+
+#[rstest]
+#[case(0, 10)]
+#[case(25, 10)]
+#[case(26, 20)]
+#[case(75, 30)]
+#[case(90, 40)]
+#[case(100, 40)]
+fn percentile_nearest_rank(#[case] percentile: u64, #[case] expected: u128) {
+    let config = GasPriceAggregationConfig {
+        strategy: GasPriceAggregationStrategy::Percentile,
+        percentile,
+        ..Default::default()
+    };
+    assert_eq!(config.aggregate(&[40, 10, 30, 20]), expected);
+}
+
+#[test]
+fn single_sample_is_returned_by_all_strategies() {
+    for strategy in [
+        GasPriceAggregationStrategy::Mean,
+        GasPriceAggregationStrategy::Median,
+        GasPriceAggregationStrategy::Percentile,
+        GasPriceAggregationStrategy::ExponentialMovingAverage,
+        GasPriceAggregationStrategy::CappedChangeRate,
+    ] {
+        assert_eq!(config(strategy).aggregate(&[7]), 7, "{strategy:?}");
+    }
+}
+
+#[test]
+fn config_validation() {
+    assert!(GasPriceAggregationConfig::default().validate().is_ok());
+    let invalid_configs = [
+        GasPriceAggregationConfig { percentile: 101, ..Default::default() },
+        GasPriceAggregationConfig { ema_alpha_percent: 0, ..Default::default() },
+        GasPriceAggregationConfig { ema_alpha_percent: 101, ..Default::default() },
+    ];
+    for config in invalid_configs {
+        assert!(config.validate().is_err(), "{config:?} should be invalid.");
+    }
+}
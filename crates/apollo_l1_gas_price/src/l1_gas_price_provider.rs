@@ -1,7 +1,7 @@
 use std::any::type_name;
 use std::collections::{BTreeMap, VecDeque};
 
-use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use apollo_infra::component_definitions::ComponentStarter;
 use apollo_infra_utils::info_every_n;
@@ -9,10 +9,11 @@ use apollo_l1_gas_price_types::errors::L1GasPriceProviderError;
 use apollo_l1_gas_price_types::{GasPriceData, L1GasPriceProviderResult, PriceInfo};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::BlockTimestamp;
+use starknet_api::block::{BlockTimestamp, GasPrice};
 use tracing::{info, trace, warn};
 use validator::Validate;
 
+use crate::gas_price_aggregation::GasPriceAggregationConfig;
 use crate::metrics::{register_provider_metrics, L1_GAS_PRICE_PROVIDER_INSUFFICIENT_HISTORY};
 
 #[cfg(test)]
@@ -29,6 +30,10 @@ pub struct L1GasPriceProviderConfig {
     pub storage_limit: usize,
     // Maximum valid time gap between the requested timestamp and the last price sample in seconds.
     pub max_time_gap_seconds: u64,
+    #[validate]
+    pub base_fee_aggregation: GasPriceAggregationConfig,
+    #[validate]
+    pub blob_fee_aggregation: GasPriceAggregationConfig,
 }
 
 impl Default for L1GasPriceProviderConfig {
@@ -39,17 +44,19 @@ impl Default for L1GasPriceProviderConfig {
             lag_margin_seconds: 60,
             storage_limit: usize::try_from(10 * MEAN_NUMBER_OF_BLOCKS).unwrap(),
             max_time_gap_seconds: 900, // 15 minutes
+            base_fee_aggregation: GasPriceAggregationConfig::default(),
+            blob_fee_aggregation: GasPriceAggregationConfig::default(),
         }
     }
 }
 
 impl SerializeConfig for L1GasPriceProviderConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
-        BTreeMap::from([
+        let mut config = BTreeMap::from([
             ser_param(
                 "number_of_blocks_for_mean",
                 &self.number_of_blocks_for_mean,
-                "Number of blocks to use for the mean gas price calculation",
+                "Number of blocks to use for the gas price aggregation",
                 ParamPrivacyInput::Public,
             ),
             ser_param(
@@ -72,7 +79,16 @@ impl SerializeConfig for L1GasPriceProviderConfig {
                  in seconds",
                 ParamPrivacyInput::Public,
             ),
-        ])
+        ]);
+        config.extend(prepend_sub_config_name(
+            self.base_fee_aggregation.dump(),
+            "base_fee_aggregation",
+        ));
+        config.extend(prepend_sub_config_name(
+            self.blob_fee_aggregation.dump(),
+            "blob_fee_aggregation",
+        ));
+        config
     }
 }
 
@@ -162,7 +178,7 @@ impl L1GasPriceProvider {
             });
         }
 
-        // This index is for the last block in the mean (inclusive).
+        // This index is for the last block in the aggregation window (inclusive).
         let index_last_timestamp_rev = samples.iter().rev().position(|data| {
             data.timestamp <= timestamp.saturating_sub(&self.config.lag_margin_seconds)
         });
@@ -187,29 +203,24 @@ impl L1GasPriceProvider {
             last_index - num_blocks
         } else {
             warn!(
-                "Not enough history to calculate the mean gas price. Using only {} blocks instead \
-                 of {}.",
+                "Not enough history to aggregate the gas price. Using only {} blocks instead of \
+                 {}.",
                 last_index, num_blocks
             );
             L1_GAS_PRICE_PROVIDER_INSUFFICIENT_HISTORY.increment(1);
             0
         };
         debug_assert!(first_index < last_index, "error calculating indices");
-        let actual_number_of_blocks = last_index - first_index;
 
         // Go over all elements between `first_index` and `last_index` (non-inclusive).
-        let price_info_summed: PriceInfo = samples
-            .iter()
-            .skip(first_index)
-            .take(actual_number_of_blocks)
-            .map(|data| &data.price_info)
-            .sum();
-        let actual_number_of_blocks =
-            u128::try_from(actual_number_of_blocks).expect("Cannot convert to u128");
-        let price_info_out = price_info_summed
-            .checked_div(actual_number_of_blocks)
-            .expect("Actual number of blocks should be non-zero");
-        Ok(price_info_out)
+        let (base_fees, blob_fees): (Vec<u128>, Vec<u128>) = samples
+            .range(first_index..last_index)
+            .map(|data| (data.price_info.base_fee_per_gas.0, data.price_info.blob_fee.0))
+            .unzip();
+        Ok(PriceInfo {
+            base_fee_per_gas: GasPrice(self.config.base_fee_aggregation.aggregate(&base_fees)),
+            blob_fee: GasPrice(self.config.blob_fee_aggregation.aggregate(&blob_fees)),
+        })
     }
 }
 
@@ -1,6 +1,7 @@
 use apollo_l1_gas_price_types::{GasPriceData, PriceInfo};
 use starknet_api::block::{BlockTimestamp, GasPrice};
 
+use crate::gas_price_aggregation::{GasPriceAggregationConfig, GasPriceAggregationStrategy};
 use crate::l1_gas_price_provider::{
     L1GasPriceProvider,
     L1GasPriceProviderConfig,
@@ -67,6 +68,28 @@ fn gas_price_provider_mean_prices() {
     assert_eq!(data_gas_price, data_price_calculation);
 }
 
+#[test]
+fn gas_price_provider_aggregates_each_fee_separately() {
+    let (mut provider, block_prices, timestamp3) = make_provider();
+    let lag = provider.config.lag_margin_seconds;
+    provider.config.base_fee_aggregation = GasPriceAggregationConfig {
+        strategy: GasPriceAggregationStrategy::Median,
+        ..Default::default()
+    };
+    provider.config.blob_fee_aggregation = GasPriceAggregationConfig {
+        strategy: GasPriceAggregationStrategy::Percentile,
+        percentile: 100,
+        ..Default::default()
+    };
+
+    // timestamp3 is used to define the interval of blocks 1 to 3.
+    let price_info = provider.get_price_info(BlockTimestamp(timestamp3 + lag)).unwrap();
+
+    // Prices increase with the block number.
+    assert_eq!(price_info.base_fee_per_gas, block_prices[2].base_fee_per_gas);
+    assert_eq!(price_info.blob_fee, block_prices[3].blob_fee);
+}
+
 #[test]
 fn gas_price_provider_adding_blocks() {
     let (mut provider, _block_prices, timestamp3) = make_provider();
@@ -8,9 +8,30 @@ define_metrics!(
         MetricCounter { L1_GAS_PRICE_SCRAPER_REORG_DETECTED, "l1_gas_price_scraper_reorg_detected", "Number of times the L1 gas price scraper detected a reorganization in the base layer", init=0 },
         MetricCounter { ETH_TO_STRK_ERROR_COUNT, "eth_to_strk_error_count", "Number of times the query to the Eth to Strk oracle failed due to an error or timeout", init=0 },
         MetricCounter { ETH_TO_STRK_SUCCESS_COUNT, "eth_to_strk_success_count", "Number of times the query to the Eth to Strk oracle succeeded", init=0 },
+        LabeledMetricCounter { ETH_TO_STRK_SOURCE_ERROR_COUNT, "eth_to_strk_source_error_count", "Number of times the query to an Eth to Strk oracle source failed, or returned a stale rate", init=0, labels = ETH_TO_STRK_SOURCE_LABELS },
+        LabeledMetricCounter { ETH_TO_STRK_SOURCE_SUCCESS_COUNT, "eth_to_strk_source_success_count", "Number of times the query to an Eth to Strk oracle source succeeded", init=0, labels = ETH_TO_STRK_SOURCE_LABELS },
+        LabeledMetricCounter { ETH_TO_STRK_SOURCE_DEVIATION_COUNT, "eth_to_strk_source_deviation_count", "Number of times the rate of an Eth to Strk oracle source deviated too much from the median of the rates of all the sources", init=0, labels = ETH_TO_STRK_SOURCE_LABELS },
+        MetricCounter { ETH_TO_STRK_RATE_DEVIATION_COUNT, "eth_to_strk_rate_deviation_count", "Number of times the Eth to Strk rate deviated too much from the last good rate", init=0 },
+        MetricCounter { ETH_TO_STRK_FALLBACK_COUNT, "eth_to_strk_fallback_count", "Number of times the last good Eth to Strk rate was used instead of a new one", init=0 },
     }
 );
 
+pub const LABEL_NAME_ETH_TO_STRK_SOURCE: &str = "source";
+
+/// The maximal number of Eth to Strk oracle sources, each labeled by its index.
+pub const MAX_ETH_TO_STRK_SOURCES: usize = 8;
+
+pub(crate) const ETH_TO_STRK_SOURCE_LABELS: &[&[(&str, &str)]; MAX_ETH_TO_STRK_SOURCES] = &[
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "0")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "1")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "2")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "3")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "4")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "5")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "6")],
+    &[(LABEL_NAME_ETH_TO_STRK_SOURCE, "7")],
+];
+
 pub(crate) fn register_provider_metrics() {
     L1_GAS_PRICE_PROVIDER_INSUFFICIENT_HISTORY.register();
 }
@@ -24,4 +45,9 @@ pub(crate) fn register_scraper_metrics() {
 pub(crate) fn register_eth_to_strk_metrics() {
     ETH_TO_STRK_ERROR_COUNT.register();
     ETH_TO_STRK_SUCCESS_COUNT.register();
+    ETH_TO_STRK_SOURCE_ERROR_COUNT.register();
+    ETH_TO_STRK_SOURCE_SUCCESS_COUNT.register();
+    ETH_TO_STRK_SOURCE_DEVIATION_COUNT.register();
+    ETH_TO_STRK_RATE_DEVIATION_COUNT.register();
+    ETH_TO_STRK_FALLBACK_COUNT.register();
 }
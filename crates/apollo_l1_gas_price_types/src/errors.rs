@@ -41,4 +41,18 @@ pub enum EthToStrkOracleClientError {
     InvalidDecimalsError(u64, u64),
     #[error("Query not yet resolved: timestamp={0}")]
     QueryNotReadyError(u64),
+    #[error("Invalid rate: {0}")]
+    InvalidRateError(String),
+    #[error("Stale rate: timestamp={timestamp}, rate_timestamp={rate_timestamp}")]
+    StaleRateError { timestamp: u64, rate_timestamp: u64 },
+    #[error(
+        "Rates disagree: timestamp={timestamp}, only {n_agreeing} out of {n_rates} rates agree \
+         with the median"
+    )]
+    RatesDisagreeError { timestamp: u64, n_agreeing: usize, n_rates: usize },
+    #[error(
+        "Rate deviates from the last good rate: timestamp={timestamp}, rate={rate}, \
+         last_good_rate={last_good_rate}"
+    )]
+    RateDeviationError { timestamp: u64, rate: u128, last_good_rate: u128 },
 }
@@ -6,7 +6,6 @@ use apollo_infra::component_definitions::{
 use apollo_infra::component_server::{LocalComponentServer, RemoteComponentServer, WrapperServer};
 use apollo_l1_provider_types::{L1ProviderRequest, L1ProviderResponse};
 use async_trait::async_trait;
-use papyrus_base_layer::monitored_base_layer::MonitoredBaseLayer;
 use tracing::instrument;
 
 pub type LocalL1ProviderServer =
@@ -20,7 +19,7 @@ pub type RemoteL1ProviderClient = RemoteComponentClient<L1ProviderRequest, L1Pro
 use crate::l1_provider::L1Provider;
 use crate::l1_scraper::L1Scraper;
 
-pub type L1ScraperServer<B> = WrapperServer<L1Scraper<MonitoredBaseLayer<B>>>;
+pub type L1ScraperServer<B> = WrapperServer<L1Scraper<B>>;
 
 #[async_trait]
 impl ComponentRequestHandler<L1ProviderRequest, L1ProviderResponse> for L1Provider {
@@ -43,6 +42,11 @@ impl ComponentRequestHandler<L1ProviderRequest, L1ProviderResponse> for L1Provid
             L1ProviderRequest::StartBlock { state, height } => {
                 L1ProviderResponse::StartBlock(self.start_block(height, state))
             }
+            L1ProviderRequest::StartPipelinedBlock { parent_l1_handler_tx_hashes, height } => {
+                L1ProviderResponse::StartPipelinedBlock(
+                    self.start_pipelined_block(parent_l1_handler_tx_hashes, height),
+                )
+            }
             L1ProviderRequest::Validate { tx_hash, height } => {
                 L1ProviderResponse::Validate(self.validate(tx_hash, height))
             }
@@ -36,6 +36,9 @@ pub struct L1Provider {
     pub config: L1ProviderConfig,
     /// Represents the L2 block height being built.
     pub current_height: BlockNumber,
+    /// The height after the current one, if it's being proposed before the current height is
+    /// committed.
+    pub pipelined_height: Option<BlockNumber>,
     pub tx_manager: TransactionManager,
     // TODO(Gilad): consider transitioning to a generic phantom state once the infra is stabilized
     // and we see how well it handles consuming the L1Provider when moving between states.
@@ -51,11 +54,35 @@ impl L1Provider {
         state: SessionState,
     ) -> L1ProviderResult<()> {
         self.validate_height(height)?;
+        self.pipelined_height = None;
         self.state = state.into();
         self.tx_manager.start_block();
         Ok(())
     }
 
+    /// Starts proposing the next height on top of the current one, whose block includes the given
+    /// transactions but isn't committed yet. The parent's transactions are staged, so they aren't
+    /// proposed again.
+    #[instrument(skip(self), err)]
+    pub fn start_pipelined_block(
+        &mut self,
+        parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+        height: BlockNumber,
+    ) -> L1ProviderResult<()> {
+        let next_height = self.current_height.unchecked_next();
+        if height != next_height {
+            return Err(L1ProviderError::UnexpectedHeight {
+                expected_height: next_height,
+                got: height,
+            });
+        }
+        self.pipelined_height = Some(height);
+        self.state = ProviderState::Propose;
+        self.tx_manager.start_block();
+        self.tx_manager.stage_txs(&parent_l1_handler_tx_hashes);
+        Ok(())
+    }
+
     pub async fn initialize(&mut self, events: Vec<Event>) -> L1ProviderResult<()> {
         info!("Initializing l1 provider");
         let Some(bootstrapper) = self.state.get_bootstrapper() else {
@@ -77,7 +104,9 @@ impl L1Provider {
         n_txs: usize,
         height: BlockNumber,
     ) -> L1ProviderResult<Vec<L1HandlerTransaction>> {
-        self.validate_height(height)?;
+        if self.pipelined_height != Some(height) {
+            self.validate_height(height)?;
+        }
 
         match self.state {
             ProviderState::Propose => {
@@ -137,7 +166,10 @@ impl L1Provider {
         self.validate_height(height)?;
         self.apply_commit_block(committed_txs, rejected_txs);
 
-        self.state = self.state.transition_to_pending();
+        // A block pipelined on top of the committed one may still be proposed.
+        if self.pipelined_height.take() != Some(self.current_height) {
+            self.state = self.state.transition_to_pending();
+        }
         Ok(())
     }
 
@@ -189,6 +221,36 @@ impl L1Provider {
                             );
                         });
                 }
+                Event::TransactionRetracted(tx_hash) => {
+                    if self.tx_manager.is_committed(tx_hash) {
+                        error!(
+                            "L1 handler transaction {tx_hash} was committed on L2, but the L1 \
+                             block which logged it was orphaned by an L1 reorg, and it wasn't \
+                             logged again on the new fork."
+                        );
+                        continue;
+                    }
+                    if self.tx_manager.retract_tx(tx_hash) {
+                        warn!(
+                            "Retracted L1 handler transaction {tx_hash} orphaned by an L1 reorg."
+                        );
+                    } else {
+                        debug!("Retracted unknown L1 handler transaction {tx_hash}.");
+                    }
+                }
+                Event::TransactionCancellationRetracted(tx_hash) => {
+                    if self.tx_manager.retract_cancellation(tx_hash) {
+                        warn!(
+                            "Retracted the cancellation request of L1 handler transaction \
+                             {tx_hash} orphaned by an L1 reorg."
+                        );
+                    } else {
+                        debug!(
+                            "Retracted the cancellation request of unknown L1 handler transaction \
+                             {tx_hash}."
+                        );
+                    }
+                }
                 _ => return Err(L1ProviderError::unsupported_l1_event(event)),
             }
         }
@@ -409,6 +471,7 @@ impl L1ProviderBuilder {
 
         L1Provider {
             current_height: l1_provider_startup_height,
+            pipelined_height: None,
             tx_manager: TransactionManager::new(
                 self.config.new_l1_handler_cooldown_seconds,
                 self.config.l1_handler_cancellation_timelock_seconds,
@@ -117,6 +117,35 @@ fn get_txs_happy_flow() {
     assert_eq!(l1_provider.get_txs(1, BlockNumber(0)).unwrap(), []);
 }
 
+#[test]
+fn get_txs_pipelined_on_top_of_uncommitted_parent() {
+    // Setup.
+    let txs = [l1_handler(0), l1_handler(1), l1_handler(2)];
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_txs(txs.clone())
+        .with_height(BlockNumber(5))
+        .with_state(ProviderState::Pending)
+        .build_into_l1_provider();
+    l1_provider.start_block(BlockNumber(5), ProposeSession).unwrap();
+    assert_eq!(l1_provider.get_txs(2, BlockNumber(5)).unwrap(), txs[..=1]);
+
+    // Test: the parent's transactions aren't proposed again in the pipelined block.
+    assert_eq!(
+        l1_provider.start_pipelined_block([tx_hash!(0)].into(), BlockNumber(7)),
+        Err(L1ProviderError::UnexpectedHeight {
+            expected_height: BlockNumber(6),
+            got: BlockNumber(7)
+        })
+    );
+    l1_provider.start_pipelined_block([tx_hash!(0)].into(), BlockNumber(6)).unwrap();
+    assert_eq!(l1_provider.get_txs(3, BlockNumber(6)).unwrap(), txs[1..=2]);
+
+    // Test: the pipelined block is still proposed once its parent is committed.
+    commit_block_no_rejected(&mut l1_provider, &[tx_hash!(0)], BlockNumber(5));
+    assert_eq!(l1_provider.state, ProviderState::Propose);
+    assert_eq!(l1_provider.get_txs(3, BlockNumber(6)).unwrap(), []);
+}
+
 #[test]
 fn validate_happy_flow() {
     // Setup.
@@ -200,6 +229,118 @@ fn process_events_committed_txs() {
     assert_eq!(l1_provider, expected_l1_provider);
 }
 
+#[test]
+fn process_events_retracted_txs() {
+    // Setup.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_txs([l1_handler(1), l1_handler(2)])
+        .with_committed_hashes([tx_hash!(3)])
+        .with_state(ProviderState::Pending)
+        .build_into_l1_provider();
+
+    // Test.
+    l1_provider
+        .add_events(vec![
+            Event::TransactionRetracted(tx_hash!(1)),
+            // Committed transaction, can't be retracted.
+            Event::TransactionRetracted(tx_hash!(3)),
+            // Unknown transaction, should be ignored.
+            Event::TransactionRetracted(tx_hash!(4)),
+        ])
+        .unwrap();
+
+    let expected_l1_provider = L1ProviderContentBuilder::new()
+        .with_txs([l1_handler(2)])
+        .with_committed_hashes([tx_hash!(3)])
+        .with_retracted([l1_handler(1)])
+        .build();
+    expected_l1_provider.assert_eq(&l1_provider);
+}
+
+#[test]
+fn process_events_retracted_tx_logged_again_is_restored() {
+    // Setup.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_retracted([l1_handler(1)])
+        .with_state(ProviderState::Pending)
+        .build_into_l1_provider();
+
+    // Test.
+    l1_provider.add_events(vec![l1_handler_event(tx_hash!(1))]).unwrap();
+
+    let expected_l1_provider =
+        L1ProviderContentBuilder::new().with_txs([l1_handler(1)]).with_retracted([]).build();
+    expected_l1_provider.assert_eq(&l1_provider);
+}
+
+#[test]
+fn process_events_retracted_cancellations() {
+    // Setup.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_cancelled_txs([l1_handler(1)])
+        .with_state(ProviderState::Pending)
+        .build_into_l1_provider();
+
+    // Test.
+    l1_provider
+        .add_events(vec![
+            Event::TransactionCancellationRetracted(tx_hash!(1)),
+            // Unknown transaction, should be ignored.
+            Event::TransactionCancellationRetracted(tx_hash!(2)),
+        ])
+        .unwrap();
+
+    let expected_l1_provider = L1ProviderContentBuilder::new()
+        .with_txs([l1_handler(1)])
+        .with_timed_cancel_requested_txs([])
+        .build();
+    expected_l1_provider.assert_eq(&l1_provider);
+}
+
+#[test]
+fn process_events_reorg_of_consumed_tx() {
+    // Setup: transaction 1 was committed on L2, then its cancellation was requested and it was
+    // consumed on L1, in a block which was orphaned.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_committed_hashes([tx_hash!(1)])
+        .with_state(ProviderState::Pending)
+        .build_into_l1_provider();
+
+    // Test: the events the scraper sends after the reorg are accepted, and leave the transaction
+    // committed.
+    l1_provider
+        .add_events(vec![
+            Event::TransactionCancellationRetracted(tx_hash!(1)),
+            l1_handler_event(tx_hash!(2)),
+        ])
+        .unwrap();
+
+    let expected_l1_provider = L1ProviderContentBuilder::new()
+        .with_txs([l1_handler(2)])
+        .with_committed_hashes([tx_hash!(1)])
+        .build();
+    expected_l1_provider.assert_eq(&l1_provider);
+}
+
+#[test]
+fn commit_block_rejects_retracted_tx() {
+    // Setup.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_retracted([l1_handler(1)])
+        .with_state(ProviderState::Propose)
+        .build_into_l1_provider();
+
+    // Test: the batcher may still reject a transaction proposed before it was retracted.
+    l1_provider.commit_block([].into(), [tx_hash!(1)].into(), BlockNumber(0)).unwrap();
+
+    let expected_l1_provider = L1ProviderContentBuilder::new()
+        .with_rejected([])
+        .with_retracted([l1_handler(1)])
+        .with_height(BlockNumber(1))
+        .build();
+    expected_l1_provider.assert_eq(&l1_provider);
+}
+
 #[test]
 fn pending_state_errors() {
     // Setup.
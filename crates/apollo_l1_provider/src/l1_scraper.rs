@@ -1,5 +1,5 @@
 use std::any::type_name;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::time::Duration;
 
 use apollo_config::converters::deserialize_float_seconds_to_duration;
@@ -17,6 +17,7 @@ use papyrus_base_layer::constants::EventIdentifier;
 use papyrus_base_layer::{BaseLayerContract, L1BlockNumber, L1BlockReference, L1Event};
 use serde::{Deserialize, Serialize};
 use starknet_api::core::ChainId;
+use starknet_api::transaction::TransactionHash;
 use starknet_api::StarknetApiError;
 use thiserror::Error;
 use tokio::time::sleep;
@@ -45,6 +46,78 @@ pub struct L1Scraper<B: BaseLayerContract> {
     pub last_l1_block_processed: L1BlockReference,
     pub l1_provider_client: SharedL1ProviderClient,
     tracked_event_identifiers: Vec<EventIdentifier>,
+    // The recent scrapes, oldest first, used to find the common ancestor of the scraped blocks and
+    // the base layer on L1 reorgs. The last one always ends at the last processed L1 block.
+    recent_scrapes: VecDeque<Scrape>,
+    // Events logged in L1 blocks which were orphaned by a reorg, and weren't retracted from the
+    // provider yet.
+    orphaned_tx_hashes: ScrapedTxHashes,
+}
+
+/// The events scraped up to an L1 block, since the previous scrape.
+#[derive(Clone, Debug, PartialEq)]
+struct Scrape {
+    last_l1_block: L1BlockReference,
+    tx_hashes: ScrapedTxHashes,
+}
+
+/// The hashes of the transactions referenced by scraped events which the provider can retract, by
+/// event kind. The provider doesn't track the consumption of transactions on L1, so consumptions
+/// aren't retracted.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ScrapedTxHashes {
+    l1_handler: Vec<TransactionHash>,
+    cancellation_started: Vec<TransactionHash>,
+}
+
+impl ScrapedTxHashes {
+    fn new(events: &[Event]) -> Self {
+        let mut tx_hashes = Self::default();
+        for event in events {
+            match event {
+                Event::L1HandlerTransaction { l1_handler_tx, .. } => {
+                    tx_hashes.l1_handler.push(l1_handler_tx.tx_hash)
+                }
+                Event::TransactionCancellationStarted { tx_hash, .. } => {
+                    tx_hashes.cancellation_started.push(*tx_hash)
+                }
+                _ => {}
+            }
+        }
+        tx_hashes
+    }
+
+    fn extend(&mut self, other: Self) {
+        self.l1_handler.extend(other.l1_handler);
+        self.cancellation_started.extend(other.cancellation_started);
+    }
+
+    fn len(&self) -> usize {
+        self.l1_handler.len() + self.cancellation_started.len()
+    }
+
+    /// The events retracting these orphaned events from the provider, except for those which were
+    /// logged again on the new fork, in `rescraped`.
+    fn retractions(&self, rescraped: &Self) -> Vec<Event> {
+        fn retract<'a>(
+            orphaned: &'a [TransactionHash],
+            rescraped: &'a [TransactionHash],
+            retraction: fn(TransactionHash) -> Event,
+        ) -> impl Iterator<Item = Event> + 'a {
+            orphaned
+                .iter()
+                .filter(|tx_hash| !rescraped.contains(tx_hash))
+                .map(move |tx_hash| retraction(*tx_hash))
+        }
+
+        retract(&self.l1_handler, &rescraped.l1_handler, Event::TransactionRetracted)
+            .chain(retract(
+                &self.cancellation_started,
+                &rescraped.cancellation_started,
+                Event::TransactionCancellationRetracted,
+            ))
+            .collect()
+    }
 }
 
 impl<B: BaseLayerContract + Send + Sync> L1Scraper<B> {
@@ -61,29 +134,40 @@ impl<B: BaseLayerContract + Send + Sync> L1Scraper<B> {
             last_l1_block_processed: l1_start_block,
             config,
             tracked_event_identifiers: events_identifiers_to_track.to_vec(),
+            recent_scrapes: VecDeque::from([Scrape {
+                last_l1_block: l1_start_block,
+                tx_hashes: ScrapedTxHashes::default(),
+            }]),
+            orphaned_tx_hashes: ScrapedTxHashes::default(),
         })
     }
 
     #[instrument(skip(self), err)]
     async fn initialize(&mut self) -> L1ScraperResult<(), B> {
         let (latest_l1_block, events) = self.fetch_events().await?;
+        let tx_hashes = ScrapedTxHashes::new(&events);
 
         // If this gets too high, send in batches.
         let initialize_result = self.l1_provider_client.initialize(events).await;
         handle_client_error(initialize_result)?;
 
-        self.last_l1_block_processed = latest_l1_block;
+        self.record_scrape(latest_l1_block, tx_hashes);
 
         Ok(())
     }
 
     pub async fn send_events_to_l1_provider(&mut self) -> L1ScraperResult<(), B> {
-        self.assert_no_l1_reorgs().await?;
+        self.recover_from_l1_reorgs().await?;
 
         let (latest_l1_block, events) = self.fetch_events().await?;
         trace!("scraped up to {latest_l1_block:?}");
         // TODO(guy.f): Replace with info_every_n_sec once implemented.
         info_every_n!(100, "scraped up to {latest_l1_block:?}");
+        let tx_hashes = ScrapedTxHashes::new(&events);
+
+        // Events which were logged again on the new fork aren't retracted.
+        let retractions = self.orphaned_tx_hashes.retractions(&tx_hashes);
+        let events = retractions.into_iter().chain(events).collect();
 
         // Sending even if there are no events, to keep the flow as simple/debuggable as possible.
         // Perf hit is minimal, since the scraper is on the same machine as the provider (no net).
@@ -91,11 +175,24 @@ impl<B: BaseLayerContract + Send + Sync> L1Scraper<B> {
         let add_events_result = self.l1_provider_client.add_events(events).await;
         handle_client_error(add_events_result)?;
 
-        self.last_l1_block_processed = latest_l1_block;
+        self.orphaned_tx_hashes = ScrapedTxHashes::default();
+        self.record_scrape(latest_l1_block, tx_hashes);
 
         Ok(())
     }
 
+    fn record_scrape(&mut self, last_l1_block: L1BlockReference, tx_hashes: ScrapedTxHashes) {
+        self.last_l1_block_processed = last_l1_block;
+        self.recent_scrapes.push_back(Scrape { last_l1_block, tx_hashes });
+        // Keep the newest scrape which is at least `max_reorg_depth` deep, as the common ancestor
+        // of the deepest reorgs the scraper recovers from.
+        while self.recent_scrapes.get(1).is_some_and(|scrape| {
+            scrape.last_l1_block.number + self.config.max_reorg_depth <= last_l1_block.number
+        }) {
+            self.recent_scrapes.pop_front();
+        }
+    }
+
     async fn fetch_events(&self) -> L1ScraperResult<(L1BlockReference, Vec<Event>), B> {
         let latest_l1_block = self
             .base_layer
@@ -134,10 +231,7 @@ impl<B: BaseLayerContract + Send + Sync> L1Scraper<B> {
             .collect::<L1ScraperResult<Vec<_>, _>>()?;
 
         // Used for debug.
-        let l2_hashes = events.iter().filter_map(|event| match event {
-            Event::L1HandlerTransaction { l1_handler_tx, .. } => Some(l1_handler_tx.tx_hash),
-            _ => None,
-        });
+        let l2_hashes = l1_handler_tx_hashes(&events);
 
         let formatted_pairs = zip_eq(l1_hashes, l2_hashes)
             .map(|(l1_hash, l2_hash)| format!("L1 hash: {l1_hash:?}, L2 hash: {l2_hash}"))
@@ -179,39 +273,89 @@ impl<B: BaseLayerContract + Send + Sync> L1Scraper<B> {
         }
     }
 
-    async fn assert_no_l1_reorgs(&self) -> L1ScraperResult<(), B> {
-        let last_processed_l1_block_number = self.last_l1_block_processed.number;
-        let last_block_processed_fresh = self
-            .base_layer
-            .l1_block_at(last_processed_l1_block_number)
-            .await
-            .map_err(L1ScraperError::BaseLayerError)?;
-
-        let Some(last_block_processed_fresh) = last_block_processed_fresh else {
-            L1_MESSAGE_SCRAPER_REORG_DETECTED.increment(1);
+    /// On an L1 reorg, rewinds the scraper to the last scraped block which wasn't orphaned, and
+    /// marks the L1 handler transactions, cancellation requests and consumptions scraped from the
+    /// orphaned blocks for retraction.
+    async fn recover_from_l1_reorgs(&mut self) -> L1ScraperResult<(), B> {
+        let Some(reorg_reason) = self.l1_reorg_reason(&self.last_l1_block_processed).await? else {
+            return Ok(());
+        };
+        L1_MESSAGE_SCRAPER_REORG_DETECTED.increment(1);
+        warn!("L1 reorg detected: {reorg_reason}.");
+
+        // The last scrape ends at the orphaned block, so it's orphaned as well.
+        let mut common_ancestor_index = None;
+        for (index, scrape) in self.recent_scrapes.iter().enumerate().rev().skip(1) {
+            if self.l1_reorg_reason(&scrape.last_l1_block).await?.is_none() {
+                common_ancestor_index = Some(index);
+                break;
+            }
+        }
+        let Some(common_ancestor_index) = common_ancestor_index else {
             return Err(L1ScraperError::L1ReorgDetected {
                 reason: format!(
-                    "Last processed L1 block with number {last_processed_l1_block_number} no \
-                     longer exists."
+                    "{reorg_reason}, and none of the L1 blocks scraped in the last {} blocks is \
+                     on the new fork",
+                    self.config.max_reorg_depth
                 ),
             });
         };
 
-        if last_block_processed_fresh.hash != self.last_l1_block_processed.hash {
-            L1_MESSAGE_SCRAPER_REORG_DETECTED.increment(1);
-            return Err(L1ScraperError::L1ReorgDetected {
-                reason: format!(
-                    "Last processed L1 block hash, {}, for block number {}, is different from the \
-                     hash stored, {}",
-                    hex::encode(last_block_processed_fresh.hash),
-                    last_processed_l1_block_number,
-                    hex::encode(self.last_l1_block_processed.hash),
-                ),
-            });
+        for orphaned_scrape in self.recent_scrapes.drain(common_ancestor_index + 1..) {
+            self.orphaned_tx_hashes.extend(orphaned_scrape.tx_hashes);
         }
+        let common_ancestor = self
+            .recent_scrapes
+            .back()
+            .expect("The common ancestor scrape was not removed")
+            .last_l1_block;
+        info!(
+            "Resuming scraping from L1 block {} after the reorg, retracting {} events from the \
+             orphaned blocks unless they are logged again.",
+            common_ancestor.number,
+            self.orphaned_tx_hashes.len()
+        );
+        self.last_l1_block_processed = common_ancestor;
 
         Ok(())
     }
+
+    // Returns why the given block was orphaned by a reorg, or None if it's still on L1.
+    async fn l1_reorg_reason(
+        &self,
+        l1_block: &L1BlockReference,
+    ) -> L1ScraperResult<Option<String>, B> {
+        let l1_block_fresh = self
+            .base_layer
+            .l1_block_at(l1_block.number)
+            .await
+            .map_err(L1ScraperError::BaseLayerError)?;
+
+        let Some(l1_block_fresh) = l1_block_fresh else {
+            return Ok(Some(format!("L1 block with number {} no longer exists", l1_block.number)));
+        };
+
+        if l1_block_fresh.hash != l1_block.hash {
+            return Ok(Some(format!(
+                "L1 block hash, {}, for block number {}, is different from the hash stored, {}",
+                hex::encode(l1_block_fresh.hash),
+                l1_block.number,
+                hex::encode(l1_block.hash),
+            )));
+        }
+
+        Ok(None)
+    }
+}
+
+fn l1_handler_tx_hashes(events: &[Event]) -> Vec<TransactionHash> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::L1HandlerTransaction { l1_handler_tx, .. } => Some(l1_handler_tx.tx_hash),
+            _ => None,
+        })
+        .collect()
 }
 
 pub async fn fetch_start_block<B: BaseLayerContract + Send + Sync>(
@@ -263,6 +407,7 @@ pub struct L1ScraperConfig {
     #[validate(custom = "validate_ascii")]
     pub chain_id: ChainId,
     pub finality: u64,
+    pub max_reorg_depth: u64,
     #[serde(deserialize_with = "deserialize_float_seconds_to_duration")]
     pub polling_interval_seconds: Duration,
 }
@@ -273,6 +418,7 @@ impl Default for L1ScraperConfig {
             startup_rewind_time_seconds: Duration::from_secs(0),
             chain_id: ChainId::Mainnet,
             finality: 0,
+            max_reorg_depth: 64,
             polling_interval_seconds: Duration::from_secs(1),
         }
     }
@@ -293,6 +439,13 @@ impl SerializeConfig for L1ScraperConfig {
                 "Number of blocks to wait for finality",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_reorg_depth",
+                &self.max_reorg_depth,
+                "The depth in L1 blocks of the deepest L1 reorg the scraper recovers from. Deeper \
+                 reorgs require restarting both the L1 provider and the scraper.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "polling_interval_seconds",
                 &self.polling_interval_seconds.as_secs(),
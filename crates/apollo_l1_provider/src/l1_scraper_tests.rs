@@ -6,16 +6,18 @@ use apollo_batcher_types::batcher_types::GetHeightResponse;
 use apollo_batcher_types::communication::MockBatcherClient;
 use apollo_infra::trace_util::configure_tracing;
 use apollo_l1_provider_types::errors::L1ProviderError;
-use apollo_l1_provider_types::{L1ProviderClient, MockL1ProviderClient};
+use apollo_l1_provider_types::{Event, L1ProviderClient, MockL1ProviderClient};
 use apollo_state_sync_types::communication::MockStateSyncClient;
 use apollo_state_sync_types::state_sync_types::SyncBlock;
 use assert_matches::assert_matches;
 use indexmap::IndexSet;
 use itertools::Itertools;
-use papyrus_base_layer::{L1BlockReference, MockBaseLayerContract};
+use papyrus_base_layer::{L1BlockNumber, L1BlockReference, L1Event, MockBaseLayerContract};
 use rstest::{fixture, rstest};
 use starknet_api::block::BlockNumber;
-use starknet_api::transaction::TransactionHash;
+use starknet_api::core::Nonce;
+use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::{L1HandlerTransaction, TransactionHash};
 
 use crate::bootstrapper::Bootstrapper;
 use crate::l1_provider::{L1Provider, L1ProviderBuilder};
@@ -477,6 +479,219 @@ async fn l1_reorg_block_number(mut dummy_base_layer: MockBaseLayerContract) {
     );
 }
 
+// An L1 chain the tests can reorg, holding the events logged in each block, indexed by block
+// number.
+type ScriptedL1Chain = Arc<Mutex<Vec<(L1BlockReference, Vec<L1Event>)>>>;
+
+/// A base layer which serves the given chain, as it is at the time of each call.
+fn scripted_base_layer(chain: ScriptedL1Chain) -> MockBaseLayerContract {
+    let mut base_layer = MockBaseLayerContract::new();
+    let chain_clone = chain.clone();
+    base_layer
+        .expect_latest_l1_block()
+        .returning(move |_| Ok(chain_clone.lock().unwrap().last().map(|(block, _)| *block)));
+    let chain_clone = chain.clone();
+    base_layer.expect_l1_block_at().returning(move |block_number| {
+        let block_index = usize::try_from(block_number).unwrap();
+        Ok(chain_clone.lock().unwrap().get(block_index).map(|(block, _)| *block))
+    });
+    base_layer.expect_events().returning(move |block_range, _| {
+        Ok(chain
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(block, _)| block_range.contains(&block.number))
+            .flat_map(|(_, events)| events.clone())
+            .collect())
+    });
+    base_layer
+}
+
+/// Appends a block logging the given L1 handler transactions to the chain, on the given fork.
+fn add_scripted_l1_block(chain: &ScriptedL1Chain, fork: u8, l1_handler_nonces: &[u64]) {
+    let events = l1_handler_nonces.iter().map(|nonce| scripted_l1_handler_event(*nonce)).collect();
+    add_scripted_l1_block_with_events(chain, fork, events);
+}
+
+/// Appends a block logging the given events to the chain, on the given fork.
+fn add_scripted_l1_block_with_events(chain: &ScriptedL1Chain, fork: u8, events: Vec<L1Event>) {
+    let mut chain = chain.lock().unwrap();
+    let number = L1BlockNumber::try_from(chain.len()).unwrap();
+    let mut hash = [fork; 32];
+    hash[..8].copy_from_slice(&number.to_be_bytes());
+    chain.push((L1BlockReference { number, hash }, events));
+}
+
+fn scripted_l1_tx(nonce: u64) -> L1HandlerTransaction {
+    L1HandlerTransaction { nonce: Nonce(nonce.into()), ..Default::default() }
+}
+
+fn scripted_l1_handler_event(nonce: u64) -> L1Event {
+    L1Event::LogMessageToL2 {
+        tx: scripted_l1_tx(nonce),
+        fee: Fee(1),
+        l1_tx_hash: None,
+        timestamp: Default::default(),
+    }
+}
+
+fn scripted_cancellation_event(nonce: u64) -> L1Event {
+    L1Event::MessageToL2CancellationStarted {
+        cancelled_tx: scripted_l1_tx(nonce),
+        cancellation_request_timestamp: Default::default(),
+    }
+}
+
+fn scripted_event(nonce: u64) -> Event {
+    to_event(scripted_l1_handler_event(nonce))
+}
+
+fn to_event(l1_event: L1Event) -> Event {
+    Event::from_l1_event(&L1ScraperConfig::default().chain_id, l1_event).unwrap()
+}
+
+fn scripted_tx_hash(nonce: u64) -> TransactionHash {
+    match scripted_event(nonce) {
+        Event::L1HandlerTransaction { l1_handler_tx, .. } => l1_handler_tx.tx_hash,
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn l1_reorg_recovery() {
+    // Setup.
+    let chain = ScriptedL1Chain::default();
+    add_scripted_l1_block(&chain, 0, &[]);
+    add_scripted_l1_block(&chain, 0, &[1]);
+    let l1_start_block = chain.lock().unwrap()[0].0;
+
+    let l1_provider_client = Arc::new(FakeL1ProviderClient::default());
+    let mut scraper = L1Scraper::new(
+        L1ScraperConfig::default(),
+        l1_provider_client.clone(),
+        scripted_base_layer(chain.clone()),
+        event_identifiers_to_track(),
+        l1_start_block,
+    )
+    .await
+    .unwrap();
+
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[scripted_event(1)]);
+    add_scripted_l1_block(&chain, 0, &[2, 3]);
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[scripted_event(2), scripted_event(3)]);
+
+    // Test.
+
+    // Reorg out the last block: the new fork logs transaction 2 again, but not transaction 3.
+    chain.lock().unwrap().pop();
+    add_scripted_l1_block(&chain, 1, &[2]);
+    add_scripted_l1_block(&chain, 1, &[4]);
+
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[
+        Event::TransactionRetracted(scripted_tx_hash(3)),
+        scripted_event(2),
+        scripted_event(4),
+    ]);
+    assert_eq!(scraper.last_l1_block_processed, chain.lock().unwrap()[3].0);
+
+    // Scraping continues normally on the new fork, with no further retractions.
+    add_scripted_l1_block(&chain, 1, &[5]);
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[scripted_event(5)]);
+}
+
+#[tokio::test]
+async fn l1_reorg_recovery_retracts_cancellations_and_consumptions() {
+    // Setup.
+    let chain = ScriptedL1Chain::default();
+    add_scripted_l1_block(&chain, 0, &[]);
+    add_scripted_l1_block(&chain, 0, &[1, 2]);
+    let l1_start_block = chain.lock().unwrap()[0].0;
+
+    let l1_provider_client = Arc::new(FakeL1ProviderClient::default());
+    let mut scraper = L1Scraper::new(
+        L1ScraperConfig::default(),
+        l1_provider_client.clone(),
+        scripted_base_layer(chain.clone()),
+        event_identifiers_to_track(),
+        l1_start_block,
+    )
+    .await
+    .unwrap();
+
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[scripted_event(1), scripted_event(2)]);
+    add_scripted_l1_block_with_events(
+        &chain,
+        0,
+        vec![
+            scripted_cancellation_event(1),
+            scripted_cancellation_event(2),
+            L1Event::ConsumedMessageToL2(scripted_l1_tx(1)),
+        ],
+    );
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[
+        to_event(scripted_cancellation_event(1)),
+        to_event(scripted_cancellation_event(2)),
+        to_event(L1Event::ConsumedMessageToL2(scripted_l1_tx(1))),
+    ]);
+
+    // Test.
+
+    // Reorg out the last block: the new fork logs the cancellation of transaction 2 again, but
+    // neither the cancellation nor the consumption of transaction 1. Only the cancellation is
+    // retracted, since the provider doesn't track consumptions.
+    chain.lock().unwrap().pop();
+    add_scripted_l1_block_with_events(&chain, 1, vec![scripted_cancellation_event(2)]);
+
+    scraper.send_events_to_l1_provider().await.unwrap();
+    l1_provider_client.assert_add_events_received_with(&[
+        Event::TransactionCancellationRetracted(scripted_tx_hash(1)),
+        to_event(scripted_cancellation_event(2)),
+    ]);
+}
+
+#[tokio::test]
+async fn l1_reorg_deeper_than_max_reorg_depth() {
+    // Setup.
+    let chain = ScriptedL1Chain::default();
+    add_scripted_l1_block(&chain, 0, &[]);
+    let l1_start_block = chain.lock().unwrap()[0].0;
+
+    let config = L1ScraperConfig { max_reorg_depth: 1, ..Default::default() };
+    let l1_provider_client = Arc::new(FakeL1ProviderClient::default());
+    let mut scraper = L1Scraper::new(
+        config,
+        l1_provider_client.clone(),
+        scripted_base_layer(chain.clone()),
+        event_identifiers_to_track(),
+        l1_start_block,
+    )
+    .await
+    .unwrap();
+
+    for nonce in 1..=3 {
+        add_scripted_l1_block(&chain, 0, &[nonce]);
+        scraper.send_events_to_l1_provider().await.unwrap();
+    }
+
+    // Test.
+
+    // Reorg out the last two blocks, which is deeper than the scraper tracks.
+    chain.lock().unwrap().truncate(2);
+    add_scripted_l1_block(&chain, 1, &[]);
+    add_scripted_l1_block(&chain, 1, &[]);
+
+    assert_matches!(
+        scraper.send_events_to_l1_provider().await,
+        Err(L1ScraperError::L1ReorgDetected { .. })
+    );
+}
+
 #[test]
 #[ignore = "similar to backlog_happy_flow, only shorter, and sprinkle some start_block/get_txs \
             attempts while its bootstrapping (and assert failure on height), then assert that they \
@@ -74,6 +74,7 @@ impl From<L1ProviderContent> for L1Provider {
             // is functionally equivalent to Pending for testing purposes.
             state: content.state.unwrap_or(ProviderState::Pending),
             current_height: content.current_height.unwrap_or_default(),
+            pipelined_height: None,
             clock: content.clock.unwrap_or_else(|| Arc::new(DefaultClock)),
         }
     }
@@ -127,6 +128,11 @@ impl L1ProviderContentBuilder {
         self
     }
 
+    pub fn with_retracted(mut self, txs: impl IntoIterator<Item = L1HandlerTransaction>) -> Self {
+        self.tx_manager_content_builder = self.tx_manager_content_builder.with_retracted(txs);
+        self
+    }
+
     pub fn with_height(mut self, height: BlockNumber) -> Self {
         self.current_height = Some(height);
         self
@@ -262,6 +268,7 @@ struct TransactionManagerContent {
     pub rejected: Option<Vec<L1HandlerTransaction>>,
     pub committed: Option<IndexMap<TransactionHash, TransactionPayload>>,
     pub cancel_requested: Option<Vec<CancellationRequest>>,
+    pub retracted: Option<Vec<L1HandlerTransaction>>,
     pub config: Option<TransactionManagerConfig>,
 }
 
@@ -297,6 +304,10 @@ impl TransactionManagerContent {
                 chain!(snapshot.cancellation_started_on_l2, snapshot.cancelled_on_l2).collect_vec(),
             );
         }
+
+        if let Some(retracted) = &self.retracted {
+            assert_eq!(retracted.iter().map(|tx| tx.tx_hash).collect_vec(), snapshot.retracted);
+        }
     }
 }
 
@@ -306,9 +317,14 @@ impl From<TransactionManagerContent> for TransactionManager {
         let rejected: Vec<_> = mem::take(&mut content.rejected).unwrap_or_default();
         let committed: IndexMap<_, _> = mem::take(&mut content.committed).unwrap_or_default();
         let cancel_requested: Vec<_> = mem::take(&mut content.cancel_requested).unwrap_or_default();
+        let retracted: Vec<_> = mem::take(&mut content.retracted).unwrap_or_default();
 
         let mut records = IndexMap::with_capacity(
-            pending.len() + rejected.len() + committed.len() + cancel_requested.len(),
+            pending.len()
+                + rejected.len()
+                + committed.len()
+                + cancel_requested.len()
+                + retracted.len(),
         );
 
         let mut proposable_index: BTreeMap<BlockTimestamp, Vec<TransactionHash>> = BTreeMap::new();
@@ -348,6 +364,17 @@ impl From<TransactionManagerContent> for TransactionManager {
             assert_eq!(records.insert(tx_hash, record), None);
         }
 
+        for retracted_tx in retracted {
+            let tx_hash = retracted_tx.tx_hash;
+            let mut record = TransactionRecord::new(TransactionPayload::Full {
+                tx: retracted_tx,
+                // Transaction "created_at" irrelevant once retracted.
+                created_at_block_timestamp: 0.into(),
+            });
+            record.mark_retracted();
+            assert_eq!(records.insert(tx_hash, record), None);
+        }
+
         let current_epoch = StagingEpoch::new();
         TransactionManager::create_for_testing(
             records.into(),
@@ -365,6 +392,7 @@ struct TransactionManagerContentBuilder {
     committed: Option<IndexMap<TransactionHash, TransactionPayload>>,
     config: Option<TransactionManagerConfig>,
     cancel_requested: Option<Vec<CancellationRequest>>,
+    retracted: Option<Vec<L1HandlerTransaction>>,
 }
 
 impl TransactionManagerContentBuilder {
@@ -432,6 +460,11 @@ impl TransactionManagerContentBuilder {
         self
     }
 
+    fn with_retracted(mut self, retracted: impl IntoIterator<Item = L1HandlerTransaction>) -> Self {
+        self.retracted = Some(retracted.into_iter().collect());
+        self
+    }
+
     fn with_config(mut self, config: TransactionManagerConfig) -> Self {
         self.config = Some(config);
         self
@@ -447,12 +480,16 @@ impl TransactionManagerContentBuilder {
             committed: self.committed,
             rejected: self.rejected,
             cancel_requested: self.cancel_requested,
+            retracted: self.retracted,
             config: self.config,
         })
     }
 
     fn is_default(&self) -> bool {
-        self.uncommitted.is_none() && self.committed.is_none() && self.cancel_requested.is_none()
+        self.uncommitted.is_none()
+            && self.committed.is_none()
+            && self.cancel_requested.is_none()
+            && self.retracted.is_none()
     }
 }
 
@@ -494,6 +531,14 @@ impl L1ProviderClient for FakeL1ProviderClient {
         todo!()
     }
 
+    async fn start_pipelined_block(
+        &self,
+        _parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+        _height: BlockNumber,
+    ) -> L1ProviderClientResult<()> {
+        todo!()
+    }
+
     async fn get_txs(
         &self,
         _n_txs: usize,
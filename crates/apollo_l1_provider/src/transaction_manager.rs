@@ -4,6 +4,7 @@ use std::ops::{Deref, Sub};
 use std::time::Duration;
 
 use apollo_l1_provider_types::{InvalidValidationStatus, ValidationStatus};
+use indexmap::IndexSet;
 use starknet_api::block::BlockTimestamp;
 use starknet_api::executable_transaction::L1HandlerTransaction;
 use starknet_api::transaction::TransactionHash;
@@ -23,8 +24,7 @@ pub struct TransactionManager {
     pub records: Records,
     pub config: TransactionManagerConfig,
     /// Ordered lexicographically by block timestamp, then order-of-arrival for
-    /// identical timestamps, also at any point the staged transactions are a prefix of the
-    /// structure under this order.
+    /// identical timestamps.
     /// Invariant: contains all hashes of transactions that are proposable, and only them.
     /// Invarariant 2: Once removed from this index, a transaction will never be proposed again.
     proposable_index: BTreeMap<BlockTimestamp, Vec<TransactionHash>>,
@@ -69,7 +69,7 @@ impl TransactionManager {
         // Linear scan, but we expect this to be a small number of transactions (< 10 roughly).
         let unstaged_tx_hashes: Vec<_> = past_cooldown_txs
             .flat_map(|(_timestamp, tx_hashes)| tx_hashes.iter())
-            .skip_while(|&&tx_hash| self.is_staged(tx_hash))
+            .filter(|&&tx_hash| !self.is_staged(tx_hash))
             .take(n_txs)
             .copied()
             .collect();
@@ -91,6 +91,14 @@ impl TransactionManager {
         txs
     }
 
+    /// Stages the given transactions, so they aren't proposed in the current block attempt.
+    pub fn stage_txs(&mut self, tx_hashes: &IndexSet<TransactionHash>) {
+        let current_staging_epoch = self.current_staging_epoch; // borrow-checker constraint.
+        for &tx_hash in tx_hashes {
+            self.with_record(tx_hash, |record| record.try_mark_staged(current_staging_epoch));
+        }
+    }
+
     pub fn validate_tx(&mut self, tx_hash: TransactionHash, unix_now: u64) -> ValidationStatus {
         let current_staging_epoch_cloned = self.current_staging_epoch;
 
@@ -110,6 +118,9 @@ impl TransactionManager {
                     TransactionState::CancelledOnL2 => {
                         InvalidValidationStatus::CancelledOnL2.into()
                     }
+                    TransactionState::Retracted => {
+                        InvalidValidationStatus::ConsumedOnL1OrUnknown.into()
+                    }
                     _ => unreachable!(),
                 }
             } else if record.try_mark_staged(current_staging_epoch_cloned) {
@@ -151,11 +162,26 @@ impl TransactionManager {
         let is_new_record = self.create_record_if_not_exist(tx_hash);
         self.with_record(tx_hash, move |record| {
             record.tx.set(tx, block_timestamp);
+            // A transaction logged again on L1 after being retracted is restored.
+            record.unmark_retracted();
         });
 
         is_new_record
     }
 
+    /// Retracts a transaction which is no longer logged on L1, so it's never proposed or validated
+    /// again. The record is kept, since the transaction may have been sent to the batcher already.
+    /// Returns false if the transaction is unknown.
+    pub fn retract_tx(&mut self, tx_hash: TransactionHash) -> bool {
+        self.with_record(tx_hash, |r| r.mark_retracted()).is_some()
+    }
+
+    /// Drops the cancellation request of a transaction, which is no longer logged on L1. Returns
+    /// false if the transaction is unknown.
+    pub fn retract_cancellation(&mut self, tx_hash: TransactionHash) -> bool {
+        self.with_record(tx_hash, |r| r.retract_cancellation_request()).is_some()
+    }
+
     pub fn request_cancellation(
         &mut self,
         tx_hash: TransactionHash,
@@ -200,6 +226,9 @@ impl TransactionManager {
                 TransactionState::CancelledOnL2 => {
                     snapshot.cancelled_on_l2.push(tx_hash);
                 }
+                TransactionState::Retracted => {
+                    snapshot.retracted.push(tx_hash);
+                }
             }
         }
 
@@ -247,20 +276,28 @@ impl TransactionManager {
                     tx_hashes.push(tx_hash);
                 }
             } else {
-                // Remove from the vec for this timestamp, and drop the entry if it becomes empty.
-                match self.proposable_index.entry(created_at) {
-                    Entry::Occupied(mut entry) => {
-                        let tx_hashes = entry.get_mut();
-                        if let Some(index_in_vec) = tx_hashes.iter().position(|&h| h == tx_hash) {
-                            tx_hashes.remove(index_in_vec);
-                            if tx_hashes.is_empty() {
-                                entry.remove();
-                            }
-                        }
+                self.remove_from_proposable_index(created_at, tx_hash);
+            }
+        }
+    }
+
+    fn remove_from_proposable_index(
+        &mut self,
+        created_at: BlockTimestamp,
+        tx_hash: TransactionHash,
+    ) {
+        // Remove from the vec for this timestamp, and drop the entry if it becomes empty.
+        match self.proposable_index.entry(created_at) {
+            Entry::Occupied(mut entry) => {
+                let tx_hashes = entry.get_mut();
+                if let Some(index_in_vec) = tx_hashes.iter().position(|&h| h == tx_hash) {
+                    tx_hashes.remove(index_in_vec);
+                    if tx_hashes.is_empty() {
+                        entry.remove();
                     }
-                    Entry::Vacant(_) => {}
                 }
             }
+            Entry::Vacant(_) => {}
         }
     }
 
@@ -293,6 +330,7 @@ pub(crate) struct TransactionManagerSnapshot {
     // NOTE: transition from cancellation-started into cancelled state is done LAZILY only when
     // validation requests are processed against a record.
     pub cancelled_on_l2: Vec<TransactionHash>,
+    pub retracted: Vec<TransactionHash>,
 }
 
 // Invariant: Monotone-increasing.
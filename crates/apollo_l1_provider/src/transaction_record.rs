@@ -24,6 +24,7 @@ pub struct TransactionRecord {
     /// calculate whether a given state transition is valid.
     committed: bool,
     rejected: bool,
+    retracted: bool,
     cancellation_requested_at: Option<BlockTimestamp>,
     /// A record is staged iff its epoch equals the record owner's (tx manager) epoch counter.
     staged_epoch: StagingEpoch,
@@ -65,17 +66,60 @@ impl TransactionRecord {
             "Attempted to reject a committed transaction {}",
             self.tx.tx_hash()
         );
-        self.state = TransactionState::Rejected;
+        // A retracted transaction stays retracted, so it's never proposed again.
+        if !self.retracted {
+            self.state = TransactionState::Rejected;
+        }
         self.rejected = true;
     }
 
+    /// Marks a transaction whose L1 block was orphaned by an L1 reorg, so it's never proposed or
+    /// validated again unless it's logged again on L1.
+    pub fn mark_retracted(&mut self) {
+        assert!(
+            !self.committed,
+            "Attempted to retract a committed transaction {}",
+            self.tx.tx_hash()
+        );
+        self.state = TransactionState::Retracted;
+        self.retracted = true;
+    }
+
+    /// Restores a retracted transaction which was logged again on L1.
+    pub fn unmark_retracted(&mut self) {
+        if !self.retracted {
+            return;
+        }
+        self.retracted = false;
+        self.state = if self.cancellation_requested_at.is_some() {
+            TransactionState::CancellationStartedOnL2
+        } else if self.rejected {
+            TransactionState::Rejected
+        } else {
+            TransactionState::Pending
+        };
+    }
+
+    /// Drops the cancellation request of the transaction, whose L1 block was orphaned by an L1
+    /// reorg.
+    pub fn retract_cancellation_request(&mut self) {
+        self.cancellation_requested_at = None;
+        if matches!(
+            self.state,
+            TransactionState::CancellationStartedOnL2 | TransactionState::CancelledOnL2
+        ) {
+            self.state =
+                if self.rejected { TransactionState::Rejected } else { TransactionState::Pending };
+        }
+    }
+
     pub fn mark_cancellation_request(
         &mut self,
         timestamp: BlockTimestamp,
     ) -> Option<BlockTimestamp> {
         // Once committed on L2, cancellation requests are only recorded for debugging purposes, but
         // not processed.
-        if !self.is_committed() {
+        if !self.is_committed() && !self.is_retracted() {
             self.state = TransactionState::CancellationStartedOnL2;
         }
         Some(*self.cancellation_requested_at.get_or_insert(timestamp))
@@ -101,6 +145,10 @@ impl TransactionRecord {
         matches!(self.state, TransactionState::Committed)
     }
 
+    pub fn is_retracted(&self) -> bool {
+        matches!(self.state, TransactionState::Retracted)
+    }
+
     /// Answers whether the transaction was fully cancelled on L2 (cancellation request timelock
     /// has expired).
     pub fn is_cancelled(&self) -> bool {
@@ -109,12 +157,13 @@ impl TransactionRecord {
 
     /// Answers whether any node can include this transaction in a block. This is generally possible
     /// in all states in its lifecycle, except after it had already been added to block, or a short
-    /// time after it's cancellation was requested on L1. In particular, this includes states
+    /// time after it's cancellation was requested on L1, or once the L1 block which logged it was
+    /// orphaned by a reorg. In particular, this includes states
     /// like: a rejected transaction, a new timelocked transaction, a
     /// transaction whose cancellation was requested on L1 too recently (there will be a
     /// timelock for this).
     pub fn is_validatable(&self) -> bool {
-        !self.is_committed() && !self.is_cancelled()
+        !self.is_committed() && !self.is_cancelled() && !self.is_retracted()
     }
 
     pub fn is_staged(&self, epoch: StagingEpoch) -> bool {
@@ -126,8 +175,8 @@ impl TransactionRecord {
     /// CancellationStartedOnL2 to CancelledOnL2 after the timelock expires.
     pub fn update_time_based_state(&mut self, unix_now: u64, policy: TransactionRecordPolicy) {
         if let Some(requested_at) = self.cancellation_requested_at {
-            if self.committed {
-                return; // Committing overrides cancellations.
+            if self.committed || self.retracted {
+                return; // Committing and retracting override cancellations.
             }
 
             let cancellation_timelock = &policy.cancellation_timelock.as_secs();
@@ -188,6 +237,7 @@ pub enum TransactionState {
     #[default]
     Pending,
     Rejected,
+    Retracted,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -67,6 +67,10 @@ pub enum L1ProviderRequest {
         state: SessionState,
         height: BlockNumber,
     },
+    StartPipelinedBlock {
+        parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+        height: BlockNumber,
+    },
     Validate {
         tx_hash: TransactionHash,
         height: BlockNumber,
@@ -82,6 +86,7 @@ pub enum L1ProviderResponse {
     GetTransactions(L1ProviderResult<Vec<L1HandlerTransaction>>),
     Initialize(L1ProviderResult<()>),
     StartBlock(L1ProviderResult<()>),
+    StartPipelinedBlock(L1ProviderResult<()>),
     Validate(L1ProviderResult<ValidationStatus>),
     GetL1ProviderSnapshot(L1ProviderResult<L1ProviderSnapshot>),
 }
@@ -98,6 +103,14 @@ pub trait L1ProviderClient: Send + Sync {
         height: BlockNumber,
     ) -> L1ProviderClientResult<()>;
 
+    /// Starts proposing the block after the current height, on top of an uncommitted parent block
+    /// which includes the given L1 handler transactions.
+    async fn start_pipelined_block(
+        &self,
+        parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+        height: BlockNumber,
+    ) -> L1ProviderClientResult<()>;
+
     async fn get_txs(
         &self,
         n_txs: usize,
@@ -143,6 +156,23 @@ where
         )
     }
 
+    #[instrument(skip(self))]
+    async fn start_pipelined_block(
+        &self,
+        parent_l1_handler_tx_hashes: IndexSet<TransactionHash>,
+        height: BlockNumber,
+    ) -> L1ProviderClientResult<()> {
+        let request =
+            L1ProviderRequest::StartPipelinedBlock { parent_l1_handler_tx_hashes, height };
+        handle_all_response_variants!(
+            L1ProviderResponse,
+            StartPipelinedBlock,
+            L1ProviderClientError,
+            L1ProviderError,
+            Direct
+        )
+    }
+
     #[instrument(skip(self))]
     async fn get_txs(
         &self,
@@ -238,6 +268,12 @@ pub enum Event {
         cancellation_request_timestamp: BlockTimestamp,
     },
     TransactionConsumed(TransactionHash),
+    /// The L1 block which logged the transaction was orphaned by an L1 reorg, and the transaction
+    /// wasn't logged again on the new fork.
+    TransactionRetracted(TransactionHash),
+    /// The L1 block which logged the cancellation request of the transaction was orphaned by an L1
+    /// reorg, and the request wasn't logged again on the new fork.
+    TransactionCancellationRetracted(TransactionHash),
 }
 
 impl Event {
@@ -287,6 +323,10 @@ impl Display for Event {
                 )
             }
             Event::TransactionConsumed(data) => write!(f, "TransactionConsumed({data})"),
+            Event::TransactionRetracted(tx_hash) => write!(f, "TransactionRetracted({tx_hash})"),
+            Event::TransactionCancellationRetracted(tx_hash) => {
+                write!(f, "TransactionCancellationRetracted({tx_hash})")
+            }
         }
     }
 }
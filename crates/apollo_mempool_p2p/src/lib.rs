@@ -10,6 +10,7 @@ use apollo_class_manager_types::SharedClassManagerClient;
 use apollo_gateway_types::communication::SharedGatewayClient;
 use apollo_mempool_p2p_types::communication::SharedMempoolP2pPropagatorClient;
 use apollo_network::gossipsub_impl::Topic;
+use apollo_network::network_manager::admin::NetworkAdminRequests;
 use apollo_network::network_manager::metrics::{BroadcastNetworkMetrics, NetworkMetrics};
 use apollo_network::network_manager::{BroadcastTopicChannels, NetworkManager};
 use futures::FutureExt;
@@ -20,6 +21,7 @@ use crate::config::MempoolP2pConfig;
 use crate::metrics::{
     MEMPOOL_P2P_NUM_CONNECTED_PEERS,
     MEMPOOL_P2P_NUM_RECEIVED_MESSAGES,
+    MEMPOOL_P2P_NUM_REJECTED_CONNECTIONS,
     MEMPOOL_P2P_NUM_SENT_MESSAGES,
 };
 use crate::propagator::MempoolP2pPropagator;
@@ -32,6 +34,7 @@ pub fn create_p2p_propagator_and_runner(
     gateway_client: SharedGatewayClient,
     class_manager_client: SharedClassManagerClient,
     mempool_p2p_propagator_client: SharedMempoolP2pPropagatorClient,
+    network_admin_requests: Option<NetworkAdminRequests>,
 ) -> (MempoolP2pPropagator, MempoolP2pRunner) {
     let transaction_converter = TransactionConverter::new(
         class_manager_client.clone(),
@@ -48,6 +51,7 @@ pub fn create_p2p_propagator_and_runner(
     let network_manager_metrics = Some(NetworkMetrics {
         num_connected_peers: MEMPOOL_P2P_NUM_CONNECTED_PEERS,
         num_blacklisted_peers: MEMPOOL_P2P_NUM_BLACKLISTED_PEERS,
+        num_rejected_connections: MEMPOOL_P2P_NUM_REJECTED_CONNECTIONS,
         broadcast_metrics_by_topic: Some(broadcast_metrics_by_topic),
         sqmr_metrics: None,
     });
@@ -56,7 +60,11 @@ pub fn create_p2p_propagator_and_runner(
         // TODO(Shahak): Consider filling this once the sequencer node has a name.
         None,
         network_manager_metrics,
+        None,
     );
+    if let Some(network_admin_requests) = network_admin_requests {
+        network_manager.serve_admin_requests(network_admin_requests);
+    }
     let BroadcastTopicChannels { broadcasted_messages_receiver, broadcast_topic_client } =
         network_manager
             .register_broadcast_topic(
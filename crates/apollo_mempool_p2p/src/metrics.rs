@@ -8,6 +8,7 @@ define_metrics!(
         // Counters
         MetricCounter { MEMPOOL_P2P_NUM_SENT_MESSAGES, "apollo_mempool_p2p_num_sent_messages", "The number of messages sent by the mempool p2p component", init = 0 },
         MetricCounter { MEMPOOL_P2P_NUM_RECEIVED_MESSAGES, "apollo_mempool_p2p_num_received_messages", "The number of messages received by the mempool p2p component", init = 0 },
+        MetricCounter { MEMPOOL_P2P_NUM_REJECTED_CONNECTIONS, "apollo_mempool_p2p_num_rejected_connections", "The number of connections rejected by the mempool p2p component since the peer is not in the allowlist", init = 0 },
         // Histogram
         MetricHistogram { MEMPOOL_P2P_BROADCASTED_BATCH_SIZE, "apollo_mempool_p2p_broadcasted_transaction_batch_size", "The number of transactions in batches broadcast by the mempool p2p component" }
     },
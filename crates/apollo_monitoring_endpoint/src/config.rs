@@ -16,6 +16,7 @@ pub struct MonitoringEndpointConfig {
     pub port: u16,
     pub collect_metrics: bool,
     pub collect_profiling_metrics: bool,
+    pub enable_network_admin_actions: bool,
 }
 
 impl MonitoringEndpointConfig {
@@ -25,6 +26,7 @@ impl MonitoringEndpointConfig {
             port: MONITORING_ENDPOINT_DEFAULT_PORT,
             collect_metrics: true,
             collect_profiling_metrics: true,
+            enable_network_admin_actions: false,
         }
     }
 }
@@ -62,6 +64,13 @@ impl SerializeConfig for MonitoringEndpointConfig {
                 "If true, collect and return profiling metrics in the monitoring endpoint.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "enable_network_admin_actions",
+                &self.enable_network_admin_actions,
+                "If true, the monitoring endpoint serves requests to ban, unban and dial peers of \
+                 the networks of the node.",
+                ParamPrivacyInput::Public,
+            ),
         ])
     }
 }
@@ -1,4 +1,8 @@
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use apollo_infra::component_definitions::ComponentStarter;
 use apollo_infra_utils::type_name::short_type_name;
@@ -6,12 +10,16 @@ use apollo_l1_provider_types::{L1ProviderSnapshot, SharedL1ProviderClient};
 use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_mempool_types::mempool_types::MempoolSnapshot;
 use apollo_metrics::metrics::COLLECT_SEQUENCER_PROFILING_METRICS;
+use apollo_network::network_manager::admin::{NetworkAdminClient, NetworkAdminError, PeerInfo};
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{async_trait, Json, Router, Server};
 use hyper::Error;
+use libp2p::{Multiaddr, PeerId};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use serde::Deserialize;
 use tracing::{error, info, instrument};
 
 use crate::config::MonitoringEndpointConfig;
@@ -27,6 +35,12 @@ pub(crate) const VERSION: &str = "nodeVersion";
 pub(crate) const METRICS: &str = "metrics";
 pub(crate) const MEMPOOL_SNAPSHOT: &str = "mempoolSnapshot";
 pub(crate) const L1_PROVIDER_SNAPSHOT: &str = "l1ProviderSnapshot";
+pub(crate) const NETWORKS: &str = "networks";
+
+// The longest a peer can be banned for through the endpoint.
+pub(crate) const MAX_BAN_DURATION_SECONDS: u64 = 365 * 24 * 60 * 60;
+
+type NetworkAdminClients = Arc<BTreeMap<String, NetworkAdminClient>>;
 
 const HISTOGRAM_BUCKETS: &[f64] =
     &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0];
@@ -37,6 +51,7 @@ pub struct MonitoringEndpoint {
     prometheus_handle: Option<PrometheusHandle>,
     mempool_client: Option<SharedMempoolClient>,
     l1_provider_client: Option<SharedL1ProviderClient>,
+    network_admin_clients: NetworkAdminClients,
 }
 
 impl MonitoringEndpoint {
@@ -45,6 +60,7 @@ impl MonitoringEndpoint {
         version: &'static str,
         mempool_client: Option<SharedMempoolClient>,
         l1_provider_client: Option<SharedL1ProviderClient>,
+        network_admin_clients: BTreeMap<String, NetworkAdminClient>,
     ) -> Self {
         // TODO(Tsabary): consider error handling
         let prometheus_handle = if config.collect_metrics {
@@ -70,6 +86,7 @@ impl MonitoringEndpoint {
             prometheus_handle,
             mempool_client,
             l1_provider_client,
+            network_admin_clients: Arc::new(network_admin_clients),
         }
     }
 
@@ -95,8 +112,9 @@ impl MonitoringEndpoint {
         let prometheus_handle = self.prometheus_handle.clone();
         let mempool_client = self.mempool_client.clone();
         let l1_provider_client = self.l1_provider_client.clone();
+        let network_admin_clients = self.network_admin_clients.clone();
 
-        Router::new()
+        let router = Router::new()
             .route(
                 format!("/{MONITORING_PREFIX}/{ALIVE}").as_str(),
                 get(move || async { StatusCode::OK.to_string() }),
@@ -121,6 +139,37 @@ impl MonitoringEndpoint {
                 format!("/{MONITORING_PREFIX}/{L1_PROVIDER_SNAPSHOT}").as_str(),
                 get(move || get_l1_provider_snapshot(l1_provider_client)),
             )
+            .route(format!("/{MONITORING_PREFIX}/{NETWORKS}").as_str(), {
+                let network_admin_clients = network_admin_clients.clone();
+                get(move || networks(network_admin_clients))
+            })
+            .route(format!("/{MONITORING_PREFIX}/{NETWORKS}/:network/peers").as_str(), {
+                let network_admin_clients = network_admin_clients.clone();
+                get(move |path| peers(network_admin_clients, path))
+            });
+        // The endpoint isn't authenticated, so changing the peers of the node is opt-in.
+        if !self.config.enable_network_admin_actions {
+            return router;
+        }
+        router
+            .route(
+                format!("/{MONITORING_PREFIX}/{NETWORKS}/:network/peers/:peer_id/ban").as_str(),
+                {
+                    let network_admin_clients = network_admin_clients.clone();
+                    post(move |path, query| ban_peer(network_admin_clients, path, query))
+                },
+            )
+            .route(
+                format!("/{MONITORING_PREFIX}/{NETWORKS}/:network/peers/:peer_id/unban").as_str(),
+                {
+                    let network_admin_clients = network_admin_clients.clone();
+                    post(move |path| unban_peer(network_admin_clients, path))
+                },
+            )
+            .route(
+                format!("/{MONITORING_PREFIX}/{NETWORKS}/:network/dial").as_str(),
+                post(move |path, query| dial(network_admin_clients, path, query)),
+            )
     }
 }
 
@@ -129,8 +178,15 @@ pub fn create_monitoring_endpoint(
     version: &'static str,
     mempool_client: Option<SharedMempoolClient>,
     l1_provider_client: Option<SharedL1ProviderClient>,
+    network_admin_clients: BTreeMap<String, NetworkAdminClient>,
 ) -> MonitoringEndpoint {
-    MonitoringEndpoint::new(config, version, mempool_client, l1_provider_client)
+    MonitoringEndpoint::new(
+        config,
+        version,
+        mempool_client,
+        l1_provider_client,
+        network_admin_clients,
+    )
 }
 
 #[async_trait]
@@ -186,3 +242,88 @@ async fn get_l1_provider_snapshot(
         None => Err(StatusCode::METHOD_NOT_ALLOWED),
     }
 }
+
+// Returns the names of the networks of the node.
+#[instrument(level = "debug", skip(network_admin_clients))]
+async fn networks(network_admin_clients: NetworkAdminClients) -> Json<Vec<String>> {
+    network_admin_clients.keys().cloned().collect::<Vec<_>>().into()
+}
+
+// Returns the peers the node is connected to in the given network.
+#[instrument(level = "debug", skip(network_admin_clients))]
+async fn peers(
+    network_admin_clients: NetworkAdminClients,
+    Path(network): Path<String>,
+) -> Result<Json<Vec<PeerInfo>>, StatusCode> {
+    let client = network_admin_client(&network_admin_clients, &network)?;
+    client.get_peers().await.map(Json).map_err(network_admin_error_status)
+}
+
+#[derive(Debug, Deserialize)]
+struct BanPeerQuery {
+    duration_seconds: u64,
+}
+
+// Disconnects from the peer and blocks it for the given duration.
+#[instrument(level = "debug", skip(network_admin_clients))]
+async fn ban_peer(
+    network_admin_clients: NetworkAdminClients,
+    Path((network, peer_id)): Path<(String, String)>,
+    Query(BanPeerQuery { duration_seconds }): Query<BanPeerQuery>,
+) -> Result<StatusCode, StatusCode> {
+    if duration_seconds > MAX_BAN_DURATION_SECONDS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let client = network_admin_client(&network_admin_clients, &network)?;
+    let peer_id = PeerId::from_str(&peer_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    client
+        .ban_peer(peer_id, Duration::from_secs(duration_seconds))
+        .await
+        .map_err(network_admin_error_status)?;
+    Ok(StatusCode::OK)
+}
+
+// Unblocks the peer and clears its misconduct score.
+#[instrument(level = "debug", skip(network_admin_clients))]
+async fn unban_peer(
+    network_admin_clients: NetworkAdminClients,
+    Path((network, peer_id)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    let client = network_admin_client(&network_admin_clients, &network)?;
+    let peer_id = PeerId::from_str(&peer_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    client.unban_peer(peer_id).await.map_err(network_admin_error_status)?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Debug, Deserialize)]
+struct DialQuery {
+    multiaddr: String,
+}
+
+#[instrument(level = "debug", skip(network_admin_clients))]
+async fn dial(
+    network_admin_clients: NetworkAdminClients,
+    Path(network): Path<String>,
+    Query(DialQuery { multiaddr }): Query<DialQuery>,
+) -> Result<StatusCode, StatusCode> {
+    let client = network_admin_client(&network_admin_clients, &network)?;
+    let multiaddr = Multiaddr::from_str(&multiaddr).map_err(|_| StatusCode::BAD_REQUEST)?;
+    client.dial_peer(multiaddr).await.map_err(network_admin_error_status)?;
+    Ok(StatusCode::OK)
+}
+
+fn network_admin_client<'a>(
+    network_admin_clients: &'a NetworkAdminClients,
+    network: &str,
+) -> Result<&'a NetworkAdminClient, StatusCode> {
+    network_admin_clients.get(network).ok_or(StatusCode::NOT_FOUND)
+}
+
+fn network_admin_error_status(err: NetworkAdminError) -> StatusCode {
+    error!("Network admin request failed: {:?}", err);
+    match err {
+        NetworkAdminError::NoSuchPeer(_) => StatusCode::NOT_FOUND,
+        NetworkAdminError::DialFailed { .. } => StatusCode::BAD_REQUEST,
+        NetworkAdminError::NetworkManagerStopped => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
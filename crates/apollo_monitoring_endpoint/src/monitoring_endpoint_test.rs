@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::IpAddr;
 use std::sync::Arc;
 
@@ -9,11 +9,14 @@ use apollo_mempool_types::mempool_types::{
     MempoolStateSnapshot,
     TransactionQueueSnapshot,
 };
-use axum::http::StatusCode;
+use apollo_network::network_manager::admin::create_network_admin_channel;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
 use axum::response::Response;
 use axum::Router;
 use hyper::body::to_bytes;
 use hyper::Client;
+use libp2p::PeerId;
 use metrics::{counter, describe_counter};
 use pretty_assertions::assert_eq;
 use serde_json::{from_slice, to_value, Value};
@@ -31,8 +34,11 @@ use crate::monitoring_endpoint::{
     MonitoringEndpoint,
     ALIVE,
     L1_PROVIDER_SNAPSHOT,
+    MAX_BAN_DURATION_SECONDS,
     MEMPOOL_SNAPSHOT,
     METRICS,
+    MONITORING_PREFIX,
+    NETWORKS,
     READY,
     VERSION,
 };
@@ -47,11 +53,12 @@ const CONFIG_WITHOUT_METRICS: MonitoringEndpointConfig = MonitoringEndpointConfi
     port: MONITORING_ENDPOINT_DEFAULT_PORT,
     collect_metrics: false,
     collect_profiling_metrics: false,
+    enable_network_admin_actions: false,
 };
 
 fn setup_monitoring_endpoint(config: Option<MonitoringEndpointConfig>) -> MonitoringEndpoint {
     let config = config.unwrap_or(CONFIG_WITHOUT_METRICS);
-    create_monitoring_endpoint(config, TEST_VERSION, None, None)
+    create_monitoring_endpoint(config, TEST_VERSION, None, None, BTreeMap::new())
 }
 
 async fn request_app(app: Router, method: &str) -> Response {
@@ -134,6 +141,7 @@ fn setup_monitoring_endpoint_with_mempool_client() -> MonitoringEndpoint {
         TEST_VERSION,
         Some(shared_mock_mempool_client),
         None,
+        BTreeMap::new(),
     )
 }
 
@@ -196,6 +204,7 @@ fn setup_monitoring_endpoint_with_l1_provider_client() -> MonitoringEndpoint {
         TEST_VERSION,
         None,
         Some(shared_mock_l1_provider_client),
+        BTreeMap::new(),
     )
 }
 
@@ -244,3 +253,73 @@ async fn l1_provider_not_present() {
     let response = request_app(app, L1_PROVIDER_SNAPSHOT).await;
     assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
 }
+
+#[tokio::test]
+async fn unknown_network() {
+    let app = setup_monitoring_endpoint(None).app();
+    let response = request_app(app, &format!("{NETWORKS}/unknown_network/peers")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// The network manager of the network isn't running, so the requests of its admin client fail.
+fn setup_monitoring_endpoint_with_network(
+    enable_network_admin_actions: bool,
+) -> MonitoringEndpoint {
+    let (network_admin_client, _) = create_network_admin_channel();
+    create_monitoring_endpoint(
+        MonitoringEndpointConfig { enable_network_admin_actions, ..CONFIG_WITHOUT_METRICS },
+        TEST_VERSION,
+        None,
+        None,
+        BTreeMap::from([("consensus".to_string(), network_admin_client)]),
+    )
+}
+
+async fn post_app(app: Router, method: &str) -> Response {
+    let request = Request::post(format!("http://0.0.0.0:0/{MONITORING_PREFIX}/{method}"))
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn networks() {
+    let app = setup_monitoring_endpoint_with_network(false).app();
+    let response = request_app(app, NETWORKS).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = to_bytes(response.into_body()).await.unwrap();
+    let received_networks: Vec<String> = from_slice(&body_bytes).unwrap();
+    assert_eq!(received_networks, vec!["consensus".to_string()]);
+}
+
+#[tokio::test]
+async fn ban_peer_with_out_of_range_duration() {
+    let endpoint = setup_monitoring_endpoint_with_network(true);
+    let peer_id = PeerId::random();
+    for duration_seconds in [MAX_BAN_DURATION_SECONDS + 1, u64::MAX] {
+        let method =
+            format!("{NETWORKS}/consensus/peers/{peer_id}/ban?duration_seconds={duration_seconds}");
+        let response = post_app(endpoint.app(), &method).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{method}");
+    }
+}
+
+#[tokio::test]
+async fn network_admin_actions_disabled_by_default() {
+    assert!(!MonitoringEndpointConfig::default().enable_network_admin_actions);
+
+    let peer_id = PeerId::random();
+    for (enable_network_admin_actions, expected_status) in
+        [(false, StatusCode::NOT_FOUND), (true, StatusCode::INTERNAL_SERVER_ERROR)]
+    {
+        let endpoint = setup_monitoring_endpoint_with_network(enable_network_admin_actions);
+        for method in [
+            format!("{NETWORKS}/consensus/peers/{peer_id}/ban?duration_seconds=60"),
+            format!("{NETWORKS}/consensus/peers/{peer_id}/unban"),
+            format!("{NETWORKS}/consensus/dial?multiaddr=/ip4/127.0.0.1/tcp/10000"),
+        ] {
+            let response = post_app(endpoint.app(), &method).await;
+            assert_eq!(response.status(), expected_status, "{method}");
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::task::Poll;
+
+use futures::StreamExt;
+use libp2p::swarm::{
+    ConnectionClosed,
+    ConnectionDenied,
+    ConnectionId,
+    FromSwarm,
+    NetworkBehaviour,
+    THandler,
+    THandlerInEvent,
+    THandlerOutEvent,
+    ToSwarm,
+};
+use libp2p::{Multiaddr, PeerId};
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkHash;
+use tokio::time::Instant;
+
+use super::handler::Handler;
+use super::{Allowlist, AllowlistError};
+
+#[derive(Debug)]
+pub enum ToOtherBehaviourEvent {
+    /// The peer has to prove it's run by a staker. Until the authentication ends, other behaviours
+    /// shouldn't exchange messages with it.
+    AuthenticationStarted {
+        peer_id: PeerId,
+    },
+    /// The peer was authenticated, rejected or disconnected.
+    AuthenticationEnded {
+        peer_id: PeerId,
+    },
+    ConnectionRejected {
+        peer_id: PeerId,
+    },
+}
+
+impl Allowlist {
+    fn handle_established_connection(
+        &mut self,
+        peer_id: PeerId,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        if self.is_allowed(&peer_id) {
+            return Ok(Handler::new(self.sign_identity.clone(), None));
+        }
+        // Without a way to verify identity proofs, only the configured peers are allowed.
+        if self.verify_identity.is_none() {
+            self.pending_events.push_back(ToSwarm::GenerateEvent(
+                ToOtherBehaviourEvent::ConnectionRejected { peer_id },
+            ));
+            return Err(ConnectionDenied::new(AllowlistError::PeerNotAllowed(peer_id)));
+        }
+        self.start_authentication(peer_id);
+        // Each connection is challenged with a new nonce, so that a proof can't be replayed.
+        let challenge = Nonce(StarkHash::from(rand::random::<u128>()));
+        Ok(Handler::new(self.sign_identity.clone(), Some(challenge)))
+    }
+}
+
+impl NetworkBehaviour for Allowlist {
+    type ConnectionHandler = Handler;
+    type ToSwarm = ToOtherBehaviourEvent;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        peer_id: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.handle_established_connection(peer_id)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        peer_id: PeerId,
+        _addr: &Multiaddr,
+        _role_override: libp2p::core::Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.handle_established_connection(peer_id)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        _connection_id: ConnectionId,
+        identity_proof: THandlerOutEvent<Self>,
+    ) {
+        self.authenticate(peer_id, identity_proof.as_ref());
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = event
+        {
+            if self.unauthenticated_peers.remove(&peer_id).is_some() {
+                self.end_authentication(peer_id);
+            }
+        }
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        while let Poll::Ready(Some(peer_id)) = self.authentication_timeouts.poll_next_unpin(cx) {
+            // The peer may have reconnected since the timeout started.
+            if self
+                .unauthenticated_peers
+                .get(&peer_id)
+                .is_some_and(|deadline| *deadline <= Instant::now())
+            {
+                self.unauthenticated_peers.remove(&peer_id);
+                self.end_authentication(peer_id);
+                self.reject(peer_id);
+            }
+        }
+        self.pending_events.pop_front().map(Poll::Ready).unwrap_or(Poll::Pending)
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+
+use libp2p::swarm::handler::{
+    ConnectionEvent,
+    DialUpgradeError,
+    FullyNegotiatedOutbound,
+    ListenUpgradeError,
+};
+use libp2p::swarm::{ConnectionHandler, ConnectionHandlerEvent, SubstreamProtocol};
+use starknet_api::core::Nonce;
+use tracing::debug;
+
+use super::protocol::{InboundProtocol, OutboundProtocol};
+use super::{IdentityProof, IdentitySigner};
+
+/// Answers the authentication challenges of the peer, and challenges the peer if it has to prove
+/// it's run by a staker.
+pub struct Handler {
+    sign_identity: Option<IdentitySigner>,
+    // Taken once the challenge is sent.
+    challenge: Option<Nonce>,
+    awaiting_identity_proof: bool,
+    // The proof the peer answered the challenge with, or None if it didn't answer with one.
+    pending_events: VecDeque<Option<IdentityProof>>,
+}
+
+impl Handler {
+    pub fn new(sign_identity: Option<IdentitySigner>, challenge: Option<Nonce>) -> Self {
+        Self {
+            sign_identity,
+            awaiting_identity_proof: challenge.is_some(),
+            challenge,
+            pending_events: VecDeque::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn challenge(&self) -> Option<Nonce> {
+        self.challenge
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Infallible;
+    type ToBehaviour = Option<IdentityProof>;
+    type InboundProtocol = InboundProtocol;
+    type OutboundProtocol = OutboundProtocol;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(InboundProtocol { sign_identity: self.sign_identity.clone() }, ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        self.awaiting_identity_proof
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if let Some(nonce) = self.challenge.take() {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(OutboundProtocol { nonce }, ()),
+            });
+        }
+        match self.pending_events.pop_front() {
+            Some(identity_proof) => {
+                Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(identity_proof))
+            }
+            None => Poll::Pending,
+        }
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        match event {}
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            '_,
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        // No need to wake because the swarm guarantees that `poll` will be called after
+        // on_connection_event. See https://github.com/libp2p/rust-libp2p/issues/5147
+        match event {
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: identity_proof,
+                ..
+            }) => {
+                self.awaiting_identity_proof = false;
+                self.pending_events.push_back(identity_proof);
+            }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { error, .. }) => {
+                debug!("The peer failed to answer the authentication challenge: {error:?}");
+                self.awaiting_identity_proof = false;
+                self.pending_events.push_back(None);
+            }
+            ConnectionEvent::ListenUpgradeError(ListenUpgradeError { error, .. }) => {
+                debug!("Failed to answer the authentication challenge of the peer: {error:?}");
+            }
+            _ => {}
+        }
+    }
+}
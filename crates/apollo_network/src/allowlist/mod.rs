@@ -0,0 +1,273 @@
+//! Restricts the connections of the node to a known set of peers, for permissioned networks.
+//!
+//! A peer is allowed if its peer id is configured, or if it proves that it's run by one of the
+//! allowed stakers. Each connection to another peer challenges it with a random nonce, which the
+//! peer answers with the staker's identity signature on its peer id and the nonce. Other peers may
+//! connect until they answer, and are disconnected if they don't prove they're run by a staker in
+//! time. Until then, the other behaviours don't exchange messages with them.
+
+pub(crate) mod behaviour_impl;
+pub(crate) mod handler;
+pub(crate) mod protocol;
+#[cfg(test)]
+mod test;
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apollo_config::converters::deserialize_milliseconds_to_duration;
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use futures::channel::mpsc::Sender;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
+use libp2p::swarm::{CloseConnection, ToSwarm};
+use libp2p::PeerId;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use starknet_api::core::Nonce;
+use starknet_api::crypto::utils::{PublicKey, RawSignature};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+pub use self::behaviour_impl::ToOtherBehaviourEvent;
+use crate::mixed_behaviour;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AllowlistError {
+    #[error("Peer {0} is not in the allowlist.")]
+    PeerNotAllowed(PeerId),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AllowlistConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "deserialize_peer_ids")]
+    pub peer_ids: Vec<PeerId>,
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub authentication_timeout_millis: Duration,
+}
+
+impl Default for AllowlistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            peer_ids: Vec::new(),
+            authentication_timeout_millis: Duration::from_millis(5000),
+        }
+    }
+}
+
+impl SerializeConfig for AllowlistConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "enabled",
+                &self.enabled,
+                "Whether to accept connections only from the configured peer ids and from peers \
+                 which prove they are run by a staker of the committee.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "peer_ids",
+                &serialize_peer_ids(&self.peer_ids),
+                "A comma separated list of the peer ids which may connect to the node when the \
+                 allowlist is enabled.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "authentication_timeout_millis",
+                &self.authentication_timeout_millis.as_millis(),
+                "The time in milliseconds a peer which isn't in the allowlist has to prove it's \
+                 run by a staker before it's disconnected.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+fn deserialize_peer_ids<'de, D>(de: D) -> Result<Vec<PeerId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    raw_str
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            PeerId::from_str(s).map_err(|_| {
+                D::Error::custom(format!("Couldn't deserialize vector. Failed to parse value: {s}"))
+            })
+        })
+        .collect()
+}
+
+fn serialize_peer_ids(peer_ids: &[PeerId]) -> String {
+    peer_ids.iter().map(|peer_id| peer_id.to_string()).collect::<Vec<String>>().join(",")
+}
+
+/// A staker's identity signature on the peer id of a node it runs, and on the nonce the node was
+/// challenged with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentityProof {
+    pub nonce: Nonce,
+    pub signature: RawSignature,
+}
+
+/// Signs the peer id of this node along with the nonce a peer challenged it with. Returns None if
+/// the node isn't run by a staker, or if it failed to sign.
+pub type IdentitySigner =
+    Arc<dyn Fn(Nonce) -> BoxFuture<'static, Option<RawSignature>> + Send + Sync>;
+
+/// Returns whether the proof is a signature of the given staker key on the given peer id and the
+/// proof's nonce.
+pub type IdentityVerifier = Arc<dyn Fn(PeerId, &IdentityProof, &PublicKey) -> bool + Send + Sync>;
+
+/// How the node proves it's run by a staker, and verifies that its peers are.
+#[derive(Clone)]
+pub struct StakerAuthentication {
+    pub sign_identity: IdentitySigner,
+    pub verify_identity: IdentityVerifier,
+}
+
+/// Replaces the stakers whose nodes may connect, given by their public keys. Used when the
+/// committee changes.
+pub type AllowedStakersSender = Sender<Vec<PublicKey>>;
+
+/// Reports a staker, given by its public key, which misbehaved. The peers which proved they are run
+/// by the staker are reported as malicious.
+pub type ReportedStakersSender = Sender<PublicKey>;
+
+pub struct Allowlist {
+    config: AllowlistConfig,
+    sign_identity: Option<IdentitySigner>,
+    verify_identity: Option<IdentityVerifier>,
+    allowed_stakers: HashSet<PublicKey>,
+    // The staker each peer proved it's run by.
+    authenticated_peers: HashMap<PeerId, PublicKey>,
+    // Connected peers which weren't authenticated yet, along with the time they must be
+    // authenticated by.
+    unauthenticated_peers: HashMap<PeerId, Instant>,
+    authentication_timeouts: FuturesUnordered<BoxFuture<'static, PeerId>>,
+    pending_events: VecDeque<ToSwarm<ToOtherBehaviourEvent, libp2p::swarm::THandlerInEvent<Self>>>,
+}
+
+impl Allowlist {
+    pub(crate) fn new(
+        config: AllowlistConfig,
+        staker_authentication: Option<StakerAuthentication>,
+    ) -> Self {
+        let (sign_identity, verify_identity) = match staker_authentication {
+            Some(StakerAuthentication { sign_identity, verify_identity }) => {
+                (Some(sign_identity), Some(verify_identity))
+            }
+            None => (None, None),
+        };
+        Self {
+            config,
+            sign_identity,
+            verify_identity,
+            allowed_stakers: HashSet::new(),
+            authenticated_peers: HashMap::new(),
+            unauthenticated_peers: HashMap::new(),
+            authentication_timeouts: FuturesUnordered::new(),
+            pending_events: VecDeque::new(),
+        }
+    }
+
+    fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        !self.config.enabled
+            || self.config.peer_ids.contains(peer_id)
+            || self
+                .authenticated_peers
+                .get(peer_id)
+                .is_some_and(|staker| self.allowed_stakers.contains(staker))
+    }
+
+    /// Replaces the allowed stakers, and disconnects the peers run by stakers which are no longer
+    /// allowed.
+    pub(crate) fn set_allowed_stakers(&mut self, stakers: Vec<PublicKey>) {
+        self.allowed_stakers = stakers.into_iter().collect();
+        let removed_peers: Vec<_> = self
+            .authenticated_peers
+            .iter()
+            .filter(|(_, staker)| !self.allowed_stakers.contains(staker))
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        for peer_id in removed_peers {
+            self.authenticated_peers.remove(&peer_id);
+            if !self.is_allowed(&peer_id) {
+                self.reject(peer_id);
+            }
+        }
+    }
+
+    /// The peers which proved they are run by the given staker.
+    pub(crate) fn peers_run_by(&self, staker: &PublicKey) -> Vec<PeerId> {
+        self.authenticated_peers
+            .iter()
+            .filter(|(_, peer_staker)| *peer_staker == staker)
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+
+    // Called on the first connection to a peer which isn't allowed yet.
+    fn start_authentication(&mut self, peer_id: PeerId) {
+        if self.unauthenticated_peers.contains_key(&peer_id) {
+            return;
+        }
+        let timeout = self.config.authentication_timeout_millis;
+        self.unauthenticated_peers.insert(peer_id, Instant::now() + timeout);
+        self.authentication_timeouts
+            .push(tokio::time::sleep(timeout).map(move |_| peer_id).boxed());
+        self.pending_events.push_back(ToSwarm::GenerateEvent(
+            ToOtherBehaviourEvent::AuthenticationStarted { peer_id },
+        ));
+    }
+
+    fn end_authentication(&mut self, peer_id: PeerId) {
+        self.pending_events.push_back(ToSwarm::GenerateEvent(
+            ToOtherBehaviourEvent::AuthenticationEnded { peer_id },
+        ));
+    }
+
+    fn authenticate(&mut self, peer_id: PeerId, identity_proof: Option<&IdentityProof>) {
+        if self.unauthenticated_peers.remove(&peer_id).is_none() {
+            return;
+        }
+        self.end_authentication(peer_id);
+        let staker = match (&self.verify_identity, identity_proof) {
+            (Some(verify_identity), Some(identity_proof)) => self
+                .allowed_stakers
+                .iter()
+                .find(|staker| verify_identity(peer_id, identity_proof, staker))
+                .copied(),
+            _ => None,
+        };
+        match staker {
+            Some(staker) => {
+                debug!("Peer {peer_id} proved it's run by the staker {staker:?}.");
+                self.authenticated_peers.insert(peer_id, staker);
+            }
+            None => self.reject(peer_id),
+        }
+    }
+
+    fn reject(&mut self, peer_id: PeerId) {
+        warn!("Disconnecting from peer {peer_id} since it's not in the allowlist.");
+        self.pending_events
+            .push_back(ToSwarm::CloseConnection { peer_id, connection: CloseConnection::All });
+        self.pending_events.push_back(ToSwarm::GenerateEvent(
+            ToOtherBehaviourEvent::ConnectionRejected { peer_id },
+        ));
+    }
+}
+
+impl From<ToOtherBehaviourEvent> for mixed_behaviour::Event {
+    fn from(event: ToOtherBehaviourEvent) -> Self {
+        Self::ToOtherBehaviourEvent(mixed_behaviour::ToOtherBehaviourEvent::Allowlist(event))
+    }
+}
@@ -0,0 +1,102 @@
+use std::{io, iter};
+
+use futures::future::BoxFuture;
+use futures::{AsyncRead, AsyncWrite, AsyncWriteExt, FutureExt};
+use libp2p::core::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo};
+use libp2p::StreamProtocol;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use starknet_api::core::Nonce;
+use starknet_api::crypto::utils::RawSignature;
+
+use super::{IdentityProof, IdentitySigner};
+use crate::sqmr::messages::{read_message, write_message};
+
+pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/starknet/authentication/1.0.0");
+
+/// Answers the challenge of a peer with this node's signature on its peer id and the challenge's
+/// nonce. Nodes which aren't run by a staker answer without a signature.
+pub struct InboundProtocol {
+    pub sign_identity: Option<IdentitySigner>,
+}
+
+impl UpgradeInfo for InboundProtocol {
+    type Info = StreamProtocol;
+    type InfoIter = iter::Once<Self::Info>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        iter::once(PROTOCOL_NAME)
+    }
+}
+
+impl<Stream> InboundUpgrade<Stream> for InboundProtocol
+where
+    Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Output = ();
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, mut stream: Stream, _: Self::Info) -> Self::Future {
+        async move {
+            let nonce: Nonce = read_json(&mut stream).await?;
+            let signature = match self.sign_identity {
+                Some(sign_identity) => sign_identity(nonce).await,
+                None => None,
+            };
+            write_json(&signature, &mut stream).await?;
+            stream.close().await
+        }
+        .boxed()
+    }
+}
+
+/// Challenges a peer to prove it's run by a staker, with a nonce that's used only once. Outputs
+/// the proof, or None if the peer didn't sign the challenge.
+#[derive(Debug)]
+pub struct OutboundProtocol {
+    pub nonce: Nonce,
+}
+
+impl UpgradeInfo for OutboundProtocol {
+    type Info = StreamProtocol;
+    type InfoIter = iter::Once<Self::Info>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        iter::once(PROTOCOL_NAME)
+    }
+}
+
+impl<Stream> OutboundUpgrade<Stream> for OutboundProtocol
+where
+    Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Output = Option<IdentityProof>;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+    fn upgrade_outbound(self, mut stream: Stream, _: Self::Info) -> Self::Future {
+        async move {
+            write_json(&self.nonce, &mut stream).await?;
+            let signature: Option<RawSignature> = read_json(&mut stream).await?;
+            Ok(signature.map(|signature| IdentityProof { nonce: self.nonce, signature }))
+        }
+        .boxed()
+    }
+}
+
+async fn write_json<T: Serialize, Stream: AsyncWrite + Unpin>(
+    value: &T,
+    stream: &mut Stream,
+) -> Result<(), io::Error> {
+    let message = serde_json::to_vec(value)?;
+    write_message(&message, stream).await?;
+    stream.flush().await
+}
+
+async fn read_json<T: DeserializeOwned, Stream: AsyncRead + Unpin>(
+    stream: &mut Stream,
+) -> Result<T, io::Error> {
+    let message = read_message(stream).await?.ok_or(io::ErrorKind::UnexpectedEof)?;
+    Ok(serde_json::from_slice(&message)?)
+}
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{FutureExt, Stream, StreamExt};
+use libp2p::core::upgrade::{InboundUpgrade, OutboundUpgrade};
+use libp2p::swarm::{CloseConnection, ConnectionId, NetworkBehaviour, ToSwarm};
+use libp2p::{Multiaddr, PeerId};
+use starknet_api::core::Nonce;
+use starknet_api::crypto::utils::{PublicKey, RawSignature};
+use starknet_api::hash::StarkHash;
+
+use super::handler::Handler;
+use super::protocol::{InboundProtocol, OutboundProtocol, PROTOCOL_NAME};
+use super::{
+    Allowlist,
+    AllowlistConfig,
+    IdentityProof,
+    IdentitySigner,
+    StakerAuthentication,
+    ToOtherBehaviourEvent,
+};
+use crate::test_utils::get_connected_streams;
+
+const AUTHENTICATION_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl Unpin for Allowlist {}
+
+impl Stream for Allowlist {
+    type Item = ToSwarm<ToOtherBehaviourEvent, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match Pin::into_inner(self).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(event) => Poll::Ready(Some(event)),
+        }
+    }
+}
+
+fn staker(key: u8) -> PublicKey {
+    PublicKey(StarkHash::from(key))
+}
+
+// The test identity signature of a staker is its key and the nonce.
+fn identity_proof(staker: PublicKey, nonce: Nonce) -> IdentityProof {
+    IdentityProof { nonce, signature: RawSignature(vec![staker.0, nonce.0]) }
+}
+
+fn staker_authentication() -> StakerAuthentication {
+    StakerAuthentication {
+        sign_identity: Arc::new(|_nonce| async { None }.boxed()),
+        verify_identity: Arc::new(|_peer_id, identity_proof, staker| {
+            *identity_proof == self::identity_proof(*staker, identity_proof.nonce)
+        }),
+    }
+}
+
+fn allowlist(
+    peer_ids: Vec<PeerId>,
+    staker_authentication: Option<StakerAuthentication>,
+) -> Allowlist {
+    let config = AllowlistConfig {
+        enabled: true,
+        peer_ids,
+        authentication_timeout_millis: AUTHENTICATION_TIMEOUT,
+    };
+    Allowlist::new(config, staker_authentication)
+}
+
+fn connect(allowlist: &mut Allowlist, peer_id: PeerId) -> Option<Handler> {
+    allowlist
+        .handle_established_inbound_connection(
+            ConnectionId::new_unchecked(0),
+            peer_id,
+            &Multiaddr::empty(),
+            &Multiaddr::empty(),
+        )
+        .ok()
+}
+
+// The peer answers the challenge of the connection with the proof of the given staker, if any.
+fn answer_challenge(
+    allowlist: &mut Allowlist,
+    peer_id: PeerId,
+    handler: &Handler,
+    staker: Option<PublicKey>,
+) {
+    let nonce = handler.challenge().expect("The peer should be challenged");
+    allowlist.on_connection_handler_event(
+        peer_id,
+        ConnectionId::new_unchecked(0),
+        staker.map(|staker| identity_proof(staker, nonce)),
+    );
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct AllowlistEvents {
+    disconnected: HashSet<PeerId>,
+    rejected: HashSet<PeerId>,
+    authentication_started: HashSet<PeerId>,
+    authentication_ended: HashSet<PeerId>,
+}
+
+fn events(allowlist: &mut Allowlist) -> AllowlistEvents {
+    let mut events = AllowlistEvents::default();
+    while let Some(Some(event)) = allowlist.next().now_or_never() {
+        match event {
+            ToSwarm::CloseConnection { peer_id, connection: CloseConnection::All } => {
+                events.disconnected.insert(peer_id);
+            }
+            ToSwarm::GenerateEvent(ToOtherBehaviourEvent::ConnectionRejected { peer_id }) => {
+                events.rejected.insert(peer_id);
+            }
+            ToSwarm::GenerateEvent(ToOtherBehaviourEvent::AuthenticationStarted { peer_id }) => {
+                events.authentication_started.insert(peer_id);
+            }
+            ToSwarm::GenerateEvent(ToOtherBehaviourEvent::AuthenticationEnded { peer_id }) => {
+                events.authentication_ended.insert(peer_id);
+            }
+            event => panic!("Unexpected event {event:?}"),
+        }
+    }
+    events
+}
+
+// Returns the peers which were disconnected, and the peers whose rejection was reported.
+fn rejected_peers(allowlist: &mut Allowlist) -> (HashSet<PeerId>, HashSet<PeerId>) {
+    let events = events(allowlist);
+    (events.disconnected, events.rejected)
+}
+
+#[test]
+fn disabled_allowlist_accepts_every_peer() {
+    let mut allowlist = Allowlist::new(AllowlistConfig::default(), None);
+    let handler = connect(&mut allowlist, PeerId::random()).unwrap();
+    assert_eq!(handler.challenge(), None);
+    assert_eq!(events(&mut allowlist), AllowlistEvents::default());
+}
+
+#[test]
+fn configured_peers_are_accepted() {
+    let allowed_peer = PeerId::random();
+    let other_peer = PeerId::random();
+    let mut allowlist = allowlist(vec![allowed_peer], None);
+
+    assert!(connect(&mut allowlist, allowed_peer).is_some());
+    // Without a way to verify identity proofs, other peers are denied right away.
+    assert!(connect(&mut allowlist, other_peer).is_none());
+    assert_eq!(rejected_peers(&mut allowlist), (HashSet::new(), HashSet::from([other_peer])));
+}
+
+#[tokio::test]
+async fn peers_run_by_stakers_are_accepted() {
+    let mut allowlist = allowlist(vec![], Some(staker_authentication()));
+    allowlist.set_allowed_stakers(vec![staker(1), staker(2)]);
+    let staker_peer = PeerId::random();
+    let other_peer = PeerId::random();
+    let unidentified_peer = PeerId::random();
+    let peers = HashSet::from([staker_peer, other_peer, unidentified_peer]);
+
+    // Every peer may connect until it answers its challenge.
+    for (peer_id, staker) in
+        [(staker_peer, Some(staker(2))), (other_peer, Some(staker(3))), (unidentified_peer, None)]
+    {
+        let handler = connect(&mut allowlist, peer_id).unwrap();
+        answer_challenge(&mut allowlist, peer_id, &handler, staker);
+    }
+
+    let rejected = HashSet::from([other_peer, unidentified_peer]);
+    assert_eq!(
+        events(&mut allowlist),
+        AllowlistEvents {
+            disconnected: rejected.clone(),
+            rejected,
+            authentication_started: peers.clone(),
+            authentication_ended: peers,
+        }
+    );
+    assert!(allowlist.is_allowed(&staker_peer));
+    assert_eq!(allowlist.peers_run_by(&staker(2)), vec![staker_peer]);
+    assert!(allowlist.peers_run_by(&staker(3)).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn peers_which_arent_identified_in_time_are_rejected() {
+    let mut allowlist = allowlist(vec![], Some(staker_authentication()));
+    let peer_id = PeerId::random();
+    assert!(connect(&mut allowlist, peer_id).is_some());
+    assert_eq!(
+        events(&mut allowlist),
+        AllowlistEvents { authentication_started: HashSet::from([peer_id]), ..Default::default() }
+    );
+
+    tokio::time::advance(AUTHENTICATION_TIMEOUT).await;
+    assert_eq!(
+        events(&mut allowlist),
+        AllowlistEvents {
+            disconnected: HashSet::from([peer_id]),
+            rejected: HashSet::from([peer_id]),
+            authentication_ended: HashSet::from([peer_id]),
+            ..Default::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn peers_of_removed_stakers_are_disconnected() {
+    let mut allowlist = allowlist(vec![], Some(staker_authentication()));
+    allowlist.set_allowed_stakers(vec![staker(1), staker(2)]);
+    let first_peer = PeerId::random();
+    let second_peer = PeerId::random();
+    for (peer_id, staker) in [(first_peer, staker(1)), (second_peer, staker(2))] {
+        let handler = connect(&mut allowlist, peer_id).unwrap();
+        answer_challenge(&mut allowlist, peer_id, &handler, Some(staker));
+    }
+    assert_eq!(rejected_peers(&mut allowlist), (HashSet::new(), HashSet::new()));
+
+    allowlist.set_allowed_stakers(vec![staker(2)]);
+    assert_eq!(
+        rejected_peers(&mut allowlist),
+        (HashSet::from([first_peer]), HashSet::from([first_peer]))
+    );
+    assert!(allowlist.is_allowed(&second_peer));
+}
+
+#[tokio::test]
+async fn proofs_of_other_challenges_are_rejected() {
+    let mut allowlist = allowlist(vec![], Some(staker_authentication()));
+    allowlist.set_allowed_stakers(vec![staker(1)]);
+    let peer_id = PeerId::random();
+    let first_handler = connect(&mut allowlist, peer_id).unwrap();
+    let second_handler = connect(&mut allowlist, peer_id).unwrap();
+    // Each connection is challenged with a new nonce.
+    assert_ne!(first_handler.challenge(), second_handler.challenge());
+
+    // A signature on the challenge of another connection doesn't authenticate the peer.
+    let replayed_signature =
+        identity_proof(staker(1), second_handler.challenge().unwrap()).signature;
+    allowlist.on_connection_handler_event(
+        peer_id,
+        ConnectionId::new_unchecked(0),
+        Some(IdentityProof {
+            nonce: first_handler.challenge().unwrap(),
+            signature: replayed_signature,
+        }),
+    );
+    assert_eq!(
+        rejected_peers(&mut allowlist),
+        (HashSet::from([peer_id]), HashSet::from([peer_id]))
+    );
+}
+
+#[tokio::test]
+async fn challenge_is_answered_with_identity_proof() {
+    let (inbound_stream, outbound_stream, _) = get_connected_streams().await;
+    let sign_identity: IdentitySigner =
+        Arc::new(|nonce| async move { Some(identity_proof(staker(1), nonce).signature) }.boxed());
+    let nonce = Nonce(StarkHash::from(7_u8));
+
+    let ((), identity_proof) = tokio::join!(
+        async move {
+            InboundProtocol { sign_identity: Some(sign_identity) }
+                .upgrade_inbound(inbound_stream, PROTOCOL_NAME)
+                .await
+                .unwrap()
+        },
+        async move {
+            OutboundProtocol { nonce }
+                .upgrade_outbound(outbound_stream, PROTOCOL_NAME)
+                .await
+                .unwrap()
+        },
+    );
+    assert_eq!(identity_proof, Some(self::identity_proof(staker(1), nonce)));
+}
+
+#[tokio::test]
+async fn nodes_not_run_by_stakers_answer_without_proof() {
+    let (inbound_stream, outbound_stream, _) = get_connected_streams().await;
+    let nonce = Nonce(StarkHash::from(7_u8));
+
+    let ((), identity_proof) = tokio::join!(
+        async move {
+            InboundProtocol { sign_identity: None }
+                .upgrade_inbound(inbound_stream, PROTOCOL_NAME)
+                .await
+                .unwrap()
+        },
+        async move {
+            OutboundProtocol { nonce }
+                .upgrade_outbound(outbound_stream, PROTOCOL_NAME)
+                .await
+                .unwrap()
+        },
+    );
+    assert_eq!(identity_proof, None);
+}
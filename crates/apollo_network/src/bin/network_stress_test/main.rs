@@ -95,7 +95,7 @@ async fn main() {
         network_config.bootstrap_peer_multiaddr = Some(vec![bootstrap_peer]);
     }
 
-    let network_manager = NetworkManager::new(network_config, None, None);
+    let network_manager = NetworkManager::new(network_config, None, None, None);
 
     let peer_id = network_manager.get_local_peer_id();
     log(&format!("My PeerId: {peer_id}"), &args, 1);
@@ -1,4 +1,4 @@
-use libp2p::{identify, Multiaddr, PeerId};
+use libp2p::{identify, Multiaddr, PeerId, StreamProtocol};
 
 use crate::mixed_behaviour;
 use crate::mixed_behaviour::BridgedBehaviour;
@@ -8,7 +8,12 @@ pub const IDENTIFY_PROTOCOL_VERSION: &str = "/staknet/identify/0.1.0-rc.0";
 
 #[derive(Debug)]
 pub enum IdentifyToOtherBehaviourEvent {
-    FoundListenAddresses { peer_id: PeerId, listen_addresses: Vec<Multiaddr> },
+    FoundListenAddresses {
+        peer_id: PeerId,
+        listen_addresses: Vec<Multiaddr>,
+        // The protocols the peer supports.
+        protocols: Vec<StreamProtocol>,
+    },
 }
 
 impl From<identify::Event> for mixed_behaviour::Event {
@@ -30,6 +35,7 @@ impl From<identify::Event> for mixed_behaviour::Event {
                         IdentifyToOtherBehaviourEvent::FoundListenAddresses {
                             peer_id,
                             listen_addresses,
+                            protocols: info.protocols,
                         },
                     ),
                 )
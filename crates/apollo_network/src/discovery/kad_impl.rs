@@ -23,7 +23,11 @@ impl<TStore: kad::store::RecordStore + Send + 'static> BridgedBehaviour for kad:
                 self.get_closest_peers(*peer_id);
             }
             mixed_behaviour::ToOtherBehaviourEvent::Identify(
-                IdentifyToOtherBehaviourEvent::FoundListenAddresses { peer_id, listen_addresses },
+                IdentifyToOtherBehaviourEvent::FoundListenAddresses {
+                    peer_id,
+                    listen_addresses,
+                    ..
+                },
             )
             | mixed_behaviour::ToOtherBehaviourEvent::Discovery(
                 super::ToOtherBehaviourEvent::FoundListenAddresses { peer_id, listen_addresses },
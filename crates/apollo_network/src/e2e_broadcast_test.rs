@@ -7,6 +7,7 @@ use libp2p::{Multiaddr, Swarm};
 use libp2p_swarm_test::SwarmExt;
 use starknet_api::core::ChainId;
 
+use crate::allowlist::AllowlistConfig;
 use crate::discovery::DiscoveryConfig;
 use crate::gossipsub_impl::Topic;
 use crate::mixed_behaviour::MixedBehaviour;
@@ -27,6 +28,8 @@ async fn create_swarm(bootstrap_peer_multiaddr: Option<Multiaddr>) -> Swarm<Mixe
             None,
             DiscoveryConfig::default(),
             PeerManagerConfig::default(),
+            AllowlistConfig::default(),
+            None,
         )
     });
     // Not using SwarmExt::listen because it panics if the swarm emits other events
@@ -2,9 +2,9 @@ use libp2p::gossipsub::TopicHash;
 use libp2p::{gossipsub, PeerId};
 use tracing::error;
 
-use crate::mixed_behaviour;
 use crate::mixed_behaviour::BridgedBehaviour;
 use crate::sqmr::Bytes;
+use crate::{allowlist, mixed_behaviour};
 
 #[cfg(test)]
 pub type Topic = gossipsub::IdentTopic;
@@ -49,5 +49,16 @@ impl From<gossipsub::Event> for mixed_behaviour::Event {
 }
 
 impl BridgedBehaviour for gossipsub::Behaviour {
-    fn on_other_behaviour_event(&mut self, _event: &mixed_behaviour::ToOtherBehaviourEvent) {}
+    fn on_other_behaviour_event(&mut self, event: &mixed_behaviour::ToOtherBehaviourEvent) {
+        // Messages from peers which weren't authenticated yet are rejected.
+        match event {
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationStarted { peer_id },
+            ) => self.blacklist_peer(peer_id),
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationEnded { peer_id },
+            ) => self.remove_blacklisted_peer(peer_id),
+            _ => {}
+        }
+    }
 }
@@ -1,3 +1,4 @@
+pub mod allowlist;
 #[cfg(test)]
 mod config_test;
 /// This crate is responsible for sending messages to a given peer and responding to them according
@@ -21,6 +22,7 @@ use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::time::Duration;
 
+use allowlist::AllowlistConfig;
 use apollo_config::converters::{
     deserialize_optional_vec_u8,
     deserialize_seconds_to_duration,
@@ -101,6 +103,7 @@ pub struct NetworkConfig {
     pub chain_id: ChainId,
     pub discovery_config: DiscoveryConfig,
     pub peer_manager_config: PeerManagerConfig,
+    pub allowlist_config: AllowlistConfig,
     pub broadcasted_message_metadata_buffer_size: usize,
     pub reported_peer_ids_buffer_size: usize,
 }
@@ -178,6 +181,7 @@ impl SerializeConfig for NetworkConfig {
             self.peer_manager_config.dump(),
             "peer_manager_config",
         ));
+        config.extend(prepend_sub_config_name(self.allowlist_config.dump(), "allowlist_config"));
         config
     }
 }
@@ -194,6 +198,7 @@ impl Default for NetworkConfig {
             chain_id: ChainId::Mainnet,
             discovery_config: DiscoveryConfig::default(),
             peer_manager_config: PeerManagerConfig::default(),
+            allowlist_config: AllowlistConfig::default(),
             broadcasted_message_metadata_buffer_size: 100000,
             reported_peer_ids_buffer_size: 100000,
         }
@@ -1,9 +1,13 @@
 use std::ops::AddAssign;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
 
 /// MisconductScore is in the range [0, 1].
 ///
-/// When a peer's total MisconductScore reaches 1, it is considered malicious.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// When a peer's total MisconductScore reaches 1, it is considered malicious. The score of a peer
+/// decays over time, so that peers which misbehave rarely are not considered malicious.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct MisconductScore {
     score: f64,
 }
@@ -21,6 +25,21 @@ impl MisconductScore {
     pub fn is_malicious(&self) -> bool {
         &Self::MALICIOUS <= self
     }
+
+    /// Returns the part of `timeout` proportional to the score, so that a malicious peer gets the
+    /// whole timeout.
+    pub fn scale_timeout(&self, timeout: Duration) -> Duration {
+        timeout.mul_f64(self.score)
+    }
+
+    /// Returns the score after `elapsed` time has passed, given that it halves every `half_life`.
+    pub fn decay(self, elapsed: Duration, half_life: Duration) -> Self {
+        if half_life.is_zero() {
+            return Self::NEUTRAL;
+        }
+        let half_lives = elapsed.as_secs_f64() / half_life.as_secs_f64();
+        Self { score: self.score * 0.5_f64.powf(half_lives) }
+    }
 }
 
 impl AddAssign for MisconductScore {
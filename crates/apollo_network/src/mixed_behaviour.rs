@@ -8,11 +8,12 @@ use libp2p::swarm::NetworkBehaviour;
 use libp2p::{gossipsub, identify, kad, Multiaddr, PeerId, StreamProtocol};
 use starknet_api::core::ChainId;
 
+use crate::allowlist::{Allowlist, AllowlistConfig, StakerAuthentication};
 use crate::discovery::identify_impl::{IdentifyToOtherBehaviourEvent, IDENTIFY_PROTOCOL_VERSION};
 use crate::discovery::kad_impl::KadToOtherBehaviourEvent;
 use crate::discovery::DiscoveryConfig;
 use crate::peer_manager::PeerManagerConfig;
-use crate::{discovery, gossipsub_impl, peer_manager, sqmr};
+use crate::{allowlist, discovery, gossipsub_impl, peer_manager, sqmr};
 
 const ONE_MEGA: usize = 1 << 20;
 
@@ -20,6 +21,8 @@ const ONE_MEGA: usize = 1 << 20;
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "Event")]
 pub struct MixedBehaviour {
+    // First, so that it denies connections before the other behaviours handle them.
+    pub allowlist: Allowlist,
     pub peer_manager: peer_manager::PeerManager,
     pub discovery: Toggle<discovery::Behaviour>,
     pub identify: identify::Behaviour,
@@ -44,6 +47,7 @@ pub enum ExternalEvent {
 #[derive(Debug)]
 pub enum ToOtherBehaviourEvent {
     NoOp,
+    Allowlist(allowlist::ToOtherBehaviourEvent),
     Identify(IdentifyToOtherBehaviourEvent),
     Kad(KadToOtherBehaviourEvent),
     Discovery(discovery::ToOtherBehaviourEvent),
@@ -58,6 +62,7 @@ pub trait BridgedBehaviour {
 impl MixedBehaviour {
     // TODO(Shahak): get config details from network manager config
     /// Panics if bootstrap_peer_multiaddr doesn't have a peer id.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         keypair: Keypair,
         // TODO(AndrewL): consider making this non optional
@@ -67,6 +72,8 @@ impl MixedBehaviour {
         node_version: Option<String>,
         discovery_config: DiscoveryConfig,
         peer_manager_config: PeerManagerConfig,
+        allowlist_config: AllowlistConfig,
+        staker_authentication: Option<StakerAuthentication>,
     ) -> Self {
         let public_key = keypair.public();
         let local_peer_id = PeerId::from_public_key(&public_key);
@@ -76,6 +83,7 @@ impl MixedBehaviour {
                 .expect("Failed to create StreamProtocol from a string that starts with /"),
         ]);
         Self {
+            allowlist: Allowlist::new(allowlist_config, staker_authentication),
             peer_manager: peer_manager::PeerManager::new(peer_manager_config),
             discovery: bootstrap_peers_multiaddrs
                 .map(|bootstrap_peer_multiaddr| {
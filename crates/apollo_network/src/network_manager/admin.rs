@@ -0,0 +1,123 @@
+//! An interface for operators to inspect and manage the peers of a network, which the monitoring
+//! endpoint serves.
+//!
+//! The admin client of a network may be created before its network manager, and the requests of
+//! the client are handled in the event loop of the network manager it's given to.
+
+use std::time::Duration;
+
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::channel::oneshot;
+use futures::SinkExt;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::misconduct_score::MisconductScore;
+
+const ADMIN_REQUESTS_BUFFER_SIZE: usize = 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NetworkAdminError {
+    #[error("The network manager is not running.")]
+    NetworkManagerStopped,
+    #[error("No such peer: {0}")]
+    NoSuchPeer(PeerId),
+    #[error("Failed to dial {multiaddr}: {error}")]
+    DialFailed { multiaddr: Multiaddr, error: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionDirection {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub remote_address: Multiaddr,
+    pub direction: ConnectionDirection,
+}
+
+/// A connected peer, as reported to operators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub peer_id: PeerId,
+    pub connections: Vec<ConnectionInfo>,
+    /// The protocols the peer reported it supports. Empty until the peer is identified.
+    pub protocols: Vec<String>,
+    /// The gossipsub topics the peer is subscribed to.
+    pub gossipsub_topics: Vec<String>,
+    /// None if the peer manager doesn't know the peer, which happens until it's identified.
+    pub misconduct_score: Option<MisconductScore>,
+    /// The time the peer is blocked until, in seconds since the Unix epoch. None if the peer isn't
+    /// blocked.
+    pub blocked_until: Option<u64>,
+}
+
+type Responder<T> = oneshot::Sender<Result<T, NetworkAdminError>>;
+
+pub(crate) enum NetworkAdminRequest {
+    GetPeers { responder: Responder<Vec<PeerInfo>> },
+    BanPeer { peer_id: PeerId, duration: Duration, responder: Responder<()> },
+    UnbanPeer { peer_id: PeerId, responder: Responder<()> },
+    DialPeer { multiaddr: Multiaddr, responder: Responder<()> },
+}
+
+#[derive(Clone)]
+pub struct NetworkAdminClient {
+    sender: Sender<NetworkAdminRequest>,
+}
+
+impl NetworkAdminClient {
+    fn new(sender: Sender<NetworkAdminRequest>) -> Self {
+        Self { sender }
+    }
+
+    /// Returns the peers the node is connected to.
+    pub async fn get_peers(&self) -> Result<Vec<PeerInfo>, NetworkAdminError> {
+        self.send_request(|responder| NetworkAdminRequest::GetPeers { responder }).await
+    }
+
+    /// Disconnects from the peer and blocks it for the given duration.
+    pub async fn ban_peer(
+        &self,
+        peer_id: PeerId,
+        duration: Duration,
+    ) -> Result<(), NetworkAdminError> {
+        self.send_request(|responder| NetworkAdminRequest::BanPeer { peer_id, duration, responder })
+            .await
+    }
+
+    /// Unblocks the peer and clears its misconduct score.
+    pub async fn unban_peer(&self, peer_id: PeerId) -> Result<(), NetworkAdminError> {
+        self.send_request(|responder| NetworkAdminRequest::UnbanPeer { peer_id, responder }).await
+    }
+
+    pub async fn dial_peer(&self, multiaddr: Multiaddr) -> Result<(), NetworkAdminError> {
+        self.send_request(|responder| NetworkAdminRequest::DialPeer { multiaddr, responder }).await
+    }
+
+    async fn send_request<T>(
+        &self,
+        request: impl FnOnce(Responder<T>) -> NetworkAdminRequest,
+    ) -> Result<T, NetworkAdminError> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .clone()
+            .send(request(responder))
+            .await
+            .map_err(|_| NetworkAdminError::NetworkManagerStopped)?;
+        response.await.map_err(|_| NetworkAdminError::NetworkManagerStopped)?
+    }
+}
+
+/// The requests of an admin client, which the network manager they're given to serves.
+pub struct NetworkAdminRequests(pub(crate) Receiver<NetworkAdminRequest>);
+
+/// Creates an admin client for a network whose network manager isn't created yet. The returned
+/// requests should be given to the network manager with
+/// [`NetworkManager::serve_admin_requests`](super::NetworkManager::serve_admin_requests).
+pub fn create_network_admin_channel() -> (NetworkAdminClient, NetworkAdminRequests) {
+    let (sender, receiver) = channel(ADMIN_REQUESTS_BUFFER_SIZE);
+    (NetworkAdminClient::new(sender), NetworkAdminRequests(receiver))
+}
@@ -34,6 +34,7 @@ impl SqmrNetworkMetrics {
 pub struct NetworkMetrics {
     pub num_connected_peers: MetricGauge,
     pub num_blacklisted_peers: MetricGauge,
+    pub num_rejected_connections: MetricCounter,
     pub broadcast_metrics_by_topic: Option<HashMap<TopicHash, BroadcastNetworkMetrics>>,
     pub sqmr_metrics: Option<SqmrNetworkMetrics>,
 }
@@ -44,6 +45,7 @@ impl NetworkMetrics {
         self.num_connected_peers.set(0f64);
         self.num_blacklisted_peers.register();
         self.num_blacklisted_peers.set(0f64);
+        self.num_rejected_connections.register();
         if let Some(broadcast_metrics_by_topic) = self.broadcast_metrics_by_topic.as_ref() {
             for broadcast_metrics in broadcast_metrics_by_topic.values() {
                 broadcast_metrics.register();
@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod metrics;
 mod swarm_trait;
 #[cfg(test)]
@@ -9,6 +10,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::pin::Pin;
 use std::str::FromStr;
 use std::task::{Context, Poll};
+use std::time::{Instant, SystemTime};
 
 use apollo_network_types::network_types::{BroadcastedMessageMetadata, OpaquePeerId};
 use async_trait::async_trait;
@@ -16,25 +18,45 @@ use futures::channel::mpsc::{Receiver, SendError, Sender};
 use futures::channel::oneshot;
 use futures::future::{ready, BoxFuture, Ready};
 use futures::sink::With;
-use futures::stream::{FuturesUnordered, Map, Stream};
+use futures::stream::{FuturesUnordered, Map, SelectAll, Stream};
 use futures::{pin_mut, FutureExt, Sink, SinkExt, StreamExt};
 use libp2p::gossipsub::{SubscriptionError, TopicHash};
 use libp2p::identity::Keypair;
-use libp2p::swarm::SwarmEvent;
+use libp2p::swarm::{ConnectionId, SwarmEvent};
 use libp2p::{Multiaddr, PeerId, StreamProtocol, Swarm, SwarmBuilder};
 use metrics::NetworkMetrics;
 use sqmr::Bytes;
+use starknet_api::crypto::utils::PublicKey;
 use tracing::{debug, error, trace, warn};
 
+use self::admin::{
+    create_network_admin_channel,
+    ConnectionDirection,
+    ConnectionInfo,
+    NetworkAdminClient,
+    NetworkAdminError,
+    NetworkAdminRequest,
+    NetworkAdminRequests,
+    PeerInfo,
+};
 use self::swarm_trait::SwarmTrait;
+use crate::allowlist::{self, AllowedStakersSender, ReportedStakersSender, StakerAuthentication};
+use crate::discovery::identify_impl::IdentifyToOtherBehaviourEvent;
 use crate::gossipsub_impl::Topic;
 use crate::misconduct_score::MisconductScore;
 use crate::mixed_behaviour::{self, BridgedBehaviour};
+use crate::peer_manager::peer_store::unix_seconds;
 use crate::sqmr::behaviour::SessionError;
 use crate::sqmr::{self, InboundSessionId, OutboundSessionId, SessionId};
 use crate::utils::{is_localhost, StreamMap};
 use crate::{gossipsub_impl, NetworkConfig};
 
+// The committee changes rarely, so there are few updates of the allowed stakers.
+const ALLOWED_STAKERS_BUFFER_SIZE: usize = 10;
+// Stakers are reported once per offense, which is rare.
+const REPORTED_STAKERS_BUFFER_SIZE: usize = 10;
+// Admin requests are sent manually by operators, so there are few of them.
+
 #[derive(thiserror::Error, Debug)]
 pub enum NetworkError {
     #[error(transparent)]
@@ -50,6 +72,8 @@ pub struct GenericNetworkManager<SwarmT: SwarmTrait> {
     sqmr_outbound_payload_receivers: StreamMap<String, SqmrClientReceiver>,
     sqmr_outbound_response_senders: HashMap<OutboundSessionId, ResponsesSender>,
     sqmr_outbound_report_receivers_awaiting_assignment: HashMap<OutboundSessionId, ReportReceiver>,
+    sqmr_outbound_assigned_peer_senders_awaiting_assignment:
+        HashMap<OutboundSessionId, AssignedPeerSender>,
     // Splitting the broadcast receivers from the broadcasted senders in order to poll all
     // receivers simultaneously.
     // Each receiver has a matching sender and vice versa (i.e the maps have the same keys).
@@ -61,6 +85,17 @@ pub struct GenericNetworkManager<SwarmT: SwarmTrait> {
     reported_peers_sender: Sender<PeerId>,
     continue_propagation_sender: Sender<BroadcastedMessageMetadata>,
     continue_propagation_receiver: Receiver<BroadcastedMessageMetadata>,
+    allowed_stakers_sender: AllowedStakersSender,
+    allowed_stakers_receiver: Receiver<Vec<PublicKey>>,
+    reported_stakers_sender: ReportedStakersSender,
+    reported_stakers_receiver: Receiver<PublicKey>,
+    admin_client: NetworkAdminClient,
+    // The requests of the client above and of the clients given with `serve_admin_requests`.
+    admin_requests_receivers: SelectAll<Receiver<NetworkAdminRequest>>,
+    // The open connections of each connected peer, reported to operators by the admin client.
+    connections: HashMap<PeerId, HashMap<ConnectionId, ConnectionInfo>>,
+    // The protocols each connected peer reported it supports in the identify protocol.
+    peer_protocols: HashMap<PeerId, Vec<StreamProtocol>>,
     metrics: Option<NetworkMetrics>,
 }
 
@@ -90,6 +125,9 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                 Some(broadcasted_message_metadata) = self.continue_propagation_receiver.next() => {
                     self.swarm.continue_propagation(broadcasted_message_metadata);
                 }
+                Some(stakers) = self.allowed_stakers_receiver.next() => self.swarm.set_allowed_stakers(stakers),
+                Some(staker) = self.reported_stakers_receiver.next() => self.swarm.report_staker_as_malicious(staker),
+                Some(request) = self.admin_requests_receivers.next() => self.handle_admin_request(request),
             }
         }
     }
@@ -112,6 +150,12 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             futures::channel::mpsc::channel(reported_peer_ids_buffer_size);
         let (continue_propagation_sender, continue_propagation_receiver) =
             futures::channel::mpsc::channel(broadcasted_message_metadata_buffer_size);
+        let (allowed_stakers_sender, allowed_stakers_receiver) =
+            futures::channel::mpsc::channel(ALLOWED_STAKERS_BUFFER_SIZE);
+        let (reported_stakers_sender, reported_stakers_receiver) =
+            futures::channel::mpsc::channel(REPORTED_STAKERS_BUFFER_SIZE);
+        let (admin_client, NetworkAdminRequests(admin_requests_receiver)) =
+            create_network_admin_channel();
         Self {
             swarm,
             inbound_protocol_to_buffer_size: HashMap::new(),
@@ -120,6 +164,7 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             sqmr_outbound_payload_receivers: StreamMap::new(BTreeMap::new()),
             sqmr_outbound_response_senders: HashMap::new(),
             sqmr_outbound_report_receivers_awaiting_assignment: HashMap::new(),
+            sqmr_outbound_assigned_peer_senders_awaiting_assignment: HashMap::new(),
             messages_to_broadcast_receivers: StreamMap::new(BTreeMap::new()),
             broadcasted_messages_senders: HashMap::new(),
             reported_peer_receivers,
@@ -128,6 +173,14 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             reported_peers_sender,
             continue_propagation_sender,
             continue_propagation_receiver,
+            allowed_stakers_sender,
+            allowed_stakers_receiver,
+            reported_stakers_sender,
+            reported_stakers_receiver,
+            admin_client,
+            admin_requests_receivers: SelectAll::from_iter([admin_requests_receiver]),
+            connections: HashMap::new(),
+            peer_protocols: HashMap::new(),
             metrics,
         }
     }
@@ -269,8 +322,26 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         event: SwarmEvent<mixed_behaviour::Event>,
     ) -> Result<(), NetworkError> {
         match event {
-            SwarmEvent::ConnectionEstablished { peer_id, num_established, .. } => {
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                endpoint,
+                num_established,
+                ..
+            } => {
                 debug!("Connected to peer id: {peer_id:?}");
+                let direction = if endpoint.is_dialer() {
+                    ConnectionDirection::Outbound
+                } else {
+                    ConnectionDirection::Inbound
+                };
+                self.connections.entry(peer_id).or_default().insert(
+                    connection_id,
+                    ConnectionInfo {
+                        remote_address: endpoint.get_remote_address().clone(),
+                        direction,
+                    },
+                );
                 if let Some(metrics) = self.metrics.as_ref() {
                     // We increment the count of connected peers only if this is the first
                     // connection with the peer.
@@ -281,6 +352,7 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             }
             SwarmEvent::ConnectionClosed {
                 peer_id,
+                connection_id,
                 cause,
                 num_established: num_remaining_connections,
                 ..
@@ -291,6 +363,13 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                     }
                     None => debug!("Connection to {peer_id:?} closed."),
                 }
+                if let Some(peer_connections) = self.connections.get_mut(&peer_id) {
+                    peer_connections.remove(&connection_id);
+                    if peer_connections.is_empty() {
+                        self.connections.remove(&peer_id);
+                        self.peer_protocols.remove(&peer_id);
+                    }
+                }
                 if let Some(metrics) = self.metrics.as_ref() {
                     // We decrement the count of connected peers only if there are no more
                     // connections with the peer.
@@ -368,10 +447,109 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         Ok(())
     }
 
+    /// Returns a sender for replacing the stakers whose nodes may connect when the allowlist is
+    /// enabled.
+    pub fn get_allowed_stakers_sender(&self) -> AllowedStakersSender {
+        self.allowed_stakers_sender.clone()
+    }
+
+    /// Returns a sender for reporting misbehaving stakers. Only the peers which proved they are run
+    /// by a staker when the allowlist is enabled can be attributed to it.
+    pub fn get_reported_stakers_sender(&self) -> ReportedStakersSender {
+        self.reported_stakers_sender.clone()
+    }
+
+    /// Returns a client for inspecting and managing the peers of the network.
+    pub fn get_admin_client(&self) -> NetworkAdminClient {
+        self.admin_client.clone()
+    }
+
+    /// Serves the requests of an admin client that was created before this network manager.
+    pub fn serve_admin_requests(&mut self, NetworkAdminRequests(receiver): NetworkAdminRequests) {
+        self.admin_requests_receivers.push(receiver);
+    }
+
+    fn handle_admin_request(&mut self, request: NetworkAdminRequest) {
+        // The requester may have stopped waiting for the response, in which case it's dropped.
+        match request {
+            NetworkAdminRequest::GetPeers { responder } => {
+                let _ = responder.send(Ok(self.get_peers_info()));
+            }
+            NetworkAdminRequest::BanPeer { peer_id, duration, responder } => {
+                warn!("Banning peer {peer_id:?} for {duration:?} by an admin request.");
+                let _ = responder.send(self.swarm.ban_peer(peer_id, duration));
+            }
+            NetworkAdminRequest::UnbanPeer { peer_id, responder } => {
+                warn!("Unbanning peer {peer_id:?} by an admin request.");
+                let _ = responder.send(self.swarm.unban_peer(peer_id));
+            }
+            NetworkAdminRequest::DialPeer { multiaddr, responder } => {
+                debug!("Dialing {multiaddr:?} by an admin request.");
+                let result = self.swarm.dial(multiaddr.clone()).map_err(|error| {
+                    NetworkAdminError::DialFailed { multiaddr, error: error.to_string() }
+                });
+                let _ = responder.send(result);
+            }
+        }
+    }
+
+    fn get_peers_info(&self) -> Vec<PeerInfo> {
+        self.connections
+            .iter()
+            .map(|(peer_id, peer_connections)| {
+                let reputation = self.swarm.peer_reputation(peer_id);
+                PeerInfo {
+                    peer_id: *peer_id,
+                    connections: peer_connections.values().cloned().collect(),
+                    protocols: self
+                        .peer_protocols
+                        .get(peer_id)
+                        .map(|protocols| protocols.iter().map(|p| p.to_string()).collect())
+                        .unwrap_or_default(),
+                    gossipsub_topics: self
+                        .swarm
+                        .peer_gossipsub_topics(peer_id)
+                        .iter()
+                        .map(|topic_hash| topic_hash.to_string())
+                        .collect(),
+                    misconduct_score: reputation
+                        .as_ref()
+                        .map(|reputation| reputation.misconduct_score),
+                    blocked_until: reputation.and_then(|reputation| reputation.blocked_until).map(
+                        |blocked_until| {
+                            unix_seconds(
+                                SystemTime::now()
+                                    + blocked_until.saturating_duration_since(Instant::now()),
+                            )
+                        },
+                    ),
+                }
+            })
+            .collect()
+    }
+
     // TODO(shahak): Move this logic to mixed_behaviour.
     fn handle_to_other_behaviour_event(&mut self, event: mixed_behaviour::ToOtherBehaviourEvent) {
-        if let mixed_behaviour::ToOtherBehaviourEvent::NoOp = event {
-            return;
+        match event {
+            mixed_behaviour::ToOtherBehaviourEvent::NoOp => return,
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::ConnectionRejected { peer_id },
+            ) => {
+                debug!("Rejected a connection with peer {peer_id:?} which isn't in the allowlist.");
+                if let Some(metrics) = self.metrics.as_ref() {
+                    metrics.num_rejected_connections.increment(1);
+                }
+            }
+            mixed_behaviour::ToOtherBehaviourEvent::Identify(
+                IdentifyToOtherBehaviourEvent::FoundListenAddresses {
+                    peer_id, ref protocols, ..
+                },
+            ) => {
+                if self.connections.contains_key(&peer_id) {
+                    self.peer_protocols.insert(peer_id, protocols.clone());
+                }
+            }
+            _ => {}
         }
         self.swarm.behaviour_mut().identify.on_other_behaviour_event(&event);
         self.swarm.behaviour_mut().kademlia.on_other_behaviour_event(&event);
@@ -476,6 +654,13 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         {
             self.handle_new_report_receiver(peer_id, report_receiver)
         }
+        if let Some(assigned_peer_sender) = self
+            .sqmr_outbound_assigned_peer_senders_awaiting_assignment
+            .remove(&outbound_session_id)
+        {
+            // The client may have dropped the query.
+            let _ = assigned_peer_sender.send(peer_id);
+        }
         if let Some(response_sender) =
             self.sqmr_outbound_response_senders.get_mut(&outbound_session_id)
         {
@@ -507,6 +692,8 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                      Ignoring incoming reports for the session."
                 );
             }
+            self.sqmr_outbound_assigned_peer_senders_awaiting_assignment
+                .remove(&outbound_session_id);
         }
     }
 
@@ -523,6 +710,8 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                      Ignoring incoming reports for the session."
                 );
             }
+            self.sqmr_outbound_assigned_peer_senders_awaiting_assignment
+                .remove(&outbound_session_id);
         }
     }
 
@@ -603,8 +792,16 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         protocol: StreamProtocol,
         client_payload: SqmrClientPayload,
     ) {
-        let SqmrClientPayload { query, report_receiver, responses_sender } = client_payload;
-        let outbound_session_id = self.swarm.send_query(query, protocol.clone());
+        let SqmrClientPayload {
+            query,
+            excluded_peers,
+            report_receiver,
+            assigned_peer_sender,
+            responses_sender,
+        } = client_payload;
+        let excluded_peers =
+            excluded_peers.iter().map(|peer_id| peer_id.private_get_peer_id()).collect();
+        let outbound_session_id = self.swarm.send_query(query, protocol.clone(), excluded_peers);
         if let Some(sqmr_metrics) =
             self.metrics.as_ref().and_then(|metrics| metrics.sqmr_metrics.as_ref())
         {
@@ -613,6 +810,8 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         self.sqmr_outbound_response_senders.insert(outbound_session_id, responses_sender);
         self.sqmr_outbound_report_receivers_awaiting_assignment
             .insert(outbound_session_id, report_receiver);
+        self.sqmr_outbound_assigned_peer_senders_awaiting_assignment
+            .insert(outbound_session_id, assigned_peer_sender);
     }
 
     fn broadcast_message(&mut self, message: Bytes, topic_hash: TopicHash) {
@@ -687,6 +886,7 @@ impl NetworkManager {
         config: NetworkConfig,
         node_version: Option<String>,
         metrics: Option<NetworkMetrics>,
+        staker_authentication: Option<StakerAuthentication>,
     ) -> Self {
         let NetworkConfig {
             port,
@@ -698,6 +898,7 @@ impl NetworkManager {
             chain_id,
             discovery_config,
             peer_manager_config,
+            allowlist_config,
             broadcasted_message_metadata_buffer_size,
             reported_peer_ids_buffer_size,
         } = config;
@@ -727,6 +928,8 @@ impl NetworkManager {
                     node_version,
                     discovery_config,
                     peer_manager_config,
+                    allowlist_config,
+                    staker_authentication,
                 )
             })
             .expect("Error while building the swarm")
@@ -758,6 +961,8 @@ impl NetworkManager {
 
 pub type ReportSender = oneshot::Sender<()>;
 type ReportReceiver = oneshot::Receiver<()>;
+type AssignedPeerSender = oneshot::Sender<PeerId>;
+type AssignedPeerReceiver = oneshot::Receiver<PeerId>;
 
 type GenericSender<T> = Box<dyn Sink<T, Error = SendError> + Unpin + Send>;
 // Box<S> implements Stream only if S: Stream + Unpin
@@ -817,32 +1022,53 @@ where
     pub async fn send_new_query(
         &mut self,
         query: Query,
+    ) -> Result<ClientResponsesManager<Response>, SendError> {
+        self.send_new_query_excluding_peers(query, Vec::new()).await
+    }
+
+    /// Sends a query that isn't assigned to any of the excluded peers, unless no other peer is
+    /// available.
+    pub async fn send_new_query_excluding_peers(
+        &mut self,
+        query: Query,
+        excluded_peers: Vec<OpaquePeerId>,
     ) -> Result<ClientResponsesManager<Response>, SendError> {
         let (report_sender, report_receiver) = oneshot::channel::<()>();
+        let (assigned_peer_sender, assigned_peer_receiver) = oneshot::channel::<PeerId>();
         let (responses_sender, responses_receiver) =
             futures::channel::mpsc::channel(self.buffer_size);
         let responses_receiver = Box::new(responses_receiver);
         let query = Bytes::from(query);
         let responses_sender =
             Box::new(responses_sender.with(|response| ready(Ok(Response::try_from(response)))));
-        let payload = SqmrClientPayload { query, report_receiver, responses_sender };
+        let payload = SqmrClientPayload {
+            query,
+            excluded_peers,
+            report_receiver,
+            assigned_peer_sender,
+            responses_sender,
+        };
         self.sender.send(payload).await?;
-        Ok(ClientResponsesManager { report_sender, responses_receiver })
+        Ok(ClientResponsesManager { report_sender, assigned_peer_receiver, responses_receiver })
     }
 }
 
 pub struct ClientResponsesManager<Response: TryFrom<Bytes>> {
     report_sender: ReportSender,
+    // Receives the peer assigned to the query once it sends its first response.
+    assigned_peer_receiver: AssignedPeerReceiver,
     pub(crate) responses_receiver: ClientResponsesReceiver<Response>,
 }
 
 impl<Response: TryFrom<Bytes>> ClientResponsesManager<Response> {
-    /// Use this function to report peer as malicious
-    pub fn report_peer(self) {
+    /// Use this function to report peer as malicious. Returns the reported peer, or None if the
+    /// peer didn't send any response.
+    pub fn report_peer(mut self) -> Option<OpaquePeerId> {
         warn!("Reporting peer");
         if let Err(e) = self.report_sender.send(()) {
             error!("Failed to report peer. Error: {e:?}");
         }
+        self.assigned_peer_receiver.try_recv().ok().flatten().map(OpaquePeerId::private_new)
     }
 }
 
@@ -858,7 +1084,9 @@ type SqmrClientReceiver = GenericReceiver<SqmrClientPayload>;
 
 pub struct SqmrClientPayload {
     query: Bytes,
+    excluded_peers: Vec<OpaquePeerId>,
     report_receiver: ReportReceiver,
+    assigned_peer_sender: AssignedPeerSender,
     responses_sender: ResponsesSender,
 }
 
@@ -1,15 +1,19 @@
+use std::time::Duration;
+
 use futures::stream::Stream;
 use libp2p::gossipsub::{SubscriptionError, TopicHash};
 use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::{DialError, NetworkBehaviour, SwarmEvent};
 use libp2p::{Multiaddr, PeerId, StreamProtocol, Swarm};
+use starknet_api::crypto::utils::PublicKey;
 use tracing::{info, warn};
 
+use super::admin::NetworkAdminError;
 use super::BroadcastedMessageMetadata;
 use crate::gossipsub_impl::Topic;
 use crate::misconduct_score::MisconductScore;
 use crate::mixed_behaviour;
-use crate::peer_manager::ReputationModifier;
+use crate::peer_manager::{PeerReputation, ReputationModifier};
 use crate::sqmr::behaviour::SessionIdNotFoundError;
 use crate::sqmr::{Bytes, InboundSessionId, OutboundSessionId, SessionId};
 
@@ -22,7 +26,12 @@ pub trait SwarmTrait: Stream<Item = Event> + Unpin {
         inbound_session_id: InboundSessionId,
     ) -> Result<(), SessionIdNotFoundError>;
 
-    fn send_query(&mut self, query: Vec<u8>, protocol: StreamProtocol) -> OutboundSessionId;
+    fn send_query(
+        &mut self,
+        query: Vec<u8>,
+        protocol: StreamProtocol,
+        excluded_peers: Vec<PeerId>,
+    ) -> OutboundSessionId;
 
     fn dial(&mut self, peer_multiaddr: Multiaddr) -> Result<(), DialError>;
 
@@ -49,6 +58,18 @@ pub trait SwarmTrait: Stream<Item = Event> + Unpin {
     fn add_new_supported_inbound_protocol(&mut self, protocol_name: StreamProtocol);
 
     fn continue_propagation(&mut self, message_metadata: BroadcastedMessageMetadata);
+
+    fn set_allowed_stakers(&mut self, stakers: Vec<PublicKey>);
+
+    fn report_staker_as_malicious(&mut self, staker: PublicKey);
+
+    fn peer_reputation(&self, peer_id: &PeerId) -> Option<PeerReputation>;
+
+    fn peer_gossipsub_topics(&self, peer_id: &PeerId) -> Vec<TopicHash>;
+
+    fn ban_peer(&mut self, peer_id: PeerId, duration: Duration) -> Result<(), NetworkAdminError>;
+
+    fn unban_peer(&mut self, peer_id: PeerId) -> Result<(), NetworkAdminError>;
 }
 
 impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
@@ -60,8 +81,13 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
         self.behaviour_mut().sqmr.send_response(response, inbound_session_id)
     }
 
-    fn send_query(&mut self, query: Vec<u8>, protocol: StreamProtocol) -> OutboundSessionId {
-        self.behaviour_mut().sqmr.start_query(query, protocol)
+    fn send_query(
+        &mut self,
+        query: Vec<u8>,
+        protocol: StreamProtocol,
+        excluded_peers: Vec<PeerId>,
+    ) -> OutboundSessionId {
+        self.behaviour_mut().sqmr.start_query(query, protocol, excluded_peers)
     }
 
     fn dial(&mut self, peer_multiaddr: Multiaddr) -> Result<(), DialError> {
@@ -123,4 +149,40 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
 
     // TODO(shahak): Implement this function.
     fn continue_propagation(&mut self, _message_metadata: BroadcastedMessageMetadata) {}
+
+    fn set_allowed_stakers(&mut self, stakers: Vec<PublicKey>) {
+        self.behaviour_mut().allowlist.set_allowed_stakers(stakers);
+    }
+
+    fn report_staker_as_malicious(&mut self, staker: PublicKey) {
+        for peer_id in self.behaviour().allowlist.peers_run_by(&staker) {
+            self.report_peer_as_malicious(peer_id, MisconductScore::MALICIOUS);
+        }
+    }
+    fn peer_reputation(&self, peer_id: &PeerId) -> Option<PeerReputation> {
+        self.behaviour().peer_manager.peer_reputation(peer_id)
+    }
+
+    fn peer_gossipsub_topics(&self, peer_id: &PeerId) -> Vec<TopicHash> {
+        self.behaviour()
+            .gossipsub
+            .all_peers()
+            .find(|(gossipsub_peer_id, _)| *gossipsub_peer_id == peer_id)
+            .map(|(_, topics)| topics.into_iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn ban_peer(&mut self, peer_id: PeerId, duration: Duration) -> Result<(), NetworkAdminError> {
+        self.behaviour_mut()
+            .peer_manager
+            .ban_peer(peer_id, duration)
+            .map_err(|_| NetworkAdminError::NoSuchPeer(peer_id))
+    }
+
+    fn unban_peer(&mut self, peer_id: PeerId) -> Result<(), NetworkAdminError> {
+        self.behaviour_mut()
+            .peer_manager
+            .unban_peer(peer_id)
+            .map_err(|_| NetworkAdminError::NoSuchPeer(peer_id))
+    }
 }
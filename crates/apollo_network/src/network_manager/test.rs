@@ -16,16 +16,25 @@ use libp2p::core::ConnectedPoint;
 use libp2p::gossipsub::{SubscriptionError, TopicHash};
 use libp2p::swarm::ConnectionId;
 use libp2p::{Multiaddr, PeerId, StreamProtocol};
+use starknet_api::crypto::utils::PublicKey;
 use tokio::select;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 
+use super::admin::{
+    create_network_admin_channel,
+    ConnectionDirection,
+    ConnectionInfo,
+    NetworkAdminError,
+    PeerInfo,
+};
 use super::swarm_trait::{Event, SwarmTrait};
 use super::{BroadcastTopicChannels, GenericNetworkManager};
 use crate::gossipsub_impl::{self, Topic};
 use crate::misconduct_score::MisconductScore;
 use crate::mixed_behaviour;
 use crate::network_manager::{BroadcastTopicClientTrait, ServerQueryManager};
+use crate::peer_manager::PeerReputation;
 use crate::sqmr::behaviour::SessionIdNotFoundError;
 use crate::sqmr::{Bytes, GenericEvent, InboundSessionId, OutboundSessionId};
 
@@ -47,6 +56,9 @@ struct MockSwarm {
     inbound_session_id_to_response_sender: HashMap<InboundSessionId, UnboundedSender<Bytes>>,
     next_outbound_session_id: usize,
     first_polled_event_notifier: Option<oneshot::Sender<()>>,
+    // The peers known to the peer manager, which may be banned.
+    known_peers: HashSet<PeerId>,
+    peer_gossipsub_topics: HashMap<PeerId, Vec<TopicHash>>,
 }
 
 impl Stream for MockSwarm {
@@ -134,7 +146,12 @@ impl SwarmTrait for MockSwarm {
         Ok(())
     }
 
-    fn send_query(&mut self, query: Vec<u8>, _protocol: StreamProtocol) -> OutboundSessionId {
+    fn send_query(
+        &mut self,
+        query: Vec<u8>,
+        _protocol: StreamProtocol,
+        _excluded_peers: Vec<PeerId>,
+    ) -> OutboundSessionId {
         let outbound_session_id = OutboundSessionId { value: self.next_outbound_session_id };
         self.create_response_events_for_query_each_num_becomes_response(
             query,
@@ -201,6 +218,36 @@ impl SwarmTrait for MockSwarm {
     fn continue_propagation(&mut self, _message_metadata: super::BroadcastedMessageMetadata) {
         unimplemented!()
     }
+
+    fn set_allowed_stakers(&mut self, _stakers: Vec<PublicKey>) {
+        unimplemented!()
+    }
+
+    fn report_staker_as_malicious(&mut self, _staker: PublicKey) {
+        unimplemented!()
+    }
+
+    fn peer_reputation(&self, peer_id: &PeerId) -> Option<PeerReputation> {
+        self.known_peers.get(peer_id).map(|_| PeerReputation {
+            misconduct_score: MisconductScore::NEUTRAL,
+            blocked_until: None,
+        })
+    }
+
+    fn peer_gossipsub_topics(&self, peer_id: &PeerId) -> Vec<TopicHash> {
+        self.peer_gossipsub_topics.get(peer_id).cloned().unwrap_or_default()
+    }
+
+    fn ban_peer(&mut self, peer_id: PeerId, _duration: Duration) -> Result<(), NetworkAdminError> {
+        match self.known_peers.contains(&peer_id) {
+            true => Ok(()),
+            false => Err(NetworkAdminError::NoSuchPeer(peer_id)),
+        }
+    }
+
+    fn unban_peer(&mut self, peer_id: PeerId) -> Result<(), NetworkAdminError> {
+        self.ban_peer(peer_id, Duration::ZERO)
+    }
 }
 
 const BUFFER_SIZE: usize = 100;
@@ -399,6 +446,75 @@ async fn receive_broadcasted_message_and_report_it() {
     }
 }
 
+#[tokio::test]
+async fn admin_client_inspects_and_bans_peers() {
+    let topic = Topic::new("TOPIC");
+    let known_peer_id = PeerId::random();
+    let unknown_peer_id = PeerId::random();
+
+    let mut mock_swarm = MockSwarm::default();
+    mock_swarm.known_peers.insert(known_peer_id);
+    mock_swarm.peer_gossipsub_topics.insert(known_peer_id, vec![topic.hash()]);
+    mock_swarm.pending_events.push(get_test_connection_established_event(known_peer_id));
+    mock_swarm.pending_events.push(get_test_connection_established_event(unknown_peer_id));
+
+    // The client is created before the network manager, as the node does.
+    let (admin_client, admin_requests) = create_network_admin_channel();
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        None,
+        None,
+        MESSAGE_METADATA_BUFFER_SIZE,
+        MESSAGE_METADATA_BUFFER_SIZE,
+    );
+    network_manager.serve_admin_requests(admin_requests);
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        _ = tokio::time::timeout(TIMEOUT, async {
+            // Wait for the network manager to handle the connections.
+            let mut peers = admin_client.get_peers().await.unwrap();
+            while peers.len() < 2 {
+                sleep(Duration::from_millis(10)).await;
+                peers = admin_client.get_peers().await.unwrap();
+            }
+            let connections = vec![ConnectionInfo {
+                remote_address: Multiaddr::empty(),
+                direction: ConnectionDirection::Outbound,
+            }];
+            assert_eq!(
+                peers.into_iter().map(|peer| (peer.peer_id, peer)).collect::<HashMap<_, _>>(),
+                HashMap::from([
+                    (known_peer_id, PeerInfo {
+                        peer_id: known_peer_id,
+                        connections: connections.clone(),
+                        protocols: vec![],
+                        gossipsub_topics: vec![topic.hash().to_string()],
+                        misconduct_score: Some(MisconductScore::NEUTRAL),
+                        blocked_until: None,
+                    }),
+                    (unknown_peer_id, PeerInfo {
+                        peer_id: unknown_peer_id,
+                        connections,
+                        protocols: vec![],
+                        gossipsub_topics: vec![],
+                        misconduct_score: None,
+                        blocked_until: None,
+                    }),
+                ])
+            );
+
+            admin_client.ban_peer(known_peer_id, Duration::from_secs(60)).await.unwrap();
+            admin_client.unban_peer(known_peer_id).await.unwrap();
+            assert!(matches!(
+                admin_client.ban_peer(unknown_peer_id, Duration::from_secs(60)).await,
+                Err(NetworkAdminError::NoSuchPeer(peer_id)) if peer_id == unknown_peer_id
+            ));
+            admin_client.dial_peer(Multiaddr::empty()).await.unwrap();
+        }) => {}
+    }
+}
+
 fn get_test_connection_established_event(mock_peer_id: PeerId) -> Event {
     Event::ConnectionEstablished {
         peer_id: mock_peer_id,
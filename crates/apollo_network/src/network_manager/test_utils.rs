@@ -1,6 +1,7 @@
 use core::net::Ipv4Addr;
 use std::time::Duration;
 
+use apollo_network_types::network_types::OpaquePeerId;
 use futures::channel::mpsc::{Receiver, SendError, Sender};
 use futures::channel::oneshot;
 use futures::future::{ready, Ready};
@@ -12,6 +13,7 @@ use libp2p::identity::Keypair;
 use libp2p::PeerId;
 
 use super::{
+    AssignedPeerSender,
     BroadcastReceivedMessagesConverterFn,
     BroadcastTopicChannels,
     BroadcastTopicClient,
@@ -201,7 +203,7 @@ where
 {
     const BUFFER_SIZE: usize = 1000;
 
-    let mut network_manager = NetworkManager::new(network_config, None, None);
+    let mut network_manager = NetworkManager::new(network_config, None, None, None);
     let broadcast_channels =
         network_manager.register_broadcast_topic(topic.clone(), BUFFER_SIZE).unwrap();
 
@@ -222,7 +224,9 @@ where
 
 pub struct MockClientResponsesManager<Query: TryFrom<Bytes>, Response: TryFrom<Bytes>> {
     query: Result<Query, <Query as TryFrom<Bytes>>::Error>,
+    excluded_peers: Vec<OpaquePeerId>,
     report_receiver: ReportReceiver,
+    assigned_peer_sender: Option<AssignedPeerSender>,
     responses_sender: ServerResponsesSender<Response>,
 }
 
@@ -231,6 +235,18 @@ impl<Query: TryFrom<Bytes>, Response: TryFrom<Bytes>> MockClientResponsesManager
         &self.query
     }
 
+    pub fn excluded_peers(&self) -> &[OpaquePeerId] {
+        &self.excluded_peers
+    }
+
+    /// Sets the peer that is reported as the one the query was assigned to. Must be called before
+    /// sending responses.
+    pub fn assign_peer(&mut self, peer_id: OpaquePeerId) {
+        let assigned_peer_sender =
+            self.assigned_peer_sender.take().expect("A peer was already assigned to the query.");
+        let _ = assigned_peer_sender.send(peer_id.private_get_peer_id());
+    }
+
     pub async fn assert_reported(self, timeout: Duration) {
         tokio::time::timeout(timeout, self.report_receiver).await.unwrap().unwrap();
     }
@@ -248,13 +264,21 @@ where
     Bytes: From<Response>,
 {
     fn from(payload: SqmrClientPayload) -> Self {
-        let SqmrClientPayload { query, report_receiver, responses_sender } = payload;
+        let SqmrClientPayload {
+            query,
+            excluded_peers,
+            report_receiver,
+            assigned_peer_sender,
+            responses_sender,
+        } = payload;
         let query = Query::try_from(query);
         let responses_sender =
             Box::new(responses_sender.with(|response: Response| ready(Ok(Bytes::from(response)))));
         Self {
             query,
+            excluded_peers,
             report_receiver,
+            assigned_peer_sender: Some(assigned_peer_sender),
             responses_sender: ServerResponsesSender { sender: responses_sender },
         }
     }
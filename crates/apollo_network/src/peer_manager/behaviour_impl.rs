@@ -1,4 +1,5 @@
 use std::task::{ready, Poll};
+use std::time::SystemTime;
 
 use libp2p::swarm::behaviour::ConnectionEstablished;
 use libp2p::swarm::{
@@ -131,16 +132,18 @@ impl NetworkBehaviour for PeerManager {
                             connection_id,
                         })
                     }));
-                    self.peers
-                        .get_mut(&peer_id)
-                        .expect(
-                            "in case we are waiting for a connection established event we assum \
-                             the peer is known to the peer manager",
-                        )
-                        .add_connection_id(connection_id);
+                    let peer = self.peers.get_mut(&peer_id).expect(
+                        "in case we are waiting for a connection established event we assum the \
+                         peer is known to the peer manager",
+                    );
+                    peer.add_connection_id(connection_id);
+                    peer.set_last_seen(SystemTime::now());
+                    self.store_peers();
                 } else {
                     if let Some(peer) = self.peers.get_mut(&peer_id) {
                         peer.add_connection_id(connection_id);
+                        peer.set_last_seen(SystemTime::now());
+                        self.store_peers();
                         return;
                     };
                     match self.connections_for_unknown_peers.get_mut(&peer_id) {
@@ -173,6 +176,10 @@ impl NetworkBehaviour for PeerManager {
                         connection_id, known_connection_ids
                     );
                 }
+                if let Some(peer) = self.peers.get_mut(&peer_id) {
+                    peer.set_last_seen(SystemTime::now());
+                    self.store_peers();
+                }
             }
             _ => {}
         }
@@ -186,6 +193,7 @@ impl NetworkBehaviour for PeerManager {
         if let Some(event) = self.pending_events.pop() {
             return Poll::Ready(event);
         }
+        self.poll_peer_store_write(cx);
         if let Some(sleep_future) = &mut self.sleep_waiting_for_unblocked_peer {
             ready!(sleep_future.as_mut().poll(cx));
             for outbound_session_id in std::mem::take(&mut self.sessions_received_when_no_peers) {
@@ -1,30 +1,34 @@
-use std::collections::{BTreeMap, HashMap};
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use apollo_config::converters::{
     deserialize_milliseconds_to_duration,
     deserialize_seconds_to_duration,
 };
-use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::dumping::{ser_optional_param, ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use libp2p::swarm::dial_opts::DialOpts;
-use libp2p::swarm::{ConnectionId, ToSwarm};
+use libp2p::swarm::{CloseConnection, ConnectionId, ToSwarm};
 use libp2p::PeerId;
 use peer::Peer;
+use peer_store::{unix_seconds, PeerStore, StoredPeer, PEER_STORE_WRITE_DELAY};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
 pub use self::behaviour_impl::ToOtherBehaviourEvent;
 use crate::discovery::identify_impl::IdentifyToOtherBehaviourEvent;
 use crate::misconduct_score::MisconductScore;
 use crate::mixed_behaviour::BridgedBehaviour;
 use crate::sqmr::OutboundSessionId;
-use crate::{discovery, mixed_behaviour, sqmr};
+use crate::{allowlist, discovery, mixed_behaviour, sqmr};
 
 pub(crate) mod behaviour_impl;
 pub(crate) mod peer;
+pub(crate) mod peer_store;
 #[cfg(test)]
 mod test;
 
@@ -39,6 +43,9 @@ pub struct PeerManager {
     peers: HashMap<PeerId, Peer>,
     // TODO(Shahak): consider implementing a cleanup mechanism to not store all queries forever
     session_to_peer_map: HashMap<OutboundSessionId, PeerId>,
+    // Peers that shouldn't be assigned to the session, e.g. since they failed an earlier query for
+    // the same data.
+    session_to_excluded_peers: HashMap<OutboundSessionId, Vec<PeerId>>,
     config: PeerManagerConfig,
     last_peer_index: usize,
     // TODO(shahak): Change to VecDeque and awake when item is added.
@@ -48,14 +55,31 @@ pub struct PeerManager {
     sleep_waiting_for_unblocked_peer: Option<BoxFuture<'static, ()>>,
     // A peer is known only after we get the identify message.
     connections_for_unknown_peers: HashMap<PeerId, Vec<ConnectionId>>,
+    // Peers which aren't assigned to sessions until the allowlist authenticates them.
+    unauthenticated_peers: HashSet<PeerId>,
+    peer_store: Option<PeerStore>,
+    // Whether the peers changed since the peer store was last written.
+    peer_store_outdated: bool,
+    // Started once the peers change, and the peer store is written when it's done.
+    peer_store_write_delay: Option<BoxFuture<'static, ()>>,
+    peer_store_write_task: Option<JoinHandle<()>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct PeerManagerConfig {
     #[serde(deserialize_with = "deserialize_seconds_to_duration")]
-    malicious_timeout_seconds: Duration,
+    misconduct_timeout_seconds: Duration,
     #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
     unstable_timeout_millis: Duration,
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    reputation_half_life_seconds: Duration,
+    peer_store_path: Option<PathBuf>,
+}
+
+pub struct PeerReputation {
+    pub misconduct_score: MisconductScore,
+    // None if the peer isn't blocked.
+    pub blocked_until: Option<Instant>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -72,19 +96,23 @@ impl Default for PeerManagerConfig {
     fn default() -> Self {
         Self {
             // TODO(shahak): Increase this once we're in a non-trusted setup.
-            malicious_timeout_seconds: Duration::from_secs(1),
+            misconduct_timeout_seconds: Duration::from_secs(1),
             unstable_timeout_millis: Duration::from_millis(1000),
+            reputation_half_life_seconds: Duration::from_secs(3600),
+            peer_store_path: None,
         }
     }
 }
 
 impl SerializeConfig for PeerManagerConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
-        BTreeMap::from([
+        let mut config = BTreeMap::from([
             ser_param(
-                "malicious_timeout_seconds",
-                &self.malicious_timeout_seconds.as_secs(),
-                "The duration in seconds a peer is blacklisted after being marked as malicious.",
+                "misconduct_timeout_seconds",
+                &self.misconduct_timeout_seconds.as_secs(),
+                "The duration in seconds a peer is blacklisted after being reported, if its \
+                 misconduct score reaches the malicious score. Peers with a lower score are \
+                 blacklisted for a proportionally shorter duration.",
                 ParamPrivacyInput::Public,
             ),
             ser_param(
@@ -93,7 +121,22 @@ impl SerializeConfig for PeerManagerConfig {
                 "The duration in milliseconds a peer blacklisted after being reported as unstable.",
                 ParamPrivacyInput::Public,
             ),
-        ])
+            ser_param(
+                "reputation_half_life_seconds",
+                &self.reputation_half_life_seconds.as_secs(),
+                "The duration in seconds after which the misconduct score of a peer is halved.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+        config.extend(ser_optional_param(
+            &self.peer_store_path,
+            PathBuf::from("./data/peer_store.json"),
+            "peer_store_path",
+            "The file the known peers and their reputation are persisted in, to reconnect to them \
+             after a restart. Must be different for each network of the node.",
+            ParamPrivacyInput::Public,
+        ));
+        config
     }
 }
 
@@ -101,9 +144,11 @@ impl SerializeConfig for PeerManagerConfig {
 impl PeerManager {
     pub(crate) fn new(config: PeerManagerConfig) -> Self {
         let peers = HashMap::new();
-        Self {
+        let peer_store = config.peer_store_path.clone().map(PeerStore::new);
+        let mut peer_manager = Self {
             peers,
             session_to_peer_map: HashMap::new(),
+            session_to_excluded_peers: HashMap::new(),
             config,
             last_peer_index: 0,
             pending_events: Vec::new(),
@@ -111,12 +156,108 @@ impl PeerManager {
             sessions_received_when_no_peers: Vec::new(),
             sleep_waiting_for_unblocked_peer: None,
             connections_for_unknown_peers: HashMap::default(),
+            unauthenticated_peers: HashSet::new(),
+            peer_store,
+            peer_store_outdated: false,
+            peer_store_write_delay: None,
+            peer_store_write_task: None,
+        };
+        peer_manager.load_stored_peers();
+        peer_manager
+    }
+
+    // Adds the peers from the peer store, and dials them.
+    fn load_stored_peers(&mut self) {
+        let Some(peer_store) = &self.peer_store else {
+            return;
+        };
+        let (stored_peers, age) = match peer_store.load() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warn!("Failed to load the peer store: {e}");
+                return;
+            }
+        };
+        info!("Loaded {} peers from the peer store.", stored_peers.len());
+        let now = unix_seconds(SystemTime::now());
+        for StoredPeer { peer_id, multiaddr, last_seen, misconduct_score, blocked_until } in
+            stored_peers
+        {
+            let mut peer = Peer::new(peer_id, multiaddr.clone());
+            peer.set_last_seen(UNIX_EPOCH + Duration::from_secs(last_seen));
+            peer.set_misconduct_score(
+                misconduct_score.decay(age, self.config.reputation_half_life_seconds),
+            );
+            // A peer which is still blocked isn't dialed.
+            if let Some(blocked_until) = blocked_until.filter(|blocked_until| *blocked_until > now)
+            {
+                peer.blacklist_peer(Duration::from_secs(blocked_until - now));
+                self.peers.insert(peer_id, peer);
+                continue;
+            }
+            self.peers.insert(peer_id, peer);
+            self.pending_events.push(ToSwarm::Dial {
+                opts: DialOpts::peer_id(peer_id).addresses(vec![multiaddr]).build(),
+            });
         }
     }
 
+    // Marks the peer store as outdated. It is written once `PEER_STORE_WRITE_DELAY` passes, so that
+    // bursts of changes result in a single write.
+    fn store_peers(&mut self) {
+        self.peer_store_outdated = self.peer_store.is_some();
+    }
+
+    fn stored_peers(&self) -> Vec<StoredPeer> {
+        let half_life = self.config.reputation_half_life_seconds;
+        self.peers
+            .values()
+            .map(|peer| StoredPeer {
+                peer_id: peer.peer_id(),
+                multiaddr: peer.multiaddr(),
+                last_seen: unix_seconds(peer.last_seen()),
+                misconduct_score: peer.misconduct_score(half_life),
+                blocked_until: peer.is_blocked().then(|| {
+                    unix_seconds(SystemTime::now()).saturating_add(peer.blocked_for().as_secs())
+                }),
+            })
+            .collect()
+    }
+
+    // Writes the peer store on a blocking thread once the write delay passes. Must be called from
+    // within a tokio runtime.
+    fn poll_peer_store_write(&mut self, cx: &mut std::task::Context<'_>) {
+        if !self.peer_store_outdated {
+            return;
+        }
+        let write_delay = self
+            .peer_store_write_delay
+            .get_or_insert_with(|| tokio::time::sleep(PEER_STORE_WRITE_DELAY).boxed());
+        if write_delay.as_mut().poll(cx).is_pending() {
+            return;
+        }
+        self.peer_store_write_delay = None;
+        // Writes are sequential, so an older store never replaces a newer one. If the previous
+        // write isn't done yet, wait for another delay.
+        if self.peer_store_write_task.as_ref().is_some_and(|task| !task.is_finished()) {
+            return self.poll_peer_store_write(cx);
+        }
+        let Some(peer_store) = self.peer_store.clone() else {
+            return;
+        };
+        let stored_peers = self.stored_peers();
+        self.peer_store_outdated = false;
+        self.peer_store_write_task = Some(tokio::task::spawn_blocking(move || {
+            if let Err(e) = peer_store.save(stored_peers) {
+                warn!("Failed to write the peer store: {e}");
+            }
+        }));
+    }
+
     fn add_peer(&mut self, peer: Peer) {
         info!("NEW_PEER: Peer Manager found new peer {:?}", peer.peer_id());
         self.peers.insert(peer.peer_id(), peer);
+        self.store_peers();
         // The new peer is unblocked so we don't need to wait for unblocked peer.
         self.sleep_waiting_for_unblocked_peer = None;
         for outbound_session_id in std::mem::take(&mut self.sessions_received_when_no_peers) {
@@ -139,14 +280,15 @@ impl PeerManager {
             self.sessions_received_when_no_peers.push(outbound_session_id);
             return None;
         }
-        let peer = self
-            .peers
-            .iter()
-            .skip(self.last_peer_index)
-            .find(|(_, peer)| peer.is_available())
-            .or_else(|| {
-                self.peers.iter().take(self.last_peer_index).find(|(_, peer)| peer.is_available())
-            });
+        let excluded_peers = self.session_to_excluded_peers.get(&outbound_session_id);
+        let is_excluded =
+            |peer_id: &PeerId| excluded_peers.is_some_and(|excluded| excluded.contains(peer_id));
+        let is_authenticated = |peer_id: &PeerId| !self.unauthenticated_peers.contains(peer_id);
+        // The excluded peers are assigned only if no other peer is available.
+        let peer = find_available_peer(&self.peers, self.last_peer_index, |peer_id| {
+            is_authenticated(peer_id) && !is_excluded(peer_id)
+        })
+        .or_else(|| find_available_peer(&self.peers, self.last_peer_index, is_authenticated));
         // TODO(shahak): advance to selected peer's index plus one.
         self.last_peer_index = (self.last_peer_index + 1) % self.peers.len();
         if peer.is_none() {
@@ -156,15 +298,15 @@ impl PeerManager {
                  {outbound_session_id:?}"
             );
             self.sessions_received_when_no_peers.push(outbound_session_id);
-            // Find the peer closest to becoming unblocked.
-            let sleep_deadline = self
+            // Find the peer closest to becoming unblocked. Peers which are being authenticated are
+            // assigned once the authentication ends.
+            self.sleep_waiting_for_unblocked_peer = self
                 .peers
-                .values()
-                .map(|peer| peer.blocked_until())
+                .iter()
+                .filter(|(peer_id, _)| !self.unauthenticated_peers.contains(peer_id))
+                .map(|(_, peer)| peer.blocked_until())
                 .min()
-                .expect("min should not return None on a non-empty iterator");
-            self.sleep_waiting_for_unblocked_peer =
-                Some(tokio::time::sleep_until(sleep_deadline.into()).boxed());
+                .map(|sleep_deadline| tokio::time::sleep_until(sleep_deadline.into()).boxed());
             return None;
         }
         peer.map(|(peer_id, peer)| {
@@ -214,16 +356,26 @@ impl PeerManager {
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             match reason {
                 ReputationModifier::Misconduct { misconduct_score } => {
-                    peer.report(misconduct_score);
-                    if peer.is_malicious() {
+                    // The peer is blacklisted for a duration proportional to its accumulated
+                    // score. The score isn't reset once the peer is blacklisted, it decays
+                    // instead. So a peer which keeps misbehaving is blacklisted for longer.
+                    let half_life = self.config.reputation_half_life_seconds;
+                    peer.report(misconduct_score, half_life);
+                    let timeout = peer
+                        .misconduct_score(half_life)
+                        .scale_timeout(self.config.misconduct_timeout_seconds);
+                    if !timeout.is_zero() {
+                        peer.blacklist_peer(timeout);
+                    }
+                    // Only malicious peers are dropped from the discovery.
+                    if peer.is_malicious(half_life) {
                         self.pending_events.push(ToSwarm::GenerateEvent(
                             ToOtherBehaviourEvent::PeerBlacklisted { peer_id },
                         ));
                         // TODO(shahak): close the connection with the peer. Do this only when
                         // we're not in a trusted setup.
-                        peer.blacklist_peer(self.config.malicious_timeout_seconds);
-                        peer.reset_misconduct_score();
                     }
+                    self.store_peers();
                 }
                 ReputationModifier::Unstable => {
                     self.pending_events.push(ToSwarm::GenerateEvent(
@@ -238,6 +390,40 @@ impl PeerManager {
         }
     }
 
+    /// Blocks the peer for the given duration and disconnects from it, regardless of its
+    /// misconduct score.
+    pub(crate) fn ban_peer(
+        &mut self,
+        peer_id: PeerId,
+        duration: Duration,
+    ) -> Result<(), PeerManagerError> {
+        let peer = self.peers.get_mut(&peer_id).ok_or(PeerManagerError::NoSuchPeer(peer_id))?;
+        peer.blacklist_peer(duration);
+        self.pending_events
+            .push(ToSwarm::GenerateEvent(ToOtherBehaviourEvent::PeerBlacklisted { peer_id }));
+        self.pending_events
+            .push(ToSwarm::CloseConnection { peer_id, connection: CloseConnection::All });
+        self.store_peers();
+        Ok(())
+    }
+
+    /// Unblocks the peer and clears its misconduct score.
+    pub(crate) fn unban_peer(&mut self, peer_id: PeerId) -> Result<(), PeerManagerError> {
+        let peer = self.peers.get_mut(&peer_id).ok_or(PeerManagerError::NoSuchPeer(peer_id))?;
+        peer.unblock();
+        peer.set_misconduct_score(MisconductScore::NEUTRAL);
+        self.store_peers();
+        Ok(())
+    }
+
+    /// Returns the reputation of the peer, or None if the peer isn't known yet.
+    pub(crate) fn peer_reputation(&self, peer_id: &PeerId) -> Option<PeerReputation> {
+        self.peers.get(peer_id).map(|peer| PeerReputation {
+            misconduct_score: peer.misconduct_score(self.config.reputation_half_life_seconds),
+            blocked_until: peer.is_blocked().then(|| peer.blocked_until()),
+        })
+    }
+
     fn report_session(
         &mut self,
         outbound_session_id: OutboundSessionId,
@@ -251,6 +437,33 @@ impl PeerManager {
     }
 }
 
+impl Drop for PeerManager {
+    // Writes the changes that weren't written yet, so they aren't lost on shutdown.
+    fn drop(&mut self) {
+        let Some(peer_store) = self.peer_store.as_ref().filter(|_| self.peer_store_outdated) else {
+            return;
+        };
+        if let Err(e) = peer_store.save(self.stored_peers()) {
+            warn!("Failed to write the peer store: {e}");
+        }
+    }
+}
+
+// Returns the first available peer allowed by `is_allowed`, going round robin from `start_index`.
+fn find_available_peer(
+    peers: &HashMap<PeerId, Peer>,
+    start_index: usize,
+    is_allowed: impl Fn(&PeerId) -> bool,
+) -> Option<(&PeerId, &Peer)> {
+    let is_assignable =
+        |(peer_id, peer): &(&PeerId, &Peer)| peer.is_available() && is_allowed(peer_id);
+    peers
+        .iter()
+        .skip(start_index)
+        .find(is_assignable)
+        .or_else(|| peers.iter().take(start_index).find(is_assignable))
+}
+
 impl From<ToOtherBehaviourEvent> for mixed_behaviour::Event {
     fn from(event: ToOtherBehaviourEvent) -> Self {
         Self::ToOtherBehaviourEvent(mixed_behaviour::ToOtherBehaviourEvent::PeerManager(event))
@@ -261,12 +474,23 @@ impl BridgedBehaviour for PeerManager {
     fn on_other_behaviour_event(&mut self, event: &mixed_behaviour::ToOtherBehaviourEvent) {
         match event {
             mixed_behaviour::ToOtherBehaviourEvent::Sqmr(
-                sqmr::ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id },
+                sqmr::ToOtherBehaviourEvent::RequestPeerAssignment {
+                    outbound_session_id,
+                    excluded_peers,
+                },
             ) => {
+                if !excluded_peers.is_empty() {
+                    self.session_to_excluded_peers
+                        .insert(*outbound_session_id, excluded_peers.clone());
+                }
                 self.assign_peer_to_session(*outbound_session_id);
             }
             mixed_behaviour::ToOtherBehaviourEvent::Identify(
-                IdentifyToOtherBehaviourEvent::FoundListenAddresses { peer_id, listen_addresses },
+                IdentifyToOtherBehaviourEvent::FoundListenAddresses {
+                    peer_id,
+                    listen_addresses,
+                    ..
+                },
             )
             | mixed_behaviour::ToOtherBehaviourEvent::Discovery(
                 discovery::ToOtherBehaviourEvent::FoundListenAddresses {
@@ -289,6 +513,22 @@ impl BridgedBehaviour for PeerManager {
                 }
                 self.add_peer(peer);
             }
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationStarted { peer_id },
+            ) => {
+                self.unauthenticated_peers.insert(*peer_id);
+            }
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationEnded { peer_id },
+            ) => {
+                if self.unauthenticated_peers.remove(peer_id) {
+                    for outbound_session_id in
+                        std::mem::take(&mut self.sessions_received_when_no_peers)
+                    {
+                        self.assign_peer_to_session(outbound_session_id);
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -1,8 +1,8 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use libp2p::swarm::ConnectionId;
 use libp2p::{Multiaddr, PeerId};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::misconduct_score::MisconductScore;
 
@@ -13,6 +13,10 @@ pub struct Peer {
     timed_out_until: Instant,
     connection_ids: Vec<ConnectionId>,
     misconduct_score: MisconductScore,
+    // The misconduct score decays from the last time it was updated.
+    misconduct_score_updated_at: Instant,
+    // The last time we had a connection to the peer.
+    last_seen: SystemTime,
 }
 
 impl Peer {
@@ -23,11 +27,20 @@ impl Peer {
             timed_out_until: get_instant_now(),
             connection_ids: Vec::new(),
             misconduct_score: MisconductScore::NEUTRAL,
+            misconduct_score_updated_at: get_instant_now(),
+            last_seen: SystemTime::now(),
         }
     }
 
     pub fn blacklist_peer(&mut self, timeout_duration: Duration) {
-        self.timed_out_until = get_instant_now() + timeout_duration;
+        let Some(timed_out_until) = get_instant_now().checked_add(timeout_duration) else {
+            warn!(
+                "Can't blacklist peer {:?} for {:?}, which is out of range.",
+                self.peer_id, timeout_duration
+            );
+            return;
+        };
+        self.timed_out_until = timed_out_until;
         info!(
             "Peer {:?} misbehaved. Blacklisting it for {:.3} seconds.",
             self.peer_id,
@@ -35,6 +48,10 @@ impl Peer {
         );
     }
 
+    pub fn unblock(&mut self) {
+        self.timed_out_until = get_instant_now();
+    }
+
     pub fn peer_id(&self) -> PeerId {
         self.peer_id
     }
@@ -59,6 +76,11 @@ impl Peer {
         }
     }
 
+    /// Returns how long the peer is still blocked for.
+    pub fn blocked_for(&self) -> Duration {
+        self.timed_out_until.saturating_duration_since(get_instant_now())
+    }
+
     pub fn connection_ids(&self) -> &Vec<ConnectionId> {
         &self.connection_ids
     }
@@ -71,16 +93,33 @@ impl Peer {
         self.connection_ids.push(connection_id);
     }
 
-    pub fn reset_misconduct_score(&mut self) {
-        self.misconduct_score = MisconductScore::NEUTRAL;
+    /// Adds to the misconduct score of the peer, after decaying it with the given half-life.
+    pub fn report(&mut self, misconduct_score: MisconductScore, half_life: Duration) {
+        self.set_misconduct_score(self.misconduct_score(half_life));
+        self.misconduct_score += misconduct_score;
     }
 
-    pub fn report(&mut self, misconduct_score: MisconductScore) {
-        self.misconduct_score += misconduct_score;
+    /// Returns the misconduct score of the peer, which halves every `half_life`.
+    pub fn misconduct_score(&self, half_life: Duration) -> MisconductScore {
+        let elapsed = get_instant_now().saturating_duration_since(self.misconduct_score_updated_at);
+        self.misconduct_score.decay(elapsed, half_life)
+    }
+
+    pub fn set_misconduct_score(&mut self, misconduct_score: MisconductScore) {
+        self.misconduct_score = misconduct_score;
+        self.misconduct_score_updated_at = get_instant_now();
+    }
+
+    pub fn is_malicious(&self, half_life: Duration) -> bool {
+        self.misconduct_score(half_life).is_malicious()
+    }
+
+    pub fn last_seen(&self) -> SystemTime {
+        self.last_seen
     }
 
-    pub fn is_malicious(&self) -> bool {
-        self.misconduct_score.is_malicious()
+    pub fn set_last_seen(&mut self, last_seen: SystemTime) {
+        self.last_seen = last_seen;
     }
 }
 
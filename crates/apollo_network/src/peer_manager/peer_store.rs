@@ -0,0 +1,90 @@
+//! A file which persists the peers known to the peer manager, so that the node can reconnect to
+//! them after it restarts instead of relying only on its bootstrap peers.
+//!
+//! The whole store is rewritten after the peers change, at most once every
+//! [`PEER_STORE_WRITE_DELAY`], and off the network thread. It is first written to a temporary file
+//! which then replaces the store, so a crash in the middle of writing leaves the previous store
+//! intact.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::misconduct_score::MisconductScore;
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum PeerStoreError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+/// A peer as written to the store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StoredPeer {
+    pub peer_id: PeerId,
+    pub multiaddr: Multiaddr,
+    /// The last time we had a connection to the peer, in seconds since the Unix epoch.
+    pub last_seen: u64,
+    /// The misconduct score of the peer when the store was written.
+    pub misconduct_score: MisconductScore,
+    /// The time the peer is blocked until, in seconds since the Unix epoch, if it was blocked when
+    /// the store was written.
+    #[serde(default)]
+    pub blocked_until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PeerStoreContent {
+    // In seconds since the Unix epoch.
+    written_at: u64,
+    peers: Vec<StoredPeer>,
+}
+
+/// How long changes to the peers are batched before the store is rewritten.
+pub(crate) const PEER_STORE_WRITE_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub(crate) struct PeerStore {
+    path: PathBuf,
+}
+
+impl PeerStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the stored peers, along with how long ago they were written. Returns no peers if
+    /// the store wasn't written yet.
+    pub fn load(&self) -> Result<(Vec<StoredPeer>, Duration), PeerStoreError> {
+        let content = match fs::read(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((Vec::new(), Duration::ZERO));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let PeerStoreContent { written_at, peers } = serde_json::from_slice(&content)?;
+        let age = Duration::from_secs(unix_seconds(SystemTime::now()).saturating_sub(written_at));
+        Ok((peers, age))
+    }
+
+    pub fn save(&self, peers: Vec<StoredPeer>) -> Result<(), PeerStoreError> {
+        let content = PeerStoreContent { written_at: unix_seconds(SystemTime::now()), peers };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, serde_json::to_vec(&content)?)?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
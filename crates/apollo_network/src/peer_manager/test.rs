@@ -3,13 +3,13 @@
 use core::{panic, time};
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use assert_matches::assert_matches;
 use futures::future::poll_fn;
 use futures::{FutureExt, Stream, StreamExt};
 use libp2p::swarm::behaviour::ConnectionEstablished;
-use libp2p::swarm::{ConnectionId, NetworkBehaviour, ToSwarm};
+use libp2p::swarm::{CloseConnection, ConnectionId, NetworkBehaviour, ToSwarm};
 use libp2p::{Multiaddr, PeerId};
 use tokio::time::sleep;
 use void::Void;
@@ -17,11 +17,12 @@ use void::Void;
 use super::behaviour_impl::ToOtherBehaviourEvent;
 use crate::discovery::identify_impl::IdentifyToOtherBehaviourEvent;
 use crate::misconduct_score::MisconductScore;
-use crate::mixed_behaviour;
 use crate::mixed_behaviour::BridgedBehaviour;
 use crate::peer_manager::peer::Peer;
+use crate::peer_manager::peer_store::{PeerStore, PEER_STORE_WRITE_DELAY};
 use crate::peer_manager::{PeerManager, PeerManagerConfig, ReputationModifier};
 use crate::sqmr::OutboundSessionId;
+use crate::{allowlist, mixed_behaviour, sqmr};
 
 impl Unpin for PeerManager {}
 
@@ -182,8 +183,11 @@ async fn peer_assignment_no_unblocked_peers() {
     const BLOCKED_UNTIL: Duration = Duration::from_secs(5);
     const TIMEOUT: Duration = Duration::from_secs(1);
     // Create a new peer manager
-    let config =
-        PeerManagerConfig { malicious_timeout_seconds: TIMEOUT, unstable_timeout_millis: TIMEOUT };
+    let config = PeerManagerConfig {
+        misconduct_timeout_seconds: TIMEOUT,
+        unstable_timeout_millis: TIMEOUT,
+        ..Default::default()
+    };
     let mut peer_manager: PeerManager = PeerManager::new(config.clone());
 
     // Create a session
@@ -387,6 +391,41 @@ fn wrap_around_in_peer_assignment() {
     assert_matches!(peer_manager.assign_peer_to_session(outbound_session_id), Some(peer_id) if peer_id == peer_id2);
 }
 
+#[test]
+fn assign_non_excluded_peer_to_session() {
+    let mut peer_manager = PeerManager::new(PeerManagerConfig::default());
+    let peer_id1 = PeerId::random();
+    let mut peer1 = Peer::new(peer_id1, Multiaddr::empty());
+    peer1.add_connection_id(ConnectionId::new_unchecked(0));
+    peer_manager.add_peer(peer1);
+    let peer_id2 = PeerId::random();
+    let mut peer2 = Peer::new(peer_id2, Multiaddr::empty());
+    peer2.add_connection_id(ConnectionId::new_unchecked(1));
+    peer_manager.add_peer(peer2);
+
+    // Request an assignment excluding the first peer. Assign twice to make sure we wrap around the
+    // HashMap.
+    let outbound_session_id = OutboundSessionId { value: 1 };
+    peer_manager.on_other_behaviour_event(&mixed_behaviour::ToOtherBehaviourEvent::Sqmr(
+        sqmr::ToOtherBehaviourEvent::RequestPeerAssignment {
+            outbound_session_id,
+            excluded_peers: vec![peer_id1],
+        },
+    ));
+    assert_eq!(peer_manager.session_to_peer_map.get(&outbound_session_id), Some(&peer_id2));
+    assert_matches!(peer_manager.assign_peer_to_session(outbound_session_id), Some(peer_id) if peer_id == peer_id2);
+
+    // The excluded peers are assigned if no other peer is available.
+    let outbound_session_id = OutboundSessionId { value: 2 };
+    peer_manager.on_other_behaviour_event(&mixed_behaviour::ToOtherBehaviourEvent::Sqmr(
+        sqmr::ToOtherBehaviourEvent::RequestPeerAssignment {
+            outbound_session_id,
+            excluded_peers: vec![peer_id1, peer_id2],
+        },
+    ));
+    assert!(peer_manager.session_to_peer_map.contains_key(&outbound_session_id));
+}
+
 #[test]
 fn block_and_allow_inbound_connection() {
     // Create a new peer manager
@@ -476,6 +515,7 @@ fn identify_on_unknown_peer_is_added_to_peer_manager() {
         IdentifyToOtherBehaviourEvent::FoundListenAddresses {
             peer_id,
             listen_addresses: vec![address.clone()],
+            protocols: vec![],
         },
     ));
 
@@ -484,3 +524,195 @@ fn identify_on_unknown_peer_is_added_to_peer_manager() {
     assert!(res_peer_id.peer_id() == peer_id);
     assert!(res_peer_id.multiaddr() == address);
 }
+
+#[tokio::test(start_paused = true)]
+async fn misconduct_timeout_is_graded_by_decaying_score() {
+    const HALF_LIFE: Duration = Duration::from_secs(10);
+    const TIMEOUT: Duration = Duration::from_secs(100);
+    let config = PeerManagerConfig {
+        reputation_half_life_seconds: HALF_LIFE,
+        misconduct_timeout_seconds: TIMEOUT,
+        ..Default::default()
+    };
+    let mut peer_manager: PeerManager = PeerManager::new(config);
+    let peer_id = PeerId::random();
+    let blocked_until = |peer_manager: &PeerManager| {
+        peer_manager.peer_reputation(&peer_id).unwrap().blocked_until.unwrap()
+    };
+
+    peer_manager.add_peer(Peer::new(peer_id, Multiaddr::empty()));
+    let misconduct = ReputationModifier::Misconduct { misconduct_score: MisconductScore::new(0.5) };
+    peer_manager.report_peer(peer_id, misconduct).unwrap();
+    let now = tokio::time::Instant::now().into_std();
+    assert_eq!(blocked_until(&peer_manager), now + TIMEOUT / 2);
+
+    // After a half-life the score is down to 0.25, so another report brings it to 0.75.
+    tokio::time::advance(HALF_LIFE).await;
+    peer_manager.report_peer(peer_id, misconduct).unwrap();
+    let now = tokio::time::Instant::now().into_std();
+    assert_eq!(blocked_until(&peer_manager), now + TIMEOUT * 3 / 4);
+    assert!(peer_manager.pending_events.is_empty());
+
+    // Without time to decay, the peer becomes malicious and is dropped from the discovery.
+    peer_manager.report_peer(peer_id, misconduct).unwrap();
+    assert_eq!(blocked_until(&peer_manager), now + TIMEOUT);
+    assert_matches!(
+        peer_manager.pending_events.as_slice(),
+        [ToSwarm::GenerateEvent(ToOtherBehaviourEvent::PeerBlacklisted { peer_id: event_peer_id })]
+        if *event_peer_id == peer_id
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn peer_store_writes_are_batched() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("peer_store.json");
+    let config = PeerManagerConfig { peer_store_path: Some(path.clone()), ..Default::default() };
+    let mut peer_manager: PeerManager = PeerManager::new(config);
+
+    peer_manager.add_peer(Peer::new(PeerId::random(), Multiaddr::empty()));
+    peer_manager.add_peer(Peer::new(PeerId::random(), Multiaddr::empty()));
+    assert!(peer_manager.next().now_or_never().is_none());
+    assert!(!path.exists());
+
+    tokio::time::advance(PEER_STORE_WRITE_DELAY).await;
+    assert!(peer_manager.next().now_or_never().is_none());
+    peer_manager.peer_store_write_task.take().unwrap().await.unwrap();
+    let (stored_peers, _) = PeerStore::new(path).load().unwrap();
+    assert_eq!(stored_peers.len(), 2);
+}
+
+#[test]
+fn peers_are_restored_from_peer_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = PeerManagerConfig {
+        peer_store_path: Some(dir.path().join("peer_store.json")),
+        ..Default::default()
+    };
+    let mut peer_manager: PeerManager = PeerManager::new(config.clone());
+
+    let peer_id = PeerId::random();
+    let address = Multiaddr::empty().with_p2p(peer_id).unwrap();
+    peer_manager.add_peer(Peer::new(peer_id, address.clone()));
+    peer_manager
+        .report_peer(
+            peer_id,
+            ReputationModifier::Misconduct { misconduct_score: MisconductScore::new(0.5) },
+        )
+        .unwrap();
+    drop(peer_manager);
+
+    // A restarted peer manager knows the peer along with its reputation, and dials it.
+    let mut peer_manager: PeerManager = PeerManager::new(config.clone());
+    let peer = peer_manager.get_mut_peer(peer_id).unwrap();
+    assert_eq!(peer.multiaddr(), address);
+    assert!(
+        peer.misconduct_score(config.reputation_half_life_seconds) > MisconductScore::new(0.49)
+    );
+    assert_matches!(
+        peer_manager.pending_events.as_slice(),
+        [ToSwarm::Dial { opts }] if opts.get_peer_id() == Some(peer_id)
+    );
+}
+
+#[test]
+fn banned_peers_stay_banned_after_restart() {
+    const BAN_DURATION: Duration = Duration::from_secs(3600);
+    let dir = tempfile::tempdir().unwrap();
+    let config = PeerManagerConfig {
+        peer_store_path: Some(dir.path().join("peer_store.json")),
+        ..Default::default()
+    };
+    let mut peer_manager: PeerManager = PeerManager::new(config.clone());
+
+    let peer_id = PeerId::random();
+    peer_manager.add_peer(Peer::new(peer_id, Multiaddr::empty().with_p2p(peer_id).unwrap()));
+    peer_manager.ban_peer(peer_id, BAN_DURATION).unwrap();
+    drop(peer_manager);
+
+    // A restarted peer manager keeps the ban, and doesn't dial the peer.
+    let peer_manager: PeerManager = PeerManager::new(config);
+    let reputation = peer_manager.peer_reputation(&peer_id).unwrap();
+    let blocked_for = reputation.blocked_until.unwrap() - Instant::now();
+    assert!(blocked_for > BAN_DURATION - Duration::from_secs(60));
+    assert!(peer_manager.pending_events.is_empty());
+}
+
+#[test]
+fn ban_with_out_of_range_duration_is_ignored() {
+    let mut peer_manager: PeerManager = PeerManager::new(PeerManagerConfig::default());
+    let peer_id = PeerId::random();
+    peer_manager.add_peer(Peer::new(peer_id, Multiaddr::empty()));
+
+    peer_manager.ban_peer(peer_id, Duration::MAX).unwrap();
+    assert_eq!(peer_manager.peer_reputation(&peer_id).unwrap().blocked_until, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn banned_peer_is_disconnected_until_unbanned() {
+    const BAN_DURATION: Duration = Duration::from_secs(3600);
+    let mut peer_manager: PeerManager = PeerManager::new(PeerManagerConfig::default());
+    let peer_id = PeerId::random();
+    peer_manager.add_peer(Peer::new(peer_id, Multiaddr::empty()));
+    peer_manager
+        .report_peer(
+            peer_id,
+            ReputationModifier::Misconduct { misconduct_score: MisconductScore::new(0.5) },
+        )
+        .unwrap();
+
+    peer_manager.ban_peer(peer_id, BAN_DURATION).unwrap();
+    let reputation = peer_manager.peer_reputation(&peer_id).unwrap();
+    assert_eq!(
+        reputation.blocked_until,
+        Some(tokio::time::Instant::now().into_std() + BAN_DURATION)
+    );
+    assert_matches!(
+        peer_manager.pending_events.as_slice(),
+        [
+            ToSwarm::GenerateEvent(ToOtherBehaviourEvent::PeerBlacklisted { .. }),
+            ToSwarm::CloseConnection { peer_id: closed_peer_id, connection: CloseConnection::All },
+        ] if *closed_peer_id == peer_id
+    );
+
+    peer_manager.unban_peer(peer_id).unwrap();
+    let reputation = peer_manager.peer_reputation(&peer_id).unwrap();
+    assert_eq!(reputation.blocked_until, None);
+    assert_eq!(reputation.misconduct_score, MisconductScore::NEUTRAL);
+
+    let unknown_peer_id = PeerId::random();
+    assert!(peer_manager.peer_reputation(&unknown_peer_id).is_none());
+    peer_manager
+        .ban_peer(unknown_peer_id, BAN_DURATION)
+        .expect_err("ban_peer on unknown peer_id should return an error");
+}
+
+#[tokio::test]
+async fn peers_are_assigned_only_once_authenticated() {
+    let mut peer_manager = PeerManager::new(PeerManagerConfig::default());
+    let peer = Peer::new(PeerId::random(), Multiaddr::empty());
+    let connection_id = ConnectionId::new_unchecked(0);
+    peer_manager.add_peer(peer.clone());
+    simulate_connection_established(&mut peer_manager, peer.peer_id(), connection_id);
+    peer_manager.on_other_behaviour_event(&mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+        allowlist::ToOtherBehaviourEvent::AuthenticationStarted { peer_id: peer.peer_id() },
+    ));
+
+    let outbound_session_id = OutboundSessionId { value: 1 };
+    assert_eq!(peer_manager.assign_peer_to_session(outbound_session_id), None);
+    assert!(peer_manager.next().now_or_never().is_none());
+
+    peer_manager.on_other_behaviour_event(&mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+        allowlist::ToOtherBehaviourEvent::AuthenticationEnded { peer_id: peer.peer_id() },
+    ));
+    assert_matches!(
+        peer_manager.next().now_or_never(),
+        Some(Some(ToSwarm::GenerateEvent(ToOtherBehaviourEvent::SessionAssigned {
+            outbound_session_id: event_outbound_session_id,
+            peer_id,
+            connection_id: event_connection_id,
+        }))) if outbound_session_id == event_outbound_session_id
+            && peer_id == peer.peer_id()
+            && connection_id == event_connection_id
+    );
+}
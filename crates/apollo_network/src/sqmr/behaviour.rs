@@ -31,7 +31,7 @@ use super::handler::{
 };
 use super::{Bytes, Config, GenericEvent, InboundSessionId, OutboundSessionId, SessionId};
 use crate::mixed_behaviour::{self, BridgedBehaviour};
-use crate::peer_manager;
+use crate::{allowlist, peer_manager};
 
 #[derive(thiserror::Error, Debug)]
 pub enum SessionError {
@@ -88,7 +88,7 @@ pub type ExternalEvent = GenericEvent<SessionError>;
 
 #[derive(Debug)]
 pub enum ToOtherBehaviourEvent {
-    RequestPeerAssignment { outbound_session_id: OutboundSessionId },
+    RequestPeerAssignment { outbound_session_id: OutboundSessionId, excluded_peers: Vec<PeerId> },
 }
 
 #[derive(Debug)]
@@ -115,6 +115,8 @@ pub struct Behaviour {
     wakers_waiting_for_event: Vec<Waker>,
     outbound_sessions_pending_peer_assignment: HashMap<OutboundSessionId, (Bytes, StreamProtocol)>,
     supported_inbound_protocols: HashSet<StreamProtocol>,
+    // Inbound sessions from these peers are dropped until the allowlist authenticates them.
+    unauthenticated_peers: HashSet<PeerId>,
 }
 
 impl Behaviour {
@@ -129,14 +131,17 @@ impl Behaviour {
             wakers_waiting_for_event: Default::default(),
             outbound_sessions_pending_peer_assignment: Default::default(),
             supported_inbound_protocols: Default::default(),
+            unauthenticated_peers: Default::default(),
         }
     }
 
-    /// Assign some peer and start a query. Return the id of the new session.
+    /// Assign some peer and start a query. Return the id of the new session. The excluded peers
+    /// aren't assigned to the query unless no other peer is available.
     pub fn start_query(
         &mut self,
         query: Bytes,
         protocol_name: StreamProtocol,
+        excluded_peers: Vec<PeerId>,
     ) -> OutboundSessionId {
         let outbound_session_id = self.next_outbound_session_id;
         self.next_outbound_session_id.value += 1;
@@ -148,7 +153,7 @@ impl Behaviour {
             outbound_session_id
         );
         self.add_event_to_queue(ToSwarm::GenerateEvent(Event::ToOtherBehaviourEvent(
-            ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id },
+            ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id, excluded_peers },
         )));
 
         outbound_session_id
@@ -295,6 +300,15 @@ impl NetworkBehaviour for Behaviour {
                     ExternalEvent::NewInboundSession { inbound_session_id, .. } => {
                         self.session_id_to_peer_id_and_connection_id
                             .insert(inbound_session_id.into(), (peer_id, connection_id));
+                        if self.unauthenticated_peers.contains(&peer_id) {
+                            debug!(
+                                "Dropping {inbound_session_id:?} since peer {peer_id:?} wasn't \
+                                 authenticated yet."
+                            );
+                            self.drop_session(inbound_session_id.into())
+                                .expect("The session was just added");
+                            is_event_muted = true;
+                        }
                     }
                     ExternalEvent::SessionFailed { session_id, .. }
                     | ExternalEvent::SessionFinishedSuccessfully { session_id, .. } => {
@@ -337,15 +351,27 @@ impl NetworkBehaviour for Behaviour {
 
 impl BridgedBehaviour for Behaviour {
     fn on_other_behaviour_event(&mut self, event: &mixed_behaviour::ToOtherBehaviourEvent) {
-        let mixed_behaviour::ToOtherBehaviourEvent::PeerManager(
-            peer_manager::ToOtherBehaviourEvent::SessionAssigned {
-                outbound_session_id,
-                peer_id,
-                connection_id,
-            },
-        ) = event
-        else {
-            return;
+        let (outbound_session_id, peer_id, connection_id) = match event {
+            mixed_behaviour::ToOtherBehaviourEvent::PeerManager(
+                peer_manager::ToOtherBehaviourEvent::SessionAssigned {
+                    outbound_session_id,
+                    peer_id,
+                    connection_id,
+                },
+            ) => (outbound_session_id, peer_id, connection_id),
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationStarted { peer_id },
+            ) => {
+                self.unauthenticated_peers.insert(*peer_id);
+                return;
+            }
+            mixed_behaviour::ToOtherBehaviourEvent::Allowlist(
+                allowlist::ToOtherBehaviourEvent::AuthenticationEnded { peer_id },
+            ) => {
+                self.unauthenticated_peers.remove(peer_id);
+                return;
+            }
+            _ => return,
         };
         debug!(
             "Assigned peer {:?} to session {:?} with connection id: {:?}",
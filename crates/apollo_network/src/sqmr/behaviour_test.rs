@@ -132,7 +132,8 @@ async fn validate_request_peer_assignment_event(
     assert_matches!(
         event,
         ToSwarm::GenerateEvent(Event::ToOtherBehaviourEvent(ToOtherBehaviourEvent::RequestPeerAssignment {
-                outbound_session_id: event_outbound_session_id
+                outbound_session_id: event_outbound_session_id,
+                ..
             },
         )) if outbound_session_id == event_outbound_session_id
     );
@@ -315,7 +316,8 @@ async fn create_and_process_outbound_session() {
 
     let peer_id = PeerId::random();
 
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone(), Vec::new());
 
     validate_request_peer_assignment_event(&mut behaviour, outbound_session_id).await;
     validate_no_events(&mut behaviour);
@@ -350,7 +352,8 @@ async fn connection_closed() {
     let peer_id = PeerId::random();
 
     // Add an outbound session on the connection.
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone(), Vec::new());
     // Consume the event to request peer assignment.
     behaviour.next().await.unwrap();
     simulate_peer_assigned(&mut behaviour, peer_id, outbound_session_id);
@@ -396,7 +399,8 @@ async fn drop_outbound_session() {
 
     let peer_id = PeerId::random();
 
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone(), Vec::new());
     // Consume the event to request peer assignment.
     behaviour.next().await.unwrap();
     simulate_peer_assigned(&mut behaviour, peer_id, outbound_session_id);
@@ -70,6 +70,7 @@ fn start_query_and_update_map(
     let outbound_session_id = outbound_swarm.behaviour_mut().start_query(
         get_bytes_from_query_indices(outbound_peer_id, inbound_peer_id),
         PROTOCOL_NAME,
+        Vec::new(),
     );
     outbound_session_id_to_peer_id.insert((outbound_peer_id, outbound_session_id), inbound_peer_id);
 }
@@ -130,6 +131,7 @@ fn check_request_peer_assignment_event_and_return_session_id(
     };
     let Event::ToOtherBehaviourEvent(ToOtherBehaviourEvent::RequestPeerAssignment {
         outbound_session_id,
+        ..
     }) = event
     else {
         panic!("Got unexpected event {event:?} when expecting RequestPeerAssignment");
@@ -6,6 +6,7 @@ use std::task::{Context, Poll, Waker};
 
 use futures::stream::{Stream, StreamExt};
 use libp2p::core::multiaddr::Protocol;
+use libp2p::identity::Keypair;
 use libp2p::{Multiaddr, PeerId};
 
 // This is an implementation of `StreamMap` from tokio_stream. The reason we're implementing it
@@ -113,3 +114,11 @@ pub fn make_tcp_multiaddr(ip: Ipv4Addr, port: u16, peer_id: PeerId) -> Multiaddr
         .with(Protocol::QuicV1)
         .with(Protocol::P2p(peer_id))
 }
+
+/// Returns the peer id of a node with the given secret key.
+pub fn peer_id_from_secret_key(secret_key: Vec<u8>) -> PeerId {
+    Keypair::ed25519_from_bytes(secret_key)
+        .expect("Error while parsing secret key")
+        .public()
+        .to_peer_id()
+}
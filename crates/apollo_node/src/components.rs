@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use apollo_batcher::batcher::{create_batcher, Batcher};
 use apollo_batcher::pre_confirmed_cende_client::PreconfirmedCendeClient;
 use apollo_class_manager::class_manager::create_class_manager;
@@ -20,11 +22,22 @@ use apollo_monitoring_endpoint::monitoring_endpoint::{
     create_monitoring_endpoint,
     MonitoringEndpoint,
 };
+use apollo_network::network_manager::admin::{
+    create_network_admin_channel,
+    NetworkAdminClient,
+    NetworkAdminRequests,
+};
 use apollo_signature_manager::{create_signature_manager, SignatureManager};
 use apollo_state_sync::runner::StateSyncRunner;
 use apollo_state_sync::{create_state_sync_and_runner, StateSync};
-use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
+use papyrus_base_layer::configured_base_layer::ConfiguredBaseLayer;
+use papyrus_base_layer::ethereum_base_layer_contract::{
+    EthereumBaseLayerConfig,
+    EthereumBaseLayerContract,
+};
 use papyrus_base_layer::monitored_base_layer::MonitoredEthereumBaseLayer;
+use papyrus_base_layer::quorum_base_layer::QuorumBaseLayer;
+use papyrus_base_layer::simulated_base_layer::SimulatedBaseLayer;
 use papyrus_base_layer::BaseLayerContract;
 use tracing::{debug, info, warn};
 
@@ -36,6 +49,9 @@ use crate::config::component_execution_config::{
 use crate::config::node_config::SequencerNodeConfig;
 use crate::version::VERSION_FULL;
 
+/// The base layer of the L1 components: the Starknet contract on Ethereum, or a simulated L1.
+pub type NodeBaseLayer = ConfiguredBaseLayer<MonitoredEthereumBaseLayer>;
+
 pub struct SequencerNodeComponents {
     pub batcher: Option<Batcher>,
     pub class_manager: Option<ClassManager>,
@@ -43,9 +59,9 @@ pub struct SequencerNodeComponents {
     pub gateway: Option<Gateway>,
     pub http_server: Option<HttpServer>,
     pub l1_endpoint_monitor: Option<L1EndpointMonitor>,
-    pub l1_scraper: Option<L1Scraper<MonitoredEthereumBaseLayer>>,
+    pub l1_scraper: Option<L1Scraper<NodeBaseLayer>>,
     pub l1_provider: Option<L1Provider>,
-    pub l1_gas_price_scraper: Option<L1GasPriceScraper<MonitoredEthereumBaseLayer>>,
+    pub l1_gas_price_scraper: Option<L1GasPriceScraper<NodeBaseLayer>>,
     pub l1_gas_price_provider: Option<L1GasPriceProvider>,
     pub mempool: Option<MempoolCommunicationWrapper>,
     pub monitoring_endpoint: Option<MonitoringEndpoint>,
@@ -62,6 +78,10 @@ pub async fn create_node_components(
     clients: &SequencerNodeClients,
 ) -> SequencerNodeComponents {
     info!("Creating node components.");
+    let shared_base_layer = SharedBaseLayer::new(config);
+    // The admin clients of the networks of the node by the network's name, served by the monitoring
+    // endpoint.
+    let mut network_admin_clients = BTreeMap::new();
     let batcher = match config.components.batcher.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
         | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
@@ -101,7 +121,7 @@ pub async fn create_node_components(
     let signature_manager = match config.components.signature_manager.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
         | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
-            Some(create_signature_manager())
+            Some(create_signature_manager(config.signature_manager_config.clone()))
         }
         ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => None,
     };
@@ -129,6 +149,7 @@ pub async fn create_node_components(
                 class_manager_client,
                 signature_manager_client,
                 l1_gas_price_client,
+                Some(add_network_admin_client(&mut network_admin_clients, "consensus")),
             ))
         }
         ActiveComponentExecutionMode::Disabled => None,
@@ -182,6 +203,7 @@ pub async fn create_node_components(
                     gateway_client,
                     class_manager_client,
                     mempool_p2p_propagator_client,
+                    Some(add_network_admin_client(&mut network_admin_clients, "mempool_p2p")),
                 );
                 (Some(mempool_p2p_propagator), Some(mempool_p2p_runner))
             }
@@ -203,6 +225,29 @@ pub async fn create_node_components(
         ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => None,
     };
 
+    let (state_sync, state_sync_runner) = match config.components.state_sync.execution_mode {
+        ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
+        | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
+            let class_manager_client = clients
+                .get_class_manager_shared_client()
+                .expect("Class Manager Client should be available");
+            let network_admin_requests = config
+                .state_sync_config
+                .network_config
+                .as_ref()
+                .map(|_| add_network_admin_client(&mut network_admin_clients, "state_sync"));
+            let (state_sync, state_sync_runner) = create_state_sync_and_runner(
+                config.state_sync_config.clone(),
+                class_manager_client,
+                network_admin_requests,
+            );
+            (Some(state_sync), Some(state_sync_runner))
+        }
+        ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => {
+            (None, None)
+        }
+    };
+
     let monitoring_endpoint = match config.components.monitoring_endpoint.execution_mode {
         ActiveComponentExecutionMode::Enabled => {
             let mempool_client = match config.components.mempool.execution_mode {
@@ -232,28 +277,12 @@ pub async fn create_node_components(
                 VERSION_FULL,
                 mempool_client,
                 l1_provider_client,
+                network_admin_clients,
             ))
         }
         ActiveComponentExecutionMode::Disabled => None,
     };
 
-    let (state_sync, state_sync_runner) = match config.components.state_sync.execution_mode {
-        ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
-        | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
-            let class_manager_client = clients
-                .get_class_manager_shared_client()
-                .expect("Class Manager Client should be available");
-            let (state_sync, state_sync_runner) = create_state_sync_and_runner(
-                config.state_sync_config.clone(),
-                class_manager_client,
-            );
-            (Some(state_sync), Some(state_sync_runner))
-        }
-        ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => {
-            (None, None)
-        }
-    };
-
     let l1_endpoint_monitor = match config.components.l1_endpoint_monitor.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
         | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => Some(
@@ -269,25 +298,17 @@ pub async fn create_node_components(
     let l1_scraper = match config.components.l1_scraper.execution_mode {
         ActiveComponentExecutionMode::Enabled => {
             let l1_provider_client = clients.get_l1_provider_shared_client().unwrap();
-            let l1_endpoint_monitor_client =
-                clients.get_l1_endpoint_monitor_shared_client().unwrap();
             let l1_scraper_config = config.l1_scraper_config.clone();
-            let base_layer = EthereumBaseLayerContract::new(config.base_layer_config.clone());
+            let base_layer = create_unmonitored_base_layer(config, &shared_base_layer);
             let l1_start_block = fetch_start_block(&base_layer, &l1_scraper_config)
                 .await
                 .unwrap_or_else(|err| panic!("Error while initializing the L1 scraper: {err}"));
 
-            let monitored_base_layer = MonitoredEthereumBaseLayer::new(
-                base_layer,
-                l1_endpoint_monitor_client,
-                config.base_layer_config.node_url.clone(),
-            );
-
             Some(
                 L1Scraper::new(
                     l1_scraper_config,
                     l1_provider_client,
-                    monitored_base_layer,
+                    create_base_layer(config, clients, &shared_base_layer),
                     event_identifiers_to_track(),
                     l1_start_block,
                 )
@@ -312,8 +333,7 @@ pub async fn create_node_components(
             match &l1_scraper {
                 Some(l1_scraper) => {
                     let l1_scraper_start_l1_height = l1_scraper.last_l1_block_processed.number;
-                    let base_layer =
-                        EthereumBaseLayerContract::new(config.base_layer_config.clone());
+                    let base_layer = create_unmonitored_base_layer(config, &shared_base_layer);
                     let scraper_synced_startup_height = base_layer
                         .get_proved_block_at(l1_scraper_start_l1_height)
                         .await
@@ -391,20 +411,12 @@ pub async fn create_node_components(
             let l1_gas_price_client = clients
                 .get_l1_gas_price_shared_client()
                 .expect("L1 gas price client should be available");
-            let l1_endpoint_monitor_client =
-                clients.get_l1_endpoint_monitor_shared_client().unwrap();
             let l1_gas_price_scraper_config = config.l1_gas_price_scraper_config.clone();
-            let base_layer = EthereumBaseLayerContract::new(config.base_layer_config.clone());
-            let monitored_base_layer = MonitoredEthereumBaseLayer::new(
-                base_layer,
-                l1_endpoint_monitor_client,
-                config.base_layer_config.node_url.clone(),
-            );
 
             Some(L1GasPriceScraper::new(
                 l1_gas_price_scraper_config,
                 l1_gas_price_client,
-                monitored_base_layer,
+                create_base_layer(config, clients, &shared_base_layer),
             ))
         }
         ActiveComponentExecutionMode::Disabled => None,
@@ -439,3 +451,88 @@ pub async fn create_node_components(
         state_sync_runner,
     }
 }
+
+/// A base layer shared by all the L1 components, so that they see the same simulated chain, or the
+/// same health of the L1 endpoints in quorum reads.
+enum SharedBaseLayer {
+    Quorum(QuorumBaseLayer<EthereumBaseLayerContract>),
+    Simulated(SimulatedBaseLayer),
+}
+
+impl SharedBaseLayer {
+    fn new(config: &SequencerNodeConfig) -> Option<Self> {
+        if let Some(simulated_base_layer_config) = &config.simulated_base_layer_config {
+            return Some(Self::Simulated(SimulatedBaseLayer::new(
+                simulated_base_layer_config.clone(),
+            )));
+        }
+
+        let quorum_base_layer_config = config.quorum_base_layer_config.clone()?;
+        let endpoints = config
+            .l1_endpoint_monitor_config
+            .ordered_l1_endpoint_urls
+            .iter()
+            .map(|node_url| {
+                let base_layer_config = EthereumBaseLayerConfig {
+                    node_url: node_url.clone(),
+                    ..config.base_layer_config.clone()
+                };
+                (node_url.clone(), EthereumBaseLayerContract::new(base_layer_config))
+            })
+            .collect();
+        Some(Self::Quorum(QuorumBaseLayer::new(quorum_base_layer_config, endpoints)))
+    }
+
+    fn configured<B>(&self) -> ConfiguredBaseLayer<B> {
+        match self {
+            Self::Quorum(quorum_base_layer) => {
+                ConfiguredBaseLayer::Quorum(quorum_base_layer.clone())
+            }
+            Self::Simulated(simulated_base_layer) => {
+                ConfiguredBaseLayer::Simulated(simulated_base_layer.clone())
+            }
+        }
+    }
+}
+
+/// Creates the base layer of an L1 component, which switches L1 endpoints through the L1 endpoint
+/// monitor, unless the L1 components share a base layer.
+fn create_base_layer(
+    config: &SequencerNodeConfig,
+    clients: &SequencerNodeClients,
+    shared_base_layer: &Option<SharedBaseLayer>,
+) -> NodeBaseLayer {
+    match shared_base_layer {
+        Some(shared_base_layer) => shared_base_layer.configured(),
+        None => ConfiguredBaseLayer::Ethereum(MonitoredEthereumBaseLayer::new(
+            EthereumBaseLayerContract::new(config.base_layer_config.clone()),
+            clients.get_l1_endpoint_monitor_shared_client().unwrap(),
+            config.base_layer_config.node_url.clone(),
+        )),
+    }
+}
+
+/// Creates a base layer for use while creating the components, when the L1 endpoint monitor isn't
+/// running yet.
+fn create_unmonitored_base_layer(
+    config: &SequencerNodeConfig,
+    shared_base_layer: &Option<SharedBaseLayer>,
+) -> ConfiguredBaseLayer<EthereumBaseLayerContract> {
+    match shared_base_layer {
+        Some(shared_base_layer) => shared_base_layer.configured(),
+        None => ConfiguredBaseLayer::Ethereum(EthereumBaseLayerContract::new(
+            config.base_layer_config.clone(),
+        )),
+    }
+}
+
+/// Creates the admin client of a network of the node, to be served by the monitoring endpoint under
+/// the network's name. Returns the requests of the client, to be given to the network's manager.
+fn add_network_admin_client(
+    network_admin_clients: &mut BTreeMap<String, NetworkAdminClient>,
+    network_name: &str,
+) -> NetworkAdminRequests {
+    let (network_admin_client, network_admin_requests) = create_network_admin_channel();
+    network_admin_clients.insert(network_name.to_string(), network_admin_client);
+    network_admin_requests
+}
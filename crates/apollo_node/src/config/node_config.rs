@@ -10,6 +10,7 @@ use apollo_compile_to_casm::config::SierraCompilationConfig;
 use apollo_config::dumping::{
     generate_struct_pointer,
     prepend_sub_config_name,
+    ser_optional_sub_config,
     ser_pointer_target_param,
     set_pointing_param_paths,
     ConfigPointers,
@@ -31,9 +32,12 @@ use apollo_mempool::config::MempoolConfig;
 use apollo_mempool_p2p::config::MempoolP2pConfig;
 use apollo_monitoring_endpoint::config::MonitoringEndpointConfig;
 use apollo_reverts::RevertConfig;
+use apollo_signature_manager::config::SignatureManagerConfig;
 use apollo_state_sync::config::StateSyncConfig;
 use clap::Command;
 use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerConfig;
+use papyrus_base_layer::quorum_base_layer::QuorumBaseLayerConfig;
+use papyrus_base_layer::simulated_base_layer::SimulatedBaseLayerConfig;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -192,6 +196,14 @@ pub struct SequencerNodeConfig {
     pub mempool_p2p_config: MempoolP2pConfig,
     #[validate]
     pub monitoring_endpoint_config: MonitoringEndpointConfig,
+    /// If set, the L1 components read from a quorum of the `l1_endpoint_monitor_config` endpoints,
+    /// instead of from the active one.
+    #[validate]
+    pub quorum_base_layer_config: Option<QuorumBaseLayerConfig>,
+    /// If set, the L1 components use an in-process simulated L1 instead of `base_layer_config`.
+    pub simulated_base_layer_config: Option<SimulatedBaseLayerConfig>,
+    #[validate]
+    pub signature_manager_config: SignatureManagerConfig,
     #[validate]
     pub state_sync_config: StateSyncConfig,
 }
@@ -217,6 +229,15 @@ impl SerializeConfig for SequencerNodeConfig {
                 self.monitoring_endpoint_config.dump(),
                 "monitoring_endpoint_config",
             ),
+            ser_optional_sub_config(&self.quorum_base_layer_config, "quorum_base_layer_config"),
+            ser_optional_sub_config(
+                &self.simulated_base_layer_config,
+                "simulated_base_layer_config",
+            ),
+            prepend_sub_config_name(
+                self.signature_manager_config.dump(),
+                "signature_manager_config",
+            ),
             prepend_sub_config_name(self.state_sync_config.dump(), "state_sync_config"),
             prepend_sub_config_name(
                 self.l1_endpoint_monitor_config.dump(),
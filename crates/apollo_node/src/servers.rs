@@ -116,12 +116,11 @@ use apollo_state_sync::runner::StateSyncRunnerServer;
 use apollo_state_sync::{LocalStateSyncServer, RemoteStateSyncServer};
 use futures::stream::FuturesUnordered;
 use futures::{Future, FutureExt, StreamExt};
-use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
 use tracing::info;
 
 use crate::clients::SequencerNodeClients;
 use crate::communication::SequencerNodeCommunication;
-use crate::components::SequencerNodeComponents;
+use crate::components::{NodeBaseLayer, SequencerNodeComponents};
 use crate::config::component_execution_config::{
     ActiveComponentExecutionMode,
     ReactiveComponentExecutionMode,
@@ -147,9 +146,8 @@ struct LocalServers {
 struct WrapperServers {
     pub(crate) consensus_manager: Option<Box<ConsensusManagerServer>>,
     pub(crate) http_server: Option<Box<HttpServer>>,
-    pub(crate) l1_scraper_server: Option<Box<L1ScraperServer<EthereumBaseLayerContract>>>,
-    pub(crate) l1_gas_price_scraper_server:
-        Option<Box<L1GasPriceScraperServer<EthereumBaseLayerContract>>>,
+    pub(crate) l1_scraper_server: Option<Box<L1ScraperServer<NodeBaseLayer>>>,
+    pub(crate) l1_gas_price_scraper_server: Option<Box<L1GasPriceScraperServer<NodeBaseLayer>>>,
     pub(crate) monitoring_endpoint: Option<Box<MonitoringEndpointServer>>,
     pub(crate) mempool_p2p_runner: Option<Box<MempoolP2pRunnerServer>>,
     pub(crate) state_sync_runner: Option<Box<StateSyncRunnerServer>>,
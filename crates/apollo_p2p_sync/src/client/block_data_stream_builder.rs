@@ -1,8 +1,8 @@
-use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
 use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_consensus::commit_certificate::CommitCertificateError;
 use apollo_network::network_manager::{ClientResponsesManager, SqmrClientSender};
 use apollo_protobuf::converters::ProtobufConversionError;
 use apollo_protobuf::sync::{BlockHashOrNumber, DataOrFin, Direction, Query};
@@ -13,12 +13,13 @@ use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use async_stream::stream;
 use futures::channel::mpsc::Receiver;
 use futures::future::BoxFuture;
-use futures::stream::BoxStream;
+use futures::stream::{BoxStream, SelectAll};
 use futures::{FutureExt, StreamExt};
-use starknet_api::block::{BlockNumber, BlockSignature};
+use starknet_api::block::{BlockNumber, BlockSignature, CommitteeMember};
 use starknet_api::core::ClassHash;
 use tracing::{debug, info, trace, warn};
 
+use super::range_scheduler::{Chunk, RangeScheduler};
 use super::{P2pSyncClientError, STEP};
 
 pub type BlockDataResult = Result<Box<dyn BlockData>, P2pSyncClientError>;
@@ -53,10 +54,13 @@ where
     // Async functions in trait don't work well with argument references
     /// Parse data for a specific block received from the network and return a future resolving to
     /// an optional block data output or a parse error.
+    ///
+    /// `consensus_committee` is the committee which decides the blocks, if consensus is enabled.
     fn parse_data_for_block<'a>(
         client_response_manager: &'a mut ClientResponsesManager<DataOrFin<InputFromNetwork>>,
         block_number: BlockNumber,
         storage_reader: &'a StorageReader,
+        consensus_committee: Option<&'a [CommitteeMember]>,
     ) -> BoxFuture<'a, Result<Option<Self::Output>, ParseDataError>>;
 
     /// Get the starting block number for this stream.
@@ -97,14 +101,102 @@ where
         .boxed()
     }
 
+    /// Query a single peer for a chunk of blocks and stream the parsed blocks. The last item
+    /// describes how the query ended.
+    fn download_chunk(
+        mut client_response_manager: ClientResponsesManager<DataOrFin<InputFromNetwork>>,
+        mut chunk: Chunk,
+        storage_reader: StorageReader,
+        consensus_committee: Option<Vec<CommitteeMember>>,
+        wait_period_for_new_data: Duration,
+    ) -> BoxStream<'static, Result<ChunkEvent<Self::Output>, P2pSyncClientError>> {
+        stream! {
+            let mut current_block_number = chunk.blocks.start;
+            while current_block_number < chunk.blocks.end {
+                let res = Self::parse_data_for_block(
+                    &mut client_response_manager,
+                    current_block_number,
+                    &storage_reader,
+                    consensus_committee.as_deref(),
+                ).await;
+                match res {
+                    Ok(Some(output)) => {
+                        yield Ok(ChunkEvent::Block(current_block_number, output));
+                        current_block_number = current_block_number.unchecked_next();
+                    }
+                    Ok(None) => {
+                        debug!(
+                            "Query for {:?} on {:?} returned with partial data. Waiting {:?} before \
+                             sending another query.",
+                            Self::TYPE_DESCRIPTION, current_block_number, wait_period_for_new_data
+                        );
+                        tokio::time::sleep(wait_period_for_new_data).await;
+                        yield Ok(ChunkEvent::Finished {
+                            chunk, received_until: current_block_number, reached_missing_data: true
+                        });
+                        return;
+                    },
+                    Err(ParseDataError::BadPeer(err)) => {
+                        warn!(
+                            "Query for {:?} on {:?} returned with bad peer error: {:?}. reporting \
+                             peer and retrying query.",
+                            Self::TYPE_DESCRIPTION, current_block_number, err
+                        );
+                        // The rest of the chunk isn't queried again from the reported peer.
+                        chunk.excluded_peers.extend(client_response_manager.report_peer());
+                        yield Ok(ChunkEvent::Finished {
+                            chunk, received_until: current_block_number, reached_missing_data: false
+                        });
+                        return;
+                    },
+                    Err(ParseDataError::Fatal(err)) => {
+                        yield Err(err);
+                        return;
+                    },
+                }
+            }
+
+            // Consume the None message signaling the end of the query.
+            match client_response_manager.next().await {
+                Some(Ok(DataOrFin(None))) => {
+                    debug!("Network query ending at block {} for {:?} finished", chunk.blocks.end, Self::TYPE_DESCRIPTION);
+                },
+                Some(_) => {
+                    warn!(
+                        "Query for {:?} returned more messages after {:?} even though it \
+                        should have returned Fin. reporting peer.",
+                        Self::TYPE_DESCRIPTION, current_block_number
+                    );
+                    client_response_manager.report_peer();
+                }
+                None => {
+                    warn!(
+                        "Query for {:?} didn't send Fin after block {:?}. Reporting peer.",
+                        Self::TYPE_DESCRIPTION, current_block_number
+                    );
+                    client_response_manager.report_peer();
+                }
+            }
+            yield Ok(ChunkEvent::Finished {
+                chunk, received_until: current_block_number, reached_missing_data: false
+            });
+        }.boxed()
+    }
+
     /// Create a stream for fetching and processing block data.
+    ///
+    /// The missing blocks are split into chunks of `num_blocks_per_query` blocks, and up to
+    /// `max_concurrent_queries` chunks are queried at once, each from the peer the network assigns
+    /// to the query. Blocks are reordered before being returned from the stream.
     fn create_stream<TQuery>(
         mut sqmr_sender: SqmrClientSender<TQuery, DataOrFin<InputFromNetwork>>,
         storage_reader: StorageReader,
+        consensus_committee: Option<Vec<CommitteeMember>>,
         mut internal_block_receiver: Option<Receiver<SyncBlock>>,
         wait_period_for_new_data: Duration,
         wait_period_for_other_protocol: Duration,
         num_blocks_per_query: u64,
+        max_concurrent_queries: usize,
     ) -> BoxStream<'static, BlockDataResult>
     where
         TQuery: From<Query> + Send + 'static,
@@ -113,119 +205,92 @@ where
         stream! {
             let mut current_block_number = Self::get_start_block_number(&storage_reader)?;
             let mut internal_blocks_received = HashMap::new();
-            'send_query_and_parse_responses: loop {
-                let limit = match Self::BLOCK_NUMBER_LIMIT {
-                    BlockNumberLimit::Unlimited => num_blocks_per_query,
-                    BlockNumberLimit::HeaderMarker | BlockNumberLimit::StateDiffMarker => {
-                        let (last_block_number, description) = match Self::BLOCK_NUMBER_LIMIT {
-                            BlockNumberLimit::HeaderMarker => (storage_reader.begin_ro_txn()?.get_header_marker()?, "header"),
-                            BlockNumberLimit::StateDiffMarker => (storage_reader.begin_ro_txn()?.get_state_marker()?, "state diff"),
-                            _ => unreachable!(),
-                        };
-                        let limit = min(last_block_number.0 - current_block_number.0, num_blocks_per_query);
-                        if limit == 0 {
-                            trace!("{:?} sync is waiting for a new {}", Self::TYPE_DESCRIPTION, description);
-                            tokio::time::sleep(wait_period_for_other_protocol).await;
-                            continue;
-                        }
-                        limit
-                    },
-                };
+            let mut scheduler = RangeScheduler::new(
+                current_block_number, num_blocks_per_query, max_concurrent_queries
+            );
+            let mut chunk_downloads = SelectAll::new();
+            // Blocks that were received ahead of `current_block_number`.
+            let mut received_blocks = BTreeMap::new();
+            loop {
+                while let Some(output) = received_blocks.remove(&current_block_number) {
+                    info!("Added {:?} for block {}.", Self::TYPE_DESCRIPTION, current_block_number);
+                    current_block_number = current_block_number.unchecked_next();
+                    yield Ok(Box::<dyn BlockData>::from(Box::new(output)));
+                }
                 if let Some(block) = Self::get_internal_block_at(&mut internal_blocks_received, &mut internal_block_receiver, current_block_number)
                     .now_or_never()
                 {
                     info!("Added internally {:?} for block {}.", Self::TYPE_DESCRIPTION, current_block_number);
-                    yield Ok(Box::<dyn BlockData>::from(Box::new(block)));
                     current_block_number = current_block_number.unchecked_next();
-                    continue 'send_query_and_parse_responses;
+                    scheduler.skip_to(current_block_number);
+                    received_blocks = received_blocks.split_off(&current_block_number);
+                    yield Ok(Box::<dyn BlockData>::from(Box::new(block)));
+                    continue;
                 }
 
-                let end_block_number = current_block_number.0 + limit;
-                debug!(
-                    "Sync sent query for {:?} for blocks [{}, {}) from network.",
-                    Self::TYPE_DESCRIPTION,
-                    current_block_number.0,
-                    end_block_number,
-                );
+                let limit = match Self::BLOCK_NUMBER_LIMIT {
+                    BlockNumberLimit::Unlimited => None,
+                    BlockNumberLimit::HeaderMarker => Some(storage_reader.begin_ro_txn()?.get_header_marker()?),
+                    BlockNumberLimit::StateDiffMarker => Some(storage_reader.begin_ro_txn()?.get_state_marker()?),
+                };
+                while let Some(chunk) = scheduler.next_chunk(limit) {
+                    debug!(
+                        "Sync sent query for {:?} for blocks [{}, {}) from network.",
+                        Self::TYPE_DESCRIPTION,
+                        chunk.blocks.start.0,
+                        chunk.blocks.end.0,
+                    );
+                    let client_response_manager = sqmr_sender
+                        .send_new_query_excluding_peers(
+                            TQuery::from(Query {
+                                start_block: BlockHashOrNumber::Number(chunk.blocks.start),
+                                direction: Direction::Forward,
+                                limit: chunk.blocks.end.0 - chunk.blocks.start.0,
+                                step: STEP,
+                            }),
+                            chunk.excluded_peers.clone(),
+                        ).await?;
+                    chunk_downloads.push(Self::download_chunk(
+                        client_response_manager,
+                        chunk,
+                        storage_reader.clone(),
+                        consensus_committee.clone(),
+                        wait_period_for_new_data,
+                    ));
+                }
+                if chunk_downloads.is_empty() {
+                    let description = match Self::BLOCK_NUMBER_LIMIT {
+                        BlockNumberLimit::StateDiffMarker => "state diff",
+                        _ => "header",
+                    };
+                    trace!("{:?} sync is waiting for a new {}", Self::TYPE_DESCRIPTION, description);
+                    tokio::time::sleep(wait_period_for_other_protocol).await;
+                    continue;
+                }
 
-                // TODO(shahak): Use the report callback.
-                let mut client_response_manager = sqmr_sender
-                    .send_new_query(
-                        TQuery::from(Query {
-                            start_block: BlockHashOrNumber::Number(current_block_number),
-                            direction: Direction::Forward,
-                            limit,
-                            step: STEP,
-                        })
-                    ).await?;
-                while current_block_number.0 < end_block_number {
-                    tokio::select! {
-                        res = Self::parse_data_for_block(
-                            &mut client_response_manager, current_block_number, &storage_reader
-                        ) => {
-                            match res {
-                                Ok(Some(output)) => {
-                                    info!("Added {:?} for block {}.", Self::TYPE_DESCRIPTION, current_block_number);
-                                    current_block_number = current_block_number.unchecked_next();
-                                    yield Ok(Box::<dyn BlockData>::from(Box::new(output)));
+                tokio::select! {
+                    event = chunk_downloads.next() => {
+                        match event.expect("Chunk downloads should not be empty") {
+                            Ok(ChunkEvent::Block(block_number, output)) => {
+                                if block_number >= current_block_number {
+                                    received_blocks.insert(block_number, output);
                                 }
-                                Ok(None) => {
-                                    debug!(
-                                        "Query for {:?} on {:?} returned with partial data. Waiting {:?} before \
-                                         sending another query.",
-                                        Self::TYPE_DESCRIPTION, current_block_number, wait_period_for_new_data
-                                    );
-                                    tokio::time::sleep(wait_period_for_new_data).await;
-                                    continue 'send_query_and_parse_responses;
-                                },
-                                Err(ParseDataError::BadPeer(err)) => {
-                                    warn!(
-                                        "Query for {:?} on {:?} returned with bad peer error: {:?}. reporting \
-                                         peer and retrying query.",
-                                        Self::TYPE_DESCRIPTION, current_block_number, err
-                                    );
-                                    client_response_manager.report_peer();
-                                    continue 'send_query_and_parse_responses;
-                                },
-                                Err(ParseDataError::Fatal(err)) => {
-                                    yield Err(err);
-                                    return;
-                                },
                             }
-                        }
-                        block = Self::get_internal_block_at(&mut internal_blocks_received, &mut internal_block_receiver, current_block_number) => {
-                                info!("Added internally {:?} for block {}.", Self::TYPE_DESCRIPTION, current_block_number);
-                                current_block_number = current_block_number.unchecked_next();
-                                yield Ok(Box::<dyn BlockData>::from(Box::new(block)));
-                                debug!("Network query ending at block {} for {:?} being ignored due to internal block", end_block_number, Self::TYPE_DESCRIPTION);
-                                continue 'send_query_and_parse_responses;
+                            Ok(ChunkEvent::Finished { chunk, received_until, reached_missing_data }) => {
+                                scheduler.finish_chunk(chunk, received_until, reached_missing_data);
+                            }
+                            Err(err) => {
+                                yield Err(err);
+                                return;
                             }
                         }
                     }
-
-                // Consume the None message signaling the end of the query.
-                match client_response_manager.next().await {
-                    Some(Ok(DataOrFin(None))) => {
-                        debug!("Network query ending at block {} for {:?} finished", end_block_number, Self::TYPE_DESCRIPTION);
-                    },
-                    Some(_) => {
-                        warn!(
-                            "Query for {:?} returned more messages after {:?} even though it \
-                            should have returned Fin. reporting peer and retrying query.",
-                            Self::TYPE_DESCRIPTION, current_block_number
-                        );
-                        client_response_manager.report_peer();
-                        continue 'send_query_and_parse_responses;
-                    }
-
-                    None => {
-                        warn!(
-                            "Query for {:?} didn't send Fin after block {:?}. \
-                            Reporting peer and retrying query.",
-                            Self::TYPE_DESCRIPTION, current_block_number
-                        );
-                        client_response_manager.report_peer();
-                        continue 'send_query_and_parse_responses;
+                    block = Self::get_internal_block_at(&mut internal_blocks_received, &mut internal_block_receiver, current_block_number) => {
+                        info!("Added internally {:?} for block {}.", Self::TYPE_DESCRIPTION, current_block_number);
+                        current_block_number = current_block_number.unchecked_next();
+                        scheduler.skip_to(current_block_number);
+                        received_blocks = received_blocks.split_off(&current_block_number);
+                        yield Ok(Box::<dyn BlockData>::from(Box::new(block)));
                     }
                 }
             }
@@ -233,6 +298,12 @@ where
     }
 }
 
+/// An item of the stream returned by [`BlockDataStreamBuilder::download_chunk`].
+pub(crate) enum ChunkEvent<Output> {
+    Block(BlockNumber, Output),
+    Finished { chunk: Chunk, received_until: BlockNumber, reached_missing_data: bool },
+}
+
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum BadPeerError {
     #[error("The sender end of the response receivers for {type_description:?} was closed.")]
@@ -247,6 +318,17 @@ pub(crate) enum BadPeerError {
          {actual} instead."
     )]
     NotEnoughTransactions { expected: usize, actual: usize, block_number: u64 },
+    #[error(
+        "The header says that block {block_number} has {expected} events. Got {actual} events \
+         from the network instead."
+    )]
+    WrongNumberOfEvents { expected: usize, actual: usize, block_number: u64 },
+    #[error("The events of block {block_number} don't match the event commitment in its header.")]
+    EventCommitmentMismatch { block_number: u64 },
+    #[error(
+        "The receipts of block {block_number} don't match the receipt commitment in its header."
+    )]
+    ReceiptCommitmentMismatch { block_number: u64 },
     #[error("Expected to receive one signature from the network. got {signatures:?} instead.")]
     WrongSignaturesLength { signatures: Vec<BlockSignature> },
     #[error(
@@ -271,6 +353,10 @@ pub(crate) enum BadPeerError {
     ClassNotInStateDiff { class_hash: ClassHash },
     #[error("Received two classes with the same hash: {class_hash}.")]
     DuplicateClass { class_hash: ClassHash },
+    #[error("Received an invalid commit certificate: {0}")]
+    InvalidCommitCertificate(CommitCertificateError),
+    #[error("Block {block_number} has no commit certificate.")]
+    MissingCommitCertificate { block_number: BlockNumber },
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -11,7 +11,7 @@ use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt};
 use papyrus_common::pending_classes::ApiContractClass;
-use starknet_api::block::BlockNumber;
+use starknet_api::block::{BlockNumber, CommitteeMember};
 use starknet_api::core::ClassHash;
 use starknet_api::state::{DeclaredClasses, DeprecatedDeclaredClasses};
 use tracing::{trace, warn};
@@ -90,6 +90,7 @@ impl BlockDataStreamBuilder<(ApiContractClass, ClassHash)> for ClassStreamBuilde
         >,
         block_number: BlockNumber,
         storage_reader: &'a StorageReader,
+        _consensus_committee: Option<&'a [CommitteeMember]>,
     ) -> BoxFuture<'a, Result<Option<Self::Output>, ParseDataError>> {
         async move {
             let (target_class_len, declared_classes, deprecated_declared_classes) = {
@@ -183,6 +183,7 @@ async fn class_basic_flow() {
             (DataType::Class, len.try_into().unwrap()),
         ]),
         Some(class_manager_client),
+        None,
         actions,
     )
     .await;
@@ -368,6 +369,7 @@ async fn validate_class_sync_fails(
             (DataType::Class, header_state_diff_lengths.len().try_into().unwrap()),
         ]),
         None,
+        None,
         actions,
     )
     .await;
@@ -1,6 +1,9 @@
 use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_consensus::commit_certificate::verify_commit_certificate;
+use apollo_consensus::types::vote_signing_payload;
 use apollo_network::network_manager::ClientResponsesManager;
 use apollo_protobuf::sync::{DataOrFin, SignedBlockHeader};
+use apollo_signature_manager::signature_manager::verify_precommit_vote_signature;
 use apollo_state_sync_metrics::metrics::{STATE_SYNC_HEADER_LATENCY_SEC, STATE_SYNC_HEADER_MARKER};
 use apollo_state_sync_types::state_sync_types::SyncBlock;
 use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
@@ -8,7 +11,8 @@ use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use chrono::{TimeZone, Utc};
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt};
-use starknet_api::block::{BlockHash, BlockHeader, BlockNumber, BlockSignature};
+use starknet_api::block::{BlockHash, BlockHeader, BlockNumber, BlockSignature, CommitteeMember};
+use starknet_api::block_hash::state_diff_hash::calculate_state_diff_hash;
 use starknet_api::hash::StarkHash;
 use tracing::debug;
 
@@ -29,7 +33,7 @@ impl BlockData for SignedBlockHeader {
         _class_manager_client: &'a mut SharedClassManagerClient,
     ) -> BoxFuture<'a, Result<(), P2pSyncClientError>> {
         async move {
-            storage_writer
+            let txn = storage_writer
                 .begin_rw_txn()?
                 .append_header(
                     self.block_header.block_header_without_hash.block_number,
@@ -44,8 +48,15 @@ impl BlockData for SignedBlockHeader {
                     // The verification that the size of the vector is 1 is done in the data
                     // verification.
                     .expect("Vec::first should return a value on a vector of size 1"),
-                )?
-                .commit()?;
+                )?;
+            let txn = match &self.commit_certificate {
+                Some(commit_certificate) => txn.append_commit_certificate(
+                    self.block_header.block_header_without_hash.block_number,
+                    commit_certificate,
+                )?,
+                None => txn,
+            };
+            txn.commit()?;
             STATE_SYNC_HEADER_MARKER.set_lossy(
                 self.block_header.block_header_without_hash.block_number.unchecked_next().0,
             );
@@ -82,7 +93,8 @@ impl BlockDataStreamBuilder<SignedBlockHeader> for HeaderStreamBuilder {
             DataOrFin<SignedBlockHeader>,
         >,
         block_number: BlockNumber,
-        _storage_reader: &'a StorageReader,
+        storage_reader: &'a StorageReader,
+        consensus_committee: Option<&'a [CommitteeMember]>,
     ) -> BoxFuture<'a, Result<Option<Self::Output>, ParseDataError>> {
         async move {
             let maybe_signed_header = signed_headers_response_manager.next().await.ok_or(
@@ -90,7 +102,7 @@ impl BlockDataStreamBuilder<SignedBlockHeader> for HeaderStreamBuilder {
                     type_description: Self::TYPE_DESCRIPTION,
                 }),
             )?;
-            let Some(signed_block_header) = maybe_signed_header?.0 else {
+            let Some(mut signed_block_header) = maybe_signed_header?.0 else {
                 return Ok(None);
             };
             // TODO(shahak): Check that parent_hash is the same as the previous block's hash
@@ -111,6 +123,30 @@ impl BlockDataStreamBuilder<SignedBlockHeader> for HeaderStreamBuilder {
                     signatures: signed_block_header.signatures,
                 }));
             }
+            let Some(committee) = consensus_committee else {
+                // Without the committee the certificate can't be verified, so it isn't stored.
+                signed_block_header.commit_certificate = None;
+                return Ok(Some(signed_block_header));
+            };
+            let commit_certificate = signed_block_header.commit_certificate.as_ref().ok_or(
+                ParseDataError::BadPeer(BadPeerError::MissingCommitCertificate { block_number }),
+            )?;
+            verify_commit_certificate(
+                commit_certificate,
+                &signed_block_header.block_header,
+                committee,
+                |vote, public_key| {
+                    verify_precommit_vote_signature(
+                        &vote_signing_payload(storage_reader.chain_id(), vote),
+                        vote.signature.clone(),
+                        *public_key,
+                    )
+                    .unwrap_or(false)
+                },
+            )
+            .map_err(|error| {
+                ParseDataError::BadPeer(BadPeerError::InvalidCommitCertificate(error))
+            })?;
             Ok(Some(signed_block_header))
         }
         .boxed()
@@ -129,12 +165,16 @@ impl BlockDataStreamBuilder<SignedBlockHeader> for HeaderStreamBuilder {
             block_header: BlockHeader {
                 block_hash: BlockHash(StarkHash::from(block_number.0)),
                 block_header_without_hash: sync_block.block_header_without_hash,
+                // The proposal commitment consensus decided on, which the commit certificate is
+                // verified against.
+                state_diff_commitment: Some(calculate_state_diff_hash(&sync_block.state_diff)),
                 state_diff_length: Some(sync_block.state_diff.len()),
                 n_transactions: sync_block.account_transaction_hashes.len()
                     + sync_block.l1_transaction_hashes.len(),
                 ..Default::default()
             },
             signatures: vec![BlockSignature::default()],
+            commit_certificate: sync_block.commit_certificate,
         }
     }
 }
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use apollo_consensus::commit_certificate::build_commit_certificate;
+use apollo_consensus::types::vote_signing_payload;
+use apollo_protobuf::consensus::{Vote, VoteType};
 use apollo_protobuf::sync::{
     BlockHashOrNumber,
     DataOrFin,
@@ -8,10 +11,24 @@ use apollo_protobuf::sync::{
     Query,
     SignedBlockHeader,
 };
+use apollo_signature_manager::signature_manager::LocalKeyStore;
+use apollo_signature_manager::SignatureManager;
 use apollo_storage::header::HeaderStorageReader;
 use apollo_test_utils::get_rng;
 use futures::{FutureExt, StreamExt};
-use starknet_api::block::{BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    CommitCertificate,
+    CommitteeMember,
+    PrecommitSignature,
+};
+use starknet_api::core::{ContractAddress, StateDiffCommitment};
+use starknet_api::felt;
+use starknet_api::hash::PoseidonHash;
+use starknet_api::test_utils::CHAIN_ID_FOR_TESTS;
 use tokio::time::timeout;
 
 use super::test_utils::{
@@ -85,6 +102,7 @@ async fn signed_headers_basic_flow() {
                             ..Default::default()
                         },
                         signatures: vec![*block_signature],
+                        commit_certificate: None,
                     })))
                     .await
                     .unwrap();
@@ -156,6 +174,7 @@ async fn sync_sends_new_header_query_if_it_got_partial_responses() {
                         ..Default::default()
                     },
                     signatures: vec![signature],
+                    commit_certificate: None,
                 })))
                 .await
                 .unwrap();
@@ -203,6 +222,7 @@ async fn wrong_block_number() {
     run_test(
         HashMap::from([(DataType::Header, 1)]),
         None,
+        None,
         vec![
             Action::RunP2pSync,
             // We already validate the query content in other tests.
@@ -225,4 +245,122 @@ async fn wrong_block_number() {
     .await;
 }
 
+fn committee() -> Vec<CommitteeMember> {
+    vec![CommitteeMember {
+        address: ContractAddress::from(1_u8),
+        weight: 1,
+        public_key: LocalKeyStore::new_for_testing().public_key,
+    }]
+}
+
+#[tokio::test]
+async fn commit_certificate_is_stored() {
+    let voter = committee()[0].address;
+    let block_hash = BlockHash(felt!("0x1234"));
+    let vote = Vote {
+        vote_type: VoteType::Precommit,
+        height: 0,
+        round: 0,
+        block_hash: Some(block_hash),
+        voter,
+        ..Default::default()
+    };
+    let signature = SignatureManager::new_for_testing()
+        .sign_precommit_vote(vote_signing_payload(&CHAIN_ID_FOR_TESTS, &vote))
+        .await
+        .unwrap();
+    let commit_certificate =
+        build_commit_certificate(block_hash, &[Vote { signature, ..vote }], committee());
+
+    let mut header = random_header(&mut get_rng(), BlockNumber(0), None, None);
+    // The certificate is of the proposal commitment, which is the state diff commitment.
+    header.block_header.state_diff_commitment =
+        Some(StateDiffCommitment(PoseidonHash(block_hash.0)));
+    header.commit_certificate = Some(commit_certificate.clone());
+    run_test(
+        HashMap::from([(DataType::Header, 1)]),
+        None,
+        Some(committee()),
+        vec![
+            Action::RunP2pSync,
+            Action::ReceiveQuery(Box::new(|_query| ()), DataType::Header),
+            Action::SendHeader(DataOrFin(Some(header))),
+            Action::CheckStorage(Box::new(|reader| {
+                async move {
+                    wait_for_marker(
+                        DataType::Header,
+                        &reader,
+                        BlockNumber(1),
+                        SLEEP_DURATION_TO_LET_SYNC_ADVANCE,
+                        TIMEOUT_FOR_TEST,
+                    )
+                    .await;
+                    assert_eq!(
+                        reader
+                            .begin_ro_txn()
+                            .unwrap()
+                            .get_commit_certificate(BlockNumber(0))
+                            .unwrap(),
+                        Some(commit_certificate)
+                    );
+                }
+                .boxed()
+            })),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn invalid_commit_certificate() {
+    let mut header = random_header(&mut get_rng(), BlockNumber(0), None, None);
+    // A precommit from a voter which isn't in the committee.
+    header.commit_certificate = Some(CommitCertificate {
+        precommits: vec![PrecommitSignature::default()],
+        ..Default::default()
+    });
+    run_test(
+        HashMap::from([(DataType::Header, 1)]),
+        None,
+        Some(committee()),
+        vec![
+            Action::RunP2pSync,
+            Action::ReceiveQuery(Box::new(|_query| ()), DataType::Header),
+            Action::SendHeader(DataOrFin(Some(header))),
+            Action::ValidateReportSent(DataType::Header),
+            Action::CheckStorage(Box::new(|reader| {
+                async move {
+                    assert_eq!(0, reader.begin_ro_txn().unwrap().get_header_marker().unwrap().0);
+                }
+                .boxed()
+            })),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn missing_commit_certificate() {
+    // A header without a commit certificate.
+    let header = random_header(&mut get_rng(), BlockNumber(0), None, None);
+    run_test(
+        HashMap::from([(DataType::Header, 1)]),
+        None,
+        Some(committee()),
+        vec![
+            Action::RunP2pSync,
+            Action::ReceiveQuery(Box::new(|_query| ()), DataType::Header),
+            Action::SendHeader(DataOrFin(Some(header))),
+            Action::ValidateReportSent(DataType::Header),
+            Action::CheckStorage(Box::new(|reader| {
+                async move {
+                    assert_eq!(0, reader.begin_ro_txn().unwrap().get_header_marker().unwrap().0);
+                }
+                .boxed()
+            })),
+        ],
+    )
+    .await;
+}
+
 // TODO(shahak): Add more negative tests.
@@ -5,6 +5,9 @@ mod class_test;
 mod header;
 #[cfg(test)]
 mod header_test;
+mod range_scheduler;
+#[cfg(test)]
+mod range_scheduler_test;
 mod state_diff;
 #[cfg(test)]
 mod state_diff_test;
@@ -44,7 +47,7 @@ use futures::{SinkExt as _, Stream};
 use header::HeaderStreamBuilder;
 use papyrus_common::pending_classes::ApiContractClass;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::BlockNumber;
+use starknet_api::block::{BlockNumber, CommitteeMember};
 use starknet_api::core::ClassHash;
 use starknet_api::transaction::FullTransaction;
 use state_diff::StateDiffStreamBuilder;
@@ -62,6 +65,8 @@ pub struct P2pSyncClientConfig {
     pub num_block_state_diffs_per_query: u64,
     pub num_block_transactions_per_query: u64,
     pub num_block_classes_per_query: u64,
+    #[validate(range(min = 1))]
+    pub max_concurrent_queries: usize,
     #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
     pub wait_period_for_new_data: Duration,
     #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
@@ -97,6 +102,13 @@ impl SerializeConfig for P2pSyncClientConfig {
                 "The maximum amount of block's classes to ask from peers in each iteration.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_concurrent_queries",
+                &self.max_concurrent_queries,
+                "The maximum amount of queries for different ranges of blocks that are sent to \
+                 peers concurrently, for each type of block data.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "wait_period_for_new_data",
                 &self.wait_period_for_new_data.as_millis(),
@@ -130,6 +142,7 @@ impl Default for P2pSyncClientConfig {
             num_block_state_diffs_per_query: 100,
             num_block_transactions_per_query: 100,
             num_block_classes_per_query: 100,
+            max_concurrent_queries: 4,
             wait_period_for_new_data: Duration::from_millis(50),
             wait_period_for_other_protocol: Duration::from_millis(50),
             // TODO(eitan): split this by protocol
@@ -179,43 +192,52 @@ impl P2pSyncClientChannels {
     pub(crate) fn create_stream(
         self,
         storage_reader: StorageReader,
+        consensus_committee: Option<Vec<CommitteeMember>>,
         config: P2pSyncClientConfig,
         internal_blocks_receivers: InternalBlocksReceivers,
     ) -> impl Stream<Item = BlockDataResult> + Send + 'static {
         let header_stream = HeaderStreamBuilder::create_stream(
             self.header_sender,
             storage_reader.clone(),
+            consensus_committee.clone(),
             Some(internal_blocks_receivers.header_receiver),
             config.wait_period_for_new_data,
             config.wait_period_for_other_protocol,
             config.num_headers_per_query,
+            config.max_concurrent_queries,
         );
 
         let state_diff_stream = StateDiffStreamBuilder::create_stream(
             self.state_diff_sender,
             storage_reader.clone(),
+            consensus_committee.clone(),
             Some(internal_blocks_receivers.state_diff_receiver),
             config.wait_period_for_new_data,
             config.wait_period_for_other_protocol,
             config.num_block_state_diffs_per_query,
+            config.max_concurrent_queries,
         );
 
         let transaction_stream = TransactionStreamFactory::create_stream(
             self.transaction_sender,
             storage_reader.clone(),
+            consensus_committee.clone(),
             Some(internal_blocks_receivers.transaction_receiver),
             config.wait_period_for_new_data,
             config.wait_period_for_other_protocol,
             config.num_block_transactions_per_query,
+            config.max_concurrent_queries,
         );
 
         let class_stream = ClassStreamBuilder::create_stream(
             self.class_sender,
-            storage_reader.clone(),
+            storage_reader,
+            consensus_committee,
             Some(internal_blocks_receivers.class_receiver),
             config.wait_period_for_new_data,
             config.wait_period_for_other_protocol,
             config.num_block_classes_per_query,
+            config.max_concurrent_queries,
         );
 
         header_stream.merge(state_diff_stream).merge(transaction_stream).merge(class_stream)
@@ -229,6 +251,9 @@ pub struct P2pSyncClient {
     p2p_sync_channels: P2pSyncClientChannels,
     internal_blocks_receiver: BoxStream<'static, SyncBlock>,
     class_manager_client: SharedClassManagerClient,
+    // The committee which decides the blocks if consensus is enabled, in which case the headers
+    // must have a commit certificate from it.
+    consensus_committee: Option<Vec<CommitteeMember>>,
 }
 
 impl P2pSyncClient {
@@ -239,6 +264,7 @@ impl P2pSyncClient {
         p2p_sync_channels: P2pSyncClientChannels,
         internal_blocks_receiver: BoxStream<'static, SyncBlock>,
         class_manager_client: SharedClassManagerClient,
+        consensus_committee: Option<Vec<CommitteeMember>>,
     ) -> Self {
         Self {
             config,
@@ -247,6 +273,7 @@ impl P2pSyncClient {
             p2p_sync_channels,
             internal_blocks_receiver,
             class_manager_client,
+            consensus_committee,
         }
     }
 
@@ -265,9 +292,14 @@ impl P2pSyncClient {
             p2p_sync_channels,
             mut internal_blocks_receiver,
             mut class_manager_client,
+            consensus_committee,
         } = self;
-        let mut data_stream =
-            p2p_sync_channels.create_stream(storage_reader, config, internal_blocks_receivers);
+        let mut data_stream = p2p_sync_channels.create_stream(
+            storage_reader,
+            consensus_committee,
+            config,
+            internal_blocks_receivers,
+        );
 
         loop {
             tokio::select! {
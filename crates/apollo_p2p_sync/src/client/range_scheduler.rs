@@ -0,0 +1,132 @@
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use apollo_network_types::network_types::OpaquePeerId;
+use starknet_api::block::BlockNumber;
+
+/// A range of blocks to query, and the peers that shouldn't be queried for it since they failed to
+/// provide some of its blocks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Chunk {
+    pub blocks: Range<BlockNumber>,
+    pub excluded_peers: Vec<OpaquePeerId>,
+}
+
+/// Splits the blocks a sync protocol is missing into chunks that are queried concurrently.
+///
+/// Parts of chunks that weren't received are queried again before any new chunk, so that the
+/// lowest missing blocks, which block writing to the storage, always have priority. A part of a
+/// chunk is queried again only from peers that didn't fail on it, if there are such peers.
+pub(crate) struct RangeScheduler {
+    chunk_size: u64,
+    max_concurrent_queries: usize,
+    num_in_flight: usize,
+    // Blocks below this one were obtained and don't need to be queried.
+    first_needed: BlockNumber,
+    // The first block that wasn't assigned to any chunk.
+    next_unassigned: BlockNumber,
+    // Parts of chunks that should be queried again, from start to end and the peers excluded from
+    // querying them.
+    retries: BTreeMap<BlockNumber, (BlockNumber, Vec<OpaquePeerId>)>,
+    // The peers excluded from querying the chunk that starts at `next_unassigned`.
+    next_unassigned_excluded_peers: Vec<OpaquePeerId>,
+    // The block at which a peer last ran out of data. While set, the sync is likely at the tip of
+    // the chain and chunks above this block aren't queried since they probably don't exist yet.
+    missing_data_at: Option<BlockNumber>,
+}
+
+impl RangeScheduler {
+    pub fn new(start: BlockNumber, chunk_size: u64, max_concurrent_queries: usize) -> Self {
+        Self {
+            chunk_size,
+            max_concurrent_queries,
+            num_in_flight: 0,
+            first_needed: start,
+            next_unassigned: start,
+            retries: BTreeMap::new(),
+            next_unassigned_excluded_peers: Vec::new(),
+            missing_data_at: None,
+        }
+    }
+
+    /// Returns the next chunk to query, or None if all the queries are in flight or there's
+    /// nothing to query. New chunks end no later than `limit`, if given.
+    pub fn next_chunk(&mut self, limit: Option<BlockNumber>) -> Option<Chunk> {
+        if self.num_in_flight >= self.max_concurrent_queries {
+            return None;
+        }
+        let chunk = match self.retries.pop_first() {
+            Some((start, (end, excluded_peers))) => Chunk { blocks: start..end, excluded_peers },
+            None => {
+                let start = self.next_unassigned;
+                if self.missing_data_at.is_some_and(|missing_data_at| start > missing_data_at) {
+                    return None;
+                }
+                let mut end = BlockNumber(start.0 + self.chunk_size);
+                if let Some(limit) = limit {
+                    end = min(end, limit);
+                }
+                if end <= start {
+                    return None;
+                }
+                self.next_unassigned = end;
+                Chunk {
+                    blocks: start..end,
+                    excluded_peers: std::mem::take(&mut self.next_unassigned_excluded_peers),
+                }
+            }
+        };
+        self.num_in_flight += 1;
+        Some(chunk)
+    }
+
+    /// Records that the query for `chunk` ended after receiving the blocks up to `received_until`
+    /// (exclusive). `reached_missing_data` indicates that the peer didn't have the next block. The
+    /// excluded peers of the chunk, including the peer that failed on it if it was reported, are
+    /// excluded from querying the rest of it.
+    pub fn finish_chunk(
+        &mut self,
+        chunk: Chunk,
+        received_until: BlockNumber,
+        reached_missing_data: bool,
+    ) {
+        let Chunk { blocks: chunk, excluded_peers } = chunk;
+        self.num_in_flight -= 1;
+        if received_until >= chunk.end {
+            if self.missing_data_at.is_some_and(|missing_data_at| chunk.end > missing_data_at) {
+                self.missing_data_at = None;
+            }
+            return;
+        }
+        if reached_missing_data {
+            self.missing_data_at = Some(received_until);
+        }
+        let retry_start = max(received_until, self.first_needed);
+        if chunk.end == self.next_unassigned {
+            // This is the highest chunk, so instead of retrying only its remainder, a full chunk
+            // is queried from the first block that wasn't received.
+            self.next_unassigned = retry_start;
+            self.next_unassigned_excluded_peers = excluded_peers;
+        } else if retry_start < chunk.end {
+            self.retries.insert(retry_start, (chunk.end, excluded_peers));
+        }
+    }
+
+    /// Records that all the blocks below `block_number` were obtained without querying them.
+    pub fn skip_to(&mut self, block_number: BlockNumber) {
+        self.first_needed = max(self.first_needed, block_number);
+        if block_number > self.next_unassigned {
+            self.next_unassigned = block_number;
+            self.next_unassigned_excluded_peers.clear();
+        }
+        self.retries = std::mem::take(&mut self.retries)
+            .into_iter()
+            .filter(|(_, (end, _))| *end > block_number)
+            .map(|(start, (end, excluded_peers))| (max(start, block_number), (end, excluded_peers)))
+            .collect();
+        if self.missing_data_at.is_some_and(|missing_data_at| missing_data_at < block_number) {
+            self.missing_data_at = None;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use apollo_network_types::network_types::OpaquePeerId;
+use apollo_test_utils::{get_rng, GetTestInstance};
+use starknet_api::block::BlockNumber;
+
+use super::range_scheduler::{Chunk, RangeScheduler};
+
+const CHUNK_SIZE: u64 = 10;
+const MAX_CONCURRENT_QUERIES: usize = 3;
+
+fn chunk(start: u64, end: u64) -> Chunk {
+    Chunk { blocks: BlockNumber(start)..BlockNumber(end), excluded_peers: Vec::new() }
+}
+
+fn chunk_excluding(start: u64, end: u64, excluded_peers: &[OpaquePeerId]) -> Chunk {
+    Chunk { excluded_peers: excluded_peers.to_vec(), ..chunk(start, end) }
+}
+
+#[test]
+fn assigns_consecutive_chunks_up_to_concurrency() {
+    let mut scheduler = RangeScheduler::new(BlockNumber(5), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(5, 15)));
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(15, 25)));
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(25, 35)));
+    assert_eq!(scheduler.next_chunk(None), None);
+
+    scheduler.finish_chunk(chunk(15, 25), BlockNumber(25), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(35, 45)));
+}
+
+#[test]
+fn chunks_respect_limit() {
+    let mut scheduler = RangeScheduler::new(BlockNumber(0), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+
+    assert_eq!(scheduler.next_chunk(Some(BlockNumber(13))), Some(chunk(0, 10)));
+    assert_eq!(scheduler.next_chunk(Some(BlockNumber(13))), Some(chunk(10, 13)));
+    assert_eq!(scheduler.next_chunk(Some(BlockNumber(13))), None);
+    assert_eq!(scheduler.next_chunk(Some(BlockNumber(20))), Some(chunk(13, 20)));
+}
+
+#[test]
+fn failed_chunks_are_retried_first() {
+    let mut scheduler = RangeScheduler::new(BlockNumber(0), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+    for _ in 0..MAX_CONCURRENT_QUERIES {
+        scheduler.next_chunk(None).unwrap();
+    }
+
+    // A bad peer in the middle chunk.
+    scheduler.finish_chunk(chunk(10, 20), BlockNumber(14), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(14, 20)));
+    assert_eq!(scheduler.next_chunk(None), None);
+
+    // The highest chunk is queried again in full.
+    scheduler.finish_chunk(chunk(20, 30), BlockNumber(22), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(22, 32)));
+}
+
+#[test]
+fn no_new_chunks_above_missing_data() {
+    let mut scheduler = RangeScheduler::new(BlockNumber(0), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+    for _ in 0..MAX_CONCURRENT_QUERIES {
+        scheduler.next_chunk(None).unwrap();
+    }
+
+    // The peers don't have blocks from 25 onwards.
+    scheduler.finish_chunk(chunk(20, 30), BlockNumber(25), true);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(25, 35)));
+    scheduler.finish_chunk(chunk(0, 10), BlockNumber(10), false);
+    assert_eq!(scheduler.next_chunk(None), None);
+
+    // Once the chunk above the missing data is received, the scheduler moves on.
+    scheduler.finish_chunk(chunk(25, 35), BlockNumber(35), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(35, 45)));
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(45, 55)));
+}
+
+#[test]
+fn skipped_blocks_are_not_retried() {
+    let mut scheduler = RangeScheduler::new(BlockNumber(0), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+    for _ in 0..MAX_CONCURRENT_QUERIES {
+        scheduler.next_chunk(None).unwrap();
+    }
+    scheduler.finish_chunk(chunk(0, 10), BlockNumber(2), true);
+
+    // The blocks up to 16 were received internally.
+    scheduler.skip_to(BlockNumber(16));
+    scheduler.finish_chunk(chunk(10, 20), BlockNumber(12), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(16, 20)));
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(30, 40)));
+}
+
+#[test]
+fn failed_chunks_are_retried_without_failed_peers() {
+    let mut rng = get_rng();
+    let bad_peer = OpaquePeerId::get_test_instance(&mut rng);
+    let other_bad_peer = OpaquePeerId::get_test_instance(&mut rng);
+    let mut scheduler = RangeScheduler::new(BlockNumber(0), CHUNK_SIZE, MAX_CONCURRENT_QUERIES);
+    for _ in 0..MAX_CONCURRENT_QUERIES {
+        scheduler.next_chunk(None).unwrap();
+    }
+
+    // A peer failed in the middle chunk.
+    scheduler.finish_chunk(chunk_excluding(10, 20, &[bad_peer.clone()]), BlockNumber(14), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk_excluding(14, 20, &[bad_peer.clone()])));
+
+    // Another peer failed on the rest of it.
+    scheduler.finish_chunk(
+        chunk_excluding(14, 20, &[bad_peer.clone(), other_bad_peer.clone()]),
+        BlockNumber(16),
+        false,
+    );
+    assert_eq!(
+        scheduler.next_chunk(None),
+        Some(chunk_excluding(16, 20, &[bad_peer.clone(), other_bad_peer]))
+    );
+
+    // A peer failed in the highest chunk, which is queried again in full.
+    scheduler.finish_chunk(chunk_excluding(20, 30, &[bad_peer.clone()]), BlockNumber(22), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk_excluding(22, 32, &[bad_peer])));
+    scheduler.finish_chunk(chunk(22, 32), BlockNumber(32), false);
+    assert_eq!(scheduler.next_chunk(None), Some(chunk(32, 42)));
+}
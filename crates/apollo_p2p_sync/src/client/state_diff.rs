@@ -11,7 +11,7 @@ use apollo_storage::state::{StateStorageReader, StateStorageWriter};
 use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt};
-use starknet_api::block::BlockNumber;
+use starknet_api::block::{BlockNumber, CommitteeMember};
 use starknet_api::state::ThinStateDiff;
 
 use super::block_data_stream_builder::BadPeerError;
@@ -54,6 +54,7 @@ impl BlockDataStreamBuilder<StateDiffChunk> for StateDiffStreamBuilder {
         >,
         block_number: BlockNumber,
         storage_reader: &'a StorageReader,
+        _consensus_committee: Option<&'a [CommitteeMember]>,
     ) -> BoxFuture<'a, Result<Option<Self::Output>, ParseDataError>> {
         async move {
             let mut result = ThinStateDiff::default();
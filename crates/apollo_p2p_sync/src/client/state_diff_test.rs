@@ -196,6 +196,7 @@ async fn state_diff_basic_flow() {
             (DataType::StateDiff, state_diffs_and_chunks.len().try_into().unwrap()),
         ]),
         None,
+        None,
         actions,
     )
     .await;
@@ -362,6 +363,7 @@ async fn validate_state_diff_fails(
             (DataType::StateDiff, header_state_diff_lengths.len().try_into().unwrap()),
         ]),
         None,
+        None,
         actions,
     )
     .await;
@@ -316,5 +316,6 @@ fn create_random_sync_block(
         account_transaction_hashes,
         l1_transaction_hashes,
         block_header_without_hash,
+        commit_certificate: None,
     }
 }
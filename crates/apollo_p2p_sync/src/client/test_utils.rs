@@ -41,6 +41,7 @@ use starknet_api::block::{
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockSignature,
+    CommitteeMember,
 };
 use starknet_api::core::ClassHash;
 use starknet_api::crypto::utils::Signature;
@@ -69,6 +70,8 @@ lazy_static! {
         num_block_state_diffs_per_query: STATE_DIFF_QUERY_LENGTH,
         num_block_transactions_per_query: TRANSACTION_QUERY_LENGTH,
         num_block_classes_per_query: CLASS_DIFF_QUERY_LENGTH,
+        // The tests expect the queries of each type to be sent one after the other.
+        max_concurrent_queries: 1,
         wait_period_for_new_data: WAIT_PERIOD_FOR_NEW_DATA,
         wait_period_for_other_protocol: WAIT_PERIOD_FOR_OTHER_PROTOCOL,
         buffer_size: BUFFER_SIZE,
@@ -122,6 +125,7 @@ pub fn setup() -> TestArgs {
         p2p_sync_channels,
         futures::stream::pending().boxed(),
         class_manager_client,
+        None,
     );
     TestArgs {
         p2p_sync,
@@ -178,6 +182,7 @@ pub enum Action {
 pub async fn run_test(
     max_query_lengths: HashMap<DataType, u64>,
     class_manager_client: Option<MockClassManagerClient>,
+    consensus_committee: Option<Vec<CommitteeMember>>,
     actions: Vec<Action>,
 ) {
     let p2p_sync_config = P2pSyncClientConfig {
@@ -191,6 +196,7 @@ pub async fn run_test(
             .cloned()
             .unwrap_or(1),
         num_block_classes_per_query: max_query_lengths.get(&DataType::Class).cloned().unwrap_or(1),
+        max_concurrent_queries: 1,
         wait_period_for_new_data: WAIT_PERIOD_FOR_NEW_DATA,
         wait_period_for_other_protocol: WAIT_PERIOD_FOR_OTHER_PROTOCOL,
         buffer_size: BUFFER_SIZE,
@@ -219,6 +225,7 @@ pub async fn run_test(
         p2p_sync_channels,
         internal_block_receiver.boxed(),
         class_manager_client,
+        consensus_committee,
     );
 
     let mut headers_current_query_responses_manager = None;
@@ -360,6 +367,9 @@ pub fn random_header(
             },
             state_diff_length: Some(state_diff_length.unwrap_or_else(|| rng.gen())),
             n_transactions: num_transactions.unwrap_or_else(|| rng.gen()),
+            // Random commitments would fail the validation of the received transactions.
+            event_commitment: None,
+            receipt_commitment: None,
             ..GetTestInstance::get_test_instance(rng)
         },
         // TODO(shahak): Remove this once get_test_instance puts random values.
@@ -367,6 +377,7 @@ pub fn random_header(
             r: rng.next_u64().into(),
             s: rng.next_u64().into(),
         })],
+        commit_certificate: None,
     }
 }
 
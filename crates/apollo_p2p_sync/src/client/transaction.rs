@@ -11,9 +11,13 @@ use apollo_storage::header::HeaderStorageReader;
 use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use futures::future::BoxFuture;
 use futures::{FutureExt, StreamExt};
-use starknet_api::block::{BlockBody, BlockNumber};
+use starknet_api::block::{BlockBody, BlockHeader, BlockNumber, CommitteeMember};
+use starknet_api::block_hash::block_hash_calculator::{BlockHashVersion, TransactionOutputForHash};
+use starknet_api::block_hash::event_commitment::{calculate_event_commitment, EventLeafElement};
+use starknet_api::block_hash::receipt_commitment::{calculate_receipt_commitment, ReceiptElement};
 use starknet_api::test_utils::invoke::{invoke_tx, InvokeTxArgs};
 use starknet_api::transaction::{FullTransaction, Transaction, TransactionOutput};
+use starknet_types_core::hash::Poseidon;
 
 use super::block_data_stream_builder::{
     BadPeerError,
@@ -42,10 +46,66 @@ impl BlockData for (BlockBody, BlockNumber) {
     }
 }
 
+/// Validates the events and receipts of the block against the commitments in its header.
+///
+/// Blocks older than 0.13.2 don't have commitments that are computed this way, so they aren't
+/// validated.
+fn validate_receipts(header: &BlockHeader, block_body: &BlockBody) -> Result<(), BadPeerError> {
+    if header.block_header_without_hash.starknet_version < BlockHashVersion::V0_13_2.into() {
+        return Ok(());
+    }
+    let block_number = header.block_header_without_hash.block_number.0;
+
+    if let Some(event_commitment) = header.event_commitment {
+        let event_leaf_elements = block_body
+            .transaction_outputs
+            .iter()
+            .zip(block_body.transaction_hashes.iter())
+            .flat_map(|(transaction_output, transaction_hash)| {
+                transaction_output.events().iter().map(|event| EventLeafElement {
+                    event: event.clone(),
+                    transaction_hash: *transaction_hash,
+                })
+            })
+            .collect::<Vec<_>>();
+        if event_leaf_elements.len() != header.n_events {
+            return Err(BadPeerError::WrongNumberOfEvents {
+                expected: header.n_events,
+                actual: event_leaf_elements.len(),
+                block_number,
+            });
+        }
+        if calculate_event_commitment::<Poseidon>(&event_leaf_elements) != event_commitment {
+            return Err(BadPeerError::EventCommitmentMismatch { block_number });
+        }
+    }
+
+    if let Some(receipt_commitment) = header.receipt_commitment {
+        let receipt_elements = block_body
+            .transaction_outputs
+            .iter()
+            .zip(block_body.transaction_hashes.iter())
+            .map(|(transaction_output, transaction_hash)| ReceiptElement {
+                transaction_hash: *transaction_hash,
+                transaction_output: TransactionOutputForHash {
+                    actual_fee: transaction_output.actual_fee(),
+                    events: transaction_output.events().to_vec(),
+                    execution_status: transaction_output.execution_status().clone(),
+                    gas_consumed: transaction_output.execution_resources().gas_consumed,
+                    messages_sent: transaction_output.messages_sent().clone(),
+                },
+            })
+            .collect::<Vec<_>>();
+        if calculate_receipt_commitment::<Poseidon>(&receipt_elements) != receipt_commitment {
+            return Err(BadPeerError::ReceiptCommitmentMismatch { block_number });
+        }
+    }
+    Ok(())
+}
+
 pub(crate) struct TransactionStreamFactory;
 
 impl BlockDataStreamBuilder<FullTransaction> for TransactionStreamFactory {
-    // TODO(Eitan): Add events protocol to BlockBody or split their write to storage
     type Output = (BlockBody, BlockNumber);
 
     const TYPE_DESCRIPTION: &'static str = "transactions";
@@ -55,15 +115,16 @@ impl BlockDataStreamBuilder<FullTransaction> for TransactionStreamFactory {
         transactions_response_manager: &'a mut ClientResponsesManager<DataOrFin<FullTransaction>>,
         block_number: BlockNumber,
         storage_reader: &'a StorageReader,
+        _consensus_committee: Option<&'a [CommitteeMember]>,
     ) -> BoxFuture<'a, Result<Option<Self::Output>, ParseDataError>> {
         async move {
             let mut block_body = BlockBody::default();
             let mut current_transaction_len = 0;
-            let target_transaction_len = storage_reader
+            let header = storage_reader
                 .begin_ro_txn()?
                 .get_block_header(block_number)?
-                .expect("A header with number lower than the header marker is missing")
-                .n_transactions;
+                .expect("A header with number lower than the header marker is missing");
+            let target_transaction_len = header.n_transactions;
             while current_transaction_len < target_transaction_len {
                 let maybe_transaction = transactions_response_manager.next().await.ok_or(
                     ParseDataError::BadPeer(BadPeerError::SessionEndedWithoutFin {
@@ -89,6 +150,7 @@ impl BlockDataStreamBuilder<FullTransaction> for TransactionStreamFactory {
                 block_body.transaction_hashes.push(transaction_hash);
                 current_transaction_len += 1;
             }
+            validate_receipts(&header, &block_body)?;
             Ok(Some((block_body, block_number)))
         }
         .boxed()
@@ -5,7 +5,8 @@ use apollo_protobuf::sync::{BlockHashOrNumber, DataOrFin, Direction, Query};
 use apollo_storage::body::BodyStorageReader;
 use apollo_test_utils::{get_rng, get_test_body};
 use futures::FutureExt;
-use starknet_api::block::{BlockBody, BlockNumber};
+use starknet_api::block::{BlockBody, BlockNumber, StarknetVersion};
+use starknet_api::core::EventCommitment;
 use starknet_api::transaction::{FullTransaction, TransactionHash};
 
 use super::test_utils::{
@@ -162,7 +163,42 @@ async fn transaction_basic_flow() {
             (DataType::Transaction, TRANSACTION_QUERY_LENGTH),
         ]),
         None,
+        None,
         actions,
     )
     .await;
 }
+
+#[tokio::test]
+async fn wrong_event_commitment() {
+    let mut rng = get_rng();
+    let block_body = get_test_body(1, Some(1), None, None);
+
+    let mut header = random_header(&mut rng, BlockNumber(0), None, Some(1));
+    header.block_header.block_header_without_hash.starknet_version = StarknetVersion::V0_13_2;
+    header.block_header.n_events = 1;
+    header.block_header.event_commitment = Some(EventCommitment::default());
+
+    let BlockBody { transactions, transaction_outputs, transaction_hashes } = block_body;
+    run_test(
+        HashMap::from([(DataType::Header, 1), (DataType::Transaction, 1)]),
+        None,
+        None,
+        vec![
+            Action::RunP2pSync,
+            // We already validate the query content in other tests.
+            Action::ReceiveQuery(Box::new(|_query| ()), DataType::Header),
+            Action::SendHeader(DataOrFin(Some(header))),
+            Action::SendHeader(DataOrFin(None)),
+            Action::ReceiveQuery(Box::new(|_query| ()), DataType::Transaction),
+            Action::SendTransaction(DataOrFin(Some(FullTransaction {
+                transaction: transactions[0].clone(),
+                transaction_output: transaction_outputs[0].clone(),
+                transaction_hash: transaction_hashes[0],
+            }))),
+            // We validate the report is sent before we send fin.
+            Action::ValidateReportSent(DataType::Transaction),
+        ],
+    )
+    .await;
+}
@@ -239,7 +239,12 @@ impl FetchBlockData for SignedBlockHeader {
         let signature = txn
             .get_block_signature(block_number)?
             .ok_or(P2pSyncServerError::SignatureNotFound { block_number })?;
-        Ok(vec![SignedBlockHeader { block_header: header, signatures: vec![signature] }])
+        let commit_certificate = txn.get_commit_certificate(block_number)?;
+        Ok(vec![SignedBlockHeader {
+            block_header: header,
+            signatures: vec![signature],
+            commit_certificate,
+        }])
     }
 }
 
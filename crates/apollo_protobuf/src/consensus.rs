@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber, GasPrice};
 use starknet_api::consensus_transaction::ConsensusTransaction;
 use starknet_api::core::ContractAddress;
+use starknet_api::crypto::utils::RawSignature;
 use starknet_api::data_availability::L1DataAvailabilityMode;
 
 use crate::converters::ProtobufConversionError;
@@ -34,6 +35,9 @@ pub struct Vote {
     pub round: u32,
     pub block_hash: Option<BlockHash>,
     pub voter: ContractAddress,
+    /// The voter's signature on the vote, signed with this field left empty. Empty for unsigned
+    /// votes.
+    pub signature: RawSignature,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -50,7 +54,7 @@ pub struct StreamMessage<T: IntoFromProto, StreamId: IntoFromProto + Clone> {
 }
 
 /// This message must be sent first when proposing a new block.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ProposalInit {
     /// The height of the consensus (block number).
     pub height: BlockNumber,
@@ -139,6 +143,21 @@ impl TryInto<ProposalInit> for ProposalPart {
     }
 }
 
+impl TryInto<ProposalFin> for ProposalPart {
+    type Error = ProtobufConversionError;
+
+    fn try_into(self: ProposalPart) -> Result<ProposalFin, Self::Error> {
+        match self {
+            ProposalPart::Fin(fin) => Ok(fin),
+            _ => Err(ProtobufConversionError::WrongEnumVariant {
+                type_description: "ProposalPart",
+                expected: "Fin",
+                value_as_str: format!("{self:?}"),
+            }),
+        }
+    }
+}
+
 impl From<ProposalInit> for ProposalPart {
     fn from(value: ProposalInit) -> Self {
         ProposalPart::Init(value)
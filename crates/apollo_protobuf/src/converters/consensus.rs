@@ -7,7 +7,9 @@ use std::convert::{TryFrom, TryInto};
 use prost::Message;
 use starknet_api::block::{BlockHash, BlockNumber, GasPrice};
 use starknet_api::consensus_transaction::ConsensusTransaction;
+use starknet_api::crypto::utils::RawSignature;
 use starknet_api::hash::StarkHash;
+use starknet_types_core::felt::Felt;
 
 use super::common::{
     enum_int_to_l1_data_availability_mode,
@@ -60,8 +62,11 @@ impl TryFrom<protobuf::Vote> for Vote {
         let block_hash: Option<BlockHash> =
             value.block_hash.map(|block_hash| block_hash.try_into()).transpose()?.map(BlockHash);
         let voter = value.voter.ok_or(missing("voter"))?.try_into()?;
+        let signature = RawSignature(
+            value.signature.into_iter().map(Felt::try_from).collect::<Result<Vec<_>, _>>()?,
+        );
 
-        Ok(Vote { vote_type, height, round, block_hash, voter })
+        Ok(Vote { vote_type, height, round, block_hash, voter, signature })
     }
 }
 
@@ -78,6 +83,7 @@ impl From<Vote> for protobuf::Vote {
             round: value.round,
             block_hash: value.block_hash.map(|hash| hash.0.into()),
             voter: Some(value.voter.into()),
+            signature: value.signature.0.into_iter().map(Into::into).collect(),
         }
     }
 }
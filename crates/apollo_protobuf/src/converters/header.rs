@@ -8,8 +8,10 @@ use starknet_api::block::{
     BlockHeader,
     BlockHeaderWithoutHash,
     BlockNumber,
-    BlockSignature,
+    CommitCertificate,
+    CommitteeMember,
     GasPricePerToken,
+    PrecommitSignature,
     StarknetVersion,
 };
 use starknet_api::core::{
@@ -20,8 +22,9 @@ use starknet_api::core::{
     StateDiffCommitment,
     TransactionCommitment,
 };
-use starknet_api::crypto::utils::Signature;
+use starknet_api::crypto::utils::{PublicKey, RawSignature, Signature};
 use starknet_api::hash::PoseidonHash;
+use starknet_types_core::felt::Felt;
 
 use super::common::{
     enum_int_to_l1_data_availability_mode,
@@ -189,12 +192,20 @@ impl TryFrom<protobuf::SignedBlockHeader> for SignedBlockHeader {
             .transpose()?
             .map(|hash| StateDiffCommitment(PoseidonHash(hash)));
 
+        let block_number = BlockNumber(value.number);
+        let commit_certificate = value
+            .commit_certificate
+            .map(|commit_certificate| {
+                commit_certificate_from_protobuf(commit_certificate, block_number)
+            })
+            .transpose()?;
+
         Ok(SignedBlockHeader {
             block_header: BlockHeader {
                 block_hash,
                 block_header_without_hash: BlockHeaderWithoutHash {
                     parent_hash,
-                    block_number: BlockNumber(value.number),
+                    block_number,
                     l1_gas_price,
                     l1_data_gas_price,
                     l2_gas_price,
@@ -220,6 +231,7 @@ impl TryFrom<protobuf::SignedBlockHeader> for SignedBlockHeader {
                 .into_iter()
                 .map(starknet_api::block::BlockSignature::try_from)
                 .collect::<Result<Vec<_>, _>>()?,
+            commit_certificate,
         })
     }
 }
@@ -230,8 +242,9 @@ impl From<DataOrFin<SignedBlockHeader>> for protobuf::BlockHeadersResponse {
     }
 }
 
-impl From<(BlockHeader, Vec<BlockSignature>)> for protobuf::SignedBlockHeader {
-    fn from((header, signatures): (BlockHeader, Vec<BlockSignature>)) -> Self {
+impl From<SignedBlockHeader> for protobuf::SignedBlockHeader {
+    fn from(value: SignedBlockHeader) -> Self {
+        let SignedBlockHeader { block_header: header, signatures, commit_certificate } = value;
         let state_diff_commitment =
             header.state_diff_length.map(|state_diff_length| protobuf::StateDiffCommitment {
                 state_diff_length: state_diff_length
@@ -287,6 +300,7 @@ impl From<(BlockHeader, Vec<BlockSignature>)> for protobuf::SignedBlockHeader {
             l2_gas_consumed: header.block_header_without_hash.l2_gas_consumed.0,
             next_l2_gas_price: Some(header.block_header_without_hash.next_l2_gas_price.0.into()),
             signatures: signatures.iter().map(|signature| (*signature).into()).collect(),
+            commit_certificate: commit_certificate.map(Into::into),
         }
     }
 }
@@ -307,16 +321,92 @@ impl From<starknet_api::block::BlockSignature> for protobuf::ConsensusSignature
     }
 }
 
+// The block number of the certificate isn't sent, since it's the number of the enclosing header.
+fn commit_certificate_from_protobuf(
+    value: protobuf::CommitCertificate,
+    block_number: BlockNumber,
+) -> Result<CommitCertificate, ProtobufConversionError> {
+    let block_hash = value
+        .block_hash
+        .ok_or(missing("CommitCertificate::block_hash"))?
+        .try_into()
+        .map(BlockHash)?;
+    let committee = value
+        .committee
+        .into_iter()
+        .map(CommitteeMember::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let precommits = value
+        .precommits
+        .into_iter()
+        .map(PrecommitSignature::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CommitCertificate { block_number, round: value.round, block_hash, committee, precommits })
+}
+
+impl From<CommitCertificate> for protobuf::CommitCertificate {
+    fn from(value: CommitCertificate) -> Self {
+        Self {
+            round: value.round,
+            block_hash: Some(value.block_hash.into()),
+            committee: value.committee.into_iter().map(Into::into).collect(),
+            precommits: value.precommits.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<protobuf::CommitteeMember> for CommitteeMember {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::CommitteeMember) -> Result<Self, Self::Error> {
+        Ok(Self {
+            address: value.address.ok_or(missing("CommitteeMember::address"))?.try_into()?,
+            weight: value.weight.ok_or(missing("CommitteeMember::weight"))?.into(),
+            public_key: PublicKey(
+                value.public_key.ok_or(missing("CommitteeMember::public_key"))?.try_into()?,
+            ),
+        })
+    }
+}
+
+impl From<CommitteeMember> for protobuf::CommitteeMember {
+    fn from(value: CommitteeMember) -> Self {
+        Self {
+            address: Some(value.address.into()),
+            weight: Some(value.weight.into()),
+            public_key: Some(value.public_key.0.into()),
+        }
+    }
+}
+
+impl TryFrom<protobuf::PrecommitSignature> for PrecommitSignature {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::PrecommitSignature) -> Result<Self, Self::Error> {
+        Ok(Self {
+            voter: value.voter.ok_or(missing("PrecommitSignature::voter"))?.try_into()?,
+            signature: RawSignature(
+                value.signature.into_iter().map(Felt::try_from).collect::<Result<Vec<_>, _>>()?,
+            ),
+        })
+    }
+}
+
+impl From<PrecommitSignature> for protobuf::PrecommitSignature {
+    fn from(value: PrecommitSignature) -> Self {
+        Self {
+            voter: Some(value.voter.into()),
+            signature: value.signature.0.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl From<Option<SignedBlockHeader>> for protobuf::BlockHeadersResponse {
     fn from(data: Option<SignedBlockHeader>) -> Self {
         match data {
-            Some(SignedBlockHeader { block_header, signatures }) => {
-                protobuf::BlockHeadersResponse {
-                    header_message: Some(protobuf::block_headers_response::HeaderMessage::Header(
-                        (block_header, signatures).into(),
-                    )),
-                }
-            }
+            Some(signed_block_header) => protobuf::BlockHeadersResponse {
+                header_message: Some(protobuf::block_headers_response::HeaderMessage::Header(
+                    signed_block_header.into(),
+                )),
+            },
             None => protobuf::BlockHeadersResponse {
                 header_message: Some(protobuf::block_headers_response::HeaderMessage::Fin(
                     protobuf::Fin {},
@@ -1,11 +1,20 @@
 use apollo_test_utils::{get_rng, GetTestInstance};
+use rand_chacha::ChaCha8Rng;
 
 use crate::sync::{DataOrFin, HeaderQuery, SignedBlockHeader};
 
+// The block number of the commit certificate isn't sent, and is taken from the header.
+fn signed_block_header(rng: &mut ChaCha8Rng) -> SignedBlockHeader {
+    let mut signed_block_header = SignedBlockHeader::get_test_instance(rng);
+    let block_number = signed_block_header.block_header.block_header_without_hash.block_number;
+    signed_block_header.commit_certificate.as_mut().unwrap().block_number = block_number;
+    signed_block_header
+}
+
 #[test]
 fn block_header_to_bytes_and_back() {
     let mut rng = get_rng();
-    let signed_block_header = SignedBlockHeader::get_test_instance(&mut rng);
+    let signed_block_header = signed_block_header(&mut rng);
     let data = DataOrFin(Some(signed_block_header.clone()));
     let bytes_data = Vec::<u8>::from(data.clone());
     let res_data = DataOrFin::try_from(bytes_data).unwrap();
@@ -15,11 +24,12 @@ fn block_header_to_bytes_and_back() {
 #[test]
 fn block_header_without_commitments_to_bytes_and_back() {
     let mut rng = get_rng();
-    let mut signed_block_header = SignedBlockHeader::get_test_instance(&mut rng);
+    let mut signed_block_header = signed_block_header(&mut rng);
     signed_block_header.block_header.state_diff_commitment = None;
     signed_block_header.block_header.transaction_commitment = None;
     signed_block_header.block_header.event_commitment = None;
     signed_block_header.block_header.receipt_commitment = None;
+    signed_block_header.commit_certificate = None;
 
     let data = DataOrFin(Some(signed_block_header.clone()));
     let bytes_data = Vec::<u8>::from(data.clone());
@@ -7,6 +7,10 @@ use starknet_api::transaction::{
     DeclareTransactionOutput,
     DeployAccountTransactionOutput,
     DeployTransactionOutput,
+    Event,
+    EventContent,
+    EventData,
+    EventKey,
     InvokeTransactionOutput,
     L1HandlerTransactionOutput,
     L2ToL1Payload,
@@ -69,15 +73,12 @@ impl From<TransactionOutput> for protobuf::Receipt {
     }
 }
 
-// The output will have an empty events vec
 impl TryFrom<protobuf::receipt::DeployAccount> for DeployAccountTransactionOutput {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::receipt::DeployAccount) -> Result<Self, Self::Error> {
-        let (actual_fee, messages_sent, execution_status, execution_resources) =
+        let (actual_fee, messages_sent, events, execution_status, execution_resources) =
             parse_common_receipt_fields(value.common)?;
 
-        let events = vec![];
-
         let contract_address =
             value.contract_address.ok_or(missing("DeployAccount::contract_address"))?;
         let felt = Felt::try_from(contract_address)?;
@@ -106,6 +107,7 @@ impl From<DeployAccountTransactionOutput> for protobuf::receipt::DeployAccount {
         let common = create_proto_receipt_common_from_txn_output_fields(
             value.actual_fee,
             value.messages_sent,
+            value.events,
             value.execution_resources,
             value.execution_status,
         );
@@ -117,15 +119,12 @@ impl From<DeployAccountTransactionOutput> for protobuf::receipt::DeployAccount {
     }
 }
 
-// The output will have an empty events vec
 impl TryFrom<protobuf::receipt::Deploy> for DeployTransactionOutput {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::receipt::Deploy) -> Result<Self, Self::Error> {
-        let (actual_fee, messages_sent, execution_status, execution_resources) =
+        let (actual_fee, messages_sent, events, execution_status, execution_resources) =
             parse_common_receipt_fields(value.common)?;
 
-        let events = vec![];
-
         let contract_address = value.contract_address.ok_or(missing("Deploy::contract_address"))?;
         let felt = Felt::try_from(contract_address)?;
         let contract_address = ContractAddress(PatriciaKey::try_from(felt).map_err(|_| {
@@ -153,6 +152,7 @@ impl From<DeployTransactionOutput> for protobuf::receipt::Deploy {
         let common = create_proto_receipt_common_from_txn_output_fields(
             value.actual_fee,
             value.messages_sent,
+            value.events,
             value.execution_resources,
             value.execution_status,
         );
@@ -164,15 +164,12 @@ impl From<DeployTransactionOutput> for protobuf::receipt::Deploy {
     }
 }
 
-// The output will have an empty events vec
 impl TryFrom<protobuf::receipt::Declare> for DeclareTransactionOutput {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::receipt::Declare) -> Result<Self, Self::Error> {
-        let (actual_fee, messages_sent, execution_status, execution_resources) =
+        let (actual_fee, messages_sent, events, execution_status, execution_resources) =
             parse_common_receipt_fields(value.common)?;
 
-        let events = vec![];
-
         Ok(Self { actual_fee, messages_sent, events, execution_status, execution_resources })
     }
 }
@@ -184,6 +181,7 @@ impl From<DeclareTransactionOutput> for protobuf::receipt::Declare {
         let common = create_proto_receipt_common_from_txn_output_fields(
             value.actual_fee,
             value.messages_sent,
+            value.events,
             value.execution_resources,
             value.execution_status,
         );
@@ -192,15 +190,12 @@ impl From<DeclareTransactionOutput> for protobuf::receipt::Declare {
     }
 }
 
-// The output will have an empty events vec
 impl TryFrom<protobuf::receipt::Invoke> for InvokeTransactionOutput {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::receipt::Invoke) -> Result<Self, Self::Error> {
-        let (actual_fee, messages_sent, execution_status, execution_resources) =
+        let (actual_fee, messages_sent, events, execution_status, execution_resources) =
             parse_common_receipt_fields(value.common)?;
 
-        let events = vec![];
-
         Ok(Self { actual_fee, messages_sent, events, execution_status, execution_resources })
     }
 }
@@ -212,6 +207,7 @@ impl From<InvokeTransactionOutput> for protobuf::receipt::Invoke {
         let common = create_proto_receipt_common_from_txn_output_fields(
             value.actual_fee,
             value.messages_sent,
+            value.events,
             value.execution_resources,
             value.execution_status,
         );
@@ -220,15 +216,12 @@ impl From<InvokeTransactionOutput> for protobuf::receipt::Invoke {
     }
 }
 
-// The output will have an empty events vec
 impl TryFrom<protobuf::receipt::L1Handler> for L1HandlerTransactionOutput {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::receipt::L1Handler) -> Result<Self, Self::Error> {
-        let (actual_fee, messages_sent, execution_status, execution_resources) =
+        let (actual_fee, messages_sent, events, execution_status, execution_resources) =
             parse_common_receipt_fields(value.common)?;
 
-        let events = vec![];
-
         Ok(Self { actual_fee, messages_sent, events, execution_status, execution_resources })
     }
 }
@@ -240,6 +233,7 @@ impl From<L1HandlerTransactionOutput> for protobuf::receipt::L1Handler {
         let common = create_proto_receipt_common_from_txn_output_fields(
             value.actual_fee,
             value.messages_sent,
+            value.events,
             value.execution_resources,
             value.execution_status,
         );
@@ -253,15 +247,25 @@ type ProtobufBuiltinCounter = protobuf::receipt::execution_resources::BuiltinCou
 impl TryFrom<ProtobufBuiltinCounter> for HashMap<Builtin, u64> {
     type Error = ProtobufConversionError;
     fn try_from(value: ProtobufBuiltinCounter) -> Result<Self, Self::Error> {
-        let mut builtin_instance_counter = HashMap::new();
-        builtin_instance_counter.insert(Builtin::RangeCheck, u64::from(value.range_check));
-        builtin_instance_counter.insert(Builtin::Pedersen, u64::from(value.pedersen));
-        builtin_instance_counter.insert(Builtin::Poseidon, u64::from(value.poseidon));
-        builtin_instance_counter.insert(Builtin::EcOp, u64::from(value.ec_op));
-        builtin_instance_counter.insert(Builtin::Ecdsa, u64::from(value.ecdsa));
-        builtin_instance_counter.insert(Builtin::Bitwise, u64::from(value.bitwise));
-        builtin_instance_counter.insert(Builtin::Keccak, u64::from(value.keccak));
-        builtin_instance_counter.insert(Builtin::SegmentArena, 0);
+        // Builtins that weren't used are omitted. The output builtin isn't part of the execution
+        // resources.
+        let builtin_instance_counter = [
+            (Builtin::RangeCheck, value.range_check),
+            (Builtin::Pedersen, value.pedersen),
+            (Builtin::Poseidon, value.poseidon),
+            (Builtin::EcOp, value.ec_op),
+            (Builtin::Ecdsa, value.ecdsa),
+            (Builtin::Bitwise, value.bitwise),
+            (Builtin::Keccak, value.keccak),
+            (Builtin::SegmentArena, value.segment_arena),
+            (Builtin::AddMod, value.add_mod),
+            (Builtin::MulMod, value.mul_mod),
+            (Builtin::RangeCheck96, value.range_check96),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count != 0)
+        .map(|(builtin, count)| (builtin, u64::from(count)))
+        .collect();
         Ok(builtin_instance_counter)
     }
 }
@@ -285,6 +289,14 @@ impl From<HashMap<Builtin, u64>> for ProtobufBuiltinCounter {
             keccak: u32::try_from(*value.get(&Builtin::Keccak).unwrap_or(&0))
                 .expect("Failed to convert u64 to u32"),
             output: 0,
+            segment_arena: u32::try_from(*value.get(&Builtin::SegmentArena).unwrap_or(&0))
+                .expect("Failed to convert u64 to u32"),
+            add_mod: u32::try_from(*value.get(&Builtin::AddMod).unwrap_or(&0))
+                .expect("Failed to convert u64 to u32"),
+            mul_mod: u32::try_from(*value.get(&Builtin::MulMod).unwrap_or(&0))
+                .expect("Failed to convert u64 to u32"),
+            range_check96: u32::try_from(*value.get(&Builtin::RangeCheck96).unwrap_or(&0))
+                .expect("Failed to convert u64 to u32"),
         };
         builtin_counter
     }
@@ -407,12 +419,47 @@ impl From<MessageToL1> for protobuf::MessageToL1 {
     }
 }
 
+impl TryFrom<protobuf::receipt::Event> for Event {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::receipt::Event) -> Result<Self, Self::Error> {
+        let from_address_felt =
+            Felt::try_from(value.from_address.ok_or(missing("Receipt::Event::from_address"))?)?;
+        let from_address =
+            ContractAddress(PatriciaKey::try_from(from_address_felt).map_err(|_| {
+                ProtobufConversionError::OutOfRangeValue {
+                    type_description: "PatriciaKey",
+                    value_as_str: format!("{from_address_felt:?}"),
+                }
+            })?);
+
+        let keys = value
+            .keys
+            .into_iter()
+            .map(|key| Felt::try_from(key).map(EventKey))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let data =
+            EventData(value.data.into_iter().map(Felt::try_from).collect::<Result<Vec<_>, _>>()?);
+
+        Ok(Event { from_address, content: EventContent { keys, data } })
+    }
+}
+
+impl From<Event> for protobuf::receipt::Event {
+    fn from(value: Event) -> Self {
+        let from_address = Some(Felt::from(value.from_address).into());
+        let keys = value.content.keys.into_iter().map(|key| key.0.into()).collect();
+        let data = value.content.data.0.into_iter().map(protobuf::Felt252::from).collect();
+        Self { from_address, keys, data }
+    }
+}
+
+type CommonReceiptFields =
+    (Fee, Vec<MessageToL1>, Vec<Event>, TransactionExecutionStatus, ExecutionResources);
+
 fn parse_common_receipt_fields(
     common: Option<protobuf::receipt::Common>,
-) -> Result<
-    (Fee, Vec<MessageToL1>, TransactionExecutionStatus, ExecutionResources),
-    ProtobufConversionError,
-> {
+) -> Result<CommonReceiptFields, ProtobufConversionError> {
     let common = common.ok_or(missing("Common"))?;
     let actual_fee_felt = Felt::try_from(common.actual_fee.ok_or(missing("Common::actual_fee"))?)?;
     let actual_fee = Fee(try_from_starkfelt_to_u128(actual_fee_felt).map_err(|_| {
@@ -426,6 +473,7 @@ fn parse_common_receipt_fields(
         .into_iter()
         .map(MessageToL1::try_from)
         .collect::<Result<Vec<_>, _>>()?;
+    let events = common.events.into_iter().map(Event::try_from).collect::<Result<Vec<_>, _>>()?;
     let execution_status =
         common.revert_reason.map_or(TransactionExecutionStatus::Succeeded, |revert_reason| {
             TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
@@ -435,17 +483,19 @@ fn parse_common_receipt_fields(
     let execution_resources = ExecutionResources::try_from(
         common.execution_resources.ok_or(missing("Common::execution_resources"))?,
     )?;
-    Ok((actual_fee, messages_sent, execution_status, execution_resources))
+    Ok((actual_fee, messages_sent, events, execution_status, execution_resources))
 }
 
 fn create_proto_receipt_common_from_txn_output_fields(
     actual_fee: Fee,
     messages_sent: Vec<MessageToL1>,
+    events: Vec<Event>,
     execution_resources: ExecutionResources,
     execution_status: TransactionExecutionStatus,
 ) -> protobuf::receipt::Common {
     let actual_fee = Felt::from(actual_fee).into();
     let messages_sent = messages_sent.into_iter().map(protobuf::MessageToL1::from).collect();
+    let events = events.into_iter().map(protobuf::receipt::Event::from).collect();
     let execution_resources = execution_resources.into();
     let revert_reason =
         if let TransactionExecutionStatus::Reverted(reverted_status) = execution_status {
@@ -459,5 +509,6 @@ fn create_proto_receipt_common_from_txn_output_fields(
         messages_sent,
         execution_resources: Some(execution_resources),
         revert_reason,
+        events,
     }
 }
@@ -26,7 +26,6 @@ macro_rules! create_transaction_output {
         let mut rng = get_rng();
         let mut transaction_output = <$tx_output_type>::get_test_instance(&mut rng);
         transaction_output.execution_resources = EXECUTION_RESOURCES.clone();
-        transaction_output.events = vec![];
         TransactionOutput::$tx_output_enum_variant(transaction_output)
     }};
 }
@@ -188,7 +187,10 @@ lazy_static! {
             (Builtin::Ecdsa, 5),
             (Builtin::Bitwise, 6),
             (Builtin::Keccak, 7),
-            (Builtin::SegmentArena, 0),
+            (Builtin::SegmentArena, 8),
+            (Builtin::AddMod, 9),
+            (Builtin::MulMod, 10),
+            (Builtin::RangeCheck96, 11),
         ]),
         memory_holes: 0,
         da_gas_consumed: GasVector::default(),
@@ -371,6 +371,9 @@ pub struct Vote {
     pub block_hash: ::core::option::Option<Hash>,
     #[prost(message, optional, tag = "6")]
     pub voter: ::core::option::Option<Address>,
+    /// The voter's signature on the vote, with this field left empty.
+    #[prost(message, repeated, tag = "7")]
+    pub signature: ::prost::alloc::vec::Vec<Felt252>,
 }
 /// Nested message and enum types in `Vote`.
 pub mod vote {
@@ -635,9 +638,16 @@ pub mod receipt {
             pub poseidon: u32,
             #[prost(uint32, tag = "7")]
             pub keccak: u32,
-            /// TODO(alonl): add the missing builtins
             #[prost(uint32, tag = "8")]
             pub output: u32,
+            #[prost(uint32, tag = "9")]
+            pub segment_arena: u32,
+            #[prost(uint32, tag = "10")]
+            pub add_mod: u32,
+            #[prost(uint32, tag = "11")]
+            pub mul_mod: u32,
+            #[prost(uint32, tag = "12")]
+            pub range_check96: u32,
         }
         /// TODO(alonl): remove GasVector and unsplit gas_consumed and da_gas_consumed
         #[allow(clippy::derive_partial_eq_without_eq)]
@@ -651,6 +661,18 @@ pub mod receipt {
             pub l2_gas: u64,
         }
     }
+    /// An event emitted by the transaction. The transaction hash is omitted since it's the hash of
+    /// the receipt's transaction.
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Event {
+        #[prost(message, optional, tag = "1")]
+        pub from_address: ::core::option::Option<super::Felt252>,
+        #[prost(message, repeated, tag = "2")]
+        pub keys: ::prost::alloc::vec::Vec<super::Felt252>,
+        #[prost(message, repeated, tag = "3")]
+        pub data: ::prost::alloc::vec::Vec<super::Felt252>,
+    }
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Common {
@@ -664,6 +686,8 @@ pub mod receipt {
         pub execution_resources: ::core::option::Option<ExecutionResources>,
         #[prost(string, optional, tag = "6")]
         pub revert_reason: ::core::option::Option<::prost::alloc::string::String>,
+        #[prost(message, repeated, tag = "7")]
+        pub events: ::prost::alloc::vec::Vec<Event>,
     }
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1079,6 +1103,41 @@ pub struct SignedBlockHeader {
     /// can be more explicit here about the signature structure as this is not part of account abstraction
     #[prost(message, repeated, tag = "21")]
     pub signatures: ::prost::alloc::vec::Vec<ConsensusSignature>,
+    /// Proof that the block was decided by the consensus committee. Absent for blocks not decided by consensus.
+    #[prost(message, optional, tag = "22")]
+    pub commit_certificate: ::core::option::Option<CommitCertificate>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommitteeMember {
+    #[prost(message, optional, tag = "1")]
+    pub address: ::core::option::Option<Address>,
+    #[prost(message, optional, tag = "2")]
+    pub weight: ::core::option::Option<Uint128>,
+    #[prost(message, optional, tag = "3")]
+    pub public_key: ::core::option::Option<Felt252>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrecommitSignature {
+    #[prost(message, optional, tag = "1")]
+    pub voter: ::core::option::Option<Address>,
+    #[prost(message, repeated, tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<Felt252>,
+}
+/// The precommits of a quorum of the committee for the block. The block number is the one of the enclosing header.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommitCertificate {
+    #[prost(uint32, tag = "1")]
+    pub round: u32,
+    /// The proposal commitment the committee voted on.
+    #[prost(message, optional, tag = "2")]
+    pub block_hash: ::core::option::Option<Hash>,
+    #[prost(message, repeated, tag = "3")]
+    pub committee: ::prost::alloc::vec::Vec<CommitteeMember>,
+    #[prost(message, repeated, tag = "4")]
+    pub precommits: ::prost::alloc::vec::Vec<PrecommitSignature>,
 }
 /// sent to all peers (except the ones this was received from, if any).
 /// for a fraction of peers, also send the GetBlockHeaders response (as if they asked for it for this block)
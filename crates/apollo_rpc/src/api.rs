@@ -13,7 +13,9 @@ use starknet_api::core::{ChainId, ContractAddress, EntryPointSelector};
 use starknet_api::transaction::fields::Calldata;
 use tokio::sync::RwLock;
 
+use crate::l1_messages::SharedL1MessagesReader;
 use crate::v0_8::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_8Impl;
+use crate::v0_9::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_9Impl;
 use crate::version_config;
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -64,6 +66,7 @@ pub fn get_methods_from_supported_apis(
     pending_classes: Arc<RwLock<PendingClasses>>,
     starknet_writer: Arc<dyn StarknetWriter>,
     class_manager_client: Option<SharedClassManagerClient>,
+    l1_messages_reader: Option<SharedL1MessagesReader>,
 ) -> Methods {
     let mut methods: Methods = Methods::new();
     let server_gen = JsonRpcServerImplGenerator {
@@ -78,6 +81,7 @@ pub fn get_methods_from_supported_apis(
         pending_classes,
         starknet_writer,
         class_manager_client,
+        l1_messages_reader,
     };
     version_config::VERSION_CONFIG
         .iter()
@@ -90,6 +94,9 @@ pub fn get_methods_from_supported_apis(
                         version_config::VERSION_0_8 => {
                             server_gen.clone().generator::<JsonRpcServerV0_8Impl>()
                         }
+                        version_config::VERSION_0_9 => {
+                            server_gen.clone().generator::<JsonRpcServerV0_9Impl>()
+                        }
                         // TODO(yair): remove this once the version is an enum instead of a string.
                         _ => unreachable!("Unrecognized RPC spec version: {}", version),
                     };
@@ -118,6 +125,7 @@ pub trait JsonRpcServerTrait: Sized {
         pending_classes: Arc<RwLock<PendingClasses>>,
         starknet_writer: Arc<dyn StarknetWriter>,
         class_manager_client: Option<SharedClassManagerClient>,
+        l1_messages_reader: Option<SharedL1MessagesReader>,
     ) -> Self;
 
     fn into_rpc_module(self) -> RpcModule<Self>;
@@ -137,6 +145,7 @@ struct JsonRpcServerImplGenerator {
     // TODO(shahak): Change this struct to be with a generic type of StarknetWriter.
     starknet_writer: Arc<dyn StarknetWriter>,
     class_manager_client: Option<SharedClassManagerClient>,
+    l1_messages_reader: Option<SharedL1MessagesReader>,
 }
 
 type JsonRpcServerImplParams = (
@@ -151,6 +160,7 @@ type JsonRpcServerImplParams = (
     Arc<RwLock<PendingClasses>>,
     Arc<dyn StarknetWriter>,
     Option<SharedClassManagerClient>,
+    Option<SharedL1MessagesReader>,
 );
 
 impl JsonRpcServerImplGenerator {
@@ -167,6 +177,7 @@ impl JsonRpcServerImplGenerator {
             self.pending_classes,
             self.starknet_writer,
             self.class_manager_client,
+            self.l1_messages_reader,
         )
     }
 
@@ -186,6 +197,7 @@ impl JsonRpcServerImplGenerator {
             pending_classes,
             starknet_writer,
             class_manager_client,
+            l1_messages_reader,
         ) = self.get_params();
         Into::<Methods>::into(
             T::new(
@@ -200,6 +212,7 @@ impl JsonRpcServerImplGenerator {
                 pending_classes,
                 starknet_writer,
                 class_manager_client,
+                l1_messages_reader,
             )
             .into_rpc_module(),
         )
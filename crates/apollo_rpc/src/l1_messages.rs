@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use papyrus_base_layer::constants::LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER;
+use papyrus_base_layer::{BaseLayerContract, L1Event, L1TxHash};
+use starknet_api::transaction::L1HandlerTransaction;
+
+/// Reads the messages that L1 transactions sent to L2. Hides the error type of the base layer, so
+/// that the RPC server isn't generic over it.
+#[async_trait]
+pub(crate) trait L1MessagesReader: Send + Sync {
+    /// Returns the L1 handler transactions of the messages to L2 that were sent in the given L1
+    /// transaction, in the order they were sent.
+    async fn get_messages_to_l2(
+        &self,
+        l1_tx_hash: L1TxHash,
+    ) -> anyhow::Result<Vec<L1HandlerTransaction>>;
+}
+
+#[async_trait]
+impl<B> L1MessagesReader for B
+where
+    B: BaseLayerContract + Send + Sync,
+    B::Error: 'static,
+{
+    async fn get_messages_to_l2(
+        &self,
+        l1_tx_hash: L1TxHash,
+    ) -> anyhow::Result<Vec<L1HandlerTransaction>> {
+        let events =
+            self.transaction_events(l1_tx_hash, &[LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER]).await?;
+        Ok(events
+            .into_iter()
+            .filter_map(|event| match event {
+                L1Event::LogMessageToL2 { tx, .. } => Some(tx),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+pub(crate) type SharedL1MessagesReader = Arc<dyn L1MessagesReader>;
@@ -3,18 +3,22 @@
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 
 mod api;
+mod l1_messages;
 mod middleware;
 mod pending;
 mod rpc_metrics;
 #[cfg(test)]
 mod rpc_test;
+mod subscription_manager;
 mod syncing_state;
 #[cfg(test)]
 mod test_utils;
 mod v0_8;
+mod v0_9;
 mod version_config;
 
 use std::collections::BTreeMap;
+use std::future::Future;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
@@ -37,12 +41,14 @@ use jsonrpsee::types::error::ErrorCode::InternalError;
 use jsonrpsee::types::error::INTERNAL_ERROR_MSG;
 use jsonrpsee::types::ErrorObjectOwned;
 pub use latest::error;
+use papyrus_base_layer::configured_base_layer::ConfiguredBaseLayer;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
 use papyrus_common::pending_classes::PendingClasses;
 use rpc_metrics::MetricLogger;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHashAndNumber, BlockNumber, BlockStatus};
 use starknet_api::core::ChainId;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tracing::{debug, error, info, instrument};
 // Aliasing the latest version of the RPC.
 use v0_8 as latest;
@@ -50,8 +56,11 @@ pub use v0_8::api::CompiledContractClass;
 use validator::Validate;
 
 use crate::api::get_methods_from_supported_apis;
+use crate::l1_messages::SharedL1MessagesReader;
 use crate::middleware::proxy_rpc_request;
+use crate::subscription_manager::{ActiveSubscriptions, SubscriptionManager};
 use crate::syncing_state::get_last_synced_block;
+use crate::v0_8::subscriptions::{subscriptions_module, SubscriptionsContext};
 pub use crate::v0_8::transaction::{
     InvokeTransaction as InvokeTransactionRPC0_8,
     InvokeTransactionV1 as InvokeTransactionV1RPC0_8,
@@ -75,6 +84,7 @@ pub struct RpcConfig {
     pub port: u16,
     pub max_events_chunk_size: usize,
     pub max_events_keys: usize,
+    pub max_subscriptions_per_connection: u32,
     // TODO(lev,shahak): remove once we remove papyrus.
     pub collect_metrics: bool,
     pub starknet_url: String,
@@ -90,6 +100,7 @@ impl Default for RpcConfig {
             port: 8090,
             max_events_chunk_size: 1000,
             max_events_keys: 100,
+            max_subscriptions_per_connection: 100,
             collect_metrics: false,
             starknet_url: String::from("https://alpha-mainnet.starknet.io/"),
             apollo_gateway_retry_config: RetryConfig {
@@ -134,6 +145,12 @@ impl SerializeConfig for RpcConfig {
                 "Maximum number of keys supported by the node in get_events requests.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_subscriptions_per_connection",
+                &self.max_subscriptions_per_connection,
+                "Maximum number of open subscriptions in a single WebSocket connection.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "collect_metrics",
                 &self.collect_metrics,
@@ -209,27 +226,34 @@ fn get_block_status<Mode: TransactionKind>(
 #[derive(Clone, Debug, PartialEq)]
 struct ContinuationTokenAsStruct(EventIndex);
 
-#[instrument(skip(storage_reader, class_manager_client), level = "debug", err)]
+#[instrument(skip(storage_reader, class_manager_client, base_layer), level = "debug", err)]
 pub async fn run_server(
     config: &RpcConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Receiver<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage_reader: StorageReader,
     node_version: &'static str,
     class_manager_client: Option<SharedClassManagerClient>,
+    base_layer: Option<ConfiguredBaseLayer<EthereumBaseLayerContract>>,
 ) -> anyhow::Result<(SocketAddr, ServerHandle)> {
     let starting_block = get_last_synced_block(storage_reader.clone())?;
     debug!("Starting JSON-RPC.");
-    let methods = get_methods_from_supported_apis(
+    let subscription_manager = SubscriptionManager::new(
+        storage_reader.clone(),
+        pending_data.clone(),
+        pending_data_updates,
+    )?;
+    let mut methods = get_methods_from_supported_apis(
         &config.chain_id,
         config.execution_config,
-        storage_reader,
+        storage_reader.clone(),
         config.max_events_chunk_size,
         config.max_events_keys,
         starting_block,
         shared_highest_block,
-        pending_data,
+        pending_data.clone(),
         pending_classes,
         Arc::new(StarknetGatewayClient::new(
             &config.starknet_url,
@@ -237,11 +261,20 @@ pub async fn run_server(
             config.apollo_gateway_retry_config,
         )?),
         class_manager_client,
+        base_layer.map(|base_layer| Arc::new(base_layer) as SharedL1MessagesReader),
     );
+    methods.merge(subscriptions_module(SubscriptionsContext {
+        storage_reader,
+        pending_data,
+        notifications_sender: subscription_manager.notifications_sender(),
+        active_subscriptions: ActiveSubscriptions::default(),
+        max_events_keys: config.max_events_keys,
+    }))?;
     let addr;
     let handle;
     let server_builder = ServerBuilder::default()
         .max_request_body_size(SERVER_MAX_BODY_SIZE)
+        .max_subscriptions_per_connection(config.max_subscriptions_per_connection)
         .set_middleware(tower::ServiceBuilder::new().filter_async(proxy_rpc_request));
 
     let server_address = SocketAddr::new(config.ip, config.port);
@@ -256,6 +289,18 @@ pub async fn run_server(
         addr = server.local_addr()?;
         handle = server.start(methods);
     }
+    spawn_until_stopped(&handle, subscription_manager.run());
     info!(local_address = %addr, "JSON-RPC is running.");
     Ok((addr, handle))
 }
+
+// Spawns a task of the server, which runs until it ends or the server stops.
+fn spawn_until_stopped(handle: &ServerHandle, task: impl Future<Output = ()> + Send + 'static) {
+    let server_stopped = handle.clone().stopped();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = task => {}
+            _ = server_stopped => {}
+        }
+    });
+}
@@ -1,16 +1,21 @@
+#[cfg(test)]
+#[path = "middleware_test.rs"]
+mod middleware_test;
+
 use hyper::{Body, Request};
 use jsonrpsee::core::http_helpers::read_body;
 use regex::Regex;
 use tower::BoxError;
 use tracing::debug;
 
-use crate::version_config::{VERSION_0_8, VERSION_PATTERN};
+use crate::version_config::{VersionState, DEFAULT_VERSION, VERSION_CONFIG, VERSION_PATTERN};
 use crate::SERVER_MAX_BODY_SIZE;
 
-/// [`Tower`] middleware intended to proxy method requests to the last supported version of the API,
-/// which is V0_8. The middleware reads the JsonRPC request body and request path then prefixes the
-/// method name with the appropriate version identifier. It returns a new [`hyper::Request`] object
-/// with the new method name.
+/// [`Tower`] middleware intended to proxy method requests to the version of the API given in the
+/// request path (e.g. "/rpc/v0_9"), or to the default version if the path is "/rpc". The middleware
+/// reads the JsonRPC request body and request path then prefixes the method name with the
+/// appropriate version identifier. It returns a new [`hyper::Request`] object with the new method
+/// name.
 ///
 /// # Arguments
 /// * req - [`hyper::Request`] object passed by the server.
@@ -24,7 +29,13 @@ pub(crate) async fn proxy_rpc_request(req: Request<Body>) -> Result<Request<Body
         return Err(BoxError::from("Unsupported path for request"));
     }
 
-    let prefix = VERSION_0_8.name;
+    // The messages of WebSocket connections don't pass through the middleware, so the
+    // subscription methods aren't versioned.
+    if is_websocket_upgrade(&req) {
+        return Ok(req);
+    }
+
+    let prefix = get_version_name_from_path(req.uri().path())?;
     let (parts, body) = req.into_parts();
     let (body_bytes, is_single) =
         read_body(&parts.headers, body, SERVER_MAX_BODY_SIZE).await.map_err(BoxError::from)?;
@@ -74,6 +85,30 @@ fn strip_starknet_from_method(method: &str) -> Option<&str> {
     split_method_name.get(1).copied()
 }
 
+fn is_websocket_upgrade(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(hyper::header::UPGRADE)
+        .and_then(|upgrade| upgrade.to_str().ok())
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+/// Returns the name of the supported version that the path points to. Accepts the version both with
+/// and without its patch (e.g. "/rpc/v0_9" and "/rpc/V0_9_0").
+fn get_version_name_from_path(path: &str) -> Result<&'static str, BoxError> {
+    let Some(requested_version) = path.strip_prefix("/rpc/") else {
+        return Ok(DEFAULT_VERSION.name);
+    };
+    VERSION_CONFIG
+        .iter()
+        .find(|(version_id, version_state)| {
+            matches!(version_state, VersionState::Supported)
+                && (requested_version.eq_ignore_ascii_case(version_id.name)
+                    || requested_version.eq_ignore_ascii_case(&version_id.to_string()))
+        })
+        .map(|(version_id, _)| version_id.name)
+        .ok_or_else(|| BoxError::from(format!("Unsupported version {requested_version}")))
+}
+
 fn is_supported_path(path: &str) -> bool {
     let re = Regex::new((r"^\/rpc(\/".to_string() + VERSION_PATTERN + ")?$").as_str())
         .expect("should be a valid regex");
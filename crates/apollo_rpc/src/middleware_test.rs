@@ -0,0 +1,25 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+
+use super::{get_version_name_from_path, is_supported_path};
+use crate::version_config::{DEFAULT_VERSION, VERSION_0_8, VERSION_0_9};
+
+#[test]
+fn version_from_path() {
+    assert_eq!(get_version_name_from_path("/rpc").unwrap(), DEFAULT_VERSION.name);
+    assert_eq!(get_version_name_from_path("/rpc/v0_8").unwrap(), VERSION_0_8.name);
+    assert_eq!(get_version_name_from_path("/rpc/V0_8_0").unwrap(), VERSION_0_8.name);
+    assert_eq!(get_version_name_from_path("/rpc/v0_9").unwrap(), VERSION_0_9.name);
+    assert_eq!(get_version_name_from_path("/rpc/V0_9_0").unwrap(), VERSION_0_9.name);
+    assert_matches!(get_version_name_from_path("/rpc/v0_7"), Err(_));
+    assert_matches!(get_version_name_from_path("/rpc/v0_9_1"), Err(_));
+}
+
+#[test]
+fn supported_paths() {
+    assert!(is_supported_path("/rpc"));
+    assert!(is_supported_path("/rpc/v0_9"));
+    assert!(is_supported_path("/rpc/V0_9_0"));
+    assert!(!is_supported_path("/v0_9"));
+    assert!(!is_supported_path("/rpc/latest"));
+}
@@ -8,7 +8,11 @@ use std::time::Instant;
 use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, TransportProtocol};
 use jsonrpsee::types::Params;
 use jsonrpsee::Methods;
+use lazy_static::lazy_static;
 use metrics::{counter, histogram};
+use regex::Regex;
+
+use crate::version_config::{DEFAULT_VERSION, VERSION_PATTERN};
 
 // Name of the metrics.
 const INCOMING_REQUEST: &str = "rpc_incoming_requests";
@@ -108,15 +112,20 @@ impl Logger for MetricLogger {
 
 // Given method_name returns (method, version).
 // Example: method_name: starknet_V0_6_0_blockNumber; output: (blockNumber, V0_6_0).
+// Methods without a version, such as the WebSocket subscription methods, belong to the default
+// version. Example: method_name: starknet_subscribeNewHeads; output: (subscribeNewHeads, V0_8_0).
 fn get_method_and_version(method_name: &str) -> (String, String) {
-    // The structure of method_name is in the following format: "starknet_V0_6_0_blockNumber".
-    // Only method in this format will arrive to this point in the code.
-    let last_underscore_index = method_name
-        .rfind('_')
-        .expect("method_name should be in the following format: starknet_V0_6_0_blockNumber");
-
-    (
-        method_name[last_underscore_index + 1..].to_string(),
-        method_name[9..last_underscore_index].to_string(),
-    )
+    lazy_static! {
+        static ref METHOD_NAME_REGEX: Regex = Regex::new(&format!(
+            "^starknet_(?:(?P<version>{VERSION_PATTERN})_)?(?P<method>[^_]+)$"
+        ))
+        .expect("Failed to compile the method name regex.");
+    }
+    let captures = METHOD_NAME_REGEX.captures(method_name).unwrap_or_else(|| {
+        panic!("method_name {method_name} should be in the format starknet_V0_6_0_blockNumber.")
+    });
+    let version = captures
+        .name("version")
+        .map_or_else(|| DEFAULT_VERSION.to_string(), |version| version.as_str().to_string());
+    (captures["method"].to_string(), version)
 }
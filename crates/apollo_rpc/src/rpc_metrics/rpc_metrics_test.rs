@@ -14,6 +14,7 @@ use pretty_assertions::assert_eq;
 use prometheus_parse::Value::Counter;
 use starknet_api::block::{BlockBody, BlockHeader, BlockNumber};
 use starknet_api::state::ThinStateDiff;
+use tokio::sync::watch;
 
 use crate::rpc_metrics::{
     get_method_and_version,
@@ -31,6 +32,7 @@ use crate::test_utils::{
     get_test_pending_data,
     get_test_rpc_config,
 };
+use crate::version_config::DEFAULT_VERSION;
 
 #[test]
 fn get_method_and_version_test() {
@@ -40,6 +42,13 @@ fn get_method_and_version_test() {
     assert_eq!(version, "V0_8_0");
 }
 
+#[test]
+fn get_method_and_version_of_subscription_method() {
+    let (method, version) = get_method_and_version("starknet_subscribeNewHeads");
+    assert_eq!(method, "subscribeNewHeads");
+    assert_eq!(version, DEFAULT_VERSION.to_string());
+}
+
 // Ignored because server_metrics test is running in parallel and we are unable to install multiple
 // recorders.
 #[ignore]
@@ -165,10 +174,12 @@ async fn server_metrics() {
         &gateway_config,
         get_test_highest_block(),
         get_test_pending_data(),
+        watch::channel(()).1,
         get_test_pending_classes(),
         storage_reader,
         "NODE VERSION",
         None,
+        None,
     )
     .await
     .unwrap();
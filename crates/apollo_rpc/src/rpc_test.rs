@@ -16,6 +16,7 @@ use starknet_api::block::{
     BlockNumber,
     BlockStatus,
 };
+use tokio::sync::watch;
 
 use crate::test_utils::{
     get_test_highest_block,
@@ -36,10 +37,12 @@ async fn run_server_no_blocks() {
         &gateway_config,
         shared_highest_block,
         pending_data,
+        watch::channel(()).1,
         pending_classes,
         storage_reader,
         "NODE VERSION",
         None,
+        None,
     )
     .await
     .unwrap();
@@ -0,0 +1,293 @@
+#[cfg(test)]
+#[path = "subscription_manager_test.rs"]
+mod subscription_manager_test;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use apollo_starknet_client::reader::objects::transaction::Transaction as ClientTransaction;
+use apollo_starknet_client::reader::PendingData;
+use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::state::StateStorageReader;
+use apollo_storage::{StorageReader, StorageResult, StorageWriteNotification};
+use jsonrpsee::types::SubscriptionId;
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
+use starknet_api::hash::StarkHash;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::{broadcast, oneshot, watch, RwLock};
+use tracing::{debug, warn};
+
+use crate::GENESIS_HASH;
+
+/// The number of notifications a subscription can fall behind before it's closed.
+const NOTIFICATIONS_CHANNEL_CAPACITY: usize = 1000;
+
+/// The number of latest announced blocks that are remembered in order to detect reverts that were
+/// missed.
+const ANNOUNCED_BLOCKS_WINDOW: usize = 1024;
+
+/// A change in the data served by the node, broadcasted to all the open subscriptions.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SubscriptionNotification {
+    /// A new block was written to the storage, including its state diff.
+    NewBlock(BlockHashAndNumber),
+    /// Blocks that were announced were reverted.
+    Reorg(RevertedBlocks),
+    /// New transactions were added to the pending block.
+    PendingTransactions(Vec<ClientTransaction>),
+}
+
+/// The range of reverted blocks. Both ends are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RevertedBlocks {
+    pub starting_block: BlockHashAndNumber,
+    pub ending_block: BlockHashAndNumber,
+}
+
+/// Follows the writes to the storage and the updates of the pending data and broadcasts the changes
+/// in them to the subscriptions. Blocks are announced once their state diff is written, same as the
+/// blocks served by the HTTP methods.
+pub(crate) struct SubscriptionManager {
+    storage_reader: StorageReader,
+    storage_writes: broadcast::Receiver<StorageWriteNotification>,
+    pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Receiver<()>,
+    // False once the pending data is no longer updated.
+    is_pending_data_updated: bool,
+    sender: broadcast::Sender<SubscriptionNotification>,
+    // The latest announced blocks, from the oldest to the newest.
+    announced_blocks: VecDeque<BlockHashAndNumber>,
+    next_block_number: BlockNumber,
+    // The parent hash of the pending block and the number of its announced transactions.
+    announced_pending_transactions: (BlockHash, usize),
+}
+
+impl SubscriptionManager {
+    /// Creates a manager that announces only the changes that happen after its creation.
+    pub(crate) fn new(
+        storage_reader: StorageReader,
+        pending_data: Arc<RwLock<PendingData>>,
+        pending_data_updates: watch::Receiver<()>,
+    ) -> StorageResult<Self> {
+        let (sender, _) = broadcast::channel(NOTIFICATIONS_CHANNEL_CAPACITY);
+        // Subscribe before reading the storage so that no write is missed.
+        let storage_writes = storage_reader.subscribe_to_writes();
+        let txn = storage_reader.begin_ro_txn()?;
+        let next_block_number = txn.get_state_marker()?;
+        let mut announced_blocks = VecDeque::new();
+        if let Some(latest_block_number) = next_block_number.prev() {
+            if let Some(header) = txn.get_block_header(latest_block_number)? {
+                announced_blocks.push_back(BlockHashAndNumber {
+                    hash: header.block_hash,
+                    number: latest_block_number,
+                });
+            }
+        }
+        Ok(Self {
+            storage_reader,
+            storage_writes,
+            pending_data,
+            pending_data_updates,
+            is_pending_data_updated: true,
+            sender,
+            announced_blocks,
+            next_block_number,
+            announced_pending_transactions: (BlockHash::default(), 0),
+        })
+    }
+
+    /// Returns a sender from which new receivers of the notifications can be created.
+    pub(crate) fn notifications_sender(&self) -> broadcast::Sender<SubscriptionNotification> {
+        self.sender.clone()
+    }
+
+    /// Broadcasts the changes after every write to the storage and every update of the pending
+    /// data, until the storage is closed.
+    pub(crate) async fn run(mut self) {
+        loop {
+            let result = tokio::select! {
+                write = self.storage_writes.recv() => match write {
+                    Ok(write) => self.handle_storage_write(Some(write)),
+                    Err(RecvError::Lagged(_)) => self.handle_storage_write(None),
+                    Err(RecvError::Closed) => return,
+                },
+                update = self.pending_data_updates.changed(), if self.is_pending_data_updated => {
+                    self.is_pending_data_updated = update.is_ok();
+                    Ok(())
+                }
+            };
+            let result = match result {
+                Ok(()) => self.update_blocks_and_pending_data().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                warn!("Failed to check for updates for the subscriptions: {err}.");
+            }
+        }
+    }
+
+    /// Broadcasts the changes of the writes to the storage that weren't handled yet and of the
+    /// latest pending data.
+    pub(crate) async fn update(&mut self) -> StorageResult<()> {
+        loop {
+            match self.storage_writes.try_recv() {
+                Ok(write) => self.handle_storage_write(Some(write))?,
+                Err(TryRecvError::Lagged(_)) => self.handle_storage_write(None)?,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        self.update_blocks_and_pending_data().await
+    }
+
+    async fn update_blocks_and_pending_data(&mut self) -> StorageResult<()> {
+        self.announce_new_blocks()?;
+        self.update_pending_transactions().await;
+        Ok(())
+    }
+
+    // Announces the announced blocks that the write reverted. A write of None means that some
+    // writes were missed, in which case the reverted blocks are found by comparing the announced
+    // blocks with the storage.
+    fn handle_storage_write(&mut self, write: Option<StorageWriteNotification>) -> StorageResult<()> {
+        let reverted_blocks: Vec<_> = match write {
+            // Reverted blocks are ordered from the newest to the oldest.
+            Some(write) => write
+                .reverted_blocks
+                .into_iter()
+                .filter(|block| block.number < self.next_block_number)
+                .collect(),
+            None => {
+                warn!("Missed writes to the storage, comparing the announced blocks to it.");
+                self.find_replaced_blocks()?
+            }
+        };
+        let (Some(ending_block), Some(starting_block)) =
+            (reverted_blocks.first(), reverted_blocks.last())
+        else {
+            return Ok(());
+        };
+        debug!(
+            "Blocks {} to {} were reverted, notifying the subscriptions.",
+            starting_block.number, ending_block.number
+        );
+        while self.announced_blocks.back().is_some_and(|block| block.number >= starting_block.number)
+        {
+            self.announced_blocks.pop_back();
+        }
+        self.next_block_number = starting_block.number;
+        self.broadcast(SubscriptionNotification::Reorg(RevertedBlocks {
+            starting_block: *starting_block,
+            ending_block: *ending_block,
+        }));
+        Ok(())
+    }
+
+    // Returns the announced blocks that are no longer in the storage, from the newest to the
+    // oldest.
+    fn find_replaced_blocks(&self) -> StorageResult<Vec<BlockHashAndNumber>> {
+        let txn = self.storage_reader.begin_ro_txn()?;
+        let state_marker = txn.get_state_marker()?;
+        let mut replaced_blocks = vec![];
+        for block in self.announced_blocks.iter().rev() {
+            let stored_hash = if block.number < state_marker {
+                txn.get_block_header(block.number)?.map(|header| header.block_hash)
+            } else {
+                None
+            };
+            if stored_hash == Some(block.hash) {
+                break;
+            }
+            replaced_blocks.push(*block);
+        }
+        Ok(replaced_blocks)
+    }
+
+    fn announce_new_blocks(&mut self) -> StorageResult<()> {
+        let txn = self.storage_reader.begin_ro_txn()?;
+        let state_marker = txn.get_state_marker()?;
+        while self.next_block_number < state_marker {
+            let Some(header) = txn.get_block_header(self.next_block_number)? else {
+                break;
+            };
+            let block =
+                BlockHashAndNumber { hash: header.block_hash, number: self.next_block_number };
+            if self.announced_blocks.len() == ANNOUNCED_BLOCKS_WINDOW {
+                self.announced_blocks.pop_front();
+            }
+            self.announced_blocks.push_back(block);
+            self.next_block_number = self.next_block_number.unchecked_next();
+            self.broadcast(SubscriptionNotification::NewBlock(block));
+        }
+        Ok(())
+    }
+
+    async fn update_pending_transactions(&mut self) {
+        let latest_block_hash = self.announced_blocks.back().map_or_else(
+            || BlockHash(StarkHash::from_hex_unchecked(GENESIS_HASH)),
+            |block| block.hash,
+        );
+        let pending_data = self.pending_data.read().await;
+        let parent_block_hash = pending_data.block.parent_block_hash();
+        // The pending data may still refer to a block that was already written or to a block that
+        // wasn't written yet.
+        if parent_block_hash != latest_block_hash {
+            return;
+        }
+
+        let (announced_parent_block_hash, n_announced_transactions) =
+            self.announced_pending_transactions;
+        let first_new_transaction = if announced_parent_block_hash == parent_block_hash {
+            n_announced_transactions
+        } else {
+            0
+        };
+        let transactions = pending_data.block.transactions();
+        if let Some(new_transactions) = transactions.get(first_new_transaction..) {
+            if !new_transactions.is_empty() {
+                self.broadcast(SubscriptionNotification::PendingTransactions(
+                    new_transactions.to_vec(),
+                ));
+            }
+        }
+        self.announced_pending_transactions = (parent_block_hash, transactions.len());
+    }
+
+    fn broadcast(&self, notification: SubscriptionNotification) {
+        // Sending fails only when there are no open subscriptions.
+        let _ = self.sender.send(notification);
+    }
+}
+
+/// The open subscriptions, by their id. Allows closing any subscription from a single unsubscribe
+/// method.
+#[derive(Clone, Default)]
+pub(crate) struct ActiveSubscriptions(
+    Arc<Mutex<HashMap<SubscriptionId<'static>, oneshot::Sender<()>>>>,
+);
+
+impl ActiveSubscriptions {
+    /// Registers a subscription. The returned receiver resolves once the subscription is closed
+    /// through [`ActiveSubscriptions::close`].
+    pub(crate) fn insert(&self, subscription_id: SubscriptionId<'static>) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.0
+            .lock()
+            .expect("Active subscriptions lock is poisoned")
+            .insert(subscription_id, sender);
+        receiver
+    }
+
+    /// Removes a subscription that ended.
+    pub(crate) fn remove(&self, subscription_id: &SubscriptionId<'static>) {
+        self.0.lock().expect("Active subscriptions lock is poisoned").remove(subscription_id);
+    }
+
+    /// Closes the given subscription. Returns false if there's no such open subscription.
+    pub(crate) fn close(&self, subscription_id: &SubscriptionId<'static>) -> bool {
+        match self.0.lock().expect("Active subscriptions lock is poisoned").remove(subscription_id)
+        {
+            Some(sender) => sender.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use apollo_starknet_client::reader::objects::transaction::Transaction as ClientTransaction;
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::StorageWriter;
+use apollo_test_utils::{get_rng, GetTestInstance};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{
+    BlockBody,
+    BlockHash,
+    BlockHashAndNumber,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+};
+use starknet_api::felt;
+use starknet_api::state::ThinStateDiff;
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::watch;
+
+use super::{RevertedBlocks, SubscriptionManager, SubscriptionNotification};
+use crate::test_utils::get_test_pending_data;
+
+fn append_block(storage_writer: &mut StorageWriter, block: BlockHashAndNumber) {
+    let header = BlockHeader {
+        block_hash: block.hash,
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: block.number,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block.number, &header)
+        .unwrap()
+        .append_body(block.number, BlockBody::default())
+        .unwrap()
+        .append_state_diff(block.number, ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+fn revert_block(storage_writer: &mut StorageWriter, block_number: BlockNumber) {
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .revert_state_diff(block_number)
+        .unwrap()
+        .0
+        .revert_body(block_number)
+        .unwrap()
+        .0
+        .revert_header(block_number)
+        .unwrap()
+        .0
+        .commit()
+        .unwrap();
+}
+
+fn block(number: u64, hash: &str) -> BlockHashAndNumber {
+    BlockHashAndNumber { hash: BlockHash(felt!(hash)), number: BlockNumber(number) }
+}
+
+#[tokio::test]
+async fn announces_new_blocks() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    append_block(&mut storage_writer, block(0, "0x10"));
+    let mut manager = SubscriptionManager::new(storage_reader, get_test_pending_data(), watch::channel(()).1)
+            .unwrap();
+    let mut receiver = manager.notifications_sender().subscribe();
+
+    // Blocks written before the manager was created aren't announced.
+    manager.update().await.unwrap();
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+    append_block(&mut storage_writer, block(1, "0x11"));
+    append_block(&mut storage_writer, block(2, "0x12"));
+    manager.update().await.unwrap();
+    assert_eq!(receiver.try_recv(), Ok(SubscriptionNotification::NewBlock(block(1, "0x11"))));
+    assert_eq!(receiver.try_recv(), Ok(SubscriptionNotification::NewBlock(block(2, "0x12"))));
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+async fn announces_reverted_blocks() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let mut manager = SubscriptionManager::new(storage_reader, get_test_pending_data(), watch::channel(()).1)
+            .unwrap();
+    let mut receiver = manager.notifications_sender().subscribe();
+    for (number, hash) in [(0, "0x10"), (1, "0x11"), (2, "0x12")] {
+        append_block(&mut storage_writer, block(number, hash));
+    }
+    manager.update().await.unwrap();
+    for _ in 0..3 {
+        assert_matches!(receiver.try_recv(), Ok(SubscriptionNotification::NewBlock(_)));
+    }
+
+    // Replace blocks 1 and 2 with a single different block. Each revert is announced separately.
+    revert_block(&mut storage_writer, BlockNumber(2));
+    revert_block(&mut storage_writer, BlockNumber(1));
+    append_block(&mut storage_writer, block(1, "0x21"));
+    manager.update().await.unwrap();
+    for reverted_block in [block(2, "0x12"), block(1, "0x11")] {
+        assert_eq!(
+            receiver.try_recv(),
+            Ok(SubscriptionNotification::Reorg(RevertedBlocks {
+                starting_block: reverted_block,
+                ending_block: reverted_block,
+            }))
+        );
+    }
+    assert_eq!(receiver.try_recv(), Ok(SubscriptionNotification::NewBlock(block(1, "0x21"))));
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+async fn announces_each_pending_transaction_once() {
+    let ((storage_reader, _storage_writer), _temp_dir) = get_test_storage();
+    let pending_data = get_test_pending_data();
+    let mut manager = SubscriptionManager::new(storage_reader, pending_data.clone(), watch::channel(()).1).unwrap();
+    let mut receiver = manager.notifications_sender().subscribe();
+    let mut rng = get_rng();
+    let first_transaction = ClientTransaction::get_test_instance(&mut rng);
+    let second_transaction = ClientTransaction::get_test_instance(&mut rng);
+
+    pending_data.write().await.block.transactions_mutable().push(first_transaction.clone());
+    manager.update().await.unwrap();
+    manager.update().await.unwrap();
+    assert_eq!(
+        receiver.try_recv(),
+        Ok(SubscriptionNotification::PendingTransactions(vec![first_transaction]))
+    );
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+    pending_data.write().await.block.transactions_mutable().push(second_transaction.clone());
+    manager.update().await.unwrap();
+    assert_eq!(
+        receiver.try_recv(),
+        Ok(SubscriptionNotification::PendingTransactions(vec![second_transaction]))
+    );
+
+    // Pending data that doesn't follow the latest block isn't announced.
+    *pending_data.write().await.block.parent_block_hash_mutable() = BlockHash(felt!("0x1234"));
+    pending_data
+        .write()
+        .await
+        .block
+        .transactions_mutable()
+        .push(ClientTransaction::get_test_instance(&mut rng));
+    manager.update().await.unwrap();
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+async fn announces_writes_without_polling() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let pending_data = get_test_pending_data();
+    let (pending_data_updates, pending_data_updates_receiver) = watch::channel(());
+    let manager =
+        SubscriptionManager::new(storage_reader, pending_data.clone(), pending_data_updates_receiver)
+            .unwrap();
+    let mut receiver = manager.notifications_sender().subscribe();
+    tokio::spawn(manager.run());
+
+    append_block(&mut storage_writer, block(0, "0x10"));
+    let notification = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await;
+    assert_eq!(notification.unwrap(), Ok(SubscriptionNotification::NewBlock(block(0, "0x10"))));
+
+    let transaction = ClientTransaction::get_test_instance(&mut get_rng());
+    {
+        let mut pending_data = pending_data.write().await;
+        *pending_data.block.parent_block_hash_mutable() = BlockHash(felt!("0x10"));
+        pending_data.block.transactions_mutable().push(transaction.clone());
+    }
+    pending_data_updates.send_replace(());
+    let notification = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await;
+    assert_eq!(
+        notification.unwrap(),
+        Ok(SubscriptionNotification::PendingTransactions(vec![transaction]))
+    );
+}
@@ -5,7 +5,7 @@ use apollo_rpc_execution::ExecutionConfig;
 use apollo_starknet_client::reader::PendingData;
 use apollo_starknet_client::writer::MockStarknetWriter;
 use apollo_storage::test_utils::get_test_storage_by_scope;
-use apollo_storage::{StorageScope, StorageWriter};
+use apollo_storage::{StorageReader, StorageScope, StorageWriter};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::server::RpcModule;
 use jsonrpsee::types::ErrorObjectOwned;
@@ -23,6 +23,7 @@ use strum_macros::EnumIter;
 use tokio::sync::RwLock;
 
 use crate::api::JsonRpcServerTrait;
+use crate::l1_messages::SharedL1MessagesReader;
 use crate::version_config::{VersionId, VERSION_PATTERN};
 use crate::RpcConfig;
 
@@ -89,12 +90,50 @@ pub(crate) fn get_test_rpc_server_and_storage_writer_from_params<T: JsonRpcServe
             pending_classes,
             mock_client_arc,
             None,
+            None,
         )
         .into_rpc_module(),
         storage_writer,
     )
 }
 
+pub(crate) fn get_test_rpc_server_from_storage_reader<T: JsonRpcServerTrait>(
+    storage_reader: StorageReader,
+) -> RpcModule<T> {
+    get_test_rpc_server(storage_reader, None)
+}
+
+pub(crate) fn get_test_rpc_server_from_storage_reader_and_l1_messages_reader<
+    T: JsonRpcServerTrait,
+>(
+    storage_reader: StorageReader,
+    l1_messages_reader: Option<SharedL1MessagesReader>,
+) -> RpcModule<T> {
+    get_test_rpc_server(storage_reader, l1_messages_reader)
+}
+
+fn get_test_rpc_server<T: JsonRpcServerTrait>(
+    storage_reader: StorageReader,
+    l1_messages_reader: Option<SharedL1MessagesReader>,
+) -> RpcModule<T> {
+    let config = get_test_rpc_config();
+    T::new(
+        config.chain_id,
+        config.execution_config,
+        storage_reader,
+        config.max_events_chunk_size,
+        config.max_events_keys,
+        BlockHashAndNumber::default(),
+        get_test_highest_block(),
+        get_test_pending_data(),
+        get_test_pending_classes(),
+        Arc::new(MockStarknetWriter::default()),
+        None,
+        l1_messages_reader,
+    )
+    .into_rpc_module()
+}
+
 // Call a method on the `RPC module` without having to spin up a server.
 // Returns the raw `result field` in JSON-RPC response and the deserialized result if successful.
 // `params_obj` should be serialized to the format that JSON-RPC expects, which is either an array
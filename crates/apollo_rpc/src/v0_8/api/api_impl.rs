@@ -26,18 +26,22 @@ use apollo_storage::body::events::{EventIndex, EventsReader};
 use apollo_storage::body::{BodyStorageReader, TransactionIndex};
 use apollo_storage::compiled_class::CasmStorageReader;
 use apollo_storage::db::{TransactionKind, RO};
+use apollo_storage::header::HeaderStorageReader;
 use apollo_storage::state::StateStorageReader;
+use apollo_storage::state_tries::StateTriesStorageReader;
 use apollo_storage::{StorageError, StorageReader, StorageTxn};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::types::ErrorObjectOwned;
 use jsonrpsee::RpcModule;
+use papyrus_base_layer::L1TxHash;
 use papyrus_common::pending_classes::{PendingClasses, PendingClassesTrait};
 use starknet_api::block::{
     BlockHash,
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockStatus,
+    CommitCertificate,
     GasPricePerToken,
 };
 use starknet_api::contract_class::SierraVersion;
@@ -58,9 +62,13 @@ use starknet_api::transaction::{
     EventIndexInTransactionOutput,
     Transaction as StarknetApiTransaction,
     TransactionHash,
+    TransactionHasher,
     TransactionOffsetInBlock,
     TransactionVersion,
 };
+use starknet_committer::block_committer::input::StarknetStorageKey;
+use starknet_committer::storage_proof::fetch_storage_proof;
+use starknet_patricia::hash::hash_trait::HashOutput;
 use starknet_types_core::felt::Felt;
 use tokio::runtime::Handle;
 use tokio::sync::RwLock;
@@ -90,11 +98,19 @@ use super::super::error::{
     INVALID_TRANSACTION_INDEX,
     NO_BLOCKS,
     PAGE_SIZE_TOO_BIG,
+    STORAGE_PROOF_NOT_SUPPORTED,
     TOO_MANY_KEYS_IN_FILTER,
     TRANSACTION_HASH_NOT_FOUND,
 };
 use super::super::execution::TransactionTrace;
-use super::super::state::{AcceptedStateUpdate, PendingStateUpdate, StateUpdate};
+use super::super::state::{
+    AcceptedStateUpdate,
+    ContractStorageKeys,
+    GlobalRoots,
+    PendingStateUpdate,
+    StateUpdate,
+    StorageProof,
+};
 use super::super::transaction::{
     get_block_tx_hashes_by_number,
     get_block_txs_by_number,
@@ -102,7 +118,9 @@ use super::super::transaction::{
     GeneralTransactionReceipt,
     L1HandlerMsgHash,
     L1L2MsgHash,
+    MessageFinalityStatus,
     MessageFromL1,
+    MessageStatus,
     PendingTransactionFinalityStatus,
     PendingTransactionOutput,
     PendingTransactionReceipt,
@@ -138,11 +156,15 @@ use super::{
     EventsChunk,
     GatewayContractClass,
     JsonRpcV0_8Server as JsonRpcServer,
+    SenderTransaction,
+    SenderTransactionsChunk,
+    SenderTransactionsFilter,
     SimulatedTransaction,
     SimulationFlag,
     TransactionTraceWithHash,
 };
 use crate::api::{BlockHashOrNumber, JsonRpcServerTrait, Tag};
+use crate::l1_messages::SharedL1MessagesReader;
 use crate::pending::client_pending_data_to_execution_pending_data;
 use crate::syncing_state::{get_last_synced_block, SyncStatus, SyncingState};
 use crate::v0_8::state::ThinStateDiff;
@@ -172,6 +194,7 @@ pub struct JsonRpcServerImpl {
     pub pending_classes: Arc<RwLock<PendingClasses>>,
     pub writer_client: Arc<dyn StarknetWriter>,
     pub class_manager_client: Option<SharedClassManagerClient>,
+    pub l1_messages_reader: Option<SharedL1MessagesReader>,
 }
 
 async fn create_class_manager_client(
@@ -1551,9 +1574,183 @@ impl JsonRpcServer for JsonRpcServerImpl {
             SierraVersion::DEPRECATED,
         ))
     }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_transactions_by_sender(
+        &self,
+        filter: SenderTransactionsFilter,
+    ) -> RpcResult<SenderTransactionsChunk> {
+        verify_storage_scope(&self.storage_reader)?;
+        if !self.storage_reader.indexes_transactions_by_sender() {
+            return Err(internal_server_error_with_msg(
+                "Unsupported method when transactions are not indexed by sender.",
+            ));
+        }
+
+        // The chunk size is bounded by the same limit as in get_events.
+        if filter.chunk_size > self.max_events_chunk_size {
+            return Err(ErrorObjectOwned::from(PAGE_SIZE_TOO_BIG));
+        }
+
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        // Take one more transaction than requested in order to know where the next chunk starts.
+        let mut sender_transactions = txn
+            .get_sender_transactions(
+                filter.sender_address,
+                filter.from_nonce,
+                filter.chunk_size + 1,
+            )
+            .map_err(internal_server_error)?;
+        let next_nonce = if sender_transactions.len() > filter.chunk_size {
+            sender_transactions.pop().map(|(nonce, _)| nonce)
+        } else {
+            None
+        };
+
+        let transactions = sender_transactions
+            .into_iter()
+            .map(|(nonce, transaction_index)| {
+                let transaction = txn
+                    .get_transaction(transaction_index)
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| {
+                        internal_server_error(format!(
+                            "Missing transaction at index {transaction_index:?}."
+                        ))
+                    })?;
+                let transaction_hash = txn
+                    .get_transaction_hash_by_idx(&transaction_index)
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| {
+                        internal_server_error(format!(
+                            "Missing transaction hash at index {transaction_index:?}."
+                        ))
+                    })?;
+                Ok(SenderTransaction {
+                    nonce,
+                    block_number: transaction_index.0,
+                    transaction: TransactionWithHash {
+                        transaction: transaction.try_into()?,
+                        transaction_hash,
+                    },
+                })
+            })
+            .collect::<RpcResult<Vec<_>>>()?;
+
+        Ok(SenderTransactionsChunk { transactions, next_nonce })
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn get_storage_proof(
+        &self,
+        block_id: BlockId,
+        class_hashes: Option<Vec<ClassHash>>,
+        contract_addresses: Option<Vec<ContractAddress>>,
+        contracts_storage_keys: Option<Vec<ContractStorageKeys>>,
+    ) -> RpcResult<StorageProof> {
+        if let BlockId::Tag(Tag::Pending) = block_id {
+            return Err(ErrorObjectOwned::from(STORAGE_PROOF_NOT_SUPPORTED));
+        }
+
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let block_number = get_accepted_block_number(&txn, block_id)?;
+        let block_hash = get_block_header_by_number(&txn, block_number)?.block_hash;
+        // The state tries are stored only for the recent blocks, and only if the sync verifies the
+        // state roots.
+        let roots = txn
+            .get_state_tries_roots(block_number)
+            .map_err(internal_server_error)?
+            .ok_or_else(|| ErrorObjectOwned::from(STORAGE_PROOF_NOT_SUPPORTED))?;
+        let contracts_trie_root_hash = HashOutput(roots.contracts_trie_root_hash);
+        let classes_trie_root_hash = HashOutput(roots.classes_trie_root_hash);
+
+        let contracts_storage_keys = contracts_storage_keys
+            .unwrap_or_default()
+            .into_iter()
+            .map(|ContractStorageKeys { contract_address, storage_keys }| {
+                (
+                    contract_address,
+                    storage_keys.into_iter().map(|key| StarknetStorageKey(key.into())).collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let proof = fetch_storage_proof(
+            &txn,
+            contracts_trie_root_hash,
+            classes_trie_root_hash,
+            &class_hashes.unwrap_or_default(),
+            &contract_addresses.unwrap_or_default(),
+            &contracts_storage_keys,
+        )
+        .map_err(internal_server_error)?;
+        Ok(StorageProof::new(
+            proof,
+            GlobalRoots {
+                contracts_tree_root: roots.contracts_trie_root_hash,
+                classes_tree_root: roots.classes_trie_root_hash,
+                block_hash,
+            },
+        ))
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn get_messages_status(
+        &self,
+        transaction_hash: L1TxHash,
+    ) -> RpcResult<Vec<MessageStatus>> {
+        let Some(l1_messages_reader) = &self.l1_messages_reader else {
+            return Err(internal_server_error_with_msg(
+                "Unsupported method, since the node isn't connected to a base layer.",
+            ));
+        };
+        let l1_handler_txs = l1_messages_reader
+            .get_messages_to_l2(transaction_hash)
+            .await
+            .map_err(internal_server_error)?;
+        if l1_handler_txs.is_empty() {
+            return Err(ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND));
+        }
+
+        let mut messages_status = Vec::with_capacity(l1_handler_txs.len());
+        for tx in l1_handler_txs {
+            let transaction_hash = tx
+                .calculate_transaction_hash(&self.chain_id, &tx.version)
+                .map_err(internal_server_error)?;
+            let message_status = match self.get_transaction_status(transaction_hash).await {
+                Ok(TransactionStatus { finality_status, execution_status }) => MessageStatus {
+                    transaction_hash,
+                    finality_status: finality_status.into(),
+                    execution_status: Some(execution_status),
+                },
+                // The message was sent, but its transaction wasn't added to L2 yet.
+                Err(err) if err.code() == TRANSACTION_HASH_NOT_FOUND.code => MessageStatus {
+                    transaction_hash,
+                    finality_status: MessageFinalityStatus::Received,
+                    execution_status: None,
+                },
+                Err(err) => return Err(err),
+            };
+            messages_status.push(message_status);
+        }
+        Ok(messages_status)
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_commit_certificate(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<CommitCertificate>> {
+        // The pending block wasn't decided yet.
+        if let BlockId::Tag(Tag::Pending) = block_id {
+            return Ok(None);
+        }
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let block_number = get_accepted_block_number(&txn, block_id)?;
+        txn.get_commit_certificate(block_number).map_err(internal_server_error)
+    }
 }
 
-async fn read_pending_data<Mode: TransactionKind>(
+pub(crate) async fn read_pending_data<Mode: TransactionKind>(
     pending_data: &Arc<RwLock<PendingData>>,
     txn: &StorageTxn<'_, Mode>,
 ) -> RpcResult<PendingData> {
@@ -1747,7 +1944,7 @@ fn get_non_pending_receipt<Mode: TransactionKind>(
     }))
 }
 
-fn client_receipt_to_rpc_pending_receipt(
+pub(crate) fn client_receipt_to_rpc_pending_receipt(
     client_transaction: &ClientTransaction,
     client_transaction_receipt: ClientTransactionReceipt,
 ) -> RpcResult<GeneralTransactionReceipt> {
@@ -1771,7 +1968,10 @@ fn client_receipt_to_rpc_pending_receipt(
     }))
 }
 
-fn do_event_keys_match_filter(event_content: &EventContent, filter: &EventFilter) -> bool {
+pub(crate) fn do_event_keys_match_filter(
+    event_content: &EventContent,
+    filter: &EventFilter,
+) -> bool {
     filter.keys.iter().enumerate().all(|(i, keys)| {
         event_content.keys.len() > i && (keys.is_empty() || keys.contains(&event_content.keys[i]))
     })
@@ -1790,6 +1990,7 @@ impl JsonRpcServerTrait for JsonRpcServerImpl {
         pending_classes: Arc<RwLock<PendingClasses>>,
         writer_client: Arc<dyn StarknetWriter>,
         class_manager_client: Option<SharedClassManagerClient>,
+        l1_messages_reader: Option<SharedL1MessagesReader>,
     ) -> Self {
         Self {
             chain_id,
@@ -1803,6 +2004,7 @@ impl JsonRpcServerTrait for JsonRpcServerImpl {
             pending_classes,
             writer_client,
             class_manager_client,
+            l1_messages_reader,
         }
     }
 
@@ -14,10 +14,11 @@ use flate2::bufread::GzDecoder;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::ErrorObjectOwned;
+use papyrus_base_layer::L1TxHash;
 use papyrus_common::deprecated_class_abi::calculate_deprecated_class_abi_length;
 use papyrus_common::pending_classes::ApiContractClass;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::{BlockHashAndNumber, BlockNumber};
+use starknet_api::block::{BlockHashAndNumber, BlockNumber, CommitCertificate};
 use starknet_api::contract_class::SierraVersion;
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_api::deprecated_contract_class::{
@@ -45,7 +46,7 @@ use super::error::{
     INVALID_CONTINUATION_TOKEN,
 };
 use super::execution::TransactionTrace;
-use super::state::{ContractClass, StateUpdate};
+use super::state::{ContractClass, ContractStorageKeys, StateUpdate, StorageProof};
 use super::transaction::{
     DeployAccountTransaction,
     DeployAccountTransactionV1,
@@ -57,6 +58,7 @@ use super::transaction::{
     InvokeTransactionV1,
     InvokeTransactionV3,
     MessageFromL1,
+    MessageStatus,
     TransactionStatus,
     TransactionWithHash,
     TypedDeployAccountTransaction,
@@ -265,6 +267,41 @@ pub trait JsonRpc {
         block_id: BlockId,
         class_hash: ClassHash,
     ) -> RpcResult<(CompiledContractClass, SierraVersion)>;
+
+    /// Returns the transactions sent by the given account, ordered by nonce. Requires the storage
+    /// to index transactions by sender.
+    #[method(name = "getTransactionsBySender")]
+    async fn get_transactions_by_sender(
+        &self,
+        filter: SenderTransactionsFilter,
+    ) -> RpcResult<SenderTransactionsChunk>;
+
+    /// Gets the Merkle proofs of the given classes, contracts and storage keys in the given block,
+    /// along with the roots of the classes and contracts tries.
+    #[method(name = "getStorageProof")]
+    async fn get_storage_proof(
+        &self,
+        block_id: BlockId,
+        class_hashes: Option<Vec<ClassHash>>,
+        contract_addresses: Option<Vec<ContractAddress>>,
+        contracts_storage_keys: Option<Vec<ContractStorageKeys>>,
+    ) -> RpcResult<StorageProof>;
+
+    /// Gets the statuses of the L1 handler transactions of the messages that the given L1
+    /// transaction sent to L2.
+    #[method(name = "getMessagesStatus")]
+    async fn get_messages_status(
+        &self,
+        transaction_hash: L1TxHash,
+    ) -> RpcResult<Vec<MessageStatus>>;
+
+    /// Gets the commit certificate of a block decided by consensus: the signed precommits of a
+    /// quorum of the committee which decided it. Returns null for blocks without a certificate.
+    #[method(name = "getCommitCertificate")]
+    async fn get_commit_certificate(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<CommitCertificate>>;
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -297,6 +334,30 @@ pub struct EventFilter {
     pub keys: Vec<HashSet<EventKey>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SenderTransactionsFilter {
+    pub sender_address: ContractAddress,
+    #[serde(default)]
+    pub from_nonce: Nonce,
+    pub chunk_size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SenderTransaction {
+    pub nonce: Nonce,
+    pub block_number: BlockNumber,
+    #[serde(flatten)]
+    pub transaction: TransactionWithHash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SenderTransactionsChunk {
+    pub transactions: Vec<SenderTransaction>,
+    /// The nonce to pass as `from_nonce` in order to get the next chunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_nonce: Option<Nonce>,
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ContinuationToken(pub String);
 
@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use std::iter;
 use std::net::SocketAddr;
 use std::ops::Index;
+use std::sync::Arc;
 
 use apollo_starknet_client::reader::objects::pending_data::{
     DeprecatedPendingBlock,
@@ -44,7 +45,8 @@ use apollo_storage::class::ClassStorageWriter;
 use apollo_storage::compiled_class::CasmStorageWriter;
 use apollo_storage::header::HeaderStorageWriter;
 use apollo_storage::state::StateStorageWriter;
-use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::state_tries::{StateTriesRoots, StateTriesStorageWriter};
+use apollo_storage::test_utils::{get_test_storage, TestStorageBuilder};
 use apollo_storage::StorageScope;
 use apollo_test_utils::{
     auto_impl_get_test_instance,
@@ -66,6 +68,7 @@ use jsonrpsee::Methods;
 use jsonschema::JSONSchema;
 use lazy_static::lazy_static;
 use mockall::predicate::eq;
+use papyrus_base_layer::{L1Event, L1TxHash, MockBaseLayerContract};
 use papyrus_common::pending_classes::{ApiContractClass, PendingClassesTrait};
 use pretty_assertions::assert_eq;
 use rand::{random, RngCore};
@@ -74,6 +77,7 @@ use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{
     Block as StarknetApiBlock,
+    BlockBody,
     BlockHash,
     BlockHashAndNumber,
     BlockHeader,
@@ -81,6 +85,7 @@ use starknet_api::block::{
     BlockNumber,
     BlockStatus,
     BlockTimestamp,
+    CommitCertificate,
     GasPricePerToken,
     StarknetVersion,
 };
@@ -102,19 +107,30 @@ use starknet_api::deprecated_contract_class::{
     FunctionStateMutability,
 };
 use starknet_api::state::{SierraContractClass as StarknetApiContractClass, StateDiff};
+use starknet_api::transaction::fields::Fee;
 use starknet_api::transaction::{
     Event as StarknetApiEvent,
     EventContent,
     EventData,
     EventIndexInTransactionOutput,
     EventKey,
+    InvokeTransactionOutput,
+    L1HandlerTransaction,
+    L1HandlerTransactionOutput,
     Transaction as StarknetApiTransaction,
+    TransactionExecutionStatus,
     TransactionHash,
+    TransactionHasher,
     TransactionOffsetInBlock,
     TransactionOutput as StarknetApiTransactionOutput,
 };
-use starknet_api::{class_hash, contract_address, felt, storage_key, tx_hash};
+use starknet_api::{class_hash, contract_address, felt, nonce, storage_key, tx_hash};
+use starknet_committer::block_committer::commit::commit_block;
+use starknet_committer::block_committer::input::{ConfigImpl, Input};
+use starknet_patricia::hash::hash_trait::HashOutput;
 use starknet_types_core::felt::Felt;
+use tokio::sync::watch;
+use tracing::level_filters::LevelFilter;
 
 use super::super::api::EventsChunk;
 use super::super::block::{Block, GeneralBlockHeader, PendingBlockHeader};
@@ -132,6 +148,7 @@ use super::super::error::{
     INVALID_TRANSACTION_INDEX,
     NO_BLOCKS,
     PAGE_SIZE_TOO_BIG,
+    STORAGE_PROOF_NOT_SUPPORTED,
     TOO_MANY_KEYS_IN_FILTER,
     TRANSACTION_HASH_NOT_FOUND,
 };
@@ -140,12 +157,15 @@ use super::super::state::{
     ClassHashes,
     ContractClass,
     ContractNonce,
+    ContractStorageKeys,
     DeployedContract,
+    GlobalRoots,
     PendingStateUpdate,
     ReplacedClass,
     StateUpdate,
     StorageDiff,
     StorageEntry,
+    StorageProof,
     ThinStateDiff,
 };
 use super::super::transaction::{
@@ -155,6 +175,8 @@ use super::super::transaction::{
     InvokeTransaction,
     L1HandlerMsgHash,
     L1L2MsgHash,
+    MessageFinalityStatus,
+    MessageStatus,
     PendingTransactionFinalityStatus,
     PendingTransactionOutput,
     PendingTransactionReceipt,
@@ -175,7 +197,14 @@ use super::super::write_api_result::{
     AddInvokeOkResult,
 };
 use super::api_impl::JsonRpcServerImpl;
-use super::{ContinuationToken, EventFilter, GatewayContractClass};
+use super::{
+    ContinuationToken,
+    EventFilter,
+    GatewayContractClass,
+    SenderTransaction,
+    SenderTransactionsChunk,
+    SenderTransactionsFilter,
+};
 use crate::api::{BlockHashOrNumber, BlockId, Tag};
 use crate::syncing_state::SyncStatus;
 use crate::test_utils::{
@@ -191,6 +220,8 @@ use crate::test_utils::{
     get_test_rpc_config,
     get_test_rpc_server_and_storage_writer,
     get_test_rpc_server_and_storage_writer_from_params,
+    get_test_rpc_server_from_storage_reader,
+    get_test_rpc_server_from_storage_reader_and_l1_messages_reader,
     method_name_to_spec_method_name,
     raw_call,
     validate_schema,
@@ -3560,10 +3591,12 @@ async fn serialize_returns_valid_json() {
         &gateway_config,
         get_test_highest_block(),
         get_test_pending_data(),
+        watch::channel(()).1,
         get_test_pending_classes(),
         storage_reader,
         NODE_VERSION,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -3839,6 +3872,248 @@ async fn get_compiled_class() {
     assert_matches!(err, Error::Call(err) if err == CLASS_HASH_NOT_FOUND.into());
 }
 
+#[tokio::test]
+async fn get_transactions_by_sender() {
+    let method_name = "starknet_V0_8_getTransactionsBySender";
+    let ((storage_reader, mut storage_writer), _config, _temp_dir) =
+        TestStorageBuilder::new(None).index_transactions_by_sender(true).build();
+    let module = get_test_rpc_server_from_storage_reader::<JsonRpcServerImpl>(storage_reader);
+
+    let sender_address = contract_address!("0x100");
+    let transactions: Vec<StarknetApiTransaction> = (0..3_u64)
+        .map(|nonce| {
+            StarknetApiTransaction::Invoke(starknet_api::transaction::InvokeTransaction::V1(
+                starknet_api::transaction::InvokeTransactionV1 {
+                    sender_address,
+                    nonce: nonce!(nonce),
+                    ..Default::default()
+                },
+            ))
+        })
+        .collect();
+    let transaction_hashes: Vec<TransactionHash> = (0..3_u64).map(|i| tx_hash!(i)).collect();
+    let body = BlockBody {
+        transaction_outputs: vec![
+            StarknetApiTransactionOutput::Invoke(InvokeTransactionOutput::default());
+            3
+        ],
+        transactions: transactions.clone(),
+        transaction_hashes: transaction_hashes.clone(),
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_body(BlockNumber(0), body)
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let expected_sender_transaction = |nonce: u64| SenderTransaction {
+        nonce: nonce!(nonce),
+        block_number: BlockNumber(0),
+        transaction: TransactionWithHash {
+            transaction: transactions[usize::try_from(nonce).unwrap()].clone().try_into().unwrap(),
+            transaction_hash: transaction_hashes[usize::try_from(nonce).unwrap()],
+        },
+    };
+
+    let res = module
+        .call::<_, SenderTransactionsChunk>(
+            method_name,
+            [SenderTransactionsFilter { sender_address, from_nonce: nonce!(0_u8), chunk_size: 2 }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        SenderTransactionsChunk {
+            transactions: vec![expected_sender_transaction(0), expected_sender_transaction(1)],
+            next_nonce: Some(nonce!(2_u8)),
+        }
+    );
+
+    let res = module
+        .call::<_, SenderTransactionsChunk>(
+            method_name,
+            [SenderTransactionsFilter { sender_address, from_nonce: nonce!(2_u8), chunk_size: 2 }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        SenderTransactionsChunk {
+            transactions: vec![expected_sender_transaction(2)],
+            next_nonce: None,
+        }
+    );
+
+    // Ask for a chunk which is too big.
+    let err = module
+        .call::<_, SenderTransactionsChunk>(
+            method_name,
+            [SenderTransactionsFilter {
+                sender_address,
+                from_nonce: nonce!(0_u8),
+                chunk_size: get_test_rpc_config().max_events_chunk_size + 1,
+            }],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == PAGE_SIZE_TOO_BIG.into());
+}
+
+#[tokio::test]
+async fn get_transactions_by_sender_index_disabled() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let err = module
+        .call::<_, SenderTransactionsChunk>(
+            "starknet_V0_8_getTransactionsBySender",
+            [SenderTransactionsFilter {
+                sender_address: contract_address!("0x100"),
+                from_nonce: nonce!(0_u8),
+                chunk_size: 1,
+            }],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        Error::Call(err) if err == internal_server_error_with_msg(
+            "Unsupported method when transactions are not indexed by sender."
+        )
+    );
+}
+
+#[tokio::test]
+async fn get_storage_proof() {
+    let method_name = "starknet_V0_8_getStorageProof";
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let header = BlockHeader { block_hash: BlockHash(felt!("0x100")), ..Default::default() };
+    let address = contract_address!("0x10");
+    let diff = starknet_api::state::ThinStateDiff {
+        deployed_contracts: indexmap! { address => class_hash!("0x1") },
+        storage_diffs: indexmap! { address => indexmap! { storage_key!("0x5") => felt!("0x55") } },
+        declared_classes: indexmap! { class_hash!("0x1") => CompiledClassHash(felt!("0x101")) },
+        ..Default::default()
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header)
+        .unwrap()
+        .append_state_diff(BlockNumber(0), diff.clone())
+        .unwrap()
+        .commit()
+        .unwrap();
+    // Stores the state tries of the block, as the sync does when it verifies the state roots. The
+    // tries are empty before the block, so committing the block requires no witnesses.
+    let filled_forest = commit_block(Input {
+        storage: HashMap::new(),
+        state_diff: diff.into(),
+        contracts_trie_root_hash: HashOutput::ROOT_OF_EMPTY_TREE,
+        classes_trie_root_hash: HashOutput::ROOT_OF_EMPTY_TREE,
+        config: ConfigImpl::new(false, LevelFilter::INFO),
+    })
+    .await
+    .unwrap();
+    let roots = StateTriesRoots {
+        contracts_trie_root_hash: filled_forest.get_contract_root_hash().0,
+        classes_trie_root_hash: filled_forest.get_compiled_class_root_hash().0,
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_tries(BlockNumber(0), &roots, filled_forest.new_nodes(), vec![], 1)
+        .unwrap()
+        .commit()
+        .unwrap();
+    let module = get_test_rpc_server_from_storage_reader::<JsonRpcServerImpl>(storage_reader);
+
+    let res = module
+        .call::<_, StorageProof>(
+            method_name,
+            (
+                BlockId::Tag(Tag::Latest),
+                Some(vec![class_hash!("0x1")]),
+                Some(vec![address, contract_address!("0x11")]),
+                Some(vec![ContractStorageKeys {
+                    contract_address: address,
+                    storage_keys: vec![storage_key!("0x5")],
+                }]),
+            ),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        res.global_roots,
+        GlobalRoots {
+            contracts_tree_root: roots.contracts_trie_root_hash,
+            classes_tree_root: roots.classes_trie_root_hash,
+            block_hash: header.block_hash,
+        }
+    );
+    // The tries contain a single leaf, so each proof is a single edge from the root to the leaf.
+    assert_eq!(res.classes_proof.len(), 1);
+    assert_eq!(res.classes_proof[0].node_hash, roots.classes_trie_root_hash);
+    assert_eq!(res.contracts_proof.nodes.len(), 1);
+    assert_eq!(res.contracts_proof.contract_leaves_data.len(), 2);
+    assert_eq!(res.contracts_proof.contract_leaves_data[0].class_hash, class_hash!("0x1"));
+    assert_eq!(res.contracts_proof.contract_leaves_data[1].class_hash, ClassHash::default());
+    assert_eq!(res.contracts_storage_proofs.len(), 1);
+    assert_eq!(res.contracts_storage_proofs[0].len(), 1);
+    assert_eq!(
+        res.contracts_storage_proofs[0][0].node_hash,
+        res.contracts_proof.contract_leaves_data[0].storage_root
+    );
+
+    // Proofs of the pending block aren't supported.
+    let err = module
+        .call::<_, StorageProof>(
+            method_name,
+            (BlockId::Tag(Tag::Pending), None::<()>, None::<()>, None::<()>),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == STORAGE_PROOF_NOT_SUPPORTED.into());
+
+    let err = module
+        .call::<_, StorageProof>(
+            method_name,
+            (
+                BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(1))),
+                None::<()>,
+                None::<()>,
+                None::<()>,
+            ),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == BLOCK_NOT_FOUND.into());
+}
+
+#[tokio::test]
+async fn get_storage_proof_without_state_tries() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &BlockHeader::default())
+        .unwrap()
+        .append_state_diff(BlockNumber(0), starknet_api::state::ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    let err = module
+        .call::<_, StorageProof>(
+            "starknet_V0_8_getStorageProof",
+            (BlockId::Tag(Tag::Latest), None::<()>, None::<()>, None::<()>),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == STORAGE_PROOF_NOT_SUPPORTED.into());
+}
+
 #[async_trait]
 trait AddTransactionTest
 where
@@ -4194,3 +4469,159 @@ auto_impl_get_test_instance! {
         DeployAccount(DeployAccountTransaction) = 0,
     }
 }
+
+#[tokio::test]
+async fn get_messages_status() {
+    let method_name = "starknet_V0_8_getMessagesStatus";
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let chain_id = get_test_rpc_config().chain_id;
+    let l1_tx_hash = L1TxHash::repeat_byte(1);
+    let l1_handler_tx = |nonce: u8| L1HandlerTransaction {
+        version: L1HandlerTransaction::VERSION,
+        nonce: nonce!(nonce),
+        contract_address: contract_address!("0x12"),
+        ..Default::default()
+    };
+    let accepted_tx = l1_handler_tx(1);
+    let received_tx = l1_handler_tx(2);
+    let accepted_tx_hash =
+        accepted_tx.calculate_transaction_hash(&chain_id, &accepted_tx.version).unwrap();
+    let received_tx_hash =
+        received_tx.calculate_transaction_hash(&chain_id, &received_tx.version).unwrap();
+
+    // Only the first message was added to L2.
+    let body = BlockBody {
+        transactions: vec![StarknetApiTransaction::L1Handler(accepted_tx.clone())],
+        transaction_outputs: vec![StarknetApiTransactionOutput::L1Handler(
+            L1HandlerTransactionOutput::default(),
+        )],
+        transaction_hashes: vec![accepted_tx_hash],
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &BlockHeader::default())
+        .unwrap()
+        .append_body(BlockNumber(0), body)
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let mut base_layer = MockBaseLayerContract::new();
+    base_layer.expect_transaction_events().returning(move |tx_hash, _| {
+        if tx_hash != l1_tx_hash {
+            return Ok(vec![]);
+        }
+        Ok([accepted_tx.clone(), received_tx.clone()]
+            .into_iter()
+            .map(|tx| L1Event::LogMessageToL2 {
+                tx,
+                fee: Fee(1),
+                l1_tx_hash: Some(l1_tx_hash),
+                timestamp: BlockTimestamp::default(),
+            })
+            .collect())
+    });
+    let module = get_test_rpc_server_from_storage_reader_and_l1_messages_reader::<JsonRpcServerImpl>(
+        storage_reader,
+        Some(Arc::new(base_layer)),
+    );
+
+    let res = module.call::<_, Vec<MessageStatus>>(method_name, [l1_tx_hash]).await.unwrap();
+    assert_eq!(
+        res,
+        vec![
+            MessageStatus {
+                transaction_hash: accepted_tx_hash,
+                finality_status: MessageFinalityStatus::AcceptedOnL2,
+                execution_status: Some(TransactionExecutionStatus::Succeeded),
+            },
+            MessageStatus {
+                transaction_hash: received_tx_hash,
+                finality_status: MessageFinalityStatus::Received,
+                execution_status: None,
+            },
+        ]
+    );
+
+    // An L1 transaction that didn't send messages to L2.
+    let err = module
+        .call::<_, Vec<MessageStatus>>(method_name, [L1TxHash::repeat_byte(2)])
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == TRANSACTION_HASH_NOT_FOUND.into());
+}
+
+#[tokio::test]
+async fn get_messages_status_without_base_layer() {
+    let (module, _storage_writer) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let err = module
+        .call::<_, Vec<MessageStatus>>(
+            "starknet_V0_8_getMessagesStatus",
+            [L1TxHash::repeat_byte(1)],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        Error::Call(err) if err == internal_server_error_with_msg(
+            "Unsupported method, since the node isn't connected to a base layer."
+        )
+    );
+}
+
+#[tokio::test]
+async fn get_commit_certificate() {
+    let method_name = "starknet_V0_8_getCommitCertificate";
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let commit_certificate = CommitCertificate::get_test_instance(&mut get_rng());
+    let header_1 = BlockHeader {
+        block_hash: BlockHash(felt!("0x1")),
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    // Only the first block has a certificate.
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &BlockHeader::default())
+        .unwrap()
+        .append_commit_certificate(BlockNumber(0), &commit_certificate)
+        .unwrap()
+        .append_state_diff(BlockNumber(0), starknet_api::state::ThinStateDiff::default())
+        .unwrap()
+        .append_header(BlockNumber(1), &header_1)
+        .unwrap()
+        .append_state_diff(BlockNumber(1), starknet_api::state::ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let res = module
+        .call::<_, Option<CommitCertificate>>(
+            method_name,
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0)))],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res, Some(commit_certificate));
+
+    let res = module
+        .call::<_, Option<CommitCertificate>>(method_name, [BlockId::Tag(Tag::Latest)])
+        .await
+        .unwrap();
+    assert_eq!(res, None);
+
+    let err = module
+        .call::<_, Option<CommitCertificate>>(
+            method_name,
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(2)))],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == BLOCK_NOT_FOUND.into());
+}
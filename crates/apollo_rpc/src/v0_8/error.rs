@@ -51,6 +51,18 @@ pub const INVALID_CONTINUATION_TOKEN: JsonRpcError<String> = JsonRpcError {
 pub const TOO_MANY_KEYS_IN_FILTER: JsonRpcError<String> =
     JsonRpcError { code: 34, message: "Too many keys provided in a filter", data: None };
 
+pub const INVALID_SUBSCRIPTION_ID: JsonRpcError<String> =
+    JsonRpcError { code: 66, message: "Invalid subscription id", data: None };
+
+pub const TOO_MANY_ADDRESSES_IN_FILTER: JsonRpcError<String> = JsonRpcError {
+    code: 67,
+    message: "Too many addresses in filter sender_address filter",
+    data: None,
+};
+
+pub const TOO_MANY_BLOCKS_BACK: JsonRpcError<String> =
+    JsonRpcError { code: 68, message: "Cannot go back more than 1024 blocks", data: None };
+
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
 pub struct ContractError {
     pub revert_error: String,
@@ -72,6 +84,13 @@ impl From<TransactionExecutionError> for JsonRpcError<TransactionExecutionError>
         Self { code: 41, message: "Transaction execution error", data: Some(tx_execution_error) }
     }
 }
+
+pub const STORAGE_PROOF_NOT_SUPPORTED: JsonRpcError<String> = JsonRpcError {
+    code: 42,
+    message: "The node doesn't support storage proofs for blocks that are too far in the past",
+    data: None,
+};
+
 pub const CLASS_ALREADY_DECLARED: JsonRpcError<String> =
     JsonRpcError { code: 51, message: "Class already declared", data: None };
 
@@ -7,6 +7,7 @@ pub mod execution;
 #[cfg(test)]
 mod execution_test;
 pub mod state;
+pub mod subscriptions;
 pub mod transaction;
 pub mod write_api_error;
 pub mod write_api_result;
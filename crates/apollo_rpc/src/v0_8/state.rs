@@ -13,6 +13,9 @@ use starknet_api::contract_class::EntryPointType;
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, GlobalRoot, Nonce};
 use starknet_api::rpc_transaction::EntryPointByType as starknet_api_EntryPointByType;
 use starknet_api::state::{EntryPoint, StorageKey, ThinStateDiff as starknet_api_ThinStateDiff};
+use starknet_committer::storage_proof::StorageProof as CommitterStorageProof;
+use starknet_patricia::patricia_merkle_tree::node_data::inner_node::{BinaryData, EdgeData};
+use starknet_patricia::patricia_merkle_tree::traversal::{Preimage, PreimageMap};
 use starknet_types_core::felt::Felt;
 
 const CONTRACT_CLASS_VERSION: &str = "0.1.0";
@@ -250,3 +253,114 @@ pub struct ReplacedClass {
     pub contract_address: ContractAddress,
     pub class_hash: ClassHash,
 }
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractStorageKeys {
+    pub contract_address: ContractAddress,
+    pub storage_keys: Vec<StorageKey>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct BinaryNode {
+    pub left: Felt,
+    pub right: Felt,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EdgeNode {
+    pub path: Felt,
+    pub length: u8,
+    pub child: Felt,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MerkleNode {
+    Binary(BinaryNode),
+    Edge(EdgeNode),
+}
+
+impl From<Preimage> for MerkleNode {
+    fn from(preimage: Preimage) -> Self {
+        match preimage {
+            Preimage::Binary(BinaryData { left_hash, right_hash }) => {
+                Self::Binary(BinaryNode { left: left_hash.0, right: right_hash.0 })
+            }
+            Preimage::Edge(EdgeData { bottom_hash, path_to_bottom }) => Self::Edge(EdgeNode {
+                path: Felt::from(&path_to_bottom.path),
+                length: path_to_bottom.length.into(),
+                child: bottom_hash.0,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NodeHashToNode {
+    pub node_hash: Felt,
+    pub node: MerkleNode,
+}
+
+/// Converts the nodes of a proof to a list, ordered by the node hashes.
+fn into_node_hash_to_node_mapping(nodes: PreimageMap) -> Vec<NodeHashToNode> {
+    let mut nodes = nodes
+        .into_iter()
+        .map(|(hash, preimage)| NodeHashToNode { node_hash: hash.0, node: preimage.into() })
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|node| node.node_hash);
+    nodes
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractLeafData {
+    pub nonce: Nonce,
+    pub class_hash: ClassHash,
+    pub storage_root: Felt,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractsProof {
+    pub nodes: Vec<NodeHashToNode>,
+    pub contract_leaves_data: Vec<ContractLeafData>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct GlobalRoots {
+    pub contracts_tree_root: Felt,
+    pub classes_tree_root: Felt,
+    pub block_hash: BlockHash,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct StorageProof {
+    pub classes_proof: Vec<NodeHashToNode>,
+    pub contracts_proof: ContractsProof,
+    pub contracts_storage_proofs: Vec<Vec<NodeHashToNode>>,
+    pub global_roots: GlobalRoots,
+}
+
+impl StorageProof {
+    pub fn new(proof: CommitterStorageProof, global_roots: GlobalRoots) -> Self {
+        Self {
+            classes_proof: into_node_hash_to_node_mapping(proof.classes_proof),
+            contracts_proof: ContractsProof {
+                nodes: into_node_hash_to_node_mapping(proof.contracts_proof),
+                contract_leaves_data: proof
+                    .contract_leaves_data
+                    .into_iter()
+                    .map(|contract_state| ContractLeafData {
+                        nonce: contract_state.nonce,
+                        class_hash: contract_state.class_hash,
+                        storage_root: contract_state.storage_root_hash.0,
+                    })
+                    .collect(),
+            },
+            contracts_storage_proofs: proof
+                .contracts_storage_proofs
+                .into_iter()
+                .map(into_node_hash_to_node_mapping)
+                .collect(),
+            global_roots,
+        }
+    }
+}
@@ -0,0 +1,612 @@
+#[cfg(test)]
+#[path = "subscriptions_test.rs"]
+mod subscriptions_test;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use apollo_starknet_client::reader::PendingData;
+use apollo_storage::body::BodyStorageReader;
+use apollo_storage::StorageReader;
+use jsonrpsee::core::{StringError, SubscriptionResult};
+use jsonrpsee::types::{ErrorObjectOwned, Params, SubscriptionId};
+use jsonrpsee::{PendingSubscriptionSink, RpcModule, SubscriptionMessage, SubscriptionSink};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ContractAddress;
+use starknet_api::transaction::{EventKey, Transaction as StarknetApiTransaction, TransactionHash};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, oneshot, RwLock};
+
+use super::api::api_impl::{
+    client_receipt_to_rpc_pending_receipt,
+    do_event_keys_match_filter,
+    read_pending_data,
+};
+use super::api::EventFilter;
+use super::block::{get_accepted_block_number, get_block_header_by_number, BlockHeader};
+use super::error::{
+    BLOCK_NOT_FOUND,
+    INVALID_SUBSCRIPTION_ID,
+    TOO_MANY_ADDRESSES_IN_FILTER,
+    TOO_MANY_BLOCKS_BACK,
+    TOO_MANY_KEYS_IN_FILTER,
+};
+use super::transaction::{Event, TransactionStatus, TransactionWithHash};
+use crate::api::{BlockId, Tag};
+use crate::subscription_manager::{ActiveSubscriptions, RevertedBlocks, SubscriptionNotification};
+use crate::{get_block_status, get_latest_block_number, internal_server_error};
+
+/// The maximal number of blocks behind the latest block that a subscription can start from.
+pub const MAX_BLOCKS_BACK: u64 = 1024;
+/// The maximal number of sender addresses in a pending transactions subscription.
+pub const MAX_ADDRESSES_IN_FILTER: usize = 1024;
+
+/// A notification of a subscription. Reverts of blocks that the subscription already notified
+/// about are sent on the same notification method as the rest of the subscription's items.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SubscriptionItem<T> {
+    Reorg(ReorgData),
+    Item(T),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReorgData {
+    pub starting_block_hash: BlockHash,
+    pub starting_block_number: BlockNumber,
+    pub ending_block_hash: BlockHash,
+    pub ending_block_number: BlockNumber,
+}
+
+impl From<RevertedBlocks> for ReorgData {
+    fn from(reverted_blocks: RevertedBlocks) -> Self {
+        Self {
+            starting_block_hash: reverted_blocks.starting_block.hash,
+            starting_block_number: reverted_blocks.starting_block.number,
+            ending_block_hash: reverted_blocks.ending_block.hash,
+            ending_block_number: reverted_blocks.ending_block.number,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NewHeadsParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<BlockId>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EventsParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<ContractAddress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Vec<HashSet<EventKey>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<BlockId>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TransactionStatusParams {
+    pub transaction_hash: TransactionHash,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct PendingTransactionsParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_details: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_address: Option<Vec<ContractAddress>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TransactionStatusResult {
+    pub transaction_hash: TransactionHash,
+    pub status: TransactionStatus,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PendingTransaction {
+    Full(TransactionWithHash),
+    Hash(TransactionHash),
+}
+
+#[derive(Clone)]
+pub(crate) struct SubscriptionsContext {
+    pub storage_reader: StorageReader,
+    pub pending_data: Arc<RwLock<PendingData>>,
+    pub notifications_sender: broadcast::Sender<SubscriptionNotification>,
+    pub active_subscriptions: ActiveSubscriptions,
+    pub max_events_keys: usize,
+}
+
+/// Returns a module with the WebSocket subscription methods. The subscriptions are closed by
+/// `starknet_unsubscribe`. Each subscription also has its own unsubscribe method, as required by
+/// jsonrpsee.
+pub(crate) fn subscriptions_module(
+    context: SubscriptionsContext,
+) -> RpcModule<SubscriptionsContext> {
+    let mut module = RpcModule::new(context);
+    module
+        .register_subscription(
+            "starknet_subscribeNewHeads",
+            "starknet_subscriptionNewHeads",
+            "starknet_unsubscribeNewHeads",
+            subscribe_new_heads,
+        )
+        .expect("Failed to register starknet_subscribeNewHeads.");
+    module
+        .register_subscription(
+            "starknet_subscribeEvents",
+            "starknet_subscriptionEvents",
+            "starknet_unsubscribeEvents",
+            subscribe_events,
+        )
+        .expect("Failed to register starknet_subscribeEvents.");
+    module
+        .register_subscription(
+            "starknet_subscribeTransactionStatus",
+            "starknet_subscriptionTransactionStatus",
+            "starknet_unsubscribeTransactionStatus",
+            subscribe_transaction_status,
+        )
+        .expect("Failed to register starknet_subscribeTransactionStatus.");
+    module
+        .register_subscription(
+            "starknet_subscribePendingTransactions",
+            "starknet_subscriptionPendingTransactions",
+            "starknet_unsubscribePendingTransactions",
+            subscribe_pending_transactions,
+        )
+        .expect("Failed to register starknet_subscribePendingTransactions.");
+    module
+        .register_method("starknet_unsubscribe", |params, context| {
+            let subscription_id = params.one::<SubscriptionId<'_>>()?.into_owned();
+            if context.active_subscriptions.close(&subscription_id) {
+                Ok(true)
+            } else {
+                Err(ErrorObjectOwned::from(INVALID_SUBSCRIPTION_ID))
+            }
+        })
+        .expect("Failed to register starknet_unsubscribe.");
+    module
+}
+
+async fn subscribe_new_heads(
+    params: Params<'static>,
+    pending: PendingSubscriptionSink,
+    context: Arc<SubscriptionsContext>,
+) -> SubscriptionResult {
+    let notifications = context.notifications_sender.subscribe();
+    let first_block_number = match parse_optional_params::<NewHeadsParams>(&params)
+        .and_then(|params| context.first_block_number(params.block_id))
+    {
+        Ok(first_block_number) => first_block_number,
+        Err(err) => {
+            pending.reject(err).await;
+            return Ok(());
+        }
+    };
+    let Some(mut subscription) = context.accept(pending, notifications).await else {
+        return Ok(());
+    };
+
+    let mut next_block_number = first_block_number;
+    let mut up_to = context.latest_block_number().map_err(error_message)?;
+    loop {
+        if let Some(up_to) = up_to {
+            for header in context.headers(next_block_number, up_to).map_err(error_message)? {
+                next_block_number = header.block_number.unchecked_next();
+                subscription.send(&SubscriptionItem::Item(header)).await?;
+            }
+        }
+        up_to = None;
+        while up_to.is_none() {
+            match subscription.next().await? {
+                None => return Ok(()),
+                Some(SubscriptionNotification::NewBlock(block))
+                    if block.number >= next_block_number =>
+                {
+                    up_to = Some(block.number);
+                }
+                Some(SubscriptionNotification::Reorg(reverted_blocks))
+                    if reverted_blocks.starting_block.number < next_block_number =>
+                {
+                    next_block_number = reverted_blocks.starting_block.number;
+                    subscription
+                        .send(&SubscriptionItem::<BlockHeader>::Reorg(reverted_blocks.into()))
+                        .await?;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+async fn subscribe_events(
+    params: Params<'static>,
+    pending: PendingSubscriptionSink,
+    context: Arc<SubscriptionsContext>,
+) -> SubscriptionResult {
+    let notifications = context.notifications_sender.subscribe();
+    let (filter, first_block_number) = match parse_optional_params::<EventsParams>(&params)
+        .and_then(|params| {
+            if params.keys.as_ref().is_some_and(|keys| keys.len() > context.max_events_keys) {
+                return Err(ErrorObjectOwned::from(TOO_MANY_KEYS_IN_FILTER));
+            }
+            let first_block_number = context.first_block_number(params.block_id)?;
+            Ok((params, first_block_number))
+        }) {
+        Ok(res) => res,
+        Err(err) => {
+            pending.reject(err).await;
+            return Ok(());
+        }
+    };
+    let Some(mut subscription) = context.accept(pending, notifications).await else {
+        return Ok(());
+    };
+
+    let mut next_block_number = first_block_number;
+    let mut up_to = context.latest_block_number().map_err(error_message)?;
+    loop {
+        if let Some(up_to) = up_to {
+            for event in context.events(next_block_number, up_to, &filter).map_err(error_message)? {
+                subscription.send(&SubscriptionItem::Item(event)).await?;
+            }
+            next_block_number = next_block_number.max(up_to.unchecked_next());
+        }
+        up_to = None;
+        while up_to.is_none() {
+            match subscription.next().await? {
+                None => return Ok(()),
+                Some(SubscriptionNotification::NewBlock(block))
+                    if block.number >= next_block_number =>
+                {
+                    up_to = Some(block.number);
+                }
+                Some(SubscriptionNotification::Reorg(reverted_blocks))
+                    if reverted_blocks.starting_block.number < next_block_number =>
+                {
+                    next_block_number = reverted_blocks.starting_block.number;
+                    subscription
+                        .send(&SubscriptionItem::<Event>::Reorg(reverted_blocks.into()))
+                        .await?;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+async fn subscribe_transaction_status(
+    params: Params<'static>,
+    pending: PendingSubscriptionSink,
+    context: Arc<SubscriptionsContext>,
+) -> SubscriptionResult {
+    let notifications = context.notifications_sender.subscribe();
+    let transaction_hash = match params.parse::<TransactionStatusParams>() {
+        Ok(params) => params.transaction_hash,
+        Err(err) => {
+            pending.reject(err).await;
+            return Ok(());
+        }
+    };
+    let Some(mut subscription) = context.accept(pending, notifications).await else {
+        return Ok(());
+    };
+
+    // The last sent status and the block of the transaction. The block is None for transactions
+    // in the pending block.
+    let mut last_status: Option<(TransactionStatus, Option<BlockNumber>)> = None;
+    loop {
+        let status = context.transaction_status(transaction_hash).await.map_err(error_message)?;
+        if let Some((new_status, _)) = &status {
+            if last_status.as_ref().map(|(status, _)| status) != Some(new_status) {
+                subscription
+                    .send(&SubscriptionItem::Item(TransactionStatusResult {
+                        transaction_hash,
+                        status: new_status.clone(),
+                    }))
+                    .await?;
+            }
+        }
+        last_status = status;
+
+        match subscription.next().await? {
+            None => return Ok(()),
+            Some(SubscriptionNotification::Reorg(reverted_blocks)) => {
+                if let Some((_, Some(block_number))) = last_status {
+                    if reverted_blocks.starting_block.number <= block_number
+                        && block_number <= reverted_blocks.ending_block.number
+                    {
+                        subscription
+                            .send(&SubscriptionItem::<TransactionStatusResult>::Reorg(
+                                reverted_blocks.into(),
+                            ))
+                            .await?;
+                        last_status = None;
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+async fn subscribe_pending_transactions(
+    params: Params<'static>,
+    pending: PendingSubscriptionSink,
+    context: Arc<SubscriptionsContext>,
+) -> SubscriptionResult {
+    let notifications = context.notifications_sender.subscribe();
+    let params = match parse_optional_params::<PendingTransactionsParams>(&params) {
+        Ok(params)
+            if params
+                .sender_address
+                .as_ref()
+                .is_some_and(|addresses| addresses.len() > MAX_ADDRESSES_IN_FILTER) =>
+        {
+            pending.reject(TOO_MANY_ADDRESSES_IN_FILTER).await;
+            return Ok(());
+        }
+        Ok(params) => params,
+        Err(err) => {
+            pending.reject(err).await;
+            return Ok(());
+        }
+    };
+    let transaction_details = params.transaction_details.unwrap_or_default();
+    let Some(mut subscription) = context.accept(pending, notifications).await else {
+        return Ok(());
+    };
+
+    loop {
+        let client_transactions = match subscription.next().await? {
+            None => return Ok(()),
+            Some(SubscriptionNotification::PendingTransactions(client_transactions)) => {
+                client_transactions
+            }
+            Some(_) => continue,
+        };
+        for client_transaction in client_transactions {
+            let transaction_hash = client_transaction.transaction_hash();
+            let transaction: StarknetApiTransaction = client_transaction
+                .try_into()
+                .map_err(|err| error_message(internal_server_error(err)))?;
+            if let Some(sender_addresses) = &params.sender_address {
+                if !sender_address(&transaction)
+                    .is_some_and(|sender_address| sender_addresses.contains(&sender_address))
+                {
+                    continue;
+                }
+            }
+            let pending_transaction = if transaction_details {
+                PendingTransaction::Full(TransactionWithHash {
+                    transaction: transaction.try_into().map_err(error_message)?,
+                    transaction_hash,
+                })
+            } else {
+                PendingTransaction::Hash(transaction_hash)
+            };
+            subscription.send(&pending_transaction).await?;
+        }
+    }
+}
+
+impl SubscriptionsContext {
+    async fn accept(
+        &self,
+        pending: PendingSubscriptionSink,
+        notifications: broadcast::Receiver<SubscriptionNotification>,
+    ) -> Option<OpenSubscription> {
+        // Failing to accept means that the connection was closed.
+        let sink = pending.accept().await.ok()?;
+        let unsubscribed = self.active_subscriptions.insert(sink.subscription_id());
+        Some(OpenSubscription {
+            sink,
+            unsubscribed,
+            notifications,
+            active_subscriptions: self.active_subscriptions.clone(),
+        })
+    }
+
+    fn latest_block_number(&self) -> Result<Option<BlockNumber>, ErrorObjectOwned> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        get_latest_block_number(&txn)
+    }
+
+    // Returns the first block to notify about. The subscription starts from the latest block by
+    // default, or only from the next block if the pending block was requested.
+    fn first_block_number(
+        &self,
+        block_id: Option<BlockId>,
+    ) -> Result<BlockNumber, ErrorObjectOwned> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let Some(latest_block_number) = get_latest_block_number(&txn)? else {
+            return match block_id {
+                Some(BlockId::HashOrNumber(_)) => Err(ErrorObjectOwned::from(BLOCK_NOT_FOUND)),
+                _ => Ok(BlockNumber(0)),
+            };
+        };
+        let first_block_number = match block_id {
+            None | Some(BlockId::Tag(Tag::Latest)) => latest_block_number,
+            Some(BlockId::Tag(Tag::Pending)) => latest_block_number.unchecked_next(),
+            Some(block_id @ BlockId::HashOrNumber(_)) => get_accepted_block_number(&txn, block_id)?,
+        };
+        if latest_block_number.0.saturating_sub(first_block_number.0) > MAX_BLOCKS_BACK {
+            return Err(ErrorObjectOwned::from(TOO_MANY_BLOCKS_BACK));
+        }
+        Ok(first_block_number)
+    }
+
+    // Returns the headers of the blocks in the given range, inclusive.
+    fn headers(
+        &self,
+        from: BlockNumber,
+        up_to: BlockNumber,
+    ) -> Result<Vec<BlockHeader>, ErrorObjectOwned> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        from.iter_up_to(up_to.unchecked_next())
+            .map(|block_number| Ok(get_block_header_by_number(&txn, block_number)?.into()))
+            .collect()
+    }
+
+    // Returns the events that match the filter in the blocks in the given range, inclusive.
+    fn events(
+        &self,
+        from: BlockNumber,
+        up_to: BlockNumber,
+        filter: &EventsParams,
+    ) -> Result<Vec<Event>, ErrorObjectOwned> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let filter = EventFilter {
+            from_block: None,
+            to_block: None,
+            continuation_token: None,
+            chunk_size: 0,
+            address: filter.from_address,
+            keys: filter.keys.clone().unwrap_or_default(),
+        };
+        let mut events = vec![];
+        for block_number in from.iter_up_to(up_to.unchecked_next()) {
+            let block_hash = get_block_header_by_number(&txn, block_number)?.block_hash;
+            let transaction_hashes = txn
+                .get_block_transaction_hashes(block_number)
+                .map_err(internal_server_error)?
+                .unwrap_or_default();
+            let transaction_outputs = txn
+                .get_block_transaction_outputs(block_number)
+                .map_err(internal_server_error)?
+                .unwrap_or_default();
+            for (transaction_hash, transaction_output) in
+                transaction_hashes.into_iter().zip(transaction_outputs)
+            {
+                for event in transaction_output.events() {
+                    if filter.address.is_some_and(|address| address != event.from_address)
+                        || !do_event_keys_match_filter(&event.content, &filter)
+                    {
+                        continue;
+                    }
+                    events.push(Event {
+                        block_hash: Some(block_hash),
+                        block_number: Some(block_number),
+                        transaction_hash,
+                        event: event.clone(),
+                    });
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    // Returns the status of the transaction and its block, or None if the transaction is unknown.
+    // The block is None for transactions in the pending block.
+    async fn transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> Result<Option<(TransactionStatus, Option<BlockNumber>)>, ErrorObjectOwned> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if let Some(transaction_index) =
+            txn.get_transaction_idx_by_hash(&transaction_hash).map_err(internal_server_error)?
+        {
+            let Some(output) =
+                txn.get_transaction_output(transaction_index).map_err(internal_server_error)?
+            else {
+                return Ok(None);
+            };
+            let block_number = transaction_index.0;
+            let status = TransactionStatus {
+                finality_status: get_block_status(&txn, block_number)?.into(),
+                execution_status: output.execution_status().clone(),
+            };
+            return Ok(Some((status, Some(block_number))));
+        }
+
+        let pending_data = read_pending_data(&self.pending_data, &txn).await?;
+        let client_transaction = pending_data
+            .block
+            .transactions()
+            .iter()
+            .find(|transaction| transaction.transaction_hash() == transaction_hash);
+        let client_transaction_receipt = pending_data
+            .block
+            .transaction_receipts()
+            .iter()
+            .find(|receipt| receipt.transaction_hash == transaction_hash);
+        let (Some(client_transaction), Some(client_transaction_receipt)) =
+            (client_transaction, client_transaction_receipt)
+        else {
+            return Ok(None);
+        };
+        let receipt = client_receipt_to_rpc_pending_receipt(
+            client_transaction,
+            client_transaction_receipt.clone(),
+        )?;
+        Ok(Some((receipt.transaction_status(), None)))
+    }
+}
+
+// An accepted subscription, registered in the active subscriptions until it's dropped.
+struct OpenSubscription {
+    sink: SubscriptionSink,
+    unsubscribed: oneshot::Receiver<()>,
+    notifications: broadcast::Receiver<SubscriptionNotification>,
+    active_subscriptions: ActiveSubscriptions,
+}
+
+impl OpenSubscription {
+    // Waits for the next notification. Returns None once the subscription is closed.
+    async fn next(&mut self) -> Result<Option<SubscriptionNotification>, StringError> {
+        tokio::select! {
+            _ = self.sink.closed() => Ok(None),
+            _ = &mut self.unsubscribed => Ok(None),
+            notification = self.notifications.recv() => match notification {
+                Ok(notification) => Ok(Some(notification)),
+                Err(RecvError::Lagged(_)) => {
+                    Err("The subscription fell behind the updates of the node.".into())
+                }
+                Err(RecvError::Closed) => Ok(None),
+            },
+        }
+    }
+
+    async fn send<T: Serialize>(&self, item: &T) -> Result<(), StringError> {
+        self.sink.send(SubscriptionMessage::from_json(item)?).await?;
+        Ok(())
+    }
+}
+
+impl Drop for OpenSubscription {
+    fn drop(&mut self) {
+        self.active_subscriptions.remove(&self.sink.subscription_id());
+    }
+}
+
+// All the parameters of the subscriptions with this parser are optional, so the parameters
+// themselves may be omitted.
+fn parse_optional_params<T: DeserializeOwned + Default>(
+    params: &Params<'_>,
+) -> Result<T, ErrorObjectOwned> {
+    if params.as_str().is_none() {
+        return Ok(T::default());
+    }
+    params.parse()
+}
+
+fn error_message(err: ErrorObjectOwned) -> StringError {
+    err.message().into()
+}
+
+fn sender_address(transaction: &StarknetApiTransaction) -> Option<ContractAddress> {
+    match transaction {
+        StarknetApiTransaction::Declare(transaction) => Some(transaction.sender_address()),
+        StarknetApiTransaction::Invoke(transaction) => Some(transaction.sender_address()),
+        StarknetApiTransaction::Deploy(_)
+        | StarknetApiTransaction::DeployAccount(_)
+        | StarknetApiTransaction::L1Handler(_) => None,
+    }
+}
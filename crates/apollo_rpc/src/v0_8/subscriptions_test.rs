@@ -0,0 +1,299 @@
+use apollo_starknet_client::reader::objects::transaction::Transaction as ClientTransaction;
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::{StorageReader, StorageWriter};
+use apollo_test_utils::{get_rng, get_test_block, GetTestInstance};
+use assert_matches::assert_matches;
+use jsonrpsee::core::Error;
+use jsonrpsee::RpcModule;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{
+    Block as StarknetApiBlock,
+    BlockHash,
+    BlockHashAndNumber,
+    BlockHeader as StarknetApiBlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+};
+use starknet_api::state::ThinStateDiff;
+use starknet_api::{contract_address, felt};
+use tokio::sync::broadcast;
+
+use super::{
+    subscriptions_module,
+    PendingTransaction,
+    ReorgData,
+    SubscriptionItem,
+    SubscriptionsContext,
+    TransactionStatusResult,
+    MAX_BLOCKS_BACK,
+};
+use crate::api::{BlockHashOrNumber, BlockId};
+use crate::subscription_manager::{ActiveSubscriptions, RevertedBlocks, SubscriptionNotification};
+use crate::test_utils::{
+    get_starknet_spec_api_schema_for_components,
+    get_test_pending_data,
+    validate_schema,
+    SpecFile,
+};
+use crate::v0_8::block::BlockHeader;
+use crate::v0_8::error::{INVALID_SUBSCRIPTION_ID, TOO_MANY_BLOCKS_BACK};
+use crate::v0_8::transaction::{Event, TransactionFinalityStatus, TransactionStatus};
+use crate::version_config::VERSION_0_8;
+
+fn get_test_module(
+    storage_reader: StorageReader,
+) -> (RpcModule<SubscriptionsContext>, broadcast::Sender<SubscriptionNotification>) {
+    let (notifications_sender, _) = broadcast::channel(100);
+    let module = subscriptions_module(SubscriptionsContext {
+        storage_reader,
+        pending_data: get_test_pending_data(),
+        notifications_sender: notifications_sender.clone(),
+        active_subscriptions: ActiveSubscriptions::default(),
+        max_events_keys: 10,
+    });
+    (module, notifications_sender)
+}
+
+// Writes a block with the given number and hash and returns its header.
+fn append_block(
+    storage_writer: &mut StorageWriter,
+    block_number: BlockNumber,
+    block_hash: BlockHash,
+    mut block: StarknetApiBlock,
+) -> StarknetApiBlockHeader {
+    block.header.block_hash = block_hash;
+    block.header.block_header_without_hash.block_number = block_number;
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &block.header)
+        .unwrap()
+        .append_body(block_number, block.body)
+        .unwrap()
+        .append_state_diff(block_number, ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    block.header
+}
+
+fn block_hash(block_number: u64) -> BlockHash {
+    BlockHash(felt!(block_number + 0x100))
+}
+
+#[tokio::test]
+async fn new_heads() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let (module, notifications_sender) = get_test_module(storage_reader);
+    let headers = (0..3)
+        .map(|i| {
+            append_block(
+                &mut storage_writer,
+                BlockNumber(i),
+                block_hash(i),
+                get_test_block(0, None, None, None),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Start from block 1 and get the blocks up to the latest block.
+    let mut subscription = module
+        .subscribe_unbounded(
+            "starknet_subscribeNewHeads",
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(1)))],
+        )
+        .await
+        .unwrap();
+    let schema = get_starknet_spec_api_schema_for_components(
+        &[(SpecFile::StarknetApiOpenrpc, &["BLOCK_HEADER"])],
+        &VERSION_0_8,
+    );
+    for header in &headers[1..] {
+        let (item, _) = subscription.next::<serde_json::Value>().await.unwrap().unwrap();
+        assert!(validate_schema(&schema, &item));
+        assert_eq!(
+            serde_json::from_value::<SubscriptionItem<BlockHeader>>(item).unwrap(),
+            SubscriptionItem::Item(BlockHeader::from(header.clone()))
+        );
+    }
+
+    // A new block.
+    let header = append_block(
+        &mut storage_writer,
+        BlockNumber(3),
+        block_hash(3),
+        get_test_block(0, None, None, None),
+    );
+    notifications_sender
+        .send(SubscriptionNotification::NewBlock(BlockHashAndNumber {
+            hash: block_hash(3),
+            number: BlockNumber(3),
+        }))
+        .unwrap();
+    let (item, _) = subscription.next::<SubscriptionItem<BlockHeader>>().await.unwrap().unwrap();
+    assert_eq!(item, SubscriptionItem::Item(BlockHeader::from(header)));
+
+    // A reorg of blocks that were sent.
+    let reverted_blocks = RevertedBlocks {
+        starting_block: BlockHashAndNumber { hash: block_hash(2), number: BlockNumber(2) },
+        ending_block: BlockHashAndNumber { hash: block_hash(3), number: BlockNumber(3) },
+    };
+    notifications_sender.send(SubscriptionNotification::Reorg(reverted_blocks)).unwrap();
+    let (item, _) = subscription.next::<SubscriptionItem<BlockHeader>>().await.unwrap().unwrap();
+    assert_eq!(item, SubscriptionItem::Reorg(ReorgData::from(reverted_blocks)));
+}
+
+#[tokio::test]
+async fn new_heads_too_many_blocks_back() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let (module, _notifications_sender) = get_test_module(storage_reader);
+    let latest_block_number = BlockNumber(MAX_BLOCKS_BACK + 1);
+    let mut txn = storage_writer.begin_rw_txn().unwrap();
+    for block_number in BlockNumber(0).iter_up_to(latest_block_number.unchecked_next()) {
+        let header = StarknetApiBlockHeader {
+            block_hash: block_hash(block_number.0),
+            block_header_without_hash: BlockHeaderWithoutHash {
+                block_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        txn = txn
+            .append_header(block_number, &header)
+            .unwrap()
+            .append_state_diff(block_number, ThinStateDiff::default())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let err = module
+        .subscribe_unbounded(
+            "starknet_subscribeNewHeads",
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0)))],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == TOO_MANY_BLOCKS_BACK.into());
+
+    module
+        .subscribe_unbounded(
+            "starknet_subscribeNewHeads",
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(1)))],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn events() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let (module, _notifications_sender) = get_test_module(storage_reader);
+    let address = contract_address!("0x22");
+    let other_address = contract_address!("0x23");
+    let block = get_test_block(2, Some(5), Some(vec![address, other_address]), None);
+    let expected_events = block
+        .body
+        .transaction_outputs
+        .iter()
+        .zip(block.body.transaction_hashes.iter())
+        .flat_map(|(output, transaction_hash)| {
+            output.events().iter().filter(|event| event.from_address == address).map(|event| {
+                Event {
+                    block_hash: Some(block_hash(0)),
+                    block_number: Some(BlockNumber(0)),
+                    transaction_hash: *transaction_hash,
+                    event: event.clone(),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    append_block(&mut storage_writer, BlockNumber(0), block_hash(0), block);
+
+    let mut subscription = module
+        .subscribe_unbounded(
+            "starknet_subscribeEvents",
+            serde_json::json!({ "from_address": address }),
+        )
+        .await
+        .unwrap();
+    let schema = get_starknet_spec_api_schema_for_components(
+        &[(SpecFile::StarknetApiOpenrpc, &["EMITTED_EVENT"])],
+        &VERSION_0_8,
+    );
+    for expected_event in expected_events {
+        let (item, _) = subscription.next::<serde_json::Value>().await.unwrap().unwrap();
+        assert!(validate_schema(&schema, &item));
+        assert_eq!(
+            serde_json::from_value::<SubscriptionItem<Event>>(item).unwrap(),
+            SubscriptionItem::Item(expected_event)
+        );
+    }
+}
+
+#[tokio::test]
+async fn transaction_status() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let (module, _notifications_sender) = get_test_module(storage_reader);
+    let block = get_test_block(1, None, None, None);
+    let transaction_hash = block.body.transaction_hashes[0];
+    let execution_status = block.body.transaction_outputs[0].execution_status().clone();
+    append_block(&mut storage_writer, BlockNumber(0), block_hash(0), block);
+
+    let mut subscription = module
+        .subscribe_unbounded(
+            "starknet_subscribeTransactionStatus",
+            serde_json::json!({ "transaction_hash": transaction_hash }),
+        )
+        .await
+        .unwrap();
+    let (item, _) =
+        subscription.next::<SubscriptionItem<TransactionStatusResult>>().await.unwrap().unwrap();
+    assert_eq!(
+        item,
+        SubscriptionItem::Item(TransactionStatusResult {
+            transaction_hash,
+            status: TransactionStatus {
+                finality_status: TransactionFinalityStatus::AcceptedOnL2,
+                execution_status,
+            },
+        })
+    );
+}
+
+#[tokio::test]
+async fn pending_transactions() {
+    let ((storage_reader, _storage_writer), _temp_dir) = get_test_storage();
+    let (module, notifications_sender) = get_test_module(storage_reader);
+    let client_transaction = ClientTransaction::get_test_instance(&mut get_rng());
+
+    let mut subscription = module
+        .subscribe_unbounded("starknet_subscribePendingTransactions", serde_json::json!({}))
+        .await
+        .unwrap();
+    notifications_sender
+        .send(SubscriptionNotification::PendingTransactions(vec![client_transaction.clone()]))
+        .unwrap();
+    let (item, _) = subscription.next::<PendingTransaction>().await.unwrap().unwrap();
+    assert_eq!(item, PendingTransaction::Hash(client_transaction.transaction_hash()));
+}
+
+#[tokio::test]
+async fn unsubscribe() {
+    let ((storage_reader, _storage_writer), _temp_dir) = get_test_storage();
+    let (module, _notifications_sender) = get_test_module(storage_reader);
+    let subscription = module
+        .subscribe_unbounded("starknet_subscribeNewHeads", serde_json::json!({}))
+        .await
+        .unwrap();
+    let subscription_id = subscription.subscription_id().clone();
+
+    let res = module.call::<_, bool>("starknet_unsubscribe", [&subscription_id]).await.unwrap();
+    assert!(res);
+
+    // The subscription is already closed.
+    let err = module.call::<_, bool>("starknet_unsubscribe", [&subscription_id]).await.unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == INVALID_SUBSCRIPTION_ID.into());
+}
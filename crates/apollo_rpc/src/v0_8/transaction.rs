@@ -672,6 +672,37 @@ pub enum TransactionFinalityStatus {
     AcceptedOnL1,
 }
 
+/// The status of the L1 handler transaction created by a message that was sent from L1 to L2.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct MessageStatus {
+    pub transaction_hash: TransactionHash,
+    pub finality_status: MessageFinalityStatus,
+    /// The execution status, once the transaction is in a block.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub execution_status: Option<TransactionExecutionStatus>,
+}
+
+/// Finality status of an L1 handler transaction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum MessageFinalityStatus {
+    /// The message was sent on L1, but its transaction isn't in a block yet.
+    #[serde(rename = "RECEIVED")]
+    Received,
+    #[serde(rename = "ACCEPTED_ON_L2")]
+    AcceptedOnL2,
+    #[serde(rename = "ACCEPTED_ON_L1")]
+    AcceptedOnL1,
+}
+
+impl From<TransactionFinalityStatus> for MessageFinalityStatus {
+    fn from(status: TransactionFinalityStatus) -> Self {
+        match status {
+            TransactionFinalityStatus::AcceptedOnL2 => MessageFinalityStatus::AcceptedOnL2,
+            TransactionFinalityStatus::AcceptedOnL1 => MessageFinalityStatus::AcceptedOnL1,
+        }
+    }
+}
+
 /// Transaction Finality status on starknet for transactions in the pending block.
 #[derive(
     Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default,
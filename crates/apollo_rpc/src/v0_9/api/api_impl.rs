@@ -0,0 +1,441 @@
+use std::sync::Arc;
+
+use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_rpc_execution::objects::FeeEstimation;
+use apollo_rpc_execution::ExecutionConfig;
+use apollo_starknet_client::reader::PendingData;
+use apollo_starknet_client::writer::{StarknetWriter, WriterClientError};
+use apollo_starknet_client::ClientError;
+use apollo_storage::StorageReader;
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::RpcModule;
+use papyrus_base_layer::L1TxHash;
+use papyrus_common::pending_classes::PendingClasses;
+use starknet_api::block::{BlockHashAndNumber, BlockNumber, CommitCertificate};
+use starknet_api::contract_class::SierraVersion;
+use starknet_api::core::{ChainId, ClassHash, ContractAddress, Nonce};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{TransactionHash, TransactionOffsetInBlock};
+use starknet_types_core::felt::Felt;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+use super::super::block::{to_v0_8_block_id, Block, GeneralBlockHeader, PreConfirmedBlockHeader};
+use super::super::transaction::{GeneralTransactionReceipt, TransactionStatus, Transactions};
+use super::super::write_api_error::{
+    starknet_error_to_declare_error,
+    starknet_error_to_deploy_account_error,
+    starknet_error_to_invoke_error,
+};
+use super::{BlockId, EventFilter, JsonRpcV0_9Server as JsonRpcServer};
+use crate::api::{BlockId as V0_8BlockId, CallRequest, JsonRpcServerTrait};
+use crate::l1_messages::SharedL1MessagesReader;
+use crate::syncing_state::SyncingState;
+use crate::v0_8::api::api_impl::JsonRpcServerImpl as V0_8JsonRpcServerImpl;
+use crate::v0_8::api::{
+    CompiledContractClass,
+    EventFilter as V0_8EventFilter,
+    EventsChunk,
+    GatewayContractClass,
+    JsonRpcV0_8Server,
+    SenderTransactionsChunk,
+    SenderTransactionsFilter,
+    SimulatedTransaction,
+    SimulationFlag,
+    TransactionTraceWithHash,
+};
+use crate::v0_8::block::{Block as V0_8Block, GeneralBlockHeader as V0_8GeneralBlockHeader};
+use crate::v0_8::broadcasted_transaction::{BroadcastedDeclareTransaction, BroadcastedTransaction};
+use crate::v0_8::execution::TransactionTrace;
+use crate::v0_8::state::{ContractStorageKeys, StateUpdate, StorageProof};
+use crate::v0_8::transaction::{
+    MessageFromL1,
+    MessageStatus,
+    TransactionWithHash,
+    TypedDeployAccountTransaction,
+    TypedInvokeTransaction,
+};
+use crate::v0_8::write_api_result::{
+    AddDeclareOkResult,
+    AddDeployAccountOkResult,
+    AddInvokeOkResult,
+};
+use crate::version_config::VERSION_0_9 as VERSION;
+use crate::{get_latest_block_number, internal_server_error};
+
+/// Rpc server. The storage access is shared with V0_8, so each method calls the V0_8 server and
+/// converts the types that changed between the versions.
+pub struct JsonRpcServerImpl {
+    pub inner: V0_8JsonRpcServerImpl,
+}
+
+#[async_trait]
+impl JsonRpcServer for JsonRpcServerImpl {
+    fn spec_version(&self) -> RpcResult<String> {
+        Ok(format!("{VERSION}"))
+    }
+
+    fn block_number(&self) -> RpcResult<BlockNumber> {
+        self.inner.block_number()
+    }
+
+    fn block_hash_and_number(&self) -> RpcResult<BlockHashAndNumber> {
+        self.inner.block_hash_and_number()
+    }
+
+    async fn get_block_w_transaction_hashes(&self, block_id: BlockId) -> RpcResult<Block> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.block_from_v0_8(self.inner.get_block_w_transaction_hashes(block_id).await?)
+    }
+
+    async fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<Block> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.block_from_v0_8(self.inner.get_block_w_full_transactions(block_id).await?)
+    }
+
+    async fn get_block_w_full_transactions_and_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.block_from_v0_8(self.inner.get_block_w_full_transactions_and_receipts(block_id).await?)
+    }
+
+    async fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        block_id: BlockId,
+    ) -> RpcResult<Felt> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_storage_at(contract_address, key, block_id).await
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionWithHash> {
+        self.inner.get_transaction_by_hash(transaction_hash).await
+    }
+
+    async fn get_transaction_by_block_id_and_index(
+        &self,
+        block_id: BlockId,
+        index: TransactionOffsetInBlock,
+    ) -> RpcResult<TransactionWithHash> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_transaction_by_block_id_and_index(block_id, index).await
+    }
+
+    async fn get_block_transaction_count(&self, block_id: BlockId) -> RpcResult<usize> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_block_transaction_count(block_id).await
+    }
+
+    async fn get_state_update(&self, block_id: BlockId) -> RpcResult<StateUpdate> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_state_update(block_id).await
+    }
+
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionStatus> {
+        Ok(self.get_transaction_receipt(transaction_hash).await?.transaction_status())
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<GeneralTransactionReceipt> {
+        let receipt = self.inner.get_transaction_receipt(transaction_hash).await?;
+        Ok(GeneralTransactionReceipt::from_v0_8(receipt, self.pre_confirmed_block_number()?))
+    }
+
+    async fn get_class(
+        &self,
+        block_id: BlockId,
+        class_hash: ClassHash,
+    ) -> RpcResult<GatewayContractClass> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_class(block_id, class_hash).await
+    }
+
+    async fn get_class_at(
+        &self,
+        block_id: BlockId,
+        contract_address: ContractAddress,
+    ) -> RpcResult<GatewayContractClass> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_class_at(block_id, contract_address).await
+    }
+
+    async fn get_class_hash_at(
+        &self,
+        block_id: BlockId,
+        contract_address: ContractAddress,
+    ) -> RpcResult<ClassHash> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_class_hash_at(block_id, contract_address).await
+    }
+
+    async fn get_nonce(
+        &self,
+        block_id: BlockId,
+        contract_address: ContractAddress,
+    ) -> RpcResult<Nonce> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_nonce(block_id, contract_address).await
+    }
+
+    fn chain_id(&self) -> RpcResult<String> {
+        self.inner.chain_id()
+    }
+
+    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk> {
+        let filter = V0_8EventFilter {
+            from_block: filter
+                .from_block
+                .map(|block_id| self.to_v0_8_block_id(block_id))
+                .transpose()?,
+            to_block: filter
+                .to_block
+                .map(|block_id| self.to_v0_8_block_id(block_id))
+                .transpose()?,
+            continuation_token: filter.continuation_token,
+            chunk_size: filter.chunk_size,
+            address: filter.address,
+            keys: filter.keys,
+        };
+        self.inner.get_events(filter).await
+    }
+
+    async fn syncing(&self) -> RpcResult<SyncingState> {
+        self.inner.syncing().await
+    }
+
+    async fn call(&self, request: CallRequest, block_id: BlockId) -> RpcResult<Vec<Felt>> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.call(request, block_id).await
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn add_invoke_transaction(
+        &self,
+        invoke_transaction: TypedInvokeTransaction,
+    ) -> RpcResult<AddInvokeOkResult> {
+        let result =
+            self.inner.writer_client.add_invoke_transaction(&invoke_transaction.into()).await;
+        match result {
+            Ok(res) => Ok(res.into()),
+            Err(WriterClientError::ClientError(ClientError::StarknetError(starknet_error))) => {
+                Err(ErrorObjectOwned::from(starknet_error_to_invoke_error(starknet_error)))
+            }
+            Err(err) => Err(internal_server_error(err)),
+        }
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn add_deploy_account_transaction(
+        &self,
+        deploy_account_transaction: TypedDeployAccountTransaction,
+    ) -> RpcResult<AddDeployAccountOkResult> {
+        let result = self
+            .inner
+            .writer_client
+            .add_deploy_account_transaction(&deploy_account_transaction.into())
+            .await;
+        match result {
+            Ok(res) => Ok(res.into()),
+            Err(WriterClientError::ClientError(ClientError::StarknetError(starknet_error))) => {
+                Err(ErrorObjectOwned::from(starknet_error_to_deploy_account_error(starknet_error)))
+            }
+            Err(err) => Err(internal_server_error(err)),
+        }
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn add_declare_transaction(
+        &self,
+        declare_transaction: BroadcastedDeclareTransaction,
+    ) -> RpcResult<AddDeclareOkResult> {
+        let result = self
+            .inner
+            .writer_client
+            .add_declare_transaction(
+                &declare_transaction.try_into().map_err(internal_server_error)?,
+            )
+            .await;
+        match result {
+            Ok(res) => Ok(res.into()),
+            Err(WriterClientError::ClientError(ClientError::StarknetError(starknet_error))) => {
+                Err(ErrorObjectOwned::from(starknet_error_to_declare_error(starknet_error)))
+            }
+            Err(err) => Err(internal_server_error(err)),
+        }
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<FeeEstimation>> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.estimate_fee(request, simulation_flags, block_id).await
+    }
+
+    async fn estimate_message_fee(
+        &self,
+        message: MessageFromL1,
+        block_id: BlockId,
+    ) -> RpcResult<FeeEstimation> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.estimate_message_fee(message, block_id).await
+    }
+
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulatedTransaction>> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.simulate_transactions(block_id, transactions, simulation_flags).await
+    }
+
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionTrace> {
+        self.inner.trace_transaction(transaction_hash).await
+    }
+
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.trace_block_transactions(block_id).await
+    }
+
+    fn get_compiled_class(
+        &self,
+        block_id: BlockId,
+        class_hash: ClassHash,
+    ) -> RpcResult<(CompiledContractClass, SierraVersion)> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_compiled_class(block_id, class_hash)
+    }
+
+    async fn get_transactions_by_sender(
+        &self,
+        filter: SenderTransactionsFilter,
+    ) -> RpcResult<SenderTransactionsChunk> {
+        self.inner.get_transactions_by_sender(filter).await
+    }
+
+    async fn get_storage_proof(
+        &self,
+        block_id: BlockId,
+        class_hashes: Option<Vec<ClassHash>>,
+        contract_addresses: Option<Vec<ContractAddress>>,
+        contracts_storage_keys: Option<Vec<ContractStorageKeys>>,
+    ) -> RpcResult<StorageProof> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner
+            .get_storage_proof(block_id, class_hashes, contract_addresses, contracts_storage_keys)
+            .await
+    }
+
+    async fn get_messages_status(
+        &self,
+        transaction_hash: L1TxHash,
+    ) -> RpcResult<Vec<MessageStatus>> {
+        self.inner.get_messages_status(transaction_hash).await
+    }
+
+    async fn get_commit_certificate(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<CommitCertificate>> {
+        let block_id = self.to_v0_8_block_id(block_id)?;
+        self.inner.get_commit_certificate(block_id).await
+    }
+}
+
+impl JsonRpcServerImpl {
+    fn to_v0_8_block_id(&self, block_id: BlockId) -> RpcResult<V0_8BlockId> {
+        let txn = self.inner.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        to_v0_8_block_id(&txn, block_id)
+    }
+
+    // The pre-confirmed block is the block that follows the latest block.
+    fn pre_confirmed_block_number(&self) -> RpcResult<BlockNumber> {
+        let txn = self.inner.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        Ok(get_latest_block_number(&txn)?
+            .map_or(BlockNumber(0), |latest_block_number| latest_block_number.unchecked_next()))
+    }
+
+    fn block_from_v0_8(&self, block: V0_8Block) -> RpcResult<Block> {
+        let (header, is_pre_confirmed) = match block.header {
+            V0_8GeneralBlockHeader::BlockHeader(header) => {
+                (GeneralBlockHeader::BlockHeader(header), false)
+            }
+            V0_8GeneralBlockHeader::PendingBlockHeader(header) => (
+                GeneralBlockHeader::PreConfirmedBlockHeader(
+                    PreConfirmedBlockHeader::from_pending_header(
+                        header,
+                        self.pre_confirmed_block_number()?,
+                    ),
+                ),
+                true,
+            ),
+        };
+        Ok(Block {
+            status: block.status,
+            header,
+            transactions: Transactions::from_v0_8(block.transactions, is_pre_confirmed),
+        })
+    }
+}
+
+impl JsonRpcServerTrait for JsonRpcServerImpl {
+    fn new(
+        chain_id: ChainId,
+        execution_config: ExecutionConfig,
+        storage_reader: StorageReader,
+        max_events_chunk_size: usize,
+        max_events_keys: usize,
+        starting_block: BlockHashAndNumber,
+        shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
+        pending_data: Arc<RwLock<PendingData>>,
+        pending_classes: Arc<RwLock<PendingClasses>>,
+        writer_client: Arc<dyn StarknetWriter>,
+        class_manager_client: Option<SharedClassManagerClient>,
+        l1_messages_reader: Option<SharedL1MessagesReader>,
+    ) -> Self {
+        Self {
+            inner: V0_8JsonRpcServerImpl::new(
+                chain_id,
+                execution_config,
+                storage_reader,
+                max_events_chunk_size,
+                max_events_keys,
+                starting_block,
+                shared_highest_block,
+                pending_data,
+                pending_classes,
+                writer_client,
+                class_manager_client,
+                l1_messages_reader,
+            ),
+        }
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        self.into_rpc()
+    }
+}
@@ -0,0 +1,292 @@
+use apollo_storage::base_layer::BaseLayerStorageWriter;
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_test_utils::get_test_block;
+use assert_matches::assert_matches;
+use itertools::Itertools;
+use jsonrpsee::core::Error;
+use jsonrpsee::Methods;
+use pretty_assertions::assert_eq;
+use rand::random;
+use starknet_api::block::{
+    BlockHash,
+    BlockNumber,
+    BlockStatus,
+    BlockTimestamp,
+    GasPricePerToken,
+    StarknetVersion,
+};
+use starknet_api::core::SequencerContractAddress;
+use starknet_api::data_availability::L1DataAvailabilityMode;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::{
+    InvokeTransactionOutput,
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus as StarknetApiTransactionExecutionStatus,
+    TransactionOutput as StarknetApiTransactionOutput,
+};
+
+use super::super::block::{Block, GeneralBlockHeader, PreConfirmedBlockHeader};
+use super::super::transaction::{
+    TransactionExecutionStatus,
+    TransactionFinalityStatus,
+    TransactionStatus,
+    Transactions,
+};
+use super::api_impl::JsonRpcServerImpl;
+use super::{BlockId, Tag};
+use crate::api::BlockHashOrNumber;
+use crate::test_utils::{
+    call_api_then_assert_and_validate_schema_for_err,
+    call_api_then_assert_and_validate_schema_for_result,
+    get_method_names_from_spec,
+    get_test_pending_data,
+    get_test_rpc_server_and_storage_writer,
+    get_test_rpc_server_and_storage_writer_from_params,
+    method_name_to_spec_method_name,
+    SpecFile,
+};
+use crate::v0_8::error::BLOCK_NOT_FOUND;
+use crate::version_config::VERSION_0_9 as VERSION;
+
+#[tokio::test]
+async fn spec_version() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        "starknet_V0_9_specVersion",
+        vec![],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &format!("{VERSION}"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn get_pre_confirmed_block() {
+    let method_name = "starknet_V0_9_getBlockWithTxHashes";
+    let pending_data = get_test_pending_data();
+    let (module, mut storage_writer) = get_test_rpc_server_and_storage_writer_from_params::<
+        JsonRpcServerImpl,
+    >(None, None, Some(pending_data.clone()), None, None);
+
+    let mut block = get_test_block(1, None, None, None);
+    let block_hash = BlockHash(random::<u64>().into());
+    block.header.block_hash = block_hash;
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block.header.block_header_without_hash.block_number, &block.header)
+        .unwrap()
+        .append_body(block.header.block_header_without_hash.block_number, block.body)
+        .unwrap()
+        .append_state_diff(
+            block.header.block_header_without_hash.block_number,
+            ThinStateDiff::default(),
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let sequencer_address = SequencerContractAddress(random::<u64>().into());
+    let timestamp = BlockTimestamp(random::<u64>());
+    let l1_gas_price = GasPricePerToken {
+        price_in_wei: random::<u128>().into(),
+        price_in_fri: random::<u128>().into(),
+    };
+    let starknet_version = StarknetVersion::V0_10_0;
+    {
+        let pending_block = &mut pending_data.write().await.block;
+        *pending_block.parent_block_hash_mutable() = block_hash;
+        *pending_block.timestamp_mutable() = timestamp;
+        *pending_block.sequencer_address_mutable() = sequencer_address;
+        pending_block.set_l1_gas_price(&l1_gas_price);
+        *pending_block.starknet_version_mutable() = starknet_version.to_string();
+    }
+
+    // The pre-confirmed block follows the latest block and has no status.
+    let expected_block = Block {
+        status: None,
+        header: GeneralBlockHeader::PreConfirmedBlockHeader(PreConfirmedBlockHeader {
+            block_number: block.header.block_header_without_hash.block_number.unchecked_next(),
+            sequencer_address,
+            timestamp,
+            l1_gas_price,
+            l1_data_gas_price: GasPricePerToken::default(),
+            l2_gas_price: GasPricePerToken { price_in_wei: 0_u8.into(), price_in_fri: 0_u8.into() },
+            l1_da_mode: L1DataAvailabilityMode::Calldata,
+            starknet_version: starknet_version.to_string(),
+        }),
+        transactions: Transactions::Hashes(vec![]),
+    };
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        method_name,
+        vec![Box::new(BlockId::Tag(Tag::PreConfirmed))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &expected_block,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn get_l1_accepted_block() {
+    let method_name = "starknet_V0_9_getBlockWithTxHashes";
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+
+    for block_number in [BlockNumber(0), BlockNumber(1)] {
+        let mut block = get_test_block(0, None, None, None);
+        block.header.block_header_without_hash.block_number = block_number;
+        block.header.block_hash = BlockHash(random::<u64>().into());
+        storage_writer
+            .begin_rw_txn()
+            .unwrap()
+            .append_header(block_number, &block.header)
+            .unwrap()
+            .append_body(block_number, block.body)
+            .unwrap()
+            .append_state_diff(block_number, ThinStateDiff::default())
+            .unwrap()
+            .commit()
+            .unwrap();
+    }
+
+    // No block was accepted on L1 yet.
+    call_api_then_assert_and_validate_schema_for_err::<_, Block>(
+        &module,
+        method_name,
+        vec![Box::new(BlockId::Tag(Tag::L1Accepted))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &BLOCK_NOT_FOUND.into(),
+    )
+    .await;
+
+    // Only the first block was accepted on L1, so it's returned instead of the latest block.
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .update_base_layer_block_marker(&BlockNumber(1))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let expected_block = module
+        .call::<_, Block>(
+            method_name,
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0)))],
+        )
+        .await
+        .unwrap();
+    assert_eq!(expected_block.status, Some(BlockStatus::AcceptedOnL1));
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        method_name,
+        vec![Box::new(BlockId::Tag(Tag::L1Accepted))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &expected_block,
+    )
+    .await;
+
+    let latest_block =
+        module.call::<_, Block>(method_name, [BlockId::Tag(Tag::Latest)]).await.unwrap();
+    assert_matches!(
+        latest_block.header,
+        GeneralBlockHeader::BlockHeader(header) if header.block_number == BlockNumber(1)
+    );
+}
+
+#[tokio::test]
+async fn get_transaction_status() {
+    let method_name = "starknet_V0_9_getTransactionStatus";
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+
+    let revert_reason = "Reverted".to_string();
+    let mut block = get_test_block(1, None, None, None);
+    block.body.transaction_outputs[0] =
+        StarknetApiTransactionOutput::Invoke(InvokeTransactionOutput {
+            execution_status: StarknetApiTransactionExecutionStatus::Reverted(
+                RevertedTransactionExecutionStatus { revert_reason: revert_reason.clone() },
+            ),
+            ..Default::default()
+        });
+    let transaction_hash = block.body.transaction_hashes[0];
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block.header.block_header_without_hash.block_number, &block.header)
+        .unwrap()
+        .append_body(block.header.block_header_without_hash.block_number, block.body)
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // The revert reason is returned as the failure reason.
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        method_name,
+        vec![Box::new(transaction_hash)],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &TransactionStatus {
+            finality_status: TransactionFinalityStatus::AcceptedOnL2,
+            execution_status: TransactionExecutionStatus::Reverted,
+            failure_reason: Some(revert_reason),
+        },
+    )
+    .await;
+
+    // Ask for a transaction in a block that was accepted on L1.
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .update_base_layer_block_marker(
+            &block.header.block_header_without_hash.block_number.unchecked_next(),
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+    let res = module.call::<_, TransactionStatus>(method_name, [transaction_hash]).await.unwrap();
+    assert_eq!(res.finality_status, TransactionFinalityStatus::AcceptedOnL1);
+}
+
+#[tokio::test]
+async fn get_block_with_invalid_tag() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+
+    // The pending tag was replaced by the pre_confirmed tag.
+    let err = module
+        .call::<_, Block>("starknet_V0_9_getBlockWithTxHashes", ["pending"])
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(_));
+}
+
+#[test]
+fn spec_api_methods_coverage() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let implemented_methods: Methods = module.into();
+    let implemented_method_names = implemented_methods
+        .method_names()
+        .map(method_name_to_spec_method_name)
+        .sorted()
+        .collect::<Vec<_>>();
+    let method_names_in_spec = get_method_names_from_spec(&VERSION)
+        .iter()
+        .map(|method| method.clone().replace('\"', ""))
+        .sorted()
+        .collect::<Vec<_>>();
+
+    // Methods in the spec are a subset of the implemented methods.
+    assert!(
+        method_names_in_spec.iter().all(|method| implemented_method_names.contains(method)),
+        "Implemented methods: {implemented_method_names:#?}, methods in spec: \
+         {method_names_in_spec:#?}"
+    );
+}
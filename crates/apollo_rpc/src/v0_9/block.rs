@@ -0,0 +1,84 @@
+use apollo_storage::base_layer::BaseLayerStorageReader;
+use apollo_storage::db::TransactionKind;
+use apollo_storage::StorageTxn;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockNumber, BlockStatus, BlockTimestamp, GasPricePerToken};
+use starknet_api::core::SequencerContractAddress;
+use starknet_api::data_availability::L1DataAvailabilityMode;
+
+use super::api::{BlockId, Tag};
+use super::transaction::Transactions;
+use crate::api::{BlockHashOrNumber, BlockId as V0_8BlockId, Tag as V0_8Tag};
+use crate::internal_server_error;
+use crate::v0_8::block::{BlockHeader, PendingBlockHeader};
+use crate::v0_8::error::BLOCK_NOT_FOUND;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+pub struct PreConfirmedBlockHeader {
+    pub block_number: BlockNumber,
+    pub sequencer_address: SequencerContractAddress,
+    pub timestamp: BlockTimestamp,
+    pub l1_gas_price: GasPricePerToken,
+    pub l1_data_gas_price: GasPricePerToken,
+    pub l2_gas_price: GasPricePerToken,
+    pub l1_da_mode: L1DataAvailabilityMode,
+    pub starknet_version: String,
+}
+
+impl PreConfirmedBlockHeader {
+    pub(crate) fn from_pending_header(
+        header: PendingBlockHeader,
+        block_number: BlockNumber,
+    ) -> Self {
+        Self {
+            block_number,
+            sequencer_address: header.sequencer_address,
+            timestamp: header.timestamp,
+            l1_gas_price: header.l1_gas_price,
+            l1_data_gas_price: header.l1_data_gas_price,
+            l2_gas_price: header.l2_gas_price,
+            l1_da_mode: header.l1_da_mode,
+            starknet_version: header.starknet_version,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum GeneralBlockHeader {
+    BlockHeader(BlockHeader),
+    PreConfirmedBlockHeader(PreConfirmedBlockHeader),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct Block {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<BlockStatus>,
+    #[serde(flatten)]
+    pub header: GeneralBlockHeader,
+    pub transactions: Transactions,
+}
+
+/// Converts the given block id to the block id of V0_8, on whose implementation this version is
+/// based. The pre-confirmed block is the pending block of V0_8, and the l1_accepted tag is
+/// resolved to the number of the latest block that was accepted on L1.
+pub(crate) fn to_v0_8_block_id<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    block_id: BlockId,
+) -> Result<V0_8BlockId, ErrorObjectOwned> {
+    Ok(match block_id {
+        BlockId::HashOrNumber(hash_or_number) => V0_8BlockId::HashOrNumber(hash_or_number),
+        BlockId::Tag(Tag::Latest) => V0_8BlockId::Tag(V0_8Tag::Latest),
+        BlockId::Tag(Tag::PreConfirmed) => V0_8BlockId::Tag(V0_8Tag::Pending),
+        BlockId::Tag(Tag::L1Accepted) => {
+            let block_number = txn
+                .get_base_layer_block_marker()
+                .map_err(internal_server_error)?
+                .prev()
+                .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))?;
+            V0_8BlockId::HashOrNumber(BlockHashOrNumber::Number(block_number))
+        }
+    })
+}
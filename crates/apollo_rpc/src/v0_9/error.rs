@@ -0,0 +1,9 @@
+use crate::v0_8::error::JsonRpcError;
+
+// The errors that were added in this version. The rest of the errors are shared with V0_8.
+
+pub const REPLACEMENT_TRANSACTION_UNDERPRICED: JsonRpcError<String> =
+    JsonRpcError { code: 64, message: "Replacement transaction is underpriced", data: None };
+
+pub const FEE_BELOW_MINIMUM: JsonRpcError<String> =
+    JsonRpcError { code: 65, message: "Transaction fee below minimum", data: None };
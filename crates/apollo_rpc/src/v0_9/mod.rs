@@ -0,0 +1,5 @@
+pub mod api;
+pub mod block;
+pub mod error;
+pub mod transaction;
+pub mod write_api_error;
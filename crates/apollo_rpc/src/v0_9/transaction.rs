@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use starknet_api::transaction::{
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus as StarknetApiTransactionExecutionStatus,
+    TransactionHash,
+};
+
+use crate::v0_8::transaction::{
+    GeneralTransactionReceipt as V0_8GeneralTransactionReceipt,
+    PendingTransactionOutput,
+    Transaction,
+    TransactionFinalityStatus as V0_8TransactionFinalityStatus,
+    TransactionOutput,
+    TransactionReceipt,
+    TransactionReceiptInBlock as V0_8TransactionReceiptInBlock,
+    TransactionWithHash,
+    Transactions as V0_8Transactions,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum Transactions {
+    Hashes(Vec<TransactionHash>),
+    Full(Vec<TransactionWithHash>),
+    FullWithReceipts(Vec<TransactionWithReceipt>),
+}
+
+impl Transactions {
+    /// Converts the transactions of a V0_8 block. The receipts of transactions in the pending
+    /// block of V0_8 are marked as pre-confirmed.
+    pub(crate) fn from_v0_8(transactions: V0_8Transactions, is_pre_confirmed: bool) -> Self {
+        match transactions {
+            V0_8Transactions::Hashes(hashes) => Self::Hashes(hashes),
+            V0_8Transactions::Full(transactions) => Self::Full(transactions),
+            V0_8Transactions::FullWithReceipts(transactions) => Self::FullWithReceipts(
+                transactions
+                    .into_iter()
+                    .map(|transaction_with_receipt| TransactionWithReceipt {
+                        receipt: TransactionReceiptInBlock::from_v0_8(
+                            transaction_with_receipt.receipt,
+                            is_pre_confirmed,
+                        ),
+                        transaction: transaction_with_receipt.transaction,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct TransactionWithReceipt {
+    pub receipt: TransactionReceiptInBlock,
+    pub transaction: Transaction,
+}
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct TransactionReceiptInBlock {
+    pub finality_status: TransactionFinalityStatus,
+    pub transaction_hash: TransactionHash,
+    #[serde(flatten)]
+    pub output: TransactionOutput,
+}
+
+impl TransactionReceiptInBlock {
+    fn from_v0_8(receipt: V0_8TransactionReceiptInBlock, is_pre_confirmed: bool) -> Self {
+        Self {
+            finality_status: if is_pre_confirmed {
+                TransactionFinalityStatus::PreConfirmed
+            } else {
+                receipt.finality_status.into()
+            },
+            transaction_hash: receipt.transaction_hash,
+            output: receipt.output,
+        }
+    }
+}
+
+/// Transaction finality status on starknet.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum TransactionFinalityStatus {
+    /// The transaction is in the block that is currently being constructed.
+    #[serde(rename = "PRE_CONFIRMED")]
+    PreConfirmed,
+    /// The transaction passed the validation and entered an actual created block.
+    #[serde(rename = "ACCEPTED_ON_L2")]
+    AcceptedOnL2,
+    /// The transaction was accepted on-chain.
+    #[serde(rename = "ACCEPTED_ON_L1")]
+    AcceptedOnL1,
+}
+
+impl From<V0_8TransactionFinalityStatus> for TransactionFinalityStatus {
+    fn from(status: V0_8TransactionFinalityStatus) -> Self {
+        match status {
+            V0_8TransactionFinalityStatus::AcceptedOnL2 => TransactionFinalityStatus::AcceptedOnL2,
+            V0_8TransactionFinalityStatus::AcceptedOnL1 => TransactionFinalityStatus::AcceptedOnL1,
+        }
+    }
+}
+
+/// Transaction finality status on starknet for transactions in the pre-confirmed block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum PreConfirmedTransactionFinalityStatus {
+    #[serde(rename = "PRE_CONFIRMED")]
+    PreConfirmed,
+}
+
+/// The execution status of a transaction, without the revert reason that is returned separately
+/// as the failure reason.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum TransactionExecutionStatus {
+    #[serde(rename = "SUCCEEDED")]
+    Succeeded,
+    #[serde(rename = "REVERTED")]
+    Reverted,
+}
+
+// The node doesn't track the mempool, so it doesn't return the RECEIVED and CANDIDATE statuses of
+// transactions that aren't in a block yet.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct TransactionStatus {
+    pub finality_status: TransactionFinalityStatus,
+    pub execution_status: TransactionExecutionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+}
+
+impl TransactionStatus {
+    fn new(
+        finality_status: TransactionFinalityStatus,
+        execution_status: &StarknetApiTransactionExecutionStatus,
+    ) -> Self {
+        let (execution_status, failure_reason) = match execution_status {
+            StarknetApiTransactionExecutionStatus::Succeeded => {
+                (TransactionExecutionStatus::Succeeded, None)
+            }
+            StarknetApiTransactionExecutionStatus::Reverted(
+                RevertedTransactionExecutionStatus { revert_reason },
+            ) => (TransactionExecutionStatus::Reverted, Some(revert_reason.clone())),
+        };
+        Self { finality_status, execution_status, failure_reason }
+    }
+}
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum GeneralTransactionReceipt {
+    TransactionReceipt(TransactionReceipt),
+    PreConfirmedTransactionReceipt(PreConfirmedTransactionReceipt),
+}
+
+impl GeneralTransactionReceipt {
+    /// Converts a V0_8 receipt. A pending receipt of V0_8 becomes a receipt of a transaction in the
+    /// pre-confirmed block, which has the given block number.
+    pub(crate) fn from_v0_8(
+        receipt: V0_8GeneralTransactionReceipt,
+        pre_confirmed_block_number: BlockNumber,
+    ) -> Self {
+        match receipt {
+            V0_8GeneralTransactionReceipt::TransactionReceipt(receipt) => {
+                Self::TransactionReceipt(receipt)
+            }
+            V0_8GeneralTransactionReceipt::PendingTransactionReceipt(receipt) => {
+                Self::PreConfirmedTransactionReceipt(PreConfirmedTransactionReceipt {
+                    finality_status: PreConfirmedTransactionFinalityStatus::PreConfirmed,
+                    transaction_hash: receipt.transaction_hash,
+                    block_number: pre_confirmed_block_number,
+                    output: receipt.output,
+                })
+            }
+        }
+    }
+
+    pub fn transaction_status(&self) -> TransactionStatus {
+        match self {
+            GeneralTransactionReceipt::TransactionReceipt(receipt) => TransactionStatus::new(
+                receipt.finality_status.into(),
+                receipt.output.execution_status(),
+            ),
+            GeneralTransactionReceipt::PreConfirmedTransactionReceipt(receipt) => {
+                TransactionStatus::new(
+                    TransactionFinalityStatus::PreConfirmed,
+                    receipt.output.execution_status(),
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PreConfirmedTransactionReceipt {
+    pub finality_status: PreConfirmedTransactionFinalityStatus,
+    pub transaction_hash: TransactionHash,
+    pub block_number: BlockNumber,
+    #[serde(flatten)]
+    pub output: PendingTransactionOutput,
+}
@@ -0,0 +1,42 @@
+use apollo_starknet_client::starknet_error::{
+    KnownStarknetErrorCode,
+    StarknetError,
+    StarknetErrorCode,
+};
+
+use super::error::{FEE_BELOW_MINIMUM, REPLACEMENT_TRANSACTION_UNDERPRICED};
+use crate::v0_8::error::JsonRpcError;
+use crate::v0_8::write_api_error as v0_8_write_api_error;
+
+#[cfg(test)]
+#[path = "write_api_error_test.rs"]
+mod write_api_error_test;
+
+pub(crate) fn starknet_error_to_invoke_error(error: StarknetError) -> JsonRpcError<String> {
+    starknet_error_to_new_error(&error)
+        .unwrap_or_else(|| v0_8_write_api_error::starknet_error_to_invoke_error(error))
+}
+
+pub(crate) fn starknet_error_to_declare_error(error: StarknetError) -> JsonRpcError<String> {
+    starknet_error_to_new_error(&error)
+        .unwrap_or_else(|| v0_8_write_api_error::starknet_error_to_declare_error(error))
+}
+
+pub(crate) fn starknet_error_to_deploy_account_error(error: StarknetError) -> JsonRpcError<String> {
+    starknet_error_to_new_error(&error)
+        .unwrap_or_else(|| v0_8_write_api_error::starknet_error_to_deploy_account_error(error))
+}
+
+// Converts the errors that V0_8 doesn't have. The rest are converted the same as in V0_8.
+fn starknet_error_to_new_error(error: &StarknetError) -> Option<JsonRpcError<String>> {
+    let StarknetErrorCode::KnownErrorCode(known_error_code) = &error.code else {
+        return None;
+    };
+    match known_error_code {
+        KnownStarknetErrorCode::FeeBelowMinimum => Some(FEE_BELOW_MINIMUM),
+        KnownStarknetErrorCode::ReplacementTransactionUnderpriced => {
+            Some(REPLACEMENT_TRANSACTION_UNDERPRICED)
+        }
+        _ => None,
+    }
+}
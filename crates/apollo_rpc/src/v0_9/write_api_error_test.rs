@@ -0,0 +1,95 @@
+use apollo_starknet_client::starknet_error::{
+    KnownStarknetErrorCode,
+    StarknetError,
+    StarknetErrorCode,
+};
+use enum_iterator::all;
+use jsonrpsee::types::ErrorObjectOwned;
+use pretty_assertions::assert_eq;
+
+use super::super::error::{FEE_BELOW_MINIMUM, REPLACEMENT_TRANSACTION_UNDERPRICED};
+use super::{
+    starknet_error_to_declare_error,
+    starknet_error_to_deploy_account_error,
+    starknet_error_to_invoke_error,
+};
+use crate::test_utils::{get_starknet_spec_api_schema_for_method_errors, SpecFile};
+use crate::v0_8::error::JsonRpcError;
+use crate::version_config::VERSION_0_9 as Version;
+
+const MESSAGE: &str = "message";
+const UNKNOWN_CODE: &str = "code";
+
+fn starknet_errors() -> impl Iterator<Item = StarknetError> {
+    all::<KnownStarknetErrorCode>()
+        .map(StarknetErrorCode::KnownErrorCode)
+        .chain([StarknetErrorCode::UnknownErrorCode(UNKNOWN_CODE.to_owned())])
+        .map(|error_code| StarknetError { code: error_code, message: MESSAGE.to_owned() })
+}
+
+fn test_error_from_conversion_fits_rpc<F: Fn(StarknetError) -> JsonRpcError<String>>(
+    f: F,
+    spec_method: &str,
+) {
+    let schema = get_starknet_spec_api_schema_for_method_errors(
+        &[(SpecFile::WriteApi, &[spec_method])],
+        &Version,
+    );
+    for starknet_error in starknet_errors() {
+        // Converting into ErrorObjectOwned since it has serialization.
+        let rpc_error: ErrorObjectOwned = f(starknet_error).into();
+        let mut json_value = serde_json::to_value(rpc_error).unwrap();
+        json_value.as_object_mut().unwrap().retain(|_, v| !v.is_null());
+        assert!(schema.is_valid(&json_value));
+    }
+}
+
+#[test]
+fn starknet_error_to_invoke_error_result_fits_specs() {
+    test_error_from_conversion_fits_rpc(
+        starknet_error_to_invoke_error,
+        "starknet_addInvokeTransaction",
+    );
+}
+
+#[test]
+fn starknet_error_to_declare_error_result_fits_specs() {
+    test_error_from_conversion_fits_rpc(
+        starknet_error_to_declare_error,
+        "starknet_addDeclareTransaction",
+    );
+}
+
+#[test]
+fn starknet_error_to_deploy_account_error_result_fits_specs() {
+    test_error_from_conversion_fits_rpc(
+        starknet_error_to_deploy_account_error,
+        "starknet_addDeployAccountTransaction",
+    );
+}
+
+#[test]
+fn new_errors_conversion() {
+    for (known_error_code, expected_error) in [
+        (KnownStarknetErrorCode::FeeBelowMinimum, FEE_BELOW_MINIMUM),
+        (
+            KnownStarknetErrorCode::ReplacementTransactionUnderpriced,
+            REPLACEMENT_TRANSACTION_UNDERPRICED,
+        ),
+    ] {
+        let starknet_error = StarknetError {
+            code: StarknetErrorCode::KnownErrorCode(known_error_code),
+            message: MESSAGE.to_owned(),
+        };
+        for f in [
+            starknet_error_to_invoke_error,
+            starknet_error_to_declare_error,
+            starknet_error_to_deploy_account_error,
+        ] {
+            assert_eq!(
+                ErrorObjectOwned::from(f(starknet_error.clone())),
+                ErrorObjectOwned::from(expected_error.clone())
+            );
+        }
+    }
+}
@@ -15,8 +15,6 @@ pub enum VersionState {
     #[allow(dead_code)]
     Deprecated,
 }
-// TODO(Lev): Since we stop support versioning and stay only with V0_8, we need to get rid of the
-// VersionState enum altogether.
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct VersionId {
@@ -33,5 +31,9 @@ impl std::fmt::Display for VersionId {
 }
 
 /// latest version must be set as supported
-pub const VERSION_CONFIG: &[(VersionId, VersionState)] = &[(VERSION_0_8, VersionState::Supported)];
+pub const VERSION_CONFIG: &[(VersionId, VersionState)] =
+    &[(VERSION_0_8, VersionState::Supported), (VERSION_0_9, VersionState::Supported)];
 pub const VERSION_0_8: VersionId = VersionId { name: "V0_8", patch: 0 };
+pub const VERSION_0_9: VersionId = VersionId { name: "V0_9", patch: 0 };
+/// The version that serves requests whose path doesn't specify a version.
+pub const DEFAULT_VERSION: VersionId = VERSION_0_8;
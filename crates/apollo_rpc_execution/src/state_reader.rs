@@ -170,34 +170,25 @@ impl BlockifierStateReader for ExecutionStateReader {
                 }
             }
         }
-        let block_number = self
-            .storage_reader
-            .begin_ro_txn()
-            .map_err(storage_err_to_state_err)?
-            .get_state_reader()
-            .map_err(storage_err_to_state_err)?
+        let txn = self.storage_reader.begin_ro_txn().map_err(storage_err_to_state_err)?;
+        let state_reader = txn.get_state_reader().map_err(storage_err_to_state_err)?;
+        let block_number = state_reader
             .get_class_definition_block_number(&class_hash)
             .map_err(storage_err_to_state_err)?
             .ok_or(StateError::UndeclaredClassHash(class_hash))?;
 
-        let state_diff = self
-            .storage_reader
-            .begin_ro_txn()
-            .map_err(storage_err_to_state_err)?
-            .get_state_diff(block_number)
+        // The compiled class hash as of the declaration, which is also available for classes of a
+        // checkpoint, whose state diff isn't stored.
+        state_reader
+            .get_compiled_class_hash_at(
+                StateNumber::unchecked_right_after_block(block_number),
+                &class_hash,
+            )
             .map_err(storage_err_to_state_err)?
             .ok_or(StateError::StateReadError(format!(
-                "Inner storage error. Missing state diff at block {block_number}."
-            )))?;
-
-        let compiled_class_hash = state_diff.declared_classes.get(&class_hash).ok_or(
-            StateError::StateReadError(format!(
                 "Inner storage error. Missing class declaration at block {block_number}, class \
                  {class_hash}."
-            )),
-        )?;
-
-        Ok(*compiled_class_hash)
+            )))
     }
 }
 
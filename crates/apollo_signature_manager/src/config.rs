@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use starknet_api::crypto::utils::PrivateKey;
+use starknet_core::types::Felt;
+use validator::Validate;
+
+#[cfg(any(test, feature = "testing"))]
+use crate::signature_manager::LocalKeyStore;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Validate)]
+pub struct SignatureManagerConfig {
+    /// The validator key of the node, which it signs its votes and identity with.
+    #[serde(deserialize_with = "deserialize_optional_private_key")]
+    pub private_key: Option<PrivateKey>,
+}
+
+impl SignatureManagerConfig {
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_for_testing() -> Self {
+        Self { private_key: Some(LocalKeyStore::new_for_testing().private_key) }
+    }
+}
+
+impl SerializeConfig for SignatureManagerConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([ser_param(
+            "private_key",
+            &serialize_optional_private_key(&self.private_key),
+            "The validator key of the node, which it signs its votes and identity with. Must be \
+             set to run the signature manager.",
+            ParamPrivacyInput::Private,
+        )])
+    }
+}
+
+fn serialize_optional_private_key(private_key: &Option<PrivateKey>) -> String {
+    private_key.map(|private_key| format!("{:#x}", private_key.0)).unwrap_or_default()
+}
+
+fn deserialize_optional_private_key<'de, D>(de: D) -> Result<Option<PrivateKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    if raw_str.is_empty() {
+        return Ok(None);
+    }
+
+    let private_key = Felt::from_hex(&raw_str)
+        .map_err(|e| D::Error::custom(format!("Couldn't deserialize private key: {e}")))?;
+    Ok(Some(PrivateKey(private_key)))
+}
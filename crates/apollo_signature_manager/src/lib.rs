@@ -1,4 +1,5 @@
 pub mod communication;
+pub mod config;
 pub mod signature_manager;
 
 use std::ops::Deref;
@@ -6,20 +7,22 @@ use std::ops::Deref;
 use apollo_infra::component_definitions::ComponentStarter;
 use async_trait::async_trait;
 
+use crate::config::SignatureManagerConfig;
 use crate::signature_manager::{LocalKeyStore, SignatureManager as GenericSignatureManager};
 
 #[derive(Clone, Debug)]
 pub struct LocalKeyStoreSignatureManager(pub GenericSignatureManager<LocalKeyStore>);
 
 impl LocalKeyStoreSignatureManager {
-    pub fn new() -> Self {
-        Self(GenericSignatureManager::new(LocalKeyStore::new_for_testing()))
+    pub fn new(config: SignatureManagerConfig) -> Self {
+        let private_key =
+            config.private_key.expect("The signature manager requires a private key to sign with.");
+        Self(GenericSignatureManager::new(LocalKeyStore::new(private_key)))
     }
-}
 
-impl Default for LocalKeyStoreSignatureManager {
-    fn default() -> Self {
-        Self::new()
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_for_testing() -> Self {
+        Self(GenericSignatureManager::new(LocalKeyStore::new_for_testing()))
     }
 }
 
@@ -33,10 +36,8 @@ impl Deref for LocalKeyStoreSignatureManager {
 
 pub use LocalKeyStoreSignatureManager as SignatureManager;
 
-// TODO(Elin): understand how key store would look in production and better define the way the
-// signature manager is created.
-pub fn create_signature_manager() -> SignatureManager {
-    SignatureManager::new()
+pub fn create_signature_manager(config: SignatureManagerConfig) -> SignatureManager {
+    SignatureManager::new(config)
 }
 
 #[async_trait]
@@ -10,7 +10,6 @@ use apollo_signature_manager_types::{
 };
 use async_trait::async_trait;
 use blake2s::blake2s_to_felt;
-use starknet_api::block::BlockHash;
 use starknet_api::core::Nonce;
 use starknet_api::crypto::utils::{PrivateKey, PublicKey, RawSignature};
 use starknet_core::crypto::{ecdsa_sign, ecdsa_verify};
@@ -56,11 +55,10 @@ impl<KS: KeyStore> SignatureManager<KS> {
         self.sign(message_digest).await
     }
 
-    pub async fn sign_precommit_vote(
-        &self,
-        block_hash: BlockHash,
-    ) -> SignatureManagerResult<RawSignature> {
-        let message_digest = build_precommit_vote_message_digest(block_hash);
+    /// Signs a consensus vote, given as the message its voter signs. Prevotes are signed in the
+    /// same domain as precommits, since the message includes the vote's type.
+    pub async fn sign_precommit_vote(&self, vote: Vec<u8>) -> SignatureManagerResult<RawSignature> {
+        let message_digest = build_precommit_vote_message_digest(&vote);
         self.sign(message_digest).await
     }
 
@@ -80,16 +78,17 @@ impl<KS: KeyStore> ComponentStarter for SignatureManager<KS> {}
 #[derive(Clone, Copy, Debug)]
 pub struct LocalKeyStore {
     pub public_key: PublicKey,
-    private_key: PrivateKey,
+    pub(crate) private_key: PrivateKey,
 }
 
 impl LocalKeyStore {
-    fn _new(private_key: PrivateKey) -> Self {
+    pub fn new(private_key: PrivateKey) -> Self {
         let public_key = PublicKey(get_public_key(&private_key));
         Self { private_key, public_key }
     }
 
-    pub(crate) const fn new_for_testing() -> Self {
+    #[cfg(any(test, feature = "testing"))]
+    pub const fn new_for_testing() -> Self {
         // Created using `cairo-lang`.
         const PRIVATE_KEY: PrivateKey = PrivateKey(Felt::from_hex_unchecked(
             "0x608bf2cdb1ad4138e72d2f82b8c5db9fa182d1883868ae582ed373429b7a133",
@@ -121,11 +120,10 @@ fn build_peer_identity_message_digest(peer_id: PeerId, nonce: Nonce) -> MessageD
     MessageDigest(blake2s_to_felt(&message))
 }
 
-fn build_precommit_vote_message_digest(block_hash: BlockHash) -> MessageDigest {
-    let block_hash = block_hash.to_bytes_be();
-    let mut message = Vec::with_capacity(PRECOMMIT_VOTE.len() + block_hash.len());
+fn build_precommit_vote_message_digest(vote: &[u8]) -> MessageDigest {
+    let mut message = Vec::with_capacity(PRECOMMIT_VOTE.len() + vote.len());
     message.extend_from_slice(PRECOMMIT_VOTE);
-    message.extend_from_slice(&block_hash);
+    message.extend_from_slice(vote);
 
     MessageDigest(blake2s_to_felt(&message))
 }
@@ -155,10 +153,10 @@ pub fn verify_identity(
 }
 
 pub fn verify_precommit_vote_signature(
-    block_hash: BlockHash,
+    vote: &[u8],
     signature: RawSignature,
     public_key: PublicKey,
 ) -> SignatureManagerResult<bool> {
-    let message_digest = build_precommit_vote_message_digest(block_hash);
+    let message_digest = build_precommit_vote_message_digest(vote);
     verify_signature(message_digest, signature, public_key)
 }
@@ -42,6 +42,11 @@ impl PeerIdentity {
     }
 }
 
+// The vote message which `ALICE_PRECOMMIT_SIGNATURE` signs.
+fn precommit_vote() -> Vec<u8> {
+    BlockHash(felt!("0x1234")).to_bytes_be().to_vec()
+}
+
 #[rstest]
 #[case::valid_signature(ALICE_IDENTITY_SIGNATURE, true)]
 #[case::invalid_signature(
@@ -62,15 +67,10 @@ fn test_verify_identity(#[case] signature: Signature, #[case] expected: bool) {
     false
 )]
 fn test_verify_precommit_vote_signature(#[case] signature: Signature, #[case] expected: bool) {
-    use starknet_api::block::BlockHash;
-
-    let block_hash = BlockHash(felt!("0x1234"));
+    let vote = precommit_vote();
     let public_key = LocalKeyStore::new_for_testing().public_key;
 
-    assert_eq!(
-        verify_precommit_vote_signature(block_hash, signature.into(), public_key),
-        Ok(expected)
-    );
+    assert_eq!(verify_precommit_vote_signature(&vote, signature.into(), public_key), Ok(expected));
 }
 
 #[tokio::test]
@@ -92,14 +92,19 @@ async fn test_sign_precommit_vote() {
     let key_store = LocalKeyStore::new_for_testing();
     let signature_manager = SignatureManager::new(key_store);
 
-    let block_hash = BlockHash(felt!("0x1234"));
-    let signature = signature_manager.sign_precommit_vote(block_hash).await;
+    let vote = precommit_vote();
+    let signature = signature_manager.sign_precommit_vote(vote.clone()).await;
 
     assert_eq!(signature, Ok(ALICE_PRECOMMIT_SIGNATURE.into()));
 
     // Test alignment with verification function.
+    let signature = signature.unwrap();
     assert_eq!(
-        verify_precommit_vote_signature(block_hash, signature.unwrap(), key_store.public_key),
+        verify_precommit_vote_signature(&vote, signature.clone(), key_store.public_key),
         Ok(true)
     );
+    assert_eq!(
+        verify_precommit_vote_signature(b"other vote", signature, key_store.public_key),
+        Ok(false)
+    );
 }
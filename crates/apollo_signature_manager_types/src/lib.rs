@@ -8,7 +8,6 @@ use async_trait::async_trait;
 #[cfg(any(feature = "testing", test))]
 use mockall::automock;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::BlockHash;
 use starknet_api::core::Nonce;
 use starknet_api::crypto::utils::{PrivateKey, RawSignature, SignatureConversionError};
 use strum_macros::AsRefStr;
@@ -63,9 +62,10 @@ pub trait SignatureManagerClient: Send + Sync {
         nonce: Nonce,
     ) -> SignatureManagerClientResult<RawSignature>;
 
+    /// Signs a consensus vote, given as the message its voter signs.
     async fn sign_precommit_vote(
         &self,
-        block_hash: BlockHash,
+        vote: Vec<u8>,
     ) -> SignatureManagerClientResult<RawSignature>;
 }
 
@@ -94,7 +94,7 @@ pub enum SignatureManagerClientError {
 #[derive(Clone, Serialize, Deserialize, AsRefStr)]
 pub enum SignatureManagerRequest {
     Identify(PeerId, Nonce),
-    SignPrecommitVote(BlockHash),
+    SignPrecommitVote(Vec<u8>),
 }
 impl_debug_for_infra_requests_and_responses!(SignatureManagerRequest);
 
@@ -128,9 +128,9 @@ where
 
     async fn sign_precommit_vote(
         &self,
-        block_hash: BlockHash,
+        vote: Vec<u8>,
     ) -> SignatureManagerClientResult<RawSignature> {
-        let request = SignatureManagerRequest::SignPrecommitVote(block_hash);
+        let request = SignatureManagerRequest::SignPrecommitVote(vote);
         handle_all_response_variants!(
             SignatureManagerResponse,
             SignPrecommitVote,
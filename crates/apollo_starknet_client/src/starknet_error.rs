@@ -59,6 +59,10 @@ pub enum KnownStarknetErrorCode {
     ValidateFailure,
     #[serde(rename = "StarknetErrorCode.TRANSACTION_LIMIT_EXCEEDED")]
     TransactionLimitExceeded,
+    #[serde(rename = "StarknetErrorCode.REPLACEMENT_TRANSACTION_UNDERPRICED")]
+    ReplacementTransactionUnderpriced,
+    #[serde(rename = "StarknetErrorCode.FEE_BELOW_MINIMUM")]
+    FeeBelowMinimum,
 }
 
 /// A client error wrapping error codes returned by the starknet gateway.
@@ -59,6 +59,11 @@ fn known_error_code_deserialization() {
             "StarknetErrorCode.TRANSACTION_LIMIT_EXCEEDED",
             KnownStarknetErrorCode::TransactionLimitExceeded,
         ),
+        (
+            "StarknetErrorCode.REPLACEMENT_TRANSACTION_UNDERPRICED",
+            KnownStarknetErrorCode::ReplacementTransactionUnderpriced,
+        ),
+        ("StarknetErrorCode.FEE_BELOW_MINIMUM", KnownStarknetErrorCode::FeeBelowMinimum),
     ] {
         let starknet_error = deserialize_starknet_error(code_str, MESSAGE);
         let expected_starknet_error = StarknetError {
@@ -19,7 +19,7 @@ use starknet_api::block::BlockNumber;
 use starknet_api::contract_class::{ContractClass, SierraVersion};
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedClass;
-use starknet_api::state::{SierraContractClass, StateNumber, StorageKey};
+use starknet_api::state::{SierraContractClass, StateNumber, StorageKey, ThinStateDiff};
 use starknet_types_core::felt::Felt;
 
 #[cfg(test)]
@@ -80,6 +80,8 @@ pub struct PapyrusReader {
     latest_block: BlockNumber,
     // Reader is `None` for reader invoked through `native_blockifier`.
     class_reader: Option<ClassReader>,
+    // The state diff of a block which isn't in the storage yet, applied on top of `latest_block`.
+    pending_state_diff: Option<ThinStateDiff>,
 }
 
 impl PapyrusReader {
@@ -88,11 +90,21 @@ impl PapyrusReader {
         latest_block: BlockNumber,
         class_reader: Option<ClassReader>,
     ) -> Self {
-        Self { storage_reader, latest_block, class_reader }
+        Self { storage_reader, latest_block, class_reader, pending_state_diff: None }
     }
 
     pub fn new(storage_reader: StorageReader, latest_block: BlockNumber) -> Self {
-        Self { storage_reader, latest_block, class_reader: None }
+        Self { storage_reader, latest_block, class_reader: None, pending_state_diff: None }
+    }
+
+    /// Reads the state as if `state_diff` was applied on top of `latest_block`, i.e., the state
+    /// after a block that wasn't written to the storage yet.
+    ///
+    /// Deprecated classes declared in `state_diff` can only be read through the class reader,
+    /// since their definitions aren't in the storage.
+    pub fn with_pending_state_diff(mut self, state_diff: ThinStateDiff) -> Self {
+        self.pending_state_diff = Some(state_diff);
+        self
     }
 
     fn reader(&self) -> StateResult<RawPapyrusReader<'_>> {
@@ -148,6 +160,16 @@ impl PapyrusReader {
 
     fn read_deprecated_casm(&self, class_hash: ClassHash) -> StateResult<Option<DeprecatedClass>> {
         let Some(class_reader) = &self.class_reader else {
+            if self
+                .pending_state_diff
+                .as_ref()
+                .is_some_and(|diff| diff.deprecated_declared_classes.contains(&class_hash))
+            {
+                return Err(StateError::StateReadError(format!(
+                    "The definition of deprecated class {class_hash} declared in the pending \
+                     state diff isn't in the storage."
+                )));
+            }
             let state_number = StateNumber(self.latest_block);
             let option_casm = self
                 .reader()?
@@ -169,6 +191,13 @@ impl StateReader for PapyrusReader {
         contract_address: ContractAddress,
         key: StorageKey,
     ) -> StateResult<Felt> {
+        if let Some(value) = self
+            .pending_state_diff
+            .as_ref()
+            .and_then(|diff| diff.storage_diffs.get(&contract_address)?.get(&key))
+        {
+            return Ok(*value);
+        }
         let state_number = StateNumber(self.latest_block);
         self.reader()?
             .get_state_reader()
@@ -177,6 +206,11 @@ impl StateReader for PapyrusReader {
     }
 
     fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        if let Some(nonce) =
+            self.pending_state_diff.as_ref().and_then(|diff| diff.nonces.get(&contract_address))
+        {
+            return Ok(*nonce);
+        }
         let state_number = StateNumber(self.latest_block);
         match self
             .reader()?
@@ -190,6 +224,13 @@ impl StateReader for PapyrusReader {
     }
 
     fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        if let Some(class_hash) = self
+            .pending_state_diff
+            .as_ref()
+            .and_then(|diff| diff.deployed_contracts.get(&contract_address))
+        {
+            return Ok(*class_hash);
+        }
         let state_number = StateNumber(self.latest_block);
         match self
             .reader()?
@@ -207,6 +248,11 @@ impl StateReader for PapyrusReader {
     }
 
     fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        if let Some(compiled_class_hash) =
+            self.pending_state_diff.as_ref().and_then(|diff| diff.declared_classes.get(&class_hash))
+        {
+            return Ok(*compiled_class_hash);
+        }
         let state_number = StateNumber(self.latest_block);
         match self
             .reader()?
@@ -226,6 +272,13 @@ impl FetchCompiledClasses for PapyrusReader {
     }
 
     fn is_declared(&self, class_hash: ClassHash) -> StateResult<bool> {
+        if self
+            .pending_state_diff
+            .as_ref()
+            .is_some_and(|diff| diff.declared_classes.contains_key(&class_hash))
+        {
+            return Ok(true);
+        }
         let state_number = StateNumber(self.latest_block);
         let class_declaration_block_number = self
             .reader()?
@@ -7,7 +7,9 @@ use blockifier::execution::call_info::CallExecution;
 use blockifier::execution::entry_point::CallEntryPoint;
 use blockifier::retdata;
 use blockifier::state::cached_state::CachedState;
+use blockifier::state::errors::StateError;
 use blockifier::state::state_api::StateReader;
+use blockifier::state::state_reader_and_contract_manager::FetchCompiledClasses;
 use blockifier::test_utils::contracts::FeatureContractTrait;
 use blockifier::test_utils::trivial_external_entry_point_new;
 use blockifier_test_utils::cairo_versions::CairoVersion;
@@ -16,8 +18,16 @@ use indexmap::IndexMap;
 use starknet_api::abi::abi_utils::selector_from_name;
 use starknet_api::block::BlockNumber;
 use starknet_api::contract_class::ContractClass;
-use starknet_api::state::{StateDiff, StorageKey};
-use starknet_api::{calldata, felt};
+use starknet_api::state::{StateDiff, StorageKey, ThinStateDiff};
+use starknet_api::{
+    calldata,
+    class_hash,
+    compiled_class_hash,
+    contract_address,
+    felt,
+    nonce,
+    storage_key,
+};
 
 use crate::papyrus_state::PapyrusReader;
 
@@ -74,3 +84,58 @@ fn test_entry_point_with_papyrus_state() -> apollo_storage::StorageResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn pending_state_diff_overrides_storage() -> apollo_storage::StorageResult<()> {
+    let ((storage_reader, mut storage_writer), _) = apollo_storage::test_utils::get_test_storage();
+    let address = contract_address!("0x1");
+    let other_address = contract_address!("0x2");
+    let key = storage_key!("0x10");
+
+    let state_diff = StateDiff {
+        deployed_contracts: IndexMap::from([(address, class_hash!("0x100"))]),
+        storage_diffs: IndexMap::from([(address, IndexMap::from([(key, felt!("0x7"))]))]),
+        nonces: IndexMap::from([(address, nonce!(1_u8)), (other_address, nonce!(1_u8))]),
+        ..Default::default()
+    };
+    storage_writer
+        .begin_rw_txn()?
+        .append_state_diff(BlockNumber(0), state_diff.into())?
+        .commit()?;
+
+    let pending_state_diff = ThinStateDiff {
+        storage_diffs: IndexMap::from([(address, IndexMap::from([(key, felt!("0x8"))]))]),
+        nonces: IndexMap::from([(address, nonce!(2_u8))]),
+        declared_classes: IndexMap::from([(class_hash!("0x200"), compiled_class_hash!(3_u8))]),
+        deprecated_declared_classes: vec![class_hash!("0x400")],
+        ..Default::default()
+    };
+    let reader = PapyrusReader::new(storage_reader, BlockNumber(1))
+        .with_pending_state_diff(pending_state_diff);
+
+    // Values in the pending state diff take precedence.
+    assert_eq!(reader.get_storage_at(address, key).unwrap(), felt!("0x8"));
+    assert_eq!(reader.get_nonce_at(address).unwrap(), nonce!(2_u8));
+    assert_eq!(
+        reader.get_compiled_class_hash(class_hash!("0x200")).unwrap(),
+        compiled_class_hash!(3_u8)
+    );
+    assert!(reader.is_declared(class_hash!("0x200")).unwrap());
+    // Everything else is read from the storage.
+    assert_eq!(reader.get_nonce_at(other_address).unwrap(), nonce!(1_u8));
+    assert_eq!(reader.get_class_hash_at(address).unwrap(), class_hash!("0x100"));
+    assert!(!reader.is_declared(class_hash!("0x300")).unwrap());
+    // A deprecated class declared in the pending state diff isn't a Cairo 1 class, and its
+    // definition can't be read without a class reader.
+    assert!(!reader.is_declared(class_hash!("0x400")).unwrap());
+    assert_matches!(
+        reader.get_compiled_class(class_hash!("0x400")),
+        Err(StateError::StateReadError(_))
+    );
+    assert_matches!(
+        reader.get_compiled_class(class_hash!("0x300")),
+        Err(StateError::UndeclaredClassHash(class_hash)) if class_hash == class_hash!("0x300")
+    );
+
+    Ok(())
+}
@@ -4,8 +4,14 @@ use std::result;
 
 use apollo_central_sync::sources::central::CentralSourceConfig;
 use apollo_central_sync::SyncConfig;
-use apollo_config::dumping::{prepend_sub_config_name, ser_optional_sub_config, SerializeConfig};
-use apollo_config::{ParamPath, SerializedParam};
+use apollo_config::dumping::{
+    prepend_sub_config_name,
+    ser_optional_sub_config,
+    ser_param,
+    SerializeConfig,
+};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_consensus::config::{deserialize_public_keys, serialize_public_keys};
 use apollo_network::NetworkConfig;
 use apollo_p2p_sync::client::P2pSyncClientConfig;
 use apollo_reverts::RevertConfig;
@@ -13,6 +19,7 @@ use apollo_rpc::RpcConfig;
 use apollo_storage::db::DbConfig;
 use apollo_storage::StorageConfig;
 use serde::{Deserialize, Serialize};
+use starknet_api::crypto::utils::PublicKey;
 use validator::{Validate, ValidationError};
 
 const STATE_SYNC_TCP_PORT: u16 = 12345;
@@ -33,6 +40,11 @@ pub struct StateSyncConfig {
     pub revert_config: RevertConfig,
     #[validate]
     pub rpc_config: RpcConfig,
+    /// The public keys of the consensus validators, in the order of their IDs, which the commit
+    /// certificates of the synced blocks are verified against. Empty if consensus isn't enabled,
+    /// in which case the blocks aren't required to have a certificate.
+    #[serde(deserialize_with = "deserialize_public_keys")]
+    pub consensus_validator_public_keys: Vec<PublicKey>,
 }
 
 impl SerializeConfig for StateSyncConfig {
@@ -51,6 +63,14 @@ impl SerializeConfig for StateSyncConfig {
             &self.central_sync_client_config,
             "central_sync_client_config",
         ));
+        config.extend([ser_param(
+            "consensus_validator_public_keys",
+            &serialize_public_keys(&self.consensus_validator_public_keys),
+            "The public keys of the consensus validators, in the order of their IDs and separated \
+             by spaces, which the commit certificates of the synced blocks are verified against. \
+             Empty if consensus isn't enabled.",
+            ParamPrivacyInput::Public,
+        )]);
         config
     }
 }
@@ -82,6 +102,7 @@ impl Default for StateSyncConfig {
             network_config: Some(NetworkConfig { port: STATE_SYNC_TCP_PORT, ..Default::default() }),
             revert_config: RevertConfig::default(),
             rpc_config: RpcConfig::default(),
+            consensus_validator_public_keys: Vec::new(),
         }
     }
 }
@@ -4,15 +4,19 @@ mod test;
 use std::sync::Arc;
 
 use apollo_central_sync::sources::central::{CentralError, CentralSource};
+use apollo_central_sync::sources::configured_central_source::ConfiguredCentralSource;
 use apollo_central_sync::sources::pending::PendingSource;
 use apollo_central_sync::{
+    DecidedCommitCertificates,
     StateSync as CentralStateSync,
     StateSyncError as CentralStateSyncError,
     GENESIS_HASH,
 };
 use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_consensus::commit_certificate::configured_committee;
 use apollo_infra::component_definitions::ComponentStarter;
 use apollo_infra::component_server::WrapperServer;
+use apollo_network::network_manager::admin::NetworkAdminRequests;
 use apollo_network::network_manager::metrics::{NetworkMetrics, SqmrNetworkMetrics};
 use apollo_network::network_manager::{NetworkError, NetworkManager};
 use apollo_p2p_sync::client::{
@@ -37,6 +41,7 @@ use apollo_state_sync_metrics::metrics::{
     P2P_SYNC_NUM_ACTIVE_OUTBOUND_SESSIONS,
     P2P_SYNC_NUM_BLACKLISTED_PEERS,
     P2P_SYNC_NUM_CONNECTED_PEERS,
+    P2P_SYNC_NUM_REJECTED_CONNECTIONS,
     STATE_SYNC_REVERTED_TRANSACTIONS,
 };
 use apollo_state_sync_types::state_sync_types::SyncBlock;
@@ -49,9 +54,9 @@ use futures::future::{self, pending, BoxFuture};
 use futures::never::Never;
 use futures::{FutureExt, StreamExt};
 use papyrus_common::pending_classes::PendingClasses;
-use starknet_api::block::{BlockHash, BlockHashAndNumber};
+use starknet_api::block::{BlockHash, BlockHashAndNumber, CommitteeMember};
 use starknet_api::felt;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tracing::info_span;
 use tracing::instrument::Instrument;
 
@@ -100,6 +105,8 @@ pub struct StateSyncResources {
     pub storage_writer: StorageWriter,
     pub shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pub pending_data: Arc<RwLock<PendingData>>,
+    // Notified whenever the pending data is updated.
+    pub pending_data_updates: watch::Sender<()>,
     pub pending_classes: Arc<RwLock<PendingClasses>>,
 }
 
@@ -117,8 +124,16 @@ impl StateSyncResources {
             }),
             ..Default::default()
         }));
+        let (pending_data_updates, _) = watch::channel(());
         let pending_classes = Arc::new(RwLock::new(PendingClasses::default()));
-        Self { storage_reader, storage_writer, shared_highest_block, pending_data, pending_classes }
+        Self {
+            storage_reader,
+            storage_writer,
+            shared_highest_block,
+            pending_data,
+            pending_data_updates,
+            pending_classes,
+        }
     }
 }
 
@@ -127,6 +142,7 @@ impl StateSyncRunner {
         config: StateSyncConfig,
         new_block_receiver: Receiver<SyncBlock>,
         class_manager_client: SharedClassManagerClient,
+        network_admin_requests: Option<NetworkAdminRequests>,
     ) -> (Self, StorageReader) {
         let StateSyncConfig {
             storage_config,
@@ -135,13 +151,17 @@ impl StateSyncRunner {
             network_config,
             revert_config,
             rpc_config,
+            consensus_validator_public_keys,
         } = config;
+        let consensus_committee = (!consensus_validator_public_keys.is_empty())
+            .then(|| configured_committee(&consensus_validator_public_keys));
 
         let StateSyncResources {
             storage_reader,
             mut storage_writer,
             shared_highest_block,
             pending_data,
+            pending_data_updates,
             pending_classes,
         } = StateSyncResources::new(&storage_config);
 
@@ -196,17 +216,23 @@ impl StateSyncRunner {
             let network_manager_metrics = Some(NetworkMetrics {
                 num_connected_peers: P2P_SYNC_NUM_CONNECTED_PEERS,
                 num_blacklisted_peers: P2P_SYNC_NUM_BLACKLISTED_PEERS,
+                num_rejected_connections: P2P_SYNC_NUM_REJECTED_CONNECTIONS,
                 broadcast_metrics_by_topic: None,
                 sqmr_metrics: Some(SqmrNetworkMetrics {
                     num_active_inbound_sessions: P2P_SYNC_NUM_ACTIVE_INBOUND_SESSIONS,
                     num_active_outbound_sessions: P2P_SYNC_NUM_ACTIVE_OUTBOUND_SESSIONS,
                 }),
             });
-            NetworkManager::new(
+            let mut network_manager = NetworkManager::new(
                 network_config.clone(),
                 Some(VERSION_FULL.to_string()),
                 network_manager_metrics,
-            )
+                None,
+            );
+            if let Some(network_admin_requests) = network_admin_requests {
+                network_manager.serve_admin_requests(network_admin_requests);
+            }
+            network_manager
         });
 
         // Creating the sync clients futures
@@ -226,6 +252,7 @@ impl StateSyncRunner {
                         network_manager,
                         new_block_receiver,
                         class_manager_client.clone(),
+                        consensus_committee,
                     );
 
                     let p2p_sync_client_future = p2p_sync_client.run().boxed();
@@ -234,20 +261,32 @@ impl StateSyncRunner {
                     (p2p_sync_client_future, central_sync_client_future, new_block_dev_null_future)
                 }
                 (None, Some(central_sync_client_config)) => {
+                    let decided_commit_certificates = consensus_committee.map(|committee| {
+                        DecidedCommitCertificates { committee, certificates: Default::default() }
+                    });
                     let central_sync_client = Self::new_central_state_sync_client(
                         storage_reader.clone(),
                         storage_writer,
                         shared_highest_block.clone(),
                         pending_data.clone(),
+                        pending_data_updates.clone(),
                         pending_classes.clone(),
                         central_sync_client_config,
                         class_manager_client.clone(),
+                        decided_commit_certificates.clone(),
                     );
 
                     let p2p_sync_client_future = future::pending().boxed();
                     let central_sync_client_future = central_sync_client.run().boxed();
-                    let new_block_dev_null_future =
-                        create_new_block_receiver_future_dev_null(new_block_receiver);
+                    // The central source serves the blocks consensus decided, so only their commit
+                    // certificates are kept.
+                    let new_block_dev_null_future = match decided_commit_certificates {
+                        Some(decided_commit_certificates) => collect_commit_certificates(
+                            new_block_receiver,
+                            decided_commit_certificates,
+                        ),
+                        None => create_new_block_receiver_future_dev_null(new_block_receiver),
+                    };
 
                     (p2p_sync_client_future, central_sync_client_future, new_block_dev_null_future)
                 }
@@ -288,6 +327,7 @@ impl StateSyncRunner {
             &rpc_config,
             shared_highest_block.clone(),
             pending_data.clone(),
+            pending_data_updates.subscribe(),
             pending_classes.clone(),
             storage_reader.clone(),
             Some(class_manager_client.clone()),
@@ -314,6 +354,7 @@ impl StateSyncRunner {
         network_manager: &mut NetworkManager,
         new_block_receiver: Receiver<SyncBlock>,
         class_manager_client: SharedClassManagerClient,
+        consensus_committee: Option<Vec<CommitteeMember>>,
     ) -> P2pSyncClient {
         let header_client_sender = network_manager
             .register_sqmr_protocol_client(Protocol::SignedBlockHeader.into(), BUFFER_SIZE);
@@ -336,6 +377,7 @@ impl StateSyncRunner {
             p2p_sync_client_channels,
             new_block_receiver.boxed(),
             class_manager_client.clone(),
+            consensus_committee,
         )
     }
 
@@ -364,29 +406,35 @@ impl StateSyncRunner {
         P2pSyncServer::new(storage_reader, p2p_sync_server_channels, class_manager_client)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new_central_state_sync_client(
         storage_reader: StorageReader,
         storage_writer: StorageWriter,
         shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
         pending_data: Arc<RwLock<PendingData>>,
+        pending_data_updates: watch::Sender<()>,
         pending_classes: Arc<RwLock<PendingClasses>>,
         central_sync_client_config: CentralSyncClientConfig,
         class_manager_client: SharedClassManagerClient,
+        decided_commit_certificates: Option<DecidedCommitCertificates>,
     ) -> CentralStateSync {
         let CentralSyncClientConfig { sync_config, central_source_config } =
             central_sync_client_config;
-        let central_source =
+        let central_source = ConfiguredCentralSource::Central(
             CentralSource::new(central_source_config.clone(), VERSION_FULL, storage_reader.clone())
                 .map_err(CentralError::ClientCreation)
-                .expect("CentralSource creation failed in central sync");
+                .expect("CentralSource creation failed in central sync"),
+        );
         let pending_source = PendingSource::new(central_source_config, VERSION_FULL)
             .map_err(CentralError::ClientCreation)
             .expect("PendingSource creation failed in central sync");
         let base_layer_source = None;
+        let checkpoint = None;
         CentralStateSync::new(
             sync_config,
             shared_highest_block,
             pending_data,
+            pending_data_updates,
             pending_classes,
             central_source,
             pending_source,
@@ -394,6 +442,8 @@ impl StateSyncRunner {
             storage_reader.clone(),
             storage_writer,
             Some(class_manager_client),
+            checkpoint,
+            decided_commit_certificates,
         )
     }
 
@@ -418,11 +468,33 @@ fn create_new_block_receiver_future_dev_null(
     .boxed()
 }
 
+/// A future that consumes the new block receiver and keeps the commit certificates of the received
+/// blocks for the central sync, which stores them along with the blocks.
+fn collect_commit_certificates(
+    mut new_block_receiver: Receiver<SyncBlock>,
+    decided_commit_certificates: DecidedCommitCertificates,
+) -> BoxFuture<'static, Never> {
+    async move {
+        while let Some(sync_block) = new_block_receiver.next().await {
+            if let Some(commit_certificate) = sync_block.commit_certificate {
+                decided_commit_certificates
+                    .certificates
+                    .lock()
+                    .expect("Lock on the commit certificates was poisoned due to a previous panic")
+                    .insert(sync_block.block_header_without_hash.block_number, commit_certificate);
+            }
+        }
+        pending().await
+    }
+    .boxed()
+}
+
 // Create JSON-RPC server
 fn spawn_rpc_server(
     rpc_config: &RpcConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Receiver<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage_reader: StorageReader,
     class_manager_client: Option<SharedClassManagerClient>,
@@ -433,10 +505,12 @@ fn spawn_rpc_server(
             &rpc_config,
             shared_highest_block,
             pending_data,
+            pending_data_updates,
             pending_classes,
             storage_reader,
             VERSION_FULL,
             class_manager_client,
+            None,
         )
         .await
         .expect("Failed running JSON-RPC server");
@@ -26,6 +26,7 @@ define_metrics!(
         // Counters
         MetricCounter { STATE_SYNC_PROCESSED_TRANSACTIONS, "apollo_state_sync_processed_transactions", "The number of transactions processed by the state sync component", init = 0 },
         MetricCounter { STATE_SYNC_REVERTED_TRANSACTIONS, "apollo_state_sync_reverted_transactions", "The number of transactions reverted by the state sync component", init = 0 },
+        MetricCounter { P2P_SYNC_NUM_REJECTED_CONNECTIONS, "apollo_p2p_sync_num_rejected_connections", "The number of connections rejected by the p2p sync component since the peer is not in the allowlist", init = 0 },
     },
 );
 
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use starknet_api::block::BlockHeaderWithoutHash;
+use starknet_api::block::{BlockHeaderWithoutHash, CommitCertificate};
 use starknet_api::state::ThinStateDiff;
 use starknet_api::transaction::TransactionHash;
 
@@ -19,6 +19,9 @@ pub struct SyncBlock {
     pub account_transaction_hashes: Vec<TransactionHash>,
     pub l1_transaction_hashes: Vec<TransactionHash>,
     pub block_header_without_hash: BlockHeaderWithoutHash,
+    /// The proof that consensus decided the block. Missing for blocks which weren't decided by
+    /// consensus, or whose certificate wasn't synced.
+    pub commit_certificate: Option<CommitCertificate>,
 }
 
 impl SyncBlock {
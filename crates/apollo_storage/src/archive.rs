@@ -0,0 +1,412 @@
+//! Exporting the stored blocks into an archive, and reading them back.
+//!
+//! An archive is a directory of zstd-compressed files, each holding the data of a range of
+//! consecutive blocks: the headers, signatures, bodies, state diffs, classes and compiled classes.
+//! This is everything needed for syncing the blocks without network access (see
+//! [`ArchivedBlock`]).
+//!
+//! A file is named `<first block>-<last block>.zst` (the block numbers are zero padded, so the
+//! files are sorted lexicographically). Its content is a format version followed by the
+//! [`ArchivedBlock`]s of its range, in order.
+
+#[cfg(test)]
+#[path = "archive_test.rs"]
+mod archive_test;
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use indexmap::IndexMap;
+use starknet_api::block::{BlockBody, BlockHeader, BlockNumber, BlockSignature};
+use starknet_api::core::ClassHash;
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::{SierraContractClass, StateNumber, ThinStateDiff};
+use tracing::{debug, info};
+
+use crate::body::BodyStorageReader;
+use crate::class::ClassStorageReader;
+use crate::compiled_class::CasmStorageReader;
+use crate::db::serialization::{StorageSerde, StorageSerdeError};
+use crate::db::RO;
+use crate::header::HeaderStorageReader;
+use crate::state::StateStorageReader;
+use crate::{StorageError, StorageReader, StorageTxn};
+
+/// The extension of the archive files.
+pub const ARCHIVE_FILE_EXTENSION: &str = "zst";
+// Bumped on every change to the serialization of the archived blocks.
+pub(crate) const ARCHIVE_FORMAT_VERSION: u8 = 0;
+// The extension of files that are being written, so that a partially written file is never read.
+const TEMP_FILE_EXTENSION: &str = "tmp";
+// The compression level to use. Higher levels are slower but compress better.
+const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+
+/// Result type for the archive operations.
+pub type ArchiveResult<V> = Result<V, ArchiveError>;
+
+/// Error type for the archive operations.
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    #[error(transparent)]
+    StorageSerdeError(#[from] StorageSerdeError),
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    #[error(
+        "Can't export blocks up to {end_block}, only the blocks below {synced_marker} are fully \
+         stored."
+    )]
+    BlocksNotSynced { end_block: BlockNumber, synced_marker: BlockNumber },
+    #[error("Missing the {data} of block {block_number} in the storage.")]
+    MissingBlockData { block_number: BlockNumber, data: String },
+    #[error("{} is not a valid archive file name.", path.display())]
+    InvalidFileName { path: PathBuf },
+    #[error(
+        "The archive files are not consecutive: expected a file starting at block \
+         {expected_block}, found {}.",
+        path.display()
+    )]
+    NonConsecutiveFiles { expected_block: BlockNumber, path: PathBuf },
+    #[error("Unsupported format version {version} of {}.", path.display())]
+    UnsupportedFormatVersion { version: u8, path: PathBuf },
+    #[error("Failed to read block {block_number} from {}.", path.display())]
+    CorruptedFile { block_number: BlockNumber, path: PathBuf },
+    #[error("Failed to read the state snapshot from {}.", path.display())]
+    CorruptedStateSnapshot { path: PathBuf },
+    #[error(
+        "The archive at {} doesn't hold block {next_block}, the next block to sync.",
+        dir.display()
+    )]
+    NextBlockNotInArchive { next_block: BlockNumber, dir: PathBuf },
+}
+
+/// All the data of a block that's needed for syncing it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ArchivedBlock {
+    /// The header of the block.
+    pub header: BlockHeader,
+    /// The signature of the block, if it was stored.
+    pub signature: Option<BlockSignature>,
+    /// The body of the block.
+    pub body: BlockBody,
+    /// The state diff of the block.
+    pub state_diff: ThinStateDiff,
+    /// The classes declared in the block.
+    pub classes: IndexMap<ClassHash, SierraContractClass>,
+    /// The Cairo 0 classes declared in the block, and the ones that were first defined in the
+    /// block by a deployment.
+    pub deprecated_classes: IndexMap<ClassHash, DeprecatedContractClass>,
+    /// The compiled classes of the classes declared in the block.
+    pub casms: IndexMap<ClassHash, CasmContractClass>,
+}
+
+impl StorageSerde for ArchivedBlock {
+    fn serialize_into(&self, res: &mut impl std::io::Write) -> Result<(), StorageSerdeError> {
+        self.header.serialize_into(res)?;
+        self.signature.serialize_into(res)?;
+        self.body.serialize_into(res)?;
+        self.state_diff.serialize_into(res)?;
+        self.classes.serialize_into(res)?;
+        self.deprecated_classes.serialize_into(res)?;
+        self.casms.serialize_into(res)
+    }
+
+    fn deserialize_from(bytes: &mut impl std::io::Read) -> Option<Self> {
+        Some(Self {
+            header: BlockHeader::deserialize_from(bytes)?,
+            signature: Option::<BlockSignature>::deserialize_from(bytes)?,
+            body: BlockBody::deserialize_from(bytes)?,
+            state_diff: ThinStateDiff::deserialize_from(bytes)?,
+            classes: IndexMap::deserialize_from(bytes)?,
+            deprecated_classes: IndexMap::deserialize_from(bytes)?,
+            casms: IndexMap::deserialize_from(bytes)?,
+        })
+    }
+}
+
+/// Exports the blocks in the range `[start_block, end_block)` into archive files in the given
+/// directory, with up to `blocks_per_file` blocks in each file. Returns the number of files that
+/// were written.
+///
+/// All the data of the exported blocks, including their classes and compiled classes, must be in
+/// the storage.
+pub fn export_archive(
+    reader: &StorageReader,
+    dir: &Path,
+    start_block: BlockNumber,
+    end_block: BlockNumber,
+    blocks_per_file: u64,
+) -> ArchiveResult<usize> {
+    assert!(blocks_per_file > 0, "An archive file must hold at least one block.");
+    let synced_marker = synced_marker(&reader.begin_ro_txn()?)?;
+    if end_block > synced_marker {
+        return Err(ArchiveError::BlocksNotSynced { end_block, synced_marker });
+    }
+    fs::create_dir_all(dir)?;
+
+    let mut n_files = 0;
+    let mut first_block = start_block;
+    while first_block < end_block {
+        let file_end_block =
+            BlockNumber(first_block.0.saturating_add(blocks_per_file)).min(end_block);
+        let last_block = file_end_block.prev().expect("The file should hold at least one block.");
+        let path = dir.join(file_name(first_block, last_block));
+        export_file(reader, &path, first_block, file_end_block)?;
+        info!("Exported blocks {first_block} to {last_block} into {}.", path.display());
+        n_files += 1;
+        first_block = file_end_block;
+    }
+    Ok(n_files)
+}
+
+// The first block that isn't fully stored.
+pub(crate) fn synced_marker(txn: &StorageTxn<'_, RO>) -> ArchiveResult<BlockNumber> {
+    Ok([
+        txn.get_header_marker()?,
+        txn.get_body_marker()?,
+        txn.get_state_marker()?,
+        txn.get_class_marker()?,
+        txn.get_compiled_class_marker()?,
+    ]
+    .into_iter()
+    .min()
+    .expect("The list of markers should not be empty."))
+}
+
+fn export_file(
+    reader: &StorageReader,
+    path: &Path,
+    first_block: BlockNumber,
+    end_block: BlockNumber,
+) -> ArchiveResult<()> {
+    // A transaction per file, to avoid keeping a read transaction open for the whole export.
+    let txn = reader.begin_ro_txn()?;
+    write_file(path, |encoder| {
+        for block_number in first_block.iter_up_to(end_block) {
+            read_block(&txn, block_number)?.serialize_into(encoder)?;
+            debug!("Exported block {block_number}.");
+        }
+        Ok(())
+    })
+}
+
+// Writes the format version and then the given content into a compressed file.
+pub(crate) fn write_file(
+    path: &Path,
+    write_content: impl FnOnce(&mut zstd::Encoder<'static, BufWriter<File>>) -> ArchiveResult<()>,
+) -> ArchiveResult<()> {
+    let temp_path = path.with_extension(TEMP_FILE_EXTENSION);
+    let mut encoder =
+        zstd::Encoder::new(BufWriter::new(File::create(&temp_path)?), COMPRESSION_LEVEL)?;
+    ARCHIVE_FORMAT_VERSION.serialize_into(&mut encoder)?;
+    write_content(&mut encoder)?;
+    encoder.finish()?.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// Reads the header, signature and body of the block, leaving its state diff and classes empty.
+pub(crate) fn read_block_without_state(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+) -> ArchiveResult<ArchivedBlock> {
+    let missing =
+        |data: &str| ArchiveError::MissingBlockData { block_number, data: data.to_string() };
+    let header = txn.get_block_header(block_number)?.ok_or_else(|| missing("header"))?;
+    let signature = txn.get_block_signature(block_number)?;
+    let body = BlockBody {
+        transactions: txn
+            .get_block_transactions(block_number)?
+            .ok_or_else(|| missing("transactions"))?,
+        transaction_outputs: txn
+            .get_block_transaction_outputs(block_number)?
+            .ok_or_else(|| missing("transaction outputs"))?,
+        transaction_hashes: txn
+            .get_block_transaction_hashes(block_number)?
+            .ok_or_else(|| missing("transaction hashes"))?,
+    };
+    Ok(ArchivedBlock { header, signature, body, ..Default::default() })
+}
+
+fn read_block(txn: &StorageTxn<'_, RO>, block_number: BlockNumber) -> ArchiveResult<ArchivedBlock> {
+    let missing =
+        |data: &str| ArchiveError::MissingBlockData { block_number, data: data.to_string() };
+    let ArchivedBlock { header, signature, body, .. } =
+        read_block_without_state(txn, block_number)?;
+    let state_diff = txn.get_state_diff(block_number)?.ok_or_else(|| missing("state diff"))?;
+
+    let mut classes = IndexMap::new();
+    let mut casms = IndexMap::new();
+    for class_hash in state_diff.declared_classes.keys() {
+        let class = txn
+            .get_class(class_hash)?
+            .ok_or_else(|| missing(&format!("declared class {class_hash}")))?;
+        let casm = txn
+            .get_casm(class_hash)?
+            .ok_or_else(|| missing(&format!("compiled class of {class_hash}")))?;
+        classes.insert(*class_hash, class);
+        casms.insert(*class_hash, casm);
+    }
+
+    let mut deprecated_classes = IndexMap::new();
+    for class_hash in &state_diff.deprecated_declared_classes {
+        let deprecated_class = txn
+            .get_deprecated_class(class_hash)?
+            .ok_or_else(|| missing(&format!("deprecated class {class_hash}")))?;
+        deprecated_classes.insert(*class_hash, deprecated_class);
+    }
+    // Cairo 0 classes that weren't declared are defined by their first deployment.
+    let state_reader = txn.get_state_reader()?;
+    for class_hash in state_diff.deployed_contracts.values() {
+        if deprecated_classes.contains_key(class_hash)
+            || state_reader.get_class_definition_block_number(class_hash)?.is_some()
+            || state_reader
+                .get_deprecated_class_definition_at(
+                    StateNumber::right_before_block(block_number),
+                    class_hash,
+                )?
+                .is_some()
+        {
+            continue;
+        }
+        let deprecated_class = state_reader
+            .get_deprecated_class_definition_at(
+                StateNumber::unchecked_right_after_block(block_number),
+                class_hash,
+            )?
+            .ok_or_else(|| missing(&format!("deprecated class {class_hash}")))?;
+        deprecated_classes.insert(*class_hash, deprecated_class);
+    }
+
+    Ok(ArchivedBlock { header, signature, body, state_diff, classes, deprecated_classes, casms })
+}
+
+fn file_name(first_block: BlockNumber, last_block: BlockNumber) -> String {
+    format!("{:012}-{:012}.{ARCHIVE_FILE_EXTENSION}", first_block.0, last_block.0)
+}
+
+/// A file of an archive, holding the blocks in the range `[first_block, last_block]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveFile {
+    /// The first block in the file.
+    pub first_block: BlockNumber,
+    /// The last block in the file.
+    pub last_block: BlockNumber,
+    /// The path of the file.
+    pub path: PathBuf,
+}
+
+impl ArchiveFile {
+    fn from_path(path: PathBuf) -> ArchiveResult<Self> {
+        let invalid_file_name = || ArchiveError::InvalidFileName { path: path.clone() };
+        let (first_block, last_block) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split_once('-'))
+            .and_then(|(first, last)| Some((first.parse::<u64>().ok()?, last.parse::<u64>().ok()?)))
+            .ok_or_else(invalid_file_name)?;
+        if first_block > last_block {
+            return Err(invalid_file_name());
+        }
+        Ok(Self {
+            first_block: BlockNumber(first_block),
+            last_block: BlockNumber(last_block),
+            path,
+        })
+    }
+
+    /// Returns whether the file holds the given block.
+    pub fn contains(&self, block_number: BlockNumber) -> bool {
+        self.first_block <= block_number && block_number <= self.last_block
+    }
+
+    /// Reads and decompresses all the blocks of the file.
+    pub fn read_blocks(&self) -> ArchiveResult<Vec<ArchivedBlock>> {
+        let mut decoder = zstd::Decoder::new(File::open(&self.path)?)?;
+        let version = u8::deserialize_from(&mut decoder).ok_or_else(|| {
+            ArchiveError::CorruptedFile { block_number: self.first_block, path: self.path.clone() }
+        })?;
+        if version != ARCHIVE_FORMAT_VERSION {
+            return Err(ArchiveError::UnsupportedFormatVersion {
+                version,
+                path: self.path.clone(),
+            });
+        }
+
+        let mut blocks = vec![];
+        for block_number in self.first_block.iter_up_to(self.last_block.unchecked_next()) {
+            let corrupted_file =
+                || ArchiveError::CorruptedFile { block_number, path: self.path.clone() };
+            let block = ArchivedBlock::deserialize_from(&mut decoder).ok_or_else(corrupted_file)?;
+            if block.header.block_header_without_hash.block_number != block_number {
+                return Err(corrupted_file());
+            }
+            blocks.push(block);
+        }
+        if decoder.read(&mut [0])? != 0 {
+            return Err(ArchiveError::CorruptedFile {
+                block_number: self.last_block.unchecked_next(),
+                path: self.path.clone(),
+            });
+        }
+        Ok(blocks)
+    }
+}
+
+/// The files of an archive directory, sorted by their blocks.
+#[derive(Debug, Clone)]
+pub struct ArchiveReader {
+    files: Vec<ArchiveFile>,
+}
+
+impl ArchiveReader {
+    /// Lists the archive files in the given directory and checks that their ranges are
+    /// consecutive and that they hold `next_block`, the first block that isn't synced yet. Files
+    /// with other extensions are ignored.
+    ///
+    /// An archive that ends right before `next_block` is accepted, since it was already synced.
+    pub fn open(dir: &Path, next_block: BlockNumber) -> ArchiveResult<Self> {
+        let mut files = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == ARCHIVE_FILE_EXTENSION) {
+                files.push(ArchiveFile::from_path(path)?);
+            }
+        }
+        files.sort_by_key(|file| file.first_block);
+        for (prev_file, file) in files.iter().zip(files.iter().skip(1)) {
+            let expected_block = prev_file.last_block.unchecked_next();
+            if file.first_block != expected_block {
+                return Err(ArchiveError::NonConsecutiveFiles {
+                    expected_block,
+                    path: file.path.clone(),
+                });
+            }
+        }
+        let holds_next_block = match (files.first(), files.last()) {
+            (Some(first_file), Some(last_file)) => {
+                first_file.first_block <= next_block
+                    && next_block <= last_file.last_block.unchecked_next()
+            }
+            _ => false,
+        };
+        if !holds_next_block {
+            return Err(ArchiveError::NextBlockNotInArchive { next_block, dir: dir.to_path_buf() });
+        }
+        Ok(Self { files })
+    }
+
+    /// The files of the archive, sorted by their blocks.
+    pub fn files(&self) -> &[ArchiveFile] {
+        &self.files
+    }
+
+    /// Returns the file that holds the given block, if there is one.
+    pub fn file_of(&self, block_number: BlockNumber) -> Option<&ArchiveFile> {
+        self.files.iter().find(|file| file.contains(block_number))
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs;
+
+use apollo_test_utils::get_test_body;
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use indexmap::indexmap;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockHash, BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::core::{ClassHash, CompiledClassHash};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::{SierraContractClass, ThinStateDiff};
+use starknet_api::test_utils::read_json_file;
+use starknet_api::{contract_address, felt};
+
+use crate::archive::{export_archive, ArchiveError, ArchiveReader, ArchivedBlock};
+use crate::body::BodyStorageWriter;
+use crate::class::ClassStorageWriter;
+use crate::compiled_class::CasmStorageWriter;
+use crate::header::HeaderStorageWriter;
+use crate::state::StateStorageWriter;
+use crate::test_utils::get_test_storage;
+use crate::{StorageReader, StorageWriter};
+
+fn append_block(writer: &mut StorageWriter, block: &ArchivedBlock) {
+    let block_number = block.header.block_header_without_hash.block_number;
+    let deprecated_classes = block
+        .deprecated_classes
+        .iter()
+        .map(|(class_hash, class)| (*class_hash, class))
+        .collect::<Vec<_>>();
+    let mut txn = writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &block.header)
+        .unwrap()
+        .append_body(block_number, block.body.clone())
+        .unwrap()
+        .append_state_diff(block_number, block.state_diff.clone())
+        .unwrap()
+        .append_classes(
+            block_number,
+            &block
+                .classes
+                .iter()
+                .map(|(class_hash, class)| (*class_hash, class))
+                .collect::<Vec<_>>(),
+            &deprecated_classes,
+        )
+        .unwrap();
+    for (class_hash, casm) in &block.casms {
+        txn = txn.append_casm(class_hash, casm).unwrap();
+    }
+    txn.commit().unwrap();
+}
+
+// Returns a storage with two blocks, and the blocks as they should be archived.
+fn storage_with_blocks() -> ((StorageReader, StorageWriter), tempfile::TempDir, Vec<ArchivedBlock>)
+{
+    let class: SierraContractClass = read_json_file("class.json");
+    let casm: CasmContractClass = read_json_file("compiled_class.json");
+    let deprecated_class: DeprecatedContractClass = read_json_file("deprecated_class.json");
+    let class_hash = ClassHash(felt!("0x1"));
+    let declared_deprecated_class_hash = ClassHash(felt!("0x2"));
+    let deployed_deprecated_class_hash = ClassHash(felt!("0x3"));
+
+    let header = |block_number: u64| BlockHeader {
+        block_hash: BlockHash(felt!(block_number + 100)),
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(block_number),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let blocks = vec![
+        ArchivedBlock {
+            header: header(0),
+            body: get_test_body(2, None, None, None),
+            state_diff: ThinStateDiff {
+                deployed_contracts: indexmap! {
+                    contract_address!("0x100") => deployed_deprecated_class_hash,
+                },
+                declared_classes: indexmap! { class_hash => CompiledClassHash(felt!("0x4")) },
+                deprecated_declared_classes: vec![declared_deprecated_class_hash],
+                ..Default::default()
+            },
+            classes: indexmap! { class_hash => class },
+            deprecated_classes: indexmap! {
+                declared_deprecated_class_hash => deprecated_class.clone(),
+                deployed_deprecated_class_hash => deprecated_class,
+            },
+            casms: indexmap! { class_hash => casm },
+            ..Default::default()
+        },
+        // A deployment of a class that was defined in a previous block doesn't archive the class
+        // again.
+        ArchivedBlock {
+            header: header(1),
+            state_diff: ThinStateDiff {
+                deployed_contracts: indexmap! {
+                    contract_address!("0x101") => deployed_deprecated_class_hash,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    ];
+
+    let ((reader, mut writer), temp_dir) = get_test_storage();
+    for block in &blocks {
+        append_block(&mut writer, block);
+    }
+    ((reader, writer), temp_dir, blocks)
+}
+
+#[test]
+fn export_and_read() {
+    let ((reader, _writer), _temp_dir, blocks) = storage_with_blocks();
+    let archive_dir = tempfile::tempdir().unwrap();
+
+    let n_files =
+        export_archive(&reader, archive_dir.path(), BlockNumber(0), BlockNumber(2), 1).unwrap();
+    assert_eq!(n_files, 2);
+
+    let archive = ArchiveReader::open(archive_dir.path(), BlockNumber(0)).unwrap();
+    let file_names = archive
+        .files()
+        .iter()
+        .map(|file| file.path.file_name().unwrap().to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(file_names, vec!["000000000000-000000000000.zst", "000000000001-000000000001.zst"]);
+    assert_eq!(archive.file_of(BlockNumber(1)), archive.files().get(1));
+    assert_eq!(archive.file_of(BlockNumber(2)), None);
+
+    let archived_blocks =
+        archive.files().iter().flat_map(|file| file.read_blocks().unwrap()).collect::<Vec<_>>();
+    assert_eq!(archived_blocks, blocks);
+}
+
+#[test]
+fn export_unsynced_blocks() {
+    let ((reader, _writer), _temp_dir, _blocks) = storage_with_blocks();
+    let archive_dir = tempfile::tempdir().unwrap();
+
+    assert_matches!(
+        export_archive(&reader, archive_dir.path(), BlockNumber(0), BlockNumber(3), 10),
+        Err(ArchiveError::BlocksNotSynced {
+            end_block: BlockNumber(3),
+            synced_marker: BlockNumber(2)
+        })
+    );
+}
+
+#[test]
+fn export_blocks_without_compiled_classes() {
+    let ((reader, mut writer), _temp_dir, _blocks) = storage_with_blocks();
+    let class_hash = ClassHash(felt!("0x5"));
+    // The compiled class of the declared class isn't stored.
+    let block = ArchivedBlock {
+        header: BlockHeader {
+            block_header_without_hash: BlockHeaderWithoutHash {
+                block_number: BlockNumber(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        state_diff: ThinStateDiff {
+            declared_classes: indexmap! { class_hash => CompiledClassHash(felt!("0x6")) },
+            ..Default::default()
+        },
+        classes: indexmap! { class_hash => read_json_file("class.json") },
+        ..Default::default()
+    };
+    append_block(&mut writer, &block);
+    let archive_dir = tempfile::tempdir().unwrap();
+
+    assert_matches!(
+        export_archive(&reader, archive_dir.path(), BlockNumber(0), BlockNumber(3), 10),
+        Err(ArchiveError::BlocksNotSynced {
+            end_block: BlockNumber(3),
+            synced_marker: BlockNumber(2)
+        })
+    );
+}
+
+#[test]
+fn non_consecutive_files() {
+    let ((reader, _writer), _temp_dir, _blocks) = storage_with_blocks();
+    let archive_dir = tempfile::tempdir().unwrap();
+    export_archive(&reader, archive_dir.path(), BlockNumber(0), BlockNumber(2), 1).unwrap();
+
+    let first_file_path = archive_dir.path().join("000000000000-000000000000.zst");
+    let moved_file_path = archive_dir.path().join("000000000003-000000000003.zst");
+    fs::rename(&first_file_path, &moved_file_path).unwrap();
+    assert_matches!(
+        ArchiveReader::open(archive_dir.path(), BlockNumber(0)),
+        Err(ArchiveError::NonConsecutiveFiles { expected_block: BlockNumber(2), path })
+            if path == moved_file_path
+    );
+
+    // An archive doesn't have to start at the genesis block.
+    fs::remove_file(&moved_file_path).unwrap();
+    let archive = ArchiveReader::open(archive_dir.path(), BlockNumber(1)).unwrap();
+    assert_eq!(archive.files().len(), 1);
+    assert_eq!(archive.files()[0].first_block, BlockNumber(1));
+}
+
+#[test]
+fn archive_not_holding_next_block() {
+    let ((reader, _writer), _temp_dir, _blocks) = storage_with_blocks();
+    let archive_dir = tempfile::tempdir().unwrap();
+    assert_matches!(
+        ArchiveReader::open(archive_dir.path(), BlockNumber(0)),
+        Err(ArchiveError::NextBlockNotInArchive { next_block: BlockNumber(0), .. })
+    );
+
+    export_archive(&reader, archive_dir.path(), BlockNumber(1), BlockNumber(2), 1).unwrap();
+    assert_matches!(
+        ArchiveReader::open(archive_dir.path(), BlockNumber(0)),
+        Err(ArchiveError::NextBlockNotInArchive { next_block: BlockNumber(0), .. })
+    );
+    assert_matches!(
+        ArchiveReader::open(archive_dir.path(), BlockNumber(3)),
+        Err(ArchiveError::NextBlockNotInArchive { next_block: BlockNumber(3), .. })
+    );
+    // An archive that was already synced.
+    ArchiveReader::open(archive_dir.path(), BlockNumber(2)).unwrap();
+}
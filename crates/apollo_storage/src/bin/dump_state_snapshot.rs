@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use apollo_storage::checkpoint::{dump_state_snapshot, write_state_snapshot};
+use apollo_storage::db::DbConfig;
+use apollo_storage::StorageConfig;
+use clap::{value_parser, Arg, Command};
+use starknet_api::block::BlockNumber;
+
+/// Dumps the whole state of a storage at a given block into a state snapshot, from which a new node
+/// can start syncing instead of from the genesis block.
+pub fn main() {
+    let cli_params = get_cli_params();
+
+    println!("Opening storage");
+    let db_config = DbConfig {
+        path_prefix: cli_params.db_path.into(),
+        chain_id: cli_params.chain_id.into(),
+        ..Default::default()
+    };
+    let config = StorageConfig { db_config, ..Default::default() };
+    let (reader, mut _writer) =
+        apollo_storage::open_storage(config).expect("Should be able to open storage");
+
+    println!("Dumping the state at block {}", cli_params.block_number);
+    let snapshot = dump_state_snapshot(&reader, cli_params.block_number)
+        .expect("Should be able to dump the state snapshot");
+    write_state_snapshot(&snapshot, &cli_params.snapshot_path)
+        .expect("Should be able to write the state snapshot");
+    println!(
+        "Wrote the state snapshot of block {} with hash {} into {}",
+        cli_params.block_number,
+        snapshot.header.block_hash,
+        cli_params.snapshot_path.display()
+    );
+}
+
+struct CliParams {
+    db_path: String,
+    chain_id: String,
+    snapshot_path: PathBuf,
+    block_number: BlockNumber,
+}
+
+fn get_cli_params() -> CliParams {
+    let matches = Command::new("Dump state snapshot")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("snapshot_path")
+                .short('s')
+                .long("snapshot_path")
+                .required(true)
+                .value_parser(value_parser!(PathBuf))
+                .help("The file to write the state snapshot to"),
+        )
+        .arg(
+            Arg::new("block_number")
+                .short('b')
+                .long("block_number")
+                .required(true)
+                .value_parser(value_parser!(u64))
+                .help("The block to dump the state after"),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_string();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_string();
+    let snapshot_path =
+        matches.get_one::<PathBuf>("snapshot_path").expect("Missing snapshot_path").clone();
+    let block_number =
+        BlockNumber(*matches.get_one::<u64>("block_number").expect("Missing block_number"));
+
+    CliParams { db_path, chain_id, snapshot_path, block_number }
+}
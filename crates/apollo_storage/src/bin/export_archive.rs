@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use apollo_storage::archive::export_archive;
+use apollo_storage::db::DbConfig;
+use apollo_storage::StorageConfig;
+use clap::{value_parser, Arg, Command};
+use starknet_api::block::BlockNumber;
+
+/// Exports the blocks of a storage into an archive, from which a node can sync without network
+/// access.
+pub fn main() {
+    let cli_params = get_cli_params();
+
+    println!("Opening storage");
+    let db_config = DbConfig {
+        path_prefix: cli_params.db_path.into(),
+        chain_id: cli_params.chain_id.into(),
+        ..Default::default()
+    };
+    let config = StorageConfig { db_config, ..Default::default() };
+    let (reader, mut _writer) =
+        apollo_storage::open_storage(config).expect("Should be able to open storage");
+
+    println!(
+        "Exporting blocks {} to {} into {}",
+        cli_params.start_block,
+        cli_params.end_block,
+        cli_params.archive_path.display()
+    );
+    let n_files = export_archive(
+        &reader,
+        &cli_params.archive_path,
+        cli_params.start_block,
+        cli_params.end_block,
+        cli_params.blocks_per_file,
+    )
+    .expect("Should be able to export the archive");
+    println!("Wrote {n_files} archive files");
+}
+
+struct CliParams {
+    db_path: String,
+    chain_id: String,
+    archive_path: PathBuf,
+    start_block: BlockNumber,
+    end_block: BlockNumber,
+    blocks_per_file: u64,
+}
+
+fn get_cli_params() -> CliParams {
+    let matches = Command::new("Export archive")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("archive_path")
+                .short('a')
+                .long("archive_path")
+                .required(true)
+                .value_parser(value_parser!(PathBuf))
+                .help("The directory to write the archive files to"),
+        )
+        .arg(
+            Arg::new("start_block")
+                .short('s')
+                .long("start_block")
+                .default_value("0")
+                .value_parser(value_parser!(u64))
+                .help("The first block to export"),
+        )
+        .arg(
+            Arg::new("end_block")
+                .short('e')
+                .long("end_block")
+                .required(true)
+                .value_parser(value_parser!(u64))
+                .help("The block to stop the export at (exclusive)"),
+        )
+        .arg(
+            Arg::new("blocks_per_file")
+                .short('b')
+                .long("blocks_per_file")
+                .default_value("1000")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The maximal number of blocks in each archive file"),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_string();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_string();
+    let archive_path =
+        matches.get_one::<PathBuf>("archive_path").expect("Missing archive_path").clone();
+    let start_block =
+        BlockNumber(*matches.get_one::<u64>("start_block").expect("Missing start_block"));
+    let end_block = BlockNumber(*matches.get_one::<u64>("end_block").expect("Missing end_block"));
+    let blocks_per_file =
+        *matches.get_one::<u64>("blocks_per_file").expect("Missing blocks_per_file");
+
+    CliParams { db_path, chain_id, archive_path, start_block, end_block, blocks_per_file }
+}
@@ -2,13 +2,26 @@ use apollo_test_utils::{get_test_block, get_test_body};
 use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 use starknet_api::block::{BlockBody, BlockNumber};
-use starknet_api::transaction::TransactionOffsetInBlock;
+use starknet_api::core::ContractAddress;
+use starknet_api::transaction::{
+    DeployAccountTransaction,
+    DeployAccountTransactionOutput,
+    DeployAccountTransactionV1,
+    InvokeTransaction,
+    InvokeTransactionOutput,
+    InvokeTransactionV0,
+    InvokeTransactionV1,
+    Transaction,
+    TransactionOffsetInBlock,
+    TransactionOutput,
+};
+use starknet_api::{contract_address, nonce, tx_hash};
 use test_case::test_case;
 
 use crate::body::{BodyStorageReader, BodyStorageWriter, TransactionIndex};
 use crate::db::table_types::Table;
 use crate::db::{DbError, KeyAlreadyExistsError};
-use crate::test_utils::{get_test_storage, get_test_storage_by_scope};
+use crate::test_utils::{get_test_storage, get_test_storage_by_scope, TestStorageBuilder};
 use crate::{OffsetKind, StorageError, StorageScope, StorageWriter};
 
 #[tokio::test]
@@ -411,3 +424,148 @@ fn update_offset_table() {
         file_offset_table.get(&txn.txn, &OffsetKind::TransactionOutput).unwrap().unwrap()
     );
 }
+
+fn invoke_v1(sender_address: ContractAddress, nonce: u64) -> Transaction {
+    Transaction::Invoke(InvokeTransaction::V1(InvokeTransactionV1 {
+        sender_address,
+        nonce: nonce!(nonce),
+        ..Default::default()
+    }))
+}
+
+fn invoke_output() -> TransactionOutput {
+    TransactionOutput::Invoke(InvokeTransactionOutput::default())
+}
+
+#[test]
+fn sender_transactions() {
+    let ((reader, mut writer), _config, _temp_dir) =
+        TestStorageBuilder::new(None).index_transactions_by_sender(true).build();
+    let account = contract_address!("0x100");
+    let other_account = contract_address!("0x200");
+
+    let body0 = BlockBody {
+        transactions: vec![
+            Transaction::DeployAccount(DeployAccountTransaction::V1(
+                DeployAccountTransactionV1::default(),
+            )),
+            invoke_v1(other_account, 0),
+        ],
+        transaction_outputs: vec![
+            TransactionOutput::DeployAccount(DeployAccountTransactionOutput {
+                contract_address: account,
+                ..Default::default()
+            }),
+            invoke_output(),
+        ],
+        transaction_hashes: vec![tx_hash!(0_u64), tx_hash!(1_u64)],
+    };
+    let body1 = BlockBody {
+        transactions: vec![
+            invoke_v1(account, 1),
+            // Invoke V0 transactions have no nonce, so they aren't indexed.
+            Transaction::Invoke(InvokeTransaction::V0(InvokeTransactionV0 {
+                contract_address: account,
+                ..Default::default()
+            })),
+            invoke_v1(account, 2),
+        ],
+        transaction_outputs: vec![invoke_output(), invoke_output(), invoke_output()],
+        transaction_hashes: vec![tx_hash!(2_u64), tx_hash!(3_u64), tx_hash!(4_u64)],
+    };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_body(BlockNumber(0), body0)
+        .unwrap()
+        .append_body(BlockNumber(1), body1)
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    let deploy_account_index = TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(0));
+    let first_invoke_index = TransactionIndex(BlockNumber(1), TransactionOffsetInBlock(0));
+    let second_invoke_index = TransactionIndex(BlockNumber(1), TransactionOffsetInBlock(2));
+    assert_eq!(
+        txn.get_sender_transactions(account, nonce!(0_u8), 10).unwrap(),
+        vec![
+            (nonce!(0_u8), deploy_account_index),
+            (nonce!(1_u8), first_invoke_index),
+            (nonce!(2_u8), second_invoke_index),
+        ]
+    );
+    // Pagination.
+    assert_eq!(
+        txn.get_sender_transactions(account, nonce!(1_u8), 1).unwrap(),
+        vec![(nonce!(1_u8), first_invoke_index)]
+    );
+    assert_eq!(txn.get_sender_transactions(account, nonce!(3_u8), 10).unwrap(), vec![]);
+    assert_eq!(
+        txn.get_sender_transactions(other_account, nonce!(0_u8), 10).unwrap(),
+        vec![(nonce!(0_u8), TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(1)))]
+    );
+    drop(txn);
+
+    // Reverting a block removes its transactions from the index.
+    let (txn, _) = writer.begin_rw_txn().unwrap().revert_body(BlockNumber(1)).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_sender_transactions(account, nonce!(0_u8), 10).unwrap(),
+        vec![(nonce!(0_u8), deploy_account_index)]
+    );
+}
+
+#[test]
+fn sender_transactions_with_repeated_nonce() {
+    let ((reader, mut writer), _config, _temp_dir) =
+        TestStorageBuilder::new(None).index_transactions_by_sender(true).build();
+    let account = contract_address!("0x100");
+    let body = |tx_hash| BlockBody {
+        transactions: vec![invoke_v1(account, 1)],
+        transaction_outputs: vec![invoke_output()],
+        transaction_hashes: vec![tx_hash],
+    };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_body(BlockNumber(0), body(tx_hash!(0_u64)))
+        .unwrap()
+        .append_body(BlockNumber(1), body(tx_hash!(1_u64)))
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let first_index = TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(0));
+    let second_index = TransactionIndex(BlockNumber(1), TransactionOffsetInBlock(0));
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_sender_transactions(account, nonce!(0_u8), 10).unwrap(),
+        vec![(nonce!(1_u8), first_index), (nonce!(1_u8), second_index)]
+    );
+
+    // Reverting the later block keeps the earlier transaction with the same nonce.
+    let (txn, _) = writer.begin_rw_txn().unwrap().revert_body(BlockNumber(1)).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_sender_transactions(account, nonce!(0_u8), 10).unwrap(),
+        vec![(nonce!(1_u8), first_index)]
+    );
+}
+
+#[test]
+fn sender_transactions_index_disabled() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let body = BlockBody {
+        transactions: vec![invoke_v1(contract_address!("0x100"), 0)],
+        transaction_outputs: vec![invoke_output()],
+        transaction_hashes: vec![tx_hash!(0_u64)],
+    };
+    writer.begin_rw_txn().unwrap().append_body(BlockNumber(0), body).unwrap().commit().unwrap();
+
+    let result = reader.begin_ro_txn().unwrap().get_sender_transactions(
+        contract_address!("0x100"),
+        nonce!(0_u8),
+        10,
+    );
+    assert_matches!(result, Err(StorageError::SenderIndexDisabled));
+}
@@ -48,8 +48,10 @@ use std::fmt::Debug;
 use apollo_proc_macros::latency_histogram;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockBody, BlockNumber};
-use starknet_api::core::ContractAddress;
+use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::transaction::{
+    DeclareTransaction,
+    InvokeTransaction,
     Transaction,
     TransactionHash,
     TransactionOffsetInBlock,
@@ -81,6 +83,11 @@ type TransactionHashToIdxTable<'env> =
 type EventsTableKey = (ContractAddress, TransactionIndex);
 type EventsTable<'env> =
     TableHandle<'env, EventsTableKey, NoVersionValueWrapper<NoValue>, CommonPrefix>;
+// A (sender, nonce) pair may repeat in the chain's history, so the transaction index is part of the
+// key.
+type SenderTransactionsTableKey = (ContractAddress, (Nonce, TransactionIndex));
+type SenderTransactionsTable<'env> =
+    TableHandle<'env, SenderTransactionsTableKey, NoVersionValueWrapper<NoValue>, CommonPrefix>;
 
 /// The index of a transaction in a block.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
@@ -145,6 +152,19 @@ pub trait BodyStorageReader {
         &self,
         block_number: BlockNumber,
     ) -> StorageResult<Option<usize>>;
+
+    /// Returns the indices of up to `limit` transactions sent by the given address, ordered by
+    /// nonce and then by index, and starting from the given nonce. Each index is paired with the
+    /// nonce of its transaction.
+    ///
+    /// Returns [`StorageError::SenderIndexDisabled`] if the storage doesn't index transactions by
+    /// sender.
+    fn get_sender_transactions(
+        &self,
+        sender_address: ContractAddress,
+        from_nonce: Nonce,
+        limit: usize,
+    ) -> StorageResult<Vec<(Nonce, TransactionIndex)>>;
 }
 
 type RevertedBlockBody = (Vec<Transaction>, Vec<TransactionOutput>, Vec<TransactionHash>);
@@ -261,7 +281,7 @@ impl<Mode: TransactionKind> BodyStorageReader for StorageTxn<'_, Mode> {
     ) -> StorageResult<Option<usize>> {
         // After this condition, we know that the block exists, so if something goes wrong is only
         // because there are no transactions in it.
-        if self.get_body_marker()? <= block_number {
+        if self.get_body_marker()? <= block_number || self.is_before_checkpoint(block_number)? {
             return Ok(None);
         }
 
@@ -283,6 +303,33 @@ impl<Mode: TransactionKind> BodyStorageReader for StorageTxn<'_, Mode> {
 
         Ok(Some(last_tx_index.0 + 1))
     }
+
+    fn get_sender_transactions(
+        &self,
+        sender_address: ContractAddress,
+        from_nonce: Nonce,
+        limit: usize,
+    ) -> StorageResult<Vec<(Nonce, TransactionIndex)>> {
+        if !self.index_transactions_by_sender {
+            return Err(StorageError::SenderIndexDisabled);
+        }
+
+        let sender_transactions_table = self.open_table(&self.tables.sender_transactions)?;
+        let mut cursor = sender_transactions_table.cursor(&self.txn)?;
+        let mut current = cursor.lower_bound(&(
+            sender_address,
+            (from_nonce, TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(0))),
+        ))?;
+        let mut res = Vec::new();
+        while let Some(((current_sender_address, (nonce, transaction_index)), _)) = current {
+            if current_sender_address != sender_address || res.len() == limit {
+                break;
+            }
+            res.push((nonce, transaction_index));
+            current = cursor.next()?;
+        }
+        Ok(res)
+    }
 }
 
 impl<'env, Mode: TransactionKind> StorageTxn<'env, Mode> {
@@ -293,7 +340,7 @@ impl<'env, Mode: TransactionKind> StorageTxn<'env, Mode> {
         transaction_metadata_table: TransactionMetadataTable<'env>,
         tx_metadata_to_tx_object: fn(TransactionMetadata, &FileHandlers<Mode>) -> StorageResult<T>,
     ) -> StorageResult<Option<Vec<T>>> {
-        if self.get_body_marker()? <= block_number {
+        if self.get_body_marker()? <= block_number || self.is_before_checkpoint(block_number)? {
             return Ok(None);
         }
         let mut cursor = transaction_metadata_table.cursor(&self.txn)?;
@@ -394,6 +441,17 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
                 &events_table,
                 block_number,
             )?;
+
+            if self.index_transactions_by_sender {
+                let sender_transactions_table =
+                    self.open_table(&self.tables.sender_transactions)?;
+                write_sender_transactions(
+                    &block_body,
+                    &self.txn,
+                    &sender_transactions_table,
+                    block_number,
+                )?;
+            }
         }
 
         Ok(self)
@@ -428,6 +486,7 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
             let transaction_hash_to_idx_table =
                 self.open_table(&self.tables.transaction_hash_to_idx)?;
             let events_table = self.open_table(&self.tables.events)?;
+            let sender_transactions_table = self.open_table(&self.tables.sender_transactions)?;
 
             let transactions = self
                 .get_block_transactions(block_number)?
@@ -440,14 +499,22 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
                 .unwrap_or_else(|| panic!("Missing transaction hashes for block {block_number}."));
 
             // Delete the transactions data.
-            for (offset, (tx_hash, tx_output)) in
-                transaction_hashes.iter().zip(transaction_outputs.iter()).enumerate()
+            for (offset, ((tx, tx_hash), tx_output)) in transactions
+                .iter()
+                .zip(transaction_hashes.iter())
+                .zip(transaction_outputs.iter())
+                .enumerate()
             {
                 let tx_index = TransactionIndex(block_number, TransactionOffsetInBlock(offset));
 
                 for event in tx_output.events().iter() {
                     events_table.delete(&self.txn, &(event.from_address, tx_index))?;
                 }
+                // Deleting is a no-op if the index was disabled when the block was written.
+                if let Some((sender_address, nonce)) = sender_and_nonce(tx, tx_output) {
+                    sender_transactions_table
+                        .delete(&self.txn, &(sender_address, (nonce, tx_index)))?;
+                }
                 transaction_hash_to_idx_table.delete(&self.txn, tx_hash)?;
                 transaction_metadata_table.delete(&self.txn, &tx_index)?;
             }
@@ -528,6 +595,46 @@ fn write_events<'env>(
     Ok(())
 }
 
+fn write_sender_transactions<'env>(
+    block_body: &BlockBody,
+    txn: &DbTransaction<'env, RW>,
+    sender_transactions_table: &'env SenderTransactionsTable<'env>,
+    block_number: BlockNumber,
+) -> StorageResult<()> {
+    for (index, (tx, tx_output)) in
+        block_body.transactions.iter().zip(block_body.transaction_outputs.iter()).enumerate()
+    {
+        let Some((sender_address, nonce)) = sender_and_nonce(tx, tx_output) else {
+            continue;
+        };
+        let transaction_index = TransactionIndex(block_number, TransactionOffsetInBlock(index));
+        sender_transactions_table.insert(
+            txn,
+            &(sender_address, (nonce, transaction_index)),
+            &NoValue,
+        )?;
+    }
+    Ok(())
+}
+
+// Returns the sender address and nonce of transactions that are sent by an account. Transactions
+// without a meaningful nonce (deploy, L1 handler, invoke V0 and declare V0) are not indexed.
+fn sender_and_nonce(
+    tx: &Transaction,
+    tx_output: &TransactionOutput,
+) -> Option<(ContractAddress, Nonce)> {
+    match (tx, tx_output) {
+        (Transaction::Invoke(InvokeTransaction::V0(_)), _)
+        | (Transaction::Declare(DeclareTransaction::V0(_)), _) => None,
+        (Transaction::Invoke(tx), _) => Some((tx.sender_address(), tx.nonce())),
+        (Transaction::Declare(tx), _) => Some((tx.sender_address(), tx.nonce())),
+        (Transaction::DeployAccount(tx), TransactionOutput::DeployAccount(output)) => {
+            Some((output.contract_address, tx.nonce()))
+        }
+        _ => None,
+    }
+}
+
 fn update_marker<'env>(
     txn: &DbTransaction<'env, RW>,
     markers_table: &'env MarkersTable<'env>,
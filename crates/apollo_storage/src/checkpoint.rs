@@ -0,0 +1,350 @@
+//! Starting a storage from a trusted checkpoint instead of from the genesis block.
+//!
+//! A storage is seeded from a state snapshot: an [`ArchivedBlock`] whose state diff is the whole
+//! state after the block, and whose classes are all the classes defined up to it (see
+//! [`dump_state_snapshot`]). The state of the snapshot is stored apart from the state diffs, since
+//! the state diff of the checkpoint block itself isn't known. After seeding, the blocks after the
+//! checkpoint are appended as usual, and the headers of the blocks below it can be prepended one
+//! by one, from the checkpoint backwards. Nothing but the headers is stored for the blocks below
+//! the checkpoint, and the states before the checkpoint can't be read.
+//!
+//! Import [`CheckpointStorageReader`] and [`CheckpointStorageWriter`] to read and write data
+//! related to the checkpoint using a [`StorageTxn`].
+
+#[cfg(test)]
+#[path = "checkpoint_test.rs"]
+mod checkpoint_test;
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use indexmap::{IndexMap, IndexSet};
+use starknet_api::block::{BlockHeader, BlockNumber};
+use starknet_api::state::ThinStateDiff;
+use starknet_types_core::felt::Felt;
+use tracing::info;
+
+use crate::archive::{
+    read_block_without_state,
+    synced_marker,
+    write_file,
+    ArchiveError,
+    ArchiveResult,
+    ArchivedBlock,
+    ARCHIVE_FORMAT_VERSION,
+};
+use crate::body::BodyStorageWriter;
+use crate::class::{ClassStorageReader, ClassStorageWriter};
+use crate::compiled_class::{CasmStorageReader, CasmStorageWriter};
+use crate::db::serialization::StorageSerde;
+use crate::db::table_types::{DbCursorTrait, Table};
+use crate::db::{TransactionKind, RW};
+use crate::header::{
+    update_hash_mapping,
+    HeaderStorageReader,
+    HeaderStorageWriter,
+    StorageBlockHeader,
+};
+use crate::state::StateStorageReader;
+use crate::{MarkerKind, OffsetKind, StorageError, StorageReader, StorageResult, StorageTxn};
+
+/// Interface for reading data related to the checkpoint of the storage.
+pub trait CheckpointStorageReader {
+    /// Returns the block the storage was seeded at, if it was seeded from a checkpoint.
+    fn get_checkpoint(&self) -> StorageResult<Option<BlockNumber>>;
+
+    /// Returns the lowest block number whose header is stored, if any header is stored. Below the
+    /// checkpoint, this is the last header that was prepended.
+    fn get_first_header_block_number(&self) -> StorageResult<Option<BlockNumber>>;
+
+    /// Returns the whole state after the checkpoint block, if the storage was seeded from a
+    /// checkpoint. The state diff of the checkpoint block itself isn't stored.
+    fn get_checkpoint_state(&self) -> StorageResult<Option<ThinStateDiff>>;
+}
+
+/// Interface for writing data related to the checkpoint of the storage.
+pub trait CheckpointStorageWriter
+where
+    Self: Sized,
+{
+    /// Seeds an empty storage with the given state snapshot, so that the next block to append is
+    /// the one after the snapshot's block.
+    // To enforce that no commit happen after a failure, we consume and return Self on success.
+    fn seed_checkpoint(self, snapshot: &ArchivedBlock) -> StorageResult<Self>;
+
+    /// Stores the header of the block right below the first stored header. The header isn't
+    /// verified against the headers after it.
+    fn prepend_header(
+        self,
+        block_number: BlockNumber,
+        block_header: &BlockHeader,
+    ) -> StorageResult<Self>;
+}
+
+impl<Mode: TransactionKind> CheckpointStorageReader for StorageTxn<'_, Mode> {
+    fn get_checkpoint(&self) -> StorageResult<Option<BlockNumber>> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        Ok(markers_table.get(&self.txn, &MarkerKind::Checkpoint)?)
+    }
+
+    fn get_first_header_block_number(&self) -> StorageResult<Option<BlockNumber>> {
+        let headers_table = self.open_table(&self.tables.headers)?;
+        let mut cursor = headers_table.cursor(&self.txn)?;
+        Ok(cursor.lower_bound(&BlockNumber(0))?.map(|(block_number, _header)| block_number))
+    }
+
+    fn get_checkpoint_state(&self) -> StorageResult<Option<ThinStateDiff>> {
+        let Some(checkpoint) = self.get_checkpoint()? else {
+            return Ok(None);
+        };
+        let checkpoint_state_table = self.open_table(&self.tables.checkpoint_state)?;
+        checkpoint_state_table
+            .get(&self.txn, &checkpoint)?
+            .map(|location| self.file_handlers.get_thin_state_diff_unchecked(location))
+            .transpose()
+    }
+}
+
+impl<Mode: TransactionKind> StorageTxn<'_, Mode> {
+    // Returns whether the storage was seeded from a checkpoint above the given block, in which case
+    // only the header of the block may be stored.
+    pub(crate) fn is_before_checkpoint(&self, block_number: BlockNumber) -> StorageResult<bool> {
+        Ok(self.get_checkpoint()?.is_some_and(|checkpoint| block_number < checkpoint))
+    }
+}
+
+impl CheckpointStorageWriter for StorageTxn<'_, RW> {
+    fn seed_checkpoint(self, snapshot: &ArchivedBlock) -> StorageResult<Self> {
+        if self.get_header_marker()? != BlockNumber(0)
+            || self.get_state_marker()? != BlockNumber(0)
+            || self.get_checkpoint()?.is_some()
+        {
+            return Err(StorageError::CheckpointInNonEmptyStorage);
+        }
+        let block_number = snapshot.header.block_header_without_hash.block_number;
+        let markers_table = self.open_table(&self.tables.markers)?;
+        for marker_kind in [
+            MarkerKind::Header,
+            MarkerKind::Body,
+            MarkerKind::Event,
+            MarkerKind::State,
+            MarkerKind::Class,
+            MarkerKind::CompiledClass,
+            MarkerKind::Checkpoint,
+        ] {
+            markers_table.upsert(&self.txn, &marker_kind, &block_number)?;
+        }
+
+        // The state is written like a state diff of the checkpoint block, but it's stored apart
+        // from the state diffs.
+        self.write_state(block_number, &snapshot.state_diff)?;
+        let location = self.file_handlers.append_state_diff(&snapshot.state_diff);
+        let checkpoint_state_table = self.open_table(&self.tables.checkpoint_state)?;
+        checkpoint_state_table.insert(&self.txn, &block_number, &location)?;
+        let file_offset_table = self.txn.open_table(&self.tables.file_offsets)?;
+        file_offset_table.upsert(&self.txn, &OffsetKind::ThinStateDiff, &location.next_offset())?;
+        markers_table.upsert(&self.txn, &MarkerKind::State, &block_number.unchecked_next())?;
+
+        let mut txn = self
+            .append_header(block_number, &snapshot.header)?
+            .append_body(block_number, snapshot.body.clone())?
+            .append_classes(
+                block_number,
+                &snapshot
+                    .classes
+                    .iter()
+                    .map(|(class_hash, class)| (*class_hash, class))
+                    .collect::<Vec<_>>(),
+                &snapshot
+                    .deprecated_classes
+                    .iter()
+                    .map(|(class_hash, deprecated_class)| (*class_hash, deprecated_class))
+                    .collect::<Vec<_>>(),
+            )?;
+        if let Some(signature) = &snapshot.signature {
+            txn = txn.append_block_signature(block_number, signature)?;
+        }
+        for (class_hash, casm) in &snapshot.casms {
+            txn = txn.append_casm(class_hash, casm)?;
+        }
+        // The checkpoint block has no state diff, so appending the compiled classes doesn't advance
+        // the marker.
+        let markers_table = txn.open_table(&txn.tables.markers)?;
+        markers_table.upsert(
+            &txn.txn,
+            &MarkerKind::CompiledClass,
+            &block_number.unchecked_next(),
+        )?;
+        Ok(txn)
+    }
+
+    fn prepend_header(
+        self,
+        block_number: BlockNumber,
+        block_header: &BlockHeader,
+    ) -> StorageResult<Self> {
+        let first_header_block_number = self.get_first_header_block_number()?;
+        let Some(next_block_number) =
+            first_header_block_number.filter(|first_header_block_number| {
+                first_header_block_number.prev() == Some(block_number)
+            })
+        else {
+            return Err(StorageError::PrependedHeaderNotConsecutive {
+                block_number,
+                first_header_block_number,
+            });
+        };
+        let headers_table = self.open_table(&self.tables.headers)?;
+        let block_hash_to_number_table = self.open_table(&self.tables.block_hash_to_number)?;
+        let starknet_version_table = self.open_table(&self.tables.starknet_version)?;
+
+        let storage_block_header = StorageBlockHeader::from(block_header);
+        headers_table.insert(&self.txn, &block_number, &storage_block_header)?;
+        update_hash_mapping(
+            &self.txn,
+            &block_hash_to_number_table,
+            &storage_block_header,
+            block_number,
+        )?;
+
+        // The Starknet version is stored only for the blocks where it changes, so the first stored
+        // header always has a mapping, and the mapping of the next block may become redundant.
+        let starknet_version = &block_header.block_header_without_hash.starknet_version;
+        if starknet_version_table.get(&self.txn, &next_block_number)?.as_ref()
+            == Some(starknet_version)
+        {
+            starknet_version_table.delete(&self.txn, &next_block_number)?;
+        }
+        starknet_version_table.insert(&self.txn, &block_number, starknet_version)?;
+        Ok(self)
+    }
+}
+
+/// Returns a state snapshot of the given block: the block with the whole state after it instead of
+/// its state diff, and with all the classes and compiled classes that are defined up to it.
+///
+/// All the data of the blocks up to the given one (from the checkpoint of the storage, if it has
+/// one), including their classes and compiled classes, must be in the storage.
+pub fn dump_state_snapshot(
+    reader: &StorageReader,
+    block_number: BlockNumber,
+) -> ArchiveResult<ArchivedBlock> {
+    let txn = reader.begin_ro_txn()?;
+    let synced_marker = synced_marker(&txn)?;
+    if block_number >= synced_marker {
+        return Err(ArchiveError::BlocksNotSynced {
+            end_block: block_number.unchecked_next(),
+            synced_marker,
+        });
+    }
+    let mut snapshot = read_block_without_state(&txn, block_number)?;
+
+    // A storage that was seeded from a checkpoint has the state of the checkpoint, and the state
+    // diffs of the blocks after it.
+    let (mut state, first_block) = match txn.get_checkpoint()? {
+        Some(checkpoint) => {
+            let checkpoint_state =
+                txn.get_checkpoint_state()?.ok_or_else(|| ArchiveError::MissingBlockData {
+                    block_number: checkpoint,
+                    data: "checkpoint state".to_string(),
+                })?;
+            (checkpoint_state, checkpoint.unchecked_next())
+        }
+        None => (ThinStateDiff::default(), BlockNumber(0)),
+    };
+    let mut deprecated_declared_classes =
+        state.deprecated_declared_classes.iter().copied().collect::<IndexSet<_>>();
+    for state_diff_block_number in first_block.iter_up_to(block_number.unchecked_next()) {
+        let state_diff = txn.get_state_diff(state_diff_block_number)?.ok_or_else(|| {
+            ArchiveError::MissingBlockData {
+                block_number: state_diff_block_number,
+                data: "state diff".to_string(),
+            }
+        })?;
+        deprecated_declared_classes.extend(state_diff.deprecated_declared_classes.iter().copied());
+        apply_state_diff(&mut state, state_diff);
+    }
+    state.deprecated_declared_classes = deprecated_declared_classes.into_iter().collect();
+
+    let missing = |data: String| ArchiveError::MissingBlockData { block_number, data };
+    let mut classes = IndexMap::new();
+    let mut casms = IndexMap::new();
+    for class_hash in state.declared_classes.keys() {
+        let class =
+            txn.get_class(class_hash)?.ok_or_else(|| missing(format!("class {class_hash}")))?;
+        let casm = txn
+            .get_casm(class_hash)?
+            .ok_or_else(|| missing(format!("compiled class of {class_hash}")))?;
+        classes.insert(*class_hash, class);
+        casms.insert(*class_hash, casm);
+    }
+    // Cairo 0 classes are either declared or defined by their first deployment.
+    let mut deprecated_classes = IndexMap::new();
+    for class_hash in
+        state.deprecated_declared_classes.iter().chain(state.deployed_contracts.values())
+    {
+        if state.declared_classes.contains_key(class_hash)
+            || deprecated_classes.contains_key(class_hash)
+        {
+            continue;
+        }
+        let deprecated_class = txn
+            .get_deprecated_class(class_hash)?
+            .ok_or_else(|| missing(format!("deprecated class {class_hash}")))?;
+        deprecated_classes.insert(*class_hash, deprecated_class);
+    }
+
+    snapshot.state_diff = state;
+    snapshot.classes = classes;
+    snapshot.deprecated_classes = deprecated_classes;
+    snapshot.casms = casms;
+    Ok(snapshot)
+}
+
+// Applies the state diff on top of the given state. Storage values that were reset to zero are
+// removed, since zero is the default value.
+fn apply_state_diff(state: &mut ThinStateDiff, state_diff: ThinStateDiff) {
+    state.deployed_contracts.extend(state_diff.deployed_contracts);
+    for (address, storage_diff) in state_diff.storage_diffs {
+        let storage = state.storage_diffs.entry(address).or_default();
+        for (key, value) in storage_diff {
+            if value == Felt::ZERO {
+                storage.shift_remove(&key);
+            } else {
+                storage.insert(key, value);
+            }
+        }
+        if storage.is_empty() {
+            state.storage_diffs.shift_remove(&address);
+        }
+    }
+    state.declared_classes.extend(state_diff.declared_classes);
+    state.nonces.extend(state_diff.nonces);
+}
+
+/// Writes a state snapshot into a compressed file.
+pub fn write_state_snapshot(snapshot: &ArchivedBlock, path: &Path) -> ArchiveResult<()> {
+    write_file(path, |encoder| Ok(snapshot.serialize_into(encoder)?))?;
+    info!(
+        "Wrote the state snapshot of block {} into {}.",
+        snapshot.header.block_header_without_hash.block_number,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Reads a state snapshot that was written by [`write_state_snapshot`].
+pub fn read_state_snapshot(path: &Path) -> ArchiveResult<ArchivedBlock> {
+    let corrupted_snapshot = || ArchiveError::CorruptedStateSnapshot { path: path.to_path_buf() };
+    let mut decoder = zstd::Decoder::new(File::open(path)?)?;
+    let version = u8::deserialize_from(&mut decoder).ok_or_else(corrupted_snapshot)?;
+    if version != ARCHIVE_FORMAT_VERSION {
+        return Err(ArchiveError::UnsupportedFormatVersion { version, path: path.to_path_buf() });
+    }
+    let snapshot = ArchivedBlock::deserialize_from(&mut decoder).ok_or_else(corrupted_snapshot)?;
+    if decoder.read(&mut [0])? != 0 {
+        return Err(corrupted_snapshot());
+    }
+    Ok(snapshot)
+}
@@ -0,0 +1,344 @@
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use indexmap::indexmap;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{
+    BlockBody,
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    StarknetVersion,
+};
+use starknet_api::core::{ClassHash, CompiledClassHash, Nonce};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use starknet_api::state::{SierraContractClass, StateNumber, ThinStateDiff};
+use starknet_api::test_utils::read_json_file;
+use starknet_api::{contract_address, felt, storage_key};
+
+use crate::archive::ArchivedBlock;
+use crate::body::{BodyStorageReader, BodyStorageWriter};
+use crate::checkpoint::{
+    dump_state_snapshot,
+    read_state_snapshot,
+    write_state_snapshot,
+    CheckpointStorageReader,
+    CheckpointStorageWriter,
+};
+use crate::class::ClassStorageWriter;
+use crate::compiled_class::{CasmStorageReader, CasmStorageWriter};
+use crate::header::{HeaderStorageReader, HeaderStorageWriter};
+use crate::state::{StateStorageReader, StateStorageWriter};
+use crate::test_utils::get_test_storage;
+use crate::StorageError;
+
+fn header(block_number: u64, starknet_version: StarknetVersion) -> BlockHeader {
+    BlockHeader {
+        block_hash: BlockHash(felt!(block_number + 100)),
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(block_number),
+            parent_hash: BlockHash(felt!(block_number + 99)),
+            starknet_version,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn snapshot(block_number: u64) -> ArchivedBlock {
+    let class_hash = ClassHash(felt!("0x1"));
+    let deprecated_class_hash = ClassHash(felt!("0x2"));
+    ArchivedBlock {
+        header: header(block_number, StarknetVersion::LATEST),
+        state_diff: ThinStateDiff {
+            deployed_contracts: indexmap! {
+                contract_address!("0x100") => class_hash,
+                contract_address!("0x101") => deprecated_class_hash,
+            },
+            storage_diffs: indexmap! {
+                contract_address!("0x100") => indexmap! { storage_key!("0x10") => felt!("0x11") },
+            },
+            declared_classes: indexmap! { class_hash => CompiledClassHash(felt!("0x3")) },
+            nonces: indexmap! { contract_address!("0x100") => Nonce(felt!("0x4")) },
+            ..Default::default()
+        },
+        classes: indexmap! { class_hash => read_json_file::<SierraContractClass>("class.json") },
+        deprecated_classes: indexmap! {
+            deprecated_class_hash => read_json_file::<DeprecatedContractClass>("deprecated_class.json"),
+        },
+        casms: indexmap! {
+            class_hash => read_json_file::<CasmContractClass>("compiled_class.json"),
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn seed_checkpoint() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let snapshot = snapshot(5);
+    writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot).unwrap().commit().unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_checkpoint().unwrap(), Some(BlockNumber(5)));
+    assert_eq!(txn.get_first_header_block_number().unwrap(), Some(BlockNumber(5)));
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(6));
+    assert_eq!(txn.get_body_marker().unwrap(), BlockNumber(6));
+    assert_eq!(txn.get_state_marker().unwrap(), BlockNumber(6));
+    assert_eq!(txn.get_compiled_class_marker().unwrap(), BlockNumber(6));
+    assert_eq!(txn.get_block_header(BlockNumber(5)).unwrap(), Some(snapshot.header.clone()));
+    assert_eq!(txn.get_block_transactions(BlockNumber(5)).unwrap(), Some(vec![]));
+    assert_eq!(
+        txn.get_casm(&ClassHash(felt!("0x1"))).unwrap(),
+        snapshot.casms.get(&ClassHash(felt!("0x1"))).cloned()
+    );
+
+    // Nothing is stored below the checkpoint.
+    assert_eq!(txn.get_block_header(BlockNumber(4)).unwrap(), None);
+    assert_eq!(txn.get_starknet_version(BlockNumber(4)).unwrap(), None);
+    assert_eq!(txn.get_block_transactions(BlockNumber(4)).unwrap(), None);
+    assert_eq!(txn.get_block_transactions_count(BlockNumber(4)).unwrap(), None);
+    assert_eq!(txn.get_state_diff(BlockNumber(4)).unwrap(), None);
+
+    // The state of the snapshot isn't the state diff of the checkpoint block.
+    assert_eq!(txn.get_state_diff(BlockNumber(5)).unwrap(), None);
+    assert_eq!(txn.get_checkpoint_state().unwrap(), Some(snapshot.state_diff.clone()));
+
+    let state_reader = txn.get_state_reader().unwrap();
+    // The states before the checkpoint aren't stored.
+    let state_before_checkpoint = StateNumber::right_before_block(BlockNumber(5));
+    assert_matches!(
+        state_reader.get_storage_at(
+            state_before_checkpoint,
+            &contract_address!("0x100"),
+            &storage_key!("0x10")
+        ),
+        Err(StorageError::StateBeforeCheckpoint {
+            state_number: StateNumber(BlockNumber(5)),
+            checkpoint: BlockNumber(5)
+        })
+    );
+    assert_matches!(
+        state_reader.get_class_hash_at(state_before_checkpoint, &contract_address!("0x100")),
+        Err(StorageError::StateBeforeCheckpoint { .. })
+    );
+    let state_number = StateNumber::unchecked_right_after_block(BlockNumber(5));
+    assert_eq!(
+        state_reader
+            .get_storage_at(state_number, &contract_address!("0x100"), &storage_key!("0x10"))
+            .unwrap(),
+        felt!("0x11")
+    );
+    assert_eq!(
+        state_reader.get_nonce_at(state_number, &contract_address!("0x100")).unwrap(),
+        Some(Nonce(felt!("0x4")))
+    );
+    assert_eq!(
+        state_reader.get_class_definition_at(state_number, &ClassHash(felt!("0x1"))).unwrap(),
+        snapshot.classes.get(&ClassHash(felt!("0x1"))).cloned()
+    );
+    assert_eq!(
+        state_reader
+            .get_deprecated_class_definition_at(state_number, &ClassHash(felt!("0x2")))
+            .unwrap(),
+        snapshot.deprecated_classes.get(&ClassHash(felt!("0x2"))).cloned()
+    );
+    drop(txn);
+
+    // The sync continues from the block after the checkpoint.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(6), &header(6, StarknetVersion::LATEST))
+        .unwrap()
+        .append_body(BlockNumber(6), BlockBody::default())
+        .unwrap()
+        .append_state_diff(BlockNumber(6), ThinStateDiff::default())
+        .unwrap()
+        .append_classes(BlockNumber(6), &[], &[])
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // A checkpoint can't be seeded twice.
+    let Err(err) = writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot) else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(err, StorageError::CheckpointInNonEmptyStorage);
+}
+
+#[test]
+fn seed_checkpoint_in_non_empty_storage() {
+    let ((_reader, mut writer), _temp_dir) = get_test_storage();
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header(0, StarknetVersion::LATEST))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let Err(err) = writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot(5)) else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(err, StorageError::CheckpointInNonEmptyStorage);
+}
+
+#[test]
+fn prepend_headers() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot(5)).unwrap().commit().unwrap();
+
+    let old_header = header(3, StarknetVersion::V0_13_0);
+    let new_header = header(4, StarknetVersion::LATEST);
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .prepend_header(BlockNumber(4), &new_header)
+        .unwrap()
+        .prepend_header(BlockNumber(3), &old_header)
+        .unwrap()
+        .commit()
+        .unwrap();
+    let Err(err) = writer
+        .begin_rw_txn()
+        .unwrap()
+        .prepend_header(BlockNumber(1), &header(1, StarknetVersion::LATEST))
+    else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(
+        err,
+        StorageError::PrependedHeaderNotConsecutive {
+            block_number: BlockNumber(1),
+            first_header_block_number: Some(BlockNumber(3))
+        }
+    );
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_first_header_block_number().unwrap(), Some(BlockNumber(3)));
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(6));
+    assert_eq!(txn.get_block_header(BlockNumber(3)).unwrap(), Some(old_header.clone()));
+    assert_eq!(txn.get_block_header(BlockNumber(4)).unwrap(), Some(new_header.clone()));
+    assert_eq!(txn.get_block_number_by_hash(&old_header.block_hash).unwrap(), Some(BlockNumber(3)));
+    assert_eq!(txn.get_starknet_version(BlockNumber(5)).unwrap(), Some(StarknetVersion::LATEST));
+    assert_eq!(txn.get_starknet_version(BlockNumber(2)).unwrap(), None);
+    // Bodies aren't backfilled.
+    assert_eq!(txn.get_block_transactions(BlockNumber(4)).unwrap(), None);
+}
+
+#[test]
+fn prepend_header_without_checkpoint() {
+    let ((_reader, mut writer), _temp_dir) = get_test_storage();
+    let Err(err) = writer
+        .begin_rw_txn()
+        .unwrap()
+        .prepend_header(BlockNumber(0), &header(0, StarknetVersion::LATEST))
+    else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(
+        err,
+        StorageError::PrependedHeaderNotConsecutive {
+            block_number: BlockNumber(0),
+            first_header_block_number: None
+        }
+    );
+}
+
+#[test]
+fn dump_and_seed_state_snapshot() {
+    let class: SierraContractClass = read_json_file("class.json");
+    let casm: CasmContractClass = read_json_file("compiled_class.json");
+    let deprecated_class: DeprecatedContractClass = read_json_file("deprecated_class.json");
+    let class_hash = ClassHash(felt!("0x1"));
+    let deprecated_class_hash = ClassHash(felt!("0x2"));
+    let address = contract_address!("0x100");
+    let deprecated_address = contract_address!("0x101");
+    let state_diffs = [
+        ThinStateDiff {
+            deployed_contracts: indexmap! { deprecated_address => deprecated_class_hash },
+            storage_diffs: indexmap! {
+                deprecated_address => indexmap! {
+                    storage_key!("0x10") => felt!("0x1"),
+                    storage_key!("0x11") => felt!("0x1"),
+                },
+            },
+            deprecated_declared_classes: vec![deprecated_class_hash],
+            ..Default::default()
+        },
+        ThinStateDiff {
+            deployed_contracts: indexmap! { address => class_hash },
+            storage_diffs: indexmap! {
+                deprecated_address => indexmap! {
+                    storage_key!("0x10") => felt!("0x2"),
+                    storage_key!("0x11") => felt!("0x0"),
+                },
+            },
+            declared_classes: indexmap! { class_hash => CompiledClassHash(felt!("0x3")) },
+            nonces: indexmap! { deprecated_address => Nonce(felt!("0x1")) },
+            ..Default::default()
+        },
+    ];
+
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    for (block_number, state_diff) in state_diffs.iter().enumerate() {
+        let block_number = BlockNumber(block_number.try_into().unwrap());
+        let (classes, deprecated_classes, casm) = if block_number == BlockNumber(0) {
+            (vec![], vec![(deprecated_class_hash, &deprecated_class)], None)
+        } else {
+            (vec![(class_hash, &class)], vec![], Some(&casm))
+        };
+        let mut txn = writer
+            .begin_rw_txn()
+            .unwrap()
+            .append_header(block_number, &header(block_number.0, StarknetVersion::LATEST))
+            .unwrap()
+            .append_body(block_number, BlockBody::default())
+            .unwrap()
+            .append_state_diff(block_number, state_diff.clone())
+            .unwrap()
+            .append_classes(block_number, &classes, &deprecated_classes)
+            .unwrap();
+        if let Some(casm) = casm {
+            txn = txn.append_casm(&class_hash, casm).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let snapshot = dump_state_snapshot(&reader, BlockNumber(1)).unwrap();
+    let expected_state = ThinStateDiff {
+        deployed_contracts: indexmap! {
+            deprecated_address => deprecated_class_hash,
+            address => class_hash,
+        },
+        // The storage values that were reset to zero are omitted.
+        storage_diffs: indexmap! {
+            deprecated_address => indexmap! { storage_key!("0x10") => felt!("0x2") },
+        },
+        declared_classes: indexmap! { class_hash => CompiledClassHash(felt!("0x3")) },
+        deprecated_declared_classes: vec![deprecated_class_hash],
+        nonces: indexmap! { deprecated_address => Nonce(felt!("0x1")) },
+    };
+    assert_eq!(snapshot.header, header(1, StarknetVersion::LATEST));
+    assert_eq!(snapshot.state_diff, expected_state);
+    assert_eq!(snapshot.classes, indexmap! { class_hash => class });
+    assert_eq!(
+        snapshot.deprecated_classes,
+        indexmap! { deprecated_class_hash => deprecated_class }
+    );
+    assert_eq!(snapshot.casms, indexmap! { class_hash => casm });
+    assert_matches!(
+        dump_state_snapshot(&reader, BlockNumber(2)),
+        Err(crate::archive::ArchiveError::BlocksNotSynced { .. })
+    );
+
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let snapshot_path = snapshot_dir.path().join("snapshot.zst");
+    write_state_snapshot(&snapshot, &snapshot_path).unwrap();
+    assert_eq!(read_state_snapshot(&snapshot_path).unwrap(), snapshot);
+
+    // A node that was seeded from the snapshot can dump the same snapshot.
+    let ((seeded_reader, mut seeded_writer), _seeded_temp_dir) = get_test_storage();
+    seeded_writer.begin_rw_txn().unwrap().seed_checkpoint(&snapshot).unwrap().commit().unwrap();
+    assert_eq!(dump_state_snapshot(&seeded_reader, BlockNumber(1)).unwrap(), snapshot);
+}
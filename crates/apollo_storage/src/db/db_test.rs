@@ -7,6 +7,7 @@ use crate::db::serialization::{NoVersionValueWrapper, ValueSerde, VersionZeroWra
 use crate::db::table_types::Table;
 use crate::db::{get_page_size, open_env, DbError, DbIter, DbReader, DbResult, DbWriter};
 use crate::test_utils::get_test_config;
+use crate::{open_storage, StorageScope};
 
 pub(crate) fn get_test_env() -> ((DbReader, DbWriter), TempDir) {
     let (config, temp_dir) = get_test_config(None);
@@ -18,6 +19,18 @@ fn open_env_scenario() {
     get_test_env();
 }
 
+// Fails if the storage has more tables than the environment allows.
+#[test]
+fn open_storage_scenario() {
+    for storage_scope in [StorageScope::FullArchive, StorageScope::StateOnly] {
+        let (config, _temp_dir) = get_test_config(Some(storage_scope));
+        let (reader, writer) = open_storage(config.clone()).expect("Failed to open storage.");
+        drop((reader, writer));
+        // Reopening creates no new tables.
+        open_storage(config).expect("Failed to reopen storage.");
+    }
+}
+
 #[test]
 fn open_env_with_enforce_file_exists() {
     let (config, _temp_dir) = get_test_config(None);
@@ -41,11 +41,13 @@ mod header_test;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{
     BlockHash,
+    BlockHashAndNumber,
     BlockHeader,
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockSignature,
     BlockTimestamp,
+    CommitCertificate,
     GasPrice,
     GasPricePerToken,
     StarknetVersion,
@@ -90,7 +92,33 @@ pub(crate) struct StorageBlockHeader {
     pub n_events: usize,
 }
 
-type BlockHashToNumberTable<'env> =
+impl From<&BlockHeader> for StorageBlockHeader {
+    fn from(block_header: &BlockHeader) -> Self {
+        Self {
+            block_hash: block_header.block_hash,
+            parent_hash: block_header.block_header_without_hash.parent_hash,
+            block_number: block_header.block_header_without_hash.block_number,
+            l1_gas_price: block_header.block_header_without_hash.l1_gas_price,
+            l1_data_gas_price: block_header.block_header_without_hash.l1_data_gas_price,
+            l2_gas_price: block_header.block_header_without_hash.l2_gas_price,
+            l2_gas_consumed: block_header.block_header_without_hash.l2_gas_consumed,
+            next_l2_gas_price: block_header.block_header_without_hash.next_l2_gas_price,
+            state_root: block_header.block_header_without_hash.state_root,
+            sequencer: block_header.block_header_without_hash.sequencer,
+            timestamp: block_header.block_header_without_hash.timestamp,
+            l1_da_mode: block_header.block_header_without_hash.l1_da_mode,
+            state_diff_commitment: block_header.state_diff_commitment,
+            transaction_commitment: block_header.transaction_commitment,
+            event_commitment: block_header.event_commitment,
+            receipt_commitment: block_header.receipt_commitment,
+            state_diff_length: block_header.state_diff_length,
+            n_transactions: block_header.n_transactions,
+            n_events: block_header.n_events,
+        }
+    }
+}
+
+pub(crate) type BlockHashToNumberTable<'env> =
     TableHandle<'env, BlockHash, NoVersionValueWrapper<BlockNumber>, SimpleTable>;
 
 /// Interface for reading data related to the block headers.
@@ -117,6 +145,12 @@ pub trait HeaderStorageReader {
         &self,
         block_number: BlockNumber,
     ) -> StorageResult<Option<BlockSignature>>;
+
+    /// Returns the commit certificate of the block with the given number.
+    fn get_commit_certificate(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<CommitCertificate>>;
 }
 
 /// Interface for writing data related to the block headers.
@@ -140,7 +174,7 @@ where
     ) -> StorageResult<Self>;
 
     /// Removes a block header and its signature (if exists) from the storage and returns the
-    /// removed data.
+    /// removed data. The block's commit certificate (if exists) is removed as well.
     fn revert_header(
         self,
         block_number: BlockNumber,
@@ -153,6 +187,14 @@ where
         block_number: BlockNumber,
         block_signature: &BlockSignature,
     ) -> StorageResult<Self>;
+
+    /// Appends the commit certificate of a block to the storage.
+    /// Written separately from the header since only blocks decided by consensus have one.
+    fn append_commit_certificate(
+        self,
+        block_number: BlockNumber,
+        commit_certificate: &CommitCertificate,
+    ) -> StorageResult<Self>;
 }
 
 impl<Mode: TransactionKind> HeaderStorageReader for StorageTxn<'_, Mode> {
@@ -221,13 +263,9 @@ impl<Mode: TransactionKind> HeaderStorageReader for StorageTxn<'_, Mode> {
         cursor.lower_bound(&next_block_number)?;
         let res = cursor.prev()?;
 
-        match res {
-            Some((_block_number, starknet_version)) => Ok(Some(starknet_version)),
-            None => unreachable!(
-                "Since block_number >= self.get_header_marker(), starknet_version_table should \
-                 have at least a single mapping."
-            ),
-        }
+        // A storage that was seeded from a checkpoint has no mapping for the blocks below its first
+        // stored header.
+        Ok(res.map(|(_block_number, starknet_version)| starknet_version))
     }
 
     fn get_block_signature(
@@ -238,6 +276,15 @@ impl<Mode: TransactionKind> HeaderStorageReader for StorageTxn<'_, Mode> {
         let block_signature = block_signatures_table.get(&self.txn, &block_number)?;
         Ok(block_signature)
     }
+
+    fn get_commit_certificate(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<CommitCertificate>> {
+        let commit_certificates_table = self.open_table(&self.tables.commit_certificates)?;
+        let commit_certificate = commit_certificates_table.get(&self.txn, &block_number)?;
+        Ok(commit_certificate)
+    }
 }
 
 impl HeaderStorageWriter for StorageTxn<'_, RW> {
@@ -252,27 +299,7 @@ impl HeaderStorageWriter for StorageTxn<'_, RW> {
 
         update_marker(&self.txn, &markers_table, block_number)?;
 
-        let storage_block_header = StorageBlockHeader {
-            block_hash: block_header.block_hash,
-            parent_hash: block_header.block_header_without_hash.parent_hash,
-            block_number: block_header.block_header_without_hash.block_number,
-            l1_gas_price: block_header.block_header_without_hash.l1_gas_price,
-            l1_data_gas_price: block_header.block_header_without_hash.l1_data_gas_price,
-            l2_gas_price: block_header.block_header_without_hash.l2_gas_price,
-            l2_gas_consumed: block_header.block_header_without_hash.l2_gas_consumed,
-            next_l2_gas_price: block_header.block_header_without_hash.next_l2_gas_price,
-            state_root: block_header.block_header_without_hash.state_root,
-            sequencer: block_header.block_header_without_hash.sequencer,
-            timestamp: block_header.block_header_without_hash.timestamp,
-            l1_da_mode: block_header.block_header_without_hash.l1_da_mode,
-            state_diff_commitment: block_header.state_diff_commitment,
-            transaction_commitment: block_header.transaction_commitment,
-            event_commitment: block_header.event_commitment,
-            receipt_commitment: block_header.receipt_commitment,
-            state_diff_length: block_header.state_diff_length,
-            n_transactions: block_header.n_transactions,
-            n_events: block_header.n_events,
-        };
+        let storage_block_header = StorageBlockHeader::from(block_header);
 
         headers_table.append(&self.txn, &block_number, &storage_block_header)?;
 
@@ -309,7 +336,7 @@ impl HeaderStorageWriter for StorageTxn<'_, RW> {
     }
 
     fn revert_header(
-        self,
+        mut self,
         block_number: BlockNumber,
     ) -> StorageResult<(Self, Option<BlockHeader>, Option<BlockSignature>)> {
         let markers_table = self.open_table(&self.tables.markers)?;
@@ -317,6 +344,7 @@ impl HeaderStorageWriter for StorageTxn<'_, RW> {
         let block_hash_to_number_table = self.open_table(&self.tables.block_hash_to_number)?;
         let starknet_version_table = self.open_table(&self.tables.starknet_version)?;
         let block_signatures_table = self.open_table(&self.tables.block_signatures)?;
+        let commit_certificates_table = self.open_table(&self.tables.commit_certificates)?;
 
         // Assert that header marker equals the reverted block number + 1
         let current_header_marker = self.get_header_marker()?;
@@ -361,6 +389,11 @@ impl HeaderStorageWriter for StorageTxn<'_, RW> {
         if reverted_block_signature.is_some() {
             block_signatures_table.delete(&self.txn, &block_number)?;
         }
+        if commit_certificates_table.get(&self.txn, &block_number)?.is_some() {
+            commit_certificates_table.delete(&self.txn, &block_number)?;
+        }
+        self.reverted_blocks
+            .push(BlockHashAndNumber { hash: reverted_header.block_hash, number: block_number });
 
         Ok((
             self,
@@ -409,9 +442,23 @@ impl HeaderStorageWriter for StorageTxn<'_, RW> {
         block_signatures_table.insert(&self.txn, &block_number, block_signature)?;
         Ok(self)
     }
+
+    fn append_commit_certificate(
+        self,
+        block_number: BlockNumber,
+        commit_certificate: &CommitCertificate,
+    ) -> StorageResult<Self> {
+        if block_number >= self.get_header_marker()? {
+            return Err(StorageError::CommitCertificateForNonExistingBlock { block_number });
+        }
+
+        let commit_certificates_table = self.open_table(&self.tables.commit_certificates)?;
+        commit_certificates_table.insert(&self.txn, &block_number, commit_certificate)?;
+        Ok(self)
+    }
 }
 
-fn update_hash_mapping<'env>(
+pub(crate) fn update_hash_mapping<'env>(
     txn: &DbTransaction<'env, RW>,
     block_hash_to_number_table: &'env BlockHashToNumberTable<'env>,
     block_header: &StorageBlockHeader,
@@ -2,16 +2,18 @@ use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 use starknet_api::block::{
     BlockHash,
+    BlockHashAndNumber,
     BlockHeader,
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockSignature,
+    CommitCertificate,
 };
 use starknet_api::felt;
 
 use crate::header::{HeaderStorageReader, HeaderStorageWriter, StarknetVersion};
 use crate::test_utils::get_test_storage;
-use crate::{StorageError, StorageWriter};
+use crate::{StorageError, StorageWriteNotification, StorageWriter};
 
 #[tokio::test]
 async fn append_header() {
@@ -128,6 +130,43 @@ async fn get_reverted_block_number_by_hash_returns_none() {
     );
 }
 
+#[tokio::test]
+async fn commits_notify_reverted_headers() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let mut write_notifications = reader.subscribe_to_writes();
+    append_2_headers(&mut writer);
+    assert_eq!(write_notifications.try_recv().unwrap(), StorageWriteNotification::default());
+
+    // Reverting a non-existing header isn't reported.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .revert_header(BlockNumber(2))
+        .unwrap()
+        .0
+        .revert_header(BlockNumber(1))
+        .unwrap()
+        .0
+        .revert_header(BlockNumber(0))
+        .unwrap()
+        .0
+        .commit()
+        .unwrap();
+    assert_eq!(
+        write_notifications.try_recv().unwrap(),
+        StorageWriteNotification {
+            reverted_blocks: vec![
+                BlockHashAndNumber { hash: BlockHash(felt!("0x1")), number: BlockNumber(1) },
+                BlockHashAndNumber { hash: BlockHash(felt!("0x0")), number: BlockNumber(0) },
+            ],
+        }
+    );
+
+    // Transactions that aren't committed aren't reported.
+    drop(writer.begin_rw_txn().unwrap());
+    assert!(write_notifications.try_recv().is_err());
+}
+
 fn append_2_headers(writer: &mut StorageWriter) {
     writer
         .begin_rw_txn()
@@ -294,6 +333,41 @@ fn get_reverted_block_signature_returns_none() {
     assert!(reader.begin_ro_txn().unwrap().get_block_signature(BlockNumber(0)).unwrap().is_none());
 }
 
+#[test]
+fn commit_certificate() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let certificate = CommitCertificate::default();
+    let result =
+        writer.begin_rw_txn().unwrap().append_commit_certificate(BlockNumber(0), &certificate);
+    assert_matches!(
+        result,
+        Err(StorageError::CommitCertificateForNonExistingBlock { block_number: BlockNumber(0) })
+    );
+
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &BlockHeader::default())
+        .unwrap()
+        .append_commit_certificate(BlockNumber(0), &certificate)
+        .unwrap()
+        .commit()
+        .unwrap();
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_commit_certificate(BlockNumber(0)).unwrap(),
+        Some(certificate)
+    );
+
+    let (txn, _, _) = writer.begin_rw_txn().unwrap().revert_header(BlockNumber(0)).unwrap();
+    txn.commit().unwrap();
+    assert!(reader
+        .begin_ro_txn()
+        .unwrap()
+        .get_commit_certificate(BlockNumber(0))
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn revert_overflowing_block_number() {
     let ((_, mut writer), _temp_dir) = get_test_storage();
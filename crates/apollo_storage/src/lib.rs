@@ -75,8 +75,10 @@
 //! [`Starknet`]: https://starknet.io/
 //! [`libmdbx`]: https://docs.rs/libmdbx/latest/libmdbx/
 
+pub mod archive;
 pub mod base_layer;
 pub mod body;
+pub mod checkpoint;
 pub mod class;
 pub mod class_hash;
 pub mod class_manager;
@@ -92,6 +94,7 @@ pub mod header;
 pub mod mmap_file;
 mod serialization;
 pub mod state;
+pub mod state_tries;
 mod version;
 
 mod deprecated;
@@ -125,12 +128,20 @@ use mmap_file::{
     Writer,
 };
 use serde::{Deserialize, Serialize};
-use starknet_api::block::{BlockHash, BlockNumber, BlockSignature, StarknetVersion};
-use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::block::{
+    BlockHash,
+    BlockHashAndNumber,
+    BlockNumber,
+    BlockSignature,
+    CommitCertificate,
+    StarknetVersion,
+};
+use starknet_api::core::{ChainId, ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::{SierraContractClass, StateNumber, StorageKey, ThinStateDiff};
 use starknet_api::transaction::{Transaction, TransactionHash, TransactionOutput};
 use starknet_types_core::felt::Felt;
+use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 use validator::Validate;
 use version::{StorageVersionError, Version};
@@ -153,13 +164,14 @@ use crate::db::{
 use crate::header::StorageBlockHeader;
 use crate::mmap_file::MMapFileStats;
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::{StateTrieNode, StateTriesChanges, StateTriesRoots};
 use crate::version::{VersionStorageReader, VersionStorageWriter};
 
 // For more details on the storage version, see the module documentation.
 /// The current version of the storage state code.
-pub const STORAGE_VERSION_STATE: Version = Version { major: 6, minor: 0 };
+pub const STORAGE_VERSION_STATE: Version = Version { major: 6, minor: 1 };
 /// The current version of the storage blocks code.
-pub const STORAGE_VERSION_BLOCKS: Version = Version { major: 6, minor: 0 };
+pub const STORAGE_VERSION_BLOCKS: Version = Version { major: 6, minor: 2 };
 
 /// Opens a storage and returns a [`StorageReader`] and a [`StorageWriter`].
 pub fn open_storage(
@@ -178,6 +190,8 @@ pub fn open_storage(
         block_hash_to_number: db_writer.create_simple_table("block_hash_to_number")?,
         block_signatures: db_writer.create_simple_table("block_signatures")?,
         casms: db_writer.create_simple_table("casms")?,
+        checkpoint_state: db_writer.create_simple_table("checkpoint_state")?,
+        commit_certificates: db_writer.create_simple_table("commit_certificates")?,
         contract_storage: db_writer.create_common_prefix_table("contract_storage")?,
         declared_classes: db_writer.create_simple_table("declared_classes")?,
         declared_classes_block: db_writer.create_simple_table("declared_classes_block")?,
@@ -191,7 +205,11 @@ pub fn open_storage(
         markers: db_writer.create_simple_table("markers")?,
         nonces: db_writer.create_common_prefix_table("nonces")?,
         file_offsets: db_writer.create_simple_table("file_offsets")?,
+        sender_transactions: db_writer.create_common_prefix_table("sender_transactions")?,
         state_diffs: db_writer.create_simple_table("state_diffs")?,
+        state_trie_nodes: db_writer.create_simple_table("state_trie_nodes")?,
+        state_tries_changes: db_writer.create_simple_table("state_tries_changes")?,
+        state_tries_roots: db_writer.create_simple_table("state_tries_roots")?,
         transaction_hash_to_idx: db_writer.create_simple_table("transaction_hash_to_idx")?,
         transaction_metadata: db_writer.create_simple_table("transaction_metadata")?,
 
@@ -211,13 +229,24 @@ pub fn open_storage(
         &tables.file_offsets,
     )?;
 
+    let (write_notifications_sender, _) = broadcast::channel(WRITE_NOTIFICATIONS_CAPACITY);
     let reader = StorageReader {
+        chain_id: storage_config.db_config.chain_id.clone(),
         db_reader,
         tables: tables.clone(),
         scope: storage_config.scope,
+        index_transactions_by_sender: storage_config.index_transactions_by_sender,
         file_readers,
+        write_notifications_sender: write_notifications_sender.clone(),
+    };
+    let writer = StorageWriter {
+        db_writer,
+        tables,
+        scope: storage_config.scope,
+        index_transactions_by_sender: storage_config.index_transactions_by_sender,
+        file_writers,
+        write_notifications_sender,
     };
-    let writer = StorageWriter { db_writer, tables, scope: storage_config.scope, file_writers };
 
     let writer = set_version_if_needed(reader.clone(), writer)?;
     verify_storage_version(reader.clone())?;
@@ -416,16 +445,35 @@ pub enum StorageScope {
     StateOnly,
 }
 
+/// The number of write notifications a subscriber can fall behind before it misses notifications.
+const WRITE_NOTIFICATIONS_CAPACITY: usize = 1000;
+
+/// Sent to the subscribers of [`StorageReader::subscribe_to_writes`] whenever a RW transaction is
+/// committed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageWriteNotification {
+    /// The blocks whose headers were reverted in the transaction, from the newest to the oldest.
+    pub reverted_blocks: Vec<BlockHashAndNumber>,
+}
+
 /// A struct for starting RO transactions ([`StorageTxn`]) to the storage.
 #[derive(Clone)]
 pub struct StorageReader {
+    chain_id: ChainId,
     db_reader: DbReader,
     file_readers: FileHandlers<RO>,
     tables: Arc<Tables>,
     scope: StorageScope,
+    index_transactions_by_sender: bool,
+    write_notifications_sender: broadcast::Sender<StorageWriteNotification>,
 }
 
 impl StorageReader {
+    /// The chain whose data the storage holds.
+    pub fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
     /// Takes a snapshot of the current state of the storage and returns a [`StorageTxn`] for
     /// reading data from the storage.
     pub fn begin_ro_txn(&self) -> StorageResult<StorageTxn<'_, RO>> {
@@ -434,9 +482,18 @@ impl StorageReader {
             file_handlers: self.file_readers.clone(),
             tables: self.tables.clone(),
             scope: self.scope,
+            index_transactions_by_sender: self.index_transactions_by_sender,
+            reverted_blocks: Vec::new(),
+            write_notifications_sender: self.write_notifications_sender.clone(),
         })
     }
 
+    /// Returns a receiver that is notified of every write to the storage committed from now on,
+    /// allowing to follow the storage without polling it.
+    pub fn subscribe_to_writes(&self) -> broadcast::Receiver<StorageWriteNotification> {
+        self.write_notifications_sender.subscribe()
+    }
+
     /// Returns metadata about the tables in the storage.
     pub fn db_tables_stats(&self) -> StorageResult<DbStats> {
         let mut tables_stats = BTreeMap::new();
@@ -455,6 +512,11 @@ impl StorageReader {
     pub fn get_scope(&self) -> StorageScope {
         self.scope
     }
+
+    /// Returns whether the storage indexes transactions by their sender address and nonce.
+    pub fn indexes_transactions_by_sender(&self) -> bool {
+        self.index_transactions_by_sender
+    }
 }
 
 /// A struct for starting RW transactions ([`StorageTxn`]) to the storage.
@@ -465,6 +527,8 @@ pub struct StorageWriter {
     file_writers: FileHandlers<RW>,
     tables: Arc<Tables>,
     scope: StorageScope,
+    index_transactions_by_sender: bool,
+    write_notifications_sender: broadcast::Sender<StorageWriteNotification>,
 }
 
 impl StorageWriter {
@@ -476,6 +540,9 @@ impl StorageWriter {
             file_handlers: self.file_writers.clone(),
             tables: self.tables.clone(),
             scope: self.scope,
+            index_transactions_by_sender: self.index_transactions_by_sender,
+            reverted_blocks: Vec::new(),
+            write_notifications_sender: self.write_notifications_sender.clone(),
         })
     }
 }
@@ -487,14 +554,24 @@ pub struct StorageTxn<'env, Mode: TransactionKind> {
     file_handlers: FileHandlers<Mode>,
     tables: Arc<Tables>,
     scope: StorageScope,
+    index_transactions_by_sender: bool,
+    // The blocks whose headers were reverted in the transaction, reported once it's committed.
+    reverted_blocks: Vec<BlockHashAndNumber>,
+    write_notifications_sender: broadcast::Sender<StorageWriteNotification>,
 }
 
 impl StorageTxn<'_, RW> {
-    /// Commits the changes made in the transaction to the storage.
+    /// Commits the changes made in the transaction to the storage and notifies the subscribers
+    /// to the writes of the storage.
     #[latency_histogram("storage_commit_latency_seconds", false)]
     pub fn commit(self) -> StorageResult<()> {
         self.file_handlers.flush();
-        Ok(self.txn.commit()?)
+        self.txn.commit()?;
+        // Sending fails only when there are no subscribers.
+        let _ = self
+            .write_notifications_sender
+            .send(StorageWriteNotification { reverted_blocks: self.reverted_blocks });
+        Ok(())
     }
 }
 
@@ -506,6 +583,7 @@ impl<Mode: TransactionKind> StorageTxn<'_, Mode> {
         if self.scope == StorageScope::StateOnly {
             let unused_tables = [
                 self.tables.events.name,
+                self.tables.sender_transactions.name,
                 self.tables.transaction_hash_to_idx.name,
                 self.tables.transaction_metadata.name,
             ];
@@ -530,6 +608,10 @@ struct_field_names! {
         block_hash_to_number: TableIdentifier<BlockHash, NoVersionValueWrapper<BlockNumber>, SimpleTable>,
         block_signatures: TableIdentifier<BlockNumber, VersionZeroWrapper<BlockSignature>, SimpleTable>,
         casms: TableIdentifier<ClassHash, VersionZeroWrapper<LocationInFile>, SimpleTable>,
+        // The whole state after the checkpoint block, populated only when the storage was seeded from a checkpoint.
+        checkpoint_state: TableIdentifier<BlockNumber, VersionZeroWrapper<LocationInFile>, SimpleTable>,
+        // Populated only for blocks decided by consensus whose certificate reached this node.
+        commit_certificates: TableIdentifier<BlockNumber, VersionZeroWrapper<CommitCertificate>, SimpleTable>,
         // Empirically, defining the common prefix as (ContractAddress, StorageKey) is better space-wise than defining the
         // common prefix only as ContractAddress.
         contract_storage: TableIdentifier<((ContractAddress, StorageKey), BlockNumber), NoVersionValueWrapper<Felt>, CommonPrefix>,
@@ -544,7 +626,13 @@ struct_field_names! {
         markers: TableIdentifier<MarkerKind, VersionZeroWrapper<BlockNumber>, SimpleTable>,
         nonces: TableIdentifier<(ContractAddress, BlockNumber), VersionZeroWrapper<Nonce>, CommonPrefix>,
         file_offsets: TableIdentifier<OffsetKind, NoVersionValueWrapper<usize>, SimpleTable>,
+        // Populated only when the storage is configured to index transactions by sender.
+        sender_transactions: TableIdentifier<(ContractAddress, (Nonce, TransactionIndex)), NoVersionValueWrapper<NoValue>, CommonPrefix>,
         state_diffs: TableIdentifier<BlockNumber, VersionZeroWrapper<LocationInFile>, SimpleTable>,
+        // The state tries of a window of recent blocks, populated only when the sync maintains them.
+        state_trie_nodes: TableIdentifier<Vec<u8>, NoVersionValueWrapper<StateTrieNode>, SimpleTable>,
+        state_tries_changes: TableIdentifier<BlockNumber, NoVersionValueWrapper<StateTriesChanges>, SimpleTable>,
+        state_tries_roots: TableIdentifier<BlockNumber, NoVersionValueWrapper<StateTriesRoots>, SimpleTable>,
         transaction_hash_to_idx: TableIdentifier<TransactionHash, NoVersionValueWrapper<TransactionIndex>, SimpleTable>,
         // TODO(dvir): consider not saving transaction hash and calculating it from the transaction on demand.
         transaction_metadata: TableIdentifier<TransactionIndex, VersionZeroWrapper<TransactionMetadata>, SimpleTable>,
@@ -625,6 +713,25 @@ pub enum StorageError {
          {block_number}."
     )]
     BlockSignatureForNonExistingBlock { block_number: BlockNumber, block_signature: BlockSignature },
+    #[error("Attempt to write a commit certificate of non-existing block {block_number}.")]
+    CommitCertificateForNonExistingBlock { block_number: BlockNumber },
+    #[error("The storage is not configured to index transactions by sender.")]
+    SenderIndexDisabled,
+    #[error("Can't seed a checkpoint into a storage that already has blocks.")]
+    CheckpointInNonEmptyStorage,
+    #[error(
+        "Can't prepend the header of block {block_number}, the first stored header is of block \
+         {first_header_block_number:?}."
+    )]
+    PrependedHeaderNotConsecutive {
+        block_number: BlockNumber,
+        first_header_block_number: Option<BlockNumber>,
+    },
+    #[error(
+        "Can't read the state at {state_number:?}, the storage holds only the states from the \
+         checkpoint {checkpoint} onwards."
+    )]
+    StateBeforeCheckpoint { state_number: StateNumber, checkpoint: BlockNumber },
 }
 
 /// A type alias that maps to std::result::Result<T, StorageError>.
@@ -639,16 +746,26 @@ pub struct StorageConfig {
     #[validate]
     pub mmap_file_config: MmapFileConfig,
     pub scope: StorageScope,
+    pub index_transactions_by_sender: bool,
 }
 
 impl SerializeConfig for StorageConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
-        let mut dumped_config = BTreeMap::from_iter([ser_param(
-            "scope",
-            &self.scope,
-            "The categories of data saved in storage.",
-            ParamPrivacyInput::Public,
-        )]);
+        let mut dumped_config = BTreeMap::from_iter([
+            ser_param(
+                "scope",
+                &self.scope,
+                "The categories of data saved in storage.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "index_transactions_by_sender",
+                &self.index_transactions_by_sender,
+                "If true, index the transactions of each block by their sender address and nonce. \
+                 Only blocks written while this is enabled are indexed.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
         dumped_config
             .extend(prepend_sub_config_name(self.mmap_file_config.dump(), "mmap_file_config"));
         dumped_config.extend(prepend_sub_config_name(self.db_config.dump(), "db_config"));
@@ -671,6 +788,8 @@ pub struct DbStats {
 // - CompiledClass <= Class <= State <= Header
 // - Body <= Header
 // - BaseLayerBlock <= Header
+// - Checkpoint < Header, Body, State, Class and CompiledClass (if the storage has a checkpoint)
+// - StateTries <= State
 // Event is currently unsupported.
 pub(crate) enum MarkerKind {
     Header,
@@ -684,6 +803,11 @@ pub(crate) enum MarkerKind {
     /// Marks the block beyond the last block that its classes can't be compiled with the current
     /// compiler version used in the class manager. Determined by starknet version.
     CompilerBackwardCompatibility,
+    /// The block the storage was seeded at, if it was seeded from a trusted checkpoint. Nothing
+    /// but the headers (which are backfilled) is stored for the blocks below it.
+    Checkpoint,
+    /// Marks the first block whose state tries aren't stored, see [`state_tries`].
+    StateTries,
 }
 
 pub(crate) type MarkersTable<'env> =
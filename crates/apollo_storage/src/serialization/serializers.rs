@@ -19,13 +19,19 @@ use num_bigint::BigUint;
 use parity_scale_codec::{Decode, Encode};
 use primitive_types::H160;
 use starknet_api::block::{
+    BlockBody,
     BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
     BlockNumber,
     BlockSignature,
     BlockStatus,
     BlockTimestamp,
+    CommitCertificate,
+    CommitteeMember,
     GasPrice,
     GasPricePerToken,
+    PrecommitSignature,
     StarknetVersion,
 };
 use starknet_api::contract_class::EntryPointType;
@@ -44,7 +50,7 @@ use starknet_api::core::{
     StateDiffCommitment,
     TransactionCommitment,
 };
-use starknet_api::crypto::utils::Signature;
+use starknet_api::crypto::utils::{PublicKey, RawSignature, Signature};
 use starknet_api::data_availability::{DataAvailabilityMode, L1DataAvailabilityMode};
 use starknet_api::deprecated_contract_class::{
     ConstructorType,
@@ -142,6 +148,7 @@ use crate::mmap_file::LocationInFile;
 #[cfg(test)]
 use crate::serialization::serializers_test::{create_storage_serde_test, StorageSerdeTest};
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::{StateTrieNode, StateTriesChanges, StateTriesRoots};
 use crate::version::Version;
 use crate::{MarkerKind, OffsetKind, TransactionMetadata};
 
@@ -155,7 +162,37 @@ auto_storage_serde! {
         pub l2_gas: ResourceBounds,
         pub l1_data_gas: ResourceBounds,
     }
+    pub struct BlockBody {
+        pub transactions: Vec<Transaction>,
+        pub transaction_outputs: Vec<TransactionOutput>,
+        pub transaction_hashes: Vec<TransactionHash>,
+    }
     pub struct BlockHash(pub StarkHash);
+    pub struct BlockHeader {
+        pub block_hash: BlockHash,
+        pub block_header_without_hash: BlockHeaderWithoutHash,
+        pub state_diff_commitment: Option<StateDiffCommitment>,
+        pub transaction_commitment: Option<TransactionCommitment>,
+        pub event_commitment: Option<EventCommitment>,
+        pub receipt_commitment: Option<ReceiptCommitment>,
+        pub state_diff_length: Option<usize>,
+        pub n_transactions: usize,
+        pub n_events: usize,
+    }
+    pub struct BlockHeaderWithoutHash {
+        pub parent_hash: BlockHash,
+        pub block_number: BlockNumber,
+        pub l1_gas_price: GasPricePerToken,
+        pub l1_data_gas_price: GasPricePerToken,
+        pub l2_gas_price: GasPricePerToken,
+        pub l2_gas_consumed: GasAmount,
+        pub next_l2_gas_price: GasPrice,
+        pub state_root: GlobalRoot,
+        pub sequencer: SequencerContractAddress,
+        pub timestamp: BlockTimestamp,
+        pub l1_da_mode: L1DataAvailabilityMode,
+        pub starknet_version: StarknetVersion,
+    }
     pub struct StorageBlockHeader {
         pub block_hash: BlockHash,
         pub parent_hash: BlockHash,
@@ -186,6 +223,18 @@ auto_storage_serde! {
     }
     pub struct BlockTimestamp(pub u64);
     pub struct Calldata(pub Arc<Vec<Felt>>);
+    pub struct CommitCertificate {
+        pub block_number: BlockNumber,
+        pub round: u32,
+        pub block_hash: BlockHash,
+        pub committee: Vec<CommitteeMember>,
+        pub precommits: Vec<PrecommitSignature>,
+    }
+    pub struct CommitteeMember {
+        pub address: ContractAddress,
+        pub weight: u128,
+        pub public_key: PublicKey,
+    }
     pub struct CompiledClassHash(pub StarkHash);
     pub struct ClassHash(pub StarkHash);
     pub struct ContractAddressSalt(pub StarkHash);
@@ -329,6 +378,8 @@ auto_storage_serde! {
         BaseLayerBlock = 6,
         ClassManagerBlock = 7,
         CompilerBackwardCompatibility = 8,
+        Checkpoint = 9,
+        StateTries = 10,
     }
     pub struct MessageToL1 {
         pub to_address: EthAddress,
@@ -354,6 +405,10 @@ auto_storage_serde! {
     }
     pub struct PaymasterData(pub Vec<Felt>);
     pub struct PoseidonHash(pub Felt);
+    pub struct PrecommitSignature {
+        pub voter: ContractAddress,
+        pub signature: RawSignature,
+    }
     pub struct Program {
         pub attributes: serde_json::Value,
         pub builtins: serde_json::Value,
@@ -366,6 +421,8 @@ auto_storage_serde! {
         pub prime: serde_json::Value,
         pub reference_manager: serde_json::Value,
     }
+    pub struct PublicKey(pub Felt);
+    pub struct RawSignature(pub Vec<Felt>);
     pub struct ReceiptCommitment(pub StarkHash);
     pub enum Resource {
         L1Gas = 0,
@@ -422,6 +479,18 @@ auto_storage_serde! {
         V0_14_1 = 23,
     }
     pub struct StateDiffCommitment(pub PoseidonHash);
+    pub struct StateTrieNode {
+        pub value: Vec<u8>,
+        pub n_occurrences: u64,
+    }
+    pub struct StateTriesChanges {
+        pub added_nodes: Vec<Vec<u8>>,
+        pub replaced_nodes: Vec<Vec<u8>>,
+    }
+    pub struct StateTriesRoots {
+        pub contracts_trie_root_hash: Felt,
+        pub classes_trie_root_hash: Felt,
+    }
     pub struct Tip(pub u64);
     pub struct TransactionCommitment(pub StarkHash);
     pub struct TypedParameter {
@@ -516,9 +585,11 @@ auto_storage_serde! {
     (ContractAddress, BlockHash);
     (ContractAddress, BlockNumber);
     (ContractAddress, Nonce);
+    (ContractAddress, (Nonce, TransactionIndex));
     (ContractAddress, StorageKey);
     (ContractAddress, TransactionIndex);
     ((ContractAddress, StorageKey), BlockNumber);
+    (Nonce, TransactionIndex);
     (usize, Vec<Hint>);
     (usize, Vec<String>);
 }
@@ -138,7 +138,8 @@ pub trait StateStorageReader<Mode: TransactionKind> {
     fn get_state_marker(&self) -> StorageResult<BlockNumber>;
     /// Returns the state diff at a given block number.
     fn get_state_diff(&self, block_number: BlockNumber) -> StorageResult<Option<ThinStateDiff>>;
-    /// Returns a state reader.
+    /// Returns a state reader. In a storage that was seeded from a checkpoint, the states before
+    /// the checkpoint can't be read.
     fn get_state_reader(&self) -> StorageResult<StateReader<'_, Mode>>;
 }
 
@@ -206,6 +207,7 @@ pub struct StateReader<'env, Mode: TransactionKind> {
     compiled_class_hash_table: CompiledClassHashTable<'env>,
     storage_table: ContractStorageTable<'env>,
     markers_table: MarkersTable<'env>,
+    checkpoint: Option<BlockNumber>,
     file_handlers: &'env FileHandlers<Mode>,
 }
 
@@ -232,6 +234,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         let nonces_table = txn.txn.open_table(&txn.tables.nonces)?;
         let storage_table = txn.txn.open_table(&txn.tables.contract_storage)?;
         let markers_table = txn.txn.open_table(&txn.tables.markers)?;
+        let checkpoint = markers_table.get(&txn.txn, &MarkerKind::Checkpoint)?;
         Ok(StateReader {
             txn: &txn.txn,
             compiled_class_hash_table,
@@ -243,10 +246,22 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
             nonces_table,
             storage_table,
             markers_table,
+            checkpoint,
             file_handlers: &txn.file_handlers,
         })
     }
 
+    // A storage that was seeded from a checkpoint holds only the states from the checkpoint
+    // onwards.
+    fn verify_state_is_stored(&self, state_number: StateNumber) -> StorageResult<()> {
+        match self.checkpoint {
+            Some(checkpoint) if state_number.is_before(checkpoint) => {
+                Err(StorageError::StateBeforeCheckpoint { state_number, checkpoint })
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the class hash at a given state number.
     /// If class hash is not found, returns `None`.
     ///
@@ -264,6 +279,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         // TODO(dvir): create an attribute instead of this.
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetClassHashAt(state_number, *address));
+        self.verify_state_is_stored(state_number)?;
 
         let first_irrelevant_block: BlockNumber = state_number.block_after();
         let db_key = (*address, first_irrelevant_block);
@@ -294,6 +310,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
     ) -> StorageResult<Option<Nonce>> {
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetNonceAt(state_number, *address));
+        self.verify_state_is_stored(state_number)?;
 
         // State diff updates are indexed by the block_number at which they occurred.
         let block_number: BlockNumber = state_number.block_after();
@@ -314,6 +331,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         state_number: StateNumber,
         class_hash: &ClassHash,
     ) -> StorageResult<Option<CompiledClassHash>> {
+        self.verify_state_is_stored(state_number)?;
         // State diff updates are indexed by the block_number at which they occurred.
         let block_number: BlockNumber = state_number.block_after();
         get_compiled_class_hash_at(
@@ -342,6 +360,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
     ) -> StorageResult<Felt> {
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetStorageAt(state_number, *address, *key));
+        self.verify_state_is_stored(state_number)?;
 
         // The updates to the storage key are indexed by the block_number at which they occurred.
         let first_irrelevant_block: BlockNumber = state_number.block_after();
@@ -384,6 +403,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         state_number: StateNumber,
         class_hash: &ClassHash,
     ) -> StorageResult<Option<SierraContractClass>> {
+        self.verify_state_is_stored(state_number)?;
         let Some(block_number) = self.declared_classes_block_table.get(self.txn, class_hash)?
         else {
             return Ok(None);
@@ -452,6 +472,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         state_number: StateNumber,
         class_hash: &ClassHash,
     ) -> StorageResult<Option<DeprecatedContractClass>> {
+        self.verify_state_is_stored(state_number)?;
         let Some(value) = self.deprecated_declared_classes_table.get(self.txn, class_hash)? else {
             return Ok(None);
         };
@@ -475,53 +496,8 @@ impl StateStorageWriter for StorageTxn<'_, RW> {
         let file_offset_table = self.txn.open_table(&self.tables.file_offsets)?;
         let markers_table = self.open_table(&self.tables.markers)?;
         let state_diffs_table = self.open_table(&self.tables.state_diffs)?;
-        let nonces_table = self.open_table(&self.tables.nonces)?;
-        let deployed_contracts_table = self.open_table(&self.tables.deployed_contracts)?;
-        let storage_table = self.open_table(&self.tables.contract_storage)?;
-        let declared_classes_block_table = self.open_table(&self.tables.declared_classes_block)?;
-        let deprecated_declared_classes_block_table =
-            self.open_table(&self.tables.deprecated_declared_classes_block)?;
-        let compiled_class_hash_table = self.open_table(&self.tables.compiled_class_hash)?;
-
-        // Write state.
-        write_deployed_contracts(
-            &thin_state_diff.deployed_contracts,
-            &self.txn,
-            block_number,
-            &deployed_contracts_table,
-            &nonces_table,
-        )?;
-        write_storage_diffs(
-            &thin_state_diff.storage_diffs,
-            &self.txn,
-            block_number,
-            &storage_table,
-        )?;
-        // Must be called after write_deployed_contracts since the nonces are updated there.
-        write_nonces(&thin_state_diff.nonces, &self.txn, block_number, &nonces_table)?;
 
-        for (class_hash, _) in &thin_state_diff.declared_classes {
-            declared_classes_block_table.insert(&self.txn, class_hash, &block_number)?;
-        }
-
-        write_compiled_class_hashes(
-            &thin_state_diff.declared_classes,
-            &self.txn,
-            block_number,
-            &compiled_class_hash_table,
-        )?;
-
-        for class_hash in thin_state_diff.deprecated_declared_classes.iter() {
-            // Cairo0 classes can be declared in different blocks. The first block to declare the
-            // class is recorded here.
-            if deprecated_declared_classes_block_table.get(&self.txn, class_hash)?.is_none() {
-                deprecated_declared_classes_block_table.insert(
-                    &self.txn,
-                    class_hash,
-                    &block_number,
-                )?;
-            }
-        }
+        self.write_state(block_number, &thin_state_diff)?;
 
         // Write state diff.
         let location = self.file_handlers.append_state_diff(&thin_state_diff);
@@ -634,6 +610,7 @@ impl StateStorageWriter for StorageTxn<'_, RW> {
             &compiled_class_hash_table,
         )?;
         state_diffs_table.delete(&self.txn, &block_number)?;
+        self.revert_state_tries(block_number)?;
 
         Ok((
             self,
@@ -649,6 +626,64 @@ impl StateStorageWriter for StorageTxn<'_, RW> {
     }
 }
 
+impl StorageTxn<'_, RW> {
+    // Writes the state changes of the block into the state tables, without storing the state diff
+    // itself.
+    pub(crate) fn write_state(
+        &self,
+        block_number: BlockNumber,
+        thin_state_diff: &ThinStateDiff,
+    ) -> StorageResult<()> {
+        let nonces_table = self.open_table(&self.tables.nonces)?;
+        let deployed_contracts_table = self.open_table(&self.tables.deployed_contracts)?;
+        let storage_table = self.open_table(&self.tables.contract_storage)?;
+        let declared_classes_block_table = self.open_table(&self.tables.declared_classes_block)?;
+        let deprecated_declared_classes_block_table =
+            self.open_table(&self.tables.deprecated_declared_classes_block)?;
+        let compiled_class_hash_table = self.open_table(&self.tables.compiled_class_hash)?;
+
+        write_deployed_contracts(
+            &thin_state_diff.deployed_contracts,
+            &self.txn,
+            block_number,
+            &deployed_contracts_table,
+            &nonces_table,
+        )?;
+        write_storage_diffs(
+            &thin_state_diff.storage_diffs,
+            &self.txn,
+            block_number,
+            &storage_table,
+        )?;
+        // Must be called after write_deployed_contracts since the nonces are updated there.
+        write_nonces(&thin_state_diff.nonces, &self.txn, block_number, &nonces_table)?;
+
+        for (class_hash, _) in &thin_state_diff.declared_classes {
+            declared_classes_block_table.insert(&self.txn, class_hash, &block_number)?;
+        }
+
+        write_compiled_class_hashes(
+            &thin_state_diff.declared_classes,
+            &self.txn,
+            block_number,
+            &compiled_class_hash_table,
+        )?;
+
+        for class_hash in thin_state_diff.deprecated_declared_classes.iter() {
+            // Cairo0 classes can be declared in different blocks. The first block to declare the
+            // class is recorded here.
+            if deprecated_declared_classes_block_table.get(&self.txn, class_hash)?.is_none() {
+                deprecated_declared_classes_block_table.insert(
+                    &self.txn,
+                    class_hash,
+                    &block_number,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[latency_histogram("storage_update_marker_to_next_block_latency_seconds", true)]
 fn update_marker_to_next_block<'env>(
     txn: &DbTransaction<'env, RW>,
@@ -0,0 +1,250 @@
+//! Interface for handling the state tries of the recent blocks: the Patricia-Merkle tries of the
+//! contracts and of the classes, whose roots make up the state root of each block.
+//!
+//! The tries are stored for a window of the most recent blocks. Each node is stored once, along
+//! with the number of its occurrences in the tries. When a block is appended, the occurrences of
+//! the nodes it adds are counted immediately, while the occurrences of the nodes it replaces are
+//! discounted only when the block before it leaves the window. This way the tries of every block
+//! in the window are complete, and a node is deleted once it's in none of them.
+//!
+//! Import [`StateTriesStorageReader`] and [`StateTriesStorageWriter`] to read and write data
+//! related to the state tries using a [`StorageTxn`]. The nodes of the tries are read through the
+//! [`ReadStorage`] implementation of the transaction.
+
+#[cfg(test)]
+#[path = "state_tries_test.rs"]
+mod state_tries_test;
+
+use starknet_api::block::BlockNumber;
+use starknet_patricia_storage::errors::StorageError as TrieStorageError;
+use starknet_patricia_storage::storage_trait::{DbKey, DbValue, ReadStorage};
+use starknet_types_core::felt::Felt;
+use tracing::warn;
+
+use crate::checkpoint::CheckpointStorageReader;
+use crate::db::serialization::NoVersionValueWrapper;
+use crate::db::table_types::{DbCursorTrait, SimpleTable, Table};
+use crate::db::{DbTransaction, TableHandle, TransactionKind, RW};
+use crate::{MarkerKind, StorageError, StorageResult, StorageTxn};
+
+/// The roots of the contracts trie and of the classes trie after a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StateTriesRoots {
+    /// The root of the trie of the contracts' states.
+    pub contracts_trie_root_hash: Felt,
+    /// The root of the trie of the classes' compiled class hashes.
+    pub classes_trie_root_hash: Felt,
+}
+
+// A node of the state tries, and the number of its occurrences in the tries of the stored blocks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StateTrieNode {
+    pub value: Vec<u8>,
+    pub n_occurrences: u64,
+}
+
+// The keys of the nodes that a block added to the tries and of the nodes it replaced, once for
+// each of their occurrences.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StateTriesChanges {
+    pub added_nodes: Vec<Vec<u8>>,
+    pub replaced_nodes: Vec<Vec<u8>>,
+}
+
+type StateTrieNodesTable<'env> =
+    TableHandle<'env, Vec<u8>, NoVersionValueWrapper<StateTrieNode>, SimpleTable>;
+
+/// Interface for reading data related to the state tries.
+pub trait StateTriesStorageReader {
+    /// The first block whose tries aren't stored. The tries are built from the first block whose
+    /// state is stored: the checkpoint if the storage was seeded from one, or the genesis block.
+    fn get_state_tries_marker(&self) -> StorageResult<BlockNumber>;
+
+    /// Returns the roots of the tries after the given block, if the block is in the window of
+    /// blocks whose tries are stored.
+    fn get_state_tries_roots(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<StateTriesRoots>>;
+}
+
+/// Interface for writing data related to the state tries.
+pub trait StateTriesStorageWriter
+where
+    Self: Sized,
+{
+    /// Stores the tries after the given block, which are the tries after the previous block with
+    /// the given nodes replaced by the new nodes. Both are given once for each of their
+    /// occurrences in the tries. Then, only the tries of the last `n_retained_blocks` blocks are
+    /// kept.
+    // To enforce that no commit happen after a failure, we consume and return Self on success.
+    fn append_state_tries(
+        self,
+        block_number: BlockNumber,
+        roots: &StateTriesRoots,
+        new_nodes: Vec<(DbKey, DbValue)>,
+        replaced_nodes: Vec<DbKey>,
+        n_retained_blocks: u64,
+    ) -> StorageResult<Self>;
+}
+
+impl<Mode: TransactionKind> StateTriesStorageReader for StorageTxn<'_, Mode> {
+    fn get_state_tries_marker(&self) -> StorageResult<BlockNumber> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        match markers_table.get(&self.txn, &MarkerKind::StateTries)? {
+            Some(marker) => Ok(marker),
+            None => Ok(self.get_checkpoint()?.unwrap_or_default()),
+        }
+    }
+
+    fn get_state_tries_roots(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<StateTriesRoots>> {
+        let state_tries_roots_table = self.open_table(&self.tables.state_tries_roots)?;
+        Ok(state_tries_roots_table.get(&self.txn, &block_number)?)
+    }
+}
+
+impl<Mode: TransactionKind> ReadStorage for StorageTxn<'_, Mode> {
+    fn read(&self, key: &DbKey) -> Result<Option<DbValue>, TrieStorageError> {
+        let read_failure = |err: StorageError| TrieStorageError::ReadFailure {
+            key: DbKey(key.0.clone()),
+            reason: err.to_string(),
+        };
+        let state_trie_nodes_table =
+            self.open_table(&self.tables.state_trie_nodes).map_err(read_failure)?;
+        let node = state_trie_nodes_table
+            .get(&self.txn, &key.0)
+            .map_err(|err| read_failure(err.into()))?;
+        Ok(node.map(|node| DbValue(node.value)))
+    }
+}
+
+impl StateTriesStorageWriter for StorageTxn<'_, RW> {
+    fn append_state_tries(
+        self,
+        block_number: BlockNumber,
+        roots: &StateTriesRoots,
+        new_nodes: Vec<(DbKey, DbValue)>,
+        replaced_nodes: Vec<DbKey>,
+        n_retained_blocks: u64,
+    ) -> StorageResult<Self> {
+        let marker = self.get_state_tries_marker()?;
+        if block_number != marker {
+            return Err(StorageError::MarkerMismatch { expected: marker, found: block_number });
+        }
+        let markers_table = self.open_table(&self.tables.markers)?;
+        let state_trie_nodes_table = self.open_table(&self.tables.state_trie_nodes)?;
+        let state_tries_changes_table = self.open_table(&self.tables.state_tries_changes)?;
+        let state_tries_roots_table = self.open_table(&self.tables.state_tries_roots)?;
+
+        let mut added_nodes = Vec::with_capacity(new_nodes.len());
+        for (key, value) in new_nodes {
+            let node = match state_trie_nodes_table.get(&self.txn, &key.0)? {
+                Some(node) => StateTrieNode { n_occurrences: node.n_occurrences + 1, ..node },
+                None => StateTrieNode { value: value.0, n_occurrences: 1 },
+            };
+            state_trie_nodes_table.upsert(&self.txn, &key.0, &node)?;
+            added_nodes.push(key.0);
+        }
+        let changes = StateTriesChanges {
+            added_nodes,
+            replaced_nodes: replaced_nodes.into_iter().map(|key| key.0).collect(),
+        };
+        state_tries_changes_table.insert(&self.txn, &block_number, &changes)?;
+        state_tries_roots_table.insert(&self.txn, &block_number, roots)?;
+        markers_table.upsert(&self.txn, &MarkerKind::StateTries, &block_number.unchecked_next())?;
+
+        // The tries of the first retained block are kept whole, so the nodes that the block after
+        // it replaced can't be discounted yet.
+        let Some(first_retained_block) =
+            block_number.unchecked_next().0.checked_sub(n_retained_blocks.max(1))
+        else {
+            return Ok(self);
+        };
+        let Some((first_stored_block, _roots)) =
+            state_tries_roots_table.cursor(&self.txn)?.lower_bound(&BlockNumber(0))?
+        else {
+            return Ok(self);
+        };
+        for pruned_block in first_stored_block.iter_up_to(BlockNumber(first_retained_block)) {
+            state_tries_roots_table.delete(&self.txn, &pruned_block)?;
+            // Only the changes of the first block of the tries, which replaced nothing, may be
+            // left at this point.
+            state_tries_changes_table.delete(&self.txn, &pruned_block)?;
+            let next_block = pruned_block.unchecked_next();
+            if let Some(changes) = state_tries_changes_table.get(&self.txn, &next_block)? {
+                discount_nodes(&self.txn, &state_trie_nodes_table, &changes.replaced_nodes)?;
+                state_tries_changes_table.delete(&self.txn, &next_block)?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl StorageTxn<'_, RW> {
+    // Reverts the tries of the given block if they're the last stored tries. The tries can't be
+    // reverted to a block before the window, in which case all of the stored tries are discarded so
+    // that they're built again from their first block. The nodes of the discarded tries are left in
+    // the storage.
+    pub(crate) fn revert_state_tries(&self, block_number: BlockNumber) -> StorageResult<()> {
+        if self.get_state_tries_marker()? != block_number.unchecked_next() {
+            return Ok(());
+        }
+        let markers_table = self.open_table(&self.tables.markers)?;
+        let state_trie_nodes_table = self.open_table(&self.tables.state_trie_nodes)?;
+        let state_tries_changes_table = self.open_table(&self.tables.state_tries_changes)?;
+        let state_tries_roots_table = self.open_table(&self.tables.state_tries_roots)?;
+
+        let Some(changes) = state_tries_changes_table.get(&self.txn, &block_number)? else {
+            warn!(
+                "Can't revert the state tries of block {block_number}, which are the first \
+                 retained tries. Discarding the stored tries."
+            );
+            let mut stored_blocks = Vec::new();
+            {
+                let mut cursor = state_tries_roots_table.cursor(&self.txn)?;
+                let mut current = cursor.lower_bound(&BlockNumber(0))?;
+                while let Some((stored_block, _roots)) = current {
+                    stored_blocks.push(stored_block);
+                    current = cursor.next()?;
+                }
+            }
+            for stored_block in stored_blocks {
+                state_tries_roots_table.delete(&self.txn, &stored_block)?;
+                state_tries_changes_table.delete(&self.txn, &stored_block)?;
+            }
+            markers_table.delete(&self.txn, &MarkerKind::StateTries)?;
+            return Ok(());
+        };
+        discount_nodes(&self.txn, &state_trie_nodes_table, &changes.added_nodes)?;
+        state_tries_changes_table.delete(&self.txn, &block_number)?;
+        state_tries_roots_table.delete(&self.txn, &block_number)?;
+        markers_table.upsert(&self.txn, &MarkerKind::StateTries, &block_number)?;
+        Ok(())
+    }
+}
+
+// Discounts an occurrence of each of the given nodes, and deletes the nodes that have no
+// occurrences left.
+fn discount_nodes<'env>(
+    txn: &'env DbTransaction<'env, RW>,
+    state_trie_nodes_table: &'env StateTrieNodesTable<'env>,
+    keys: &[Vec<u8>],
+) -> StorageResult<()> {
+    for key in keys {
+        let node = state_trie_nodes_table.get(txn, key)?.ok_or(StorageError::DBInconsistency {
+            msg: format!("Missing the state trie node {key:?}, which the stored tries contain."),
+        })?;
+        match node.n_occurrences {
+            0 | 1 => state_trie_nodes_table.delete(txn, key)?,
+            n_occurrences => state_trie_nodes_table.upsert(
+                txn,
+                key,
+                &StateTrieNode { n_occurrences: n_occurrences - 1, ..node },
+            )?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+use starknet_api::felt;
+use starknet_api::state::ThinStateDiff;
+use starknet_patricia_storage::storage_trait::{DbKey, DbValue, ReadStorage};
+
+use crate::state::StateStorageWriter;
+use crate::state_tries::{StateTriesRoots, StateTriesStorageReader, StateTriesStorageWriter};
+use crate::test_utils::get_test_storage;
+use crate::{StorageError, StorageReader, StorageWriter};
+
+fn key(name: &str) -> DbKey {
+    DbKey(name.as_bytes().to_vec())
+}
+
+fn node(name: &str) -> (DbKey, DbValue) {
+    (key(name), DbValue(format!("value of {name}").into_bytes()))
+}
+
+fn roots(block_number: u64) -> StateTriesRoots {
+    StateTriesRoots {
+        contracts_trie_root_hash: felt!(block_number + 100),
+        classes_trie_root_hash: felt!(block_number + 200),
+    }
+}
+
+fn append(writer: &mut StorageWriter, block_number: u64, new_nodes: &[&str], replaced_nodes: &[&str]) {
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_tries(
+            BlockNumber(block_number),
+            &roots(block_number),
+            new_nodes.iter().map(|name| node(name)).collect(),
+            replaced_nodes.iter().map(|name| key(name)).collect(),
+            2,
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+// Returns the names of the given nodes that are stored.
+fn stored_nodes<'a>(reader: &StorageReader, names: &[&'a str]) -> Vec<&'a str> {
+    let txn = reader.begin_ro_txn().unwrap();
+    names
+        .iter()
+        .copied()
+        .filter(|name| match txn.read(&key(name)).unwrap() {
+            Some(value) => {
+                assert_eq!(value, node(name).1);
+                true
+            }
+            None => false,
+        })
+        .collect()
+}
+
+fn stored_roots(reader: &StorageReader) -> Vec<u64> {
+    let txn = reader.begin_ro_txn().unwrap();
+    (0..5)
+        .filter(|block_number| {
+            txn.get_state_tries_roots(BlockNumber(*block_number))
+                .unwrap()
+                .inspect(|stored_roots| assert_eq!(*stored_roots, roots(*block_number)))
+                .is_some()
+        })
+        .collect()
+}
+
+#[test]
+fn append_prune_and_revert() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let all_nodes = ["a", "b", "c", "d"];
+    // The tries of block 0 are {a, b}, of block 1 {a, c}, of block 2 {a, b} and of block 3 {b, d}.
+    append(&mut writer, 0, &["a", "b"], &[]);
+    append(&mut writer, 1, &["c"], &["b"]);
+    assert_eq!(stored_nodes(&reader, &all_nodes), ["a", "b", "c"]);
+
+    // The tries of block 0 are pruned, but their nodes are in the tries of the retained blocks.
+    append(&mut writer, 2, &["b"], &["c"]);
+    assert_eq!(stored_roots(&reader), [1, 2]);
+    assert_eq!(stored_nodes(&reader, &all_nodes), ["a", "b", "c"]);
+
+    // Node c is only in the tries of block 1.
+    append(&mut writer, 3, &["d"], &["a"]);
+    assert_eq!(stored_roots(&reader), [2, 3]);
+    assert_eq!(stored_nodes(&reader, &all_nodes), ["a", "b", "d"]);
+    assert_eq!(reader.begin_ro_txn().unwrap().get_state_tries_marker().unwrap(), BlockNumber(4));
+
+    // Reverting restores the tries of the previous block.
+    let txn = writer.begin_rw_txn().unwrap();
+    txn.revert_state_tries(BlockNumber(3)).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(stored_roots(&reader), [2]);
+    assert_eq!(stored_nodes(&reader, &all_nodes), ["a", "b"]);
+    assert_eq!(reader.begin_ro_txn().unwrap().get_state_tries_marker().unwrap(), BlockNumber(3));
+
+    // The tries can't be reverted to a block that was pruned, so they're discarded.
+    let txn = writer.begin_rw_txn().unwrap();
+    txn.revert_state_tries(BlockNumber(2)).unwrap();
+    txn.commit().unwrap();
+    assert!(stored_roots(&reader).is_empty());
+    assert_eq!(reader.begin_ro_txn().unwrap().get_state_tries_marker().unwrap(), BlockNumber(0));
+    append(&mut writer, 0, &["a", "b"], &[]);
+    assert_eq!(stored_roots(&reader), [0]);
+}
+
+#[test]
+fn append_not_at_marker_fails() {
+    let ((_reader, mut writer), _temp_dir) = get_test_storage();
+    let result = writer.begin_rw_txn().unwrap().append_state_tries(
+        BlockNumber(1),
+        &roots(1),
+        vec![],
+        vec![],
+        2,
+    );
+    assert_matches!(
+        result,
+        Err(StorageError::MarkerMismatch { expected: BlockNumber(0), found: BlockNumber(1) })
+    );
+}
+
+#[test]
+fn reverting_the_state_diff_reverts_the_tries() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    append(&mut writer, 0, &["a"], &[]);
+
+    let (txn, _reverted_state_diff) =
+        writer.begin_rw_txn().unwrap().revert_state_diff(BlockNumber(0)).unwrap();
+    txn.commit().unwrap();
+    assert!(stored_roots(&reader).is_empty());
+    assert!(stored_nodes(&reader, &["a"]).is_empty());
+    assert_eq!(reader.begin_ro_txn().unwrap().get_state_tries_marker().unwrap(), BlockNumber(0));
+}
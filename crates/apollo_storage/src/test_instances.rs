@@ -15,12 +15,14 @@ use starknet_api::transaction::{
     TransactionHash,
     TransactionOffsetInBlock,
 };
+use starknet_types_core::felt::Felt;
 
 use crate::body::TransactionIndex;
 use crate::compression_utils::IsCompressed;
 use crate::header::StorageBlockHeader;
 use crate::mmap_file::LocationInFile;
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::{StateTrieNode, StateTriesChanges, StateTriesRoots};
 use crate::version::Version;
 use crate::{EventIndex, MarkerKind, OffsetKind, TransactionMetadata};
 
@@ -66,6 +68,8 @@ auto_impl_get_test_instance! {
         BaseLayerBlock = 6,
         ClassManagerBlock = 7,
         CompilerBackwardCompatibility = 8,
+        Checkpoint = 9,
+        StateTries = 10,
     }
     pub enum OffsetKind {
         ThinStateDiff = 0,
@@ -73,6 +77,18 @@ auto_impl_get_test_instance! {
         Casm = 2,
         DeprecatedContractClass = 3,
     }
+    pub struct StateTrieNode {
+        pub value: Vec<u8>,
+        pub n_occurrences: u64,
+    }
+    pub struct StateTriesChanges {
+        pub added_nodes: Vec<Vec<u8>>,
+        pub replaced_nodes: Vec<Vec<u8>>,
+    }
+    pub struct StateTriesRoots {
+        pub contracts_trie_root_hash: Felt,
+        pub classes_trie_root_hash: Felt,
+    }
     pub struct TransactionMetadata{
         pub tx_hash: TransactionHash,
         pub tx_location: LocationInFile,
@@ -24,6 +24,7 @@ fn build_storage_config(storage_scope: StorageScope, path_prefix: PathBuf) -> St
         },
         scope: storage_scope,
         mmap_file_config: get_mmap_file_test_config(),
+        index_transactions_by_sender: false,
     }
 }
 
@@ -107,6 +108,12 @@ impl TestStorageBuilder {
         self
     }
 
+    /// Sets whether transactions are indexed by their sender.
+    pub fn index_transactions_by_sender(mut self, index_transactions_by_sender: bool) -> Self {
+        self.config.index_transactions_by_sender = index_transactions_by_sender;
+        self
+    }
+
     /// Sets the chain id.
     pub fn chain_id(mut self, chain_id: ChainId) -> Self {
         self.config.db_config.chain_id = chain_id;
@@ -1,4 +1,6 @@
 #![allow(clippy::unwrap_used)]
+// The test instance macro below expands recursively, once per type.
+#![recursion_limit = "256"]
 #[cfg(test)]
 mod precision_test;
 
@@ -45,8 +47,11 @@ use starknet_api::block::{
     BlockSignature,
     BlockStatus,
     BlockTimestamp,
+    CommitCertificate,
+    CommitteeMember,
     GasPrice,
     GasPricePerToken,
+    PrecommitSignature,
     StarknetVersion,
 };
 use starknet_api::consensus_transaction::ConsensusTransaction;
@@ -65,7 +70,7 @@ use starknet_api::core::{
     StateDiffCommitment,
     TransactionCommitment,
 };
-use starknet_api::crypto::utils::Signature;
+use starknet_api::crypto::utils::{PublicKey, RawSignature, Signature};
 use starknet_api::data_availability::{DataAvailabilityMode, L1DataAvailabilityMode};
 use starknet_api::deprecated_contract_class::{
     ConstructorType,
@@ -427,6 +432,11 @@ auto_impl_get_test_instance! {
         pub l2_gas: ResourceBounds,
         pub l1_data_gas: ResourceBounds,
     }
+    pub struct BlockBody {
+        pub transactions: Vec<Transaction>,
+        pub transaction_outputs: Vec<TransactionOutput>,
+        pub transaction_hashes: Vec<TransactionHash>,
+    }
     pub struct BlockHash(pub StarkHash);
     pub struct BlockHeader {
         pub block_hash: BlockHash,
@@ -504,6 +514,18 @@ auto_impl_get_test_instance! {
 
     pub struct Calldata(pub Arc<Vec<Felt>>);
     pub struct ClassHash(pub StarkHash);
+    pub struct CommitCertificate {
+        pub block_number: BlockNumber,
+        pub round: u32,
+        pub block_hash: BlockHash,
+        pub committee: Vec<CommitteeMember>,
+        pub precommits: Vec<PrecommitSignature>,
+    }
+    pub struct CommitteeMember {
+        pub address: ContractAddress,
+        pub weight: u128,
+        pub public_key: PublicKey,
+    }
     pub struct CompiledClassHash(pub StarkHash);
     pub struct ContractAddressSalt(pub StarkHash);
     pub enum ConsensusTransaction {
@@ -741,6 +763,10 @@ auto_impl_get_test_instance! {
     pub struct TransactionCommitment(pub StarkHash);
     pub struct PaymasterData(pub Vec<Felt>);
     pub struct PoseidonHash(pub Felt);
+    pub struct PrecommitSignature {
+        pub voter: ContractAddress,
+        pub signature: RawSignature,
+    }
     pub struct Program {
         pub attributes: serde_json::Value,
         pub builtins: serde_json::Value,
@@ -753,6 +779,8 @@ auto_impl_get_test_instance! {
         pub prime: serde_json::Value,
         pub reference_manager: serde_json::Value,
     }
+    pub struct PublicKey(pub Felt);
+    pub struct RawSignature(pub Vec<Felt>);
     pub struct ReceiptCommitment(pub StarkHash);
     pub enum Resource {
         L1Gas = 0,
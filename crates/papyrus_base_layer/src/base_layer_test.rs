@@ -1,12 +1,20 @@
-use alloy::consensus::Header;
-use alloy::primitives::B256;
+use alloy::consensus::{Header, Receipt, ReceiptEnvelope, ReceiptWithBloom};
+use alloy::primitives::{Address, Log as AlloyLog, B256, U256};
 use alloy::providers::mock::Asserter;
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::types::{Block, BlockTransactions, Header as AlloyRpcHeader};
+use alloy::rpc::types::{
+    Block,
+    BlockTransactions,
+    Header as AlloyRpcHeader,
+    Log,
+    TransactionReceipt,
+};
+use alloy::sol_types::SolEvent;
 use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
-use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
-use starknet_api::core::EntryPointSelector;
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber, BlockTimestamp};
+use starknet_api::core::{EntryPointSelector, Nonce};
+use starknet_api::transaction::fields::Fee;
 use starknet_api::transaction::L1HandlerTransaction;
 use starknet_api::{calldata, contract_address, felt};
 
@@ -188,3 +196,74 @@ async fn events_from_other_contract() {
     assert_eq!(events.len(), 1, "Expected only events from this contract.");
     assert_matches!(events.remove(0), L1Event::LogMessageToL2 { tx, .. } if tx == this_l1_handler);
 }
+
+#[tokio::test]
+async fn transaction_events() {
+    const EVENT_IDENTIFIERS: &[EventIdentifier] = &[LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER];
+    const L1_BLOCK_NUMBER: u64 = 10;
+    let (base_layer, asserter) = base_layer_with_mocked_provider();
+    let l1_tx_hash = B256::repeat_byte(1);
+
+    let message_to_l2 = Starknet::LogMessageToL2 {
+        fromAddress: Address::repeat_byte(2),
+        toAddress: U256::from(0x12),
+        selector: U256::from(0x34),
+        payload: vec![U256::from(1), U256::from(2)],
+        nonce: U256::from(3),
+        fee: U256::from(4),
+    };
+    let log = |address| Log {
+        inner: AlloyLog { address, data: message_to_l2.encode_log_data() },
+        block_number: Some(L1_BLOCK_NUMBER),
+        transaction_hash: Some(l1_tx_hash),
+        ..Default::default()
+    };
+    // The same transaction also sends a message through another contract, which is ignored.
+    let logs = vec![log(base_layer.config.starknet_contract_address), log(Address::repeat_byte(5))];
+    let receipt = TransactionReceipt {
+        inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+            receipt: Receipt { status: true.into(), cumulative_gas_used: 0, logs },
+            logs_bloom: Default::default(),
+        }),
+        transaction_hash: l1_tx_hash,
+        transaction_index: Some(0),
+        block_hash: Some(B256::default()),
+        block_number: Some(L1_BLOCK_NUMBER),
+        gas_used: 0,
+        effective_gas_price: 0,
+        blob_gas_used: None,
+        blob_gas_price: None,
+        from: Address::default(),
+        to: None,
+        contract_address: None,
+    };
+    asserter.push_success(&Some(receipt));
+    let header = Header {
+        number: L1_BLOCK_NUMBER,
+        timestamp: 7,
+        base_fee_per_gas: Some(5),
+        ..Default::default()
+    };
+    asserter.push_success(&Some(Block::new(
+        AlloyRpcHeader::new(header),
+        BlockTransactions::<B256>::default(),
+    )));
+
+    let mut events = base_layer.transaction_events(l1_tx_hash, EVENT_IDENTIFIERS).await.unwrap();
+
+    assert_eq!(events.len(), 1, "Expected only events from the Starknet contract.");
+    assert_matches!(
+        events.remove(0),
+        L1Event::LogMessageToL2 { tx, fee, l1_tx_hash: Some(hash), timestamp }
+        if tx.contract_address == contract_address!("0x12")
+            && tx.nonce == Nonce(felt!("0x3"))
+            && fee == Fee(4)
+            && hash == l1_tx_hash
+            && timestamp == BlockTimestamp(7)
+    );
+
+    // An unknown transaction has no events.
+    asserter.push_success(&None::<TransactionReceipt>);
+    let events = base_layer.transaction_events(l1_tx_hash, EVENT_IDENTIFIERS).await.unwrap();
+    assert_eq!(events, vec![]);
+}
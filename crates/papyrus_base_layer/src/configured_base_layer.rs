@@ -0,0 +1,116 @@
+use std::ops::RangeInclusive;
+
+use async_trait::async_trait;
+use starknet_api::block::BlockHashAndNumber;
+use url::Url;
+
+use crate::ethereum_base_layer_contract::EthereumBaseLayerContract;
+use crate::quorum_base_layer::{QuorumBaseLayer, QuorumBaseLayerError};
+use crate::simulated_base_layer::{SimulatedBaseLayer, SimulatedBaseLayerError};
+use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event, L1TxHash};
+
+/// The base layer a node is configured to use: the Starknet contract on Ethereum, through `B` or
+/// through a quorum of Ethereum endpoints, or a simulated L1.
+#[derive(Clone, Debug)]
+pub enum ConfiguredBaseLayer<B> {
+    Ethereum(B),
+    Quorum(QuorumBaseLayer<EthereumBaseLayerContract>),
+    Simulated(SimulatedBaseLayer),
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ConfiguredBaseLayerError<E: std::error::Error> {
+    #[error(transparent)]
+    Ethereum(E),
+    #[error(transparent)]
+    Quorum(QuorumBaseLayerError),
+    #[error(transparent)]
+    Simulated(SimulatedBaseLayerError),
+}
+
+// Calls the method on the configured base layer, and wraps its error.
+macro_rules! delegate {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            ConfiguredBaseLayer::Ethereum(base_layer) => base_layer
+                .$method($($arg),*)
+                .await
+                .map_err(ConfiguredBaseLayerError::Ethereum),
+            ConfiguredBaseLayer::Quorum(base_layer) => base_layer
+                .$method($($arg),*)
+                .await
+                .map_err(ConfiguredBaseLayerError::Quorum),
+            ConfiguredBaseLayer::Simulated(base_layer) => base_layer
+                .$method($($arg),*)
+                .await
+                .map_err(ConfiguredBaseLayerError::Simulated),
+        }
+    };
+}
+
+#[async_trait]
+impl<B: BaseLayerContract + Send + Sync> BaseLayerContract for ConfiguredBaseLayer<B> {
+    type Error = ConfiguredBaseLayerError<B::Error>;
+
+    async fn get_proved_block_at(
+        &self,
+        l1_block: L1BlockNumber,
+    ) -> Result<BlockHashAndNumber, Self::Error> {
+        delegate!(self.get_proved_block_at(l1_block))
+    }
+
+    async fn latest_proved_block(
+        &self,
+        finality: u64,
+    ) -> Result<Option<BlockHashAndNumber>, Self::Error> {
+        delegate!(self.latest_proved_block(finality))
+    }
+
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> Result<Option<L1BlockNumber>, Self::Error> {
+        delegate!(self.latest_l1_block_number(finality))
+    }
+
+    async fn latest_l1_block(
+        &self,
+        finality: u64,
+    ) -> Result<Option<L1BlockReference>, Self::Error> {
+        delegate!(self.latest_l1_block(finality))
+    }
+
+    async fn l1_block_at(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> Result<Option<L1BlockReference>, Self::Error> {
+        delegate!(self.l1_block_at(block_number))
+    }
+
+    async fn events<'a>(
+        &'a self,
+        block_range: RangeInclusive<L1BlockNumber>,
+        event_identifiers: &'a [&'a str],
+    ) -> Result<Vec<L1Event>, Self::Error> {
+        delegate!(self.events(block_range, event_identifiers))
+    }
+
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> Result<Vec<L1Event>, Self::Error> {
+        delegate!(self.transaction_events(l1_tx_hash, event_identifiers))
+    }
+
+    async fn get_block_header(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> Result<Option<L1BlockHeader>, Self::Error> {
+        delegate!(self.get_block_header(block_number))
+    }
+
+    async fn set_provider_url(&mut self, url: Url) -> Result<(), Self::Error> {
+        delegate!(self.set_provider_url(url))
+    }
+}
@@ -5,7 +5,7 @@ use std::time::Duration;
 
 use alloy::dyn_abi::SolType;
 use alloy::eips::eip7840;
-use alloy::primitives::Address as EthereumContractAddress;
+use alloy::primitives::{keccak256, Address as EthereumContractAddress};
 use alloy::providers::{Provider, ProviderBuilder, RootProvider};
 use alloy::rpc::json_rpc::RpcError;
 use alloy::rpc::types::eth::Filter as EthEventFilter;
@@ -26,7 +26,7 @@ use url::Url;
 use validator::Validate;
 
 use crate::eth_events::parse_event;
-use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event};
+use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event, L1TxHash};
 
 pub type EthereumBaseLayerResult<T> = Result<T, EthereumBaseLayerError>;
 
@@ -175,6 +175,45 @@ impl BaseLayerContract for EthereumBaseLayerContract {
         futures::future::join_all(block_header_futures).await.into_iter().collect()
     }
 
+    #[instrument(skip(self), err)]
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> EthereumBaseLayerResult<Vec<L1Event>> {
+        let receipt = tokio::time::timeout(
+            self.config.timeout_millis,
+            self.contract.provider().get_transaction_receipt(l1_tx_hash),
+        )
+        .await??;
+        // A receipt without a block number belongs to a pending transaction.
+        let Some((receipt, block_number)) =
+            receipt.and_then(|receipt| receipt.block_number.map(|number| (receipt, number)))
+        else {
+            return Ok(vec![]);
+        };
+
+        let event_signatures: Vec<_> = event_identifiers.iter().map(keccak256).collect();
+        let matching_logs: Vec<_> = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| {
+                log.address() == self.config.starknet_contract_address
+                    && log.topic0().is_some_and(|topic| event_signatures.contains(topic))
+            })
+            .cloned()
+            .collect();
+        if matching_logs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let Some(header) = self.get_block_header(block_number).await? else {
+            return Ok(vec![]);
+        };
+        matching_logs.into_iter().map(|log| parse_event(log, header.timestamp)).collect()
+    }
+
     #[instrument(skip(self), err)]
     async fn latest_l1_block_number(
         &self,
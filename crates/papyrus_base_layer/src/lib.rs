@@ -14,9 +14,13 @@ use starknet_api::transaction::fields::{Calldata, Fee};
 use starknet_api::transaction::L1HandlerTransaction;
 use url::Url;
 
+pub mod configured_base_layer;
 pub mod constants;
 pub mod ethereum_base_layer_contract;
+pub mod metrics;
 pub mod monitored_base_layer;
+pub mod quorum_base_layer;
+pub mod simulated_base_layer;
 
 pub(crate) mod eth_events;
 
@@ -28,6 +32,7 @@ mod base_layer_test;
 
 pub type L1BlockNumber = u64;
 pub type L1BlockHash = [u8; 32];
+pub type L1TxHash = FixedBytes<32>;
 
 #[cfg(any(feature = "testing", test))]
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
@@ -59,7 +64,7 @@ pub trait BaseLayerContract {
     ) -> Result<Option<L1BlockNumber>, Self::Error>;
 
     async fn latest_l1_block(&self, finality: u64)
-    -> Result<Option<L1BlockReference>, Self::Error>;
+        -> Result<Option<L1BlockReference>, Self::Error>;
 
     async fn l1_block_at(
         &self,
@@ -73,6 +78,14 @@ pub trait BaseLayerContract {
         event_identifiers: &'a [&'a str],
     ) -> Result<Vec<L1Event>, Self::Error>;
 
+    /// Get specific events that the Starknet base contract emitted in an L1 transaction. Returns
+    /// no events if the transaction isn't in an L1 block.
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> Result<Vec<L1Event>, Self::Error>;
+
     async fn get_block_header(
         &self,
         block_number: L1BlockNumber,
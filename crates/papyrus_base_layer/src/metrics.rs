@@ -0,0 +1,17 @@
+use apollo_metrics::define_metrics;
+
+define_metrics!(
+    BaseLayer => {
+        MetricCounter { L1_QUORUM_DISAGREEMENTS, "l1_quorum_disagreements", "Number of quorum L1 reads in which an endpoint disagreed with the accepted result", init=0 },
+        MetricCounter { L1_QUORUM_ENDPOINT_ERRORS, "l1_quorum_endpoint_errors", "Number of errors returned by L1 endpoints in quorum L1 reads", init=0 },
+        MetricCounter { L1_QUORUM_FAILURES, "l1_quorum_failures", "Number of quorum L1 reads in which no result reached the required agreement", init=0 },
+        MetricGauge { L1_QUORUM_HEALTHY_ENDPOINTS, "l1_quorum_healthy_endpoints", "Number of L1 endpoints with a non-negative health score in quorum L1 reads" },
+    }
+);
+
+pub(crate) fn register_quorum_metrics() {
+    L1_QUORUM_DISAGREEMENTS.register();
+    L1_QUORUM_ENDPOINT_ERRORS.register();
+    L1_QUORUM_FAILURES.register();
+    L1_QUORUM_HEALTHY_ENDPOINTS.register();
+}
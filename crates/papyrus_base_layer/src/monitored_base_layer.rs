@@ -12,7 +12,7 @@ use tracing::{error, info};
 use url::Url;
 
 use crate::ethereum_base_layer_contract::EthereumBaseLayerContract;
-use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event};
+use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event, L1TxHash};
 
 pub type MonitoredEthereumBaseLayer = MonitoredBaseLayer<EthereumBaseLayerContract>;
 
@@ -149,6 +149,18 @@ impl<B: BaseLayerContract + Send + Sync> BaseLayerContract for MonitoredBaseLaye
             .map_err(|err| MonitoredBaseLayerError::BaseLayerContractError(err))
     }
 
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> Result<Vec<L1Event>, Self::Error> {
+        self.get()
+            .await?
+            .transaction_events(l1_tx_hash, event_identifiers)
+            .await
+            .map_err(|err| MonitoredBaseLayerError::BaseLayerContractError(err))
+    }
+
     async fn get_block_header(
         &self,
         block_number: L1BlockNumber,
@@ -0,0 +1,375 @@
+//! Quorum reads of the Starknet contract across several L1 endpoints, so that a single faulty or
+//! lagging endpoint can't feed wrong data to the node.
+//!
+//! Every read queries the healthiest `endpoints_per_request` endpoints concurrently, and only
+//! accepts a result that at least `min_agreement` of them returned. Endpoints that agree with the
+//! accepted result gain health, and endpoints that disagree with it or fail lose health. Every
+//! `probe_interval` reads, one of the other endpoints is queried as well, in turns, so that
+//! endpoints which recovered can regain health. A probed endpoint doesn't count towards the quorum.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockHashAndNumber;
+use tracing::warn;
+use url::Url;
+use validator::{Validate, ValidationError};
+
+use crate::metrics::{
+    register_quorum_metrics,
+    L1_QUORUM_DISAGREEMENTS,
+    L1_QUORUM_ENDPOINT_ERRORS,
+    L1_QUORUM_FAILURES,
+    L1_QUORUM_HEALTHY_ENDPOINTS,
+};
+use crate::{BaseLayerContract, L1BlockHeader, L1BlockNumber, L1BlockReference, L1Event, L1TxHash};
+
+#[cfg(test)]
+#[path = "quorum_base_layer_test.rs"]
+mod quorum_base_layer_test;
+
+pub type QuorumBaseLayerResult<T> = Result<T, QuorumBaseLayerError>;
+
+pub const MAX_HEALTH_SCORE: i64 = 10;
+pub const MIN_HEALTH_SCORE: i64 = -10;
+const AGREEMENT_REWARD: i64 = 1;
+const DISAGREEMENT_PENALTY: i64 = 3;
+const ERROR_PENALTY: i64 = 2;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Validate)]
+#[validate(schema(function = "validate_quorum_base_layer_config"))]
+pub struct QuorumBaseLayerConfig {
+    pub endpoints_per_request: usize,
+    pub min_agreement: usize,
+    pub probe_interval: usize,
+}
+
+impl SerializeConfig for QuorumBaseLayerConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "endpoints_per_request",
+                &self.endpoints_per_request,
+                "The number of L1 endpoints queried in each L1 read, chosen by their health.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "min_agreement",
+                &self.min_agreement,
+                "The number of queried L1 endpoints that must return the same result for an L1 \
+                 read to succeed. Must be a majority of endpoints_per_request.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "probe_interval",
+                &self.probe_interval,
+                "Every probe_interval L1 reads, one L1 endpoint which isn't among the healthiest \
+                 is queried as well, so that it can regain health. 0 disables probing.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl Default for QuorumBaseLayerConfig {
+    fn default() -> Self {
+        Self { endpoints_per_request: 3, min_agreement: 2, probe_interval: 10 }
+    }
+}
+
+fn validate_quorum_base_layer_config(
+    config: &QuorumBaseLayerConfig,
+) -> Result<(), ValidationError> {
+    // A majority guarantees that two different results can't both be accepted.
+    if config.min_agreement > config.endpoints_per_request
+        || 2 * config.min_agreement <= config.endpoints_per_request
+    {
+        return Err(ValidationError::new(
+            "min_agreement must be a majority of endpoints_per_request",
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the Starknet contract through a quorum of base layers, each connected to a different L1
+/// endpoint. Clones share the endpoints' health scores.
+#[derive(Clone, Debug)]
+pub struct QuorumBaseLayer<B> {
+    config: QuorumBaseLayerConfig,
+    endpoints: Vec<QuorumEndpoint<B>>,
+    // Indexed like `endpoints`.
+    health_scores: Arc<Mutex<Vec<i64>>>,
+    n_reads: Arc<AtomicUsize>,
+}
+
+#[derive(Clone, Debug)]
+struct QuorumEndpoint<B> {
+    url: Url,
+    base_layer: B,
+}
+
+impl<B: BaseLayerContract + Send + Sync> QuorumBaseLayer<B> {
+    /// Panics if there are fewer endpoints than `endpoints_per_request`.
+    pub fn new(config: QuorumBaseLayerConfig, endpoints: Vec<(Url, B)>) -> Self {
+        assert!(
+            endpoints.len() >= config.endpoints_per_request,
+            "Quorum reads of {} L1 endpoints require at least as many configured endpoints, got \
+             {}.",
+            config.endpoints_per_request,
+            endpoints.len()
+        );
+        register_quorum_metrics();
+        L1_QUORUM_HEALTHY_ENDPOINTS.set_lossy(endpoints.len());
+
+        let health_scores = Arc::new(Mutex::new(vec![0; endpoints.len()]));
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, base_layer)| QuorumEndpoint { url, base_layer })
+            .collect();
+        Self { config, endpoints, health_scores, n_reads: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// Returns the health score of every endpoint, between `MIN_HEALTH_SCORE` and
+    /// `MAX_HEALTH_SCORE`.
+    pub fn health_scores(&self) -> Vec<(Url, i64)> {
+        let health_scores = self.health_scores.lock().expect("Health scores lock is poisoned.");
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.url.clone())
+            .zip(health_scores.clone())
+            .collect()
+    }
+
+    /// Returns the indices of the healthiest endpoints, preferring earlier endpoints on ties. Every
+    /// `probe_interval` reads, the index of one of the other endpoints, taken in turns, is appended
+    /// to them as a probe.
+    fn select_endpoints(&self) -> Vec<usize> {
+        let health_scores = self.health_scores.lock().expect("Health scores lock is poisoned.");
+        let mut indices: Vec<usize> = (0..self.endpoints.len()).collect();
+        indices.sort_by_key(|&index| std::cmp::Reverse(health_scores[index]));
+        let mut others = indices.split_off(self.config.endpoints_per_request);
+
+        let n_reads = self.n_reads.fetch_add(1, Ordering::Relaxed) + 1;
+        let probe_interval = self.config.probe_interval;
+        if probe_interval > 0 && n_reads % probe_interval == 0 && !others.is_empty() {
+            others.sort_unstable();
+            indices.push(others[(n_reads / probe_interval) % others.len()]);
+        }
+        indices
+    }
+
+    /// Accepts a result that a quorum of the selected endpoints returned, and updates the health of
+    /// the endpoints accordingly, including the probed endpoint if there is one.
+    /// `resolve` picks the accepted value out of the successful results, if there is one, and
+    /// `agrees` tells whether a result agrees with the accepted value.
+    fn tally<T: Debug>(
+        &self,
+        method: &'static str,
+        selected: &[usize],
+        results: Vec<Result<T, B::Error>>,
+        resolve: impl FnOnce(Vec<&T>) -> Option<T>,
+        agrees: impl Fn(&T, &T) -> bool,
+    ) -> QuorumBaseLayerResult<T> {
+        let mut values = Vec::new();
+        let mut probed_values = Vec::new();
+        let mut failing = Vec::new();
+        for (position, (&index, result)) in selected.iter().zip(results).enumerate() {
+            match result {
+                Ok(value) if position < self.config.endpoints_per_request => {
+                    values.push((index, value))
+                }
+                Ok(value) => probed_values.push((index, value)),
+                Err(err) => {
+                    warn!("L1 endpoint {} failed in {method}: {err}", self.endpoints[index].url);
+                    L1_QUORUM_ENDPOINT_ERRORS.increment(1);
+                    failing.push(index);
+                }
+            }
+        }
+
+        let Some(accepted) = resolve(values.iter().map(|(_, value)| value).collect()) else {
+            warn!(
+                "No result of {method} was returned by at least {} L1 endpoints: {values:?}.",
+                self.config.min_agreement
+            );
+            L1_QUORUM_FAILURES.increment(1);
+            // Without a quorum there is no telling which of the results is wrong.
+            self.update_health(&[], &[], &failing);
+            return Err(QuorumBaseLayerError::NoQuorum {
+                method,
+                min_agreement: self.config.min_agreement,
+            });
+        };
+
+        let (agreeing, disagreeing): (Vec<_>, Vec<_>) =
+            values.iter().chain(&probed_values).partition(|(_, value)| agrees(value, &accepted));
+        if !disagreeing.is_empty() {
+            warn!(
+                "L1 endpoints disagreed with the accepted result of {method}, {accepted:?}: {:?}.",
+                disagreeing
+                    .iter()
+                    .map(|(index, value)| (self.endpoints[*index].url.as_str(), value))
+                    .collect::<Vec<_>>()
+            );
+            L1_QUORUM_DISAGREEMENTS.increment(
+                disagreeing.len().try_into().expect("Number of endpoints should fit in u64."),
+            );
+        }
+        self.update_health(
+            &agreeing.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            &disagreeing.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            &failing,
+        );
+
+        Ok(accepted)
+    }
+
+    /// Accepts a result that at least `min_agreement` of the selected endpoints returned exactly.
+    fn tally_equal<T: Clone + Debug + PartialEq>(
+        &self,
+        method: &'static str,
+        selected: &[usize],
+        results: Vec<Result<T, B::Error>>,
+    ) -> QuorumBaseLayerResult<T> {
+        let min_agreement = self.config.min_agreement;
+        let resolve = |values: Vec<&T>| {
+            values
+                .iter()
+                .find(|value| values.iter().filter(|other| other == value).count() >= min_agreement)
+                .map(|value| (*value).clone())
+        };
+        self.tally(method, selected, results, resolve, |value, accepted| value == accepted)
+    }
+
+    fn update_health(&self, agreeing: &[usize], disagreeing: &[usize], failing: &[usize]) {
+        let mut health_scores = self.health_scores.lock().expect("Health scores lock is poisoned.");
+        for &index in agreeing {
+            health_scores[index] = (health_scores[index] + AGREEMENT_REWARD).min(MAX_HEALTH_SCORE);
+        }
+        for (indices, penalty) in [(disagreeing, DISAGREEMENT_PENALTY), (failing, ERROR_PENALTY)] {
+            for &index in indices {
+                health_scores[index] = (health_scores[index] - penalty).max(MIN_HEALTH_SCORE);
+            }
+        }
+        L1_QUORUM_HEALTHY_ENDPOINTS
+            .set_lossy(health_scores.iter().filter(|&&score| score >= 0).count());
+    }
+}
+
+// Queries the selected endpoints concurrently, and accepts the result a quorum of them agreed on.
+macro_rules! quorum_read {
+    ($self:ident.$method:ident($($arg:expr),*)) => {{
+        let selected = $self.select_endpoints();
+        let results = join_all(
+            selected.iter().map(|&index| $self.endpoints[index].base_layer.$method($($arg),*)),
+        )
+        .await;
+        $self.tally_equal(stringify!($method), &selected, results)
+    }};
+}
+
+#[async_trait]
+impl<B: BaseLayerContract + Send + Sync> BaseLayerContract for QuorumBaseLayer<B> {
+    type Error = QuorumBaseLayerError;
+
+    async fn get_proved_block_at(
+        &self,
+        l1_block: L1BlockNumber,
+    ) -> QuorumBaseLayerResult<BlockHashAndNumber> {
+        quorum_read!(self.get_proved_block_at(l1_block))
+    }
+
+    async fn latest_proved_block(
+        &self,
+        finality: u64,
+    ) -> QuorumBaseLayerResult<Option<BlockHashAndNumber>> {
+        let Some(block_number) = self.latest_l1_block_number(finality).await? else {
+            return Ok(None);
+        };
+        self.get_proved_block_at(block_number).await.map(Some)
+    }
+
+    /// Returns the latest block that at least `min_agreement` of the endpoints reached, since
+    /// endpoints may lag a few blocks behind each other. Endpoints behind it count as disagreeing.
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> QuorumBaseLayerResult<Option<L1BlockNumber>> {
+        let min_agreement = self.config.min_agreement;
+        let selected = self.select_endpoints();
+        let results = join_all(
+            selected
+                .iter()
+                .map(|&index| self.endpoints[index].base_layer.latest_l1_block_number(finality)),
+        )
+        .await;
+        let resolve = |mut block_numbers: Vec<&Option<L1BlockNumber>>| {
+            block_numbers.sort_unstable_by(|a, b| b.cmp(a));
+            block_numbers.get(min_agreement - 1).map(|&&block_number| block_number)
+        };
+        self.tally("latest_l1_block_number", &selected, results, resolve, |value, accepted| {
+            value >= accepted
+        })
+    }
+
+    async fn latest_l1_block(
+        &self,
+        finality: u64,
+    ) -> QuorumBaseLayerResult<Option<L1BlockReference>> {
+        let Some(block_number) = self.latest_l1_block_number(finality).await? else {
+            return Ok(None);
+        };
+        self.l1_block_at(block_number).await
+    }
+
+    async fn l1_block_at(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> QuorumBaseLayerResult<Option<L1BlockReference>> {
+        quorum_read!(self.l1_block_at(block_number))
+    }
+
+    async fn events<'a>(
+        &'a self,
+        block_range: RangeInclusive<L1BlockNumber>,
+        event_identifiers: &'a [&'a str],
+    ) -> QuorumBaseLayerResult<Vec<L1Event>> {
+        quorum_read!(self.events(block_range.clone(), event_identifiers))
+    }
+
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> QuorumBaseLayerResult<Vec<L1Event>> {
+        quorum_read!(self.transaction_events(l1_tx_hash, event_identifiers))
+    }
+
+    async fn get_block_header(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> QuorumBaseLayerResult<Option<L1BlockHeader>> {
+        quorum_read!(self.get_block_header(block_number))
+    }
+
+    /// The endpoints of a quorum are fixed by its configuration, so they can't be switched.
+    async fn set_provider_url(&mut self, url: Url) -> QuorumBaseLayerResult<()> {
+        Err(QuorumBaseLayerError::FixedEndpoints { url })
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum QuorumBaseLayerError {
+    #[error("No result of {method} was returned by at least {min_agreement} L1 endpoints.")]
+    NoQuorum { method: &'static str, min_agreement: usize },
+    #[error("The L1 endpoints of a quorum are fixed by its configuration, can't switch to {url}.")]
+    FixedEndpoints { url: Url },
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use url::Url;
+use validator::Validate;
+
+use crate::quorum_base_layer::{
+    QuorumBaseLayer,
+    QuorumBaseLayerConfig,
+    QuorumBaseLayerError,
+    DISAGREEMENT_PENALTY,
+    ERROR_PENALTY,
+};
+use crate::simulated_base_layer::{SimulatedBaseLayer, SimulatedBaseLayerConfig};
+use crate::BaseLayerContract;
+
+// Probing is tested separately.
+const CONFIG: QuorumBaseLayerConfig =
+    QuorumBaseLayerConfig { endpoints_per_request: 3, min_agreement: 2, probe_interval: 0 };
+
+fn url(index: usize) -> Url {
+    Url::parse(&format!("http://endpoint{index}.io")).unwrap()
+}
+
+/// Returns identical simulated chains, each standing for another L1 endpoint.
+fn simulated_endpoints(n_endpoints: usize, n_blocks: usize) -> Vec<SimulatedBaseLayer> {
+    (0..n_endpoints)
+        .map(|_| {
+            let base_layer = SimulatedBaseLayer::new(SimulatedBaseLayerConfig {
+                block_time_millis: Duration::ZERO,
+                ..Default::default()
+            });
+            for _ in 0..n_blocks {
+                base_layer.mine_block();
+            }
+            base_layer
+        })
+        .collect()
+}
+
+fn quorum_base_layer(
+    config: QuorumBaseLayerConfig,
+    endpoints: &[SimulatedBaseLayer],
+) -> QuorumBaseLayer<SimulatedBaseLayer> {
+    QuorumBaseLayer::new(
+        config,
+        endpoints
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, endpoint)| (url(index), endpoint))
+            .collect(),
+    )
+}
+
+fn scores(base_layer: &QuorumBaseLayer<SimulatedBaseLayer>) -> Vec<i64> {
+    base_layer.health_scores().into_iter().map(|(_, score)| score).collect()
+}
+
+#[test]
+fn config_requires_majority() {
+    assert!(CONFIG.validate().is_ok());
+    for (endpoints_per_request, min_agreement) in [(3, 1), (4, 2), (3, 4), (1, 0)] {
+        let config = QuorumBaseLayerConfig { endpoints_per_request, min_agreement, ..CONFIG };
+        assert!(config.validate().is_err(), "{config:?} should be invalid.");
+    }
+}
+
+#[tokio::test]
+async fn accepts_majority_result() {
+    let endpoints = simulated_endpoints(3, 2);
+    let expected_block = endpoints[0].l1_block_at(2).await.unwrap();
+    // The faulty endpoint is on another fork.
+    endpoints[2].reorg(1);
+    let base_layer = quorum_base_layer(CONFIG, &endpoints);
+
+    assert_eq!(base_layer.l1_block_at(2).await, Ok(expected_block));
+    assert_eq!(scores(&base_layer), vec![1, 1, -DISAGREEMENT_PENALTY]);
+}
+
+#[tokio::test]
+async fn latest_block_number_tolerates_lagging_endpoints() {
+    let endpoints = simulated_endpoints(3, 1);
+    endpoints[0].mine_block();
+    endpoints[0].mine_block();
+    endpoints[1].mine_block();
+    let base_layer = quorum_base_layer(CONFIG, &endpoints);
+
+    // The highest block that two of the endpoints reached.
+    assert_eq!(base_layer.latest_l1_block_number(0).await, Ok(Some(2)));
+    assert_eq!(scores(&base_layer), vec![1, 1, -DISAGREEMENT_PENALTY]);
+    assert_eq!(base_layer.latest_l1_block_number(2).await, Ok(Some(0)));
+    assert_eq!(base_layer.latest_l1_block_number(3).await, Ok(None));
+}
+
+#[tokio::test]
+async fn no_quorum() {
+    let endpoints = simulated_endpoints(3, 3);
+    endpoints[1].reorg(1);
+    endpoints[2].reorg(2);
+    let base_layer = quorum_base_layer(CONFIG, &endpoints);
+
+    assert_eq!(
+        base_layer.l1_block_at(3).await,
+        Err(QuorumBaseLayerError::NoQuorum { method: "l1_block_at", min_agreement: 2 })
+    );
+    // No endpoint is blamed for disagreeing without a quorum.
+    assert_eq!(scores(&base_layer), vec![0, 0, 0]);
+
+    // Missing blocks are errors of the simulated base layer.
+    assert_matches!(
+        base_layer.get_proved_block_at(4).await,
+        Err(QuorumBaseLayerError::NoQuorum { method: "get_proved_block_at", .. })
+    );
+    assert_eq!(scores(&base_layer), vec![-ERROR_PENALTY; 3]);
+}
+
+#[tokio::test]
+async fn unhealthy_endpoints_are_replaced() {
+    let endpoints = simulated_endpoints(4, 1);
+    endpoints[0].reorg(1);
+    let base_layer = quorum_base_layer(CONFIG, &endpoints);
+    assert_eq!(base_layer.select_endpoints(), vec![0, 1, 2]);
+
+    base_layer.l1_block_at(1).await.unwrap();
+
+    assert_eq!(scores(&base_layer), vec![-DISAGREEMENT_PENALTY, 1, 1, 0]);
+    assert_eq!(base_layer.select_endpoints(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn endpoints_outside_the_quorum_are_probed() {
+    let endpoints = simulated_endpoints(5, 1);
+    let config = QuorumBaseLayerConfig { probe_interval: 2, ..CONFIG };
+    let base_layer = quorum_base_layer(config, &endpoints);
+
+    base_layer.l1_block_at(1).await.unwrap();
+    assert_eq!(scores(&base_layer), vec![1, 1, 1, 0, 0]);
+
+    // The other endpoints are probed in turns.
+    base_layer.l1_block_at(1).await.unwrap();
+    assert_eq!(scores(&base_layer), vec![2, 2, 2, 0, 1]);
+    base_layer.l1_block_at(1).await.unwrap();
+    base_layer.l1_block_at(1).await.unwrap();
+    assert_eq!(scores(&base_layer), vec![4, 4, 4, 1, 1]);
+}
+
+#[tokio::test]
+async fn probed_endpoint_does_not_count_towards_quorum() {
+    let endpoints = simulated_endpoints(4, 3);
+    endpoints[1].reorg(1);
+    endpoints[2].reorg(2);
+    let config = QuorumBaseLayerConfig { probe_interval: 1, ..CONFIG };
+    let base_layer = quorum_base_layer(config, &endpoints);
+
+    // Only the probed endpoint agrees with the first one.
+    assert_matches!(base_layer.l1_block_at(3).await, Err(QuorumBaseLayerError::NoQuorum { .. }));
+    assert_eq!(scores(&base_layer), vec![0, 0, 0, 0]);
+}
+
+#[tokio::test]
+async fn provider_url_is_fixed() {
+    let endpoints = simulated_endpoints(3, 1);
+    let mut base_layer = quorum_base_layer(CONFIG, &endpoints);
+
+    assert_eq!(
+        base_layer.set_provider_url(url(3)).await,
+        Err(QuorumBaseLayerError::FixedEndpoints { url: url(3) })
+    );
+}
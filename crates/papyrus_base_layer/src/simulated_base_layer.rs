@@ -0,0 +1,501 @@
+//! An in-process simulation of the Starknet contract on L1, for running local chains and tests
+//! without an Ethereum node.
+//!
+//! Blocks are mined as time passes, according to the configured block time, or on demand. Events,
+//! proved Starknet blocks, gas prices and reorgs are injected through [`SimulatedBaseLayer`], or
+//! scripted per block in a JSON file given in the config, as a [`SimulatedL1Script`].
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::mem;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::keccak256;
+use apollo_config::converters::deserialize_milliseconds_to_duration;
+use apollo_config::dumping::{ser_optional_param, ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHashAndNumber, BlockTimestamp};
+use tracing::debug;
+use url::Url;
+use validator::Validate;
+
+use crate::constants::{
+    EventIdentifier,
+    CONSUMED_MESSAGE_TO_L2_EVENT_IDENTIFIER,
+    LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER,
+    MESSAGE_TO_L2_CANCELED_EVENT_IDENTIFIER,
+    MESSAGE_TO_L2_CANCELLATION_STARTED_EVENT_IDENTIFIER,
+};
+use crate::{
+    BaseLayerContract,
+    L1BlockHash,
+    L1BlockHeader,
+    L1BlockNumber,
+    L1BlockReference,
+    L1Event,
+    L1TxHash,
+};
+
+#[cfg(test)]
+#[path = "simulated_base_layer_test.rs"]
+mod simulated_base_layer_test;
+
+pub type SimulatedBaseLayerResult<T> = Result<T, SimulatedBaseLayerError>;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Validate)]
+pub struct SimulatedBaseLayerConfig {
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub block_time_millis: Duration,
+    pub base_fee_per_gas: u64,
+    pub blob_fee: u64,
+    pub script_path: Option<PathBuf>,
+}
+
+impl SerializeConfig for SimulatedBaseLayerConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let mut dump = BTreeMap::from_iter([
+            ser_param(
+                "block_time_millis",
+                &self.block_time_millis.as_millis(),
+                "The time (milliseconds) between simulated L1 blocks. If 0, blocks are only mined \
+                 on demand.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "base_fee_per_gas",
+                &self.base_fee_per_gas,
+                "The base fee per gas (wei) of simulated L1 blocks, unless other gas prices are \
+                 injected.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "blob_fee",
+                &self.blob_fee,
+                "The blob fee (wei) of simulated L1 blocks, unless other gas prices are injected.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+        dump.extend(ser_optional_param(
+            &self.script_path,
+            PathBuf::new(),
+            "script_path",
+            "A JSON file scripting the simulated L1, mapping L1 block numbers to the reorg, \
+             events, proved Starknet block and gas prices of the block.",
+            ParamPrivacyInput::Public,
+        ));
+        dump
+    }
+}
+
+impl Default for SimulatedBaseLayerConfig {
+    fn default() -> Self {
+        Self {
+            block_time_millis: Duration::from_secs(12),
+            base_fee_per_gas: 10_000_000_000,
+            blob_fee: 1,
+            script_path: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct SimulatedGasPrices {
+    pub base_fee_per_gas: u128,
+    pub blob_fee: u128,
+}
+
+/// The scripted blocks of a simulated L1, by block number.
+pub type SimulatedL1Script = BTreeMap<L1BlockNumber, SimulatedBlockScript>;
+
+/// What happens on the simulated L1 when a block is mined.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulatedBlockScript {
+    /// The number of latest blocks replaced by empty blocks on a new fork before the block is
+    /// mined.
+    pub reorg_depth: u64,
+    /// Events logged in the block, like [`SimulatedBaseLayer::add_event`].
+    pub events: Vec<L1Event>,
+    /// The Starknet block proved in the block, like [`SimulatedBaseLayer::prove_block`].
+    pub proved_block: Option<BlockHashAndNumber>,
+    /// The gas prices of the block, which later blocks keep. Gas prices queued through
+    /// [`SimulatedBaseLayer::queue_gas_prices`] apply from the next block.
+    pub gas_prices: Option<SimulatedGasPrices>,
+}
+
+/// A simulated L1. Clones share the same chain, so that one clone can inject events into the chain
+/// that the others read.
+#[derive(Clone, Debug)]
+pub struct SimulatedBaseLayer {
+    chain: Arc<Mutex<SimulatedChain>>,
+}
+
+impl SimulatedBaseLayer {
+    /// Creates a chain with only a genesis block, which follows the script in the config, if there
+    /// is one. Panics if the script can't be read.
+    pub fn new(config: SimulatedBaseLayerConfig) -> Self {
+        let script = match &config.script_path {
+            Some(script_path) => read_script(script_path).unwrap_or_else(|error| {
+                panic!("Failed to read the simulated L1 script {}: {error}", script_path.display())
+            }),
+            None => SimulatedL1Script::new(),
+        };
+        Self::new_with_script(config, script)
+    }
+
+    /// Creates a chain with only a genesis block, which follows the given script.
+    pub fn new_with_script(config: SimulatedBaseLayerConfig, script: SimulatedL1Script) -> Self {
+        let mut chain = SimulatedChain {
+            gas_prices: VecDeque::new(),
+            last_gas_prices: SimulatedGasPrices {
+                base_fee_per_gas: config.base_fee_per_gas.into(),
+                blob_fee: config.blob_fee.into(),
+            },
+            config,
+            blocks: Vec::new(),
+            pending_events: Vec::new(),
+            pending_proved_block: None,
+            script,
+            n_hashes: 0,
+            last_mined_at: Instant::now(),
+            clock: unix_now(),
+        };
+        chain.mine_next_block(block_timestamp(chain.clock));
+        Self { chain: Arc::new(Mutex::new(chain)) }
+    }
+
+    /// Logs the event in the next mined block, and returns the hash of the L1 transaction which
+    /// logged it. Timestamps in the event are overridden by the timestamp of that block.
+    pub fn add_event(&self, event: L1Event) -> L1TxHash {
+        self.chain().add_event(event)
+    }
+
+    /// Proves the Starknet block on L1 in the next mined block, as a state update would.
+    pub fn prove_block(&self, block: BlockHashAndNumber) {
+        self.chain().pending_proved_block = Some(block);
+    }
+
+    /// Sets the gas prices of the next mined blocks, one block per entry. Blocks mined after the
+    /// queued gas prices run out keep the last gas prices.
+    pub fn queue_gas_prices(&self, gas_prices: impl IntoIterator<Item = SimulatedGasPrices>) {
+        self.chain().gas_prices.extend(gas_prices);
+    }
+
+    /// Mines a block with the pending events, regardless of the block time.
+    pub fn mine_block(&self) -> L1BlockReference {
+        let mut chain = self.chain();
+        chain.last_mined_at = Instant::now();
+        chain.clock = unix_now();
+        let timestamp = block_timestamp(chain.clock);
+        chain.mine_next_block(timestamp)
+    }
+
+    /// Replaces the last `depth` blocks with as many empty blocks on a new fork, and returns the
+    /// events logged in the orphaned blocks, which can be added again to be logged on the new fork.
+    /// Starknet blocks proved in the orphaned blocks are no longer proved.
+    pub fn reorg(&self, depth: u64) -> Vec<L1Event> {
+        self.chain().reorg(depth)
+    }
+
+    /// Locks the chain, after mining the blocks due since the last block was mined.
+    fn chain(&self) -> MutexGuard<'_, SimulatedChain> {
+        let mut chain = self.chain.lock().expect("Simulated L1 lock should not be poisoned");
+        chain.mine_due_blocks();
+        chain
+    }
+}
+
+#[async_trait]
+impl BaseLayerContract for SimulatedBaseLayer {
+    type Error = SimulatedBaseLayerError;
+
+    async fn get_proved_block_at(
+        &self,
+        l1_block: L1BlockNumber,
+    ) -> SimulatedBaseLayerResult<BlockHashAndNumber> {
+        let chain = self.chain();
+        let block =
+            chain.block_at(l1_block).ok_or(SimulatedBaseLayerError::NoSuchBlock(l1_block))?;
+        // Like the Starknet contract, which returns zeros before the first state update.
+        Ok(block.proved_block.unwrap_or_default())
+    }
+
+    async fn latest_proved_block(
+        &self,
+        finality: u64,
+    ) -> SimulatedBaseLayerResult<Option<BlockHashAndNumber>> {
+        let chain = self.chain();
+        Ok(chain
+            .latest_block_number(finality)
+            .and_then(|number| chain.block_at(number))
+            .map(|block| block.proved_block.unwrap_or_default()))
+    }
+
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> SimulatedBaseLayerResult<Option<L1BlockNumber>> {
+        Ok(self.chain().latest_block_number(finality))
+    }
+
+    async fn latest_l1_block(
+        &self,
+        finality: u64,
+    ) -> SimulatedBaseLayerResult<Option<L1BlockReference>> {
+        let chain = self.chain();
+        Ok(chain
+            .latest_block_number(finality)
+            .and_then(|number| chain.block_at(number))
+            .map(SimulatedBlock::reference))
+    }
+
+    async fn l1_block_at(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> SimulatedBaseLayerResult<Option<L1BlockReference>> {
+        Ok(self.chain().block_at(block_number).map(SimulatedBlock::reference))
+    }
+
+    async fn events<'a>(
+        &'a self,
+        block_range: RangeInclusive<L1BlockNumber>,
+        event_identifiers: &'a [&'a str],
+    ) -> SimulatedBaseLayerResult<Vec<L1Event>> {
+        let chain = self.chain();
+        Ok(chain
+            .blocks
+            .iter()
+            .filter(|block| block_range.contains(&block.header.number))
+            .flat_map(|block| &block.events)
+            .filter(|(_, event)| event_identifiers.contains(&event_identifier(event)))
+            .map(|(_, event)| event.clone())
+            .collect())
+    }
+
+    async fn transaction_events<'a>(
+        &'a self,
+        l1_tx_hash: L1TxHash,
+        event_identifiers: &'a [&'a str],
+    ) -> SimulatedBaseLayerResult<Vec<L1Event>> {
+        let chain = self.chain();
+        Ok(chain
+            .blocks
+            .iter()
+            .flat_map(|block| &block.events)
+            .filter(|(event_l1_tx_hash, event)| {
+                *event_l1_tx_hash == l1_tx_hash
+                    && event_identifiers.contains(&event_identifier(event))
+            })
+            .map(|(_, event)| event.clone())
+            .collect())
+    }
+
+    async fn get_block_header(
+        &self,
+        block_number: L1BlockNumber,
+    ) -> SimulatedBaseLayerResult<Option<L1BlockHeader>> {
+        Ok(self.chain().block_at(block_number).map(|block| block.header.clone()))
+    }
+
+    /// The simulated L1 has no provider, so this is a no-op.
+    async fn set_provider_url(&mut self, _url: Url) -> SimulatedBaseLayerResult<()> {
+        Ok(())
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum SimulatedBaseLayerError {
+    #[error("L1 block {0} does not exist.")]
+    NoSuchBlock(L1BlockNumber),
+}
+
+#[derive(Debug)]
+struct SimulatedChain {
+    config: SimulatedBaseLayerConfig,
+    // Indexed by block number.
+    blocks: Vec<SimulatedBlock>,
+    pending_events: Vec<(L1TxHash, L1Event)>,
+    pending_proved_block: Option<BlockHashAndNumber>,
+    // The scripts of the blocks which weren't mined yet.
+    script: SimulatedL1Script,
+    gas_prices: VecDeque<SimulatedGasPrices>,
+    last_gas_prices: SimulatedGasPrices,
+    // The number of hashes generated so far, from which the next hash is derived.
+    n_hashes: u64,
+    last_mined_at: Instant,
+    // The time since the epoch at which the last block was mined, kept in full precision so that
+    // block times under a second still add up.
+    clock: Duration,
+}
+
+impl SimulatedChain {
+    fn mine_due_blocks(&mut self) {
+        let block_time = self.config.block_time_millis;
+        if block_time.is_zero() {
+            return;
+        }
+        while self.last_mined_at.elapsed() >= block_time {
+            self.last_mined_at += block_time;
+            self.clock += block_time;
+            self.mine_next_block(block_timestamp(self.clock));
+        }
+    }
+
+    fn add_event(&mut self, event: L1Event) -> L1TxHash {
+        let l1_tx_hash = self.unique_hash().into();
+        self.pending_events.push((l1_tx_hash, event));
+        l1_tx_hash
+    }
+
+    fn reorg(&mut self, depth: u64) -> Vec<L1Event> {
+        let latest_number = self.latest_block().header.number;
+        assert!(depth <= latest_number, "Can't reorg the genesis block, the chain is too short.");
+
+        let fork_number = usize::try_from(latest_number - depth + 1).expect("Block number fits");
+        let orphaned_blocks = self.blocks.split_off(fork_number);
+        // Orphaned events and proved blocks aren't logged on the new fork, but the pending ones are
+        // only logged after it.
+        let pending_events = mem::take(&mut self.pending_events);
+        let pending_proved_block = self.pending_proved_block.take();
+        for orphaned_block in &orphaned_blocks {
+            self.mine_block(orphaned_block.header.timestamp);
+        }
+        self.pending_events = pending_events;
+        self.pending_proved_block = pending_proved_block;
+
+        debug!("Simulated an L1 reorg of depth {depth}.");
+        orphaned_blocks
+            .into_iter()
+            .flat_map(|block| block.events.into_iter().map(|(_, event)| event))
+            .collect()
+    }
+
+    // Mines the next block, after applying its script.
+    fn mine_next_block(&mut self, timestamp: BlockTimestamp) -> L1BlockReference {
+        let number = self.blocks.last().map_or(0, |parent| parent.header.number + 1);
+        if let Some(block_script) = self.script.remove(&number) {
+            if block_script.reorg_depth > 0 {
+                self.reorg(block_script.reorg_depth);
+            }
+            for event in block_script.events {
+                self.add_event(event);
+            }
+            if let Some(proved_block) = block_script.proved_block {
+                self.pending_proved_block = Some(proved_block);
+            }
+            if let Some(gas_prices) = block_script.gas_prices {
+                self.gas_prices.push_front(gas_prices);
+            }
+        }
+        self.mine_block(timestamp)
+    }
+
+    fn mine_block(&mut self, timestamp: BlockTimestamp) -> L1BlockReference {
+        let parent = self.blocks.last();
+        let number = parent.map_or(0, |parent| parent.header.number + 1);
+        let parent_hash = parent.map_or(L1BlockHash::default(), |parent| parent.header.hash);
+        // Timestamps never decrease, even if the clock does.
+        let timestamp = parent.map_or(timestamp, |parent| timestamp.max(parent.header.timestamp));
+        let parent_proved_block = parent.and_then(|parent| parent.proved_block);
+
+        let gas_prices = self.gas_prices.pop_front().unwrap_or(self.last_gas_prices);
+        self.last_gas_prices = gas_prices;
+        let events = mem::take(&mut self.pending_events)
+            .into_iter()
+            .map(|(l1_tx_hash, event)| (l1_tx_hash, logged_event(event, l1_tx_hash, timestamp)))
+            .collect();
+        let proved_block = self.pending_proved_block.take().or(parent_proved_block);
+
+        let block = SimulatedBlock {
+            header: L1BlockHeader {
+                number,
+                hash: self.unique_hash(),
+                parent_hash,
+                timestamp,
+                base_fee_per_gas: gas_prices.base_fee_per_gas,
+                blob_fee: gas_prices.blob_fee,
+            },
+            events,
+            proved_block,
+        };
+        let reference = block.reference();
+        self.blocks.push(block);
+        reference
+    }
+
+    fn unique_hash(&mut self) -> [u8; 32] {
+        self.n_hashes += 1;
+        keccak256(self.n_hashes.to_be_bytes()).0
+    }
+
+    fn latest_block(&self) -> &SimulatedBlock {
+        self.blocks.last().expect("The simulated chain has a genesis block")
+    }
+
+    fn latest_block_number(&self, finality: u64) -> Option<L1BlockNumber> {
+        self.latest_block().header.number.checked_sub(finality)
+    }
+
+    fn block_at(&self, block_number: L1BlockNumber) -> Option<&SimulatedBlock> {
+        self.blocks.get(usize::try_from(block_number).ok()?)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SimulatedBlock {
+    header: L1BlockHeader,
+    events: Vec<(L1TxHash, L1Event)>,
+    // The latest Starknet block proved on L1 as of this block.
+    proved_block: Option<BlockHashAndNumber>,
+}
+
+impl SimulatedBlock {
+    fn reference(&self) -> L1BlockReference {
+        L1BlockReference { number: self.header.number, hash: self.header.hash }
+    }
+}
+
+// Sets the fields of the event which are determined by the L1 transaction and block logging it.
+fn logged_event(event: L1Event, l1_tx_hash: L1TxHash, timestamp: BlockTimestamp) -> L1Event {
+    match event {
+        L1Event::LogMessageToL2 { tx, fee, .. } => {
+            L1Event::LogMessageToL2 { tx, fee, l1_tx_hash: Some(l1_tx_hash), timestamp }
+        }
+        L1Event::MessageToL2CancellationStarted { cancelled_tx, .. } => {
+            L1Event::MessageToL2CancellationStarted {
+                cancelled_tx,
+                cancellation_request_timestamp: timestamp,
+            }
+        }
+        L1Event::ConsumedMessageToL2(_) | L1Event::MessageToL2Canceled(_) => event,
+    }
+}
+
+fn event_identifier(event: &L1Event) -> EventIdentifier {
+    match event {
+        L1Event::ConsumedMessageToL2(_) => CONSUMED_MESSAGE_TO_L2_EVENT_IDENTIFIER,
+        L1Event::LogMessageToL2 { .. } => LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER,
+        L1Event::MessageToL2CancellationStarted { .. } => {
+            MESSAGE_TO_L2_CANCELLATION_STARTED_EVENT_IDENTIFIER
+        }
+        L1Event::MessageToL2Canceled(_) => MESSAGE_TO_L2_CANCELED_EVENT_IDENTIFIER,
+    }
+}
+
+fn read_script(script_path: &Path) -> Result<SimulatedL1Script, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_reader(File::open(script_path)?)?)
+}
+
+fn unix_now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("The clock is after the epoch")
+}
+
+// Rounds the time since the epoch to the nearest second.
+fn block_timestamp(time: Duration) -> BlockTimestamp {
+    BlockTimestamp((time + Duration::from_millis(500)).as_secs())
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber, BlockTimestamp};
+use starknet_api::core::Nonce;
+use starknet_api::felt;
+use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::L1HandlerTransaction;
+
+use crate::constants::{
+    LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER,
+    MESSAGE_TO_L2_CANCELLATION_STARTED_EVENT_IDENTIFIER,
+};
+use crate::simulated_base_layer::{
+    SimulatedBaseLayer,
+    SimulatedBaseLayerConfig,
+    SimulatedBaseLayerError,
+    SimulatedBlockScript,
+    SimulatedGasPrices,
+    SimulatedL1Script,
+};
+use crate::{BaseLayerContract, L1Event};
+
+const ALL_EVENTS: &[&str] =
+    &[LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER, MESSAGE_TO_L2_CANCELLATION_STARTED_EVENT_IDENTIFIER];
+
+fn on_demand_base_layer() -> SimulatedBaseLayer {
+    SimulatedBaseLayer::new(SimulatedBaseLayerConfig {
+        block_time_millis: Duration::ZERO,
+        ..Default::default()
+    })
+}
+
+fn message_to_l2(nonce: u64) -> L1Event {
+    L1Event::LogMessageToL2 {
+        tx: L1HandlerTransaction { nonce: Nonce(felt!(nonce)), ..Default::default() },
+        fee: Fee(1),
+        l1_tx_hash: None,
+        timestamp: BlockTimestamp(0),
+    }
+}
+
+#[tokio::test]
+async fn events_are_logged_in_the_next_mined_block() {
+    let base_layer = on_demand_base_layer();
+    assert_eq!(base_layer.latest_l1_block_number(0).await, Ok(Some(0)));
+
+    let l1_tx_hash = base_layer.add_event(message_to_l2(1));
+    let cancellation_l1_tx_hash = base_layer.add_event(L1Event::MessageToL2CancellationStarted {
+        cancelled_tx: Default::default(),
+        cancellation_request_timestamp: BlockTimestamp(0),
+    });
+    assert_eq!(base_layer.events(0..=0, ALL_EVENTS).await, Ok(vec![]));
+
+    let block = base_layer.mine_block();
+    assert_eq!(block.number, 1);
+    assert_eq!(base_layer.latest_l1_block(0).await, Ok(Some(block)));
+    let timestamp = base_layer.get_block_header(1).await.unwrap().unwrap().timestamp;
+
+    let expected_message = L1Event::LogMessageToL2 {
+        tx: L1HandlerTransaction { nonce: Nonce(felt!(1_u8)), ..Default::default() },
+        fee: Fee(1),
+        l1_tx_hash: Some(l1_tx_hash),
+        timestamp,
+    };
+    assert_eq!(
+        base_layer.events(0..=1, &[LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER]).await,
+        Ok(vec![expected_message.clone()])
+    );
+    assert_eq!(base_layer.events(0..=1, ALL_EVENTS).await.unwrap().len(), 2);
+    assert_eq!(
+        base_layer.transaction_events(l1_tx_hash, ALL_EVENTS).await,
+        Ok(vec![expected_message])
+    );
+    assert_eq!(
+        base_layer.transaction_events(cancellation_l1_tx_hash, ALL_EVENTS).await,
+        Ok(vec![L1Event::MessageToL2CancellationStarted {
+            cancelled_tx: Default::default(),
+            cancellation_request_timestamp: timestamp,
+        }])
+    );
+}
+
+#[tokio::test]
+async fn proved_blocks() {
+    let base_layer = on_demand_base_layer();
+    let proved_block = BlockHashAndNumber { number: BlockNumber(7), hash: BlockHash(felt!(7_u8)) };
+
+    base_layer.prove_block(proved_block);
+    base_layer.mine_block();
+    base_layer.mine_block();
+
+    // Before the first state update, the proved block is zeroed, like in the Starknet contract.
+    assert_eq!(base_layer.get_proved_block_at(0).await, Ok(BlockHashAndNumber::default()));
+    assert_eq!(base_layer.get_proved_block_at(1).await, Ok(proved_block));
+    assert_eq!(base_layer.latest_proved_block(1).await, Ok(Some(proved_block)));
+    assert_eq!(base_layer.latest_proved_block(3).await, Ok(None));
+    assert_eq!(
+        base_layer.get_proved_block_at(3).await,
+        Err(SimulatedBaseLayerError::NoSuchBlock(3))
+    );
+}
+
+#[tokio::test]
+async fn gas_price_series() {
+    let base_layer = on_demand_base_layer();
+    let config = SimulatedBaseLayerConfig::default();
+    let low = SimulatedGasPrices { base_fee_per_gas: 1, blob_fee: 2 };
+    let high = SimulatedGasPrices { base_fee_per_gas: 100, blob_fee: 200 };
+
+    base_layer.queue_gas_prices([low, high]);
+    for _ in 0..3 {
+        base_layer.mine_block();
+    }
+
+    let mut gas_prices = Vec::new();
+    for block_number in 0..=3 {
+        let header = base_layer.get_block_header(block_number).await.unwrap().unwrap();
+        gas_prices.push(SimulatedGasPrices {
+            base_fee_per_gas: header.base_fee_per_gas,
+            blob_fee: header.blob_fee,
+        });
+    }
+    let initial = SimulatedGasPrices {
+        base_fee_per_gas: config.base_fee_per_gas.into(),
+        blob_fee: config.blob_fee.into(),
+    };
+    // Once the queued gas prices run out, the last ones stay.
+    assert_eq!(gas_prices, vec![initial, low, high, high]);
+}
+
+#[tokio::test]
+async fn reorg() {
+    let base_layer = on_demand_base_layer();
+    base_layer.mine_block();
+    base_layer.add_event(message_to_l2(1));
+    base_layer.prove_block(BlockHashAndNumber { number: BlockNumber(1), ..Default::default() });
+    let orphaned_block = base_layer.mine_block();
+    let pending_l1_tx_hash = base_layer.add_event(message_to_l2(2));
+
+    let orphaned_events = base_layer.reorg(1);
+
+    assert_eq!(orphaned_events.len(), 1);
+    assert_matches!(
+        &orphaned_events[0],
+        L1Event::LogMessageToL2 { tx, .. } if tx.nonce == Nonce(felt!(1_u8))
+    );
+    let new_block = base_layer.l1_block_at(2).await.unwrap().unwrap();
+    assert_eq!(new_block.number, orphaned_block.number);
+    assert_ne!(new_block.hash, orphaned_block.hash);
+    let parent_hash = base_layer.get_block_header(2).await.unwrap().unwrap().parent_hash;
+    assert_eq!(parent_hash, base_layer.l1_block_at(1).await.unwrap().unwrap().hash);
+    assert_eq!(base_layer.events(0..=2, ALL_EVENTS).await, Ok(vec![]));
+    assert_eq!(base_layer.get_proved_block_at(2).await, Ok(BlockHashAndNumber::default()));
+
+    // Pending events are logged after the new fork.
+    base_layer.mine_block();
+    assert_eq!(
+        base_layer.transaction_events(pending_l1_tx_hash, ALL_EVENTS).await.unwrap().len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn blocks_are_mined_as_time_passes() {
+    let block_time = Duration::from_millis(10);
+    let base_layer = SimulatedBaseLayer::new(SimulatedBaseLayerConfig {
+        block_time_millis: block_time,
+        ..Default::default()
+    });
+
+    tokio::time::sleep(3 * block_time).await;
+
+    let latest_block_number = base_layer.latest_l1_block_number(0).await.unwrap().unwrap();
+    assert!(latest_block_number >= 3, "Expected at least 3 blocks, got {latest_block_number}.");
+}
+
+#[tokio::test]
+async fn sub_second_block_times_add_up() {
+    let block_time = Duration::from_millis(400);
+    let base_layer = SimulatedBaseLayer::new(SimulatedBaseLayerConfig {
+        block_time_millis: block_time,
+        ..Default::default()
+    });
+    // Make 5 blocks due, without waiting for them.
+    base_layer.chain.lock().unwrap().last_mined_at -= 5 * block_time;
+
+    let latest_block_number = base_layer.latest_l1_block_number(0).await.unwrap().unwrap();
+    assert!(latest_block_number >= 5, "Expected at least 5 blocks, got {latest_block_number}.");
+    let genesis_timestamp = base_layer.get_block_header(0).await.unwrap().unwrap().timestamp;
+    let timestamp = base_layer.get_block_header(5).await.unwrap().unwrap().timestamp;
+    assert_eq!(timestamp.0 - genesis_timestamp.0, 2);
+}
+
+#[tokio::test]
+async fn scripted_blocks() {
+    let high = SimulatedGasPrices { base_fee_per_gas: 100, blob_fee: 200 };
+    let script = SimulatedL1Script::from([
+        (
+            1,
+            SimulatedBlockScript {
+                events: vec![message_to_l2(1)],
+                gas_prices: Some(high),
+                ..Default::default()
+            },
+        ),
+        (
+            2,
+            SimulatedBlockScript {
+                proved_block: Some(BlockHashAndNumber {
+                    number: BlockNumber(7),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ),
+        (
+            3,
+            SimulatedBlockScript {
+                reorg_depth: 1,
+                events: vec![message_to_l2(2)],
+                ..Default::default()
+            },
+        ),
+    ]);
+    let script_file = tempfile::NamedTempFile::new().unwrap();
+    serde_json::to_writer(&script_file, &script).unwrap();
+    let base_layer = SimulatedBaseLayer::new(SimulatedBaseLayerConfig {
+        block_time_millis: Duration::ZERO,
+        script_path: Some(script_file.path().to_path_buf()),
+        ..Default::default()
+    });
+
+    base_layer.mine_block();
+    let orphaned_block = base_layer.mine_block();
+    assert_eq!(base_layer.get_proved_block_at(2).await.unwrap().number, BlockNumber(7));
+    base_layer.mine_block();
+
+    let messages = base_layer.events(0..=3, ALL_EVENTS).await.unwrap();
+    let nonces: Vec<_> = messages
+        .iter()
+        .map(|message| assert_matches!(message, L1Event::LogMessageToL2 { tx, .. } => tx.nonce))
+        .collect();
+    assert_eq!(nonces, vec![Nonce(felt!(1_u8)), Nonce(felt!(2_u8))]);
+    // Block 2 was replaced by the reorg before block 3 was mined, and its proved block with it.
+    assert_ne!(base_layer.l1_block_at(2).await.unwrap().unwrap().hash, orphaned_block.hash);
+    assert_eq!(base_layer.get_proved_block_at(3).await, Ok(BlockHashAndNumber::default()));
+    // The scripted gas prices stay.
+    let header = base_layer.get_block_header(3).await.unwrap().unwrap();
+    assert_eq!((header.base_fee_per_gas, header.blob_fee), (100, 200));
+}
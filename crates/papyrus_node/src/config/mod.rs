@@ -12,6 +12,8 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{env, fs, io};
 
+use apollo_central_sync::checkpoint::CheckpointConfig;
+use apollo_central_sync::sources::archive::ArchiveSourceConfig;
 use apollo_central_sync::sources::central::CentralSourceConfig;
 use apollo_central_sync::SyncConfig;
 use apollo_config::dumping::{
@@ -36,6 +38,7 @@ use clap::{arg, value_parser, Arg, ArgMatches, Command};
 use itertools::{chain, Itertools};
 use lazy_static::lazy_static;
 use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerConfig;
+use papyrus_base_layer::simulated_base_layer::SimulatedBaseLayerConfig;
 use papyrus_monitoring_gateway::MonitoringGatewayConfig;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -54,7 +57,14 @@ pub struct NodeConfig {
     #[validate]
     pub rpc: RpcConfig,
     pub central: CentralSourceConfig,
+    /// If set, the sync reads the blocks from a local archive instead of `central`.
+    pub archive_source: Option<ArchiveSourceConfig>,
+    /// If set, a node with an empty storage starts syncing from this trusted checkpoint instead of
+    /// from the genesis block.
+    pub checkpoint: Option<CheckpointConfig>,
     pub base_layer: EthereumBaseLayerConfig,
+    /// If set, the sync uses an in-process simulated L1 instead of `base_layer`.
+    pub simulated_base_layer: Option<SimulatedBaseLayerConfig>,
     pub monitoring_gateway: MonitoringGatewayConfig,
     #[validate]
     pub storage: StorageConfig,
@@ -76,7 +86,10 @@ impl Default for NodeConfig {
     fn default() -> Self {
         NodeConfig {
             central: CentralSourceConfig::default(),
+            archive_source: None,
+            checkpoint: None,
             base_layer: EthereumBaseLayerConfig::default(),
+            simulated_base_layer: None,
             #[cfg(feature = "rpc")]
             rpc: RpcConfig::default(),
             monitoring_gateway: MonitoringGatewayConfig::default(),
@@ -96,7 +109,10 @@ impl SerializeConfig for NodeConfig {
         #[allow(unused_mut)]
         let mut sub_configs = vec![
             prepend_sub_config_name(self.central.dump(), "central"),
+            ser_optional_sub_config(&self.archive_source, "archive_source"),
+            ser_optional_sub_config(&self.checkpoint, "checkpoint"),
             prepend_sub_config_name(self.base_layer.dump(), "base_layer"),
+            ser_optional_sub_config(&self.simulated_base_layer, "simulated_base_layer"),
             prepend_sub_config_name(self.monitoring_gateway.dump(), "monitoring_gateway"),
             prepend_sub_config_name(self.storage.dump(), "storage"),
             ser_optional_sub_config(&self.sync, "sync"),
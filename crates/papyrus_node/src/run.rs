@@ -2,13 +2,17 @@
 #[path = "run_test.rs"]
 mod run_test;
 
+use std::cmp::min;
 use std::future;
 use std::process::exit;
 use std::sync::Arc;
 use std::time::Duration;
 
-use apollo_central_sync::sources::base_layer::EthereumBaseLayerSource;
+use apollo_central_sync::checkpoint::CheckpointConfig;
+use apollo_central_sync::sources::archive::{ArchiveSource, ArchiveSourceConfig};
+use apollo_central_sync::sources::base_layer::ConfiguredBaseLayerSource;
 use apollo_central_sync::sources::central::{CentralError, CentralSource, CentralSourceConfig};
+use apollo_central_sync::sources::configured_central_source::ConfiguredCentralSource;
 use apollo_central_sync::sources::pending::PendingSource;
 use apollo_central_sync::{StateSync as CentralStateSync, SyncConfig as CentralSyncConfig};
 use apollo_class_manager_types::{EmptyClassManagerClient, SharedClassManagerClient};
@@ -26,16 +30,20 @@ use apollo_starknet_client::reader::objects::pending_data::{
     PendingBlockOrDeprecated,
 };
 use apollo_starknet_client::reader::PendingData;
+use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::state::StateStorageReader;
 use apollo_storage::storage_metrics::update_storage_metrics;
 use apollo_storage::{open_storage, StorageReader, StorageWriter};
 use futures::StreamExt;
-use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerConfig;
+use papyrus_base_layer::configured_base_layer::ConfiguredBaseLayer;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
+use papyrus_base_layer::simulated_base_layer::SimulatedBaseLayer;
 use papyrus_common::metrics::COLLECT_PROFILING_METRICS;
 use papyrus_common::pending_classes::PendingClasses;
 use papyrus_monitoring_gateway::MonitoringServer;
-use starknet_api::block::{BlockHash, BlockHashAndNumber};
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
 use starknet_api::felt;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tokio::task::JoinHandle;
 use tracing::metadata::LevelFilter;
 use tracing::{debug_span, error, info, warn, Instrument};
@@ -64,8 +72,12 @@ pub struct PapyrusResources {
     pub local_peer_id: String,
     pub shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pub pending_data: Arc<RwLock<PendingData>>,
+    // Notified whenever the pending data is updated.
+    pub pending_data_updates: watch::Sender<()>,
     pub pending_classes: Arc<RwLock<PendingClasses>>,
     pub class_manager_client: SharedClassManagerClient,
+    // Shared by the sync and the RPC server, so that they see the same simulated L1.
+    pub base_layer: ConfiguredBaseLayerSource,
 }
 
 /// Struct which allows configuring how the node will run.
@@ -96,9 +108,18 @@ impl PapyrusResources {
             }),
             ..Default::default()
         }));
+        let (pending_data_updates, _) = watch::channel(());
         let pending_classes = Arc::new(RwLock::new(PendingClasses::default()));
         // TODO(noamsp): Remove this and use the real client instead once implemented.
         let class_manager_client = Arc::new(EmptyClassManagerClient);
+        let base_layer = match &config.simulated_base_layer {
+            Some(simulated_base_layer_config) => ConfiguredBaseLayer::Simulated(
+                SimulatedBaseLayer::new(simulated_base_layer_config.clone()),
+            ),
+            None => ConfiguredBaseLayer::Ethereum(EthereumBaseLayerContract::new(
+                config.base_layer.clone(),
+            )),
+        };
         Ok(Self {
             storage_reader,
             storage_writer,
@@ -106,8 +127,10 @@ impl PapyrusResources {
             local_peer_id,
             shared_highest_block,
             pending_data,
+            pending_data_updates,
             pending_classes,
             class_manager_client,
+            base_layer,
         })
     }
 }
@@ -122,6 +145,7 @@ fn build_network_manager(
         network_config.clone(),
         Some(VERSION_FULL.to_string()),
         None,
+        None,
     );
     let local_peer_id = network_manager.get_local_peer_id();
 
@@ -133,17 +157,21 @@ async fn spawn_rpc_server(
     config: &NodeConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Receiver<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage_reader: StorageReader,
+    base_layer: ConfiguredBaseLayerSource,
 ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
     let (_, server_handle) = run_server(
         &config.rpc,
         shared_highest_block,
         pending_data,
+        pending_data_updates,
         pending_classes,
         storage_reader,
         VERSION_FULL,
         None,
+        Some(base_layer),
     )
     .await?;
     Ok(tokio::spawn(async move {
@@ -157,8 +185,10 @@ async fn spawn_rpc_server(
     _config: &NodeConfig,
     _shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     _pending_data: Arc<RwLock<PendingData>>,
+    _pending_data_updates: watch::Receiver<()>,
     _pending_classes: Arc<RwLock<PendingClasses>>,
     _storage_reader: StorageReader,
+    _base_layer: ConfiguredBaseLayerSource,
 ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
     Ok(tokio::spawn(future::pending()))
 }
@@ -179,33 +209,66 @@ fn spawn_monitoring_server(
     Ok(tokio::spawn(async move { Ok(monitoring_server.run_server().await?) }))
 }
 
+// The first block the sync will ask its source for. An empty storage is first seeded from the
+// checkpoint, if there is one.
+fn next_block_to_sync(
+    storage_reader: &StorageReader,
+    checkpoint_config: Option<&CheckpointConfig>,
+) -> anyhow::Result<BlockNumber> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let next_block = min(txn.get_header_marker()?, txn.get_state_marker()?);
+    Ok(match checkpoint_config {
+        Some(checkpoint_config) if next_block == BlockNumber(0) => {
+            checkpoint_config.block_number.unchecked_next()
+        }
+        _ => next_block,
+    })
+}
+
 async fn run_sync(
-    configs: (CentralSyncConfig, CentralSourceConfig, EthereumBaseLayerConfig),
+    configs: (
+        CentralSyncConfig,
+        CentralSourceConfig,
+        Option<ArchiveSourceConfig>,
+        Option<CheckpointConfig>,
+    ),
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Sender<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage: (StorageReader, StorageWriter),
+    base_layer_source: ConfiguredBaseLayerSource,
 ) -> anyhow::Result<()> {
-    let (sync_config, central_config, base_layer_config) = configs;
+    let (sync_config, central_config, archive_source_config, checkpoint_config) = configs;
     let (storage_reader, storage_writer) = storage;
-    let central_source =
-        CentralSource::new(central_config.clone(), VERSION_FULL, storage_reader.clone())
-            .map_err(CentralError::ClientCreation)?;
+    let central_source = match archive_source_config {
+        Some(config) => ConfiguredCentralSource::Archive(ArchiveSource::new(
+            config,
+            next_block_to_sync(&storage_reader, checkpoint_config.as_ref())?,
+        )?),
+        None => ConfiguredCentralSource::Central(
+            CentralSource::new(central_config.clone(), VERSION_FULL, storage_reader.clone())
+                .map_err(CentralError::ClientCreation)?,
+        ),
+    };
     let pending_source =
         PendingSource::new(central_config, VERSION_FULL).map_err(CentralError::ClientCreation)?;
-    let base_layer_source = Some(EthereumBaseLayerSource::new(base_layer_config));
     let class_manager_client = None;
     let sync = CentralStateSync::new(
         sync_config,
         shared_highest_block,
         pending_data,
+        pending_data_updates,
         pending_classes,
         central_source,
         pending_source,
-        base_layer_source,
+        Some(base_layer_source),
         storage_reader.clone(),
         storage_writer,
         class_manager_client,
+        checkpoint_config,
+        // Papyrus doesn't take part in consensus.
+        None,
     );
     Ok(sync.run().await?)
 }
@@ -218,8 +281,10 @@ async fn spawn_sync_client(
     config: &NodeConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
+    pending_data_updates: watch::Sender<()>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     class_manager_client: SharedClassManagerClient,
+    base_layer: ConfiguredBaseLayerSource,
 ) -> JoinHandle<anyhow::Result<()>> {
     match (config.sync, config.p2p_sync) {
         (Some(_), Some(_)) => {
@@ -227,14 +292,21 @@ async fn spawn_sync_client(
         }
         (None, None) => tokio::spawn(future::pending()),
         (Some(sync_config), None) => {
-            let configs = (sync_config, config.central.clone(), config.base_layer.clone());
+            let configs = (
+                sync_config,
+                config.central.clone(),
+                config.archive_source.clone(),
+                config.checkpoint.clone(),
+            );
             let storage = (storage_reader.clone(), storage_writer);
             tokio::spawn(run_sync(
                 configs,
                 shared_highest_block,
                 pending_data,
+                pending_data_updates,
                 pending_classes,
                 storage,
+                base_layer,
             ))
         }
         (None, Some(p2p_sync_client_config)) => {
@@ -261,6 +333,8 @@ async fn spawn_sync_client(
                 p2p_sync_client_channels,
                 futures::stream::pending().boxed(),
                 class_manager_client,
+                // Papyrus doesn't take part in consensus.
+                None,
             );
             tokio::spawn(async move { Ok(p2p_sync.run().await.map(|_never| ())?) })
         }
@@ -337,8 +411,10 @@ async fn run_threads(
             &config,
             resources.shared_highest_block.clone(),
             resources.pending_data.clone(),
+            resources.pending_data_updates.subscribe(),
             resources.pending_classes.clone(),
             resources.storage_reader.clone(),
+            resources.base_layer.clone(),
         )
         .await?
     };
@@ -365,8 +441,10 @@ async fn run_threads(
             &config,
             resources.shared_highest_block,
             resources.pending_data,
+            resources.pending_data_updates,
             resources.pending_classes,
             resources.class_manager_client.clone(),
+            resources.base_layer,
         )
         .await
     };
@@ -22,7 +22,13 @@ use crate::core::{
     StateDiffCommitment,
     TransactionCommitment,
 };
-use crate::crypto::utils::{verify_message_hash_signature, CryptoError, Signature};
+use crate::crypto::utils::{
+    verify_message_hash_signature,
+    CryptoError,
+    PublicKey,
+    RawSignature,
+    Signature,
+};
 use crate::data_availability::L1DataAvailabilityMode;
 use crate::execution_resources::GasAmount;
 use crate::hash::StarkHash;
@@ -603,6 +609,37 @@ pub struct BlockInfo {
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub struct BlockSignature(pub Signature);
 
+/// A member of the consensus committee which decided a block, along with its voting weight and the
+/// public key its votes are signed with.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct CommitteeMember {
+    pub address: ContractAddress,
+    pub weight: u128,
+    pub public_key: PublicKey,
+}
+
+/// The signature of a single committee member on its precommit for a decided block.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct PrecommitSignature {
+    pub voter: ContractAddress,
+    pub signature: RawSignature,
+}
+
+/// Proof that a [Block](`crate::block::Block`) was decided by the consensus committee: the signed
+/// precommits of a quorum of the committee, for the same round and proposal commitment.
+// TODO(Matan): Reference the committee by the staking contract's epoch instead of embedding it, so
+// that it can be checked against L1.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct CommitCertificate {
+    pub block_number: BlockNumber,
+    pub round: u32,
+    /// The proposal commitment the committee voted on.
+    pub block_hash: BlockHash,
+    /// The committee of the height, in the order used by consensus.
+    pub committee: Vec<CommitteeMember>,
+    pub precommits: Vec<PrecommitSignature>,
+}
+
 /// The error type returned from the block verification functions.
 #[derive(thiserror::Error, Clone, Debug)]
 pub enum BlockVerificationError {
@@ -8,7 +8,13 @@ use super::event_commitment::{calculate_event_commitment, EventLeafElement};
 use super::receipt_commitment::{calculate_receipt_commitment, ReceiptElement};
 use super::state_diff_hash::calculate_state_diff_hash;
 use super::transaction_commitment::{calculate_transaction_commitment, TransactionLeafElement};
-use crate::block::{BlockHash, BlockHeaderWithoutHash, GasPricePerToken, StarknetVersion};
+use crate::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    GasPricePerToken,
+    StarknetVersion,
+};
 use crate::core::{
     ascii_as_felt,
     EventCommitment,
@@ -108,6 +114,25 @@ pub struct BlockHeaderCommitments {
     pub concatenated_counts: Felt,
 }
 
+impl BlockHeaderCommitments {
+    /// Returns the commitments of the block from its header, or None if the header doesn't hold
+    /// all of them (as in blocks before Starknet v0.13.2).
+    pub fn from_header(header: &BlockHeader) -> Option<Self> {
+        Some(Self {
+            transaction_commitment: header.transaction_commitment?,
+            event_commitment: header.event_commitment?,
+            receipt_commitment: header.receipt_commitment?,
+            state_diff_commitment: header.state_diff_commitment?,
+            concatenated_counts: concat_counts(
+                header.n_transactions,
+                header.n_events,
+                header.state_diff_length?,
+                header.block_header_without_hash.l1_da_mode,
+            ),
+        })
+    }
+}
+
 /// Poseidon (
 ///     block_hash_constant, block_number, global_state_root, sequencer_address,
 ///     block_timestamp, concat_counts, state_diff_hash, transaction_commitment,
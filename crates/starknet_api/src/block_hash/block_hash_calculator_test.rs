@@ -4,6 +4,7 @@ use starknet_types_core::felt::Felt;
 use super::concat_counts;
 use crate::block::{
     BlockHash,
+    BlockHeader,
     BlockHeaderWithoutHash,
     BlockNumber,
     BlockTimestamp,
@@ -218,3 +219,35 @@ fn change_field_of_hash_input() {
     );
     // TODO(Aviv, 10/06/2024): add tests that changes the first hash input, and the const zero.
 }
+
+#[test]
+fn block_header_commitments_from_header() {
+    let header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            l1_da_mode: L1DataAvailabilityMode::Blob,
+            ..Default::default()
+        },
+        state_diff_commitment: Some(StateDiffCommitment(PoseidonHash(felt!("0x1")))),
+        state_diff_length: Some(2),
+        transaction_commitment: Some(TransactionCommitment(felt!("0x3"))),
+        event_commitment: Some(EventCommitment(felt!("0x4"))),
+        n_transactions: 4,
+        n_events: 3,
+        receipt_commitment: Some(ReceiptCommitment(felt!("0x5"))),
+        ..Default::default()
+    };
+    assert_eq!(
+        BlockHeaderCommitments::from_header(&header),
+        Some(BlockHeaderCommitments {
+            transaction_commitment: TransactionCommitment(felt!("0x3")),
+            event_commitment: EventCommitment(felt!("0x4")),
+            receipt_commitment: ReceiptCommitment(felt!("0x5")),
+            state_diff_commitment: StateDiffCommitment(PoseidonHash(felt!("0x1"))),
+            concatenated_counts: concat_counts(4, 3, 2, L1DataAvailabilityMode::Blob),
+        })
+    );
+
+    // Headers of blocks before Starknet v0.13.2 don't hold the receipt commitment.
+    let header = BlockHeader { receipt_commitment: None, ..header };
+    assert_eq!(BlockHeaderCommitments::from_header(&header), None);
+}
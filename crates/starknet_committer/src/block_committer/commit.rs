@@ -1,8 +1,20 @@
 use std::collections::HashMap;
+use std::sync::LazyLock;
 
-use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::core::{ascii_as_felt, ClassHash, ContractAddress, GlobalRoot, Nonce};
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia::patricia_merkle_tree::node_data::leaf::Leaf;
+use starknet_patricia::patricia_merkle_tree::traversal::{
+    fetch_patricia_witnesses,
+    fetch_replaced_patricia_nodes,
+    PatriciaPaths,
+    TraversalResult,
+};
 use starknet_patricia::patricia_merkle_tree::types::{NodeIndex, SortedLeafIndices};
 use starknet_patricia_storage::map_storage::MapStorage;
+use starknet_patricia_storage::storage_trait::{DbKey, DbValue, ReadStorage};
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Poseidon, StarkHash};
 use tracing::{info, warn};
 
 use crate::block_committer::errors::BlockCommitmentError;
@@ -11,6 +23,7 @@ use crate::block_committer::input::{
     Config,
     ConfigImpl,
     Input,
+    StarknetStorageValue,
     StateDiff,
 };
 use crate::forest::filled_forest::FilledForest;
@@ -18,10 +31,14 @@ use crate::forest::original_skeleton_forest::{ForestSortedIndices, OriginalSkele
 use crate::forest::updated_skeleton_forest::UpdatedSkeletonForest;
 use crate::hash_function::hash::TreeHashFunctionImpl;
 use crate::patricia_merkle_tree::leaf::leaf_impl::ContractState;
-use crate::patricia_merkle_tree::types::class_hash_into_node_index;
+use crate::patricia_merkle_tree::types::{class_hash_into_node_index, CompiledClassHash};
 
 type BlockCommitmentResult<T> = Result<T, BlockCommitmentError>;
 
+static STARKNET_STATE_V0: LazyLock<Felt> = LazyLock::new(|| {
+    ascii_as_felt("STARKNET_STATE_V0").expect("ascii_as_felt failed for 'STARKNET_STATE_V0'")
+});
+
 pub async fn commit_block(input: Input<ConfigImpl>) -> BlockCommitmentResult<FilledForest> {
     let (mut storage_tries_indices, mut contracts_trie_indices, mut classes_trie_indices) =
         get_all_modified_indices(&input.state_diff);
@@ -78,6 +95,121 @@ pub async fn commit_block(input: Input<ConfigImpl>) -> BlockCommitmentResult<Fil
     Ok(filled_forest)
 }
 
+/// Returns the global state root, as it appears in the block header, of the tries with the given
+/// roots.
+pub fn calculate_global_root(
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+) -> GlobalRoot {
+    if classes_trie_root_hash == HashOutput::ROOT_OF_EMPTY_TREE {
+        return GlobalRoot(contracts_trie_root_hash.0);
+    }
+    GlobalRoot(Poseidon::hash_array(&[
+        *STARKNET_STATE_V0,
+        contracts_trie_root_hash.0,
+        classes_trie_root_hash.0,
+    ]))
+}
+
+/// Fetches from the storage the nodes required to commit the given state diff on top of the tries
+/// with the given roots. The result can be used as the storage of the commitment's input, instead
+/// of the entire storage.
+pub fn fetch_commitment_witnesses(
+    storage: &impl ReadStorage,
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+    state_diff: &StateDiff,
+) -> TraversalResult<HashMap<DbKey, DbValue>> {
+    Ok(fetch_forest_nodes(
+        storage,
+        contracts_trie_root_hash,
+        classes_trie_root_hash,
+        state_diff,
+        false,
+    )?
+    .into_iter()
+    .collect())
+}
+
+/// Returns the keys of the nodes that are replaced when the given state diff is committed on top
+/// of the tries with the given roots, once for each tree they're replaced in. The nodes that
+/// replace them are the nodes of the resulting filled forest, see [FilledForest::new_nodes].
+pub fn fetch_replaced_nodes(
+    storage: &impl ReadStorage,
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+    state_diff: &StateDiff,
+) -> TraversalResult<Vec<DbKey>> {
+    Ok(fetch_forest_nodes(
+        storage,
+        contracts_trie_root_hash,
+        classes_trie_root_hash,
+        state_diff,
+        true,
+    )?
+    .into_iter()
+    .map(|(key, _value)| key)
+    .collect())
+}
+
+// Fetches the nodes of each of the tries that the state diff modifies. If `replaced_only` is set,
+// only the nodes that the commitment replaces are fetched, and otherwise all of its witnesses.
+fn fetch_forest_nodes(
+    storage: &impl ReadStorage,
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+    state_diff: &StateDiff,
+    replaced_only: bool,
+) -> TraversalResult<Vec<(DbKey, DbValue)>> {
+    let (mut storage_tries_indices, mut contracts_trie_indices, mut classes_trie_indices) =
+        get_all_modified_indices(state_diff);
+    let contracts_trie_paths = fetch_tree_nodes::<ContractState>(
+        storage,
+        contracts_trie_root_hash,
+        SortedLeafIndices::new(&mut contracts_trie_indices),
+        replaced_only,
+    )?;
+    let classes_trie_paths = fetch_tree_nodes::<CompiledClassHash>(
+        storage,
+        classes_trie_root_hash,
+        SortedLeafIndices::new(&mut classes_trie_indices),
+        replaced_only,
+    )?;
+
+    let mut nodes = Vec::new();
+    for (address, indices) in storage_tries_indices.iter_mut() {
+        let storage_root_hash = contracts_trie_paths
+            .get_leaf(&contract_address_into_node_index(address))
+            .map(|contract_state| contract_state.storage_root_hash)
+            .unwrap_or_default();
+        nodes.extend(
+            fetch_tree_nodes::<StarknetStorageValue>(
+                storage,
+                storage_root_hash,
+                SortedLeafIndices::new(indices),
+                replaced_only,
+            )?
+            .into_storage_entries(),
+        );
+    }
+    nodes.extend(contracts_trie_paths.into_storage_entries());
+    nodes.extend(classes_trie_paths.into_storage_entries());
+    Ok(nodes)
+}
+
+fn fetch_tree_nodes<L: Leaf>(
+    storage: &impl ReadStorage,
+    root_hash: HashOutput,
+    sorted_leaf_indices: SortedLeafIndices<'_>,
+    replaced_only: bool,
+) -> TraversalResult<PatriciaPaths<L>> {
+    if replaced_only {
+        fetch_replaced_patricia_nodes(storage, root_hash, sorted_leaf_indices)
+    } else {
+        fetch_patricia_witnesses(storage, root_hash, sorted_leaf_indices)
+    }
+}
+
 /// Compares the previous state's nonce and class hash with the given in the state diff.
 /// In case of trivial update, logs out a warning for trivial state diff update.
 fn check_trivial_nonce_and_class_hash_updates(
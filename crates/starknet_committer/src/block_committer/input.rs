@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::state::ThinStateDiff;
 use starknet_patricia::hash::hash_trait::HashOutput;
 use starknet_patricia::patricia_merkle_tree::node_data::leaf::{LeafModifications, SkeletonLeaf};
 use starknet_patricia::patricia_merkle_tree::types::NodeIndex;
@@ -101,6 +102,38 @@ pub struct Input<C: Config> {
     pub config: C,
 }
 
+impl From<ThinStateDiff> for StateDiff {
+    /// Deprecated declared classes don't appear in the classes trie, so they're omitted.
+    fn from(state_diff: ThinStateDiff) -> Self {
+        Self {
+            address_to_class_hash: state_diff.deployed_contracts.into_iter().collect(),
+            address_to_nonce: state_diff.nonces.into_iter().collect(),
+            class_hash_to_compiled_class_hash: state_diff
+                .declared_classes
+                .into_iter()
+                .map(|(class_hash, compiled_class_hash)| {
+                    (class_hash, CompiledClassHash(compiled_class_hash.0))
+                })
+                .collect(),
+            storage_updates: state_diff
+                .storage_diffs
+                .into_iter()
+                .map(|(address, updates)| {
+                    (
+                        address,
+                        updates
+                            .into_iter()
+                            .map(|(key, value)| {
+                                (StarknetStorageKey(key.into()), StarknetStorageValue(value))
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
 impl StateDiff {
     pub(crate) fn accessed_addresses(&self) -> HashSet<&ContractAddress> {
         HashSet::from_iter(
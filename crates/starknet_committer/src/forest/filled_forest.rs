@@ -2,11 +2,12 @@ use std::collections::HashMap;
 
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_patricia::hash::hash_trait::HashOutput;
-use starknet_patricia::patricia_merkle_tree::filled_tree::tree::FilledTree;
-use starknet_patricia::patricia_merkle_tree::node_data::leaf::LeafModifications;
+use starknet_patricia::patricia_merkle_tree::filled_tree::tree::{FilledTree, FilledTreeImpl};
+use starknet_patricia::patricia_merkle_tree::node_data::leaf::{Leaf, LeafModifications};
 use starknet_patricia::patricia_merkle_tree::types::NodeIndex;
 use starknet_patricia::patricia_merkle_tree::updated_skeleton_tree::tree::UpdatedSkeletonTreeImpl;
-use starknet_patricia_storage::storage_trait::Storage;
+use starknet_patricia_storage::db_object::DBObject;
+use starknet_patricia_storage::storage_trait::{DbKey, DbValue, Storage};
 use tracing::info;
 
 use crate::block_committer::input::{
@@ -46,6 +47,17 @@ impl FilledForest {
         storage.mset(new_db_objects);
     }
 
+    /// Returns the nodes that the commitment added to the forest, once for each position in the
+    /// forest they were added at. Unlike [Self::write_to_storage], identical nodes aren't merged.
+    pub fn new_nodes(&self) -> Vec<(DbKey, DbValue)> {
+        self.storage_tries
+            .values()
+            .flat_map(tree_new_nodes)
+            .chain(tree_new_nodes(&self.contracts_trie))
+            .chain(tree_new_nodes(&self.classes_trie))
+            .collect()
+    }
+
     pub fn get_contract_root_hash(&self) -> HashOutput {
         self.contracts_trie.get_root_hash()
     }
@@ -159,3 +171,9 @@ impl FilledForest {
         Ok(leaf_index_to_leaf_input)
     }
 }
+
+fn tree_new_nodes<L: Leaf>(
+    tree: &FilledTreeImpl<L>,
+) -> impl Iterator<Item = (DbKey, DbValue)> + '_ {
+    tree.tree_map.values().map(|node| (node.db_key(), node.serialize()))
+}
@@ -2,3 +2,4 @@ pub mod block_committer;
 pub mod forest;
 pub mod hash_function;
 pub mod patricia_merkle_tree;
+pub mod storage_proof;
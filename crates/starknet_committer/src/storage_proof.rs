@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia::patricia_merkle_tree::traversal::{
+    fetch_patricia_paths,
+    PreimageMap,
+    TraversalResult,
+};
+use starknet_patricia::patricia_merkle_tree::types::{NodeIndex, SortedLeafIndices};
+use starknet_patricia_storage::storage_trait::ReadStorage;
+
+use crate::block_committer::input::{
+    contract_address_into_node_index,
+    StarknetStorageKey,
+    StarknetStorageValue,
+};
+use crate::patricia_merkle_tree::leaf::leaf_impl::ContractState;
+use crate::patricia_merkle_tree::types::{class_hash_into_node_index, CompiledClassHash};
+
+#[cfg(test)]
+#[path = "storage_proof_test.rs"]
+pub mod storage_proof_test;
+
+/// Merkle proofs of classes, contracts and storage keys in the committed state tries.
+#[derive(Debug, Default, PartialEq)]
+pub struct StorageProof {
+    /// The nodes on the paths to the requested classes in the classes trie.
+    pub classes_proof: PreimageMap,
+    /// The nodes on the paths to the requested contracts in the contracts trie, including the
+    /// contracts of the requested storage keys.
+    pub contracts_proof: PreimageMap,
+    /// The states of the requested contracts, in the requested order. A contract that doesn't
+    /// exist has an empty state.
+    pub contract_leaves_data: Vec<ContractState>,
+    /// The nodes on the paths to the requested storage keys in the storage trie of each contract,
+    /// in the requested order.
+    pub contracts_storage_proofs: Vec<PreimageMap>,
+}
+
+/// Fetches from the storage the proofs of the given classes, contracts and storage keys in the
+/// tries with the given roots.
+pub fn fetch_storage_proof(
+    storage: &impl ReadStorage,
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+    class_hashes: &[ClassHash],
+    contract_addresses: &[ContractAddress],
+    contracts_storage_keys: &[(ContractAddress, Vec<StarknetStorageKey>)],
+) -> TraversalResult<StorageProof> {
+    let mut classes_trie_indices =
+        sorted_unique(class_hashes.iter().map(class_hash_into_node_index));
+    let classes_proof = fetch_patricia_paths::<CompiledClassHash>(
+        storage,
+        classes_trie_root_hash,
+        SortedLeafIndices::new(&mut classes_trie_indices),
+    )?
+    .nodes;
+
+    let mut contracts_trie_indices = sorted_unique(
+        contract_addresses
+            .iter()
+            .chain(contracts_storage_keys.iter().map(|(address, _)| address))
+            .map(contract_address_into_node_index),
+    );
+    let contracts_trie_paths = fetch_patricia_paths::<ContractState>(
+        storage,
+        contracts_trie_root_hash,
+        SortedLeafIndices::new(&mut contracts_trie_indices),
+    )?;
+    let get_contract_state = |address: &ContractAddress| {
+        contracts_trie_paths
+            .get_leaf(&contract_address_into_node_index(address))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let contract_leaves_data = contract_addresses.iter().map(get_contract_state).collect();
+
+    let mut contracts_storage_proofs = Vec::with_capacity(contracts_storage_keys.len());
+    for (address, keys) in contracts_storage_keys {
+        let mut storage_trie_indices = sorted_unique(keys.iter().map(NodeIndex::from));
+        contracts_storage_proofs.push(
+            fetch_patricia_paths::<StarknetStorageValue>(
+                storage,
+                get_contract_state(address).storage_root_hash,
+                SortedLeafIndices::new(&mut storage_trie_indices),
+            )?
+            .nodes,
+        );
+    }
+
+    Ok(StorageProof {
+        classes_proof,
+        contracts_proof: contracts_trie_paths.nodes,
+        contract_leaves_data,
+        contracts_storage_proofs,
+    })
+}
+
+// The traversal assumes that the leaf indices are unique.
+fn sorted_unique(indices: impl Iterator<Item = NodeIndex>) -> Vec<NodeIndex> {
+    indices.collect::<BTreeSet<_>>().into_iter().collect()
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+use starknet_api::core::Nonce;
+use starknet_api::{class_hash, contract_address, felt};
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia::patricia_merkle_tree::node_data::leaf::Leaf;
+use starknet_patricia::patricia_merkle_tree::traversal::PreimageMap;
+use starknet_patricia::patricia_merkle_tree::updated_skeleton_tree::hash_function::TreeHashFunction;
+use starknet_patricia_storage::map_storage::MapStorage;
+use starknet_patricia_storage::storage_trait::DbKey;
+use tracing::level_filters::LevelFilter;
+
+use super::fetch_storage_proof;
+use crate::block_committer::commit::{
+    commit_block,
+    fetch_commitment_witnesses,
+    fetch_replaced_nodes,
+};
+use crate::block_committer::input::{
+    ConfigImpl,
+    Input,
+    StarknetStorageKey,
+    StarknetStorageValue,
+    StateDiff,
+};
+use crate::hash_function::hash::TreeHashFunctionImpl;
+use crate::patricia_merkle_tree::leaf::leaf_impl::ContractState;
+use crate::patricia_merkle_tree::types::CompiledClassHash;
+
+async fn commit(
+    storage: &mut MapStorage,
+    roots: (HashOutput, HashOutput),
+    state_diff: StateDiff,
+) -> (HashOutput, HashOutput) {
+    let filled_forest = commit_block(Input {
+        storage: storage.storage.clone(),
+        state_diff,
+        contracts_trie_root_hash: roots.0,
+        classes_trie_root_hash: roots.1,
+        config: ConfigImpl::new(false, LevelFilter::DEBUG),
+    })
+    .await
+    .unwrap();
+    filled_forest.write_to_storage(storage);
+    (filled_forest.get_contract_root_hash(), filled_forest.get_compiled_class_root_hash())
+}
+
+fn first_state_diff() -> StateDiff {
+    StateDiff {
+        address_to_class_hash: HashMap::from([
+            (contract_address!("0x10"), class_hash!("0x1")),
+            (contract_address!("0x11"), class_hash!("0x1")),
+            (contract_address!("0x12"), class_hash!("0x2")),
+        ]),
+        address_to_nonce: HashMap::from([(contract_address!("0x10"), Nonce(felt!("0x1")))]),
+        class_hash_to_compiled_class_hash: HashMap::from([
+            (class_hash!("0x1"), CompiledClassHash(felt!("0x101"))),
+            (class_hash!("0x2"), CompiledClassHash(felt!("0x102"))),
+        ]),
+        storage_updates: HashMap::from([
+            (
+                contract_address!("0x10"),
+                HashMap::from([
+                    (StarknetStorageKey(felt!("0x5")), StarknetStorageValue(felt!("0x55"))),
+                    (StarknetStorageKey(felt!("0x6")), StarknetStorageValue(felt!("0x66"))),
+                ]),
+            ),
+            (
+                contract_address!("0x12"),
+                HashMap::from([(
+                    StarknetStorageKey(felt!("0x5")),
+                    StarknetStorageValue(felt!("0x7")),
+                )]),
+            ),
+        ]),
+    }
+}
+
+fn second_state_diff() -> StateDiff {
+    StateDiff {
+        address_to_class_hash: HashMap::from([(contract_address!("0x13"), class_hash!("0x3"))]),
+        address_to_nonce: HashMap::from([(contract_address!("0x10"), Nonce(felt!("0x2")))]),
+        class_hash_to_compiled_class_hash: HashMap::from([(
+            class_hash!("0x3"),
+            CompiledClassHash(felt!("0x103")),
+        )]),
+        storage_updates: HashMap::from([(
+            contract_address!("0x10"),
+            HashMap::from([
+                (StarknetStorageKey(felt!("0x6")), StarknetStorageValue(felt!("0x0"))),
+                (StarknetStorageKey(felt!("0x7")), StarknetStorageValue(felt!("0x77"))),
+            ]),
+        )]),
+    }
+}
+
+// Asserts that each node in the proof is the pre-image of its hash, and that the proof contains the
+// root.
+fn assert_valid_proof<L: Leaf>(proof: &PreimageMap, root_hash: HashOutput)
+where
+    TreeHashFunctionImpl: TreeHashFunction<L>,
+{
+    for (hash, preimage) in proof {
+        assert_eq!(TreeHashFunctionImpl::compute_node_hash(&preimage.clone().into()), *hash);
+    }
+    assert!(proof.contains_key(&root_hash));
+}
+
+#[tokio::test]
+async fn proves_classes_contracts_and_storage() {
+    let mut storage = MapStorage::default();
+    let roots = commit(
+        &mut storage,
+        (HashOutput::ROOT_OF_EMPTY_TREE, HashOutput::ROOT_OF_EMPTY_TREE),
+        first_state_diff(),
+    )
+    .await;
+
+    let contract_addresses = [contract_address!("0x10"), contract_address!("0x99")];
+    let contracts_storage_keys = [
+        (contract_address!("0x12"), vec![StarknetStorageKey(felt!("0x5"))]),
+        (contract_address!("0x99"), vec![StarknetStorageKey(felt!("0x5"))]),
+    ];
+    let proof = fetch_storage_proof(
+        &storage,
+        roots.0,
+        roots.1,
+        &[class_hash!("0x1"), class_hash!("0x3")],
+        &contract_addresses,
+        &contracts_storage_keys,
+    )
+    .unwrap();
+
+    assert_valid_proof::<CompiledClassHash>(&proof.classes_proof, roots.1);
+    assert_valid_proof::<ContractState>(&proof.contracts_proof, roots.0);
+    assert_eq!(proof.contract_leaves_data[1], ContractState::default());
+    let contract_state = &proof.contract_leaves_data[0];
+    assert_eq!(contract_state.class_hash, class_hash!("0x1"));
+    assert_eq!(contract_state.nonce, Nonce(felt!("0x1")));
+    assert_eq!(proof.contracts_storage_proofs.len(), 2);
+    assert!(!proof.contracts_storage_proofs[0].is_empty());
+    // The storage trie of a contract that doesn't exist is empty.
+    assert!(proof.contracts_storage_proofs[1].is_empty());
+}
+
+#[tokio::test]
+async fn empty_request_has_empty_proof() {
+    let mut storage = MapStorage::default();
+    let roots = commit(
+        &mut storage,
+        (HashOutput::ROOT_OF_EMPTY_TREE, HashOutput::ROOT_OF_EMPTY_TREE),
+        first_state_diff(),
+    )
+    .await;
+    let proof = fetch_storage_proof(&storage, roots.0, roots.1, &[], &[], &[]).unwrap();
+    assert_eq!(proof, Default::default());
+}
+
+#[tokio::test]
+async fn commitment_witnesses_suffice_for_commitment() {
+    let mut storage = MapStorage::default();
+    let roots = commit(
+        &mut storage,
+        (HashOutput::ROOT_OF_EMPTY_TREE, HashOutput::ROOT_OF_EMPTY_TREE),
+        first_state_diff(),
+    )
+    .await;
+
+    let mut witnesses = MapStorage::from(
+        fetch_commitment_witnesses(&storage, roots.0, roots.1, &second_state_diff()).unwrap(),
+    );
+    assert!(witnesses.storage.len() < storage.storage.len());
+    let expected_roots = commit(&mut storage, roots, second_state_diff()).await;
+    assert_eq!(commit(&mut witnesses, roots, second_state_diff()).await, expected_roots);
+}
+
+// The state after both state diffs.
+fn combined_state_diff() -> StateDiff {
+    let mut state_diff = first_state_diff();
+    let second_state_diff = second_state_diff();
+    state_diff.address_to_class_hash.extend(second_state_diff.address_to_class_hash);
+    state_diff.address_to_nonce.extend(second_state_diff.address_to_nonce);
+    state_diff
+        .class_hash_to_compiled_class_hash
+        .extend(second_state_diff.class_hash_to_compiled_class_hash);
+    let storage_updates = state_diff.storage_updates.get_mut(&contract_address!("0x10")).unwrap();
+    storage_updates.remove(&StarknetStorageKey(felt!("0x6")));
+    storage_updates.insert(StarknetStorageKey(felt!("0x7")), StarknetStorageValue(felt!("0x77")));
+    state_diff
+}
+
+fn count_nodes(counts: &mut HashMap<DbKey, i64>, keys: impl IntoIterator<Item = DbKey>, diff: i64) {
+    for key in keys {
+        *counts.entry(key).or_default() += diff;
+    }
+    counts.retain(|_key, count| *count != 0);
+}
+
+#[tokio::test]
+async fn replaced_and_new_nodes_count_the_forest_nodes() {
+    let empty_roots = (HashOutput::ROOT_OF_EMPTY_TREE, HashOutput::ROOT_OF_EMPTY_TREE);
+    let commit_with_input = |storage: &MapStorage, roots: (HashOutput, HashOutput), state_diff| {
+        commit_block(Input {
+            storage: storage.storage.clone(),
+            state_diff,
+            contracts_trie_root_hash: roots.0,
+            classes_trie_root_hash: roots.1,
+            config: ConfigImpl::new(false, LevelFilter::DEBUG),
+        })
+    };
+
+    // The number of occurrences of each node in the forest, according to the nodes that each
+    // commitment replaces and adds.
+    let mut counts = HashMap::new();
+    let mut storage = MapStorage::default();
+    let filled_forest =
+        commit_with_input(&storage, empty_roots, first_state_diff()).await.unwrap();
+    filled_forest.write_to_storage(&mut storage);
+    count_nodes(&mut counts, filled_forest.new_nodes().into_iter().map(|(key, _)| key), 1);
+    let roots =
+        (filled_forest.get_contract_root_hash(), filled_forest.get_compiled_class_root_hash());
+
+    let replaced_nodes =
+        fetch_replaced_nodes(&storage, roots.0, roots.1, &second_state_diff()).unwrap();
+    let filled_forest = commit_with_input(&storage, roots, second_state_diff()).await.unwrap();
+    count_nodes(&mut counts, replaced_nodes, -1);
+    count_nodes(&mut counts, filled_forest.new_nodes().into_iter().map(|(key, _)| key), 1);
+
+    // A forest that is committed at once consists of new nodes only.
+    let expected_filled_forest =
+        commit_with_input(&MapStorage::default(), empty_roots, combined_state_diff())
+            .await
+            .unwrap();
+    assert_eq!(
+        expected_filled_forest.get_contract_root_hash(),
+        filled_forest.get_contract_root_hash()
+    );
+    let mut expected_counts = HashMap::new();
+    count_nodes(
+        &mut expected_counts,
+        expected_filled_forest.new_nodes().into_iter().map(|(key, _)| key),
+        1,
+    );
+    assert_eq!(counts, expected_counts);
+}
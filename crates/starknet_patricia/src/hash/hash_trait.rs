@@ -8,7 +8,7 @@ pub struct HashOutput(pub Felt);
 
 impl HashOutput {
     pub(crate) const ZERO: HashOutput = HashOutput(Felt::ZERO);
-    pub(crate) const ROOT_OF_EMPTY_TREE: HashOutput = Self::ZERO;
+    pub const ROOT_OF_EMPTY_TREE: HashOutput = Self::ZERO;
 }
 
 impl_from_hex_for_felt_wrapper!(HashOutput);
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use starknet_patricia_storage::db_object::DBObject;
+use starknet_patricia_storage::errors::{DeserializationError, StorageError};
+use starknet_patricia_storage::storage_trait::{create_db_key, DbKey, DbValue, ReadStorage};
+use thiserror::Error;
+
+use crate::hash::hash_trait::HashOutput;
+use crate::patricia_merkle_tree::filled_tree::node::FilledNode;
+use crate::patricia_merkle_tree::filled_tree::node_serde::PatriciaPrefix;
+use crate::patricia_merkle_tree::node_data::inner_node::{BinaryData, EdgeData, NodeData};
+use crate::patricia_merkle_tree::node_data::leaf::Leaf;
+use crate::patricia_merkle_tree::original_skeleton_tree::utils::{get_node_height, split_leaves};
+use crate::patricia_merkle_tree::types::{NodeIndex, SortedLeafIndices};
+
+#[cfg(test)]
+#[path = "traversal_test.rs"]
+pub mod traversal_test;
+
+#[derive(Debug, Error)]
+pub enum TraversalError {
+    #[error("Failed to deserialize the storage value: {0:?} while traversing the tree.")]
+    Deserialization(#[from] DeserializationError),
+    #[error("Unable to read from storage the storage key: {0:?} while traversing the tree.")]
+    StorageRead(#[from] StorageError),
+}
+
+pub type TraversalResult<T> = Result<T, TraversalError>;
+
+/// The pre-image of an inner node's hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Preimage {
+    Binary(BinaryData),
+    Edge(EdgeData),
+}
+
+impl<L: Leaf> From<Preimage> for NodeData<L> {
+    fn from(preimage: Preimage) -> Self {
+        match preimage {
+            Preimage::Binary(binary_data) => NodeData::Binary(binary_data),
+            Preimage::Edge(edge_data) => NodeData::Edge(edge_data),
+        }
+    }
+}
+
+pub type PreimageMap = HashMap<HashOutput, Preimage>;
+
+/// The roots of the untraversed subtrees that hang off the traversed paths, which are fetched along
+/// with the paths.
+#[derive(Clone, Copy)]
+enum SiblingRoots {
+    None,
+    /// Inner nodes, whose type determines how they're merged with their modified siblings.
+    Inner,
+    /// Edge nodes, which are recomputed when their sibling is modified, or merged into a new edge.
+    Edge,
+}
+
+/// The nodes on the paths from the root of a tree to a set of leaves.
+#[derive(Debug, PartialEq)]
+pub struct PatriciaPaths<L: Leaf> {
+    /// The inner nodes on the paths, by their hash. Together they prove the membership or the
+    /// non-membership of each of the leaves.
+    pub nodes: PreimageMap,
+    /// The non-empty leaves among the requested leaves, by their index.
+    pub leaves: HashMap<NodeIndex, FilledNode<L>>,
+}
+
+impl<L: Leaf> Default for PatriciaPaths<L> {
+    fn default() -> Self {
+        Self { nodes: HashMap::new(), leaves: HashMap::new() }
+    }
+}
+
+impl<L: Leaf> PatriciaPaths<L> {
+    /// Returns the data of the leaf at the given index, if it's one of the non-empty leaves.
+    pub fn get_leaf(&self, index: &NodeIndex) -> Option<&L> {
+        match &self.leaves.get(index)?.data {
+            NodeData::Leaf(leaf) => Some(leaf),
+            NodeData::Binary(_) | NodeData::Edge(_) => None,
+        }
+    }
+
+    /// Returns the storage entries of the nodes on the paths. These are the witnesses required to
+    /// modify the requested leaves.
+    pub fn into_storage_entries(self) -> HashMap<DbKey, DbValue> {
+        self.nodes
+            .into_iter()
+            .map(|(hash, preimage)| FilledNode::<L> { hash, data: preimage.into() })
+            .chain(self.leaves.into_values())
+            .map(|node| (node.db_key(), node.serialize()))
+            .collect()
+    }
+}
+
+/// Fetches from the storage the nodes on the paths from the root of the tree to the given leaves.
+/// Subtrees that don't contain any of the leaves are not traversed.
+pub fn fetch_patricia_paths<L: Leaf>(
+    storage: &impl ReadStorage,
+    root_hash: HashOutput,
+    sorted_leaf_indices: SortedLeafIndices<'_>,
+) -> TraversalResult<PatriciaPaths<L>> {
+    traverse(storage, root_hash, sorted_leaf_indices, SiblingRoots::None)
+}
+
+/// Fetches from the storage the nodes required to modify the given leaves: the nodes on the paths
+/// to the leaves, and the roots of the untraversed subtrees that hang off these paths.
+pub fn fetch_patricia_witnesses<L: Leaf>(
+    storage: &impl ReadStorage,
+    root_hash: HashOutput,
+    sorted_leaf_indices: SortedLeafIndices<'_>,
+) -> TraversalResult<PatriciaPaths<L>> {
+    traverse(storage, root_hash, sorted_leaf_indices, SiblingRoots::Inner)
+}
+
+/// Fetches from the storage the nodes that are replaced when the given leaves are modified: the
+/// nodes on the paths to the leaves, and the edge nodes that hang off these paths. Together with
+/// the nodes of the filled tree, they describe which nodes are added to the tree and which are
+/// removed from it.
+pub fn fetch_replaced_patricia_nodes<L: Leaf>(
+    storage: &impl ReadStorage,
+    root_hash: HashOutput,
+    sorted_leaf_indices: SortedLeafIndices<'_>,
+) -> TraversalResult<PatriciaPaths<L>> {
+    traverse(storage, root_hash, sorted_leaf_indices, SiblingRoots::Edge)
+}
+
+fn traverse<L: Leaf>(
+    storage: &impl ReadStorage,
+    root_hash: HashOutput,
+    sorted_leaf_indices: SortedLeafIndices<'_>,
+    sibling_roots: SiblingRoots,
+) -> TraversalResult<PatriciaPaths<L>> {
+    let mut paths = PatriciaPaths::default();
+    if root_hash == HashOutput::ROOT_OF_EMPTY_TREE || sorted_leaf_indices.is_empty() {
+        return Ok(paths);
+    }
+
+    let mut subtrees = vec![(NodeIndex::ROOT, root_hash, sorted_leaf_indices)];
+    while let Some((root_index, root_hash, leaf_indices)) = subtrees.pop() {
+        if leaf_indices.is_empty() {
+            if root_index.is_leaf() {
+                continue;
+            }
+            let preimage = match sibling_roots {
+                SiblingRoots::None => None,
+                SiblingRoots::Inner => into_preimage(get_node::<L>(storage, root_hash, false)?.data),
+                SiblingRoots::Edge => match get_node::<L>(storage, root_hash, false)?.data {
+                    NodeData::Edge(edge_data) => Some(Preimage::Edge(edge_data)),
+                    NodeData::Binary(_) | NodeData::Leaf(_) => None,
+                },
+            };
+            if let Some(preimage) = preimage {
+                paths.nodes.insert(root_hash, preimage);
+            }
+            continue;
+        }
+        let node = get_node::<L>(storage, root_hash, root_index.is_leaf())?;
+        match node.data {
+            NodeData::Binary(binary_data) => {
+                let [left_leaves, right_leaves] = split_leaves(&root_index, &leaf_indices);
+                let [left_index, right_index] = root_index.get_children_indices();
+                subtrees.push((left_index, binary_data.left_hash, left_leaves));
+                subtrees.push((right_index, binary_data.right_hash, right_leaves));
+                paths.nodes.insert(root_hash, Preimage::Binary(binary_data));
+            }
+            NodeData::Edge(edge_data) => {
+                let bottom_index = edge_data.path_to_bottom.bottom_index(root_index);
+                let bottom_leaves = get_descendant_leaves(&bottom_index, &leaf_indices);
+                subtrees.push((bottom_index, edge_data.bottom_hash, bottom_leaves));
+                paths.nodes.insert(root_hash, Preimage::Edge(edge_data));
+            }
+            NodeData::Leaf(_) => {
+                paths.leaves.insert(root_index, node);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+fn into_preimage<L: Leaf>(node_data: NodeData<L>) -> Option<Preimage> {
+    match node_data {
+        NodeData::Binary(binary_data) => Some(Preimage::Binary(binary_data)),
+        NodeData::Edge(edge_data) => Some(Preimage::Edge(edge_data)),
+        NodeData::Leaf(_) => None,
+    }
+}
+
+fn get_node<L: Leaf>(
+    storage: &impl ReadStorage,
+    hash: HashOutput,
+    is_leaf: bool,
+) -> TraversalResult<FilledNode<L>> {
+    let prefix = if is_leaf {
+        PatriciaPrefix::Leaf(L::get_static_prefix())
+    } else {
+        PatriciaPrefix::InnerNode
+    };
+    let key = create_db_key(prefix.into(), &hash.0.to_bytes_be());
+    let value = storage.read(&key)?.ok_or(StorageError::MissingKey(key))?;
+    Ok(FilledNode::deserialize(hash, &value, is_leaf)?)
+}
+
+/// Returns the leaves that are descendants of the given node.
+fn get_descendant_leaves<'a>(
+    root_index: &NodeIndex,
+    leaf_indices: &SortedLeafIndices<'a>,
+) -> SortedLeafIndices<'a> {
+    let height = u8::from(get_node_height(root_index));
+    let leftmost_leaf = *root_index << height;
+    let rightmost_leaf = leftmost_leaf - NodeIndex::ROOT + (NodeIndex::ROOT << height);
+    leaf_indices.subslice(
+        leaf_indices.bisect_left(&leftmost_leaf),
+        leaf_indices.bisect_right(&rightmost_leaf),
+    )
+}
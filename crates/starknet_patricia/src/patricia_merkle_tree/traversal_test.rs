@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use ethnum::U256;
+use pretty_assertions::assert_eq;
+use starknet_patricia_storage::map_storage::MapStorage;
+use starknet_patricia_storage::storage_trait::Storage;
+use starknet_types_core::felt::Felt;
+
+use super::{
+    fetch_patricia_paths,
+    fetch_patricia_witnesses,
+    fetch_replaced_patricia_nodes,
+    PatriciaPaths,
+    Preimage,
+};
+use crate::hash::hash_trait::HashOutput;
+use crate::patricia_merkle_tree::external_test_utils::{
+    create_binary_entry,
+    create_edge_entry,
+    create_root_edge_entry,
+};
+use crate::patricia_merkle_tree::filled_tree::node::FilledNode;
+use crate::patricia_merkle_tree::internal_test_utils::{small_tree_index_to_full, MockLeaf};
+use crate::patricia_merkle_tree::node_data::inner_node::{
+    BinaryData,
+    EdgeData,
+    EdgePathLength,
+    NodeData,
+    PathToBottom,
+};
+use crate::patricia_merkle_tree::original_skeleton_tree::create_tree::create_tree_test::create_mock_leaf_entry;
+use crate::patricia_merkle_tree::types::{NodeIndex, SortedLeafIndices, SubTreeHeight};
+
+// This test assumes for simplicity that hash is addition (i.e hash(a,b) = a + b).
+///                 Tree structure:
+///
+///                             50
+///                           /   \
+///                         30     20
+///                        /  \     \
+///                       17  13     *
+///                      /  \   \     \
+///                     8    9  11     15
+fn create_storage() -> MapStorage {
+    HashMap::from([
+        create_root_edge_entry(50, SubTreeHeight::new(3)),
+        create_binary_entry(30, 20),
+        create_binary_entry(17, 13),
+        create_edge_entry(15, 3, 2),
+        create_binary_entry(8, 9),
+        create_edge_entry(11, 1, 1),
+        create_mock_leaf_entry(8),
+        create_mock_leaf_entry(9),
+        create_mock_leaf_entry(11),
+        create_mock_leaf_entry(15),
+    ])
+    .into()
+}
+
+fn hash(value: u128) -> HashOutput {
+    HashOutput(Felt::from(value))
+}
+
+fn binary(left: u128, right: u128) -> (HashOutput, Preimage) {
+    (
+        hash(left + right),
+        Preimage::Binary(BinaryData { left_hash: hash(left), right_hash: hash(right) }),
+    )
+}
+
+fn edge(bottom: u128, path: u128, length: u8) -> (HashOutput, Preimage) {
+    (
+        hash(bottom + path + u128::from(length)),
+        Preimage::Edge(EdgeData {
+            bottom_hash: hash(bottom),
+            path_to_bottom: PathToBottom::new(path.into(), EdgePathLength::new(length).unwrap())
+                .unwrap(),
+        }),
+    )
+}
+
+fn leaf_index(small_tree_index: u128) -> NodeIndex {
+    small_tree_index_to_full(U256::from(small_tree_index), SubTreeHeight::new(3))
+}
+
+fn leaf(value: u128) -> (NodeIndex, FilledNode<MockLeaf>) {
+    (
+        leaf_index(value),
+        FilledNode { hash: hash(value), data: NodeData::Leaf(MockLeaf(Felt::from(value))) },
+    )
+}
+
+#[test]
+fn fetches_the_paths_to_the_leaves() {
+    let storage = create_storage();
+    // Leaf 10 is empty, so the path to it ends at the edge node above leaf 11.
+    let mut leaf_indices = [8, 10, 15].map(leaf_index);
+    let paths = fetch_patricia_paths::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+
+    let expected_paths = PatriciaPaths {
+        nodes: HashMap::from([
+            edge(50, 0, 248),
+            binary(30, 20),
+            binary(17, 13),
+            binary(8, 9),
+            edge(11, 1, 1),
+            edge(15, 3, 2),
+        ]),
+        leaves: HashMap::from([leaf(8), leaf(15)]),
+    };
+    assert_eq!(paths, expected_paths);
+
+    // The nodes can be written back to a storage and traversed again.
+    let storage_entries = paths.into_storage_entries();
+    for (key, value) in &storage_entries {
+        assert_eq!(storage.get(key), Some(value));
+    }
+    let paths = fetch_patricia_paths::<MockLeaf>(
+        &MapStorage::from(storage_entries),
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+    assert_eq!(paths, expected_paths);
+}
+
+#[test]
+fn witnesses_include_the_roots_of_sibling_subtrees() {
+    let storage = create_storage();
+    let mut leaf_indices = [8].map(leaf_index);
+    let paths = fetch_patricia_paths::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+    let witnesses = fetch_patricia_witnesses::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+
+    let path_nodes = [edge(50, 0, 248), binary(30, 20), binary(17, 13), binary(8, 9)];
+    assert_eq!(paths.nodes, HashMap::from(path_nodes.clone()));
+    // Leaf siblings aren't fetched, as their hash is all that's needed.
+    let expected_witnesses = PatriciaPaths {
+        nodes: path_nodes.into_iter().chain([edge(11, 1, 1), edge(15, 3, 2)]).collect(),
+        leaves: HashMap::from([leaf(8)]),
+    };
+    assert_eq!(witnesses, expected_witnesses);
+}
+
+#[test]
+fn replaced_nodes_include_the_edge_siblings() {
+    let storage = create_storage();
+    let mut leaf_indices = [15].map(leaf_index);
+    let witnesses = fetch_patricia_witnesses::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+    let replaced_nodes = fetch_replaced_patricia_nodes::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+
+    let path_nodes = [edge(50, 0, 248), binary(30, 20), edge(15, 3, 2)];
+    // The binary sibling is a witness, but it isn't replaced.
+    assert_eq!(
+        witnesses.nodes,
+        path_nodes.clone().into_iter().chain([binary(17, 13)]).collect::<HashMap<_, _>>()
+    );
+    let expected_replaced_nodes =
+        PatriciaPaths { nodes: HashMap::from(path_nodes), leaves: HashMap::from([leaf(15)]) };
+    assert_eq!(replaced_nodes, expected_replaced_nodes);
+
+    // The edge siblings of the paths are replaced.
+    let mut leaf_indices = [8].map(leaf_index);
+    let replaced_nodes = fetch_replaced_patricia_nodes::<MockLeaf>(
+        &storage,
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+    assert_eq!(
+        replaced_nodes.nodes,
+        HashMap::from([
+            edge(50, 0, 248),
+            binary(30, 20),
+            binary(17, 13),
+            binary(8, 9),
+            edge(11, 1, 1),
+            edge(15, 3, 2),
+        ])
+    );
+}
+
+#[test]
+fn empty_tree_has_no_paths() {
+    let mut leaf_indices = [8, 10].map(leaf_index);
+    let paths = fetch_patricia_paths::<MockLeaf>(
+        &MapStorage::default(),
+        HashOutput::ROOT_OF_EMPTY_TREE,
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .unwrap();
+    assert_eq!(paths, PatriciaPaths::default());
+}
+
+#[test]
+fn missing_node_fails() {
+    let mut leaf_indices = [8].map(leaf_index);
+    assert!(fetch_patricia_paths::<MockLeaf>(
+        &MapStorage::default(),
+        hash(50 + 248),
+        SortedLeafIndices::new(&mut leaf_indices),
+    )
+    .is_err());
+}
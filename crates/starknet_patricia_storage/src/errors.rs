@@ -9,6 +9,8 @@ use crate::storage_trait::DbKey;
 pub enum StorageError {
     #[error("The key {0:?} does not exist in storage.")]
     MissingKey(DbKey),
+    #[error("Failed to read the key {key:?} from storage: {reason}")]
+    ReadFailure { key: DbKey, reason: String },
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::storage_trait::{DbKey, DbValue, Storage};
+use crate::errors::StorageError;
+use crate::storage_trait::{DbKey, DbValue, ReadStorage, Storage};
 
 #[derive(Serialize, Debug, Default)]
 #[cfg_attr(any(test, feature = "testing"), derive(Clone))]
@@ -32,6 +33,12 @@ impl Storage for MapStorage {
     }
 }
 
+impl ReadStorage for MapStorage {
+    fn read(&self, key: &DbKey) -> Result<Option<DbValue>, StorageError> {
+        Ok(self.storage.get(key).map(|value| DbValue(value.0.clone())))
+    }
+}
+
 impl From<HashMap<DbKey, DbValue>> for MapStorage {
     fn from(storage: HashMap<DbKey, DbValue>) -> Self {
         Self { storage }
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::{Serialize, Serializer};
 use starknet_types_core::felt::Felt;
 
+use crate::errors::StorageError;
+
 #[derive(Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(Clone))]
 pub struct DbKey(pub Vec<u8>);
@@ -30,6 +32,12 @@ pub trait Storage: From<HashMap<DbKey, DbValue>> {
     fn delete(&mut self, key: &DbKey) -> Option<DbValue>;
 }
 
+/// A storage that the values of the tree nodes can be read from, without holding them in memory.
+pub trait ReadStorage {
+    /// Returns value from storage, if it exists.
+    fn read(&self, key: &DbKey) -> Result<Option<DbValue>, StorageError>;
+}
+
 #[derive(Debug)]
 pub struct DbKeyPrefix(&'static [u8]);
 